@@ -641,6 +641,143 @@ impl BenchConfig {
   }
 }
 
+/// A permission list in a `permissions` config: `true` for everything of that
+/// kind, or the allowed paths, hosts, variables, commands or system info.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+enum SerializedPermissionList {
+  All(bool),
+  List(Vec<String>),
+}
+
+impl SerializedPermissionList {
+  fn into_allowlist(self) -> Option<Vec<String>> {
+    match self {
+      Self::All(true) => Some(vec![]),
+      Self::All(false) => None,
+      Self::List(list) => Some(list),
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedPermissionAllowConfig {
+  pub env: Option<SerializedPermissionList>,
+  pub ffi: Option<SerializedPermissionList>,
+  pub hrtime: bool,
+  pub net: Option<SerializedPermissionList>,
+  pub read: Option<SerializedPermissionList>,
+  pub run: Option<SerializedPermissionList>,
+  pub sys: Option<SerializedPermissionList>,
+  pub write: Option<SerializedPermissionList>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedPermissionDenyConfig {
+  pub env: Vec<String>,
+  pub ffi: Vec<String>,
+  pub net: Vec<String>,
+  pub read: Vec<String>,
+  pub run: Vec<String>,
+  pub sys: Vec<String>,
+  pub write: Vec<String>,
+}
+
+/// `permissions` config representation for serde, which is also the format
+/// of the file passed to `--permission-set`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct SerializedPermissionSetConfig {
+  pub allow: SerializedPermissionAllowConfig,
+  pub deny: SerializedPermissionDenyConfig,
+}
+
+impl SerializedPermissionSetConfig {
+  /// Resolves relative paths against `base`, the directory of the file the
+  /// config was read from.
+  pub fn into_resolved(self, base: &Path) -> PermissionSetConfig {
+    let resolve_paths = |paths: Vec<String>| {
+      paths.into_iter().map(|p| base.join(p)).collect::<Vec<_>>()
+    };
+    let allow = self.allow;
+    let deny = self.deny;
+    PermissionSetConfig {
+      allow_env: allow.env.and_then(|l| l.into_allowlist()),
+      allow_ffi: allow
+        .ffi
+        .and_then(|l| l.into_allowlist())
+        .map(resolve_paths),
+      allow_hrtime: allow.hrtime,
+      allow_net: allow.net.and_then(|l| l.into_allowlist()),
+      allow_read: allow
+        .read
+        .and_then(|l| l.into_allowlist())
+        .map(resolve_paths),
+      allow_run: allow.run.and_then(|l| l.into_allowlist()),
+      allow_sys: allow.sys.and_then(|l| l.into_allowlist()),
+      allow_write: allow
+        .write
+        .and_then(|l| l.into_allowlist())
+        .map(resolve_paths),
+      deny_env: deny.env,
+      deny_ffi: resolve_paths(deny.ffi),
+      deny_net: deny.net,
+      deny_read: resolve_paths(deny.read),
+      deny_run: deny.run,
+      deny_sys: deny.sys,
+      deny_write: resolve_paths(deny.write),
+    }
+  }
+}
+
+/// Permissions declared in the `permissions` config or in a file passed to
+/// `--permission-set`. An `allow_*` list of `Some(vec![])` grants everything
+/// of that kind, like a flag without a value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PermissionSetConfig {
+  pub allow_env: Option<Vec<String>>,
+  pub allow_ffi: Option<Vec<PathBuf>>,
+  pub allow_hrtime: bool,
+  pub allow_net: Option<Vec<String>>,
+  pub allow_read: Option<Vec<PathBuf>>,
+  pub allow_run: Option<Vec<String>>,
+  pub allow_sys: Option<Vec<String>>,
+  pub allow_write: Option<Vec<PathBuf>>,
+  pub deny_env: Vec<String>,
+  pub deny_ffi: Vec<PathBuf>,
+  pub deny_net: Vec<String>,
+  pub deny_read: Vec<PathBuf>,
+  pub deny_run: Vec<String>,
+  pub deny_sys: Vec<String>,
+  pub deny_write: Vec<PathBuf>,
+}
+
+impl PermissionSetConfig {
+  /// Reads a permission set file, which may contain comments like a config
+  /// file.
+  pub fn read(path: &Path) -> Result<Self, AnyError> {
+    let text = std::fs::read_to_string(path).with_context(|| {
+      format!("Failed to read permission set file \"{}\"", path.display())
+    })?;
+    let value = jsonc_parser::parse_to_serde_value(&text, &Default::default())
+      .map_err(|err| {
+        anyhow!(
+          "Unable to parse permission set file \"{}\" because of {}",
+          path.display(),
+          err
+        )
+      })?
+      .unwrap_or_else(|| json!({}));
+    let config: SerializedPermissionSetConfig = serde_json::from_value(value)
+      .with_context(|| {
+      format!("Failed to parse permission set file \"{}\"", path.display())
+    })?;
+    Ok(config.into_resolved(path.parent().unwrap_or(Path::new("."))))
+  }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(untagged)]
 pub enum LockConfig {
@@ -662,7 +799,9 @@ pub struct ConfigFileJson {
   pub bench: Option<Value>,
   pub lock: Option<Value>,
   pub exclude: Option<Value>,
+  pub permissions: Option<Value>,
   pub node_modules_dir: Option<bool>,
+  pub byonm: Option<bool>,
 }
 
 #[derive(Clone, Debug)]
@@ -848,6 +987,12 @@ impl ConfigFile {
     self.json.node_modules_dir
   }
 
+  /// If npm packages should be resolved from a node_modules folder that is
+  /// managed by another tool instead of being installed by Deno.
+  pub fn byonm(&self) -> bool {
+    self.json.byonm == Some(true)
+  }
+
   pub fn to_import_map_value(&self) -> Value {
     let mut value = serde_json::Map::with_capacity(2);
     if let Some(imports) = &self.json.imports {
@@ -1068,6 +1213,20 @@ impl ConfigFile {
     }
   }
 
+  pub fn to_permission_set_config(
+    &self,
+  ) -> Result<Option<PermissionSetConfig>, AnyError> {
+    if let Some(config) = self.json.permissions.clone() {
+      let permission_set: SerializedPermissionSetConfig =
+        serde_json::from_value(config)
+          .context("Failed to parse \"permissions\" configuration")?;
+      let base = specifier_to_file_path(&specifier_parent(&self.specifier))?;
+      Ok(Some(permission_set.into_resolved(&base)))
+    } else {
+      Ok(None)
+    }
+  }
+
   pub fn resolve_lockfile_path(&self) -> Result<Option<PathBuf>, AnyError> {
     match self.to_lock_config()? {
       Some(LockConfig::Bool(lock)) if !lock => Ok(None),
@@ -1513,6 +1672,46 @@ mod tests {
     assert!(options_value.is_object());
   }
 
+  #[test]
+  fn test_parse_config_with_permissions() {
+    let config_text = r#"{
+      "permissions": {
+        "allow": {
+          "read": ["./data"],
+          "env": true,
+          "net": ["*.internal.corp:443"],
+          "hrtime": true
+        },
+        "deny": {
+          "read": ["./data/secrets"],
+          "env": ["AWS_SECRET_ACCESS_KEY"]
+        }
+      }
+    }"#;
+    let config_dir = ModuleSpecifier::parse("file:///deno/").unwrap();
+    let config_specifier = config_dir.join("tsconfig.json").unwrap();
+    let config_file = ConfigFile::new(config_text, config_specifier).unwrap();
+    let config_dir_path = specifier_to_file_path(&config_dir).unwrap();
+
+    assert_eq!(
+      config_file.to_permission_set_config().unwrap(),
+      Some(PermissionSetConfig {
+        allow_env: Some(vec![]),
+        allow_hrtime: true,
+        allow_net: Some(vec!["*.internal.corp:443".to_string()]),
+        allow_read: Some(vec![config_dir_path.join("./data")]),
+        deny_env: vec!["AWS_SECRET_ACCESS_KEY".to_string()],
+        deny_read: vec![config_dir_path.join("./data/secrets")],
+        ..Default::default()
+      })
+    );
+
+    let config_text = r#"{ "permissions": { "allow": { "fs": true } } }"#;
+    let config_specifier = config_dir.join("tsconfig.json").unwrap();
+    let config_file = ConfigFile::new(config_text, config_specifier).unwrap();
+    assert!(config_file.to_permission_set_config().is_err());
+  }
+
   #[test]
   fn test_parse_config_with_commented_file() {
     let config_text = r#"//{"foo":"bar"}"#;
@@ -32,12 +32,22 @@ pub struct FileFlags {
   pub include: Vec<PathBuf>,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AuditFlags {
+  pub files: Vec<String>,
+  pub json: bool,
+  pub audit_level: String,
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct BenchFlags {
   pub files: FileFlags,
   pub filter: Option<String>,
   pub json: bool,
   pub no_run: bool,
+  pub save_baseline: Option<String>,
+  pub compare: Option<String>,
+  pub regression_threshold: Option<u32>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -137,6 +147,13 @@ pub struct InstallFlags {
   pub force: bool,
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JupyterFlags {
+  pub install: bool,
+  pub kernel: bool,
+  pub conn_file: Option<PathBuf>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct UninstallFlags {
   pub name: String,
@@ -189,6 +206,32 @@ pub struct TestFlags {
   pub shuffle: Option<u64>,
   pub concurrent_jobs: Option<NonZeroUsize>,
   pub trace_ops: bool,
+  pub shard: Option<TestShard>,
+  pub shard_timings: Option<PathBuf>,
+  pub reporter: TestReporterConfig,
+  pub junit_path: Option<PathBuf>,
+  pub update_snapshots: bool,
+  pub retries: usize,
+  pub fail_on_flaky: bool,
+  pub tags: Vec<String>,
+  pub exclude_tags: Vec<String>,
+}
+
+/// The format the results of `deno test` are reported in.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum TestReporterConfig {
+  #[default]
+  Pretty,
+  Junit,
+  Tap,
+}
+
+/// The shard of the test files to run, where `index` is 1-based and at most
+/// `count`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TestShard {
+  pub index: usize,
+  pub count: usize,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -209,6 +252,7 @@ pub struct VendorFlags {
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DenoSubcommand {
+  Audit(AuditFlags),
   Bench(BenchFlags),
   Bundle(BundleFlags),
   Cache(CacheFlags),
@@ -222,6 +266,7 @@ pub enum DenoSubcommand {
   Init(InitFlags),
   Info(InfoFlags),
   Install(InstallFlags),
+  Jupyter(JupyterFlags),
   Uninstall(UninstallFlags),
   Lsp,
   Lint(LintFlags),
@@ -330,8 +375,11 @@ pub struct Flags {
   pub config_flag: ConfigFlag,
   pub node_modules_dir: Option<bool>,
   pub coverage_dir: Option<String>,
+  pub dns_servers: Option<Vec<String>>,
+  pub dns_over_https: Option<String>,
   pub enable_testing_features: bool,
   pub ext: Option<String>,
+  pub fs_root: Option<PathBuf>,
   pub ignore: Vec<PathBuf>,
   pub import_map_path: Option<String>,
   pub inspect_brk: Option<SocketAddr>,
@@ -345,10 +393,13 @@ pub struct Flags {
   pub no_lock: bool,
   pub no_npm: bool,
   pub no_prompt: bool,
+  pub permission_audit_log: Option<PathBuf>,
+  pub permission_set: Option<PathBuf>,
   pub reload: bool,
   pub seed: Option<u64>,
   pub unstable: bool,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  pub unsafely_log_tls_keys: bool,
   pub v8_flags: Vec<String>,
   pub version: bool,
   pub watch: Option<Vec<PathBuf>>,
@@ -547,7 +598,7 @@ impl Flags {
           .ok()
       }
       Task(_) | Check(_) | Coverage(_) | Cache(_) | Info(_) | Eval(_)
-      | Test(_) | Bench(_) | Repl(_) | Compile(_) => {
+      | Test(_) | Bench(_) | Repl(_) | Compile(_) | Audit(_) | Jupyter(_) => {
         std::env::current_dir().ok()
       }
       Bundle(_) | Completions(_) | Doc(_) | Fmt(_) | Init(_) | Install(_)
@@ -590,6 +641,12 @@ static ENV_VARIABLES_HELP: &str = r#"ENVIRONMENT VARIABLES:
     DENO_TLS_CA_STORE    Comma-separated list of order dependent certificate
                          stores. Possible values: "system", "mozilla".
                          Defaults to "mozilla".
+    DENO_BROADCAST_CHANNEL_DIR
+                         Directory through which BroadcastChannel messages
+                         are shared with other processes (Unix only)
+    DENO_CACHE_STORAGE_MAX_SIZE
+                         Maximum total size in bytes of the responses
+                         stored with the Cache API
     DENO_CERT            Load certificate authority from PEM encoded file
     DENO_DIR             Set the cache directory
     DENO_INSTALL_ROOT    Set deno install's output directory
@@ -661,6 +718,7 @@ pub fn flags_from_vec(args: Vec<String>) -> clap::error::Result<Flags> {
 
   if let Some((subcommand, mut m)) = matches.remove_subcommand() {
     match subcommand.as_str() {
+      "audit" => audit_parse(&mut flags, &mut m),
       "bench" => bench_parse(&mut flags, &mut m),
       "bundle" => bundle_parse(&mut flags, &mut m),
       "cache" => cache_parse(&mut flags, &mut m),
@@ -674,6 +732,7 @@ pub fn flags_from_vec(args: Vec<String>) -> clap::error::Result<Flags> {
       "init" => init_parse(&mut flags, &mut m),
       "info" => info_parse(&mut flags, &mut m),
       "install" => install_parse(&mut flags, &mut m),
+      "jupyter" => jupyter_parse(&mut flags, &mut m),
       "lint" => lint_parse(&mut flags, &mut m),
       "lsp" => lsp_parse(&mut flags, &mut m),
       "repl" => repl_parse(&mut flags, &mut m),
@@ -762,6 +821,7 @@ fn clap_root() -> Command {
     .subcommand(run_subcommand())
     .defer(|cmd| {
       cmd
+        .subcommand(audit_subcommand())
         .subcommand(bench_subcommand())
         .subcommand(bundle_subcommand())
         .subcommand(cache_subcommand())
@@ -775,6 +835,7 @@ fn clap_root() -> Command {
         .subcommand(init_subcommand())
         .subcommand(info_subcommand())
         .subcommand(install_subcommand())
+        .subcommand(jupyter_subcommand())
         .subcommand(uninstall_subcommand())
         .subcommand(lsp_subcommand())
         .subcommand(lint_subcommand())
@@ -789,6 +850,56 @@ fn clap_root() -> Command {
     .after_help(ENV_VARIABLES_HELP)
 }
 
+fn audit_subcommand() -> Command {
+  Command::new("audit").defer(|cmd| {
+    cmd
+      .about("Audit the dependencies of modules")
+      .long_about(
+        "Audit the dependencies of the provided modules.
+
+Verifies that the cached remote modules and npm packages match the hashes in
+the lock file, and checks the npm packages against the advisory database of
+the npm registry for known vulnerabilities. An npm package is verified through
+the tarball it was extracted from, which is kept in the cache.
+
+  deno audit main.ts
+
+Exits with a non-zero code if a hash doesn't match or a vulnerable package is
+found, which makes it suitable for CI:
+
+  deno audit --json --audit-level=high main.ts",
+      )
+      .arg(
+        Arg::new("file")
+          .num_args(1..)
+          .required(true)
+          .value_hint(ValueHint::FilePath),
+      )
+      .arg(
+        Arg::new("json")
+          .long("json")
+          .help("Output the audit report in JSON format")
+          .action(ArgAction::SetTrue),
+      )
+      .arg(
+        Arg::new("audit-level")
+          .long("audit-level")
+          .help("The minimum severity of advisories to report")
+          .value_parser(["low", "moderate", "high", "critical"])
+          .default_value("low"),
+      )
+      .arg(no_config_arg())
+      .arg(config_arg())
+      .arg(import_map_arg())
+      .arg(lock_arg())
+      .arg(no_npm_arg())
+      .arg(no_remote_arg())
+      .arg(node_modules_dir_arg())
+      .arg(reload_arg())
+      .arg(ca_file_arg())
+  })
+}
+
 fn bench_subcommand() -> Command {
   Command::new("bench").defer(|cmd| {
     runtime_args(cmd, true, false)
@@ -827,6 +938,33 @@ fn bench_subcommand() -> Command {
           .help("Cache bench modules, but don't run benchmarks")
           .action(ArgAction::SetTrue),
       )
+      .arg(
+        Arg::new("save-baseline")
+          .long("save-baseline")
+          .value_name("NAME")
+          .help("Save the results as the baseline NAME in DENO_DIR, which later runs compare with using --compare")
+          .require_equals(true)
+          .value_parser(bench_baseline_name_validate)
+          .conflicts_with("watch"),
+      )
+      .arg(
+        Arg::new("compare")
+          .long("compare")
+          .value_name("NAME")
+          .help("Compare the results with the baseline NAME, failing if a benchmark regressed beyond the threshold")
+          .require_equals(true)
+          .value_parser(bench_baseline_name_validate)
+          .conflicts_with("watch"),
+      )
+      .arg(
+        Arg::new("regression-threshold")
+          .long("regression-threshold")
+          .value_name("PERCENT")
+          .help("The percent by which the average time of a benchmark may differ from the baseline before it's a significant change [default: 10]")
+          .require_equals(true)
+          .requires("compare")
+          .value_parser(value_parser!(u32)),
+      )
       .arg(watch_arg(false))
       .arg(no_clear_screen_arg())
       .arg(script_arg().last(true))
@@ -1450,6 +1588,59 @@ The installation root is determined, in order of precedence:
 These must be added to the path manually if required."))
 }
 
+fn jupyter_subcommand() -> Command {
+  Command::new("jupyter").defer(|cmd| {
+    cmd
+      .about("Deno kernel for Jupyter notebooks")
+      .long_about(
+        "UNSTABLE: Deno kernel for Jupyter notebooks.
+
+Install the kernel so that it can be selected in Jupyter:
+
+  deno jupyter --unstable --install
+
+Show whether the kernel is installed:
+
+  deno jupyter --unstable
+
+Code in a notebook is evaluated like in the REPL, so top-level await is
+supported. Values with a method keyed by Symbol.for(\"Jupyter.display\")
+are displayed with the MIME bundle that the method returns:
+
+  ({ [Symbol.for(\"Jupyter.display\")]() {
+    return { \"text/html\": \"<b>Hello</b>\" };
+  } })",
+      )
+      .arg(
+        Arg::new("install")
+          .long("install")
+          .help("Installs the kernel")
+          .conflicts_with("kernel")
+          .action(ArgAction::SetTrue),
+      )
+      .arg(
+        Arg::new("kernel")
+          .long("kernel")
+          .help("Start the kernel")
+          .conflicts_with("install")
+          .requires("conn")
+          .action(ArgAction::SetTrue),
+      )
+      .arg(
+        Arg::new("conn")
+          .long("conn")
+          .help("Path to the JSON file describing the connection parameters, provided by Jupyter")
+          .value_parser(value_parser!(PathBuf))
+          .value_hint(ValueHint::FilePath)
+          .conflicts_with("install"),
+      )
+      .arg(no_config_arg())
+      .arg(config_arg())
+      .arg(import_map_arg())
+      .arg(node_modules_dir_arg())
+  })
+}
+
 fn uninstall_subcommand() -> Command {
   Command::new("uninstall")
     .defer(|cmd| cmd.arg(Arg::new("name").required(true))
@@ -1748,6 +1939,81 @@ fn test_subcommand() -> Command {
         .require_equals(true)
         .value_parser(value_parser!(u64)),
     )
+    .arg(
+      Arg::new("shard")
+        .long("shard")
+        .value_name("INDEX/COUNT")
+        .help("Run only the INDEX-th of COUNT shards of the test files, ex. --shard=3/8")
+        .require_equals(true)
+        .value_parser(test_shard_validate),
+    )
+    .arg(
+      Arg::new("shard-timings")
+        .long("shard-timings")
+        .value_name("FILE")
+        .help("Balance the shards by the durations of the test files in FILE, which is updated with the durations of the test files that ran")
+        .requires("shard")
+        .require_equals(true)
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("reporter")
+        .long("reporter")
+        .help("Select the format of the test results: pretty output for the terminal, JUnit XML or the Test Anything Protocol")
+        .value_parser(["pretty", "junit", "tap"])
+        .default_value("pretty")
+        .require_equals(true),
+    )
+    .arg(
+      Arg::new("junit-path")
+        .long("junit-path")
+        .value_name("PATH")
+        .help("Write a JUnit XML report of the test results to PATH, in addition to the output of the reporter")
+        .require_equals(true)
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("update-snapshots")
+        .long("update-snapshots")
+        .help("Write the values asserted with t.assertSnapshot() to the snapshot files, removing the snapshots that are no longer asserted")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("retries")
+        .long("retries")
+        .value_name("N")
+        .help("Run a failed test again up to N times, reporting it as flaky if it passes on a later attempt")
+        .require_equals(true)
+        .value_parser(value_parser!(usize)),
+    )
+    .arg(
+      Arg::new("fail-on-flaky")
+        .long("fail-on-flaky")
+        .help("Fail the test run if any test only passed after being retried")
+        .action(ArgAction::SetTrue),
+    )
+    .arg(
+      Arg::new("tags")
+        .long("tags")
+        .value_name("TAGS")
+        .help("Run only the tests that have at least one of the comma separated tags")
+        .require_equals(true)
+        .num_args(1..)
+        .action(ArgAction::Append)
+        .use_value_delimiter(true),
+    )
+    .arg(
+      Arg::new("exclude-tags")
+        .long("exclude-tags")
+        .value_name("TAGS")
+        .help("Skip the tests that have any of the comma separated tags")
+        .require_equals(true)
+        .num_args(1..)
+        .action(ArgAction::Append)
+        .use_value_delimiter(true),
+    )
     .arg(
       Arg::new("coverage")
         .long("coverage")
@@ -1785,7 +2051,8 @@ fn test_subcommand() -> Command {
     .arg(
       watch_arg(false)
         .conflicts_with("no-run")
-        .conflicts_with("coverage"),
+        .conflicts_with("coverage")
+        .conflicts_with("shard"),
     )
     .arg(no_clear_screen_arg())
     .arg(script_arg().last(true))
@@ -2135,6 +2402,28 @@ fn permission_args(app: Command) -> Command {
         .action(ArgAction::SetTrue)
         .help("Always throw if required permission wasn't passed"),
     )
+    .arg(
+      Arg::new("permission-audit-log")
+        .long("permission-audit-log")
+        .value_name("FILE")
+        .help("Append a line of JSON for every permission check to FILE")
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+      Arg::new("permission-set")
+        .long("permission-set")
+        .value_name("FILE")
+        .help("Load allowed and denied permissions from a JSON file")
+        .long_help(
+          r#"Load allowed and denied permissions from a JSON file, e.g.
+    {"allow": {"read": ["./data"], "env": true}, "deny": {"read": ["./data/secrets"]}}
+    Takes the place of the "permissions" key of the configuration file.
+    Permission flags take precedence over the allow lists of the file."#,
+        )
+        .value_parser(value_parser!(PathBuf))
+        .value_hint(ValueHint::FilePath),
+    )
 }
 
 fn runtime_args(
@@ -2158,6 +2447,10 @@ fn runtime_args(
     .arg(location_arg())
     .arg(v8_flags_arg())
     .arg(seed_arg())
+    .arg(fs_root_arg())
+    .arg(dns_server_arg())
+    .arg(dns_over_https_arg())
+    .arg(unsafely_log_tls_keys_arg())
     .arg(enable_testing_features_arg())
 }
 
@@ -2315,6 +2608,60 @@ fn seed_arg() -> Arg {
     .value_parser(value_parser!(u64))
 }
 
+fn fs_root_arg() -> Arg {
+  Arg::new("fs-root")
+    .long("fs-root")
+    .value_name("DIR")
+    .help("Confine the file system operations of the program to DIR")
+    .long_help(
+      "Confine the file system operations of the program to DIR, regardless of
+permissions. Paths are resolved including their symlinks, and those outside of
+DIR are rejected. Modules and npm packages loaded by Deno itself are not
+affected.",
+    )
+    .require_equals(true)
+    .value_parser(value_parser!(PathBuf))
+    .value_hint(ValueHint::DirPath)
+}
+
+fn dns_server_arg() -> Arg {
+  Arg::new("dns-server")
+    .long("dns-server")
+    .num_args(1..)
+    .use_value_delimiter(true)
+    .require_equals(true)
+    .value_name("IP_ADDR[:PORT]")
+    .help("Resolve host names with these name servers")
+    .long_help(
+      "Resolve host names for fetch() and the net APIs with these name servers,
+instead of the system's resolver.
+  --dns-server=1.1.1.1,[2606:4700:4700::1111]:53",
+    )
+    .value_parser(|server: &str| {
+      if server.parse::<SocketAddr>().is_ok()
+        || server.parse::<std::net::IpAddr>().is_ok()
+      {
+        Ok(server.to_string())
+      } else {
+        Err(format!("Invalid name server address: {server}"))
+      }
+    })
+}
+
+fn dns_over_https_arg() -> Arg {
+  Arg::new("dns-over-https")
+    .long("dns-over-https")
+    .require_equals(true)
+    .requires("dns-server")
+    .value_name("TLS_NAME")
+    .help("Query the --dns-server name servers with DNS-over-HTTPS")
+    .long_help(
+      "Query the --dns-server name servers with DNS-over-HTTPS, verifying their
+certificates against TLS_NAME. The port defaults to 443.
+  --dns-server=1.1.1.1 --dns-over-https=cloudflare-dns.com",
+    )
+}
+
 fn watch_arg(takes_files: bool) -> Arg {
   let arg = Arg::new("watch")
     .long("watch")
@@ -2487,6 +2834,19 @@ fn node_modules_dir_arg() -> Arg {
     .help("Enables or disables the use of a local node_modules folder for npm packages")
 }
 
+fn unsafely_log_tls_keys_arg() -> Arg {
+  Arg::new("unsafely-log-tls-keys")
+    .long("unsafely-log-tls-keys")
+    .action(ArgAction::SetTrue)
+    .help("DANGER: Writes TLS secrets to the file in SSLKEYLOGFILE")
+    .long_help(
+      "DANGER: Writes the secrets of all TLS connections to the file named by the
+SSLKEYLOGFILE environment variable, so that their traffic can be decrypted
+with tools like Wireshark. Only use this for debugging.
+  SSLKEYLOGFILE=keys.log deno run --unsafely-log-tls-keys main.ts",
+    )
+}
+
 fn unsafely_ignore_certificate_errors_arg() -> Arg {
   Arg::new("unsafely-ignore-certificate-errors")
     .long("unsafely-ignore-certificate-errors")
@@ -2498,6 +2858,23 @@ fn unsafely_ignore_certificate_errors_arg() -> Arg {
     .value_parser(flags_allow_net::validator)
 }
 
+fn audit_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  ca_file_arg_parse(flags, matches);
+  config_args_parse(flags, matches);
+  import_map_arg_parse(flags, matches);
+  lock_arg_parse(flags, matches);
+  no_npm_arg_parse(flags, matches);
+  no_remote_arg_parse(flags, matches);
+  node_modules_dir_arg_parse(flags, matches);
+  reload_arg_parse(flags, matches);
+
+  flags.subcommand = DenoSubcommand::Audit(AuditFlags {
+    files: matches.remove_many::<String>("file").unwrap().collect(),
+    json: matches.get_flag("json"),
+    audit_level: matches.remove_one::<String>("audit-level").unwrap(),
+  });
+}
+
 fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   flags.type_check_mode = TypeCheckMode::Local;
 
@@ -2529,6 +2906,9 @@ fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   };
 
   let no_run = matches.get_flag("no-run");
+  let save_baseline = matches.remove_one::<String>("save-baseline");
+  let compare = matches.remove_one::<String>("compare");
+  let regression_threshold = matches.remove_one::<u32>("regression-threshold");
 
   watch_arg_parse(flags, matches, false);
   flags.subcommand = DenoSubcommand::Bench(BenchFlags {
@@ -2536,6 +2916,9 @@ fn bench_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     filter,
     json,
     no_run,
+    save_baseline,
+    compare,
+    regression_threshold,
   });
 }
 
@@ -2805,6 +3188,18 @@ fn install_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   });
 }
 
+fn jupyter_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  config_args_parse(flags, matches);
+  import_map_arg_parse(flags, matches);
+  node_modules_dir_arg_parse(flags, matches);
+
+  flags.subcommand = DenoSubcommand::Jupyter(JupyterFlags {
+    install: matches.get_flag("install"),
+    kernel: matches.get_flag("kernel"),
+    conn_file: matches.remove_one::<PathBuf>("conn"),
+  });
+}
+
 fn uninstall_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   let root = matches.remove_one::<PathBuf>("root");
 
@@ -2987,6 +3382,27 @@ fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     Vec::new()
   };
 
+  let shard = matches.remove_one::<TestShard>("shard");
+  let shard_timings = matches.remove_one::<PathBuf>("shard-timings");
+  let reporter =
+    match matches.remove_one::<String>("reporter").unwrap().as_str() {
+      "junit" => TestReporterConfig::Junit,
+      "tap" => TestReporterConfig::Tap,
+      _ => TestReporterConfig::Pretty,
+    };
+  let junit_path = matches.remove_one::<PathBuf>("junit-path");
+  let update_snapshots = matches.get_flag("update-snapshots");
+  let retries = matches.remove_one::<usize>("retries").unwrap_or(0);
+  let fail_on_flaky = matches.get_flag("fail-on-flaky");
+  let tags = matches
+    .remove_many::<String>("tags")
+    .map(|tags| tags.collect())
+    .unwrap_or_default();
+  let exclude_tags = matches
+    .remove_many::<String>("exclude-tags")
+    .map(|tags| tags.collect())
+    .unwrap_or_default();
+
   flags.coverage_dir = matches.remove_one::<String>("coverage");
   watch_arg_parse(flags, matches, false);
   flags.subcommand = DenoSubcommand::Test(TestFlags {
@@ -2999,6 +3415,15 @@ fn test_parse(flags: &mut Flags, matches: &mut ArgMatches) {
     allow_none,
     concurrent_jobs,
     trace_ops,
+    shard,
+    shard_timings,
+    reporter,
+    junit_path,
+    update_snapshots,
+    retries,
+    fail_on_flaky,
+    tags,
+    exclude_tags,
   });
 }
 
@@ -3113,6 +3538,9 @@ fn permission_args_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   if matches.get_flag("no-prompt") {
     flags.no_prompt = true;
   }
+  flags.permission_audit_log =
+    matches.remove_one::<PathBuf>("permission-audit-log");
+  flags.permission_set = matches.remove_one::<PathBuf>("permission-set");
 }
 fn unsafely_ignore_certificate_errors_parse(
   flags: &mut Flags,
@@ -3143,6 +3571,9 @@ fn runtime_args_parse(
   location_arg_parse(flags, matches);
   v8_flags_arg_parse(flags, matches);
   seed_arg_parse(flags, matches);
+  fs_root_arg_parse(flags, matches);
+  dns_server_arg_parse(flags, matches);
+  flags.unsafely_log_tls_keys = matches.get_flag("unsafely-log-tls-keys");
   enable_testing_features_arg_parse(flags, matches);
 }
 
@@ -3235,6 +3666,17 @@ fn seed_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   }
 }
 
+fn fs_root_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.fs_root = matches.remove_one::<PathBuf>("fs-root");
+}
+
+fn dns_server_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
+  flags.dns_servers = matches
+    .remove_many::<String>("dns-server")
+    .map(|servers| servers.collect());
+  flags.dns_over_https = matches.remove_one::<String>("dns-over-https");
+}
+
 fn no_check_arg_parse(flags: &mut Flags, matches: &mut ArgMatches) {
   if let Some(cache_type) = matches.get_one::<String>("no-check") {
     match cache_type.as_str() {
@@ -3322,6 +3764,34 @@ fn reload_arg_validate(urlstr: &str) -> Result<String, String> {
   }
 }
 
+fn test_shard_validate(value: &str) -> Result<TestShard, String> {
+  let invalid = || {
+    format!("Invalid shard '{value}'. Expected INDEX/COUNT, ex. 3/8, where INDEX is between 1 and COUNT.")
+  };
+  let (index, count) = value.split_once('/').ok_or_else(invalid)?;
+  let index = index.parse::<usize>().map_err(|_| invalid())?;
+  let count = count.parse::<usize>().map_err(|_| invalid())?;
+  if index == 0 || index > count {
+    return Err(invalid());
+  }
+  Ok(TestShard { index, count })
+}
+
+fn bench_baseline_name_validate(value: &str) -> Result<String, String> {
+  // the name is used as the name of the file of the baseline
+  if value.is_empty()
+    || !value
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    || value.starts_with('.')
+  {
+    return Err(format!(
+      "Invalid baseline name '{value}'. Expected letters, digits, '-', '_' and '.'."
+    ));
+  }
+  Ok(value.to_string())
+}
+
 fn watch_arg_parse(
   flags: &mut Flags,
   matches: &mut ArgMatches,
@@ -3641,6 +4111,108 @@ mod tests {
     );
   }
 
+  #[test]
+  fn fs_root() {
+    let r =
+      flags_from_vec(svec!["deno", "run", "--fs-root=./sandbox", "gist.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "gist.ts".to_string(),
+        }),
+        fs_root: Some(PathBuf::from("./sandbox")),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn dns_server() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--dns-server=1.1.1.1,[::1]:5353",
+      "--dns-over-https=cloudflare-dns.com",
+      "gist.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "gist.ts".to_string(),
+        }),
+        dns_servers: Some(svec!["1.1.1.1", "[::1]:5353"]),
+        dns_over_https: Some("cloudflare-dns.com".to_string()),
+        ..Flags::default()
+      }
+    );
+
+    let r =
+      flags_from_vec(svec!["deno", "run", "--dns-server=example", "gist.ts"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn unsafely_log_tls_keys() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--unsafely-log-tls-keys",
+      "gist.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "gist.ts".to_string(),
+        }),
+        unsafely_log_tls_keys: true,
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn permission_audit_log() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--permission-audit-log=audit.jsonl",
+      "gist.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "gist.ts".to_string(),
+        }),
+        permission_audit_log: Some(PathBuf::from("audit.jsonl")),
+        ..Flags::default()
+      }
+    );
+  }
+
+  #[test]
+  fn permission_set() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "run",
+      "--permission-set=perms.json",
+      "gist.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Run(RunFlags {
+          script: "gist.ts".to_string(),
+        }),
+        permission_set: Some(PathBuf::from("perms.json")),
+        ..Flags::default()
+      }
+    );
+  }
+
   #[test]
   fn double_hyphen() {
     // notice that flags passed after double dash will not
@@ -4236,6 +4808,52 @@ mod tests {
     }
   }
 
+  #[test]
+  fn audit() {
+    let r = flags_from_vec(svec!["deno", "audit", "main.ts"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Audit(AuditFlags {
+          files: svec!["main.ts"],
+          json: false,
+          audit_level: "low".to_string(),
+        }),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "audit",
+      "--json",
+      "--audit-level=high",
+      "--lock=deno.lock",
+      "main.ts",
+      "dev_deps.ts"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Audit(AuditFlags {
+          files: svec!["main.ts", "dev_deps.ts"],
+          json: true,
+          audit_level: "high".to_string(),
+        }),
+        lock: Some(PathBuf::from("deno.lock")),
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "audit",
+      "--audit-level=unknown",
+      "main.ts"
+    ]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn info() {
     let r = flags_from_vec(svec!["deno", "info", "script.ts"]);
@@ -5199,6 +5817,70 @@ mod tests {
     );
   }
 
+  #[test]
+  fn jupyter() {
+    let r = flags_from_vec(svec!["deno", "jupyter", "--unstable"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Jupyter(JupyterFlags {
+          install: false,
+          kernel: false,
+          conn_file: None,
+        }),
+        unstable: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "jupyter", "--unstable", "--install"]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Jupyter(JupyterFlags {
+          install: true,
+          kernel: false,
+          conn_file: None,
+        }),
+        unstable: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec![
+      "deno",
+      "jupyter",
+      "--unstable",
+      "--kernel",
+      "--conn",
+      "path/to/conn/file"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Jupyter(JupyterFlags {
+          install: false,
+          kernel: true,
+          conn_file: Some(PathBuf::from("path/to/conn/file")),
+        }),
+        unstable: true,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "jupyter", "--kernel"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec![
+      "deno",
+      "jupyter",
+      "--install",
+      "--kernel",
+      "--conn",
+      "path/to/conn/file"
+    ]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn uninstall() {
     let r = flags_from_vec(svec!["deno", "uninstall", "file_server"]);
@@ -5730,7 +6412,7 @@ mod tests {
   #[test]
   fn test_with_flags() {
     #[rustfmt::skip]
-    let r = flags_from_vec(svec!["deno", "test", "--unstable", "--no-npm", "--no-remote", "--trace-ops", "--no-run", "--filter", "- foo", "--coverage=cov", "--location", "https:foo", "--allow-net", "--allow-none", "dir1/", "dir2/", "--", "arg1", "arg2"]);
+    let r = flags_from_vec(svec!["deno", "test", "--unstable", "--no-npm", "--no-remote", "--trace-ops", "--update-snapshots", "--no-run", "--filter", "- foo", "--coverage=cov", "--location", "https:foo", "--allow-net", "--allow-none", "dir1/", "dir2/", "--", "arg1", "arg2"]);
     assert_eq!(
       r.unwrap(),
       Flags {
@@ -5747,6 +6429,15 @@ mod tests {
           shuffle: None,
           concurrent_jobs: None,
           trace_ops: true,
+          shard: None,
+          shard_timings: None,
+          reporter: Default::default(),
+          junit_path: None,
+          update_snapshots: true,
+          retries: 0,
+          fail_on_flaky: false,
+          tags: vec![],
+          exclude_tags: vec![],
         }),
         unstable: true,
         no_prompt: true,
@@ -5822,6 +6513,15 @@ mod tests {
           },
           concurrent_jobs: Some(NonZeroUsize::new(4).unwrap()),
           trace_ops: false,
+          shard: None,
+          shard_timings: None,
+          reporter: Default::default(),
+          junit_path: None,
+          update_snapshots: false,
+          retries: 0,
+          fail_on_flaky: false,
+          tags: vec![],
+          exclude_tags: vec![],
         }),
         type_check_mode: TypeCheckMode::Local,
         no_prompt: true,
@@ -5852,6 +6552,15 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          shard: None,
+          shard_timings: None,
+          reporter: Default::default(),
+          junit_path: None,
+          update_snapshots: false,
+          retries: 0,
+          fail_on_flaky: false,
+          tags: vec![],
+          exclude_tags: vec![],
         }),
         type_check_mode: TypeCheckMode::Local,
         no_prompt: true,
@@ -5886,6 +6595,15 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          shard: None,
+          shard_timings: None,
+          reporter: Default::default(),
+          junit_path: None,
+          update_snapshots: false,
+          retries: 0,
+          fail_on_flaky: false,
+          tags: vec![],
+          exclude_tags: vec![],
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5914,6 +6632,15 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          shard: None,
+          shard_timings: None,
+          reporter: Default::default(),
+          junit_path: None,
+          update_snapshots: false,
+          retries: 0,
+          fail_on_flaky: false,
+          tags: vec![],
+          exclude_tags: vec![],
         }),
         no_prompt: true,
         watch: None,
@@ -5923,6 +6650,141 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_shard() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--shard=3/8",
+      "--shard-timings=timings.json"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          filter: None,
+          allow_none: false,
+          shuffle: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_ops: false,
+          shard: Some(TestShard { index: 3, count: 8 }),
+          shard_timings: Some(PathBuf::from("timings.json")),
+          reporter: Default::default(),
+          junit_path: None,
+          update_snapshots: false,
+          retries: 0,
+          fail_on_flaky: false,
+          tags: vec![],
+          exclude_tags: vec![],
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    for invalid in ["0/8", "9/8", "3", "3/", "a/8", "1/0"] {
+      let r =
+        flags_from_vec(svec!["deno", "test", format!("--shard={invalid}")]);
+      assert!(r.is_err(), "{invalid}");
+    }
+    let r =
+      flags_from_vec(svec!["deno", "test", "--shard-timings=timings.json"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec!["deno", "test", "--shard=1/2", "--watch"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_reporter() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--reporter=tap",
+      "--junit-path=reports/junit.xml"
+    ]);
+    assert_eq!(
+      r.unwrap(),
+      Flags {
+        subcommand: DenoSubcommand::Test(TestFlags {
+          no_run: false,
+          doc: false,
+          fail_fast: None,
+          filter: None,
+          allow_none: false,
+          shuffle: None,
+          files: FileFlags {
+            include: vec![],
+            ignore: vec![],
+          },
+          concurrent_jobs: None,
+          trace_ops: false,
+          shard: None,
+          shard_timings: None,
+          reporter: TestReporterConfig::Tap,
+          junit_path: Some(PathBuf::from("reports/junit.xml")),
+          update_snapshots: false,
+          retries: 0,
+          fail_on_flaky: false,
+          tags: vec![],
+          exclude_tags: vec![],
+        }),
+        no_prompt: true,
+        type_check_mode: TypeCheckMode::Local,
+        ..Flags::default()
+      }
+    );
+
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=junit"]);
+    assert!(matches!(
+      r.unwrap().subcommand,
+      DenoSubcommand::Test(TestFlags {
+        reporter: TestReporterConfig::Junit,
+        ..
+      })
+    ));
+    let r = flags_from_vec(svec!["deno", "test", "--reporter=xml"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_retries() {
+    let r =
+      flags_from_vec(svec!["deno", "test", "--retries=2", "--fail-on-flaky"]);
+    assert!(matches!(
+      r.unwrap().subcommand,
+      DenoSubcommand::Test(TestFlags {
+        retries: 2,
+        fail_on_flaky: true,
+        ..
+      })
+    ));
+    let r = flags_from_vec(svec!["deno", "test", "--retries=-1"]);
+    assert!(r.is_err());
+  }
+
+  #[test]
+  fn test_tags() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "test",
+      "--tags=integration,db",
+      "--exclude-tags=slow"
+    ]);
+    let DenoSubcommand::Test(test_flags) = r.unwrap().subcommand else {
+      panic!("expected the test subcommand");
+    };
+    assert_eq!(test_flags.tags, svec!["integration", "db"]);
+    assert_eq!(test_flags.exclude_tags, svec!["slow"]);
+  }
+
   #[test]
   fn test_watch() {
     let r = flags_from_vec(svec!["deno", "test", "--watch"]);
@@ -5942,6 +6804,15 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          shard: None,
+          shard_timings: None,
+          reporter: Default::default(),
+          junit_path: None,
+          update_snapshots: false,
+          retries: 0,
+          fail_on_flaky: false,
+          tags: vec![],
+          exclude_tags: vec![],
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5969,6 +6840,15 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          shard: None,
+          shard_timings: None,
+          reporter: Default::default(),
+          junit_path: None,
+          update_snapshots: false,
+          retries: 0,
+          fail_on_flaky: false,
+          tags: vec![],
+          exclude_tags: vec![],
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -5998,6 +6878,15 @@ mod tests {
           },
           concurrent_jobs: None,
           trace_ops: false,
+          shard: None,
+          shard_timings: None,
+          reporter: Default::default(),
+          junit_path: None,
+          update_snapshots: false,
+          retries: 0,
+          fail_on_flaky: false,
+          tags: vec![],
+          exclude_tags: vec![],
         }),
         watch: Some(vec![]),
         type_check_mode: TypeCheckMode::Local,
@@ -6670,6 +7559,9 @@ mod tests {
             include: vec![PathBuf::from("dir1/"), PathBuf::from("dir2/")],
             ignore: vec![],
           },
+          save_baseline: None,
+          compare: None,
+          regression_threshold: None,
         }),
         unstable: true,
         no_npm: true,
@@ -6698,6 +7590,9 @@ mod tests {
             include: vec![],
             ignore: vec![],
           },
+          save_baseline: None,
+          compare: None,
+          regression_threshold: None,
         }),
         no_prompt: true,
         type_check_mode: TypeCheckMode::Local,
@@ -6707,6 +7602,30 @@ mod tests {
     );
   }
 
+  #[test]
+  fn bench_baseline() {
+    let r = flags_from_vec(svec![
+      "deno",
+      "bench",
+      "--save-baseline=feature",
+      "--compare=main",
+      "--regression-threshold=5"
+    ]);
+    let DenoSubcommand::Bench(bench_flags) = r.unwrap().subcommand else {
+      panic!("expected the bench subcommand");
+    };
+    assert_eq!(bench_flags.save_baseline, Some("feature".to_string()));
+    assert_eq!(bench_flags.compare, Some("main".to_string()));
+    assert_eq!(bench_flags.regression_threshold, Some(5));
+
+    let r = flags_from_vec(svec!["deno", "bench", "--compare=../main"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec!["deno", "bench", "--regression-threshold=5"]);
+    assert!(r.is_err());
+    let r = flags_from_vec(svec!["deno", "bench", "--watch", "--compare=main"]);
+    assert!(r.is_err());
+  }
+
   #[test]
   fn run_with_check() {
     let r = flags_from_vec(svec!["deno", "run", "--check", "script.ts",]);
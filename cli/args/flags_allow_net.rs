@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use deno_core::url::Url;
+use deno_runtime::permissions::NetDescriptor;
 use std::net::IpAddr;
 use std::str::FromStr;
 
@@ -30,6 +31,7 @@ pub fn validator(host_and_port: &str) -> Result<String, String> {
   if Url::parse(&format!("internal://{host_and_port}")).is_ok()
     || host_and_port.parse::<IpAddr>().is_ok()
     || host_and_port.parse::<BarePort>().is_ok()
+    || is_net_pattern(host_and_port)
   {
     Ok(host_and_port.to_string())
   } else {
@@ -37,13 +39,22 @@ pub fn validator(host_and_port: &str) -> Result<String, String> {
   }
 }
 
+/// Whether `s` is a wildcard host (`*.example.com[:port]`) or a URL pattern
+/// (`https://api.example.com/v1/*`).
+fn is_net_pattern(s: &str) -> bool {
+  (s.starts_with("*.") || s.contains("://"))
+    && NetDescriptor::from_str(s).is_ok()
+}
+
 /// Expands "bare port" paths (eg. ":8080") into full paths with hosts. It
 /// expands to such paths into 3 paths with following hosts: `0.0.0.0:port`,
 /// `127.0.0.1:port` and `localhost:port`.
 pub fn parse(paths: Vec<String>) -> clap::error::Result<Vec<String>> {
   let mut out: Vec<String> = vec![];
   for host_and_port in paths.iter() {
-    if Url::parse(&format!("internal://{host_and_port}")).is_ok()
+    if is_net_pattern(host_and_port) {
+      out.push(host_and_port.to_owned())
+    } else if Url::parse(&format!("internal://{host_and_port}")).is_ok()
       || host_and_port.parse::<IpAddr>().is_ok()
     {
       out.push(host_and_port.to_owned())
@@ -172,6 +183,13 @@ mod tests {
     assert_eq!(actual, expected);
   }
 
+  #[test]
+  fn parse_net_args_patterns() {
+    let entries = svec!["*.internal.corp:443", "https://api.example.com/v1/*"];
+    let actual = parse(entries.clone()).unwrap();
+    assert_eq!(actual, entries);
+  }
+
   #[test]
   fn parse_net_args_ipv6() {
     let entries =
@@ -10,6 +10,8 @@ pub mod package_json;
 pub use self::import_map::resolve_import_map_from_specifier;
 pub use self::lockfile::snapshot_from_lockfile;
 use self::package_json::PackageJsonDeps;
+use self::package_json::PackageJsonWorkspace;
+use self::package_json::WorkspaceMember;
 use ::import_map::ImportMap;
 use deno_core::resolve_url_or_path;
 use deno_npm::resolution::ValidSerializedNpmResolutionSnapshot;
@@ -26,6 +28,7 @@ pub use config_file::FilesConfig;
 pub use config_file::FmtOptionsConfig;
 pub use config_file::JsxImportSourceConfig;
 pub use config_file::LintRulesConfig;
+pub use config_file::PermissionSetConfig;
 pub use config_file::ProseWrap;
 pub use config_file::TsConfig;
 pub use config_file::TsConfigForEmit;
@@ -46,6 +49,9 @@ use deno_core::parking_lot::Mutex;
 use deno_core::serde_json;
 use deno_core::url::Url;
 use deno_runtime::colors;
+use deno_runtime::deno_net::resolver::DnsConfig;
+use deno_runtime::deno_net::resolver::DnsProtocol;
+use deno_runtime::deno_net::resolver::NameServer;
 use deno_runtime::deno_node::PackageJson;
 use deno_runtime::deno_tls::rustls;
 use deno_runtime::deno_tls::rustls::RootCertStore;
@@ -126,6 +132,9 @@ pub struct BenchOptions {
   pub filter: Option<String>,
   pub json: bool,
   pub no_run: bool,
+  pub save_baseline: Option<String>,
+  pub compare: Option<String>,
+  pub regression_threshold: u32,
 }
 
 impl BenchOptions {
@@ -142,6 +151,9 @@ impl BenchOptions {
       filter: bench_flags.filter,
       json: bench_flags.json,
       no_run: bench_flags.no_run,
+      save_baseline: bench_flags.save_baseline,
+      compare: bench_flags.compare,
+      regression_threshold: bench_flags.regression_threshold.unwrap_or(10),
     })
   }
 }
@@ -240,6 +252,15 @@ pub struct TestOptions {
   pub shuffle: Option<u64>,
   pub concurrent_jobs: NonZeroUsize,
   pub trace_ops: bool,
+  pub shard: Option<TestShard>,
+  pub shard_timings: Option<PathBuf>,
+  pub reporter: TestReporterConfig,
+  pub junit_path: Option<PathBuf>,
+  pub update_snapshots: bool,
+  pub retries: usize,
+  pub fail_on_flaky: bool,
+  pub tags: Vec<String>,
+  pub exclude_tags: Vec<String>,
 }
 
 impl TestOptions {
@@ -264,6 +285,15 @@ impl TestOptions {
       no_run: test_flags.no_run,
       shuffle: test_flags.shuffle,
       trace_ops: test_flags.trace_ops,
+      shard: test_flags.shard,
+      shard_timings: test_flags.shard_timings,
+      reporter: test_flags.reporter,
+      junit_path: test_flags.junit_path,
+      update_snapshots: test_flags.update_snapshots,
+      retries: test_flags.retries,
+      fail_on_flaky: test_flags.fail_on_flaky,
+      tags: test_flags.tags,
+      exclude_tags: test_flags.exclude_tags,
     })
   }
 }
@@ -566,7 +596,9 @@ pub struct CliOptions {
   maybe_node_modules_folder: Option<PathBuf>,
   maybe_config_file: Option<ConfigFile>,
   maybe_package_json: Option<PackageJson>,
+  maybe_workspace: Option<PackageJsonWorkspace>,
   maybe_lockfile: Option<Arc<Mutex<Lockfile>>>,
+  maybe_permission_set: Option<PermissionSetConfig>,
   overrides: CliOptionOverrides,
 }
 
@@ -591,22 +623,55 @@ impl CliOptions {
       // use eprintln instead of log::warn so this always gets shown
       eprintln!("{}", colors::yellow(msg));
     }
+    if flags.unsafely_log_tls_keys {
+      let msg = match std::env::var_os("SSLKEYLOGFILE") {
+        Some(path) => format!(
+          "DANGER: TLS secrets are written to {}",
+          PathBuf::from(path).display()
+        ),
+        None => {
+          "--unsafely-log-tls-keys has no effect as SSLKEYLOGFILE is not set"
+            .to_string()
+        }
+      };
+      eprintln!("{}", colors::yellow(msg));
+    }
+
+    let maybe_workspace = match &maybe_package_json {
+      Some(package_json) => PackageJsonWorkspace::discover(package_json)
+        .with_context(|| "Resolving npm workspace.")?,
+      None => None,
+    };
 
+    // the packages of a workspace share the node_modules folder of its root
     let maybe_node_modules_folder = resolve_local_node_modules_folder(
       &initial_cwd,
       &flags,
       maybe_config_file.as_ref(),
-      maybe_package_json.as_ref(),
+      maybe_workspace
+        .as_ref()
+        .map(|workspace| &workspace.root)
+        .or(maybe_package_json.as_ref()),
     )
     .with_context(|| "Resolving node_modules folder.")?;
 
+    let maybe_permission_set = match &flags.permission_set {
+      Some(path) => Some(PermissionSetConfig::read(&initial_cwd.join(path))?),
+      None => match &maybe_config_file {
+        Some(config_file) => config_file.to_permission_set_config()?,
+        None => None,
+      },
+    };
+
     Ok(Self {
       flags,
       initial_cwd,
       maybe_config_file,
       maybe_lockfile,
       maybe_package_json,
+      maybe_workspace,
       maybe_node_modules_folder,
+      maybe_permission_set,
       overrides: Default::default(),
     })
   }
@@ -776,6 +841,10 @@ impl CliOptions {
         resolve_url_or_path("./$deno$repl.ts", self.initial_cwd())
           .map_err(AnyError::from)
       }
+      DenoSubcommand::Jupyter(_) => {
+        resolve_url_or_path("./$deno$jupyter.ts", self.initial_cwd())
+          .map_err(AnyError::from)
+      }
       DenoSubcommand::Run(run_flags) => {
         if run_flags.is_stdin() {
           std::env::current_dir()
@@ -874,6 +943,16 @@ impl CliOptions {
     self.maybe_node_modules_folder.clone()
   }
 
+  /// If npm packages are resolved from a node_modules folder that is
+  /// managed by another tool, like pnpm or yarn.
+  pub fn byonm(&self) -> bool {
+    self
+      .maybe_config_file
+      .as_ref()
+      .map(|c| c.byonm())
+      .unwrap_or(false)
+  }
+
   pub fn node_modules_dir_enablement(&self) -> Option<bool> {
     self.flags.node_modules_dir.or_else(|| {
       self
@@ -971,6 +1050,12 @@ impl CliOptions {
     ) {
       // don't have any package json dependencies for deno task with no args
       None
+    } else if self.byonm() {
+      // the dependencies are installed by another tool and bare specifiers
+      // are resolved from the node_modules folder
+      None
+    } else if let Some(workspace) = &self.maybe_workspace {
+      Some(workspace.version_reqs())
     } else {
       self
         .maybe_package_json()
@@ -979,6 +1064,16 @@ impl CliOptions {
     }
   }
 
+  /// The packages of the npm workspace, which are linked into the
+  /// node_modules folder instead of being installed from the registry.
+  pub fn workspace_members(&self) -> Vec<WorkspaceMember> {
+    self
+      .maybe_workspace
+      .as_ref()
+      .map(|workspace| workspace.members.clone())
+      .unwrap_or_default()
+  }
+
   pub fn resolve_fmt_options(
     &self,
     fmt_flags: FmtFlags,
@@ -1104,6 +1199,10 @@ impl CliOptions {
       .unwrap_or(false)
   }
 
+  pub fn fs_root(&self) -> Option<&PathBuf> {
+    self.flags.fs_root.as_ref()
+  }
+
   pub fn location_flag(&self) -> &Option<Url> {
     &self.flags.location
   }
@@ -1128,24 +1227,74 @@ impl CliOptions {
     self.flags.no_npm
   }
 
+  /// The permission flags, falling back to the allow lists of the
+  /// permission set for the kinds without a flag. The deny lists of the
+  /// permission set always apply.
   pub fn permissions_options(&self) -> PermissionsOptions {
+    let set = self.maybe_permission_set.clone().unwrap_or_default();
     PermissionsOptions {
-      allow_env: self.flags.allow_env.clone(),
-      allow_hrtime: self.flags.allow_hrtime,
-      allow_net: self.flags.allow_net.clone(),
-      allow_ffi: self.flags.allow_ffi.clone(),
-      allow_read: self.flags.allow_read.clone(),
-      allow_run: self.flags.allow_run.clone(),
-      allow_sys: self.flags.allow_sys.clone(),
-      allow_write: self.flags.allow_write.clone(),
+      allow_env: self.flags.allow_env.clone().or(set.allow_env),
+      allow_hrtime: self.flags.allow_hrtime || set.allow_hrtime,
+      allow_net: self.flags.allow_net.clone().or(set.allow_net),
+      allow_ffi: self.flags.allow_ffi.clone().or(set.allow_ffi),
+      allow_read: self.flags.allow_read.clone().or(set.allow_read),
+      allow_run: self.flags.allow_run.clone().or(set.allow_run),
+      allow_sys: self.flags.allow_sys.clone().or(set.allow_sys),
+      allow_write: self.flags.allow_write.clone().or(set.allow_write),
+      deny_env: set.deny_env,
+      deny_net: set.deny_net,
+      deny_ffi: set.deny_ffi,
+      deny_read: set.deny_read,
+      deny_run: set.deny_run,
+      deny_sys: set.deny_sys,
+      deny_write: set.deny_write,
       prompt: !self.no_prompt(),
     }
   }
 
+  pub fn permission_audit_log(&self) -> Option<&PathBuf> {
+    self.flags.permission_audit_log.as_ref()
+  }
+
   pub fn reload_flag(&self) -> bool {
     self.flags.reload
   }
 
+  /// The resolver configured with `--dns-server`, or `None` to use the
+  /// system's resolver.
+  pub fn dns_config(&self) -> Option<DnsConfig> {
+    let servers = self.flags.dns_servers.as_ref()?;
+    let protocol = if self.flags.dns_over_https.is_some() {
+      DnsProtocol::Https
+    } else {
+      DnsProtocol::Udp
+    };
+    let default_port = match protocol {
+      DnsProtocol::Udp => 53,
+      DnsProtocol::Https => 443,
+    };
+    let name_servers = servers
+      .iter()
+      .map(|server| match server.parse::<SocketAddr>() {
+        Ok(addr) => NameServer {
+          ip_addr: addr.ip().to_string(),
+          port: addr.port(),
+        },
+        // Validated when parsing the flags.
+        Err(_) => NameServer {
+          ip_addr: server.clone(),
+          port: default_port,
+        },
+      })
+      .collect();
+    Some(DnsConfig {
+      name_servers,
+      protocol,
+      tls_name: self.flags.dns_over_https.clone(),
+      cache_size: None,
+    })
+  }
+
   pub fn seed(&self) -> Option<u64> {
     self.flags.seed
   }
@@ -1182,6 +1331,14 @@ fn resolve_local_node_modules_folder(
   maybe_config_file: Option<&ConfigFile>,
   maybe_package_json: Option<&PackageJson>,
 ) -> Result<Option<PathBuf>, AnyError> {
+  if maybe_config_file.map(|c| c.byonm()).unwrap_or(false) {
+    return resolve_byonm_node_modules_folder(
+      maybe_config_file.unwrap(),
+      maybe_package_json,
+    )
+    .map(Some);
+  }
+
   let use_node_modules_dir = flags
     .node_modules_dir
     .or_else(|| maybe_config_file.and_then(|c| c.node_modules_dir()));
@@ -1205,6 +1362,35 @@ fn resolve_local_node_modules_folder(
   Ok(Some(canonicalize_path_maybe_not_exists(&path)?))
 }
 
+/// Resolves the existing node_modules folder to resolve npm packages from
+/// when it is managed by another tool.
+fn resolve_byonm_node_modules_folder(
+  config_file: &ConfigFile,
+  maybe_package_json: Option<&PackageJson>,
+) -> Result<PathBuf, AnyError> {
+  let root_dir = match maybe_package_json {
+    Some(package_json) => package_json.path.parent().unwrap().to_path_buf(),
+    None => {
+      let config_path = config_file.specifier.to_file_path().map_err(|_| {
+        anyhow!("Invalid config file path: {}", config_file.specifier)
+      })?;
+      config_path.parent().unwrap().to_path_buf()
+    }
+  };
+  let path = root_dir.join("node_modules");
+  if !path.is_dir() {
+    bail!(
+      concat!(
+        "Could not find the node_modules folder at '{}'. ",
+        "Install the npm packages with your package manager first or ",
+        "remove the \"byonm\" option from the configuration file."
+      ),
+      path.display()
+    );
+  }
+  Ok(canonicalize_path_maybe_not_exists(&path)?)
+}
+
 fn resolve_import_map_specifier(
   maybe_import_map_path: Option<&str>,
   maybe_config_file: Option<&ConfigFile>,
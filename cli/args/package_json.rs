@@ -1,20 +1,29 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 
+use deno_core::anyhow::anyhow;
 use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
+use deno_core::serde_json::Value;
+use deno_core::ModuleSpecifier;
 use deno_npm::registry::parse_dep_entry_name_and_raw_version;
 use deno_npm::registry::PackageDepNpmSchemeValueParseError;
+use deno_runtime::deno_node::NodeModuleKind;
 use deno_runtime::deno_node::PackageJson;
+use deno_runtime::deno_node::PathClean;
 use deno_semver::npm::NpmPackageReq;
 use deno_semver::npm::NpmVersionReqSpecifierParseError;
 use deno_semver::VersionReq;
 use thiserror::Error;
 
+use crate::util::glob::glob;
+use crate::util::glob::GlobPattern;
+
 #[derive(Debug, Error, Clone)]
 pub enum PackageJsonDepValueParseError {
   #[error(transparent)]
@@ -29,19 +38,33 @@ pub type PackageJsonDeps =
   BTreeMap<String, Result<NpmPackageReq, PackageJsonDepValueParseError>>;
 
 #[derive(Debug, Default)]
-pub struct PackageJsonDepsProvider(Option<PackageJsonDeps>);
+pub struct PackageJsonDepsProvider {
+  deps: Option<PackageJsonDeps>,
+  workspace_members: Vec<WorkspaceMember>,
+}
 
 impl PackageJsonDepsProvider {
-  pub fn new(deps: Option<PackageJsonDeps>) -> Self {
-    Self(deps)
+  pub fn new(
+    deps: Option<PackageJsonDeps>,
+    workspace_members: Vec<WorkspaceMember>,
+  ) -> Self {
+    Self {
+      deps,
+      workspace_members,
+    }
   }
 
   pub fn deps(&self) -> Option<&PackageJsonDeps> {
-    self.0.as_ref()
+    self.deps.as_ref()
+  }
+
+  /// The packages of the workspace, which are linked instead of installed.
+  pub fn workspace_members(&self) -> &[WorkspaceMember] {
+    &self.workspace_members
   }
 
   pub fn reqs(&self) -> Vec<&NpmPackageReq> {
-    match &self.0 {
+    match &self.deps {
       Some(deps) => {
         let mut package_reqs = deps
           .values()
@@ -114,6 +137,225 @@ pub fn get_local_package_json_version_reqs(
   result
 }
 
+/// The conditions used to resolve the `exports` of workspace members, which
+/// are imported as ES modules.
+const WORKSPACE_EXPORTS_CONDITIONS: &[&str] =
+  &["deno", "node", "import", "default"];
+
+/// A package of an npm workspace. Imports of it are resolved to its directory
+/// instead of a package downloaded from the registry.
+#[derive(Debug, Clone)]
+pub struct WorkspaceMember {
+  pub name: String,
+  pub package_json: PackageJson,
+}
+
+impl WorkspaceMember {
+  pub fn dir(&self) -> &Path {
+    self.package_json.path.parent().unwrap()
+  }
+
+  /// Resolves an import of `sub_path` of the package, which is either empty
+  /// or starts with a slash, using its `exports` or `main` entry.
+  pub fn resolve(&self, sub_path: &str) -> Result<ModuleSpecifier, AnyError> {
+    let export_name = format!(".{sub_path}");
+    let target = match &self.package_json.exports {
+      Some(exports) => resolve_exports_target(exports, &export_name)
+        .ok_or_else(|| {
+          anyhow!(
+            "Package subpath '{}' is not defined by \"exports\" in {}",
+            export_name,
+            self.package_json.path.display()
+          )
+        })?,
+      None if sub_path.is_empty() => self
+        .package_json
+        .main(NodeModuleKind::Esm)
+        .cloned()
+        .unwrap_or_else(|| "index.js".to_string()),
+      None => sub_path[1..].to_string(),
+    };
+    let path = self.dir().join(target).clean();
+    ModuleSpecifier::from_file_path(&path)
+      .map_err(|_| anyhow!("Could not convert '{}' to url.", path.display()))
+  }
+}
+
+fn resolve_exports_target(
+  exports: &deno_core::serde_json::Map<String, Value>,
+  export_name: &str,
+) -> Option<String> {
+  if let Some(target) = exports.get(export_name) {
+    return resolve_conditional_target(target);
+  }
+  // subpath patterns, e.g. "./utils/*": "./src/utils/*.js"
+  for (key, target) in exports {
+    let Some((prefix, suffix)) = key.split_once('*') else {
+      continue;
+    };
+    if export_name.len() >= prefix.len() + suffix.len()
+      && export_name.starts_with(prefix)
+      && export_name.ends_with(suffix)
+    {
+      let matched =
+        &export_name[prefix.len()..export_name.len() - suffix.len()];
+      return resolve_conditional_target(target)
+        .map(|target| target.replace('*', matched));
+    }
+  }
+  None
+}
+
+fn resolve_conditional_target(target: &Value) -> Option<String> {
+  match target {
+    Value::String(target) => Some(target.to_string()),
+    Value::Array(targets) => {
+      targets.iter().find_map(resolve_conditional_target)
+    }
+    Value::Object(conditions) => conditions
+      .iter()
+      .filter(|(condition, _)| {
+        WORKSPACE_EXPORTS_CONDITIONS.contains(&condition.as_str())
+      })
+      .find_map(|(_, target)| resolve_conditional_target(target)),
+    _ => None,
+  }
+}
+
+/// An npm workspace, which is declared by the `workspaces` globs of its root
+/// package.json.
+#[derive(Debug, Clone)]
+pub struct PackageJsonWorkspace {
+  pub root: PackageJson,
+  pub members: Vec<WorkspaceMember>,
+}
+
+impl PackageJsonWorkspace {
+  /// Discovers the workspace of `package_json`, which is either the root of
+  /// the workspace or a member of a workspace declared in an ancestor
+  /// directory.
+  pub fn discover(
+    package_json: &PackageJson,
+  ) -> Result<Option<Self>, AnyError> {
+    if package_json.workspaces.is_some() {
+      return Self::from_root(package_json.clone()).map(Some);
+    }
+
+    let package_dir = package_json.path.parent().unwrap();
+    for ancestor in package_dir.ancestors().skip(1) {
+      let Some(root) = load_package_json(&ancestor.join("package.json"))?
+      else {
+        continue;
+      };
+      if root.workspaces.is_none() {
+        continue;
+      }
+      // like npm, only the closest workspace root is considered
+      let workspace = Self::from_root(root)?;
+      if workspace
+        .members
+        .iter()
+        .any(|member| member.dir() == package_dir)
+      {
+        log::debug!(
+          "package.json is a member of the workspace at '{}'",
+          workspace.root.path.display()
+        );
+        return Ok(Some(workspace));
+      }
+      break;
+    }
+    Ok(None)
+  }
+
+  /// Expands the `workspaces` globs of the root package.json. Globs that
+  /// start with `!` exclude the matched directories.
+  pub fn from_root(root: PackageJson) -> Result<Self, AnyError> {
+    let root_dir = root.path.parent().unwrap();
+    let to_absolute_pattern = |pattern: &str| {
+      root_dir
+        .join(pattern.trim_start_matches("./").trim_end_matches('/'))
+        .to_string_lossy()
+        .to_string()
+    };
+
+    let mut include_patterns = Vec::new();
+    let mut exclude_patterns = Vec::new();
+    for pattern in root.workspaces.iter().flatten() {
+      match pattern.strip_prefix('!') {
+        Some(pattern) => exclude_patterns
+          .push(GlobPattern::new(&to_absolute_pattern(pattern))?),
+        None => include_patterns.push(to_absolute_pattern(pattern)),
+      }
+    }
+
+    let mut member_dirs = BTreeSet::new();
+    for pattern in include_patterns {
+      for dir in glob(&pattern)? {
+        let dir = dir?;
+        if dir.is_dir()
+          && !exclude_patterns.iter().any(|p| p.matches_path(&dir))
+        {
+          member_dirs.insert(dir);
+        }
+      }
+    }
+
+    let mut members = Vec::with_capacity(member_dirs.len());
+    for dir in member_dirs {
+      let Some(package_json) = load_package_json(&dir.join("package.json"))?
+      else {
+        continue;
+      };
+      match package_json.name.clone() {
+        Some(name) => members.push(WorkspaceMember { name, package_json }),
+        None => log::warn!(
+          "Ignoring the workspace package at '{}' that has no name.",
+          dir.display()
+        ),
+      }
+    }
+
+    Ok(Self { root, members })
+  }
+
+  pub fn member(&self, name: &str) -> Option<&WorkspaceMember> {
+    self.members.iter().find(|member| member.name == name)
+  }
+
+  /// Gets the npm package requirements of the root package.json and all the
+  /// members. Dependencies on members are left out since they're linked.
+  pub fn version_reqs(&self) -> PackageJsonDeps {
+    let mut result = BTreeMap::new();
+    // insert the root dependencies last so they take priority
+    for package_json in self
+      .members
+      .iter()
+      .map(|member| &member.package_json)
+      .chain([&self.root])
+    {
+      result.extend(get_local_package_json_version_reqs(package_json));
+    }
+    result.retain(|name, _| self.member(name).is_none());
+    result
+  }
+}
+
+fn load_package_json(path: &Path) -> Result<Option<PackageJson>, AnyError> {
+  match std::fs::read_to_string(path) {
+    Ok(source) => Ok(Some(PackageJson::load_from_string(
+      path.to_path_buf(),
+      source,
+    )?)),
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+    Err(err) => bail!(
+      "Error loading package.json at {}. {:#}",
+      path.display(),
+      err
+    ),
+  }
+}
+
 /// Attempts to discover the package.json file, maybe stopping when it
 /// reaches the specified `maybe_stop_at` directory.
 pub fn discover_from(
@@ -295,4 +537,73 @@ mod test {
       ])
     );
   }
+
+  #[test]
+  fn test_workspace_discover() {
+    use test_util::TempDir;
+    let temp_dir = TempDir::new();
+    temp_dir.write(
+      "package.json",
+      r#"{ "workspaces": ["packages/*", "!packages/ignored"] }"#,
+    );
+    temp_dir.create_dir_all("packages/app");
+    temp_dir.write(
+      "packages/app/package.json",
+      r#"{ "name": "app", "dependencies": { "lib": "workspace:*", "chalk": "^5.0.0" } }"#,
+    );
+    temp_dir.create_dir_all("packages/lib");
+    temp_dir.write(
+      "packages/lib/package.json",
+      r#"{ "name": "lib", "exports": { ".": { "types": "./mod.d.ts", "import": "./mod.js" }, "./utils/*": "./src/utils/*.js" } }"#,
+    );
+    temp_dir.create_dir_all("packages/ignored");
+    temp_dir.write("packages/ignored/package.json", r#"{ "name": "ignored" }"#);
+
+    let app_package_json = load_package_json(
+      &temp_dir
+        .path()
+        .join("packages/app/package.json")
+        .to_path_buf(),
+    )
+    .unwrap()
+    .unwrap();
+    let workspace = PackageJsonWorkspace::discover(&app_package_json)
+      .unwrap()
+      .unwrap();
+    assert_eq!(
+      workspace
+        .members
+        .iter()
+        .map(|member| member.name.as_str())
+        .collect::<Vec<_>>(),
+      vec!["app", "lib"]
+    );
+
+    let deps = workspace
+      .version_reqs()
+      .into_iter()
+      .map(|(k, v)| (k, v.unwrap()))
+      .collect::<BTreeMap<_, _>>();
+    assert_eq!(
+      deps,
+      BTreeMap::from([(
+        "chalk".to_string(),
+        NpmPackageReq::from_str("chalk@^5.0.0").unwrap()
+      )])
+    );
+
+    let lib = workspace.member("lib").unwrap();
+    assert_eq!(
+      lib.resolve("").unwrap(),
+      temp_dir.uri().join("packages/lib/mod.js").unwrap()
+    );
+    assert_eq!(
+      lib.resolve("/utils/format").unwrap(),
+      temp_dir
+        .uri()
+        .join("packages/lib/src/utils/format.js")
+        .unwrap()
+    );
+    assert!(lib.resolve("/other").is_err());
+  }
 }
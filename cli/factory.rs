@@ -26,6 +26,7 @@ use crate::module_loader::ModuleLoadPreparer;
 use crate::module_loader::NpmModuleLoader;
 use crate::node::CliCjsEsmCodeAnalyzer;
 use crate::node::CliNodeCodeTranslator;
+use crate::npm::create_byonm_npm_fs_resolver;
 use crate::npm::create_npm_fs_resolver;
 use crate::npm::CliNpmRegistryApi;
 use crate::npm::CliNpmResolver;
@@ -34,6 +35,7 @@ use crate::npm::NpmCacheDir;
 use crate::npm::NpmPackageFsResolver;
 use crate::npm::NpmResolution;
 use crate::npm::PackageJsonDepsInstaller;
+use crate::resolver::ByonmSpecifierResolver;
 use crate::resolver::CliGraphResolver;
 use crate::standalone::DenoCompileBinaryWriter;
 use crate::tools::check::TypeChecker;
@@ -45,6 +47,7 @@ use crate::worker::CliMainWorkerFactory;
 use crate::worker::CliMainWorkerOptions;
 use crate::worker::HasNodeSpecifierChecker;
 
+use deno_core::anyhow::Context;
 use deno_core::error::AnyError;
 use deno_core::parking_lot::Mutex;
 
@@ -55,6 +58,7 @@ use deno_runtime::deno_node::NodeResolver;
 use deno_runtime::deno_tls::RootCertStoreProvider;
 use deno_runtime::deno_web::BlobStore;
 use deno_runtime::inspector_server::InspectorServer;
+use deno_runtime::permissions::PermissionAuditLog;
 use deno_semver::npm::NpmPackageReqReference;
 use import_map::ImportMap;
 use log::warn;
@@ -261,6 +265,19 @@ impl CliFactory {
     self.services.fs.get_or_init(|| Arc::new(deno_fs::RealFs))
   }
 
+  /// The file system of the workers running user code, confined to the
+  /// `--fs-root` directory if there is one.
+  fn worker_fs(&self) -> Result<Arc<dyn deno_fs::FileSystem>, AnyError> {
+    let fs = self.fs().clone();
+    let Some(root) = self.options.fs_root() else {
+      return Ok(fs);
+    };
+    let fs = deno_fs::RootedFs::new(root, fs)
+      .map_err(AnyError::from)
+      .with_context(|| format!("Invalid --fs-root {}", root.display()))?;
+    Ok(Arc::new(fs))
+  }
+
   pub fn maybe_lockfile(&self) -> &Option<Arc<Mutex<Lockfile>>> {
     self
       .services
@@ -316,15 +333,21 @@ impl CliFactory {
       .get_or_try_init_async(async {
         let npm_resolution = self.npm_resolution().await?;
         let fs = self.fs().clone();
-        let npm_fs_resolver = create_npm_fs_resolver(
-          fs.clone(),
-          self.npm_cache()?.clone(),
-          self.text_only_progress_bar(),
-          CliNpmRegistryApi::default_url().to_owned(),
-          npm_resolution.clone(),
-          self.options.node_modules_dir_path(),
-          self.options.npm_system_info(),
-        );
+        let npm_fs_resolver = match self.options.node_modules_dir_path() {
+          Some(node_modules_path) if self.options.byonm() => {
+            create_byonm_npm_fs_resolver(fs.clone(), node_modules_path)
+          }
+          maybe_node_modules_path => create_npm_fs_resolver(
+            fs.clone(),
+            self.npm_cache()?.clone(),
+            self.text_only_progress_bar(),
+            CliNpmRegistryApi::default_url().to_owned(),
+            npm_resolution.clone(),
+            maybe_node_modules_path,
+            self.options.npm_system_info(),
+            self.options.workspace_members(),
+          ),
+        };
         Ok(Arc::new(CliNpmResolver::new(
           fs.clone(),
           npm_resolution.clone(),
@@ -349,6 +372,7 @@ impl CliFactory {
       // local node_modules variant of an npm fs resolver
       Some(node_modules_dir_path),
       self.options.npm_system_info(),
+      self.options.workspace_members(),
     ))
   }
 
@@ -356,6 +380,7 @@ impl CliFactory {
     self.services.package_json_deps_provider.get_or_init(|| {
       Arc::new(PackageJsonDepsProvider::new(
         self.options.maybe_package_json_deps(),
+        self.options.workspace_members(),
       ))
     })
   }
@@ -367,6 +392,10 @@ impl CliFactory {
       .services
       .package_json_deps_installer
       .get_or_try_init_async(async {
+        if self.options.byonm() {
+          // the packages are installed by another tool
+          return Ok(Arc::new(PackageJsonDepsInstaller::no_op()));
+        }
         Ok(Arc::new(PackageJsonDepsInstaller::new(
           self.package_json_deps_provider().clone(),
           self.npm_api()?.clone(),
@@ -407,11 +436,25 @@ impl CliFactory {
           self.npm_resolution().await?.clone(),
           self.package_json_deps_provider().clone(),
           self.package_json_deps_installer().await?.clone(),
+          self.maybe_byonm_resolver().await?,
         )))
       })
       .await
   }
 
+  async fn maybe_byonm_resolver(
+    &self,
+  ) -> Result<Option<Arc<ByonmSpecifierResolver>>, AnyError> {
+    if !self.options.byonm() {
+      return Ok(None);
+    }
+    Ok(Some(Arc::new(ByonmSpecifierResolver::new(
+      self.fs().clone(),
+      self.node_resolver().await?.clone(),
+      self.npm_resolver().await?.clone(),
+    ))))
+  }
+
   pub fn file_watcher(&self) -> Result<&Arc<FileWatcher>, AnyError> {
     self.services.file_watcher.get_or_try_init(|| {
       let watcher = FileWatcher::new(
@@ -616,6 +659,7 @@ impl CliFactory {
     let options = self.cli_options().clone();
     let main_worker_options = self.create_cli_main_worker_options()?;
     let fs = self.fs().clone();
+    let worker_fs = self.worker_fs()?;
     let root_cert_store_provider = self.root_cert_store_provider().clone();
     let node_resolver = self.node_resolver().await?.clone();
     let npm_resolver = self.npm_resolver().await?.clone();
@@ -643,7 +687,7 @@ impl CliFactory {
           ),
         )),
         root_cert_store_provider.clone(),
-        fs.clone(),
+        worker_fs.clone(),
         maybe_inspector_server.clone(),
         maybe_lockfile.clone(),
         main_worker_options.clone(),
@@ -677,7 +721,7 @@ impl CliFactory {
         ),
       )),
       self.root_cert_store_provider().clone(),
-      self.fs().clone(),
+      self.worker_fs()?,
       self.maybe_inspector_server().clone(),
       self.maybe_lockfile().clone(),
       self.create_cli_main_worker_options()?,
@@ -691,6 +735,7 @@ impl CliFactory {
       argv: self.options.argv().clone(),
       log_level: self.options.log_level().unwrap_or(log::Level::Info).into(),
       coverage_dir: self.options.coverage_dir(),
+      dns_config: self.options.dns_config(),
       enable_testing_features: self.options.enable_testing_features(),
       has_node_modules_dir: self.options.has_node_modules_dir(),
       inspect_brk: self.options.inspect_brk().is_some(),
@@ -711,6 +756,11 @@ impl CliFactory {
         maybe_binary_command_name
       },
       origin_data_folder_path: Some(self.deno_dir()?.origin_data_folder_path()),
+      permission_audit_log: self
+        .options
+        .permission_audit_log()
+        .map(|path| PermissionAuditLog::open(path))
+        .transpose()?,
       seed: self.options.seed(),
       unsafely_ignore_certificate_errors: self
         .options
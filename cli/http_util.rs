@@ -10,9 +10,12 @@ use deno_core::error::custom_error;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::futures::StreamExt;
+use deno_core::serde::de::DeserializeOwned;
+use deno_core::serde_json;
 use deno_core::url::Url;
 use deno_runtime::deno_fetch::create_http_client;
 use deno_runtime::deno_fetch::reqwest;
+use deno_runtime::deno_fetch::reqwest::header::CONTENT_TYPE;
 use deno_runtime::deno_fetch::reqwest::header::LOCATION;
 use deno_runtime::deno_fetch::reqwest::Response;
 use deno_runtime::deno_fetch::CreateHttpClientOptions;
@@ -282,6 +285,35 @@ impl HttpClient {
     Ok(self.client()?.get(url))
   }
 
+  /// Do a POST request with a JSON body and deserialize the JSON response.
+  pub async fn post_json<U: reqwest::IntoUrl, T: DeserializeOwned>(
+    &self,
+    url: U,
+    body: &serde_json::Value,
+  ) -> Result<T, AnyError> {
+    let response = self
+      .client()?
+      .post(url)
+      .header(CONTENT_TYPE, "application/json")
+      .body(serde_json::to_vec(body)?)
+      .send()
+      .await?;
+    if !response.status().is_success() {
+      let status = response.status();
+      let maybe_response_text = response.text().await.ok();
+      bail!(
+        "Bad response: {:?}{}",
+        status,
+        match maybe_response_text {
+          Some(text) => format!("\n\n{text}"),
+          None => String::new(),
+        }
+      );
+    }
+    let bytes = response.bytes().await?;
+    Ok(serde_json::from_slice(&bytes)?)
+  }
+
   pub async fn download_text<U: reqwest::IntoUrl>(
     &self,
     url: U,
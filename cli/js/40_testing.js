@@ -3,11 +3,13 @@
 const core = globalThis.Deno.core;
 const ops = core.ops;
 import { setExitHandler } from "ext:runtime/30_os.js";
-import { Console } from "ext:deno_console/01_console.js";
+import { Console, inspect } from "ext:deno_console/01_console.js";
 import { serializePermissions } from "ext:runtime/10_permissions.js";
-import { assert } from "ext:deno_web/00_infra.js";
+import { assert, AssertionError } from "ext:deno_web/00_infra.js";
 const primordials = globalThis.__bootstrap.primordials;
 const {
+  ArrayIsArray,
+  ArrayPrototypeEvery,
   ArrayPrototypeFilter,
   ArrayPrototypeJoin,
   ArrayPrototypePush,
@@ -64,6 +66,7 @@ function handleOpSanitizerDelayMacrotask() {
 // deno-fmt-ignore
 const OP_DETAILS = {
   "op_blob_read_part": ["read from a Blob or File", "awaiting the result of a Blob or File read"],
+  "op_blob_read_part_range": ["read from a Blob or File", "awaiting the result of a Blob or File read"],
   "op_broadcast_recv": ["receive a message from a BroadcastChannel", "closing the BroadcastChannel"],
   "op_broadcast_send": ["send a message to a BroadcastChannel", "closing the BroadcastChannel"],
   "op_chmod_async": ["change the permissions of a file", "awaiting the result of a `Deno.chmod` call"],
@@ -107,6 +110,7 @@ const OP_DETAILS = {
   "op_net_connect_tcp": ["connect to a TCP server", "awaiting a `Deno.connect` call"],
   "op_net_connect_unix": ["connect to a Unix server", "awaiting a `Deno.connect` call"],
   "op_open_async": ["open a file", "awaiting the result of a `Deno.open` call"],
+  "op_pipe_resources": ["pipe a resource into another resource", "awaiting the result of a `ReadableStream#pipeTo` call"],
   "op_read_dir_async": ["read a directory", "collecting all items in the async iterable returned from a `Deno.readDir` call"],
   "op_read_link_async": ["read a symlink", "awaiting the result of a `Deno.readLink` call"],
   "op_realpath_async": ["resolve a path", "awaiting the result of a `Deno.realpath` call"],
@@ -420,6 +424,10 @@ function assertExit(fn, isTest) {
 
 function wrapOuter(fn, desc) {
   return async function outerWrapped() {
+    // a failed test is run again when it is retried
+    const state = MapPrototypeGet(testStates, desc.id);
+    state.children = [];
+    state.completed = false;
     try {
       if (desc.ignore) {
         return "ignored";
@@ -428,7 +436,6 @@ function wrapOuter(fn, desc) {
     } catch (error) {
       return { failed: { jsError: core.destructureError(error) } };
     } finally {
-      const state = MapPrototypeGet(testStates, desc.id);
       for (const childDesc of state.children) {
         stepReportResult(childDesc, { failed: "incomplete" }, 0);
       }
@@ -665,6 +672,14 @@ function test(
     testDesc = { ...defaults, ...nameOrFnOrOptions, fn, name };
   }
 
+  if (
+    testDesc.tags != undefined &&
+    (!ArrayIsArray(testDesc.tags) ||
+      !ArrayPrototypeEvery(testDesc.tags, (tag) => typeof tag === "string"))
+  ) {
+    throw new TypeError("Expected 'tags' field to be an array of strings.");
+  }
+
   // Delete this prop in case the user passed it. It's used to detect steps.
   delete testDesc.parent;
   const jsError = core.destructureError(new Error());
@@ -964,6 +979,25 @@ function getFullName(desc) {
   return desc.name;
 }
 
+/**
+ * Serializes a value for its snapshot like `Deno.inspect()`, but without
+ * abbreviating it so the snapshot changes with any part of the value.
+ * @param value {unknown}
+ * @returns {string}
+ */
+function serializeSnapshot(value) {
+  return inspect(value, {
+    depth: Infinity,
+    sorted: true,
+    trailingComma: true,
+    compact: false,
+    iterableLimit: Infinity,
+    strAbbreviateSize: Infinity,
+    breakLength: Infinity,
+    colors: false,
+  });
+}
+
 function usesSanitizer(desc) {
   return desc.sanitizeResources || desc.sanitizeOps || desc.sanitizeExit;
 }
@@ -1088,6 +1122,20 @@ function createTestContext(desc) {
       stepReportResult(stepDesc, result, elapsed);
       return result == "ok";
     },
+    /**
+     * @param actual {unknown}
+     * @param options {{ serializer?: (value: unknown) => string }}
+     */
+    assertSnapshot(actual, options = {}) {
+      const serializer = options.serializer ?? serializeSnapshot;
+      const message = ops.op_test_assert_snapshot(
+        getFullName(desc),
+        serializer(actual),
+      );
+      if (message !== null) {
+        throw new AssertionError(message);
+      }
+    },
   };
 }
 
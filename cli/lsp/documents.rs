@@ -1225,8 +1225,10 @@ impl Documents {
       maybe_jsx_config.as_ref(),
       maybe_package_json_deps.as_ref(),
     );
-    let deps_provider =
-      Arc::new(PackageJsonDepsProvider::new(maybe_package_json_deps));
+    let deps_provider = Arc::new(PackageJsonDepsProvider::new(
+      maybe_package_json_deps,
+      Vec::new(),
+    ));
     let deps_installer = Arc::new(PackageJsonDepsInstaller::no_op());
     self.resolver = Arc::new(CliGraphResolver::new(
       maybe_jsx_config,
@@ -1236,6 +1238,7 @@ impl Documents {
       options.npm_resolution,
       deps_provider,
       deps_installer,
+      None,
     ));
     self.imports = Arc::new(
       if let Some(Ok(imports)) =
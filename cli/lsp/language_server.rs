@@ -586,6 +586,7 @@ fn create_npm_resolver_and_resolution(
     resolution.clone(),
     node_modules_dir_path,
     NpmSystemInfo::default(),
+    Vec::new(),
   );
   (
     Arc::new(CliNpmResolver::new(
@@ -867,6 +868,7 @@ impl Inner {
         npm_resolution,
         self.maybe_node_modules_dir_path().cloned(),
         NpmSystemInfo::default(),
+        Vec::new(),
       ),
       self.maybe_lockfile().cloned(),
     ));
@@ -283,6 +283,8 @@ impl TestRun {
         exclude: lsp_filter
           .map(|f| f.exclude.values().map(|t| t.name.clone()).collect())
           .unwrap_or_default(),
+        tags: Vec::new(),
+        exclude_tags: Vec::new(),
       };
       let token = self.token.clone();
 
@@ -304,6 +306,8 @@ impl TestRun {
               filter,
               shuffle: None,
               trace_ops: false,
+              update_snapshots: false,
+              retries: 0,
             },
           ))
         };
@@ -363,7 +367,9 @@ impl TestRun {
             test::TestEvent::Result(id, result, elapsed) => {
               let description = tests.read().get(&id).unwrap().clone();
               match &result {
-                test::TestResult::Ok => summary.passed += 1,
+                test::TestResult::Ok | test::TestResult::Flaky(_) => {
+                  summary.passed += 1
+                }
                 test::TestResult::Ignored => summary.ignored += 1,
                 test::TestResult::Failed(error) => {
                   summary.failed += 1;
@@ -376,6 +382,7 @@ impl TestRun {
 
               reporter.report_result(&description, &result, elapsed);
             }
+            test::TestEvent::Retry(..) => {}
             test::TestEvent::UncaughtError(origin, error) => {
               reporter.report_uncaught_error(&origin, &error);
               summary.failed += 1;
@@ -664,7 +671,7 @@ impl LspTestReporter {
     assert_eq!(stack.pop(), Some(desc.into()));
     self.current_origin = None;
     match result {
-      test::TestResult::Ok => {
+      test::TestResult::Ok | test::TestResult::Flaky(_) => {
         self.progress(lsp_custom::TestRunProgressMessage::Passed {
           test: desc.into(),
           duration: Some(elapsed as u32),
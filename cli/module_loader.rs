@@ -52,6 +52,7 @@ use deno_runtime::permissions::PermissionsContainer;
 use deno_semver::npm::NpmPackageNvReference;
 use deno_semver::npm::NpmPackageReqReference;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::pin::Pin;
 use std::rc::Rc;
@@ -330,7 +331,10 @@ impl CliModuleLoaderFactory {
         lib_window: options.ts_type_lib_window(),
         lib_worker: options.ts_type_lib_worker(),
         is_inspecting: options.is_inspecting(),
-        is_repl: matches!(options.sub_command(), DenoSubcommand::Repl(_)),
+        is_repl: matches!(
+          options.sub_command(),
+          DenoSubcommand::Repl(_) | DenoSubcommand::Jupyter(_)
+        ),
         prepared_module_loader: PreparedModuleLoader {
           emitter,
           graph_container: graph_container.clone(),
@@ -496,7 +500,12 @@ impl ModuleLoader for CliModuleLoader {
             Some(Module::Esm(module)) => Ok(module.specifier.clone()),
             Some(Module::Json(module)) => Ok(module.specifier.clone()),
             Some(Module::External(module)) => {
-              Ok(node::resolve_specifier_into_node_modules(&module.specifier))
+              let specifier =
+                node::resolve_specifier_into_node_modules(&module.specifier);
+              self
+                .shared
+                .npm_module_loader
+                .resolve_in_npm_package(specifier)
             }
             None => Ok(specifier.clone()),
           };
@@ -550,6 +559,7 @@ impl ModuleLoader for CliModuleLoader {
     specifier: &ModuleSpecifier,
     maybe_referrer: Option<&ModuleSpecifier>,
     is_dynamic: bool,
+    _import_assertions: &HashMap<String, String>,
   ) -> Pin<Box<deno_core::ModuleSourceFuture>> {
     // NOTE: this block is async only because of `deno_core` interface
     // requirements; module was already loaded when constructing module graph
@@ -685,6 +695,24 @@ impl NpmModuleLoader {
     }
   }
 
+  /// Checks if a resolved specifier in an npm package is CommonJS or ESM so
+  /// it's loaded accordingly.
+  pub fn resolve_in_npm_package(
+    &self,
+    specifier: ModuleSpecifier,
+  ) -> Result<ModuleSpecifier, AnyError> {
+    if self.node_resolver.in_npm_package(&specifier) {
+      self.handle_node_resolve_result(
+        self
+          .node_resolver
+          .url_to_node_resolution(specifier)
+          .map(Some),
+      )
+    } else {
+      Ok(specifier)
+    }
+  }
+
   pub fn resolve_nv_ref(
     &self,
     nv_ref: &NpmPackageNvReference,
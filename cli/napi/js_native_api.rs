@@ -8,6 +8,7 @@ use v8::BackingStore;
 use v8::UniqueRef;
 
 use super::util::get_array_buffer_ptr;
+use super::util::set_buffer_prototype;
 use deno_runtime::deno_napi::function::create_function;
 use deno_runtime::deno_napi::function::create_function_template;
 use deno_runtime::deno_napi::function::CallbackInfo;
@@ -257,6 +258,7 @@ fn napi_create_buffer(
     *data = get_array_buffer_ptr(value);
   }
   let value = v8::Uint8Array::new(&mut env.scope(), value, 0, len).unwrap();
+  set_buffer_prototype(env, value);
   let value: v8::Local<v8::Value> = value.into();
   *result = value.into();
   napi_ok
@@ -279,6 +281,7 @@ fn napi_create_buffer_copy(
     *result_data = ptr;
   }
   let value = v8::Uint8Array::new(&mut env.scope(), value, 0, len).unwrap();
+  set_buffer_prototype(env, value);
   let value: v8::Local<v8::Value> = value.into();
   *result = value.into();
   napi_ok
@@ -644,6 +647,7 @@ fn napi_create_external_buffer(
     v8::ArrayBuffer::with_backing_store(&mut env.scope(), &store.make_shared());
   let value =
     v8::Uint8Array::new(&mut env.scope(), ab, 0, byte_length).unwrap();
+  set_buffer_prototype(env, value);
   let value: v8::Local<v8::Value> = value.into();
   *result = value.into();
   napi_ok
@@ -1706,7 +1710,9 @@ fn napi_get_arraybuffer_info(
   length: *mut usize,
 ) -> napi_status {
   let value = napi_value_unchecked(value);
-  let buf = v8::Local::<v8::ArrayBuffer>::try_from(value).unwrap();
+  let Ok(buf) = v8::Local::<v8::ArrayBuffer>::try_from(value) else {
+    return napi_invalid_arg;
+  };
   if !data.is_null() {
     *data = get_array_buffer_ptr(buf);
   }
@@ -1739,17 +1745,18 @@ fn napi_get_buffer_info(
   check_env!(env);
   let env = unsafe { &mut *env };
   let value = napi_value_unchecked(value);
-  let buf = v8::Local::<v8::Uint8Array>::try_from(value).unwrap();
-  let buffer_name = v8::String::new(&mut env.scope(), "buffer").unwrap();
-  let abuf = v8::Local::<v8::ArrayBuffer>::try_from(
-    buf.get(&mut env.scope(), buffer_name.into()).unwrap(),
-  )
-  .unwrap();
+  // Like Node.js, accept any view, which includes `Buffer`.
+  let Ok(buf) = v8::Local::<v8::ArrayBufferView>::try_from(value) else {
+    return napi_invalid_arg;
+  };
   if !data.is_null() {
-    *data = get_array_buffer_ptr(abuf);
+    let Some(abuf) = buf.buffer(&mut env.scope()) else {
+      return napi_generic_failure;
+    };
+    *data = get_array_buffer_ptr(abuf).add(buf.byte_offset());
   }
   if !length.is_null() {
-    *length = abuf.byte_length();
+    *length = buf.byte_length();
   }
   napi_ok
 }
@@ -2224,9 +2231,8 @@ fn napi_is_buffer(
   result: *mut bool,
 ) -> napi_status {
   let value = napi_value_unchecked(value);
-  // TODO: should we assume Buffer as Uint8Array in Deno?
-  // or use std/node polyfill?
-  *result = value.is_typed_array();
+  // Node.js considers any view a buffer.
+  *result = value.is_array_buffer_view();
   napi_ok
 }
 
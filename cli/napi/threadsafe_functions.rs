@@ -111,10 +111,15 @@ impl TsFn {
       });
       // This call should never fail
       self.sender.unbounded_send(call).unwrap();
-    } else if let Some(_js_func) = js_func {
+    } else if let Some(js_func) = js_func {
+      let env = self.env;
       let call = Box::new(move || {
-        // TODO: func.call
-        // let func = js_func.open(scope);
+        // Without `call_js_cb`, the function is called without arguments.
+        let scope = &mut unsafe { (*env).scope() };
+        let func = js_func.open(scope);
+        let recv = v8::undefined(scope).into();
+        func.call(scope, recv, &[]);
+
         // Receiver might have been already dropped
         let _ = tx.send(());
       });
@@ -2,6 +2,7 @@
 
 use deno_runtime::deno_napi::*;
 use std::cell::Cell;
+use std::ptr::NonNull;
 
 unsafe fn get_backing_store_slice(
   backing_store: &mut v8::SharedRef<v8::BackingStore>,
@@ -21,3 +22,32 @@ pub fn get_array_buffer_ptr(ab: v8::Local<v8::ArrayBuffer>) -> *mut u8 {
     unsafe { get_backing_store_slice(&mut backing_store, 0, byte_length) };
   slice.as_mut_ptr()
 }
+
+/// Gives `value` the prototype of the `Buffer` class found on the global
+/// object the module was loaded with, so that addons create Node.js buffers.
+/// `value` stays a plain `Uint8Array` if there is no such class.
+pub fn set_buffer_prototype(env: &Env, value: v8::Local<v8::Uint8Array>) {
+  let scope = &mut env.scope();
+  // SAFETY: `v8::Local` is always non-null pointer and the global object
+  // outlives the env.
+  let global = unsafe {
+    transmute::<NonNull<v8::Value>, v8::Local<v8::Value>>(env.global)
+  };
+  let Ok(global) = v8::Local::<v8::Object>::try_from(global) else {
+    return;
+  };
+  let buffer_name = v8::String::new(scope, "Buffer").unwrap();
+  let Some(buffer) = global.get(scope, buffer_name.into()) else {
+    return;
+  };
+  let Ok(buffer) = v8::Local::<v8::Function>::try_from(buffer) else {
+    return;
+  };
+  let prototype_name = v8::String::new(scope, "prototype").unwrap();
+  let Some(prototype) = buffer.get(scope, prototype_name.into()) else {
+    return;
+  };
+  if prototype.is_object() {
+    value.set_prototype(scope, prototype);
+  }
+}
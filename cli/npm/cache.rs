@@ -21,7 +21,9 @@ use deno_semver::Version;
 use once_cell::sync::Lazy;
 
 use crate::args::CacheSetting;
+use crate::cache::CACHE_PERM;
 use crate::http_util::HttpClient;
+use crate::util::fs::atomic_write_file;
 use crate::util::fs::canonicalize_path;
 use crate::util::fs::hard_link_dir_recursive;
 use crate::util::path::root_url_to_safe_local_dirname;
@@ -170,6 +172,17 @@ impl NpmCacheDir {
       .join(package.version.to_string())
   }
 
+  /// The tarball the package folder was extracted from, kept next to it.
+  pub fn package_tarball_for_name_and_version(
+    &self,
+    package: &NpmPackageNv,
+    registry_url: &Url,
+  ) -> PathBuf {
+    self
+      .package_name_folder(&package.name, registry_url)
+      .join(format!("{}.tgz", package.version))
+  }
+
   pub fn package_name_folder(&self, name: &str, registry_url: &Url) -> PathBuf {
     let mut dir = self.registry_folder(registry_url);
     if name.to_lowercase() != name {
@@ -385,7 +398,14 @@ impl NpmCache {
       .await?;
     match maybe_bytes {
       Some(bytes) => {
-        verify_and_extract_tarball(package, &bytes, dist, &package_folder)
+        verify_and_extract_tarball(package, &bytes, dist, &package_folder)?;
+        // Keep the verified tarball so `deno audit` can check the extracted
+        // files against it and the lockfile later.
+        let tarball_path = self
+          .cache_dir
+          .package_tarball_for_name_and_version(package, registry_url);
+        atomic_write_file(&tarball_path, bytes, CACHE_PERM)?;
+        Ok(())
       }
       None => {
         bail!("Could not find npm package tarball at: {}", dist.tarball);
@@ -443,6 +463,16 @@ impl NpmCache {
       .package_folder_for_name_and_version(package, registry_url)
   }
 
+  pub fn package_tarball_for_name_and_version(
+    &self,
+    package: &NpmPackageNv,
+    registry_url: &Url,
+  ) -> PathBuf {
+    self
+      .cache_dir
+      .package_tarball_for_name_and_version(package, registry_url)
+  }
+
   pub fn package_name_folder(&self, name: &str, registry_url: &Url) -> PathBuf {
     self.cache_dir.package_name_folder(name, registry_url)
   }
@@ -13,7 +13,10 @@ pub use cache::NpmCacheDir;
 pub use installer::PackageJsonDepsInstaller;
 pub use registry::CliNpmRegistryApi;
 pub use resolution::NpmResolution;
+pub use resolvers::create_byonm_npm_fs_resolver;
 pub use resolvers::create_npm_fs_resolver;
 pub use resolvers::CliNpmResolver;
 pub use resolvers::NpmPackageFsResolver;
 pub use resolvers::NpmProcessState;
+pub use tarball::find_modified_files;
+pub use tarball::tarball_integrity;
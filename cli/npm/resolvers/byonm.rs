@@ -0,0 +1,225 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Code for resolving npm packages from a node_modules directory that is
+//! managed by another tool (ex. npm, pnpm or yarn).
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use deno_npm::NpmPackageId;
+use deno_runtime::deno_fs;
+use deno_runtime::deno_node::NodePermissions;
+use deno_runtime::deno_node::NodeResolutionMode;
+use deno_runtime::deno_node::PackageJson;
+
+use super::common::types_package_name;
+use super::common::NpmPackageFsResolver;
+use super::common::RegistryReadPermissionChecker;
+use super::local::join_package_name;
+
+/// Resolver that resolves packages from an existing node_modules directory
+/// without installing anything into it.
+#[derive(Debug)]
+pub struct ByonmNpmPackageResolver {
+  fs: Arc<dyn deno_fs::FileSystem>,
+  root_node_modules_path: PathBuf,
+  root_node_modules_url: Url,
+  registry_read_permission_checker: RegistryReadPermissionChecker,
+}
+
+impl ByonmNpmPackageResolver {
+  pub fn new(
+    fs: Arc<dyn deno_fs::FileSystem>,
+    node_modules_folder: PathBuf,
+  ) -> Self {
+    Self {
+      fs: fs.clone(),
+      root_node_modules_url: Url::from_directory_path(&node_modules_folder)
+        .unwrap(),
+      root_node_modules_path: node_modules_folder.clone(),
+      registry_read_permission_checker: RegistryReadPermissionChecker::new(
+        fs,
+        node_modules_folder,
+      ),
+    }
+  }
+
+  fn resolve_folder_for_specifier(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<PathBuf, AnyError> {
+    let is_in_node_modules = self
+      .root_node_modules_url
+      .make_relative(specifier)
+      .map(|relative_url| !relative_url.starts_with("../"))
+      .unwrap_or(false);
+    if is_in_node_modules {
+      if let Ok(path) = specifier.to_file_path() {
+        if let Some(package_root) = resolve_package_root(&path) {
+          return Ok(package_root);
+        }
+      }
+    }
+    bail!("could not find npm package for '{}'", specifier)
+  }
+}
+
+#[async_trait]
+impl NpmPackageFsResolver for ByonmNpmPackageResolver {
+  fn root_dir_url(&self) -> &Url {
+    &self.root_node_modules_url
+  }
+
+  fn node_modules_path(&self) -> Option<PathBuf> {
+    Some(self.root_node_modules_path.clone())
+  }
+
+  fn package_folder(&self, id: &NpmPackageId) -> Result<PathBuf, AnyError> {
+    bail!(
+      "Could not find package information for '{}' because the node_modules folder is managed by another tool.",
+      id.as_serialized()
+    )
+  }
+
+  fn resolve_package_folder_from_package(
+    &self,
+    name: &str,
+    referrer: &ModuleSpecifier,
+    mode: NodeResolutionMode,
+  ) -> Result<PathBuf, AnyError> {
+    let Ok(referrer_path) = referrer.to_file_path() else {
+      bail!(
+        "could not find package '{}' from referrer '{}'.",
+        name,
+        referrer
+      );
+    };
+    // like node, look for the package in the node_modules folder of every
+    // ancestor directory of the referrer
+    for current_folder in referrer_path.ancestors().skip(1) {
+      if current_folder.ends_with("node_modules") {
+        continue;
+      }
+      let node_modules_folder = current_folder.join("node_modules");
+      let sub_dir = join_package_name(&node_modules_folder, name);
+      if self.fs.is_dir(&sub_dir) {
+        // if doing types resolution, only resolve the package if it specifies a types property
+        if mode.is_types() && !name.starts_with("@types/") {
+          let package_json = PackageJson::load_skip_read_permission(
+            &*self.fs,
+            sub_dir.join("package.json"),
+          )?;
+          if package_json.types.is_some() {
+            return Ok(sub_dir);
+          }
+        } else {
+          return Ok(sub_dir);
+        }
+      }
+
+      // if doing type resolution, check for the existence of a @types package
+      if mode.is_types() && !name.starts_with("@types/") {
+        let sub_dir =
+          join_package_name(&node_modules_folder, &types_package_name(name));
+        if self.fs.is_dir(&sub_dir) {
+          return Ok(sub_dir);
+        }
+      }
+    }
+
+    bail!(
+      "could not find package '{}' from referrer '{}'. Is it installed in the node_modules folder?",
+      name,
+      referrer
+    )
+  }
+
+  fn resolve_package_folder_from_specifier(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<PathBuf, AnyError> {
+    self.resolve_folder_for_specifier(specifier)
+  }
+
+  async fn cache_packages(&self) -> Result<(), AnyError> {
+    // the packages are installed by another tool
+    Ok(())
+  }
+
+  fn ensure_read_permission(
+    &self,
+    permissions: &dyn NodePermissions,
+    path: &Path,
+  ) -> Result<(), AnyError> {
+    self
+      .registry_read_permission_checker
+      .ensure_registry_read_permission(permissions, path)
+  }
+}
+
+/// Gets the folder of the package that contains the provided path, which is
+/// the directory right after the last `node_modules` directory, taking into
+/// account scoped packages.
+fn resolve_package_root(path: &Path) -> Option<PathBuf> {
+  let mut previous = None;
+  for ancestor in path.ancestors() {
+    let parent = ancestor.parent()?;
+    if parent.file_name()? == "node_modules" {
+      let is_scope = ancestor
+        .file_name()
+        .map(|name| name.to_string_lossy().starts_with('@'))
+        .unwrap_or(false);
+      return if is_scope {
+        previous.map(Path::to_path_buf)
+      } else {
+        Some(ancestor.to_path_buf())
+      };
+    }
+    previous = Some(ancestor);
+  }
+  None
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_resolve_package_root() {
+    fn resolve(path: &str) -> Option<String> {
+      resolve_package_root(Path::new(path))
+        .map(|path| path.to_string_lossy().replace('\\', "/"))
+    }
+
+    assert_eq!(
+      resolve("/project/node_modules/chalk/source/index.js"),
+      Some("/project/node_modules/chalk".to_string()),
+    );
+    assert_eq!(
+      resolve("/project/node_modules/@types/node/index.d.ts"),
+      Some("/project/node_modules/@types/node".to_string()),
+    );
+    // pnpm layout
+    assert_eq!(
+      resolve(
+        "/project/node_modules/.pnpm/chalk@5.2.0/node_modules/chalk/index.js"
+      ),
+      Some(
+        "/project/node_modules/.pnpm/chalk@5.2.0/node_modules/chalk"
+          .to_string()
+      ),
+    );
+    // nested node_modules folder
+    assert_eq!(
+      resolve("/project/node_modules/a/node_modules/@scope/b/lib/index.js"),
+      Some("/project/node_modules/a/node_modules/@scope/b".to_string()),
+    );
+    assert_eq!(resolve("/project/src/main.ts"), None);
+  }
+}
@@ -34,6 +34,7 @@ use deno_runtime::deno_node::NodePermissions;
 use deno_runtime::deno_node::NodeResolutionMode;
 use deno_runtime::deno_node::PackageJson;
 
+use crate::args::package_json::WorkspaceMember;
 use crate::npm::cache::mixed_case_package_name_encode;
 use crate::npm::cache::should_sync_download;
 use crate::npm::resolution::NpmResolution;
@@ -58,9 +59,11 @@ pub struct LocalNpmPackageResolver {
   root_node_modules_url: Url,
   system_info: NpmSystemInfo,
   registry_read_permission_checker: RegistryReadPermissionChecker,
+  workspace_members: Vec<WorkspaceMember>,
 }
 
 impl LocalNpmPackageResolver {
+  #[allow(clippy::too_many_arguments)]
   pub fn new(
     fs: Arc<dyn deno_fs::FileSystem>,
     cache: Arc<NpmCache>,
@@ -69,6 +72,7 @@ impl LocalNpmPackageResolver {
     node_modules_folder: PathBuf,
     resolution: Arc<NpmResolution>,
     system_info: NpmSystemInfo,
+    workspace_members: Vec<WorkspaceMember>,
   ) -> Self {
     Self {
       fs: fs.clone(),
@@ -84,6 +88,7 @@ impl LocalNpmPackageResolver {
         fs,
         node_modules_folder,
       ),
+      workspace_members,
     }
   }
 
@@ -224,7 +229,11 @@ impl NpmPackageFsResolver for LocalNpmPackageResolver {
       &self.root_node_modules_path,
       &self.system_info,
     )
-    .await
+    .await?;
+    link_workspace_members(
+      &self.workspace_members,
+      &self.root_node_modules_path,
+    )
   }
 
   fn ensure_read_permission(
@@ -454,6 +463,28 @@ async fn sync_resolution_with_fs(
   Ok(())
 }
 
+/// Symlinks node_modules/<package_name> to the directory of each workspace
+/// member, so the members can import each other by package name.
+fn link_workspace_members(
+  workspace_members: &[WorkspaceMember],
+  root_node_modules_dir_path: &Path,
+) -> Result<(), AnyError> {
+  if workspace_members.is_empty() {
+    return Ok(());
+  }
+
+  fs::create_dir_all(root_node_modules_dir_path).with_context(|| {
+    format!("Creating '{}'", root_node_modules_dir_path.display())
+  })?;
+  for member in workspace_members {
+    symlink_package_dir(
+      member.dir(),
+      &join_package_name(root_node_modules_dir_path, &member.name),
+    )?;
+  }
+  Ok(())
+}
+
 fn get_package_folder_id_folder_name(
   folder_id: &NpmPackageCacheFolderId,
 ) -> String {
@@ -523,7 +554,7 @@ fn junction_or_symlink_dir(
   }
 }
 
-fn join_package_name(path: &Path, package_name: &str) -> PathBuf {
+pub(super) fn join_package_name(path: &Path, package_name: &str) -> PathBuf {
   let mut path = path.to_path_buf();
   // ensure backslashes are used on windows
   for part in package_name.split('/') {
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+mod byonm;
 mod common;
 mod global;
 mod local;
@@ -32,10 +33,12 @@ use global::GlobalNpmPackageResolver;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::args::package_json::WorkspaceMember;
 use crate::args::Lockfile;
 use crate::util::fs::canonicalize_path_maybe_not_exists_with_fs;
 use crate::util::progress_bar::ProgressBar;
 
+use self::byonm::ByonmNpmPackageResolver;
 use self::local::LocalNpmPackageResolver;
 use super::resolution::NpmResolution;
 use super::NpmCache;
@@ -297,6 +300,7 @@ impl NpmResolver for CliNpmResolver {
   }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn create_npm_fs_resolver(
   fs: Arc<dyn FileSystem>,
   cache: Arc<NpmCache>,
@@ -305,6 +309,7 @@ pub fn create_npm_fs_resolver(
   resolution: Arc<NpmResolution>,
   maybe_node_modules_path: Option<PathBuf>,
   system_info: NpmSystemInfo,
+  workspace_members: Vec<WorkspaceMember>,
 ) -> Arc<dyn NpmPackageFsResolver> {
   match maybe_node_modules_path {
     Some(node_modules_folder) => Arc::new(LocalNpmPackageResolver::new(
@@ -315,6 +320,7 @@ pub fn create_npm_fs_resolver(
       node_modules_folder,
       resolution,
       system_info,
+      workspace_members,
     )),
     None => Arc::new(GlobalNpmPackageResolver::new(
       fs,
@@ -326,6 +332,15 @@ pub fn create_npm_fs_resolver(
   }
 }
 
+/// Creates a resolver for packages in a node_modules folder that is managed
+/// by another tool, which never installs any packages.
+pub fn create_byonm_npm_fs_resolver(
+  fs: Arc<dyn FileSystem>,
+  node_modules_path: PathBuf,
+) -> Arc<dyn NpmPackageFsResolver> {
+  Arc::new(ByonmNpmPackageResolver::new(fs, node_modules_path))
+}
+
 fn path_to_specifier(path: &Path) -> Result<ModuleSpecifier, AnyError> {
   match ModuleSpecifier::from_file_path(path.to_path_buf().clean()) {
     Ok(specifier) => Ok(specifier),
@@ -2,6 +2,7 @@
 
 use std::collections::HashSet;
 use std::fs;
+use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -28,36 +29,56 @@ pub fn verify_and_extract_tarball(
   })
 }
 
-fn verify_tarball_integrity(
+/// Hashes a tarball with the hash function of `npm_integrity`, returning the
+/// result in the same `<hash function>-<base64 digest>` form.
+pub fn tarball_integrity(
   package: &NpmPackageNv,
   data: &[u8],
   npm_integrity: &str,
-) -> Result<(), AnyError> {
+) -> Result<String, AnyError> {
+  let (hash_kind, checksum) = tarball_checksum(package, data, npm_integrity)?;
+  Ok(format!("{hash_kind}-{checksum}"))
+}
+
+fn tarball_checksum<'a>(
+  package: &NpmPackageNv,
+  data: &[u8],
+  npm_integrity: &'a str,
+) -> Result<(&'a str, String), AnyError> {
   use ring::digest::Context;
-  let (algo, expected_checksum) = match npm_integrity.split_once('-') {
-    Some((hash_kind, checksum)) => {
-      let algo = match hash_kind {
-        "sha512" => &ring::digest::SHA512,
-        "sha1" => &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
-        hash_kind => bail!(
-          "Not implemented hash function for {}: {}",
-          package,
-          hash_kind
-        ),
-      };
-      (algo, checksum.to_lowercase())
-    }
+  let hash_kind = match npm_integrity.split_once('-') {
+    Some((hash_kind, _)) => hash_kind,
     None => bail!(
       "Not implemented integrity kind for {}: {}",
       package,
       npm_integrity
     ),
   };
+  let algo = match hash_kind {
+    "sha512" => &ring::digest::SHA512,
+    "sha1" => &ring::digest::SHA1_FOR_LEGACY_USE_ONLY,
+    hash_kind => bail!(
+      "Not implemented hash function for {}: {}",
+      package,
+      hash_kind
+    ),
+  };
 
   let mut hash_ctx = Context::new(algo);
   hash_ctx.update(data);
   let digest = hash_ctx.finish();
-  let tarball_checksum = base64::encode(digest.as_ref()).to_lowercase();
+  Ok((hash_kind, base64::encode(digest.as_ref())))
+}
+
+fn verify_tarball_integrity(
+  package: &NpmPackageNv,
+  data: &[u8],
+  npm_integrity: &str,
+) -> Result<(), AnyError> {
+  let (_, tarball_checksum) = tarball_checksum(package, data, npm_integrity)?;
+  let tarball_checksum = tarball_checksum.to_lowercase();
+  let expected_checksum = npm_integrity.split_once('-').unwrap().1;
+  let expected_checksum = expected_checksum.to_lowercase();
   if tarball_checksum != expected_checksum {
     bail!(
       "Tarball checksum did not match what was provided by npm registry for {}.\n\nExpected: {}\nActual: {}",
@@ -69,6 +90,35 @@ fn verify_tarball_integrity(
   Ok(())
 }
 
+/// Compares a package extracted by [`verify_and_extract_tarball`] to the
+/// tarball it was extracted from. Returns the paths, relative to the package
+/// folder, of the files of the tarball that are missing from the folder or
+/// whose content differs.
+pub fn find_modified_files(
+  data: &[u8],
+  package_folder: &Path,
+) -> Result<Vec<String>, AnyError> {
+  let mut archive = Archive::new(GzDecoder::new(data));
+  let mut modified_files = Vec::new();
+  for entry in archive.entries()? {
+    let mut entry = entry?;
+    if entry.header().entry_type() != EntryType::Regular {
+      continue;
+    }
+    // skip the first component like when extracting
+    let relative_path = entry.path()?.components().skip(1).collect::<PathBuf>();
+    let mut expected = Vec::new();
+    entry.read_to_end(&mut expected)?;
+    match fs::read(package_folder.join(&relative_path)) {
+      Ok(actual) if actual == expected => {}
+      _ => {
+        modified_files.push(relative_path.to_string_lossy().replace('\\', "/"))
+      }
+    }
+  }
+  Ok(modified_files)
+}
+
 fn extract_tarball(data: &[u8], output_folder: &Path) -> Result<(), AnyError> {
   fs::create_dir_all(output_folder)?;
   let output_folder = fs::canonicalize(output_folder)?;
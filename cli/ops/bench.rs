@@ -55,10 +55,12 @@ pub fn op_pledge_test_permissions(
   let token = Uuid::new_v4();
   let parent_permissions = state.borrow_mut::<PermissionsContainer>();
   let worker_permissions = {
-    let mut parent_permissions = parent_permissions.0.lock();
-    let perms = create_child_permissions(&mut parent_permissions, args)?;
-    PermissionsContainer::new(perms)
+    let mut parent_permissions = parent_permissions.lock();
+    create_child_permissions(&mut parent_permissions, args)?
   };
+  let mut worker_permissions = PermissionsContainer::new(worker_permissions);
+  worker_permissions.set_shared_prompter(parent_permissions.prompter());
+  worker_permissions.set_audit_log(parent_permissions.audit_log());
   let parent_permissions = parent_permissions.clone();
 
   if state.try_take::<PermissionsHolder>().is_some() {
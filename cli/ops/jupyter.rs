@@ -0,0 +1,46 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::AnyError;
+use deno_core::futures::channel::mpsc;
+use deno_core::op;
+use deno_core::OpState;
+
+/// Output of `console` methods that the Jupyter kernel publishes to the
+/// notebook.
+#[derive(Debug)]
+pub enum StdioMsg {
+  Stdout(String),
+  Stderr(String),
+}
+
+deno_core::extension!(deno_jupyter,
+  options = {
+    sender: mpsc::UnboundedSender<StdioMsg>,
+  },
+  middleware = |op| match op.name {
+    "op_print" => op_print::decl(),
+    _ => op,
+  },
+  state = |state, options| {
+    state.put(options.sender);
+  },
+);
+
+// override op_print to send the output to the notebook instead of stdout and
+// stderr of the kernel process
+#[op]
+pub fn op_print(
+  state: &mut OpState,
+  msg: &str,
+  is_err: bool,
+) -> Result<(), AnyError> {
+  let sender = state.borrow::<mpsc::UnboundedSender<StdioMsg>>();
+  let msg = if is_err {
+    StdioMsg::Stderr(msg.to_string())
+  } else {
+    StdioMsg::Stdout(msg.to_string())
+  };
+  // ignore the error as the kernel might be shutting down
+  let _ = sender.unbounded_send(msg);
+  Ok(())
+}
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::tools::test::SnapshotFile;
 use crate::tools::test::TestDescription;
 use crate::tools::test::TestEvent;
 use crate::tools::test::TestEventSender;
@@ -35,16 +36,29 @@ deno_core::extension!(deno_test,
     op_register_test,
     op_register_test_step,
     op_dispatch_test_event,
+    op_test_assert_snapshot,
   ],
   options = {
     sender: TestEventSender,
+    update_snapshots: bool,
   },
   state = |state, options| {
     state.put(options.sender);
     state.put(TestContainer::default());
+    state.put(SnapshotState {
+      update: options.update_snapshots,
+      file: None,
+    });
   },
 );
 
+/// The snapshots of the test module, which are loaded on the first
+/// assertion of a snapshot.
+pub(crate) struct SnapshotState {
+  pub update: bool,
+  pub file: Option<SnapshotFile>,
+}
+
 #[derive(Clone)]
 struct PermissionsHolder(Uuid, PermissionsContainer);
 
@@ -56,10 +70,12 @@ pub fn op_pledge_test_permissions(
   let token = Uuid::new_v4();
   let parent_permissions = state.borrow_mut::<PermissionsContainer>();
   let worker_permissions = {
-    let mut parent_permissions = parent_permissions.0.lock();
-    let perms = create_child_permissions(&mut parent_permissions, args)?;
-    PermissionsContainer::new(perms)
+    let mut parent_permissions = parent_permissions.lock();
+    create_child_permissions(&mut parent_permissions, args)?
   };
+  let mut worker_permissions = PermissionsContainer::new(worker_permissions);
+  worker_permissions.set_shared_prompter(parent_permissions.prompter());
+  worker_permissions.set_audit_log(parent_permissions.audit_log());
   let parent_permissions = parent_permissions.clone();
 
   if state.try_take::<PermissionsHolder>().is_some() {
@@ -101,6 +117,10 @@ struct TestInfo<'s> {
   ignore: bool,
   #[serde(default)]
   only: bool,
+  #[serde(default)]
+  retries: Option<usize>,
+  #[serde(default)]
+  tags: Vec<String>,
   location: TestLocation,
 }
 
@@ -128,6 +148,8 @@ fn op_register_test<'a>(
     only: info.only,
     origin: origin.clone(),
     location: info.location,
+    retries: info.retries,
+    tags: info.tags,
   };
   let function: v8::Local<v8::Function> = info.function.v8_value.try_into()?;
   let function = v8::Global::new(scope, function);
@@ -199,3 +221,20 @@ fn op_dispatch_test_event(
   sender.send(event).ok();
   Ok(())
 }
+
+/// Compares the serialized value with its snapshot, returning the message of
+/// the assertion error if they don't match.
+#[op]
+fn op_test_assert_snapshot(
+  state: &mut OpState,
+  test_name: String,
+  actual: String,
+) -> Result<Option<String>, AnyError> {
+  let specifier = state.borrow::<ModuleSpecifier>().clone();
+  let snapshots = state.borrow_mut::<SnapshotState>();
+  if snapshots.file.is_none() {
+    snapshots.file = Some(SnapshotFile::load(&specifier, snapshots.update)?);
+  }
+  let file = snapshots.file.as_mut().unwrap();
+  Ok(file.assert(&test_name, actual))
+}
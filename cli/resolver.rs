@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use deno_core::anyhow::anyhow;
+use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
 use deno_core::futures::future;
 use deno_core::futures::future::LocalBoxFuture;
@@ -12,16 +13,27 @@ use deno_graph::source::NpmResolver;
 use deno_graph::source::Resolver;
 use deno_graph::source::UnknownBuiltInNodeModuleError;
 use deno_graph::source::DEFAULT_JSX_IMPORT_SOURCE_MODULE;
+use deno_graph::SpecifierError;
 use deno_npm::registry::NpmRegistryApi;
+use deno_runtime::deno_fs::FileSystem;
 use deno_runtime::deno_node::is_builtin_node_module;
+use deno_runtime::deno_node::NodeResolutionMode;
+use deno_runtime::deno_node::NodeResolver;
+use deno_runtime::deno_node::NpmResolver as _;
+use deno_runtime::deno_node::PackageJson;
+use deno_runtime::permissions::PermissionsContainer;
 use deno_semver::npm::NpmPackageReq;
+use deno_semver::npm::NpmPackageReqReference;
+use deno_semver::Version;
 use import_map::ImportMap;
 use std::sync::Arc;
 
 use crate::args::package_json::PackageJsonDeps;
+use crate::args::package_json::WorkspaceMember;
 use crate::args::JsxImportSourceConfig;
 use crate::args::PackageJsonDepsProvider;
 use crate::npm::CliNpmRegistryApi;
+use crate::npm::CliNpmResolver;
 use crate::npm::NpmResolution;
 use crate::npm::PackageJsonDepsInstaller;
 use crate::util::sync::AtomicFlag;
@@ -32,6 +44,7 @@ pub enum MappedResolution {
   None,
   PackageJson(ModuleSpecifier),
   ImportMap(ModuleSpecifier),
+  Workspace(ModuleSpecifier),
 }
 
 impl MappedResolution {
@@ -40,6 +53,7 @@ impl MappedResolution {
       MappedResolution::None => Option::None,
       MappedResolution::PackageJson(specifier) => Some(specifier),
       MappedResolution::ImportMap(specifier) => Some(specifier),
+      MappedResolution::Workspace(specifier) => Some(specifier),
     }
   }
 }
@@ -86,6 +100,14 @@ impl MappedSpecifierResolver {
       }
     }
 
+    // then with the packages of the workspace
+    if let Some(specifier) = resolve_workspace_member(
+      specifier,
+      self.package_json_deps_provider.workspace_members(),
+    )? {
+      return Ok(MappedResolution::Workspace(specifier));
+    }
+
     // otherwise, surface the import map error or try resolving when has no import map
     if let Some(err) = maybe_import_map_err {
       Err(err.into())
@@ -95,6 +117,105 @@ impl MappedSpecifierResolver {
   }
 }
 
+/// Resolves npm specifiers and bare specifiers to the packages of a
+/// node_modules folder that is managed by another tool (ex. pnpm or yarn).
+#[derive(Debug)]
+pub struct ByonmSpecifierResolver {
+  fs: Arc<dyn FileSystem>,
+  node_resolver: Arc<NodeResolver>,
+  npm_resolver: Arc<CliNpmResolver>,
+}
+
+impl ByonmSpecifierResolver {
+  pub fn new(
+    fs: Arc<dyn FileSystem>,
+    node_resolver: Arc<NodeResolver>,
+    npm_resolver: Arc<CliNpmResolver>,
+  ) -> Self {
+    Self {
+      fs,
+      node_resolver,
+      npm_resolver,
+    }
+  }
+
+  /// Resolves the specifier with node resolution from the referrer, or
+  /// returns `None` if it's neither an npm nor a bare specifier.
+  pub fn resolve(
+    &self,
+    specifier: &str,
+    referrer: &ModuleSpecifier,
+  ) -> Result<Option<ModuleSpecifier>, AnyError> {
+    let bare_specifier =
+      if let Ok(reference) = NpmPackageReqReference::from_str(specifier) {
+        self.check_installed_version(&reference.req, referrer)?;
+        match &reference.sub_path {
+          Some(sub_path) => format!("{}/{}", reference.req.name, sub_path),
+          None => reference.req.name,
+        }
+      } else if matches!(
+        deno_graph::resolve_import(specifier, referrer),
+        Err(SpecifierError::ImportPrefixMissing(..))
+      ) {
+        specifier.to_string()
+      } else {
+        return Ok(None);
+      };
+
+    let maybe_resolution = self.node_resolver.resolve(
+      &bare_specifier,
+      referrer,
+      NodeResolutionMode::Execution,
+      &PermissionsContainer::allow_all(),
+    )?;
+    match maybe_resolution {
+      Some(resolution) => Ok(Some(resolution.into_url())),
+      None => bail!(
+        "Could not resolve '{}' from the node_modules folder.",
+        specifier
+      ),
+    }
+  }
+
+  /// Errors when the package installed in the node_modules folder doesn't
+  /// match the version requirement of the npm specifier.
+  fn check_installed_version(
+    &self,
+    req: &NpmPackageReq,
+    referrer: &ModuleSpecifier,
+  ) -> Result<(), AnyError> {
+    let Some(version_req) = &req.version_req else {
+      return Ok(());
+    };
+    let package_folder =
+      self.npm_resolver.resolve_package_folder_from_package(
+        &req.name,
+        referrer,
+        NodeResolutionMode::Execution,
+      )?;
+    let package_json = PackageJson::load_skip_read_permission(
+      &*self.fs,
+      package_folder.join("package.json"),
+    )?;
+    let version = package_json.version.as_deref().unwrap_or("unknown");
+    let is_match = Version::parse_from_npm(version)
+      .map(|version| version_req.matches(&version))
+      .unwrap_or(false);
+    if !is_match {
+      bail!(
+        concat!(
+          "The installed version {} of '{}' does not satisfy '{}'. ",
+          "Update the node_modules folder with your package manager."
+        ),
+        version,
+        req.name,
+        req
+      );
+    }
+    Ok(())
+  }
+}
+
 /// A resolver that takes care of resolution, taking into account loaded
 /// import map, JSX settings.
 #[derive(Debug)]
@@ -108,6 +229,7 @@ pub struct CliGraphResolver {
   package_json_deps_installer: Arc<PackageJsonDepsInstaller>,
   found_package_json_dep_flag: Arc<AtomicFlag>,
   sync_download_queue: Option<Arc<TaskQueue>>,
+  maybe_byonm_resolver: Option<Arc<ByonmSpecifierResolver>>,
 }
 
 impl Default for CliGraphResolver {
@@ -133,11 +255,13 @@ impl Default for CliGraphResolver {
       package_json_deps_installer: Default::default(),
       found_package_json_dep_flag: Default::default(),
       sync_download_queue: Self::create_sync_download_queue(),
+      maybe_byonm_resolver: None,
     }
   }
 }
 
 impl CliGraphResolver {
+  #[allow(clippy::too_many_arguments)]
   pub fn new(
     maybe_jsx_import_source_config: Option<JsxImportSourceConfig>,
     maybe_import_map: Option<Arc<ImportMap>>,
@@ -146,6 +270,7 @@ impl CliGraphResolver {
     npm_resolution: Arc<NpmResolution>,
     package_json_deps_provider: Arc<PackageJsonDepsProvider>,
     package_json_deps_installer: Arc<PackageJsonDepsInstaller>,
+    maybe_byonm_resolver: Option<Arc<ByonmSpecifierResolver>>,
   ) -> Self {
     Self {
       mapped_specifier_resolver: MappedSpecifierResolver {
@@ -163,6 +288,7 @@ impl CliGraphResolver {
       package_json_deps_installer,
       found_package_json_dep_flag: Default::default(),
       sync_download_queue: Self::create_sync_download_queue(),
+      maybe_byonm_resolver,
     }
   }
 
@@ -223,15 +349,24 @@ impl Resolver for CliGraphResolver {
       .mapped_specifier_resolver
       .resolve(specifier, referrer)?
     {
-      ImportMap(specifier) => Ok(specifier),
+      ImportMap(specifier) | Workspace(specifier) => Ok(specifier),
       PackageJson(specifier) => {
         // found a specifier in the package.json, so mark that
         // we need to do an "npm install" later
         self.found_package_json_dep_flag.raise();
         Ok(specifier)
       }
-      None => deno_graph::resolve_import(specifier, referrer)
-        .map_err(|err| err.into()),
+      None => {
+        if let Some(byonm_resolver) = &self.maybe_byonm_resolver {
+          if let Some(specifier) =
+            byonm_resolver.resolve(specifier, referrer)?
+          {
+            return Ok(specifier);
+          }
+        }
+        deno_graph::resolve_import(specifier, referrer)
+          .map_err(|err| err.into())
+      }
     }
   }
 }
@@ -258,6 +393,23 @@ fn resolve_package_json_dep(
   Ok(None)
 }
 
+/// Resolves an import of a workspace member by its package name to the
+/// member's directory.
+fn resolve_workspace_member(
+  specifier: &str,
+  workspace_members: &[WorkspaceMember],
+) -> Result<Option<ModuleSpecifier>, AnyError> {
+  for member in workspace_members {
+    if let Some(path) = specifier.strip_prefix(&member.name) {
+      if path.is_empty() || path.starts_with('/') {
+        return member.resolve(path).map(Some);
+      }
+    }
+  }
+
+  Ok(None)
+}
+
 impl NpmResolver for CliGraphResolver {
   fn resolve_builtin_node_module(
     &self,
@@ -5,6 +5,8 @@ use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
 
+use deno_runtime::deno_fs::CloneMode;
+use deno_runtime::deno_fs::CopyFileOptions;
 use deno_runtime::deno_fs::FileSystem;
 use deno_runtime::deno_fs::FsDirEntry;
 use deno_runtime::deno_fs::FsFileType;
@@ -33,7 +35,16 @@ impl DenoCompileFileSystem {
     }
   }
 
-  fn copy_to_real_path(&self, oldpath: &Path, newpath: &Path) -> FsResult<()> {
+  fn copy_to_real_path(
+    &self,
+    oldpath: &Path,
+    newpath: &Path,
+    options: CopyFileOptions,
+  ) -> FsResult<()> {
+    // Files embedded in the executable can't be cloned.
+    if options.clone == CloneMode::Force {
+      return Err(FsError::NotSupported);
+    }
     let old_file = self.0.file_entry(oldpath)?;
     let old_file_bytes = self.0.read_file_all(old_file)?;
     RealFs.write_file_sync(
@@ -44,7 +55,7 @@ impl DenoCompileFileSystem {
         create: true,
         truncate: true,
         append: false,
-        create_new: false,
+        create_new: options.exclusive,
         mode: None,
       },
       &old_file_bytes,
@@ -150,28 +161,34 @@ impl FileSystem for DenoCompileFileSystem {
     RealFs.remove_async(path, recursive).await
   }
 
-  fn copy_file_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()> {
+  fn copy_file_sync(
+    &self,
+    oldpath: &Path,
+    newpath: &Path,
+    options: CopyFileOptions,
+  ) -> FsResult<()> {
     self.error_if_in_vfs(newpath)?;
     if self.0.is_path_within(oldpath) {
-      self.copy_to_real_path(oldpath, newpath)
+      self.copy_to_real_path(oldpath, newpath, options)
     } else {
-      RealFs.copy_file_sync(oldpath, newpath)
+      RealFs.copy_file_sync(oldpath, newpath, options)
     }
   }
   async fn copy_file_async(
     &self,
     oldpath: PathBuf,
     newpath: PathBuf,
+    options: CopyFileOptions,
   ) -> FsResult<()> {
     self.error_if_in_vfs(&newpath)?;
     if self.0.is_path_within(&oldpath) {
       let fs = self.clone();
       tokio::task::spawn_blocking(move || {
-        fs.copy_to_real_path(&oldpath, &newpath)
+        fs.copy_to_real_path(&oldpath, &newpath, options)
       })
       .await?
     } else {
-      RealFs.copy_file_async(oldpath, newpath).await
+      RealFs.copy_file_async(oldpath, newpath, options).await
     }
   }
 
@@ -334,4 +351,62 @@ impl FileSystem for DenoCompileFileSystem {
       .utime_async(path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
       .await
   }
+
+  fn get_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+  ) -> FsResult<Option<Vec<u8>>> {
+    self.error_if_in_vfs(path)?;
+    RealFs.get_xattr_sync(path, name)
+  }
+  async fn get_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<Option<Vec<u8>>> {
+    self.error_if_in_vfs(&path)?;
+    RealFs.get_xattr_async(path, name).await
+  }
+
+  fn set_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+    value: &[u8],
+  ) -> FsResult<()> {
+    self.error_if_in_vfs(path)?;
+    RealFs.set_xattr_sync(path, name, value)
+  }
+  async fn set_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+    value: Vec<u8>,
+  ) -> FsResult<()> {
+    self.error_if_in_vfs(&path)?;
+    RealFs.set_xattr_async(path, name, value).await
+  }
+
+  fn list_xattr_sync(&self, path: &Path) -> FsResult<Vec<String>> {
+    self.error_if_in_vfs(path)?;
+    RealFs.list_xattr_sync(path)
+  }
+  async fn list_xattr_async(&self, path: PathBuf) -> FsResult<Vec<String>> {
+    self.error_if_in_vfs(&path)?;
+    RealFs.list_xattr_async(path).await
+  }
+
+  fn remove_xattr_sync(&self, path: &Path, name: &str) -> FsResult<()> {
+    self.error_if_in_vfs(path)?;
+    RealFs.remove_xattr_sync(path, name)
+  }
+  async fn remove_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<()> {
+    self.error_if_in_vfs(&path)?;
+    RealFs.remove_xattr_async(path, name).await
+  }
 }
@@ -50,6 +50,7 @@ use deno_runtime::permissions::PermissionsContainer;
 use deno_runtime::WorkerLogLevel;
 use deno_semver::npm::NpmPackageReqReference;
 use import_map::parse_from_json;
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -141,6 +142,7 @@ impl ModuleLoader for EmbeddedModuleLoader {
     module_specifier: &ModuleSpecifier,
     maybe_referrer: Option<&ModuleSpecifier>,
     is_dynamic: bool,
+    _import_assertions: &HashMap<String, String>,
   ) -> Pin<Box<deno_core::ModuleSourceFuture>> {
     let is_data_uri = get_source_from_data_url(module_specifier).ok();
     let permissions = if is_dynamic {
@@ -355,6 +357,7 @@ pub async fn run(
     npm_resolution.clone(),
     node_modules_path,
     NpmSystemInfo::default(),
+    Vec::new(),
   );
   let npm_resolver = Arc::new(CliNpmResolver::new(
     fs.clone(),
@@ -378,6 +381,7 @@ pub async fn run(
     metadata
       .package_json_deps
       .map(|serialized| serialized.into_deps()),
+    Vec::new(),
   ));
   let maybe_import_map = metadata.maybe_import_map.map(|(base, source)| {
     Arc::new(parse_from_json(&base, &source).unwrap().import_map)
@@ -432,6 +436,7 @@ pub async fn run(
       argv: metadata.argv,
       log_level: WorkerLogLevel::Info,
       coverage_dir: None,
+      dns_config: metadata.dns_config,
       enable_testing_features: false,
       has_node_modules_dir,
       inspect_brk: false,
@@ -445,6 +450,7 @@ pub async fn run(
       .ok()
       .map(|req_ref| npm_pkg_req_ref_to_binary_command(&req_ref)),
       origin_data_folder_path: None,
+      permission_audit_log: None,
       seed: metadata.seed,
       unsafely_ignore_certificate_errors: metadata
         .unsafely_ignore_certificate_errors,
@@ -6,6 +6,7 @@ use util::assert_contains;
 use util::assert_not_contains;
 use util::env_vars_for_npm_tests;
 use util::TestContext;
+use util::TestContextBuilder;
 
 itest!(overloads {
   args: "bench bench/overloads.ts",
@@ -272,3 +273,48 @@ fn conditionally_loads_type_graph() {
     .run();
   assert_not_contains!(output.combined_output(), "type_reference.d.ts");
 }
+
+#[test]
+fn baseline_comparison() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write("a_bench.ts", "Deno.bench(\"work\", () => {});\n");
+
+  let output = context.new_command().args("bench --compare=main").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Bench baseline \"main\" not found"
+  );
+
+  let output = context
+    .new_command()
+    .args("bench --save-baseline=main")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Saved bench baseline \"main\"");
+
+  // the same benchmark doing much more work regresses
+  temp_dir.write(
+    "a_bench.ts",
+    r#"Deno.bench("work", () => {
+  let sum = 0;
+  for (let i = 0; i < 100_000; i++) {
+    sum += Math.sqrt(i);
+  }
+  return sum;
+});
+Deno.bench("added", () => {});
+"#,
+  );
+  let output = context.new_command().args("bench --compare=main").run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "comparison with baseline \"main\"");
+  assert_contains!(output_text, "regressed");
+  assert_contains!(output_text, "new");
+  assert_contains!(
+    output_text,
+    "Bench failed because 1 benchmark regressed by more than 10% compared to the baseline"
+  );
+}
@@ -21,8 +21,10 @@ util::unit_test_factory!(
     chmod_test,
     chown_test,
     command_test,
+    compression_stream_test,
     console_test,
     copy_file_test,
+    cron_test,
     custom_event_test,
     dir_test,
     dom_exception_test,
@@ -103,6 +105,7 @@ util::unit_test_factory!(
     worker_types,
     write_file_test,
     write_text_file_test,
+    xattr_test,
   ]
 );
 
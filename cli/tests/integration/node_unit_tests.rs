@@ -56,9 +56,11 @@ util::unit_test_factory!(
     fs_test,
     http_test,
     http2_test,
+    inspector_test,
     _randomBytes_test = internal / _randomBytes_test,
     _randomFill_test = internal / _randomFill_test,
     _randomInt_test = internal / _randomInt_test,
+    argon2_test = internal / argon2_test,
     pbkdf2_test = internal / pbkdf2_test,
     scrypt_test = internal / scrypt_test,
     module_test,
@@ -72,6 +74,7 @@ util::unit_test_factory!(
     tty_test,
     util_test,
     v8_test,
+    vm_test,
     worker_threads_test
   ]
 );
@@ -4047,6 +4047,7 @@ fn websocketstream() {
     .arg("test")
     .arg("--unstable")
     .arg("--allow-net")
+    .arg("--allow-read")
     .arg("--cert")
     .arg(root_ca)
     .arg(script)
@@ -4277,6 +4278,11 @@ itest!(permission_args_quiet {
   output: "run/001_hello.js.out",
 });
 
+itest!(fs_root {
+  args: "run --quiet --allow-read --fs-root=run/fs_root run/fs_root/main.ts",
+  output: "run/fs_root/main.out",
+});
+
 // Regression test for https://github.com/denoland/deno/issues/16772
 #[test]
 fn file_fetcher_preserves_permissions() {
@@ -4406,3 +4412,58 @@ itest!(extension_dynamic_import {
   output: "run/extension_dynamic_import.ts.out",
   exit_code: 1,
 });
+
+#[cfg(unix)]
+#[test]
+fn broadcast_channel_cross_process() {
+  use std::io::BufRead;
+
+  let channel_dir = TempDir::new();
+  let mut receiver = util::deno_cmd()
+    .current_dir(util::testdata_path())
+    .env("DENO_BROADCAST_CHANNEL_DIR", channel_dir.path())
+    .arg("run")
+    .arg("--unstable")
+    .arg("run/broadcast_channel_cross_process/receiver.ts")
+    .stdout(Stdio::piped())
+    .spawn()
+    .unwrap();
+  let mut stdout =
+    std::io::BufReader::new(receiver.stdout.take().unwrap()).lines();
+  assert_eq!(stdout.next().unwrap().unwrap(), "ready");
+
+  let status = util::deno_cmd()
+    .current_dir(util::testdata_path())
+    .env("DENO_BROADCAST_CHANNEL_DIR", channel_dir.path())
+    .arg("run")
+    .arg("--unstable")
+    .arg("run/broadcast_channel_cross_process/sender.ts")
+    .status()
+    .unwrap();
+  assert!(status.success());
+
+  assert_eq!(
+    stdout.next().unwrap().unwrap(),
+    "{ hello: \"from another process\" }"
+  );
+  assert!(receiver.wait().unwrap().success());
+}
+
+itest!(kv_remote {
+  args: "run --quiet --unstable --allow-net --allow-env run/kv_remote.ts",
+  output: "run/kv_remote.ts.out",
+  envs: vec![(
+    "DENO_KV_ACCESS_TOKEN".to_string(),
+    test_util::kv_remote::KV_ACCESS_TOKEN.to_string()
+  )],
+  http_server: true,
+});
+
+itest!(cache_storage_max_size {
+  args: "run --quiet --location http://localhost/cache_storage_max_size run/cache_storage_max_size.ts",
+  output: "run/cache_storage_max_size.ts.out",
+  envs: vec![(
+    "DENO_CACHE_STORAGE_MAX_SIZE".to_string(),
+    "1000".to_string()
+  )],
+});
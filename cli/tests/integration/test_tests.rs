@@ -345,6 +345,24 @@ itest!(steps_output_within {
   output: "test/steps/output_within.out",
 });
 
+itest!(reporter_tap {
+  args: "test --reporter=tap test/reporter/reporter.ts",
+  exit_code: 1,
+  output: "test/reporter/tap.out",
+});
+
+itest!(reporter_junit {
+  args: "test --reporter=junit test/reporter/reporter.ts",
+  exit_code: 1,
+  output: "test/reporter/junit.out",
+});
+
+itest!(snapshot {
+  args: "test test/snapshot/snapshot_test.ts",
+  exit_code: 1,
+  output: "test/snapshot/snapshot_test.out",
+});
+
 itest!(no_prompt_by_default {
   args: "test --quiet test/no_prompt_by_default.ts",
   exit_code: 1,
@@ -398,6 +416,144 @@ fn captured_output() {
   assert_eq!(lines.join(" "), "0 1 2 3 4 5 6 7 8 9");
 }
 
+#[test]
+fn snapshot_update() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "a_test.ts",
+    r#"Deno.test("value", (t) => {
+  t.assertSnapshot([1, "two"]);
+});
+"#,
+  );
+
+  // the snapshot is missing until it's written
+  let output = context.new_command().args("test a_test.ts").run();
+  output.assert_exit_code(1);
+  assert_contains!(output.combined_output(), "Missing snapshot: value 1");
+
+  let output = context
+    .new_command()
+    .args("test --update-snapshots a_test.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "Updated snapshots");
+  assert_contains!(
+    temp_dir.read_to_string("__snapshots__/a_test.ts.snap"),
+    "snapshot[`value 1`] = `"
+  );
+
+  let output = context.new_command().args("test a_test.ts").run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+
+  // the snapshot is removed once no test asserts it
+  temp_dir.write("a_test.ts", "Deno.test(\"value\", () => {});\n");
+  let output = context
+    .new_command()
+    .args("test --update-snapshots a_test.ts")
+    .run();
+  output.assert_exit_code(0);
+  assert_contains!(output.combined_output(), "(0 written, 1 removed)");
+  assert!(!temp_dir
+    .path()
+    .join("__snapshots__/a_test.ts.snap")
+    .exists());
+}
+
+#[test]
+fn retries() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "a_test.ts",
+    r#"let attempts = 0;
+Deno.test("flaky", () => {
+  attempts++;
+  if (attempts < 2) {
+    throw new Error("failed attempt");
+  }
+});
+Deno.test({ name: "always fails", retries: 1, fn: () => {
+  throw new Error("failed");
+} });
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("test --retries=2 a_test.ts")
+    .run();
+  output.assert_exit_code(1);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "retrying after attempt 1");
+  assert_contains!(output_text, "flaky (passed on attempt 2)");
+  assert_contains!(output_text, "1 passed | 1 failed | 1 flaky");
+
+  temp_dir.write(
+    "b_test.ts",
+    r#"let attempts = 0;
+Deno.test("flaky", () => {
+  if (attempts++ === 0) {
+    throw new Error("failed attempt");
+  }
+});
+"#,
+  );
+  let output = context
+    .new_command()
+    .args("test --retries=1 b_test.ts")
+    .run();
+  output.assert_exit_code(0);
+  output.skip_output_check();
+
+  let output = context
+    .new_command()
+    .args("test --retries=1 --fail-on-flaky b_test.ts")
+    .run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Test failed because tests only passed after being retried"
+  );
+}
+
+#[test]
+fn tags() {
+  let context = TestContextBuilder::new().use_temp_cwd().build();
+  let temp_dir = context.temp_dir();
+  temp_dir.write(
+    "a_test.ts",
+    r#"Deno.test({ name: "unit", fn() {} });
+Deno.test({ name: "integration", tags: ["integration"], fn() {} });
+Deno.test({ name: "slow integration", tags: ["integration", "slow"], fn() {} });
+"#,
+  );
+
+  let output = context
+    .new_command()
+    .args("test --tags=integration --exclude-tags=slow a_test.ts")
+    .run();
+  output.assert_exit_code(0);
+  let output_text = output.combined_output();
+  assert_contains!(output_text, "integration [integration] ...");
+  assert_not_contains!(output_text, "unit ...");
+  assert_not_contains!(output_text, "slow integration");
+  assert_contains!(output_text, "1 passed | 0 failed | 2 filtered out");
+
+  temp_dir.write(
+    "b_test.ts",
+    "Deno.test({ name: \"invalid\", tags: \"slow\", fn() {} });\n",
+  );
+  let output = context.new_command().args("test b_test.ts").run();
+  output.assert_exit_code(1);
+  assert_contains!(
+    output.combined_output(),
+    "Expected 'tags' field to be an array of strings."
+  );
+}
+
 #[test]
 fn recursive_permissions_pledge() {
   let context = TestContext::default();
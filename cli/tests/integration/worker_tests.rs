@@ -32,6 +32,16 @@ itest!(worker_message_handler_error {
   exit_code: 1,
 });
 
+itest!(worker_cpu_time_limit {
+  args: "run --quiet --unstable workers/cpu_time_limit.ts",
+  output: "workers/cpu_time_limit.ts.out",
+});
+
+itest!(worker_memory_limit {
+  args: "run --quiet --unstable workers/memory_limit.ts",
+  output: "workers/memory_limit.ts.out",
+});
+
 itest!(nonexistent_worker {
   args: "run --allow-read workers/nonexistent_worker.ts",
   output: "workers/nonexistent_worker.out",
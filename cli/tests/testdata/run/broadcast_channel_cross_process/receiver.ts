@@ -0,0 +1,6 @@
+const channel = new BroadcastChannel("cross-process");
+channel.onmessage = (e) => {
+  console.log(e.data);
+  channel.close();
+};
+console.log("ready");
@@ -0,0 +1,5 @@
+const channel = new BroadcastChannel("cross-process");
+channel.postMessage({ hello: "from another process" });
+// Messages are sent to other processes asynchronously; closing the channel
+// right away would drop this one.
+setTimeout(() => channel.close(), 100);
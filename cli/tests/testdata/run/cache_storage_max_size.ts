@@ -0,0 +1,28 @@
+const core = Deno[Deno.internal].core;
+const cacheName = "cache-max-size";
+await caches.delete(cacheName);
+const cache = await caches.open(cacheName);
+
+const body = "x".repeat(400);
+await cache.put("https://example.com/a", new Response(body));
+await cache.put("https://example.com/b", new Response(body));
+// Using "a" makes "b" the least recently used response.
+await (await cache.match("https://example.com/a"))?.text();
+await cache.put("https://example.com/c", new Response(body));
+
+for (const url of ["a", "b", "c"]) {
+  const response = await cache.match(`https://example.com/${url}`);
+  console.log(url, response !== undefined);
+  await response?.text();
+}
+const { usage, quota } = await core.opAsync("op_cache_storage_usage");
+console.log(usage <= quota, quota);
+
+try {
+  await cache.put("https://example.com/d", new Response("x".repeat(2000)));
+} catch (error) {
+  console.log(error.name);
+}
+console.log(await cache.match("https://example.com/d"));
+
+await caches.delete(cacheName);
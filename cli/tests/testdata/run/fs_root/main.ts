@@ -0,0 +1,9 @@
+console.log(Deno.readTextFileSync("./run/fs_root/inside.txt").trim());
+
+for (const path of ["./run/001_hello.js", "./run/fs_root/../001_hello.js"]) {
+  try {
+    Deno.readTextFileSync(path);
+  } catch (err) {
+    console.log(err instanceof Deno.errors.PermissionDenied);
+  }
+}
@@ -0,0 +1,28 @@
+const db = await Deno.openKv("http://localhost:4545/kv/kv_remote");
+
+await db.set(["a"], "hello");
+await db.set(["b", 1], 1n);
+await db.set(["b", 2], new Uint8Array([1, 2, 3]));
+console.log((await db.get(["a"])).value);
+
+const entries = [];
+for await (const entry of db.list({ prefix: ["b"] })) {
+  entries.push([entry.key, entry.value]);
+}
+console.log(entries);
+
+const current = await db.get(["a"]);
+const ok = await db.atomic().check(current).set(["a"], "world").commit();
+console.log(ok.ok);
+const conflict = await db.atomic().check(current).set(["a"], "again")
+  .commit();
+console.log(conflict.ok);
+
+await db.atomic().sum(["counter"], new Deno.KvU64(5n)).commit();
+await db.atomic().sum(["counter"], new Deno.KvU64(2n)).commit();
+console.log((await db.get(["counter"])).value);
+
+await db.delete(["a"]);
+console.log((await db.get(["a"])).value);
+
+db.close();
@@ -57,6 +57,35 @@ Deno.test("echo string tls", async () => {
   await ws.closed;
 });
 
+Deno.test("echo string tls client certificate", async () => {
+  const ws = new WebSocketStream("wss://localhost:4246", {
+    certChain: await Deno.readTextFile(
+      new URL("../tls/localhost.crt", import.meta.url),
+    ),
+    privateKey: await Deno.readTextFile(
+      new URL("../tls/localhost.key", import.meta.url),
+    ),
+  });
+  const { readable, writable } = await ws.connection;
+  await writable.getWriter().write("foo");
+  const res = await readable.getReader().read();
+  assertEquals(res.value, "foo");
+  ws.close();
+  await ws.closed;
+});
+
+Deno.test("client certificate without private key", async () => {
+  const ws = new WebSocketStream("wss://localhost:4246", {
+    certChain: await Deno.readTextFile(
+      new URL("../tls/localhost.crt", import.meta.url),
+    ),
+  });
+  await Promise.all([
+    assertRejects(() => ws.connection, TypeError, "No private key provided"),
+    assertRejects(() => ws.closed, TypeError, "No private key provided"),
+  ]);
+});
+
 Deno.test("websocket error", async () => {
   const ws = new WebSocketStream("wss://localhost:4242");
   await Promise.all([
@@ -84,6 +113,40 @@ Deno.test("echo uint8array", async () => {
   await ws.closed;
 });
 
+Deno.test("echo arraybuffer", async () => {
+  const ws = new WebSocketStream("ws://localhost:4242");
+  const { readable, writable } = await ws.opened;
+  await writable.getWriter().write(new Uint8Array([102, 111, 111]).buffer);
+  const res = await readable.getReader().read();
+  assertEquals(res.value, new Uint8Array([102, 111, 111]));
+  ws.close({ closeCode: 1000 });
+  await ws.closed;
+});
+
+Deno.test("closed resolves with the close code of the server", async () => {
+  const listener = Deno.listen({ port: 4512 });
+  const promise = (async () => {
+    const conn = await listener.accept();
+    const httpConn = Deno.serveHttp(conn);
+    const { request, respondWith } = (await httpConn.nextRequest())!;
+    const { response, socket } = Deno.upgradeWebSocket(request);
+    socket.onopen = () => socket.close(4000, "bye");
+    await respondWith(response);
+  })();
+
+  const ws = new WebSocketStream("ws://localhost:4512");
+  const { readable } = await ws.opened;
+  const reader = readable.getReader();
+  assertEquals(await reader.read(), { value: undefined, done: true });
+  assertEquals(await ws.closed, {
+    closeCode: 4000,
+    code: 4000,
+    reason: "bye",
+  });
+  await promise;
+  listener.close();
+});
+
 Deno.test("aborting immediately throws an AbortError", async () => {
   const controller = new AbortController();
   const wss = new WebSocketStream("ws://localhost:4242", {
@@ -0,0 +1,10 @@
+Deno.test("passes", () => {});
+
+Deno.test({ name: "is ignored", ignore: true, fn() {} });
+
+Deno.test("has steps", async (t) => {
+  await t.step("step 1", () => {});
+  await t.step("step 2", () => {
+    throw new Error("step failed");
+  });
+});
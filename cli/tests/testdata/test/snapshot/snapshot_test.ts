@@ -0,0 +1,7 @@
+Deno.test("matches", (t) => {
+  t.assertSnapshot({ b: [1, 2], a: "text" });
+});
+
+Deno.test("mismatches", (t) => {
+  t.assertSnapshot("actual");
+});
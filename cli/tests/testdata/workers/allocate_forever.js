@@ -0,0 +1,4 @@
+const arrays = [];
+while (true) {
+  arrays.push(new Array(100_000).fill(0));
+}
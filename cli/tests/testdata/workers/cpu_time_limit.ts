@@ -0,0 +1,8 @@
+const worker = new Worker(import.meta.resolve("./infinite_loop.js"), {
+  type: "module",
+  deno: { cpuTimeLimitMs: 100 },
+});
+worker.onerror = (e) => {
+  console.log(e.message);
+  e.preventDefault();
+};
@@ -0,0 +1,3 @@
+while (true) {
+  // spin
+}
@@ -0,0 +1,8 @@
+const worker = new Worker(import.meta.resolve("./allocate_forever.js"), {
+  type: "module",
+  deno: { memoryLimitMb: 32 },
+});
+worker.onerror = (e) => {
+  console.log(e.message);
+  e.preventDefault();
+};
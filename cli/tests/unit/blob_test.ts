@@ -1,5 +1,10 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
-import { assert, assertEquals, assertStringIncludes } from "./test_util.ts";
+import {
+  assert,
+  assertEquals,
+  assertRejects,
+  assertStringIncludes,
+} from "./test_util.ts";
 import { concat } from "../../../test_util/std/bytes/concat.ts";
 
 Deno.test(function blobString() {
@@ -113,3 +118,32 @@ Deno.test(function blobCustomInspectFunction() {
   );
   assertStringIncludes(Deno.inspect(Blob.prototype), "Blob");
 });
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function blobOpenFile() {
+    const path = Deno.makeTempFileSync();
+    const contents = "0123456789".repeat(10000);
+    Deno.writeTextFileSync(path, contents);
+
+    const file = await Deno.openBlob(path, { type: "text/plain" });
+    assert(file instanceof File);
+    assertEquals(file.size, contents.length);
+    assertEquals(file.type, "text/plain");
+    assertEquals(file.lastModified, Deno.statSync(path).mtime!.getTime());
+    assertEquals(await file.text(), contents);
+    assertEquals(await file.slice(65530, 65540).text(), "0123456789");
+
+    Deno.writeTextFileSync(path, "changed");
+    const error = await assertRejects(() => file.text(), DOMException);
+    assertEquals(error.name, "NotReadableError");
+    Deno.removeSync(path);
+  },
+);
+
+Deno.test({ permissions: { read: false } }, async function blobOpenFilePerm() {
+  await assertRejects(
+    () => Deno.openBlob("cli/tests/testdata/assets/fixture.json"),
+    Deno.errors.PermissionDenied,
+  );
+});
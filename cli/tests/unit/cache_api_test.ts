@@ -190,3 +190,22 @@ Deno.test(async function cachePutOverwrite() {
   const res_ = await cache.match(request);
   assertEquals(await res_?.text(), "res2");
 });
+
+Deno.test(async function cacheStorageUsage() {
+  // @ts-ignore: Deno[Deno.internal].core allowed
+  const core = Deno[Deno.internal].core;
+  const cacheName = "cache-usage";
+  await caches.delete(cacheName);
+  const before = await core.opAsync("op_cache_storage_usage");
+  assertEquals(before.quota, null);
+
+  const cache = await caches.open(cacheName);
+  const request = new Request("https://example.com/usage");
+  await cache.put(request, new Response("0123456789"));
+  const after = await core.opAsync("op_cache_storage_usage");
+  assert(after.usage >= before.usage + 10);
+
+  await caches.delete(cacheName);
+  const deleted = await core.opAsync("op_cache_storage_usage");
+  assertEquals(deleted.usage, before.usage);
+});
@@ -885,3 +885,54 @@ Deno.test(
     );
   },
 );
+
+Deno.test(
+  {
+    permissions: { run: true, read: true },
+    ignore: Deno.build.os === "windows",
+  },
+  async function commandPty() {
+    const child = new Deno.Command(Deno.execPath(), {
+      args: [
+        "eval",
+        "console.log(Deno.isatty(Deno.stdout.rid), Deno.consoleSize().columns)",
+      ],
+      pty: { columns: 100, rows: 30 },
+    }).spawn();
+    const output = await new Response(child.pty.readable).text();
+    const status = await child.status;
+    assert(status.success);
+    assertStringIncludes(output, "true 100");
+    assertThrows(() => child.stdout, TypeError, "stdout is not piped");
+  },
+);
+
+Deno.test(
+  { permissions: { run: true, read: true } },
+  function commandOutputPtyFails() {
+    assertThrows(
+      () =>
+        new Deno.Command(Deno.execPath(), {
+          args: ["help"],
+          pty: true,
+        }).output(),
+      TypeError,
+      "A pty is not supported",
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { run: true, read: true } },
+  async function commandNewProcessGroup() {
+    const child = new Deno.Command(Deno.execPath(), {
+      args: ["eval", "console.log('hello')"],
+      newProcessGroup: true,
+      stdout: "piped",
+      stderr: "null",
+    });
+    const { success, stdout } = await child.output();
+    assert(success);
+    assertEquals(new TextDecoder().decode(stdout), "hello\n");
+  },
+);
@@ -0,0 +1,93 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, assertRejects } from "./test_util.ts";
+
+async function concat(stream: ReadableStream<Uint8Array>) {
+  const chunks = [];
+  for await (const chunk of stream) {
+    chunks.push(chunk);
+  }
+  const out = new Uint8Array(chunks.reduce((len, c) => len + c.length, 0));
+  let offset = 0;
+  for (const chunk of chunks) {
+    out.set(chunk, offset);
+    offset += chunk.length;
+  }
+  return out;
+}
+
+function streamOf(...chunks: Uint8Array[]) {
+  return new ReadableStream<Uint8Array>({
+    start(controller) {
+      for (const chunk of chunks) {
+        controller.enqueue(chunk);
+      }
+      controller.close();
+    },
+  });
+}
+
+for (const format of ["br", "zstd"]) {
+  Deno.test(`compressionStreamRoundTrip_${format}`, async () => {
+    const text = "hello world ".repeat(1000);
+    const input = new TextEncoder().encode(text);
+    const compressed = await concat(
+      streamOf(input.subarray(0, 5000), input.subarray(5000))
+        .pipeThrough(new CompressionStream(format)),
+    );
+    assert(compressed.length < input.length);
+
+    const decompressed = await concat(
+      streamOf(compressed.subarray(0, 10), compressed.subarray(10))
+        .pipeThrough(new DecompressionStream(format)),
+    );
+    assertEquals(new TextDecoder().decode(decompressed), text);
+  });
+
+  Deno.test(`compressionStreamFlushesChunks_${format}`, async () => {
+    const compression = new CompressionStream(format);
+    const writer = compression.writable.getWriter();
+    const reader = compression.readable.getReader();
+    writer.write(new TextEncoder().encode("hello"));
+    // The compressed data of a chunk is available before the stream ends.
+    const { value } = await reader.read();
+    assert(value!.length > 0);
+
+    const decompression = new DecompressionStream(format);
+    const decompressionWriter = decompression.writable.getWriter();
+    const decompressionReader = decompression.readable.getReader();
+    decompressionWriter.write(value!);
+    const decompressed = await decompressionReader.read();
+    assertEquals(new TextDecoder().decode(decompressed.value), "hello");
+
+    const closed = writer.close();
+    const rest = await concat(
+      new ReadableStream({
+        async pull(controller) {
+          const { done, value } = await reader.read();
+          if (done) controller.close();
+          else controller.enqueue(value);
+        },
+      }),
+    );
+    await closed;
+    decompressionWriter.write(rest);
+    decompressionWriter.close();
+    assert((await decompressionReader.read()).done);
+  });
+}
+
+Deno.test(async function decompressionStreamTruncatedBrotli() {
+  const input = new TextEncoder().encode("hello world ".repeat(100));
+  const compressed = await concat(
+    streamOf(input).pipeThrough(new CompressionStream("br")),
+  );
+  await assertRejects(
+    () =>
+      concat(
+        streamOf(compressed.subarray(0, compressed.length - 2))
+          .pipeThrough(new DecompressionStream("br")),
+      ),
+    TypeError,
+    "Unexpected end of brotli stream",
+  );
+});
@@ -236,3 +236,59 @@ Deno.test(
     copyFileSyncMode("Hello world!".repeat(128 * 1024));
   },
 );
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  function copyFileSyncExclusive() {
+    const tempDir = Deno.makeTempDirSync();
+    const fromFilename = tempDir + "/from.txt";
+    const toFilename = tempDir + "/to.txt";
+    writeFileString(fromFilename, "Hello world!");
+    writeFileString(toFilename, "Goodbye!");
+    assertThrows(() => {
+      Deno.copyFileSync(fromFilename, toFilename, { exclusive: true });
+    }, Deno.errors.AlreadyExists);
+    assertEquals(readFileString(toFilename), "Goodbye!");
+
+    Deno.removeSync(toFilename);
+    Deno.copyFileSync(fromFilename, toFilename, { exclusive: true });
+    assertSameContent(fromFilename, toFilename);
+
+    Deno.removeSync(tempDir, { recursive: true });
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function copyFileExclusive() {
+    const tempDir = await Deno.makeTempDir();
+    const fromFilename = tempDir + "/from.txt";
+    const toFilename = tempDir + "/to.txt";
+    writeFileString(fromFilename, "Hello world!".repeat(128 * 1024));
+    writeFileString(toFilename, "Goodbye!");
+    await assertRejects(async () => {
+      await Deno.copyFile(fromFilename, toFilename, { exclusive: true });
+    }, Deno.errors.AlreadyExists);
+    assertEquals(readFileString(toFilename), "Goodbye!");
+
+    await Deno.remove(tempDir, { recursive: true });
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function copyFileCloneModes() {
+    const tempDir = await Deno.makeTempDir();
+    const fromFilename = tempDir + "/from.txt";
+    const toFilename = tempDir + "/to.txt";
+    writeFileString(fromFilename, "Hello world!".repeat(128 * 1024));
+    for (const clone of ["auto", "never"] as const) {
+      await Deno.copyFile(fromFilename, toFilename, { clone });
+      assertSameContent(fromFilename, toFilename);
+      Deno.copyFileSync(fromFilename, toFilename, { clone });
+      assertSameContent(fromFilename, toFilename);
+    }
+
+    await Deno.remove(tempDir, { recursive: true });
+  },
+);
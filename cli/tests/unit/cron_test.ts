@@ -0,0 +1,88 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+import { assertThrows } from "./test_util.ts";
+
+Deno.test(function cronInvalidArguments() {
+  assertThrows(
+    // @ts-ignore test
+    () => Deno.cron(),
+    TypeError,
+    "Deno.cron requires a unique name",
+  );
+  assertThrows(
+    // @ts-ignore test
+    () => Deno.cron("name"),
+    TypeError,
+    "Deno.cron requires a valid schedule",
+  );
+  assertThrows(
+    // @ts-ignore test
+    () => Deno.cron("name", "* * * * *"),
+    TypeError,
+    "Deno.cron requires a handler",
+  );
+  assertThrows(
+    // @ts-ignore test
+    () => Deno.cron("name", "* * * * *", { overlap: "never" }, () => {}),
+    TypeError,
+    "Invalid overlap policy 'never'",
+  );
+  assertThrows(
+    () => Deno.cron("invalid/name", "* * * * *", () => {}),
+    TypeError,
+    "Cron name must only contain alphanumeric characters",
+  );
+  assertThrows(
+    () => Deno.cron("a".repeat(65), "* * * * *", () => {}),
+    TypeError,
+    "Cron name cannot exceed 64 characters",
+  );
+  assertThrows(
+    () => Deno.cron("name", "* * * *", () => {}),
+    TypeError,
+    "Invalid cron schedule: '* * * *'",
+  );
+  assertThrows(
+    () => Deno.cron("name", "61 * * * *", () => {}),
+    TypeError,
+    "Invalid cron schedule",
+  );
+  assertThrows(
+    () => Deno.cron("name", "0 0 30 FEB *", () => {}),
+    TypeError,
+    "Cron schedule never matches",
+  );
+});
+
+Deno.test(async function cronDuplicateName() {
+  const ac = new AbortController();
+  const first = Deno.cron(
+    "duplicate",
+    "@hourly",
+    { signal: ac.signal },
+    () => {},
+  );
+  assertThrows(
+    () => Deno.cron("duplicate", "@daily", () => {}),
+    TypeError,
+    "Cron with name 'duplicate' already exists",
+  );
+  ac.abort();
+  await first;
+
+  // The name is free again once the cron was unregistered.
+  const ac2 = new AbortController();
+  const second = Deno.cron(
+    "duplicate",
+    "@daily",
+    { signal: ac2.signal },
+    () => {},
+  );
+  ac2.abort();
+  await second;
+});
+
+Deno.test(async function cronAbortedSignal() {
+  const ac = new AbortController();
+  ac.abort();
+  await Deno.cron("aborted", "* * * * *", { signal: ac.signal }, () => {});
+});
@@ -3,6 +3,7 @@ import {
   assert,
   assertEquals,
   assertRejects,
+  assertThrows,
   deferred,
   delay,
   fail,
@@ -1134,6 +1135,31 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true, read: true } },
+  async function fetchCustomHttpClientUpdateTls() {
+    const client = Deno.createHttpClient({});
+    await assertRejects(
+      () => fetch("https://localhost:5545/assets/fixture.json", { client }),
+      TypeError,
+    );
+
+    const caCert = Deno.readTextFileSync("cli/tests/testdata/tls/RootCA.pem");
+    client.updateTls({ caCerts: [caCert] });
+    const response = await fetch("https://localhost:5545/assets/fixture.json", {
+      client,
+    });
+    const json = await response.json();
+    assertEquals(json.name, "deno");
+
+    assertThrows(
+      () => client.updateTls({ crl: ["not a crl"] }),
+      Deno.errors.InvalidData,
+    );
+    client.close();
+  },
+);
+
 Deno.test(
   { permissions: { net: true } },
   async function fetchCustomClientUserAgent(): Promise<
@@ -1947,3 +1973,206 @@ Deno.test("Response with subarray TypedArray body", async () => {
   const expected = new Uint8Array([2, 3, 4, 5]);
   assertEquals(actual, expected);
 });
+
+async function readRequestHead(conn: Deno.Conn): Promise<string> {
+  const decoder = new TextDecoder();
+  const buf = new Uint8Array(1024);
+  let head = "";
+  while (!head.includes("\r\n\r\n")) {
+    const n = await conn.read(buf);
+    if (n === null) break;
+    head += decoder.decode(buf.subarray(0, n));
+  }
+  return head;
+}
+
+const okResponse = new TextEncoder().encode(
+  "HTTP/1.1 200 OK\r\nContent-Length: 7\r\nConnection: close\r\n\r\nproxied",
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchWithPerRequestHttpProxy() {
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: listenPort });
+    const server = (async () => {
+      const conn = await listener.accept();
+      const head = await readRequestHead(conn);
+      // Requests to a proxy use the absolute form of the request target.
+      assert(head.startsWith("GET http://example.invalid/path HTTP/1.1\r\n"));
+      assert(head.includes("proxy-authorization: Basic dXNlcjpwYXNz\r\n"));
+      await conn.write(okResponse);
+      conn.close();
+    })();
+
+    const client = Deno.createHttpClient({});
+    const response = await fetch("http://example.invalid/path", {
+      client,
+      proxy: {
+        url: `http://127.0.0.1:${listenPort}`,
+        basicAuth: { username: "user", password: "pass" },
+      },
+    });
+    assertEquals(await response.text(), "proxied");
+    await server;
+    listener.close();
+    client.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchWithPerRequestSocks5Proxy() {
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: listenPort });
+    const server = (async () => {
+      const conn = await listener.accept();
+      const buf = new Uint8Array(512);
+      // Greeting, offering the "no authentication" method.
+      let n = await conn.read(buf);
+      assertEquals(buf.subarray(0, 2), new Uint8Array([5, n! - 2]));
+      assert(buf.subarray(2, n!).includes(0));
+      await conn.write(new Uint8Array([5, 0]));
+      // CONNECT to a host name, as socks5h resolves them on the proxy.
+      n = await conn.read(buf);
+      assertEquals(buf.subarray(0, 4), new Uint8Array([5, 1, 0, 3]));
+      const host = new TextDecoder().decode(buf.subarray(5, 5 + buf[4]));
+      assertEquals(host, "example.invalid");
+      assertEquals(buf[5 + buf[4]] << 8 | buf[6 + buf[4]], 80);
+      assertEquals(n, 7 + buf[4]);
+      await conn.write(new Uint8Array([5, 0, 0, 1, 0, 0, 0, 0, 0, 0]));
+      const head = await readRequestHead(conn);
+      assert(head.startsWith("GET /path HTTP/1.1\r\n"));
+      await conn.write(okResponse);
+      conn.close();
+    })();
+
+    const response = await fetch("http://example.invalid/path", {
+      proxy: { url: `socks5h://127.0.0.1:${listenPort}` },
+    });
+    assertEquals(await response.text(), "proxied");
+    await server;
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchWithUnsupportedProxyScheme() {
+    await assertRejects(
+      () =>
+        fetch("http://localhost:4545/", {
+          proxy: { url: "ftp://localhost:21" },
+        }),
+      TypeError,
+      "Unsupported proxy scheme: ftp",
+    );
+    assertThrows(
+      () =>
+        Deno.createHttpClient({ proxy: { url: "socks4://localhost:1080" } }),
+      TypeError,
+      "Unsupported proxy scheme: socks4",
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchCustomClientCookieJar() {
+    const ac = new AbortController();
+    const listening = deferred();
+    const server = Deno.serve({
+      hostname: "127.0.0.1",
+      port: listenPort,
+      signal: ac.signal,
+      onListen: () => listening.resolve(),
+      onError: () => fail(),
+    }, (req) => {
+      const { pathname } = new URL(req.url);
+      if (pathname === "/login") {
+        const headers = new Headers({ location: "/account" });
+        headers.append("set-cookie", "session=abc; Path=/; HttpOnly");
+        headers.append("set-cookie", "login=1; Path=/login");
+        return new Response(null, { status: 302, headers });
+      }
+      return new Response(req.headers.get("cookie"));
+    });
+    await listening;
+
+    const cookieJar = new Deno.CookieJar();
+    const client = Deno.createHttpClient({ cookieJar });
+    const base = `http://127.0.0.1:${listenPort}`;
+    // The cookie set by the redirect is sent to its target.
+    let response = await fetch(`${base}/login`, { client });
+    assertEquals(await response.text(), "session=abc");
+    response = await fetch(`${base}/other`, {
+      client,
+      headers: { cookie: "extra=1" },
+    });
+    assertEquals(await response.text(), "extra=1; session=abc");
+    // Clients without the jar don't send its cookies.
+    response = await fetch(`${base}/other`);
+    assertEquals(await response.text(), "");
+
+    const cookies = cookieJar.toJSON();
+    assertEquals(cookies.map((c) => c.name), ["session", "login"]);
+    assertEquals(cookies[0].domain, "127.0.0.1");
+    assertEquals(cookies[0].httpOnly, true);
+    assertEquals(cookieJar.getCookies(`${base}/login`).map((c) => c.name), [
+      "login",
+      "session",
+    ]);
+
+    client.close();
+    cookieJar.close();
+    ac.abort();
+    await server.finished;
+  },
+);
+
+Deno.test(function cookieJarSetCookieAndRestore() {
+  const cookieJar = new Deno.CookieJar();
+  cookieJar.setCookie(
+    "https://www.example.com/",
+    "a=1; Domain=example.com; Secure",
+  );
+  assertThrows(
+    () => cookieJar.setCookie("https://example.com/", "b=2; Domain=other.com"),
+    TypeError,
+    "Invalid cookie for the given URL",
+  );
+  const restored = new Deno.CookieJar(
+    JSON.parse(JSON.stringify(cookieJar)),
+  );
+  assertEquals(restored.getCookies("https://api.example.com/x").length, 1);
+  assertEquals(restored.getCookies("http://api.example.com/x").length, 0);
+  restored.clear();
+  assertEquals(restored.getCookies(), []);
+  cookieJar.close();
+  restored.close();
+});
+
+Deno.test(
+  { permissions: { net: true } },
+  async function fetchCustomClientPoolMetrics() {
+    const client = Deno.createHttpClient({ poolMaxIdlePerHost: 1 });
+    assertEquals(client.poolMetrics(), {
+      requests: 0,
+      inFlight: 0,
+      newConnections: 0,
+      reusedConnections: 0,
+    });
+    for (let i = 0; i < 3; i++) {
+      const response = await fetch(
+        "http://localhost:4545/assets/fixture.json",
+        { client },
+      );
+      await response.text();
+    }
+    assertEquals(client.poolMetrics(), {
+      requests: 3,
+      inFlight: 0,
+      newConnections: 1,
+      reusedConnections: 2,
+    });
+    client.close();
+  },
+);
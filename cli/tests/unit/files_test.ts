@@ -809,6 +809,65 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function readableStreamPipeToFile() {
+    const data = new Uint8Array(1024 * 1024);
+    for (let i = 0; i < data.length; i++) {
+      data[i] = i % 251;
+    }
+    const srcPath = await Deno.makeTempFile();
+    await Deno.writeFile(srcPath, data);
+    const dstPath = await Deno.makeTempFile();
+
+    const src = await Deno.open(srcPath);
+    const dst = await Deno.open(dstPath, { write: true });
+    await src.readable.pipeTo(dst.writable);
+    assertEquals(await Deno.readFile(dstPath), data);
+    // Both files were closed when the pipe finished.
+    assertThrows(() => src.close(), Deno.errors.BadResource);
+    assertThrows(() => dst.close(), Deno.errors.BadResource);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function readableStreamPipeToFilePreventClose() {
+    const srcPath = await Deno.makeTempFile();
+    await Deno.writeTextFile(srcPath, "hello ");
+    const dstPath = await Deno.makeTempFile();
+
+    const src = await Deno.open(srcPath);
+    const dst = await Deno.open(dstPath, { write: true });
+    await src.readable.pipeTo(dst.writable, { preventClose: true });
+    const writer = dst.writable.getWriter();
+    await writer.write(new TextEncoder().encode("world!"));
+    await writer.close();
+    assertEquals(await Deno.readTextFile(dstPath), "hello world!");
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function readableStreamPipeToFileAborted() {
+    const srcPath = await Deno.makeTempFile();
+    await Deno.writeTextFile(srcPath, "hello world!");
+    const dstPath = await Deno.makeTempFile();
+
+    const src = await Deno.open(srcPath);
+    const dst = await Deno.open(dstPath, { write: true });
+    const controller = new AbortController();
+    const reason = new Error("aborted");
+    controller.abort(reason);
+    await assertRejects(
+      () => src.readable.pipeTo(dst.writable, { signal: controller.signal }),
+      Error,
+      "aborted",
+    );
+    assertEquals(await Deno.readTextFile(dstPath), "");
+  },
+);
+
 Deno.test(
   { permissions: { read: true, write: true } },
   async function readTextFileNonUtf8() {
@@ -107,3 +107,69 @@ Deno.test(
     assertEquals(events, []);
   },
 );
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function watchFsIncludeExclude() {
+    const testDir = await makeTempDir();
+    const iter = Deno.watchFs(testDir, {
+      include: ["*.ts"],
+      exclude: ["excluded.ts"],
+    });
+
+    const eventsPromise = getTwoEvents(iter);
+
+    Deno.writeFileSync(testDir + "/file.txt", new Uint8Array([0, 1, 2]));
+    Deno.writeFileSync(testDir + "/excluded.ts", new Uint8Array([0, 1, 2]));
+    Deno.writeFileSync(testDir + "/file1.ts", new Uint8Array([0, 1, 2]));
+    Deno.writeFileSync(testDir + "/file2.ts", new Uint8Array([0, 1, 2]));
+
+    const events = await eventsPromise;
+    for (const event of events) {
+      for (const path of event.paths) {
+        assert(path.endsWith("file1.ts") || path.endsWith("file2.ts"));
+      }
+    }
+    iter.close();
+  },
+);
+
+Deno.test(
+  { permissions: { read: true } },
+  function watchFsInvalidGlob() {
+    assertThrows(
+      () => {
+        Deno.watchFs(".", { include: ["[*.ts"] });
+      },
+      TypeError,
+      'Invalid glob pattern "[*.ts"',
+    );
+  },
+);
+
+Deno.test(
+  {
+    ignore: Deno.build.os !== "linux",
+    permissions: { read: true, write: true },
+  },
+  async function watchFsCoalesceRenames() {
+    const testDir = await makeTempDir();
+    const oldPath = testDir + "/old.txt";
+    const newPath = testDir + "/new.txt";
+    Deno.writeFileSync(oldPath, new Uint8Array([0, 1, 2]));
+    await delay(100);
+    const iter = Deno.watchFs(testDir, {
+      debounce: 100,
+      coalesceRenames: true,
+    });
+
+    Deno.renameSync(oldPath, newPath);
+
+    for await (const event of iter) {
+      assertEquals(event.kind, "rename");
+      assertEquals(event.paths, [oldPath, newPath]);
+      break;
+    }
+    iter.close();
+  },
+);
@@ -1525,6 +1525,56 @@ queueTest("queue retries", async (db) => {
   assertEquals(4, count);
 });
 
+queueTest("queue retries with custom backoff schedule", async (db) => {
+  let count = 0;
+  const listener = db.listenQueue((_msg) => {
+    count += 1;
+    throw new TypeError("dequeue error");
+  });
+  try {
+    await db.enqueue("test", { backoffSchedule: [10, 20] });
+    await sleep(2000);
+  } finally {
+    db.close();
+    await listener;
+  }
+
+  // There should have been 1 attempt + 2 retries
+  assertEquals(3, count);
+});
+
+queueTest("queue empty backoff schedule", async (db) => {
+  let count = 0;
+  const listener = db.listenQueue((_msg) => {
+    count += 1;
+    throw new TypeError("dequeue error");
+  });
+  try {
+    await db.atomic().enqueue("test", { backoffSchedule: [] }).commit();
+    await sleep(1000);
+  } finally {
+    db.close();
+    await listener;
+  }
+
+  assertEquals(1, count);
+});
+
+dbTest("queue invalid backoff schedule", async (db) => {
+  await assertRejects(async () => {
+    await db.enqueue("test", { backoffSchedule: [1, 1, 1, 1, 1, 1] });
+  }, TypeError, "backoffSchedule cannot have more than 5 intervals");
+  await assertRejects(async () => {
+    await db.enqueue("test", { backoffSchedule: [-1] });
+  }, TypeError, "backoffSchedule intervals must be non-negative integers");
+  await assertRejects(async () => {
+    await db.enqueue("test", { backoffSchedule: [60 * 60 * 1000 + 1] });
+  }, TypeError, "backoffSchedule interval cannot be greater than one hour");
+  assertThrows(() => {
+    db.atomic().enqueue("test", { backoffSchedule: [0.5] });
+  }, TypeError, "backoffSchedule intervals must be non-negative integers");
+});
+
 queueTest("multiple listenQueues", async (db) => {
   const numListens = 10;
   let count = 0;
@@ -1749,3 +1799,79 @@ Deno.test({
     }
   },
 });
+
+dbTest("watch yields the current entries and their changes", async (db) => {
+  await db.set(["a"], 1);
+  const stream = db.watch([["a"], ["b"]]);
+  const reader = stream.getReader();
+
+  let { value } = await reader.read();
+  assertEquals(value![0].value, 1);
+  assertEquals(value![1].versionstamp, null);
+
+  await db.set(["b"], 2);
+  ({ value } = await reader.read());
+  assertEquals(value![0].value, 1);
+  assertEquals(value![1].value, 2);
+
+  await db.delete(["a"]);
+  ({ value } = await reader.read());
+  assertEquals(value![0], { key: ["a"], value: null, versionstamp: null });
+  assertEquals(value![1].value, 2);
+
+  // Writes to other keys don't yield.
+  await db.set(["c"], 3);
+  await db.set(["a"], 4);
+  ({ value } = await reader.read());
+  assertEquals(value![0].value, 4);
+
+  await reader.cancel();
+});
+
+Deno.test({
+  name: "watch ends when the database is closed",
+  // https://github.com/denoland/deno/issues/18363
+  ignore: Deno.build.os === "darwin" && isCI,
+  async fn() {
+    const db = await Deno.openKv(":memory:");
+    const reader = db.watch([["a"]]).getReader();
+    await reader.read();
+    const next = reader.read();
+    db.close();
+    assertEquals((await next).done, true);
+  },
+});
+
+dbTest("watch too many keys", async (db) => {
+  assertThrows(
+    () => db.watch(Array.from({ length: 11 }, (_, i) => [i])),
+    TypeError,
+    "too many keys (max 10)",
+  );
+});
+
+Deno.test({
+  name: "watch notices writes through other connections",
+  // https://github.com/denoland/deno/issues/18363
+  ignore: Deno.build.os === "darwin" && isCI,
+  permissions: { read: true, write: true },
+  async fn() {
+    const filename = await Deno.makeTempFile({ prefix: "kv_watch_db" });
+    try {
+      const watching = await Deno.openKv(filename);
+      const writing = await Deno.openKv(filename);
+      const reader = watching.watch([["a"]]).getReader();
+      await reader.read();
+
+      await writing.set(["a"], "from another connection");
+      const { value } = await reader.read();
+      assertEquals(value![0].value, "from another connection");
+
+      await reader.cancel();
+      writing.close();
+      watching.close();
+    } finally {
+      await Deno.remove(filename);
+    }
+  },
+});
@@ -286,6 +286,81 @@ Deno.test({ permissions: { net: true } }, async function netTcpSetNoDelay() {
   conn.close();
 });
 
+Deno.test(
+  { permissions: { net: true } },
+  async function netTcpConnectHappyEyeballsOptions() {
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: listenPort });
+    const options = [
+      { happyEyeballs: false },
+      { happyEyeballsDelay: 10 },
+    ];
+    for (const option of options) {
+      const acceptPromise = listener.accept();
+      const conn = await Deno.connect({
+        hostname: "localhost",
+        port: listenPort,
+        ...option,
+      });
+      assertEquals(conn.remoteAddr.hostname, "127.0.0.1");
+      (await acceptPromise).close();
+      conn.close();
+    }
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function netResolverInvalidConfig() {
+    await assertRejects(
+      () =>
+        Deno.resolveDns("example.com", "A", {
+          resolver: { nameServers: [{ ipAddr: "not an ip" }] },
+        }),
+      TypeError,
+      "Invalid name server address",
+    );
+    await assertRejects(
+      () =>
+        Deno.connect({
+          hostname: "example.com",
+          port: 443,
+          resolver: { protocol: "https" },
+        }),
+      TypeError,
+      "Name servers must be specified for DNS-over-HTTPS",
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function netTcpBufferSizes() {
+    const listener = Deno.listen({
+      port: listenPort,
+      sendBufferSize: 64 * 1024,
+      recvBufferSize: 64 * 1024,
+    });
+    const acceptPromise = listener.accept();
+    const conn = await Deno.connect({
+      hostname: "127.0.0.1",
+      port: listenPort,
+    });
+    const accepted = await acceptPromise as Deno.TcpConn;
+    // The kernel may round or double the requested sizes.
+    assert(accepted.recvBufferSize() >= 64 * 1024);
+
+    conn.setSendBufferSize(32 * 1024);
+    conn.setRecvBufferSize(32 * 1024);
+    assert(conn.sendBufferSize() >= 32 * 1024);
+    assert(conn.recvBufferSize() >= 32 * 1024);
+
+    accepted.close();
+    conn.close();
+    listener.close();
+  },
+);
+
 Deno.test({ permissions: { net: true } }, async function netTcpSetKeepAlive() {
   const listener = Deno.listen({ port: listenPort });
   listener.accept().then(
@@ -469,11 +544,34 @@ Deno.test(
 
     membership.setLoopback(true);
     membership.setLoopback(false);
+    membership.setTTL(50);
     membership.leave();
     listener.close();
   },
 );
 
+Deno.test(
+  { permissions: { net: true } },
+  async function netUdpSocketOptions() {
+    const socket = Deno.listenDatagram({
+      port: listenPort,
+      transport: "udp",
+      broadcast: false,
+    });
+
+    await socket.setBroadcast(true);
+    await socket.setBroadcast(false);
+    await socket.setMulticastLoopback(true);
+    await socket.setMulticastTTL(1);
+    socket.close();
+
+    await assertRejects(
+      () => socket.setBroadcast(true),
+      Deno.errors.BadResource,
+    );
+  },
+);
+
 Deno.test(
   { permissions: { net: true }, ignore: true },
   async function netUdpSendReceiveMulticastv4() {
@@ -6,6 +6,7 @@ import { TextProtoReader } from "../testdata/run/textproto.ts";
 import {
   assert,
   assertEquals,
+  assertRejects,
   assertStringIncludes,
   assertThrows,
   Deferred,
@@ -62,6 +63,63 @@ Deno.test(async function httpServerShutsDownPortBeforeResolving() {
   listener!.close();
 });
 
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerGracefulShutdown() {
+    const listeningPromise = deferred();
+    const requestPromise = deferred();
+    const responsePromise = deferred<Response>();
+
+    const server = Deno.serve({
+      handler: async (_req) => {
+        requestPromise.resolve();
+        return await responsePromise;
+      },
+      port: servePort,
+      onListen: onListen(listeningPromise),
+    });
+    await listeningPromise;
+
+    const fetchPromise = fetch(`http://127.0.0.1:${servePort}/`);
+    await requestPromise;
+    const shutdownPromise = server.shutdown();
+
+    // The in-flight request completes, on a connection that's then closed.
+    responsePromise.resolve(new Response("ok"));
+    const resp = await fetchPromise;
+    assertEquals(await resp.text(), "ok");
+    assertEquals(resp.headers.get("connection"), "close");
+    await shutdownPromise;
+
+    const listener = Deno.listen({ port: servePort });
+    listener.close();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerGracefulShutdownGracePeriod() {
+    const listeningPromise = deferred();
+    const requestPromise = deferred();
+
+    const server = Deno.serve({
+      handler: (_req) => {
+        requestPromise.resolve();
+        // Never responds
+        return new Promise<Response>(() => {});
+      },
+      port: servePort,
+      onListen: onListen(listeningPromise),
+    });
+    await listeningPromise;
+
+    const fetchPromise = fetch(`http://127.0.0.1:${servePort}/`);
+    await requestPromise;
+    await server.shutdown({ gracePeriodMs: 10 });
+    await assertRejects(() => fetchPromise, TypeError);
+  },
+);
+
 Deno.test(
   { permissions: { read: true, run: true } },
   async function httpServerUnref() {
@@ -2270,6 +2328,106 @@ for (const testCase of compressionTestCases) {
   );
 }
 
+// fetch() transparently decompresses gzip and brotli, but not zstd, so zstd
+// lets us observe how a response was encoded.
+async function serveCompressed(
+  compression: boolean | Deno.ServeCompressionOptions | undefined,
+  acceptEncoding: string,
+  contentType: string,
+  length: number,
+) {
+  const ac = new AbortController();
+  const listeningPromise = deferred();
+  const server = Deno.serve({
+    handler: () =>
+      new Response("a".repeat(length), {
+        headers: { "content-type": contentType },
+      }),
+    port: servePort,
+    signal: ac.signal,
+    compression,
+    onListen: onListen(listeningPromise),
+    onError: createOnErrorCb(ac),
+  });
+  try {
+    await listeningPromise;
+    const resp = await fetch(`http://127.0.0.1:${servePort}/`, {
+      headers: { "accept-encoding": acceptEncoding },
+    });
+    await resp.arrayBuffer();
+    return resp.headers.get("content-encoding");
+  } finally {
+    ac.abort();
+    await server.finished;
+  }
+}
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerCompressionZstd() {
+    assertEquals(
+      await serveCompressed(undefined, "zstd", "text/plain", 1024),
+      "zstd",
+    );
+    assertEquals(
+      await serveCompressed(undefined, "br;q=0.5, zstd", "text/plain", 1024),
+      "zstd",
+    );
+    assertEquals(
+      await serveCompressed(undefined, "zstd;q=0", "text/plain", 1024),
+      null,
+    );
+    assertEquals(
+      await serveCompressed(
+        { encodings: ["gzip"] },
+        "zstd",
+        "text/plain",
+        1024,
+      ),
+      null,
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerCompressionOptions() {
+    assertEquals(
+      await serveCompressed(false, "zstd", "text/plain", 1024),
+      null,
+    );
+    assertEquals(
+      await serveCompressed({ minSize: 2048 }, "zstd", "text/plain", 1024),
+      null,
+    );
+    assertEquals(
+      await serveCompressed({ minSize: 2048 }, "zstd", "text/plain", 4096),
+      "zstd",
+    );
+    const contentTypes = ["application/x-custom", "image/*"];
+    for (
+      const [contentType, expected] of [
+        ["application/x-custom; charset=utf-8", "zstd"],
+        ["image/svg+xml", "zstd"],
+        ["text/plain", null],
+      ]
+    ) {
+      assertEquals(
+        await serveCompressed({ contentTypes }, "zstd", contentType!, 1024),
+        expected,
+      );
+    }
+    assertThrows(
+      () =>
+        Deno.serve(
+          { port: servePort, compression: { zstdLevel: 100 } },
+          () => new Response(),
+        ),
+      RangeError,
+    );
+  },
+);
+
 Deno.test(
   { permissions: { net: true, write: true, read: true } },
   async function httpServerPostFile() {
@@ -2860,6 +3018,135 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true, read: true, write: true } },
+  async function httpServerFileResponseRange() {
+    const tmpFile = await Deno.makeTempFile();
+    await Deno.writeTextFile(tmpFile, "0123456789".repeat(10));
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const server = Deno.serve({
+      handler: async () => {
+        const file = await Deno.open(tmpFile);
+        return new Response(file.readable);
+      },
+      port: 4501,
+      signal: ac.signal,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+    });
+
+    try {
+      await listeningPromise;
+      const url = "http://127.0.0.1:4501/";
+
+      const full = await fetch(url);
+      assertEquals(full.status, 200);
+      assertEquals(full.headers.get("accept-ranges"), "bytes");
+      assertEquals(full.headers.get("content-length"), "100");
+      assertEquals((await full.text()).length, 100);
+
+      const partial = await fetch(url, { headers: { range: "bytes=5-14" } });
+      assertEquals(partial.status, 206);
+      assertEquals(partial.headers.get("content-range"), "bytes 5-14/100");
+      assertEquals(partial.headers.get("content-length"), "10");
+      assertEquals(await partial.text(), "5678901234");
+
+      const suffix = await fetch(url, { headers: { range: "bytes=-3" } });
+      assertEquals(suffix.status, 206);
+      assertEquals(await suffix.text(), "789");
+
+      const unsatisfiable = await fetch(url, {
+        headers: { range: "bytes=100-" },
+      });
+      assertEquals(unsatisfiable.status, 416);
+      assertEquals(unsatisfiable.headers.get("content-range"), "bytes */100");
+      assertEquals(await unsatisfiable.text(), "");
+    } finally {
+      ac.abort();
+      await server.finished;
+      await Deno.remove(tmpFile);
+    }
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerServerSentEvents() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const server = Deno.serve({
+      handler: (_req) => {
+        const stream = new Deno.ServerSentEventStream({ keepAlive: false });
+        (async () => {
+          await stream.send("hello");
+          await stream.send({ event: "update", id: 2, data: "a\nb" });
+          stream.close();
+        })();
+        return stream.toResponse();
+      },
+      port: servePort,
+      signal: ac.signal,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+    });
+
+    try {
+      await listeningPromise;
+      const resp = await fetch(`http://127.0.0.1:${servePort}/`);
+      assertEquals(resp.headers.get("content-type"), "text/event-stream");
+      assertEquals(resp.headers.get("cache-control"), "no-cache");
+      assertEquals(
+        await resp.text(),
+        "data: hello\n\nevent: update\nid: 2\ndata: a\ndata: b\n\n",
+      );
+    } finally {
+      ac.abort();
+      await server.finished;
+    }
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function httpServerServerSentEventsClientDisconnect() {
+    const ac = new AbortController();
+    const listeningPromise = deferred();
+    const streamClosed = deferred();
+    const server = Deno.serve({
+      handler: (_req) => {
+        const stream = new Deno.ServerSentEventStream({ keepAlive: 10 });
+        stream.closed.then(async () => {
+          await assertRejects(
+            () => stream.send("too late"),
+            Deno.errors.BrokenPipe,
+          );
+          streamClosed.resolve();
+        });
+        return stream.toResponse();
+      },
+      port: servePort,
+      signal: ac.signal,
+      onListen: onListen(listeningPromise),
+      onError: createOnErrorCb(ac),
+    });
+
+    try {
+      await listeningPromise;
+      const resp = await fetch(`http://127.0.0.1:${servePort}/`);
+      const reader = resp.body!.getReader();
+      // Without events, keep-alive comments are sent.
+      const { value } = await reader.read();
+      assertEquals(new TextDecoder().decode(value), ":\n\n");
+      await reader.cancel();
+      await streamClosed;
+    } finally {
+      ac.abort();
+      await server.finished;
+    }
+  },
+);
+
 function chunkedBodyReader(h: Headers, r: BufReader): Deno.Reader {
   // Based on https://tools.ietf.org/html/rfc2616#section-19.4.6
   const tp = new TextProtoReader(r);
@@ -3098,6 +3385,70 @@ Deno.test(
   },
 );
 
+Deno.test(
+  {
+    ignore: Deno.build.os === "windows",
+    permissions: { read: true, write: true },
+  },
+  async function httpServerUnixSocket() {
+    const path = Deno.makeTempDirSync() + "/server.sock";
+    const ac = new AbortController();
+    const listeningPromise = deferred<string>();
+
+    const server = Deno.serve({
+      handler: (request) => {
+        const { pathname, search } = new URL(request.url);
+        return new Response(`${request.method} ${pathname}${search}`, {
+          headers: { "x-host": request.headers.get("host")! },
+        });
+      },
+      path,
+      signal: ac.signal,
+      onListen: ({ path }) => listeningPromise.resolve(path),
+      onError: createOnErrorCb(ac),
+    });
+
+    assertEquals(await listeningPromise, path);
+    const client = Deno.createHttpClient({
+      proxy: { transport: "unix", path },
+    });
+    const resp = await fetch("http://localhost:4545/path?q=1", {
+      method: "POST",
+      body: "hello",
+      client,
+    });
+    assertEquals(resp.status, 200);
+    assertEquals(resp.headers.get("x-host"), "localhost:4545");
+    assertEquals(await resp.text(), "POST /path?q=1");
+    await assertRejects(
+      () => fetch("https://localhost/", { client }),
+      TypeError,
+      "HTTPS is not supported over unix sockets.",
+    );
+    client.close();
+
+    ac.abort();
+    await server.finished;
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  function httpServerUnixSocketHttps() {
+    assertThrows(
+      () =>
+        Deno.serve({
+          handler: () => new Response(),
+          path: "/tmp/server.sock",
+          cert: "cert",
+          key: "key",
+        }),
+      TypeError,
+      "HTTPS is not supported on unix sockets.",
+    );
+  },
+);
+
 async function curlRequest(args: string[]) {
   const { success, stdout } = await new Deno.Command("curl", {
     args,
@@ -5,7 +5,7 @@ Deno.test(
   { ignore: Deno.build.os !== "windows" },
   function signalsNotImplemented() {
     const msg =
-      "Windows only supports ctrl-c (SIGINT) and ctrl-break (SIGBREAK).";
+      "Windows only supports ctrl-c (SIGINT), ctrl-break (SIGBREAK) and ctrl-close (SIGHUP).";
     assertThrows(
       () => {
         Deno.addSignalListener("SIGALRM", () => {});
@@ -20,13 +20,6 @@ Deno.test(
       Error,
       msg,
     );
-    assertThrows(
-      () => {
-        Deno.addSignalListener("SIGHUP", () => {});
-      },
-      Error,
-      msg,
-    );
     assertThrows(
       () => {
         Deno.addSignalListener("SIGIO", () => {});
@@ -239,6 +232,47 @@ Deno.test(
   },
 );
 
+Deno.test(
+  {
+    ignore: Deno.build.os !== "windows",
+    permissions: { run: true, read: true },
+  },
+  async function windowsKillChildWithCtrlBreak() {
+    const child = new Deno.Command(Deno.execPath(), {
+      args: [
+        "eval",
+        `Deno.addSignalListener("SIGBREAK", () => {
+          console.log("break");
+          Deno.exit(0);
+        });
+        console.log("ready");
+        setTimeout(() => {}, 10_000);`,
+      ],
+      newProcessGroup: true,
+      stdout: "piped",
+      stderr: "null",
+    }).spawn();
+    const reader = child.stdout.getReader();
+    const decoder = new TextDecoder();
+    let output = "";
+    while (!output.includes("ready")) {
+      const { value } = await reader.read();
+      output += decoder.decode(value);
+    }
+    child.kill("SIGBREAK");
+    while (!output.includes("break")) {
+      const { value, done } = await reader.read();
+      if (done) break;
+      output += decoder.decode(value);
+    }
+    reader.releaseLock();
+    await child.stdout.cancel();
+    const { code } = await child.status;
+    assertEquals(code, 0);
+    assertEquals(output.includes("break"), true);
+  },
+);
+
 Deno.test(function signalInvalidHandlerTest() {
   assertThrows(() => {
     // deno-lint-ignore no-explicit-any
@@ -287,6 +287,42 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { read: false, net: true } },
+  async function listenTlsWithSniCertificates() {
+    const port = 3499;
+    const listener = Deno.listenTls({
+      hostname: "localhost",
+      port,
+      sni: { "LOCALHOST": { cert, key } },
+    });
+    const acceptPromise = listener.accept().then(async (conn) => {
+      await (conn as Deno.TlsConn).handshake();
+      conn.close();
+    });
+    const conn = await Deno.connectTls({ hostname: "localhost", port, caCerts });
+    await conn.handshake();
+    conn.close();
+    await acceptPromise;
+
+    // Without a default certificate, clients requesting an unknown server
+    // name can't complete the handshake.
+    const rejectedPromise = listener.accept().then(async (conn) => {
+      await assertRejects(() => (conn as Deno.TlsConn).handshake());
+      conn.close();
+    });
+    const ipConn = await Deno.connectTls({
+      hostname: "127.0.0.1",
+      port,
+      caCerts,
+    });
+    await assertRejects(() => ipConn.handshake());
+    ipConn.close();
+    await rejectedPromise;
+    listener.close();
+  },
+);
+
 let nextPort = 3501;
 function getPort() {
   return nextPort++;
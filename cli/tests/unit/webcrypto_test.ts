@@ -2006,3 +2006,116 @@ Deno.test(async function testHmacJwkImport() {
     ["sign", "verify"],
   );
 });
+
+Deno.test(async function testSecp256k1EcdsaSignVerify() {
+  const alg = { name: "ECDSA", namedCurve: "K-256" };
+  const { privateKey, publicKey } = await crypto.subtle.generateKey(
+    alg,
+    true,
+    ["sign", "verify"],
+  ) as CryptoKeyPair;
+  const data = new TextEncoder().encode("hello world");
+  const signature = await crypto.subtle.sign(
+    { name: "ECDSA", hash: "SHA-256" },
+    privateKey,
+    data,
+  );
+  assert(
+    await crypto.subtle.verify(
+      { name: "ECDSA", hash: "SHA-256" },
+      publicKey,
+      signature,
+      data,
+    ),
+  );
+
+  await assertRejects(
+    () =>
+      crypto.subtle.sign({ name: "ECDSA", hash: "SHA-384" }, privateKey, data),
+    DOMException,
+  );
+
+  const jwk = await crypto.subtle.exportKey("jwk", publicKey);
+  assertEquals(jwk.crv, "secp256k1");
+  assertEquals(jwk.alg, "ES256K");
+  const imported = await crypto.subtle.importKey("jwk", jwk, alg, true, [
+    "verify",
+  ]);
+  assert(
+    await crypto.subtle.verify(
+      { name: "ECDSA", hash: "SHA-256" },
+      imported,
+      signature,
+      data,
+    ),
+  );
+});
+
+Deno.test(async function testEd448Unsupported() {
+  await assertRejects(
+    () => crypto.subtle.generateKey("Ed448", true, ["sign", "verify"]),
+    DOMException,
+    "Unrecognized algorithm name",
+  );
+});
+
+Deno.test(async function testX25519AndX448DeriveBits() {
+  for (const [name, length] of [["X25519", 256], ["X448", 448]] as const) {
+    const alice = await crypto.subtle.generateKey({ name }, true, [
+      "deriveBits",
+    ]) as CryptoKeyPair;
+    const bob = await crypto.subtle.generateKey({ name }, true, [
+      "deriveBits",
+    ]) as CryptoKeyPair;
+
+    const aliceBits = await crypto.subtle.deriveBits(
+      { name, public: bob.publicKey },
+      alice.privateKey,
+      length,
+    );
+    const bobBits = await crypto.subtle.deriveBits(
+      { name, public: alice.publicKey },
+      bob.privateKey,
+      length,
+    );
+    assertEquals(aliceBits.byteLength, length / 8);
+    assertEquals(new Uint8Array(aliceBits), new Uint8Array(bobBits));
+  }
+});
+
+Deno.test(async function testDigestReadableStream() {
+  const chunks = [
+    new TextEncoder().encode("hello "),
+    new TextEncoder().encode("streaming "),
+    new TextEncoder().encode("world"),
+  ];
+  const stream = new ReadableStream<Uint8Array>({
+    start(controller) {
+      for (const chunk of chunks) {
+        controller.enqueue(chunk);
+      }
+      controller.close();
+    },
+  });
+
+  const streamed = await crypto.subtle.digest("SHA-256", stream);
+  const buffered = await crypto.subtle.digest(
+    "SHA-256",
+    new TextEncoder().encode("hello streaming world"),
+  );
+  assertEquals(new Uint8Array(streamed), new Uint8Array(buffered));
+});
+
+Deno.test(async function testDigestReadableStreamInvalidChunk() {
+  const stream = new ReadableStream({
+    start(controller) {
+      controller.enqueue("not a buffer");
+      controller.close();
+    },
+  });
+  await assertRejects(
+    // deno-lint-ignore no-explicit-any
+    () => crypto.subtle.digest("SHA-256", stream as any),
+    TypeError,
+  );
+});
@@ -0,0 +1,70 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+import { assertEquals, assertRejects, assertThrows } from "./test_util.ts";
+
+const name = Deno.build.os === "windows" ? "checksum" : "user.checksum";
+const value = new TextEncoder().encode("abc123");
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  function xattrSyncSuccess() {
+    const path = Deno.makeTempFileSync();
+    assertEquals(Deno.getXattrSync(path, name), null);
+    Deno.setXattrSync(path, name, value);
+    assertEquals(Deno.getXattrSync(path, name), value);
+    assertEquals(Deno.listXattrSync(path), [name]);
+    Deno.removeXattrSync(path, name);
+    assertEquals(Deno.getXattrSync(path, name), null);
+    assertEquals(Deno.listXattrSync(path), []);
+    Deno.removeSync(path);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function xattrSuccess() {
+    const path = await Deno.makeTempFile();
+    assertEquals(await Deno.getXattr(path, name), null);
+    await Deno.setXattr(path, name, value);
+    assertEquals(await Deno.getXattr(path, name), value);
+    assertEquals(await Deno.listXattr(path), [name]);
+    await Deno.removeXattr(path, name);
+    assertEquals(await Deno.getXattr(path, name), null);
+    await Deno.remove(path);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: true } },
+  async function xattrNotFound() {
+    assertThrows(() => {
+      Deno.getXattrSync("/non/existent/file", name);
+    }, Deno.errors.NotFound);
+    await assertRejects(async () => {
+      await Deno.listXattr("/non/existent/file");
+    }, Deno.errors.NotFound);
+  },
+);
+
+Deno.test(
+  { permissions: { read: true, write: false } },
+  function xattrWritePermissionDenied() {
+    assertThrows(() => {
+      Deno.setXattrSync("README.md", name, value);
+    }, Deno.errors.PermissionDenied);
+    assertThrows(() => {
+      Deno.removeXattrSync("README.md", name);
+    }, Deno.errors.PermissionDenied);
+  },
+);
+
+Deno.test(
+  { permissions: { read: false } },
+  async function xattrReadPermissionDenied() {
+    await assertRejects(async () => {
+      await Deno.getXattr("README.md", name);
+    }, Deno.errors.PermissionDenied);
+    assertThrows(() => {
+      Deno.listXattrSync("README.md");
+    }, Deno.errors.PermissionDenied);
+  },
+);
@@ -1,5 +1,12 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
-import { AsyncLocalStorage, AsyncResource } from "node:async_hooks";
+import {
+  AsyncLocalStorage,
+  AsyncResource,
+  createHook,
+  executionAsyncId,
+} from "node:async_hooks";
+import process from "node:process";
+import { clearTimeout, setTimeout as nodeSetTimeout } from "node:timers";
 import {
   assert,
   assertEquals,
@@ -81,3 +88,66 @@ Deno.test(async function nested() {
   assertEquals(await promise, { x: 1 });
   assertEquals(await promise1, null);
 });
+
+Deno.test(async function storePropagatesToTimersAndTicks() {
+  const als = new AsyncLocalStorage();
+  const timeout = deferred();
+  const tick = deferred();
+
+  als.run("store", () => {
+    nodeSetTimeout(() => timeout.resolve(als.getStore()), 1);
+    process.nextTick(() => tick.resolve(als.getStore()));
+  });
+
+  assertEquals(als.getStore(), undefined);
+  assertEquals(await timeout, "store");
+  assertEquals(await tick, "store");
+});
+
+Deno.test(async function createHookEmitsEvents() {
+  const events: string[] = [];
+  const ids = new Set<number>();
+  const hook = createHook({
+    init(asyncId, type) {
+      if (type === "Timeout") {
+        ids.add(asyncId);
+        events.push("init");
+      }
+    },
+    before(asyncId) {
+      if (ids.has(asyncId)) events.push("before");
+    },
+    after(asyncId) {
+      if (ids.has(asyncId)) events.push("after");
+    },
+    destroy(asyncId) {
+      if (ids.has(asyncId)) events.push("destroy");
+    },
+  }).enable();
+
+  const done = deferred<number>();
+  const outerId = executionAsyncId();
+  nodeSetTimeout(() => done.resolve(executionAsyncId()), 1);
+  const innerId = await done;
+  clearTimeout(nodeSetTimeout(() => {}, 1000));
+  hook.disable();
+
+  assert(ids.has(innerId));
+  assert(innerId !== outerId);
+  assertEquals(events, [
+    "init",
+    "before",
+    "after",
+    "destroy",
+    "init",
+    "destroy",
+  ]);
+});
+
+Deno.test(function asyncResourceIds() {
+  const resource = new AsyncResource("test", { triggerAsyncId: 42 });
+  assertEquals(resource.triggerAsyncId(), 42);
+  const asyncId = resource.runInAsyncScope(() => executionAsyncId(), null);
+  assertEquals(asyncId, resource.asyncId());
+  resource.emitDestroy();
+});
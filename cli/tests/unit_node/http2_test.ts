@@ -37,7 +37,7 @@ Deno.test("[node/http2 client]", async () => {
   // Get a session
   const sessionPromise = deferred();
   const session = http2.connect(
-    `localhost:${port}`,
+    `http://localhost:${port}`,
     {},
     sessionPromise.resolve.bind(sessionPromise),
   );
@@ -64,11 +64,18 @@ Deno.test("[node/http2 client]", async () => {
   // Read a response
   const headerPromise = new Promise<Record<string, string | string[]>>((
     resolve,
-  ) => stream.on("headers", resolve));
+  ) => stream.on("response", resolve));
   const headers = await headerPromise;
   assertEquals(headers["resp-header-name"], "resp-header-value");
   assertEquals(headers[HTTP2_HEADER_STATUS], "401");
 
+  stream.setEncoding("utf8");
+  let body = "";
+  stream.on("data", (chunk) => body += chunk);
+  await new Promise((resolve) => stream.on("close", resolve));
+  assertEquals(body, "body");
+
+  await new Promise((resolve) => session.close(resolve));
   ac.abort();
   await server.finished;
 });
@@ -87,12 +94,13 @@ Deno.test("[node/http2 server]", async () => {
   });
 
   const session = await sessionPromise;
-  const stream = await new Promise<http2.ServerHttp2Stream>((resolve) =>
-    session.on("stream", resolve)
-  );
-  const _headers = await new Promise((resolve) =>
-    stream.on("headers", resolve)
+  const [stream, headers] = await new Promise<
+    [http2.ServerHttp2Stream, http2.IncomingHttpHeaders]
+  >((resolve) =>
+    session.on("stream", (stream, headers) => resolve([stream, headers]))
   );
+  assertEquals(headers[HTTP2_HEADER_METHOD], "POST");
+  assertEquals(headers[HTTP2_HEADER_PATH], "/path");
   const _data = await new Promise((resolve) => stream.on("data", resolve));
   const _end = await new Promise((resolve) => stream.on("end", resolve));
   stream.respond();
@@ -102,3 +110,90 @@ Deno.test("[node/http2 server]", async () => {
 
   await new Promise((resolve) => server.close(resolve));
 });
+
+Deno.test("[node/http2 client and server] trailers", async () => {
+  const server = http2.createServer();
+  server.on("stream", (stream, headers) => {
+    let body = "";
+    stream.setEncoding("utf8");
+    stream.on("data", (chunk) => body += chunk);
+    stream.on("end", () => {
+      stream.respond({
+        [HTTP2_HEADER_STATUS]: 200,
+        "content-type": "text/plain",
+      }, { waitForTrailers: true });
+      stream.on("wantTrailers", () => {
+        stream.sendTrailers({ "grpc-status": "0" });
+      });
+      stream.end(`${headers[HTTP2_HEADER_PATH]}: ${body}`);
+    });
+  });
+  server.listen(0);
+  const port = (<net.AddressInfo> server.address()).port;
+
+  const session = http2.connect(`http://localhost:${port}`);
+  const stream = session.request({
+    [HTTP2_HEADER_METHOD]: "POST",
+    [HTTP2_HEADER_PATH]: "/echo",
+  });
+  stream.end("hello");
+
+  const responsePromise = deferred<Record<string, string | string[]>>();
+  const trailersPromise = deferred<Record<string, string | string[]>>();
+  stream.on("response", responsePromise.resolve);
+  stream.on("trailers", trailersPromise.resolve);
+  let body = "";
+  stream.setEncoding("utf8");
+  stream.on("data", (chunk) => body += chunk);
+  const closePromise = new Promise((resolve) => stream.on("close", resolve));
+
+  const response = await responsePromise;
+  assertEquals(response[HTTP2_HEADER_STATUS], "200");
+  assertEquals(response["content-type"], "text/plain");
+  assertEquals((await trailersPromise)["grpc-status"], "0");
+  await closePromise;
+  assertEquals(body, "/echo: hello");
+  assertEquals(stream.rstCode, http2.constants.NGHTTP2_NO_ERROR);
+
+  await new Promise((resolve) => session.close(resolve));
+  await new Promise((resolve) => server.close(resolve));
+});
+
+Deno.test("[node/http2 server] compatibility API", async () => {
+  const server = http2.createServer((req, res) => {
+    res.setHeader("x-method", req.method);
+    res.writeHead(201, { "x-url": req.url });
+    res.end("created");
+  });
+  server.listen(0);
+  const port = (<net.AddressInfo> server.address()).port;
+
+  const session = http2.connect(`http://localhost:${port}`);
+  const stream = session.request({ [HTTP2_HEADER_PATH]: "/items?id=1" });
+  const headers = await new Promise<Record<string, string | string[]>>(
+    (resolve) => stream.on("response", resolve),
+  );
+  stream.setEncoding("utf8");
+  let body = "";
+  stream.on("data", (chunk) => body += chunk);
+  await new Promise((resolve) => stream.on("end", resolve));
+
+  assertEquals(headers[HTTP2_HEADER_STATUS], "201");
+  assertEquals(headers["x-method"], "GET");
+  assertEquals(headers["x-url"], "/items?id=1");
+  assertEquals(body, "created");
+
+  await new Promise((resolve) => session.close(resolve));
+  await new Promise((resolve) => server.close(resolve));
+});
+
+Deno.test("[node/http2] settings", () => {
+  const settings = { headerTableSize: 1024, enablePush: false };
+  const packed = http2.getPackedSettings(settings);
+  assertEquals(packed.length, 12);
+  assertEquals(http2.getUnpackedSettings(packed), settings);
+  assertEquals(
+    http2.getDefaultSettings().maxFrameSize,
+    http2.constants.DEFAULT_SETTINGS_MAX_FRAME_SIZE,
+  );
+});
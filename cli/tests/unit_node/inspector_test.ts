@@ -0,0 +1,99 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+import inspector, { Session } from "node:inspector";
+import {
+  assert,
+  assertEquals,
+  assertThrows,
+} from "../../../test_util/std/testing/asserts.ts";
+import { deferred } from "../../../test_util/std/async/deferred.ts";
+
+Deno.test("[node/inspector] Session.post evaluates code", async () => {
+  const session = new Session();
+  session.connect();
+  const result = deferred<unknown>();
+  session.post(
+    "Runtime.evaluate",
+    { expression: "1 + 1" },
+    (error, response) => {
+      if (error) {
+        result.reject(error);
+      } else {
+        result.resolve(response);
+      }
+    },
+  );
+  assertEquals(await result, { result: { type: "number", value: 2 } });
+  session.disconnect();
+});
+
+Deno.test("[node/inspector] Session emits notifications", async () => {
+  const session = new Session();
+  session.connect();
+  const notification = deferred<string>();
+  session.on("inspectorNotification", (message) => {
+    notification.resolve(message.method);
+  });
+  const enabled = deferred();
+  session.post("Runtime.enable", () => enabled.resolve());
+  assertEquals(await notification, "Runtime.executionContextCreated");
+  await enabled;
+  session.disconnect();
+});
+
+Deno.test("[node/inspector] Session reports protocol errors", async () => {
+  const session = new Session();
+  session.connect();
+  const error = deferred<Error | null>();
+  session.post("Runtime.notAMethod", (err) => error.resolve(err));
+  assertEquals(
+    // deno-lint-ignore no-explicit-any
+    ((await error) as any).code,
+    "ERR_INSPECTOR_COMMAND",
+  );
+  session.disconnect();
+});
+
+Deno.test("[node/inspector] Session requires a connection", () => {
+  const session = new Session();
+  assertThrows(
+    () => session.post("Runtime.enable"),
+    Error,
+    "Session is not connected",
+  );
+  session.connect();
+  assertThrows(
+    () => session.connect(),
+    Error,
+    "The inspector session is already connected",
+  );
+  session.disconnect();
+});
+
+Deno.test("[node/inspector] disconnect fails pending callbacks", async () => {
+  const session = new Session();
+  session.connect();
+  const error = deferred<Error | null>();
+  session.post("Runtime.evaluate", { expression: "1" }, (err) =>
+    error.resolve(err)
+  );
+  session.disconnect();
+  assertEquals(
+    // deno-lint-ignore no-explicit-any
+    ((await error) as any).code,
+    "ERR_INSPECTOR_CLOSED",
+  );
+});
+
+Deno.test("[node/inspector] open and close", () => {
+  assertEquals(inspector.url(), undefined);
+  inspector.open(0);
+  const url = inspector.url();
+  assert(url?.startsWith("ws://127.0.0.1:"));
+  assertThrows(
+    () => inspector.open(0),
+    Error,
+    "Inspector is already activated",
+  );
+  inspector.close();
+  assertEquals(inspector.url(), undefined);
+});
@@ -0,0 +1,60 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+// @ts-ignore argon2 was added to node:crypto after the bundled @types/node
+import { argon2, argon2Sync } from "node:crypto";
+import { Buffer } from "node:buffer";
+import {
+  assertEquals,
+  assertThrows,
+} from "../../../../test_util/std/testing/asserts.ts";
+import { deferred } from "../../../../test_util/std/async/deferred.ts";
+
+// https://www.rfc-editor.org/rfc/rfc9106#section-5.3
+const parameters = {
+  message: Buffer.alloc(32, 0x01),
+  nonce: Buffer.alloc(16, 0x02),
+  secret: Buffer.alloc(8, 0x03),
+  associatedData: Buffer.alloc(12, 0x04),
+  parallelism: 4,
+  tagLength: 32,
+  memory: 32,
+  passes: 3,
+};
+const expected = Buffer.from(
+  "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659",
+  "hex",
+);
+
+Deno.test("argon2Sync matches the RFC 9106 test vector", () => {
+  assertEquals(argon2Sync("argon2id", parameters), expected);
+});
+
+Deno.test("argon2 matches the RFC 9106 test vector", async () => {
+  const promise = deferred();
+
+  argon2("argon2id", parameters, (err: unknown, key: Buffer) => {
+    if (err) throw err;
+    assertEquals(key, expected);
+    promise.resolve(true);
+  });
+
+  await promise;
+});
+
+Deno.test("argon2Sync validates its parameters", () => {
+  assertThrows(
+    () => argon2Sync("argon2", parameters),
+    TypeError,
+  );
+  assertThrows(
+    () => argon2Sync("argon2id", { ...parameters, nonce: "short" }),
+    RangeError,
+  );
+  assertThrows(
+    () => argon2Sync("argon2id", { ...parameters, memory: 31 }),
+    RangeError,
+  );
+  assertThrows(
+    () => argon2Sync("argon2id", { ...parameters, passes: 0 }),
+    RangeError,
+  );
+});
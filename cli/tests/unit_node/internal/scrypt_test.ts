@@ -1,7 +1,10 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 import { scrypt, scryptSync } from "node:crypto";
 import { Buffer } from "node:buffer";
-import { assertEquals } from "../../../../test_util/std/testing/asserts.ts";
+import {
+  assertEquals,
+  assertThrows,
+} from "../../../../test_util/std/testing/asserts.ts";
 import { deferred } from "../../../../test_util/std/async/deferred.ts";
 
 Deno.test("scrypt works correctly", async () => {
@@ -189,3 +192,12 @@ Deno.test("scryptSync with options works correctly", () => {
     ]),
   );
 });
+
+Deno.test("scryptSync validates its parameters", () => {
+  assertThrows(() => scryptSync("password", "salt", 32, { N: 1000 }));
+  assertThrows(() => scryptSync("password", "salt", 32, { N: 16, cost: 16 }));
+  assertThrows(() =>
+    scryptSync("password", "salt", 32, { N: 2 ** 20, maxmem: 32 << 20 })
+  );
+  assertThrows(() => scryptSync("password", "salt", -1));
+});
@@ -0,0 +1,116 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+import {
+  compileFunction,
+  createContext,
+  isContext,
+  runInContext,
+  runInNewContext,
+  runInThisContext,
+  Script,
+} from "node:vm";
+import {
+  assertEquals,
+  assertThrows,
+} from "../../../test_util/std/testing/asserts.ts";
+
+Deno.test({
+  name: "vm runInNewContext",
+  fn() {
+    const two = runInNewContext("1 + 1");
+    assertEquals(two, 2);
+  },
+});
+
+Deno.test({
+  name: "vm runInContext reads and writes the sandbox",
+  fn() {
+    const context = createContext({ x: 2 });
+    runInContext("y = x * 21; x = 'changed'", context);
+    assertEquals(context, { x: "changed", y: 42 });
+  },
+});
+
+Deno.test({
+  name: "vm contexts have their own globals",
+  fn() {
+    const context = createContext({});
+    assertEquals(runInContext("typeof Deno", context), "undefined");
+    assertEquals(runInContext("Array === this.Array", context), true);
+    const array = runInContext("[]", context);
+    assertEquals(array instanceof Array, false);
+    runInContext("globalThis.leaked = true", context);
+    assertEquals("leaked" in globalThis, false);
+  },
+});
+
+Deno.test({
+  name: "vm Script runs in several contexts",
+  fn() {
+    const script = new Script("count += 1; name = 'kitty';");
+    const contexts = [{ count: 0 }, { count: 10 }].map((sandbox) =>
+      createContext(sandbox)
+    );
+    for (const context of contexts) {
+      script.runInContext(context);
+      script.runInContext(context);
+    }
+    assertEquals(contexts, [
+      { count: 2, name: "kitty" },
+      { count: 12, name: "kitty" },
+    ]);
+  },
+});
+
+Deno.test({
+  name: "vm runInThisContext",
+  fn() {
+    // deno-lint-ignore no-explicit-any
+    (globalThis as any).vmTestValue = 1;
+    assertEquals(runInThisContext("vmTestValue + 1"), 2);
+    // deno-lint-ignore no-explicit-any
+    delete (globalThis as any).vmTestValue;
+  },
+});
+
+Deno.test({
+  name: "vm isContext",
+  fn() {
+    const sandbox = {};
+    assertEquals(isContext(sandbox), false);
+    assertEquals(createContext(sandbox), sandbox);
+    assertEquals(isContext(sandbox), true);
+  },
+});
+
+Deno.test({
+  name: "vm rethrows errors thrown by the script",
+  fn() {
+    const context = createContext({});
+    assertThrows(() => runInContext("throw new Error('boom')", context));
+    // Errors are instances of the classes of the context they were created in.
+    const error = assertThrows(() => runInContext("}", context));
+    assertEquals((error as Error).name, "SyntaxError");
+  },
+});
+
+Deno.test({
+  name: "vm terminates scripts that exceed the timeout",
+  fn() {
+    const context = createContext({});
+    assertThrows(
+      () => runInContext("while (true) {}", context, { timeout: 50 }),
+      Error,
+      "Script execution timed out after 50ms",
+    );
+    // The context is still usable afterwards.
+    assertEquals(runInContext("1 + 1", context, { timeout: 50 }), 2);
+  },
+});
+
+Deno.test({
+  name: "vm compileFunction",
+  fn() {
+    const add = compileFunction("return a + b", ["a", "b"]);
+    assertEquals(add(1, 2), 3);
+  },
+});
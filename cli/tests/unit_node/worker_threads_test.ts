@@ -192,3 +192,65 @@ Deno.test({
     worker.terminate();
   },
 });
+
+Deno.test({
+  name: "[worker_threads] receiveMessageOnPort",
+  fn() {
+    const { port1, port2 } = new workerThreads.MessageChannel();
+    assertEquals(workerThreads.receiveMessageOnPort(port2), undefined);
+    port1.postMessage({ hello: "world" });
+    assertEquals(workerThreads.receiveMessageOnPort(port2), {
+      message: { hello: "world" },
+    });
+    assertEquals(workerThreads.receiveMessageOnPort(port2), undefined);
+    port1.close();
+    port2.close();
+  },
+});
+
+Deno.test({
+  name: "[worker_threads] Worker online, terminate and exit",
+  async fn() {
+    const worker = new workerThreads.Worker(
+      `
+      import { parentPort } from "node:worker_threads";
+      parentPort.postMessage("ready");
+      `,
+      {
+        eval: true,
+        resourceLimits: { maxOldGenerationSizeMb: 64 },
+      },
+    );
+    await once(worker, "online");
+    await once(worker, "message");
+    assertEquals(worker.resourceLimits.maxOldGenerationSizeMb, 64);
+    const exit = once(worker, "exit");
+    assertEquals(await worker.terminate(), 1);
+    assertEquals((await exit)[0], 1);
+    assertEquals(await worker.terminate(), 1);
+  },
+});
+
+Deno.test({
+  name: "[worker_threads] SharedArrayBuffer in workerData",
+  async fn() {
+    const sab = new SharedArrayBuffer(4);
+    const worker = new workerThreads.Worker(
+      `
+      import { parentPort, workerData } from "node:worker_threads";
+      parentPort.once("message", () => {
+        Atomics.store(new Int32Array(workerData), 0, 42);
+        parentPort.postMessage("done");
+      });
+      `,
+      {
+        eval: true,
+        workerData: sab,
+      },
+    );
+    worker.postMessage("store");
+    await once(worker, "message");
+    assertEquals(Atomics.load(new Int32Array(sab), 0), 42);
+    worker.terminate();
+  },
+});
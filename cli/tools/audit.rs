@@ -0,0 +1,700 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::resolve_url_or_path;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_graph::GraphKind;
+use deno_graph::Module;
+use deno_graph::ModuleGraph;
+use deno_lockfile::NpmPackageInfo;
+use deno_npm::resolution::NpmResolutionSnapshot;
+use deno_runtime::colors;
+use deno_semver::npm::NpmPackageNv;
+use deno_semver::VersionReq;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::args::AuditFlags;
+use crate::args::Flags;
+use crate::args::Lockfile;
+use crate::display;
+use crate::factory::CliFactory;
+use crate::file_fetcher::FileFetcher;
+use crate::graph_util::graph_valid_with_cli_options;
+use crate::http_util::HttpClient;
+use crate::npm::find_modified_files;
+use crate::npm::tarball_integrity;
+use crate::npm::CliNpmRegistryApi;
+use crate::npm::NpmCache;
+use crate::util::checksum;
+
+/// The severity of an advisory, as reported by the npm registry.
+#[derive(
+  Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "lowercase")]
+enum AdvisorySeverity {
+  Info,
+  Low,
+  Moderate,
+  High,
+  Critical,
+}
+
+impl AdvisorySeverity {
+  fn from_level(level: &str) -> Result<Self, AnyError> {
+    match level {
+      "low" => Ok(Self::Low),
+      "moderate" => Ok(Self::Moderate),
+      "high" => Ok(Self::High),
+      "critical" => Ok(Self::Critical),
+      _ => bail!("Invalid audit level: {}", level),
+    }
+  }
+
+  fn as_str(&self) -> &'static str {
+    match self {
+      Self::Info => "info",
+      Self::Low => "low",
+      Self::Moderate => "moderate",
+      Self::High => "high",
+      Self::Critical => "critical",
+    }
+  }
+}
+
+/// An advisory returned by the bulk advisory endpoint of the npm registry.
+#[derive(Debug, Deserialize)]
+struct NpmAdvisory {
+  id: u64,
+  url: String,
+  title: String,
+  severity: AdvisorySeverity,
+  vulnerable_versions: String,
+}
+
+/// A module or npm package whose cached copy doesn't match the hash in the
+/// lockfile.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct IntegrityIssue {
+  specifier: String,
+  /// The hash in the lockfile, or `None` if it's missing from the lockfile.
+  expected: Option<String>,
+  /// The hash of the cached module or npm tarball, or `None` if the tarball
+  /// of a cached npm package is missing.
+  actual: Option<String>,
+  /// Files of a cached npm package that differ from its tarball.
+  #[serde(skip_serializing_if = "Vec::is_empty")]
+  modified_files: Vec<String>,
+}
+
+/// An npm package in the module graph that is affected by an advisory.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct VulnerablePackage {
+  package: String,
+  id: u64,
+  title: String,
+  severity: AdvisorySeverity,
+  url: String,
+  vulnerable_versions: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct AuditReport {
+  integrity: Vec<IntegrityIssue>,
+  advisories: Vec<VulnerablePackage>,
+}
+
+impl AuditReport {
+  fn is_empty(&self) -> bool {
+    self.integrity.is_empty() && self.advisories.is_empty()
+  }
+}
+
+pub async fn audit(
+  flags: Flags,
+  audit_flags: AuditFlags,
+) -> Result<(), AnyError> {
+  let min_severity = AdvisorySeverity::from_level(&audit_flags.audit_level)?;
+  let factory = CliFactory::from_flags(flags).await?;
+  let cli_options = factory.cli_options();
+  let Some(lockfile) = factory.maybe_lockfile().clone() else {
+    bail!(concat!(
+      "deno audit requires a lock file. Create one with --lock-write ",
+      "or by adding a deno.json next to the audited modules."
+    ));
+  };
+  let module_graph_builder = factory.module_graph_builder().await?;
+  let npm_resolver = factory.npm_resolver().await?;
+  let registry_url = CliNpmRegistryApi::default_url();
+
+  // Building the graph adds what's missing to the lockfile, so keep what it
+  // contains now.
+  let (locked_remote, locked_npm) = {
+    let lockfile = lockfile.lock();
+    (
+      locked_remote_modules(&lockfile)?,
+      lockfile.content.npm.packages.clone(),
+    )
+  };
+
+  // The caches are checked before building the graph, which exits on the
+  // first module not matching the lockfile.
+  let mut report = AuditReport {
+    integrity: check_remote_cache(factory.file_fetcher()?, &locked_remote)?,
+    ..Default::default()
+  };
+  if report.integrity.is_empty() {
+    let roots = audit_flags
+      .files
+      .iter()
+      .map(|file| resolve_url_or_path(file, cli_options.initial_cwd()))
+      .collect::<Result<Vec<_>, _>>()?;
+    let graph = module_graph_builder
+      .create_graph(GraphKind::All, roots.clone())
+      .await?;
+    graph_valid_with_cli_options(&graph, &roots, cli_options)?;
+    report.integrity = find_unlocked_remote_modules(&graph, &locked_remote);
+  }
+
+  let snapshot = npm_resolver.snapshot();
+  report.integrity.extend(check_npm_cache(
+    &snapshot,
+    &locked_npm,
+    factory.npm_cache()?,
+    registry_url,
+  )?);
+
+  let packages = snapshot
+    .all_packages_for_every_system()
+    .map(|package| package.id.nv.clone())
+    .collect::<Vec<_>>();
+  if !packages.is_empty() {
+    let advisories =
+      fetch_npm_advisories(factory.http_client(), registry_url, &packages)
+        .await?;
+    report.advisories =
+      find_vulnerable_packages(&packages, &advisories, min_severity);
+  }
+
+  if audit_flags.json {
+    display::write_json_to_stdout(&report)?;
+  } else {
+    let mut output = String::new();
+    write_report(&report, &mut output)?;
+    display::write_to_stdout_ignore_sigpipe(output.as_bytes())?;
+  }
+
+  if !report.is_empty() {
+    std::process::exit(1);
+  }
+  Ok(())
+}
+
+/// Gets the checksums of the remote modules in the lockfile, which
+/// `deno_lockfile` only exposes through the serialized lockfile.
+fn locked_remote_modules(
+  lockfile: &Lockfile,
+) -> Result<BTreeMap<String, String>, AnyError> {
+  let mut content = serde_json::to_value(&lockfile.content)?;
+  Ok(serde_json::from_value(content["remote"].take())?)
+}
+
+/// Compares the cached source of the remote modules in the lockfile to their
+/// hashes in it. Modules that aren't cached are skipped, the module graph
+/// verifies them against the lockfile when downloading them.
+fn check_remote_cache(
+  file_fetcher: &FileFetcher,
+  locked_remote: &BTreeMap<String, String>,
+) -> Result<Vec<IntegrityIssue>, AnyError> {
+  let mut issues = Vec::new();
+  for (specifier, expected) in locked_remote {
+    let Ok(url) = Url::parse(specifier) else {
+      continue;
+    };
+    let Some(file) = file_fetcher.fetch_cached(&url, 10)? else {
+      continue;
+    };
+    let actual = checksum::gen(&[file.source.as_bytes()]);
+    if actual != *expected {
+      issues.push(IntegrityIssue {
+        specifier: specifier.clone(),
+        expected: Some(expected.clone()),
+        actual: Some(actual),
+        modified_files: Vec::new(),
+      });
+    }
+  }
+  Ok(issues)
+}
+
+/// Gets the remote modules in the graph that weren't in the lockfile before
+/// the graph was built.
+fn find_unlocked_remote_modules(
+  graph: &ModuleGraph,
+  locked_remote: &BTreeMap<String, String>,
+) -> Vec<IntegrityIssue> {
+  let mut issues = Vec::new();
+  for module in graph.modules() {
+    let source = match module {
+      Module::Esm(module) => &module.source,
+      Module::Json(module) => &module.source,
+      Module::Node(_) | Module::Npm(_) | Module::External(_) => continue,
+    };
+    let specifier = module.specifier();
+    if !matches!(specifier.scheme(), "http" | "https")
+      || locked_remote.contains_key(specifier.as_str())
+    {
+      continue;
+    }
+    issues.push(IntegrityIssue {
+      specifier: specifier.to_string(),
+      expected: None,
+      actual: Some(checksum::gen(&[source.as_bytes()])),
+      modified_files: Vec::new(),
+    });
+  }
+  issues
+}
+
+/// Checks the cached npm packages of the snapshot against the integrity in
+/// the lockfile.
+fn check_npm_cache(
+  snapshot: &NpmResolutionSnapshot,
+  locked_npm: &BTreeMap<String, NpmPackageInfo>,
+  npm_cache: &NpmCache,
+  registry_url: &Url,
+) -> Result<Vec<IntegrityIssue>, AnyError> {
+  let mut issues = Vec::new();
+  for package in snapshot.all_packages_for_every_system() {
+    let nv = &package.id.nv;
+    let maybe_issue = match locked_npm.get(&package.id.as_serialized()) {
+      Some(info) => check_npm_package(
+        nv,
+        &info.integrity,
+        &npm_cache.package_tarball_for_name_and_version(nv, registry_url),
+        &npm_cache.package_folder_for_name_and_version(nv, registry_url),
+      )?,
+      None => Some(IntegrityIssue {
+        specifier: format!("npm:{nv}"),
+        expected: None,
+        actual: Some(package.dist.integrity().to_string()),
+        modified_files: Vec::new(),
+      }),
+    };
+    issues.extend(maybe_issue);
+  }
+  issues.sort_by(|a, b| a.specifier.cmp(&b.specifier));
+  issues.dedup_by(|a, b| a.specifier == b.specifier);
+  Ok(issues)
+}
+
+/// Checks that the tarball a cached npm package was extracted from hashes to
+/// the integrity in the lockfile, and that the extracted files are still the
+/// ones in the tarball. Packages that aren't cached are skipped, they are
+/// verified when downloading them.
+fn check_npm_package(
+  nv: &NpmPackageNv,
+  expected: &str,
+  tarball_path: &Path,
+  package_folder: &Path,
+) -> Result<Option<IntegrityIssue>, AnyError> {
+  if !package_folder.exists() {
+    return Ok(None);
+  }
+  let mut issue = IntegrityIssue {
+    specifier: format!("npm:{nv}"),
+    expected: Some(expected.to_string()),
+    actual: None,
+    modified_files: Vec::new(),
+  };
+  let data = match std::fs::read(tarball_path) {
+    Ok(data) => data,
+    // Cached before tarballs were kept, so it can't be verified.
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Some(issue)),
+    Err(err) => return Err(err.into()),
+  };
+  let actual = tarball_integrity(nv, &data, expected)?;
+  if actual.to_lowercase() != expected.to_lowercase() {
+    issue.actual = Some(actual);
+    return Ok(Some(issue));
+  }
+  issue.modified_files = find_modified_files(&data, package_folder)?;
+  if issue.modified_files.is_empty() {
+    return Ok(None);
+  }
+  issue.actual = Some(actual);
+  Ok(Some(issue))
+}
+
+/// Fetches the advisories for the provided packages from the npm registry.
+async fn fetch_npm_advisories(
+  http_client: &HttpClient,
+  registry_url: &Url,
+  packages: &[NpmPackageNv],
+) -> Result<HashMap<String, Vec<NpmAdvisory>>, AnyError> {
+  let mut versions = BTreeMap::<&str, Vec<String>>::new();
+  for nv in packages {
+    versions
+      .entry(nv.name.as_str())
+      .or_default()
+      .push(nv.version.to_string());
+  }
+  let url = registry_url.join("-/npm/v1/security/advisories/bulk")?;
+  http_client
+    .post_json(url, &serde_json::to_value(versions)?)
+    .await
+}
+
+/// Gets the packages whose version is affected by an advisory of at least
+/// the provided severity.
+fn find_vulnerable_packages(
+  packages: &[NpmPackageNv],
+  advisories: &HashMap<String, Vec<NpmAdvisory>>,
+  min_severity: AdvisorySeverity,
+) -> Vec<VulnerablePackage> {
+  let mut vulnerable_packages = Vec::new();
+  for nv in packages {
+    let Some(package_advisories) = advisories.get(&nv.name) else {
+      continue;
+    };
+    for advisory in package_advisories {
+      if advisory.severity < min_severity {
+        continue;
+      }
+      let is_vulnerable =
+        VersionReq::parse_from_npm(&advisory.vulnerable_versions)
+          .map(|version_req| version_req.matches(&nv.version))
+          .unwrap_or(true);
+      if is_vulnerable {
+        vulnerable_packages.push(VulnerablePackage {
+          package: nv.to_string(),
+          id: advisory.id,
+          title: advisory.title.clone(),
+          severity: advisory.severity,
+          url: advisory.url.clone(),
+          vulnerable_versions: advisory.vulnerable_versions.clone(),
+        });
+      }
+    }
+  }
+  vulnerable_packages.sort_by(|a, b| {
+    b.severity
+      .cmp(&a.severity)
+      .then_with(|| a.package.cmp(&b.package))
+  });
+  vulnerable_packages
+}
+
+fn write_report(report: &AuditReport, f: &mut String) -> std::fmt::Result {
+  if report.is_empty() {
+    return writeln!(
+      f,
+      "{} No integrity mismatches or known vulnerabilities found.",
+      colors::green("ok")
+    );
+  }
+
+  if !report.integrity.is_empty() {
+    writeln!(f, "{}", colors::bold("Integrity"))?;
+    for issue in &report.integrity {
+      writeln!(f, "  {} {}", colors::red("x"), issue.specifier)?;
+      match &issue.expected {
+        Some(expected) => writeln!(f, "    expected: {expected}")?,
+        None => {
+          writeln!(f, "    expected: {}", colors::gray("(not in lock file)"))?
+        }
+      }
+      match &issue.actual {
+        Some(actual) => writeln!(f, "    actual:   {actual}")?,
+        None => writeln!(
+          f,
+          "    actual:   {}",
+          colors::gray("(tarball not cached, reload to verify)")
+        )?,
+      }
+      if !issue.modified_files.is_empty() {
+        writeln!(f, "    modified: {}", issue.modified_files.join(", "))?;
+      }
+    }
+  }
+
+  if !report.advisories.is_empty() {
+    if !report.integrity.is_empty() {
+      writeln!(f)?;
+    }
+    writeln!(f, "{}", colors::bold("Advisories"))?;
+    for package in &report.advisories {
+      let severity = match package.severity {
+        AdvisorySeverity::High | AdvisorySeverity::Critical => {
+          colors::red_bold(package.severity.as_str()).to_string()
+        }
+        _ => colors::yellow(package.severity.as_str()).to_string(),
+      };
+      writeln!(f, "  {} {} {}", severity, package.package, package.title)?;
+      writeln!(
+        f,
+        "    vulnerable versions: {}",
+        package.vulnerable_versions
+      )?;
+      writeln!(f, "    {}", colors::gray(&package.url))?;
+    }
+  }
+
+  writeln!(f)?;
+  writeln!(
+    f,
+    "{} {} integrity {}, {} vulnerable {}",
+    colors::red("error:"),
+    report.integrity.len(),
+    if report.integrity.len() == 1 {
+      "mismatch"
+    } else {
+      "mismatches"
+    },
+    report.advisories.len(),
+    if report.advisories.len() == 1 {
+      "package"
+    } else {
+      "packages"
+    },
+  )
+}
+
+#[cfg(test)]
+mod test {
+  use std::sync::Arc;
+
+  use deno_runtime::deno_web::BlobStore;
+  use deno_semver::Version;
+  use test_util::TempDir;
+
+  use super::*;
+  use crate::args::CacheSetting;
+  use crate::cache::HttpCache;
+
+  fn advisory(
+    id: u64,
+    severity: AdvisorySeverity,
+    vulnerable_versions: &str,
+  ) -> NpmAdvisory {
+    NpmAdvisory {
+      id,
+      url: format!("https://github.com/advisories/{id}"),
+      title: format!("Advisory {id}"),
+      severity,
+      vulnerable_versions: vulnerable_versions.to_string(),
+    }
+  }
+
+  fn nv(name: &str, version: &str) -> NpmPackageNv {
+    NpmPackageNv {
+      name: name.to_string(),
+      version: Version::parse_from_npm(version).unwrap(),
+    }
+  }
+
+  #[test]
+  fn test_find_vulnerable_packages() {
+    let packages = vec![
+      nv("lodash", "4.17.20"),
+      nv("minimist", "1.2.6"),
+      nv("chalk", "5.2.0"),
+    ];
+    let advisories = HashMap::from([
+      (
+        "lodash".to_string(),
+        vec![
+          advisory(1, AdvisorySeverity::High, "<4.17.21"),
+          advisory(2, AdvisorySeverity::Low, "<4.17.19"),
+        ],
+      ),
+      (
+        "minimist".to_string(),
+        vec![
+          advisory(3, AdvisorySeverity::Critical, ">=1.0.0 <1.2.6"),
+          advisory(4, AdvisorySeverity::Moderate, "<0.2.1 || >=1.0.0 <1.2.7"),
+        ],
+      ),
+    ]);
+
+    let found =
+      find_vulnerable_packages(&packages, &advisories, AdvisorySeverity::Low);
+    assert_eq!(
+      found
+        .iter()
+        .map(|p| (p.package.as_str(), p.id))
+        .collect::<Vec<_>>(),
+      vec![("lodash@4.17.20", 1), ("minimist@1.2.6", 4)],
+    );
+
+    let found =
+      find_vulnerable_packages(&packages, &advisories, AdvisorySeverity::High);
+    assert_eq!(
+      found
+        .iter()
+        .map(|p| (p.package.as_str(), p.id))
+        .collect::<Vec<_>>(),
+      vec![("lodash@4.17.20", 1)],
+    );
+  }
+
+  #[test]
+  fn test_check_remote_cache() {
+    let temp_dir = TempDir::new();
+    let http_cache = HttpCache::new(temp_dir.path().join("deps").to_path_buf());
+    let file_fetcher = FileFetcher::new(
+      http_cache.clone(),
+      CacheSetting::Only,
+      true,
+      Arc::new(HttpClient::new(None, None)),
+      BlobStore::default(),
+      None,
+    );
+    let url = Url::parse("https://deno.land/x/mod.ts").unwrap();
+    let headers = HashMap::from([(
+      "content-type".to_string(),
+      "application/typescript".to_string(),
+    )]);
+    let source = "export const a = 1;\n";
+    http_cache
+      .set(&url, headers.clone(), source.as_bytes())
+      .unwrap();
+    let locked_remote = BTreeMap::from([
+      (url.to_string(), checksum::gen(&[source.as_bytes()])),
+      // not cached, so not checked
+      (
+        "https://deno.land/x/other.ts".to_string(),
+        "abc".to_string(),
+      ),
+    ]);
+    assert_eq!(
+      check_remote_cache(&file_fetcher, &locked_remote).unwrap(),
+      vec![]
+    );
+
+    let tampered = "export const a = 2;\n";
+    http_cache.set(&url, headers, tampered.as_bytes()).unwrap();
+    assert_eq!(
+      check_remote_cache(&file_fetcher, &locked_remote).unwrap(),
+      vec![IntegrityIssue {
+        specifier: url.to_string(),
+        expected: Some(checksum::gen(&[source.as_bytes()])),
+        actual: Some(checksum::gen(&[tampered.as_bytes()])),
+        modified_files: vec![],
+      }]
+    );
+  }
+
+  fn create_tarball(files: &[(&str, &str)]) -> Vec<u8> {
+    let encoder =
+      flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    for (path, content) in files {
+      let mut header = tar::Header::new_gnu();
+      header.set_size(content.len() as u64);
+      header.set_mode(0o644);
+      header.set_cksum();
+      builder
+        .append_data(&mut header, format!("package/{path}"), content.as_bytes())
+        .unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+  }
+
+  #[test]
+  fn test_check_npm_package() {
+    let temp_dir = TempDir::new();
+    let package = nv("chalk", "5.0.1");
+    let tarball_path = temp_dir.path().join("5.0.1.tgz").to_path_buf();
+    let package_folder = temp_dir.path().join("5.0.1").to_path_buf();
+    let files = [
+      ("package.json", r#"{ "name": "chalk" }"#),
+      ("source/index.js", "export default {};"),
+    ];
+    let tarball = create_tarball(&files);
+    let integrity = format!(
+      "sha512-{}",
+      base64::encode(ring::digest::digest(&ring::digest::SHA512, &tarball))
+    );
+    let check = || {
+      check_npm_package(&package, &integrity, &tarball_path, &package_folder)
+        .unwrap()
+    };
+
+    // not cached
+    assert_eq!(check(), None);
+
+    std::fs::write(&tarball_path, &tarball).unwrap();
+    for (path, content) in files {
+      let path = package_folder.join(path);
+      std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+      std::fs::write(path, content).unwrap();
+    }
+    assert_eq!(check(), None);
+
+    // a modified file of the extracted package
+    std::fs::write(
+      package_folder.join("source/index.js"),
+      "export default { evil: true };",
+    )
+    .unwrap();
+    assert_eq!(
+      check(),
+      Some(IntegrityIssue {
+        specifier: "npm:chalk@5.0.1".to_string(),
+        expected: Some(integrity.clone()),
+        actual: Some(integrity.clone()),
+        modified_files: vec!["source/index.js".to_string()],
+      })
+    );
+
+    // a tarball that doesn't match the lockfile
+    let tampered =
+      create_tarball(&[("source/index.js", "export default { evil: true };")]);
+    std::fs::write(&tarball_path, &tampered).unwrap();
+    let issue = check().unwrap();
+    assert_eq!(issue.expected, Some(integrity.clone()));
+    assert_ne!(issue.actual, Some(integrity.clone()));
+    assert!(issue.actual.unwrap().starts_with("sha512-"));
+
+    // a missing tarball can't be verified
+    std::fs::remove_file(&tarball_path).unwrap();
+    assert_eq!(check().unwrap().actual, None);
+  }
+
+  #[test]
+  fn test_advisory_severity() {
+    assert_eq!(
+      AdvisorySeverity::from_level("moderate").unwrap(),
+      AdvisorySeverity::Moderate
+    );
+    assert!(AdvisorySeverity::from_level("unknown").is_err());
+    assert!(AdvisorySeverity::Critical > AdvisorySeverity::High);
+    assert!(AdvisorySeverity::Low > AdvisorySeverity::Info);
+
+    let advisories: HashMap<String, Vec<NpmAdvisory>> =
+      serde_json::from_value(serde_json::json!({
+        "lodash": [{
+          "id": 1,
+          "url": "https://github.com/advisories/GHSA-35jh-r3h4-6jhm",
+          "title": "Command Injection in lodash",
+          "severity": "high",
+          "vulnerable_versions": "<4.17.21",
+          "cwe": ["CWE-77"],
+        }],
+      }))
+      .unwrap();
+    assert_eq!(advisories["lodash"][0].severity, AdvisorySeverity::High);
+  }
+}
@@ -0,0 +1,321 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::mitata::fmt_duration;
+use super::BenchDescription;
+use super::BenchStats;
+use crate::colors;
+use crate::version::get_user_agent;
+
+use deno_core::anyhow::Context as _;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::url::Url;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The statistics of the benchmarks of a run saved with `--save-baseline`,
+/// which later runs compare their results with using `--compare`.
+#[derive(Debug, Clone)]
+pub struct BenchBaseline {
+  name: String,
+  path: PathBuf,
+  /// The directory the modules of the benchmarks are relative to, so the
+  /// baseline can be compared with runs from a different checkout.
+  base_url: Url,
+  file: BaselineFile,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BaselineFile {
+  runtime: String,
+  benches: Vec<BaselineBench>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BaselineBench {
+  origin: String,
+  group: Option<String>,
+  name: String,
+  stats: BenchStats,
+}
+
+impl BenchBaseline {
+  pub fn new(dir: &Path, name: &str, base_url: Url) -> Self {
+    Self {
+      name: name.to_string(),
+      path: dir.join(format!("{name}.json")),
+      base_url,
+      file: BaselineFile {
+        runtime: format!("{} {}", get_user_agent(), env!("TARGET")),
+        benches: Vec::new(),
+      },
+    }
+  }
+
+  pub fn load(dir: &Path, name: &str, base_url: Url) -> Result<Self, AnyError> {
+    let mut baseline = Self::new(dir, name, base_url);
+    let text = match std::fs::read_to_string(&baseline.path) {
+      Ok(text) => text,
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+        return Err(generic_error(format!(
+          "Bench baseline \"{name}\" not found, save it with `deno bench --save-baseline={name}`"
+        )));
+      }
+      Err(err) => return Err(err.into()),
+    };
+    baseline.file = serde_json::from_str(&text).with_context(|| {
+      format!(
+        "Failed to parse bench baseline: {}",
+        baseline.path.display()
+      )
+    })?;
+    Ok(baseline)
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn origin_key(&self, origin: &str) -> String {
+    Url::parse(origin)
+      .ok()
+      .and_then(|url| self.base_url.make_relative(&url))
+      .unwrap_or_else(|| origin.to_string())
+  }
+
+  fn get(&self, desc: &BenchDescription) -> Option<&BenchStats> {
+    let origin = self.origin_key(&desc.origin);
+    self
+      .file
+      .benches
+      .iter()
+      .find(|bench| {
+        bench.origin == origin
+          && bench.group == desc.group
+          && bench.name == desc.name
+      })
+      .map(|bench| &bench.stats)
+  }
+
+  pub fn insert(&mut self, desc: &BenchDescription, stats: &BenchStats) {
+    let origin = self.origin_key(&desc.origin);
+    // a later benchmark with the same name replaces the earlier one
+    self.file.benches.retain(|bench| {
+      bench.origin != origin
+        || bench.group != desc.group
+        || bench.name != desc.name
+    });
+    self.file.benches.push(BaselineBench {
+      origin,
+      group: desc.group.clone(),
+      name: desc.name.clone(),
+      stats: stats.clone(),
+    });
+  }
+
+  pub fn save(&self) -> Result<(), AnyError> {
+    if let Some(dir) = self.path.parent() {
+      std::fs::create_dir_all(dir)?;
+    }
+    let text = serde_json::to_string_pretty(&self.file)?;
+    std::fs::write(&self.path, format!("{text}\n")).with_context(|| {
+      format!("Failed to write bench baseline: {}", self.path.display())
+    })
+  }
+}
+
+/// The baselines a run of the benchmarks is compared with and saved as.
+#[derive(Debug, Clone, Default)]
+pub struct BaselineOptions {
+  pub compare: Option<BenchBaseline>,
+  /// The percent by which the average time of a benchmark may differ from
+  /// the baseline before it's reported as a significant change.
+  pub regression_threshold: u32,
+  pub save: Option<BenchBaseline>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Change {
+  /// The benchmark is not in the baseline.
+  New,
+  /// The difference is within the threshold, which is likely noise.
+  Insignificant,
+  Improved,
+  Regressed,
+}
+
+struct Comparison<'a> {
+  desc: &'a BenchDescription,
+  baseline_avg: Option<f64>,
+  avg: f64,
+  /// The difference of the average time in percent of the baseline.
+  delta: f64,
+  change: Change,
+}
+
+fn compare<'a>(
+  baseline: &BenchBaseline,
+  measurements: &'a [(BenchDescription, BenchStats)],
+  threshold: u32,
+) -> Vec<Comparison<'a>> {
+  measurements
+    .iter()
+    .map(|(desc, stats)| {
+      let Some(baseline_stats) = baseline.get(desc) else {
+        return Comparison {
+          desc,
+          baseline_avg: None,
+          avg: stats.avg,
+          delta: 0.0,
+          change: Change::New,
+        };
+      };
+      let delta = if baseline_stats.avg > 0.0 {
+        (stats.avg - baseline_stats.avg) / baseline_stats.avg * 100.0
+      } else {
+        0.0
+      };
+      let change = if delta.abs() <= threshold as f64 {
+        Change::Insignificant
+      } else if delta > 0.0 {
+        Change::Regressed
+      } else {
+        Change::Improved
+      };
+      Comparison {
+        desc,
+        baseline_avg: Some(baseline_stats.avg),
+        avg: stats.avg,
+        delta,
+        change,
+      }
+    })
+    .collect()
+}
+
+/// Prints the differences of the results to the baseline, returning the
+/// number of benchmarks that regressed beyond the threshold.
+pub fn report_comparison(
+  baseline: &BenchBaseline,
+  measurements: &[(BenchDescription, BenchStats)],
+  threshold: u32,
+  print: bool,
+) -> usize {
+  let comparisons = compare(baseline, measurements, threshold);
+  if print {
+    let name_width = comparisons
+      .iter()
+      .map(|comparison| comparison.desc.name.chars().count())
+      .max()
+      .unwrap_or(0)
+      .max("benchmark".len());
+    println!(
+      "\n{}",
+      colors::gray(format!(
+        "comparison with baseline \"{}\" (threshold ±{threshold}%)",
+        baseline.name()
+      ))
+    );
+    println!(
+      "{:<name_width$} {:>14} {:>14} {:>9}",
+      "benchmark", "baseline", "time (avg)", "delta"
+    );
+    for comparison in &comparisons {
+      let baseline_avg = comparison
+        .baseline_avg
+        .map(fmt_duration)
+        .unwrap_or_else(|| "-".to_string());
+      let delta = match comparison.change {
+        Change::New => "-".to_string(),
+        _ => format!("{:+.1}%", comparison.delta),
+      };
+      let change = match comparison.change {
+        Change::New => colors::gray("new").to_string(),
+        Change::Insignificant => {
+          colors::gray("no significant change").to_string()
+        }
+        Change::Improved => colors::green("improved").to_string(),
+        Change::Regressed => colors::red_bold("regressed").to_string(),
+      };
+      println!(
+        "{:<name_width$} {:>14} {:>14} {:>9} {}",
+        comparison.desc.name,
+        baseline_avg,
+        fmt_duration(comparison.avg),
+        delta,
+        change,
+      );
+    }
+  }
+  comparisons
+    .iter()
+    .filter(|comparison| comparison.change == Change::Regressed)
+    .count()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn bench(id: usize, name: &str) -> BenchDescription {
+    BenchDescription {
+      id,
+      name: name.to_string(),
+      origin: "file:///project/a_bench.ts".to_string(),
+      baseline: false,
+      group: None,
+      ignore: false,
+      only: false,
+    }
+  }
+
+  fn stats(avg: f64) -> BenchStats {
+    BenchStats {
+      n: 100,
+      min: avg,
+      max: avg,
+      avg,
+      p75: avg,
+      p99: avg,
+      p995: avg,
+      p999: avg,
+    }
+  }
+
+  #[test]
+  fn compare_with_baseline() {
+    let base_url = Url::parse("file:///project/").unwrap();
+    let mut baseline =
+      BenchBaseline::new(Path::new("/baselines"), "main", base_url);
+    baseline.insert(&bench(0, "same"), &stats(1000.0));
+    baseline.insert(&bench(1, "slower"), &stats(1000.0));
+    baseline.insert(&bench(2, "faster"), &stats(1000.0));
+    assert_eq!(baseline.path, Path::new("/baselines/main.json"));
+    assert_eq!(baseline.file.benches[0].origin, "a_bench.ts");
+
+    let measurements = vec![
+      (bench(3, "same"), stats(1050.0)),
+      (bench(4, "slower"), stats(1200.0)),
+      (bench(5, "faster"), stats(500.0)),
+      (bench(6, "added"), stats(10.0)),
+    ];
+    let changes = compare(&baseline, &measurements, 10)
+      .iter()
+      .map(|comparison| comparison.change)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      changes,
+      vec![
+        Change::Insignificant,
+        Change::Regressed,
+        Change::Improved,
+        Change::New
+      ]
+    );
+    assert_eq!(report_comparison(&baseline, &measurements, 10, false), 1);
+    assert_eq!(report_comparison(&baseline, &measurements, 25, false), 0);
+  }
+}
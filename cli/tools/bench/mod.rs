@@ -28,6 +28,7 @@ use deno_core::located_script_name;
 use deno_core::serde_v8;
 use deno_core::task::spawn;
 use deno_core::task::spawn_blocking;
+use deno_core::url::Url;
 use deno_core::v8;
 use deno_core::ModuleSpecifier;
 use deno_runtime::permissions::Permissions;
@@ -45,6 +46,11 @@ use std::sync::Arc;
 use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::UnboundedSender;
 
+mod baseline;
+
+use baseline::BaselineOptions;
+use baseline::BenchBaseline;
+
 #[derive(Debug, Clone)]
 struct BenchSpecifierOptions {
   filter: TestFilter,
@@ -512,6 +518,7 @@ async fn bench_specifiers(
   permissions: &Permissions,
   specifiers: Vec<ModuleSpecifier>,
   options: BenchSpecifierOptions,
+  baseline_options: BaselineOptions,
 ) -> Result<(), AnyError> {
   let (sender, mut receiver) = unbounded_channel::<BenchEvent>();
   let log_level = options.log_level;
@@ -590,6 +597,29 @@ async fn bench_specifiers(
 
       reporter.report_end(&report);
 
+      let mut regressions = 0;
+      if let Some(baseline) = &baseline_options.compare {
+        // the comparison would make the JSON output invalid
+        regressions = baseline::report_comparison(
+          baseline,
+          &report.measurements,
+          baseline_options.regression_threshold,
+          !options.json,
+        );
+      }
+
+      if let Some(mut baseline) = baseline_options.save {
+        for (desc, stats) in &report.measurements {
+          baseline.insert(desc, stats);
+        }
+        baseline.save()?;
+        log::info!(
+          "{} bench baseline \"{}\"",
+          colors::green("Saved"),
+          baseline.name()
+        );
+      }
+
       if used_only {
         return Err(generic_error(
           "Bench failed because the \"only\" option was used",
@@ -600,6 +630,15 @@ async fn bench_specifiers(
         return Err(generic_error("Bench failed"));
       }
 
+      if regressions > 0 {
+        return Err(generic_error(format!(
+          "Bench failed because {} {} regressed by more than {}% compared to the baseline",
+          regressions,
+          if regressions == 1 { "benchmark" } else { "benchmarks" },
+          baseline_options.regression_threshold,
+        )));
+      }
+
       Ok(())
     })
   };
@@ -659,6 +698,21 @@ pub async fn run_benchmarks(
     return Ok(());
   }
 
+  let baselines_dir = factory.deno_dir()?.bench_baselines_folder_path();
+  let cwd = Url::from_directory_path(cli_options.initial_cwd()).unwrap();
+  let baseline_options = BaselineOptions {
+    compare: bench_options
+      .compare
+      .as_ref()
+      .map(|name| BenchBaseline::load(&baselines_dir, name, cwd.clone()))
+      .transpose()?,
+    regression_threshold: bench_options.regression_threshold,
+    save: bench_options
+      .save_baseline
+      .as_ref()
+      .map(|name| BenchBaseline::new(&baselines_dir, name, cwd.clone())),
+  };
+
   let log_level = cli_options.log_level();
   let worker_factory =
     Arc::new(factory.create_cli_main_worker_factory().await?);
@@ -671,13 +725,14 @@ pub async fn run_benchmarks(
       json: bench_options.json,
       log_level,
     },
+    baseline_options,
   )
   .await?;
 
   Ok(())
 }
 
-// TODO(bartlomieju): heavy duplication of code with `cli/tools/test.rs`
+// TODO(bartlomieju): heavy duplication of code with `cli/tools/test/mod.rs`
 pub async fn run_benchmarks_with_watch(
   cli_options: CliOptions,
   bench_options: BenchOptions,
@@ -827,6 +882,9 @@ pub async fn run_benchmarks_with_watch(
           json: bench_options.json,
           log_level,
         },
+        // the baselines cover all benchmarks, but a rerun of the watcher
+        // only runs the changed modules
+        BaselineOptions::default(),
       )
       .await?;
 
@@ -860,7 +918,7 @@ mod mitata {
   use crate::colors;
   use std::str::FromStr;
 
-  fn fmt_duration(time: f64) -> String {
+  pub fn fmt_duration(time: f64) -> String {
     // SAFETY: this is safe since its just reformatting numbers
     unsafe {
       if time < 1e0 {
@@ -402,6 +402,18 @@ async fn resolve_shim_data(
     executable_args.push(seed.to_string());
   }
 
+  if let Some(dns_servers) = &flags.dns_servers {
+    executable_args.push(format!("--dns-server={}", dns_servers.join(",")));
+  }
+
+  if let Some(tls_name) = &flags.dns_over_https {
+    executable_args.push(format!("--dns-over-https={tls_name}"));
+  }
+
+  if let Some(fs_root) = &flags.fs_root {
+    executable_args.push(format!("--fs-root={}", cwd.join(fs_root).display()));
+  }
+
   if let Some(inspect) = flags.inspect {
     executable_args.push(format!("--inspect={inspect}"));
   }
@@ -0,0 +1,92 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::path::PathBuf;
+
+use deno_core::anyhow::Context;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+
+use crate::colors;
+
+/// Name of the directory of the kernel spec in the `kernels` directory of
+/// Jupyter.
+const KERNEL_NAME: &str = "deno";
+
+/// Gets the data directory of Jupyter, which kernel specs are installed
+/// into.
+/// <https://docs.jupyter.org/en/latest/use/jupyter-directories.html#data-files>
+fn jupyter_data_dir() -> Option<PathBuf> {
+  if let Some(data_dir) = std::env::var_os("JUPYTER_DATA_DIR") {
+    return Some(PathBuf::from(data_dir));
+  }
+  if cfg!(windows) {
+    std::env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("jupyter"))
+  } else {
+    let home_dir = std::env::var_os("HOME").map(PathBuf::from);
+    if cfg!(target_os = "macos") {
+      home_dir.map(|home| home.join("Library/Jupyter"))
+    } else {
+      std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home_dir.map(|home| home.join(".local/share")))
+        .map(|dir| dir.join("jupyter"))
+    }
+  }
+}
+
+fn kernel_spec_dir() -> Result<PathBuf, AnyError> {
+  jupyter_data_dir()
+    .map(|dir| dir.join("kernels").join(KERNEL_NAME))
+    .ok_or_else(|| generic_error("Could not find the Jupyter data directory."))
+}
+
+pub fn status() -> Result<(), AnyError> {
+  let kernel_spec_path = kernel_spec_dir()?.join("kernel.json");
+  if kernel_spec_path.exists() {
+    log::info!("Deno kernel is installed at {}", kernel_spec_path.display());
+  } else {
+    log::warn!(
+      "{} Deno kernel is not installed, run `deno jupyter --unstable --install` to install it",
+      colors::yellow("Warning")
+    );
+  }
+  Ok(())
+}
+
+pub fn install() -> Result<(), AnyError> {
+  let kernel_spec_dir = kernel_spec_dir()?;
+  std::fs::create_dir_all(&kernel_spec_dir).with_context(|| {
+    format!("Failed to create {}", kernel_spec_dir.display())
+  })?;
+
+  let current_exe = std::env::current_exe()?;
+  // Jupyter replaces `{connection_file}` with the path to the connection file
+  // when it starts the kernel.
+  let kernel_spec = json!({
+    "argv": [
+      current_exe.to_string_lossy(),
+      "jupyter",
+      "--unstable",
+      "--kernel",
+      "--conn",
+      "{connection_file}"
+    ],
+    "display_name": "Deno",
+    "language": "typescript",
+  });
+  let kernel_spec_path = kernel_spec_dir.join("kernel.json");
+  std::fs::write(
+    &kernel_spec_path,
+    serde_json::to_string_pretty(&kernel_spec)?,
+  )
+  .with_context(|| format!("Failed to write {}", kernel_spec_path.display()))?;
+
+  log::info!(
+    "{} Deno kernel to {}",
+    colors::green("Installed"),
+    kernel_spec_dir.display()
+  );
+  Ok(())
+}
@@ -0,0 +1,223 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Messages of the Jupyter messaging protocol and how they are sent over
+//! ZeroMQ sockets.
+//! <https://jupyter-client.readthedocs.io/en/latest/messaging.html>
+
+use bytes::Bytes;
+use deno_core::anyhow::bail;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use ring::hmac;
+use uuid::Uuid;
+use zeromq::SocketRecv;
+use zeromq::SocketSend;
+use zeromq::ZmqMessage;
+
+use crate::util::time::utc_now;
+
+/// Separates the ZeroMQ routing identities from the message frames.
+const DELIMITER: &[u8] = b"<IDS|MSG>";
+pub const PROTOCOL_VERSION: &str = "5.3";
+
+/// A socket of the kernel along with the key the messages sent over it are
+/// signed with.
+pub struct Connection<S> {
+  socket: S,
+  /// `None` if the connection file has an empty key, which disables the
+  /// signing of messages.
+  mac: Option<hmac::Key>,
+}
+
+impl<S> Connection<S> {
+  pub fn new(socket: S, key: &str) -> Self {
+    let mac = if key.is_empty() {
+      None
+    } else {
+      Some(hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes()))
+    };
+    Self { socket, mac }
+  }
+
+  fn sign(&self, frames: &[&[u8]]) -> String {
+    let Some(mac) = &self.mac else {
+      return String::new();
+    };
+    let mut context = hmac::Context::with_key(mac);
+    for frame in frames {
+      context.update(frame);
+    }
+    hex::encode(context.sign().as_ref())
+  }
+
+  fn verify(&self, signature: &[u8], frames: &[Bytes]) -> Result<(), AnyError> {
+    let Some(mac) = &self.mac else {
+      return Ok(());
+    };
+    let signature = hex::decode(signature)?;
+    hmac::verify(mac, &frames.concat(), &signature)
+      .map_err(|_| generic_error("Invalid signature of Jupyter message"))
+  }
+}
+
+impl<S: SocketRecv> Connection<S> {
+  pub async fn read(&mut self) -> Result<JupyterMessage, AnyError> {
+    let frames = self.socket.recv().await?.into_vec();
+    let Some(delimiter_index) =
+      frames.iter().position(|frame| frame.as_ref() == DELIMITER)
+    else {
+      bail!("Missing delimiter in Jupyter message");
+    };
+    let identities = frames[..delimiter_index].to_vec();
+    // signature, header, parent header, metadata, content and buffers
+    let frames = &frames[delimiter_index + 1..];
+    if frames.len() < 5 {
+      bail!("Incomplete Jupyter message");
+    }
+    self.verify(&frames[0], &frames[1..5])?;
+
+    Ok(JupyterMessage {
+      identities,
+      header: serde_json::from_slice(&frames[1])?,
+      parent_header: serde_json::from_slice(&frames[2])?,
+      metadata: serde_json::from_slice(&frames[3])?,
+      content: serde_json::from_slice(&frames[4])?,
+      buffers: frames[5..].to_vec(),
+    })
+  }
+}
+
+impl<S: SocketSend> Connection<S> {
+  pub async fn send(
+    &mut self,
+    message: &JupyterMessage,
+  ) -> Result<(), AnyError> {
+    let header = serde_json::to_vec(&message.header)?;
+    let parent_header = serde_json::to_vec(&message.parent_header)?;
+    let metadata = serde_json::to_vec(&message.metadata)?;
+    let content = serde_json::to_vec(&message.content)?;
+    let signature = self.sign(&[&header, &parent_header, &metadata, &content]);
+
+    let mut frames = message.identities.clone();
+    frames.push(Bytes::from_static(DELIMITER));
+    frames.push(signature.into());
+    frames.push(header.into());
+    frames.push(parent_header.into());
+    frames.push(metadata.into());
+    frames.push(content.into());
+    frames.extend(message.buffers.iter().cloned());
+    let message = ZmqMessage::try_from(frames).map_err(generic_error)?;
+    self.socket.send(message).await?;
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct JupyterMessage {
+  /// Routing identities of the client that sent the message, which replies
+  /// are sent back to.
+  identities: Vec<Bytes>,
+  header: Value,
+  parent_header: Value,
+  metadata: Value,
+  content: Value,
+  buffers: Vec<Bytes>,
+}
+
+impl JupyterMessage {
+  pub fn message_type(&self) -> &str {
+    self.header["msg_type"].as_str().unwrap_or("")
+  }
+
+  pub fn content(&self) -> &Value {
+    &self.content
+  }
+
+  /// Creates the reply to this request, ex. `execute_reply` for an
+  /// `execute_request`.
+  pub fn new_reply(&self) -> JupyterMessage {
+    let message_type = self.message_type().replace("_request", "_reply");
+    JupyterMessage {
+      identities: self.identities.clone(),
+      ..self.new_message(&message_type)
+    }
+  }
+
+  /// Creates a message of `message_type` that is published on the IOPub
+  /// socket as a side effect of this request.
+  pub fn new_message(&self, message_type: &str) -> JupyterMessage {
+    JupyterMessage {
+      identities: Vec::new(),
+      header: json!({
+        "msg_id": Uuid::new_v4().to_string(),
+        "session": self.header["session"].clone(),
+        "username": "kernel",
+        "date": utc_now().to_rfc3339(),
+        "msg_type": message_type,
+        "version": PROTOCOL_VERSION,
+      }),
+      parent_header: self.header.clone(),
+      metadata: json!({}),
+      content: json!({}),
+      buffers: Vec::new(),
+    }
+  }
+
+  pub fn with_content(mut self, content: Value) -> JupyterMessage {
+    self.content = content;
+    self
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn execute_request() -> JupyterMessage {
+    JupyterMessage {
+      identities: vec![Bytes::from_static(b"client")],
+      header: json!({
+        "msg_id": "1",
+        "session": "session",
+        "msg_type": "execute_request",
+      }),
+      parent_header: json!({}),
+      metadata: json!({}),
+      content: json!({ "code": "1 + 1" }),
+      buffers: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn reply_keeps_identities() {
+    let request = execute_request();
+    let reply = request.new_reply();
+    assert_eq!(reply.message_type(), "execute_reply");
+    assert_eq!(reply.identities, request.identities);
+    assert_eq!(reply.parent_header, request.header);
+    assert_eq!(reply.header["session"], "session");
+
+    let message = request.new_message("stream");
+    assert_eq!(message.message_type(), "stream");
+    assert!(message.identities.is_empty());
+    assert_eq!(message.parent_header, request.header);
+  }
+
+  #[test]
+  fn sign_and_verify() {
+    let connection = Connection::new((), "key");
+    let frames = [Bytes::from_static(b"{}"), Bytes::from_static(b"{}")];
+    let signature = connection.sign(&[b"{}", b"{}"]);
+    assert_eq!(signature.len(), 64);
+    assert!(connection.verify(signature.as_bytes(), &frames).is_ok());
+    assert!(connection.verify(b"00", &frames).is_err());
+
+    // an empty key disables signing
+    let connection = Connection::new((), "");
+    assert_eq!(connection.sign(&[b"{}"]), "");
+    assert!(connection.verify(b"", &frames).is_ok());
+  }
+}
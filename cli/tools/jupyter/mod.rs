@@ -0,0 +1,79 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::args::Flags;
+use crate::args::JupyterFlags;
+use crate::factory::CliFactory;
+use crate::ops;
+use crate::tools::repl::ReplSession;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::futures::channel::mpsc;
+use deno_core::serde_json;
+use deno_runtime::permissions::Permissions;
+use deno_runtime::permissions::PermissionsContainer;
+
+mod install;
+mod jupyter_msg;
+mod server;
+
+pub async fn kernel(
+  flags: Flags,
+  jupyter_flags: JupyterFlags,
+) -> Result<(), AnyError> {
+  if !flags.unstable {
+    eprintln!(
+      "Unstable subcommand 'deno jupyter'. The --unstable flag must be provided."
+    );
+    std::process::exit(70);
+  }
+
+  if jupyter_flags.install {
+    return install::install();
+  }
+
+  let Some(connection_filepath) = jupyter_flags.conn_file else {
+    return install::status();
+  };
+
+  let factory = CliFactory::from_flags(flags).await?;
+  let cli_options = factory.cli_options();
+  let main_module = cli_options.resolve_main_module()?;
+  // there is no terminal to prompt for permissions, so the code of notebooks
+  // runs with all permissions like it does in the kernels of other languages
+  let permissions = PermissionsContainer::new(Permissions::allow_all());
+  let npm_resolver = factory.npm_resolver().await?.clone();
+  let resolver = factory.resolver().await?.clone();
+  let worker_factory = factory.create_cli_main_worker_factory().await?;
+
+  let connection_file = std::fs::read_to_string(&connection_filepath)
+    .with_context(|| {
+      format!(
+        "Failed to read connection file: {}",
+        connection_filepath.display()
+      )
+    })?;
+  let spec: server::ConnectionSpec = serde_json::from_str(&connection_file)
+    .with_context(|| {
+      format!(
+        "Failed to parse connection file: {}",
+        connection_filepath.display()
+      )
+    })?;
+
+  let (stdio_tx, stdio_rx) = mpsc::unbounded();
+  let mut worker = worker_factory
+    .create_custom_worker(
+      main_module,
+      permissions,
+      vec![ops::jupyter::deno_jupyter::init_ops(stdio_tx)],
+      Default::default(),
+    )
+    .await?;
+  worker.setup_repl().await?;
+  let worker = worker.into_main_worker();
+  let repl_session =
+    ReplSession::initialize(cli_options, npm_resolver, resolver, worker)
+      .await?;
+
+  server::JupyterServer::start(spec, stdio_rx, repl_session).await
+}
@@ -0,0 +1,438 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::futures::channel::mpsc;
+use deno_core::futures::StreamExt;
+use deno_core::serde_json::json;
+use deno_core::serde_json::Value;
+use serde::Deserialize;
+use zeromq::Socket;
+use zeromq::SocketRecv;
+use zeromq::SocketSend;
+
+use super::jupyter_msg::Connection;
+use super::jupyter_msg::JupyterMessage;
+use super::jupyter_msg::PROTOCOL_VERSION;
+use crate::ops::jupyter::StdioMsg;
+use crate::tools::repl::cdp;
+use crate::tools::repl::ReplSession;
+use crate::tools::repl::TsEvaluateResponse;
+
+/// Parameters of the sockets of the kernel, which Jupyter provides in the
+/// connection file.
+/// <https://jupyter-client.readthedocs.io/en/latest/kernels.html#connection-files>
+#[derive(Debug, Deserialize)]
+pub struct ConnectionSpec {
+  ip: String,
+  transport: String,
+  control_port: u16,
+  shell_port: u16,
+  stdin_port: u16,
+  hb_port: u16,
+  iopub_port: u16,
+  signature_scheme: String,
+  key: String,
+}
+
+impl ConnectionSpec {
+  fn endpoint(&self, port: u16) -> String {
+    format!("{}://{}:{}", self.transport, self.ip, port)
+  }
+}
+
+async fn bind_socket<S: Socket>(
+  spec: &ConnectionSpec,
+  port: u16,
+) -> Result<S, AnyError> {
+  let endpoint = spec.endpoint(port);
+  let mut socket = S::new();
+  socket
+    .bind(&endpoint)
+    .await
+    .with_context(|| format!("Failed to bind socket to {endpoint}"))?;
+  Ok(socket)
+}
+
+pub struct JupyterServer {
+  execution_count: usize,
+  repl_session: ReplSession,
+  stdio_rx: mpsc::UnboundedReceiver<StdioMsg>,
+  iopub: Connection<zeromq::PubSocket>,
+  /// The parent of the stream messages of output that is written when no
+  /// request is handled, ex. by timers of a previous execution.
+  last_execute_request: Option<JupyterMessage>,
+}
+
+impl JupyterServer {
+  pub async fn start(
+    spec: ConnectionSpec,
+    stdio_rx: mpsc::UnboundedReceiver<StdioMsg>,
+    repl_session: ReplSession,
+  ) -> Result<(), AnyError> {
+    if spec.signature_scheme != "hmac-sha256" {
+      bail!("Unsupported signature scheme: {}", spec.signature_scheme);
+    }
+
+    let mut heartbeat =
+      bind_socket::<zeromq::RepSocket>(&spec, spec.hb_port).await?;
+    let mut shell = Connection::new(
+      bind_socket::<zeromq::RouterSocket>(&spec, spec.shell_port).await?,
+      &spec.key,
+    );
+    let mut control = Connection::new(
+      bind_socket::<zeromq::RouterSocket>(&spec, spec.control_port).await?,
+      &spec.key,
+    );
+    // input requests are not supported, but the socket is bound so that
+    // clients are able to connect to the kernel
+    let _stdin =
+      bind_socket::<zeromq::RouterSocket>(&spec, spec.stdin_port).await?;
+    let iopub = Connection::new(
+      bind_socket::<zeromq::PubSocket>(&spec, spec.iopub_port).await?,
+      &spec.key,
+    );
+
+    // the heartbeat is answered on its own task so that the kernel isn't
+    // considered dead while it evaluates code
+    deno_core::task::spawn(async move {
+      while let Ok(message) = heartbeat.recv().await {
+        if heartbeat.send(message).await.is_err() {
+          break;
+        }
+      }
+    });
+
+    let mut server = Self {
+      execution_count: 0,
+      repl_session,
+      stdio_rx,
+      iopub,
+      last_execute_request: None,
+    };
+    let mut poll_worker = true;
+
+    loop {
+      tokio::select! {
+        message = shell.read() => {
+          server.handle_shell_message(message?, &mut shell).await?;
+          poll_worker = true;
+        }
+        message = control.read() => {
+          let message = message?;
+          match message.message_type() {
+            "shutdown_request" => {
+              let restart = message.content()["restart"].as_bool();
+              control
+                .send(&message.new_reply().with_content(json!({
+                  "status": "ok",
+                  "restart": restart.unwrap_or(false),
+                })))
+                .await?;
+              return Ok(());
+            }
+            "interrupt_request" => {
+              // code is evaluated on the task that handles control messages,
+              // so there is no execution in progress to interrupt
+              control
+                .send(&message.new_reply().with_content(json!({
+                  "status": "ok",
+                })))
+                .await?;
+            }
+            message_type => {
+              log::debug!(
+                "Unhandled Jupyter control message: {}",
+                message_type
+              );
+            }
+          }
+        }
+        Some(msg) = server.stdio_rx.next() => {
+          if let Some(parent) = &server.last_execute_request {
+            send_stream(&mut server.iopub, parent, msg).await?;
+          }
+        }
+        _ = server.repl_session.run_event_loop(), if poll_worker => {
+          poll_worker = false;
+        }
+      }
+    }
+  }
+
+  async fn handle_shell_message(
+    &mut self,
+    message: JupyterMessage,
+    shell: &mut Connection<zeromq::RouterSocket>,
+  ) -> Result<(), AnyError> {
+    self.send_status(&message, "busy").await?;
+    let reply_content = match message.message_type() {
+      "kernel_info_request" => Some(kernel_info()),
+      "execute_request" => Some(self.handle_execute_request(&message).await?),
+      "complete_request" => Some(self.handle_complete_request(&message).await),
+      "is_complete_request" => Some(json!({ "status": "complete" })),
+      "inspect_request" => Some(json!({
+        "status": "ok",
+        "found": false,
+        "data": {},
+        "metadata": {},
+      })),
+      "history_request" => Some(json!({ "status": "ok", "history": [] })),
+      "comm_info_request" => Some(json!({ "status": "ok", "comms": {} })),
+      message_type => {
+        log::debug!("Unhandled Jupyter shell message: {}", message_type);
+        None
+      }
+    };
+    if let Some(content) = reply_content {
+      shell
+        .send(&message.new_reply().with_content(content))
+        .await?;
+    }
+    self.send_status(&message, "idle").await
+  }
+
+  async fn handle_execute_request(
+    &mut self,
+    message: &JupyterMessage,
+  ) -> Result<Value, AnyError> {
+    self.execution_count += 1;
+    self.last_execute_request = Some(message.clone());
+    let code = message.content()["code"].as_str().unwrap_or_default();
+    self
+      .iopub
+      .send(&message.new_message("execute_input").with_content(json!({
+        "code": code,
+        "execution_count": self.execution_count,
+      })))
+      .await?;
+
+    let evaluate_result = {
+      let evaluate_fut =
+        self.repl_session.evaluate_line_with_object_wrapping(code);
+      tokio::pin!(evaluate_fut);
+      // publish the output of the code while it runs
+      loop {
+        tokio::select! {
+          biased;
+          result = &mut evaluate_fut => break result,
+          Some(msg) = self.stdio_rx.next() => {
+            send_stream(&mut self.iopub, message, msg).await?;
+          }
+        }
+      }
+    };
+    self.flush_stdio(message).await?;
+
+    let TsEvaluateResponse {
+      ts_code,
+      value:
+        cdp::EvaluateResponse {
+          result,
+          exception_details,
+        },
+    } = match evaluate_result {
+      Ok(response) => response,
+      Err(err) => {
+        let evalue = format!("{err:#}");
+        let traceback = evalue.lines().map(String::from).collect();
+        return self
+          .execute_error(message, "Error", &evalue, traceback)
+          .await;
+      }
+    };
+
+    if let Some(exception_details) = exception_details {
+      self.repl_session.set_last_thrown_error(&result).await?;
+      let (ename, description) = match exception_details.exception {
+        Some(exception) => (
+          exception.class_name.unwrap_or_else(|| "Error".to_string()),
+          exception
+            .description
+            .or_else(|| exception.value.map(|value| value.to_string()))
+            .unwrap_or_else(|| "undefined".to_string()),
+        ),
+        None => ("Error".to_string(), "Unknown exception".to_string()),
+      };
+      let evalue = description.lines().next().unwrap_or_default().to_string();
+      let traceback = format!("{} {}", exception_details.text, description)
+        .lines()
+        .map(String::from)
+        .collect();
+      return self
+        .execute_error(message, &ename, &evalue, traceback)
+        .await;
+    }
+
+    self
+      .repl_session
+      .language_server
+      .commit_text(&ts_code)
+      .await;
+    self.repl_session.set_last_eval_result(&result).await?;
+
+    let silent = message.content()["silent"].as_bool().unwrap_or(false);
+    if !silent {
+      let display_result = self.repl_session.get_jupyter_display(&result).await;
+      self.flush_stdio(message).await?;
+      let data = match display_result {
+        Ok(data) => data,
+        Err(err) => {
+          let evalue = err.to_string();
+          return self
+            .execute_error(message, "Error", &evalue, vec![evalue.clone()])
+            .await;
+        }
+      };
+      if !data.is_empty() {
+        self
+          .iopub
+          .send(&message.new_message("execute_result").with_content(json!({
+            "execution_count": self.execution_count,
+            "data": data,
+            "metadata": {},
+          })))
+          .await?;
+      }
+    }
+
+    Ok(json!({
+      "status": "ok",
+      "execution_count": self.execution_count,
+      "payload": [],
+      "user_expressions": {},
+    }))
+  }
+
+  /// Publishes the error of a failed execution and gets the content of the
+  /// reply to it.
+  async fn execute_error(
+    &mut self,
+    message: &JupyterMessage,
+    ename: &str,
+    evalue: &str,
+    traceback: Vec<String>,
+  ) -> Result<Value, AnyError> {
+    self
+      .iopub
+      .send(&message.new_message("error").with_content(json!({
+        "ename": ename,
+        "evalue": evalue,
+        "traceback": traceback,
+      })))
+      .await?;
+    Ok(json!({
+      "status": "error",
+      "execution_count": self.execution_count,
+      "ename": ename,
+      "evalue": evalue,
+      "traceback": traceback,
+    }))
+  }
+
+  async fn handle_complete_request(
+    &mut self,
+    message: &JupyterMessage,
+  ) -> Value {
+    let code = message.content()["code"].as_str().unwrap_or_default();
+    let cursor_pos =
+      message.content()["cursor_pos"].as_u64().unwrap_or(0) as usize;
+    // Jupyter counts positions in unicode code points, while the language
+    // server uses byte offsets
+    let position = code
+      .char_indices()
+      .nth(cursor_pos)
+      .map(|(index, _)| index)
+      .unwrap_or(code.len());
+    let completions = self
+      .repl_session
+      .language_server
+      .completions(code, position)
+      .await;
+    // assumes all completions have the same range
+    let range = completions
+      .first()
+      .map(|completion| completion.range.clone())
+      .unwrap_or(position..position);
+    let to_cursor_pos = |offset: usize| {
+      code
+        .get(..offset)
+        .map(|text| text.chars().count())
+        .unwrap_or(cursor_pos)
+    };
+    json!({
+      "status": "ok",
+      "matches": completions
+        .into_iter()
+        .map(|completion| completion.new_text)
+        .collect::<Vec<_>>(),
+      "cursor_start": to_cursor_pos(range.start),
+      "cursor_end": to_cursor_pos(range.end),
+      "metadata": {},
+    })
+  }
+
+  async fn flush_stdio(
+    &mut self,
+    parent: &JupyterMessage,
+  ) -> Result<(), AnyError> {
+    while let Ok(Some(msg)) = self.stdio_rx.try_next() {
+      send_stream(&mut self.iopub, parent, msg).await?;
+    }
+    Ok(())
+  }
+
+  async fn send_status(
+    &mut self,
+    parent: &JupyterMessage,
+    execution_state: &str,
+  ) -> Result<(), AnyError> {
+    self
+      .iopub
+      .send(&parent.new_message("status").with_content(json!({
+        "execution_state": execution_state,
+      })))
+      .await
+  }
+}
+
+async fn send_stream(
+  iopub: &mut Connection<zeromq::PubSocket>,
+  parent: &JupyterMessage,
+  msg: StdioMsg,
+) -> Result<(), AnyError> {
+  let (name, text) = match msg {
+    StdioMsg::Stdout(text) => ("stdout", text),
+    StdioMsg::Stderr(text) => ("stderr", text),
+  };
+  iopub
+    .send(&parent.new_message("stream").with_content(json!({
+      "name": name,
+      "text": text,
+    })))
+    .await
+}
+
+fn kernel_info() -> Value {
+  json!({
+    "status": "ok",
+    "protocol_version": PROTOCOL_VERSION,
+    "implementation": "Deno kernel",
+    "implementation_version": crate::version::deno(),
+    "language_info": {
+      "name": "typescript",
+      "version": crate::version::TYPESCRIPT,
+      "mimetype": "text/x.typescript",
+      "file_extension": ".ts",
+      "pygments_lexer": "typescript",
+      "codemirror_mode": "typescript",
+      "nbconvert_exporter": "script",
+    },
+    "banner": "Welcome to Deno kernel",
+    "help_links": [{
+      "text": "Visit Deno manual",
+      "url": "https://deno.land/manual",
+    }],
+  })
+}
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+pub mod audit;
 pub mod bench;
 pub mod bundle;
 pub mod check;
@@ -10,6 +11,7 @@ pub mod fmt;
 pub mod info;
 pub mod init;
 pub mod installer;
+pub mod jupyter;
 pub mod lint;
 pub mod repl;
 pub mod run;
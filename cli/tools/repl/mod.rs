@@ -13,7 +13,7 @@ use deno_runtime::permissions::Permissions;
 use deno_runtime::permissions::PermissionsContainer;
 use rustyline::error::ReadlineError;
 
-mod cdp;
+pub mod cdp;
 mod channel;
 mod editor;
 mod session;
@@ -24,8 +24,9 @@ use channel::RustylineSyncMessageHandler;
 use channel::RustylineSyncResponse;
 use editor::EditorHelper;
 use editor::ReplEditor;
-use session::EvaluationOutput;
-use session::ReplSession;
+pub use session::EvaluationOutput;
+pub use session::ReplSession;
+pub use session::TsEvaluateResponse;
 
 async fn read_line_and_poll(
   repl_session: &mut ReplSession,
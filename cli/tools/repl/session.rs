@@ -17,6 +17,7 @@ use deno_ast::swc::visit::VisitWith;
 use deno_ast::DiagnosticsError;
 use deno_ast::ImportsNotUsedAsValues;
 use deno_ast::ModuleSpecifier;
+use deno_core::anyhow::bail;
 use deno_core::error::AnyError;
 use deno_core::futures::channel::mpsc::UnboundedReceiver;
 use deno_core::futures::FutureExt;
@@ -117,9 +118,9 @@ pub fn result_to_evaluation_output(
   }
 }
 
-struct TsEvaluateResponse {
-  ts_code: String,
-  value: cdp::EvaluateResponse,
+pub struct TsEvaluateResponse {
+  pub ts_code: String,
+  pub value: cdp::EvaluateResponse,
 }
 
 pub struct ReplSession {
@@ -310,7 +311,7 @@ impl ReplSession {
     result_to_evaluation_output(result)
   }
 
-  async fn evaluate_line_with_object_wrapping(
+  pub async fn evaluate_line_with_object_wrapping(
     &mut self,
     line: &str,
   ) -> Result<TsEvaluateResponse, AnyError> {
@@ -344,7 +345,7 @@ impl ReplSession {
     }
   }
 
-  async fn set_last_thrown_error(
+  pub async fn set_last_thrown_error(
     &mut self,
     error: &cdp::RemoteObject,
   ) -> Result<(), AnyError> {
@@ -372,7 +373,7 @@ impl ReplSession {
     Ok(())
   }
 
-  async fn set_last_eval_result(
+  pub async fn set_last_eval_result(
     &mut self,
     evaluate_result: &cdp::RemoteObject,
   ) -> Result<(), AnyError> {
@@ -443,6 +444,62 @@ impl ReplSession {
     Ok(s.to_string())
   }
 
+  /// Gets the MIME bundle that a Jupyter notebook displays for the result
+  /// of an evaluation. Values can provide their own bundle with a method
+  /// keyed by `Symbol.for("Jupyter.display")`, otherwise the inspected value
+  /// is displayed as plain text.
+  pub async fn get_jupyter_display(
+    &mut self,
+    evaluate_result: &cdp::RemoteObject,
+  ) -> Result<serde_json::Map<String, Value>, AnyError> {
+    let response = self
+      .post_message_with_event_loop(
+        "Runtime.callFunctionOn",
+        Some(cdp::CallFunctionOnArgs {
+          function_declaration: format!(
+            r#"async function (object) {{
+          if (object === undefined) {{
+            return {{}};
+          }}
+          const display = object?.[Symbol.for("Jupyter.display")];
+          if (typeof display === "function") {{
+            return await display.call(object);
+          }}
+          return {{
+            "text/plain": {0}.inspectArgs(["%o", object], {{ colors: !{0}.noColor }}),
+          }};
+        }}"#,
+            *REPL_INTERNALS_NAME
+          ),
+          object_id: None,
+          arguments: Some(vec![evaluate_result.into()]),
+          silent: None,
+          return_by_value: Some(true),
+          generate_preview: None,
+          user_gesture: None,
+          await_promise: Some(true),
+          execution_context_id: Some(self.context_id),
+          object_group: None,
+          throw_on_side_effect: None,
+        }),
+      )
+      .await?;
+
+    let response: cdp::CallFunctionOnResponse =
+      serde_json::from_value(response)?;
+    if let Some(exception_details) = response.exception_details {
+      let description = exception_details
+        .exception
+        .and_then(|exception| exception.description)
+        .unwrap_or(exception_details.text);
+      bail!("Failed to display value: {}", description);
+    }
+    match response.result.value {
+      Some(Value::Object(bundle)) => Ok(bundle),
+      _ => bail!("Expected display to return an object of MIME types"),
+    }
+  }
+
   async fn evaluate_ts_expression(
     &mut self,
     expression: &str,
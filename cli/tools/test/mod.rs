@@ -3,6 +3,8 @@
 use crate::args::CliOptions;
 use crate::args::FilesConfig;
 use crate::args::TestOptions;
+use crate::args::TestReporterConfig;
+use crate::args::TestShard;
 use crate::colors;
 use crate::display;
 use crate::factory::CliFactory;
@@ -23,6 +25,7 @@ use crate::worker::CliMainWorkerFactory;
 use deno_ast::swc::common::comments::CommentKind;
 use deno_ast::MediaType;
 use deno_ast::SourceRangedForSpanned;
+use deno_core::anyhow::Context as _;
 use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::error::JsError;
@@ -33,6 +36,7 @@ use deno_core::futures::FutureExt;
 use deno_core::futures::StreamExt;
 use deno_core::located_script_name;
 use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
 use deno_core::serde_v8;
 use deno_core::task::spawn;
 use deno_core::task::spawn_blocking;
@@ -76,6 +80,17 @@ use tokio::sync::mpsc::unbounded_channel;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::mpsc::WeakUnboundedSender;
 
+mod reporters;
+mod snapshot;
+
+pub use snapshot::SnapshotFile;
+
+use reporters::CompoundTestReporter;
+use reporters::JunitTestReporter;
+use reporters::PrettyTestReporter;
+use reporters::TapTestReporter;
+use reporters::TestReporter;
+
 /// The test mode is used to determine how a specifier is to be tested.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TestMode {
@@ -94,6 +109,11 @@ pub struct TestFilter {
   pub regex: Option<Regex>,
   pub include: Option<Vec<String>>,
   pub exclude: Vec<String>,
+  /// Only the tests that have at least one of these tags are included, unless
+  /// it's empty.
+  pub tags: Vec<String>,
+  /// The tests that have any of these tags are excluded.
+  pub exclude_tags: Vec<String>,
 }
 
 impl TestFilter {
@@ -119,6 +139,13 @@ impl TestFilter {
     true
   }
 
+  pub fn includes_tags(&self, tags: &[String]) -> bool {
+    if !self.tags.is_empty() && !tags.iter().any(|t| self.tags.contains(t)) {
+      return false;
+    }
+    !tags.iter().any(|t| self.exclude_tags.contains(t))
+  }
+
   pub fn from_flag(flag: &Option<String>) -> Self {
     let mut substring = None;
     let mut regex = None;
@@ -156,6 +183,10 @@ pub struct TestDescription {
   pub only: bool,
   pub origin: String,
   pub location: TestLocation,
+  /// The number of times the test is run again after failing, which overrides
+  /// `--retries` for this test.
+  pub retries: Option<usize>,
+  pub tags: Vec<String>,
 }
 
 impl TestDescription {
@@ -266,6 +297,8 @@ pub enum TestResult {
   Ignored,
   Failed(TestFailure),
   Cancelled,
+  /// The test passed after failing the given number of attempts.
+  Flaky(usize),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
@@ -317,6 +350,8 @@ pub enum TestEvent {
   Wait(usize),
   Output(Vec<u8>),
   Result(usize, TestResult, u64),
+  /// A failed attempt of a test that is run again.
+  Retry(usize, TestFailure, u64),
   UncaughtError(String, Box<JsError>),
   StepRegister(TestStepDescription),
   StepWait(usize),
@@ -337,6 +372,9 @@ pub struct TestSummary {
   pub measured: usize,
   pub failures: Vec<(TestDescription, TestFailure)>,
   pub uncaught_errors: Vec<(String, Box<JsError>)>,
+  /// The tests that passed after failing, along with the number of failed
+  /// attempts.
+  pub flaky: Vec<(TestDescription, usize)>,
 }
 
 #[derive(Debug, Clone)]
@@ -345,6 +383,10 @@ struct TestSpecifiersOptions {
   fail_fast: Option<NonZeroUsize>,
   log_level: Option<log::Level>,
   specifier: TestSpecifierOptions,
+  shard_timings: Option<ShardTimings>,
+  reporter: TestReporterConfig,
+  junit_path: Option<PathBuf>,
+  fail_on_flaky: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -352,6 +394,8 @@ pub struct TestSpecifierOptions {
   pub shuffle: Option<u64>,
   pub filter: TestFilter,
   pub trace_ops: bool,
+  pub update_snapshots: bool,
+  pub retries: usize,
 }
 
 impl TestSummary {
@@ -368,6 +412,7 @@ impl TestSummary {
       measured: 0,
       failures: Vec::new(),
       uncaught_errors: Vec::new(),
+      flaky: Vec::new(),
     }
   }
 
@@ -376,488 +421,6 @@ impl TestSummary {
   }
 }
 
-struct PrettyTestReporter {
-  parallel: bool,
-  echo_output: bool,
-  in_new_line: bool,
-  scope_test_id: Option<usize>,
-  cwd: Url,
-  did_have_user_output: bool,
-  started_tests: bool,
-  child_results_buffer:
-    HashMap<usize, IndexMap<usize, (TestStepDescription, TestStepResult, u64)>>,
-}
-
-impl PrettyTestReporter {
-  fn new(parallel: bool, echo_output: bool) -> PrettyTestReporter {
-    PrettyTestReporter {
-      parallel,
-      echo_output,
-      in_new_line: true,
-      scope_test_id: None,
-      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
-      did_have_user_output: false,
-      started_tests: false,
-      child_results_buffer: Default::default(),
-    }
-  }
-
-  fn force_report_wait(&mut self, description: &TestDescription) {
-    if !self.in_new_line {
-      println!();
-    }
-    if self.parallel {
-      print!(
-        "{}",
-        colors::gray(format!(
-          "{} => ",
-          self.to_relative_path_or_remote_url(&description.origin)
-        ))
-      );
-    }
-    print!("{} ...", description.name);
-    self.in_new_line = false;
-    // flush for faster feedback when line buffered
-    std::io::stdout().flush().unwrap();
-    self.scope_test_id = Some(description.id);
-  }
-
-  fn to_relative_path_or_remote_url(&self, path_or_url: &str) -> String {
-    let url = Url::parse(path_or_url).unwrap();
-    if url.scheme() == "file" {
-      if let Some(mut r) = self.cwd.make_relative(&url) {
-        if !r.starts_with("../") {
-          r = format!("./{r}");
-        }
-        return r;
-      }
-    }
-    path_or_url.to_string()
-  }
-
-  fn force_report_step_wait(&mut self, description: &TestStepDescription) {
-    self.write_output_end();
-    if !self.in_new_line {
-      println!();
-    }
-    print!("{}{} ...", "  ".repeat(description.level), description.name);
-    self.in_new_line = false;
-    // flush for faster feedback when line buffered
-    std::io::stdout().flush().unwrap();
-    self.scope_test_id = Some(description.id);
-  }
-
-  fn force_report_step_result(
-    &mut self,
-    description: &TestStepDescription,
-    result: &TestStepResult,
-    elapsed: u64,
-  ) {
-    self.write_output_end();
-    if self.in_new_line || self.scope_test_id != Some(description.id) {
-      self.force_report_step_wait(description);
-    }
-
-    if !self.parallel {
-      let child_results = self
-        .child_results_buffer
-        .remove(&description.id)
-        .unwrap_or_default();
-      for (desc, result, elapsed) in child_results.values() {
-        self.force_report_step_result(desc, result, *elapsed);
-      }
-      if !child_results.is_empty() {
-        self.force_report_step_wait(description);
-      }
-    }
-
-    let status = match &result {
-      TestStepResult::Ok => colors::green("ok").to_string(),
-      TestStepResult::Ignored => colors::yellow("ignored").to_string(),
-      TestStepResult::Failed(failure) => failure.format_label(),
-    };
-    print!(" {}", status);
-    if let TestStepResult::Failed(failure) = result {
-      if let Some(inline_summary) = failure.format_inline_summary() {
-        print!(" ({})", inline_summary)
-      }
-    }
-    if !matches!(result, TestStepResult::Failed(TestFailure::Incomplete)) {
-      print!(
-        " {}",
-        colors::gray(format!("({})", display::human_elapsed(elapsed.into())))
-      );
-    }
-    println!();
-    self.in_new_line = true;
-    if self.parallel {
-      self.scope_test_id = None;
-    } else {
-      self.scope_test_id = Some(description.parent_id);
-    }
-    self
-      .child_results_buffer
-      .entry(description.parent_id)
-      .or_default()
-      .remove(&description.id);
-  }
-
-  fn write_output_end(&mut self) {
-    if self.did_have_user_output {
-      println!("{}", colors::gray("----- output end -----"));
-      self.in_new_line = true;
-      self.did_have_user_output = false;
-    }
-  }
-
-  fn report_register(&mut self, _description: &TestDescription) {}
-
-  fn report_plan(&mut self, plan: &TestPlan) {
-    if self.parallel {
-      return;
-    }
-    let inflection = if plan.total == 1 { "test" } else { "tests" };
-    println!(
-      "{}",
-      colors::gray(format!(
-        "running {} {} from {}",
-        plan.total,
-        inflection,
-        self.to_relative_path_or_remote_url(&plan.origin)
-      ))
-    );
-    self.in_new_line = true;
-  }
-
-  fn report_wait(&mut self, description: &TestDescription) {
-    if !self.parallel {
-      self.force_report_wait(description);
-    }
-    self.started_tests = true;
-  }
-
-  fn report_output(&mut self, output: &[u8]) {
-    if !self.echo_output {
-      return;
-    }
-
-    if !self.did_have_user_output && self.started_tests {
-      self.did_have_user_output = true;
-      if !self.in_new_line {
-        println!();
-      }
-      println!("{}", colors::gray("------- output -------"));
-      self.in_new_line = true;
-    }
-
-    // output everything to stdout in order to prevent
-    // stdout and stderr racing
-    std::io::stdout().write_all(output).unwrap();
-  }
-
-  fn report_result(
-    &mut self,
-    description: &TestDescription,
-    result: &TestResult,
-    elapsed: u64,
-  ) {
-    if self.parallel {
-      self.force_report_wait(description);
-    }
-
-    self.write_output_end();
-    if self.in_new_line || self.scope_test_id != Some(description.id) {
-      self.force_report_wait(description);
-    }
-
-    let status = match result {
-      TestResult::Ok => colors::green("ok").to_string(),
-      TestResult::Ignored => colors::yellow("ignored").to_string(),
-      TestResult::Failed(failure) => failure.format_label(),
-      TestResult::Cancelled => colors::gray("cancelled").to_string(),
-    };
-    print!(" {}", status);
-    if let TestResult::Failed(failure) = result {
-      if let Some(inline_summary) = failure.format_inline_summary() {
-        print!(" ({})", inline_summary)
-      }
-    }
-    println!(
-      " {}",
-      colors::gray(format!("({})", display::human_elapsed(elapsed.into())))
-    );
-    self.in_new_line = true;
-    self.scope_test_id = None;
-  }
-
-  fn report_uncaught_error(&mut self, origin: &str, _error: &JsError) {
-    if !self.in_new_line {
-      println!();
-    }
-    println!(
-      "Uncaught error from {} {}",
-      self.to_relative_path_or_remote_url(origin),
-      colors::red("FAILED")
-    );
-    self.in_new_line = true;
-    self.did_have_user_output = false;
-  }
-
-  fn report_step_register(&mut self, _description: &TestStepDescription) {}
-
-  fn report_step_wait(&mut self, description: &TestStepDescription) {
-    if !self.parallel && self.scope_test_id == Some(description.parent_id) {
-      self.force_report_step_wait(description);
-    }
-  }
-
-  fn report_step_result(
-    &mut self,
-    desc: &TestStepDescription,
-    result: &TestStepResult,
-    elapsed: u64,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) {
-    if self.parallel {
-      self.write_output_end();
-      print!(
-        "{} {} ...",
-        colors::gray(format!(
-          "{} =>",
-          self.to_relative_path_or_remote_url(&desc.origin)
-        )),
-        self.format_test_step_ancestry(desc, tests, test_steps)
-      );
-      self.in_new_line = false;
-      self.scope_test_id = Some(desc.id);
-      self.force_report_step_result(desc, result, elapsed);
-    } else {
-      let sibling_results =
-        self.child_results_buffer.entry(desc.parent_id).or_default();
-      if self.scope_test_id == Some(desc.id)
-        || self.scope_test_id == Some(desc.parent_id)
-      {
-        let sibling_results = std::mem::take(sibling_results);
-        self.force_report_step_result(desc, result, elapsed);
-        // Flush buffered sibling results.
-        for (desc, result, elapsed) in sibling_results.values() {
-          self.force_report_step_result(desc, result, *elapsed);
-        }
-      } else {
-        sibling_results
-          .insert(desc.id, (desc.clone(), result.clone(), elapsed));
-      }
-    }
-  }
-
-  fn report_summary(&mut self, summary: &TestSummary, elapsed: &Duration) {
-    if !summary.failures.is_empty() || !summary.uncaught_errors.is_empty() {
-      #[allow(clippy::type_complexity)] // Type alias doesn't look better here
-      let mut failures_by_origin: BTreeMap<
-        String,
-        (Vec<(&TestDescription, &TestFailure)>, Option<&JsError>),
-      > = BTreeMap::default();
-      let mut failure_titles = vec![];
-      for (description, failure) in &summary.failures {
-        let (failures, _) = failures_by_origin
-          .entry(description.origin.clone())
-          .or_default();
-        failures.push((description, failure));
-      }
-      for (origin, js_error) in &summary.uncaught_errors {
-        let (_, uncaught_error) =
-          failures_by_origin.entry(origin.clone()).or_default();
-        let _ = uncaught_error.insert(js_error.as_ref());
-      }
-      // note: the trailing whitespace is intentional to get a red background
-      println!("\n{}\n", colors::white_bold_on_red(" ERRORS "));
-      for (origin, (failures, uncaught_error)) in failures_by_origin {
-        for (description, failure) in failures {
-          if !failure.hide_in_summary() {
-            let failure_title = self.format_test_for_summary(description);
-            println!("{}", &failure_title);
-            println!("{}: {}", colors::red_bold("error"), failure.to_string());
-            println!();
-            failure_titles.push(failure_title);
-          }
-        }
-        if let Some(js_error) = uncaught_error {
-          let failure_title = format!(
-            "{} (uncaught error)",
-            self.to_relative_path_or_remote_url(&origin)
-          );
-          println!("{}", &failure_title);
-          println!(
-            "{}: {}",
-            colors::red_bold("error"),
-            format_test_error(js_error)
-          );
-          println!("This error was not caught from a test and caused the test runner to fail on the referenced module.");
-          println!("It most likely originated from a dangling promise, event/timeout handler or top-level code.");
-          println!();
-          failure_titles.push(failure_title);
-        }
-      }
-      // note: the trailing whitespace is intentional to get a red background
-      println!("{}\n", colors::white_bold_on_red(" FAILURES "));
-      for failure_title in failure_titles {
-        println!("{failure_title}");
-      }
-    }
-
-    let status = if summary.has_failed() {
-      colors::red("FAILED").to_string()
-    } else {
-      colors::green("ok").to_string()
-    };
-
-    let get_steps_text = |count: usize| -> String {
-      if count == 0 {
-        String::new()
-      } else if count == 1 {
-        " (1 step)".to_string()
-      } else {
-        format!(" ({count} steps)")
-      }
-    };
-
-    let mut summary_result = String::new();
-
-    write!(
-      summary_result,
-      "{} passed{} | {} failed{}",
-      summary.passed,
-      get_steps_text(summary.passed_steps),
-      summary.failed,
-      get_steps_text(summary.failed_steps),
-    )
-    .unwrap();
-
-    let ignored_steps = get_steps_text(summary.ignored_steps);
-    if summary.ignored > 0 || !ignored_steps.is_empty() {
-      write!(
-        summary_result,
-        " | {} ignored{}",
-        summary.ignored, ignored_steps
-      )
-      .unwrap()
-    }
-
-    if summary.measured > 0 {
-      write!(summary_result, " | {} measured", summary.measured,).unwrap();
-    }
-
-    if summary.filtered_out > 0 {
-      write!(summary_result, " | {} filtered out", summary.filtered_out)
-        .unwrap()
-    };
-
-    println!(
-      "\n{} | {} {}\n",
-      status,
-      summary_result,
-      colors::gray(format!(
-        "({})",
-        display::human_elapsed(elapsed.as_millis())
-      )),
-    );
-    self.in_new_line = true;
-  }
-
-  fn report_sigint(
-    &mut self,
-    tests_pending: &HashSet<usize>,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) {
-    if tests_pending.is_empty() {
-      return;
-    }
-    let mut formatted_pending = BTreeSet::new();
-    for id in tests_pending {
-      if let Some(desc) = tests.get(id) {
-        formatted_pending.insert(self.format_test_for_summary(desc));
-      }
-      if let Some(desc) = test_steps.get(id) {
-        formatted_pending
-          .insert(self.format_test_step_for_summary(desc, tests, test_steps));
-      }
-    }
-    println!(
-      "\n{} The following tests were pending:\n",
-      colors::intense_blue("SIGINT")
-    );
-    for entry in formatted_pending {
-      println!("{}", entry);
-    }
-    println!();
-    self.in_new_line = true;
-  }
-
-  fn format_test_step_ancestry(
-    &self,
-    desc: &TestStepDescription,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) -> String {
-    let root;
-    let mut ancestor_names = vec![];
-    let mut current_desc = desc;
-    loop {
-      if let Some(step_desc) = test_steps.get(&current_desc.parent_id) {
-        ancestor_names.push(&step_desc.name);
-        current_desc = step_desc;
-      } else {
-        root = tests.get(&current_desc.parent_id).unwrap();
-        break;
-      }
-    }
-    ancestor_names.reverse();
-    let mut result = String::new();
-    result.push_str(&root.name);
-    result.push_str(" ... ");
-    for name in ancestor_names {
-      result.push_str(name);
-      result.push_str(" ... ");
-    }
-    result.push_str(&desc.name);
-    result
-  }
-
-  fn format_test_for_summary(&self, desc: &TestDescription) -> String {
-    format!(
-      "{} {}",
-      &desc.name,
-      colors::gray(format!(
-        "=> {}:{}:{}",
-        self.to_relative_path_or_remote_url(&desc.location.file_name),
-        desc.location.line_number,
-        desc.location.column_number
-      ))
-    )
-  }
-
-  fn format_test_step_for_summary(
-    &self,
-    desc: &TestStepDescription,
-    tests: &IndexMap<usize, TestDescription>,
-    test_steps: &IndexMap<usize, TestStepDescription>,
-  ) -> String {
-    let long_name = self.format_test_step_ancestry(desc, tests, test_steps);
-    format!(
-      "{} {}",
-      long_name,
-      colors::gray(format!(
-        "=> {}:{}:{}",
-        self.to_relative_path_or_remote_url(&desc.location.file_name),
-        desc.location.line_number,
-        desc.location.column_number
-      ))
-    )
-  }
-}
-
 fn abbreviate_test_error(js_error: &JsError) -> JsError {
   let mut js_error = js_error.clone();
   let frames = std::mem::take(&mut js_error.frames);
@@ -935,7 +498,10 @@ pub async fn test_specifier(
     .create_custom_worker(
       specifier.clone(),
       PermissionsContainer::new(permissions),
-      vec![ops::testing::deno_test::init_ops(sender.clone())],
+      vec![ops::testing::deno_test::init_ops(
+        sender.clone(),
+        options.update_snapshots,
+      )],
       Stdio {
         stdin: StdioPipe::Inherit,
         stdout,
@@ -977,13 +543,20 @@ pub async fn test_specifier(
     std::mem::take(&mut state.borrow_mut::<ops::testing::TestContainer>().0)
   };
   let unfiltered = tests.len();
+  let registered_tests = tests
+    .iter()
+    .map(|(desc, _)| desc.name.clone())
+    .collect::<HashSet<_>>();
+  let mut ran_tests = HashSet::new();
   let (only, no_only): (Vec<_>, Vec<_>) =
     tests.into_iter().partition(|(d, _)| d.only);
   let used_only = !only.is_empty();
   let tests = if used_only { only } else { no_only };
   let mut tests = tests
     .into_iter()
-    .filter(|(d, _)| options.filter.includes(&d.name))
+    .filter(|(d, _)| {
+      options.filter.includes(&d.name) && options.filter.includes_tags(&d.tags)
+    })
     .collect::<Vec<_>>();
   if let Some(seed) = options.shuffle {
     tests.shuffle(&mut SmallRng::seed_from_u64(seed));
@@ -995,7 +568,7 @@ pub async fn test_specifier(
     used_only,
   }))?;
   let mut had_uncaught_error = false;
-  for (desc, function) in tests {
+  'tests: for (desc, function) in tests {
     if fail_fast_tracker.should_stop() {
       break;
     }
@@ -1008,47 +581,78 @@ pub async fn test_specifier(
       continue;
     }
     sender.send(TestEvent::Wait(desc.id))?;
+    ran_tests.insert(desc.name.clone());
 
-    // TODO(bartlomieju): this is a nasty (beautiful) hack, that was required
-    // when switching `JsRuntime` from `FuturesUnordered` to `JoinSet`. With
-    // `JoinSet` all pending ops are immediately polled and that caused a problem
-    // when some async ops were fired and canceled before running tests (giving
-    // false positives in the ops sanitizer). We should probably rewrite sanitizers
-    // to be done in Rust instead of in JS (40_testing.js).
-    {
-      // Poll event loop once, this will allow all ops that are already resolved,
-      // but haven't responded to settle.
-      let waker = noop_waker();
-      let mut cx = Context::from_waker(&waker);
-      let _ = worker.js_runtime.poll_event_loop(&mut cx, false);
-    }
+    let retries = desc.retries.unwrap_or(options.retries);
+    let mut failed_attempts = 0;
+    loop {
+      // TODO(bartlomieju): this is a nasty (beautiful) hack, that was required
+      // when switching `JsRuntime` from `FuturesUnordered` to `JoinSet`. With
+      // `JoinSet` all pending ops are immediately polled and that caused a problem
+      // when some async ops were fired and canceled before running tests (giving
+      // false positives in the ops sanitizer). We should probably rewrite sanitizers
+      // to be done in Rust instead of in JS (40_testing.js).
+      {
+        // Poll event loop once, this will allow all ops that are already resolved,
+        // but haven't responded to settle.
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let _ = worker.js_runtime.poll_event_loop(&mut cx, false);
+      }
 
-    let earlier = SystemTime::now();
-    let result = match worker.js_runtime.call_and_await(&function).await {
-      Ok(r) => r,
-      Err(error) => {
-        if error.is::<JsError>() {
-          sender.send(TestEvent::UncaughtError(
-            specifier.to_string(),
-            Box::new(error.downcast::<JsError>().unwrap()),
-          ))?;
-          fail_fast_tracker.add_failure();
-          sender.send(TestEvent::Result(desc.id, TestResult::Cancelled, 0))?;
-          had_uncaught_error = true;
+      let earlier = SystemTime::now();
+      let result = match worker.js_runtime.call_and_await(&function).await {
+        Ok(r) => r,
+        Err(error) => {
+          if error.is::<JsError>() {
+            sender.send(TestEvent::UncaughtError(
+              specifier.to_string(),
+              Box::new(error.downcast::<JsError>().unwrap()),
+            ))?;
+            fail_fast_tracker.add_failure();
+            sender.send(TestEvent::Result(
+              desc.id,
+              TestResult::Cancelled,
+              0,
+            ))?;
+            had_uncaught_error = true;
+            continue 'tests;
+          } else {
+            return Err(error);
+          }
+        }
+      };
+      let result = {
+        let scope = &mut worker.js_runtime.handle_scope();
+        let result = v8::Local::new(scope, result);
+        serde_v8::from_v8::<TestResult>(scope, result)?
+      };
+      let elapsed =
+        SystemTime::now().duration_since(earlier)?.as_millis() as u64;
+      let result = match result {
+        // only the failed tests are run again
+        TestResult::Failed(failure) if failed_attempts < retries => {
+          failed_attempts += 1;
+          sender.send(TestEvent::Retry(desc.id, failure, elapsed))?;
+          let state_rc = worker.js_runtime.op_state();
+          let mut state = state_rc.borrow_mut();
+          let snapshots = state.borrow_mut::<ops::testing::SnapshotState>();
+          if let Some(snapshots) = &mut snapshots.file {
+            snapshots.reset_test(&desc.name);
+          }
           continue;
-        } else {
-          return Err(error);
         }
+        TestResult::Ok if failed_attempts > 0 => {
+          TestResult::Flaky(failed_attempts)
+        }
+        result => result,
+      };
+      if matches!(result, TestResult::Failed(_)) {
+        fail_fast_tracker.add_failure();
       }
-    };
-    let scope = &mut worker.js_runtime.handle_scope();
-    let result = v8::Local::new(scope, result);
-    let result = serde_v8::from_v8::<TestResult>(scope, result)?;
-    if matches!(result, TestResult::Failed(_)) {
-      fail_fast_tracker.add_failure();
+      sender.send(TestEvent::Result(desc.id, result, elapsed))?;
+      break;
     }
-    let elapsed = SystemTime::now().duration_since(earlier)?.as_millis();
-    sender.send(TestEvent::Result(desc.id, result, elapsed as u64))?;
   }
 
   // Ignore `defaultPrevented` of the `beforeunload` event. We don't allow the
@@ -1056,6 +660,29 @@ pub async fn test_specifier(
   worker.dispatch_beforeunload_event(located_script_name!())?;
   worker.dispatch_unload_event(located_script_name!())?;
 
+  if options.update_snapshots {
+    let snapshots = {
+      let state_rc = worker.js_runtime.op_state();
+      let mut state = state_rc.borrow_mut();
+      state
+        .borrow_mut::<ops::testing::SnapshotState>()
+        .file
+        .take()
+    };
+    // the snapshot file is loaded even if no test asserted a snapshot, so
+    // the snapshots of the tests that no longer assert any are removed
+    let snapshots = match snapshots {
+      Some(snapshots) => Some(snapshots),
+      None if specifier.scheme() == "file" => {
+        Some(SnapshotFile::load(&specifier, true)?)
+      }
+      None => None,
+    };
+    if let Some(snapshots) = snapshots {
+      snapshots.save(&registered_tests, &ran_tests)?;
+    }
+  }
+
   if let Some(coverage_collector) = coverage_collector.as_mut() {
     worker
       .with_event_loop(coverage_collector.stop_collecting().boxed_local())
@@ -1313,6 +940,25 @@ pub async fn check_specifiers(
 static HAS_TEST_RUN_SIGINT_HANDLER: AtomicBool = AtomicBool::new(false);
 
 /// Test a collection of specifiers with test modes concurrently.
+fn get_test_reporter(options: &TestSpecifiersOptions) -> Box<dyn TestReporter> {
+  let parallel = options.concurrent_jobs.get() > 1;
+  let reporter: Box<dyn TestReporter> = match &options.reporter {
+    TestReporterConfig::Pretty => Box::new(PrettyTestReporter::new(
+      parallel,
+      options.log_level != Some(Level::Error),
+    )),
+    TestReporterConfig::Junit => Box::new(JunitTestReporter::new(None)),
+    TestReporterConfig::Tap => Box::new(TapTestReporter::new(parallel)),
+  };
+
+  if let Some(junit_path) = &options.junit_path {
+    let junit = Box::new(JunitTestReporter::new(Some(junit_path.clone())));
+    return Box::new(CompoundTestReporter::new(vec![reporter, junit]));
+  }
+
+  reporter
+}
+
 async fn test_specifiers(
   worker_factory: Arc<CliMainWorkerFactory>,
   permissions: &Permissions,
@@ -1332,6 +978,8 @@ async fn test_specifiers(
   let (sender, mut receiver) = unbounded_channel::<TestEvent>();
   let sender = TestEventSender::new(sender);
   let concurrent_jobs = options.concurrent_jobs;
+  let mut reporter = get_test_reporter(&options);
+  let fail_on_flaky = options.fail_on_flaky;
 
   let sender_ = sender.downgrade();
   let sigint_handler_handle = spawn(async move {
@@ -1347,25 +995,27 @@ async fn test_specifiers(
     let fail_fast_tracker = FailFastTracker::new(options.fail_fast);
     let specifier_options = options.specifier.clone();
     spawn_blocking(move || {
+      let started = Instant::now();
       create_and_run_current_thread(test_specifier(
         worker_factory,
         permissions,
-        specifier,
+        specifier.clone(),
         sender.clone(),
         fail_fast_tracker,
         specifier_options,
-      ))
+      ))?;
+      Ok::<_, AnyError>((specifier, started.elapsed()))
     })
   });
 
   let join_stream = stream::iter(join_handles)
     .buffer_unordered(concurrent_jobs.get())
-    .collect::<Vec<Result<Result<(), AnyError>, tokio::task::JoinError>>>();
-
-  let mut reporter = Box::new(PrettyTestReporter::new(
-    concurrent_jobs.get() > 1,
-    options.log_level != Some(Level::Error),
-  ));
+    .collect::<Vec<
+      Result<
+        Result<(ModuleSpecifier, Duration), AnyError>,
+        tokio::task::JoinError,
+      >,
+    >>();
 
   let handler = {
     spawn(async move {
@@ -1374,6 +1024,8 @@ async fn test_specifiers(
       let mut test_steps = IndexMap::new();
       let mut tests_started = HashSet::new();
       let mut tests_with_result = HashSet::new();
+      let mut step_results = HashMap::new();
+      let mut failed_attempts = HashMap::new();
       let mut summary = TestSummary::new();
       let mut used_only = false;
 
@@ -1424,11 +1076,40 @@ async fn test_specifiers(
                 TestResult::Cancelled => {
                   summary.failed += 1;
                 }
+                TestResult::Flaky(attempts) => {
+                  summary.passed += 1;
+                  summary.flaky.push((description.clone(), *attempts));
+                }
               }
               reporter.report_result(description, &result, elapsed);
             }
           }
 
+          TestEvent::Retry(id, failure, elapsed) => {
+            let description = tests.get(&id).unwrap();
+            // the steps of the failed attempt are run again, so their results
+            // no longer count
+            let attempt_steps = step_results
+              .keys()
+              .copied()
+              .filter(|step_id| test_steps[step_id].root_id == id)
+              .collect::<HashSet<usize>>();
+            for step_id in &attempt_steps {
+              match step_results.remove(step_id).unwrap() {
+                TestStepResult::Ok => summary.passed_steps -= 1,
+                TestStepResult::Ignored => summary.ignored_steps -= 1,
+                TestStepResult::Failed(_) => summary.failed_steps -= 1,
+              }
+            }
+            summary
+              .failures
+              .retain(|(failed, _)| !attempt_steps.contains(&failed.id));
+
+            let attempt = failed_attempts.entry(id).or_insert(0);
+            *attempt += 1;
+            reporter.report_retry(description, &failure, *attempt, elapsed);
+          }
+
           TestEvent::UncaughtError(origin, error) => {
             reporter.report_uncaught_error(&origin, &error);
             summary.failed += 1;
@@ -1461,7 +1142,7 @@ async fn test_specifiers(
                   summary.failures.push((
                     TestDescription {
                       id: description.id,
-                      name: reporter.format_test_step_ancestry(
+                      name: reporters::format_test_step_ancestry(
                         description,
                         &tests,
                         &test_steps,
@@ -1470,11 +1151,14 @@ async fn test_specifiers(
                       only: false,
                       origin: description.origin.clone(),
                       location: description.location.clone(),
+                      retries: None,
+                      tags: Vec::new(),
                     },
                     failure.clone(),
                   ))
                 }
               }
+              step_results.insert(id, result.clone());
 
               reporter.report_step_result(
                 description,
@@ -1505,6 +1189,7 @@ async fn test_specifiers(
 
       let elapsed = Instant::now().duration_since(earlier);
       reporter.report_summary(&summary, &elapsed);
+      reporter.flush_report(&elapsed, &tests, &test_steps)?;
 
       if used_only {
         return Err(generic_error(
@@ -1516,6 +1201,12 @@ async fn test_specifiers(
         return Err(generic_error("Test failed"));
       }
 
+      if fail_on_flaky && !summary.flaky.is_empty() {
+        return Err(generic_error(
+          "Test failed because tests only passed after being retried",
+        ));
+      }
+
       Ok(())
     })
   };
@@ -1523,8 +1214,16 @@ async fn test_specifiers(
   let (join_results, result) = future::join(join_stream, handler).await;
 
   // propagate any errors
+  let mut durations = Vec::with_capacity(join_results.len());
   for join_result in join_results {
-    join_result??;
+    durations.push(join_result??);
+  }
+
+  if let Some(mut shard_timings) = options.shard_timings {
+    for (specifier, duration) in durations {
+      shard_timings.insert(&specifier, duration);
+    }
+    shard_timings.save()?;
   }
 
   result??;
@@ -1640,6 +1339,122 @@ async fn fetch_specifiers_with_test_mode(
   Ok(specifiers_with_mode)
 }
 
+/// Durations of test files, which are used to balance the shards of
+/// `--shard`. The file maps the test files, relative to the directory of the
+/// file, to their durations in milliseconds.
+#[derive(Debug, Clone)]
+struct ShardTimings {
+  path: PathBuf,
+  base_url: Url,
+  durations: BTreeMap<String, u64>,
+}
+
+impl ShardTimings {
+  fn load(path: PathBuf) -> Result<Self, AnyError> {
+    let base_url = path
+      .parent()
+      .and_then(|dir| Url::from_directory_path(dir).ok())
+      .ok_or_else(|| {
+        generic_error(format!("Invalid shard timings path: {}", path.display()))
+      })?;
+    let durations = match std::fs::read_to_string(&path) {
+      Ok(text) => serde_json::from_str(&text).with_context(|| {
+        format!("Failed to parse shard timings: {}", path.display())
+      })?,
+      // the file is created after the first run
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+      Err(err) => return Err(err.into()),
+    };
+    Ok(Self {
+      path,
+      base_url,
+      durations,
+    })
+  }
+
+  fn key(&self, specifier: &ModuleSpecifier) -> String {
+    self
+      .base_url
+      .make_relative(specifier)
+      .unwrap_or_else(|| specifier.to_string())
+  }
+
+  fn get(&self, specifier: &ModuleSpecifier) -> Option<u64> {
+    self.durations.get(&self.key(specifier)).copied()
+  }
+
+  fn insert(&mut self, specifier: &ModuleSpecifier, duration: Duration) {
+    let key = self.key(specifier);
+    self.durations.insert(key, duration.as_millis() as u64);
+  }
+
+  fn save(&self) -> Result<(), AnyError> {
+    let text = serde_json::to_string_pretty(&self.durations)?;
+    std::fs::write(&self.path, format!("{text}\n")).with_context(|| {
+      format!("Failed to write shard timings: {}", self.path.display())
+    })
+  }
+}
+
+/// Selects the test files of `shard`. Every shard partitions the same sorted
+/// list, so the shards are disjoint and cover all test files regardless of
+/// the order in which the files were discovered.
+///
+/// Without timings the files are dealt out in turn. With timings the longest
+/// files are assigned first, each to the shard with the least total duration,
+/// and files without a timing are assumed to take the average duration.
+fn select_shard(
+  mut specifiers_with_mode: Vec<(ModuleSpecifier, TestMode)>,
+  shard: TestShard,
+  timings: Option<&ShardTimings>,
+) -> Vec<(ModuleSpecifier, TestMode)> {
+  specifiers_with_mode.sort_by(|(a, _), (b, _)| a.cmp(b));
+  let shard_index = shard.index - 1;
+
+  let known_durations = specifiers_with_mode
+    .iter()
+    .filter_map(|(specifier, _)| timings?.get(specifier))
+    .collect::<Vec<_>>();
+  if known_durations.is_empty() {
+    return specifiers_with_mode
+      .into_iter()
+      .enumerate()
+      .filter(|(index, _)| index % shard.count == shard_index)
+      .map(|(_, specifier_with_mode)| specifier_with_mode)
+      .collect();
+  }
+
+  let average_duration =
+    known_durations.iter().sum::<u64>() / known_durations.len() as u64;
+  let mut durations = specifiers_with_mode
+    .into_iter()
+    .map(|specifier_with_mode| {
+      let duration = timings
+        .and_then(|timings| timings.get(&specifier_with_mode.0))
+        .unwrap_or(average_duration);
+      (duration, specifier_with_mode)
+    })
+    .collect::<Vec<_>>();
+  // stable sort, so files with the same duration stay sorted by specifier
+  durations.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+  let mut shard_totals = vec![0u64; shard.count];
+  let mut selected = Vec::new();
+  for (duration, specifier_with_mode) in durations {
+    let (index, total) = shard_totals
+      .iter_mut()
+      .enumerate()
+      .min_by_key(|(_, total)| **total)
+      .unwrap();
+    *total += duration;
+    if index == shard_index {
+      selected.push(specifier_with_mode);
+    }
+  }
+  selected.sort_by(|(a, _), (b, _)| a.cmp(b));
+  selected
+}
+
 pub async fn run_tests(
   cli_options: CliOptions,
   test_options: TestOptions,
@@ -1666,6 +1481,18 @@ pub async fn run_tests(
     return Err(generic_error("No test modules found"));
   }
 
+  let shard_timings = test_options
+    .shard_timings
+    .as_ref()
+    .map(|path| ShardTimings::load(cli_options.initial_cwd().join(path)))
+    .transpose()?;
+  let specifiers_with_mode = match test_options.shard {
+    Some(shard) => {
+      select_shard(specifiers_with_mode, shard, shard_timings.as_ref())
+    }
+    None => specifiers_with_mode,
+  };
+
   check_specifiers(
     cli_options,
     file_fetcher,
@@ -1696,10 +1523,20 @@ pub async fn run_tests(
       fail_fast: test_options.fail_fast,
       log_level,
       specifier: TestSpecifierOptions {
-        filter: TestFilter::from_flag(&test_options.filter),
+        filter: TestFilter {
+          tags: test_options.tags.clone(),
+          exclude_tags: test_options.exclude_tags.clone(),
+          ..TestFilter::from_flag(&test_options.filter)
+        },
         shuffle: test_options.shuffle,
         trace_ops: test_options.trace_ops,
+        update_snapshots: test_options.update_snapshots,
+        retries: test_options.retries,
       },
+      shard_timings,
+      reporter: test_options.reporter,
+      junit_path: test_options.junit_path,
+      fail_on_flaky: test_options.fail_on_flaky,
     },
   )
   .await?;
@@ -1876,10 +1713,20 @@ pub async fn run_tests_with_watch(
           fail_fast: test_options.fail_fast,
           log_level,
           specifier: TestSpecifierOptions {
-            filter: TestFilter::from_flag(&test_options.filter),
+            filter: TestFilter {
+              tags: test_options.tags.clone(),
+              exclude_tags: test_options.exclude_tags.clone(),
+              ..TestFilter::from_flag(&test_options.filter)
+            },
             shuffle: test_options.shuffle,
             trace_ops: test_options.trace_ops,
+            update_snapshots: test_options.update_snapshots,
+            retries: test_options.retries,
           },
+          shard_timings: None,
+          reporter: test_options.reporter.clone(),
+          junit_path: test_options.junit_path.clone(),
+          fail_on_flaky: test_options.fail_on_flaky,
         },
       )
       .await?;
@@ -2165,4 +2012,65 @@ mod inner_test {
     assert!(!is_supported_test_path(Path::new("notatest.js")));
     assert!(!is_supported_test_path(Path::new("NotAtest.ts")));
   }
+
+  #[test]
+  fn test_select_shard() {
+    fn specifiers(names: &[&str]) -> Vec<(ModuleSpecifier, TestMode)> {
+      names
+        .iter()
+        .map(|name| {
+          let specifier =
+            ModuleSpecifier::parse(&format!("file:///project/{name}")).unwrap();
+          (specifier, TestMode::Executable)
+        })
+        .collect()
+    }
+    fn select(
+      names: &[&str],
+      index: usize,
+      count: usize,
+      timings: Option<&ShardTimings>,
+    ) -> Vec<String> {
+      select_shard(specifiers(names), TestShard { index, count }, timings)
+        .into_iter()
+        .map(|(specifier, _)| specifier.path()[9..].to_string())
+        .collect()
+    }
+
+    // the order of discovery doesn't matter
+    let names = [
+      "e_test.ts",
+      "a_test.ts",
+      "d_test.ts",
+      "b_test.ts",
+      "c_test.ts",
+    ];
+    assert_eq!(
+      select(&names, 1, 2, None),
+      ["a_test.ts", "c_test.ts", "e_test.ts"]
+    );
+    assert_eq!(select(&names, 2, 2, None), ["b_test.ts", "d_test.ts"]);
+    assert_eq!(select(&names, 3, 3, None), ["c_test.ts"]);
+    assert!(select(&names[..1], 2, 2, None).is_empty());
+
+    let timings = ShardTimings {
+      path: PathBuf::from("/project/timings.json"),
+      base_url: Url::parse("file:///project/").unwrap(),
+      durations: BTreeMap::from([
+        ("a_test.ts".to_string(), 100),
+        ("b_test.ts".to_string(), 40),
+        ("c_test.ts".to_string(), 30),
+        ("d_test.ts".to_string(), 20),
+      ]),
+    };
+    // e_test.ts is assumed to take the average of 47ms
+    assert_eq!(
+      select(&names, 1, 2, Some(&timings)),
+      ["a_test.ts", "d_test.ts"]
+    );
+    assert_eq!(
+      select(&names, 2, 2, Some(&timings)),
+      ["b_test.ts", "c_test.ts", "e_test.ts"]
+    );
+  }
 }
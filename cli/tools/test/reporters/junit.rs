@@ -0,0 +1,464 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::common::format_test_step_ancestry;
+use super::common::to_relative_path_or_remote_url;
+use super::*;
+use deno_core::anyhow::Context as _;
+
+/// Reports the results of a test run in the JUnit XML format that CI services
+/// display test results from.
+/// <https://github.com/testmoapp/junitxml>
+pub struct JunitTestReporter {
+  /// The file to write the report to, or `None` to write it to stdout.
+  path: Option<PathBuf>,
+  cwd: Url,
+  /// The completed tests and steps by their id.
+  cases: IndexMap<usize, JunitTestCase>,
+  /// The modules that had an uncaught error along with the error.
+  uncaught_errors: Vec<(String, String)>,
+  /// The failures of the attempts of tests that are run again by their id.
+  retry_failures: HashMap<usize, Vec<String>>,
+}
+
+struct JunitTestCase {
+  name: String,
+  origin: String,
+  location: TestLocation,
+  is_step: bool,
+  /// The id of the test this case is or is a step of.
+  root_id: usize,
+  elapsed: u64,
+  outcome: JunitOutcome,
+  /// The failures of the attempts before the final one.
+  retry_failures: Vec<String>,
+  tags: Vec<String>,
+}
+
+enum JunitOutcome {
+  Passed,
+  Skipped,
+  Failed(String),
+}
+
+impl JunitTestReporter {
+  pub fn new(path: Option<PathBuf>) -> Self {
+    Self {
+      path,
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      cases: IndexMap::new(),
+      uncaught_errors: Vec::new(),
+      retry_failures: HashMap::new(),
+    }
+  }
+
+  fn build_report(&self, elapsed: &Duration) -> String {
+    // the tests and steps are grouped into a test suite per module
+    let mut suites: IndexMap<&str, Vec<&JunitTestCase>> = IndexMap::new();
+    let mut cases = self.cases.iter().collect::<Vec<_>>();
+    // steps complete before the test they belong to, but ids are assigned in
+    // the order the tests and steps are declared in
+    cases.sort_by_key(|(id, _)| **id);
+    for (_, case) in cases {
+      suites.entry(case.origin.as_str()).or_default().push(case);
+    }
+    for (origin, _) in &self.uncaught_errors {
+      suites.entry(origin.as_str()).or_default();
+    }
+
+    let count_failures = |cases: &[&JunitTestCase]| {
+      cases
+        .iter()
+        .filter(|case| matches!(case.outcome, JunitOutcome::Failed(_)))
+        .count()
+    };
+    let count_errors = |origin: &str| {
+      self
+        .uncaught_errors
+        .iter()
+        .filter(|(error_origin, _)| error_origin == origin)
+        .count()
+    };
+
+    let mut report = String::new();
+    writeln!(report, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+    writeln!(
+      report,
+      r#"<testsuites name="deno test" tests="{}" failures="{}" errors="{}" time="{}">"#,
+      self.cases.len(),
+      count_failures(&self.cases.values().collect::<Vec<_>>()),
+      self.uncaught_errors.len(),
+      format_seconds(elapsed.as_millis() as u64),
+    )
+    .unwrap();
+    for (origin, cases) in suites {
+      let suite_name =
+        escape_xml(&to_relative_path_or_remote_url(&self.cwd, origin));
+      let skipped = cases
+        .iter()
+        .filter(|case| matches!(case.outcome, JunitOutcome::Skipped))
+        .count();
+      // the steps run as part of their test, so only the tests add up to the
+      // time of the suite
+      let suite_elapsed = cases
+        .iter()
+        .filter(|case| !case.is_step)
+        .map(|case| case.elapsed)
+        .sum::<u64>();
+      writeln!(
+        report,
+        r#"  <testsuite name="{}" tests="{}" skipped="{}" failures="{}" errors="{}" time="{}">"#,
+        suite_name,
+        cases.len(),
+        skipped,
+        count_failures(&cases),
+        count_errors(origin),
+        format_seconds(suite_elapsed),
+      )
+      .unwrap();
+      for case in cases {
+        write!(
+          report,
+          r#"    <testcase name="{}" classname="{}" file="{}" line="{}" time="{}""#,
+          escape_xml(&case.name),
+          suite_name,
+          escape_xml(&to_relative_path_or_remote_url(
+            &self.cwd,
+            &case.location.file_name
+          )),
+          case.location.line_number,
+          format_seconds(case.elapsed),
+        )
+        .unwrap();
+        if matches!(case.outcome, JunitOutcome::Passed)
+          && case.retry_failures.is_empty()
+          && case.tags.is_empty()
+        {
+          writeln!(report, "/>").unwrap();
+          continue;
+        }
+        writeln!(report, ">").unwrap();
+        if !case.tags.is_empty() {
+          writeln!(report, "      <properties>").unwrap();
+          for tag in &case.tags {
+            writeln!(
+              report,
+              r#"        <property name="tag" value="{}"/>"#,
+              escape_xml(tag)
+            )
+            .unwrap();
+          }
+          writeln!(report, "      </properties>").unwrap();
+        }
+        match &case.outcome {
+          JunitOutcome::Passed => {}
+          JunitOutcome::Skipped => {
+            writeln!(report, "      <skipped/>").unwrap()
+          }
+          JunitOutcome::Failed(message) => {
+            writeln!(
+              report,
+              r#"      <failure message="{}">{}</failure>"#,
+              escape_xml(message.lines().next().unwrap_or_default()),
+              escape_xml(message),
+            )
+            .unwrap();
+          }
+        }
+        // the failed attempts use the elements of the Maven Surefire report,
+        // which most CI services understand
+        let element = if matches!(case.outcome, JunitOutcome::Failed(_)) {
+          "rerunFailure"
+        } else {
+          "flakyFailure"
+        };
+        for message in &case.retry_failures {
+          writeln!(
+            report,
+            r#"      <{element} message="{}">{}</{element}>"#,
+            escape_xml(message.lines().next().unwrap_or_default()),
+            escape_xml(message),
+          )
+          .unwrap();
+        }
+        writeln!(report, "    </testcase>").unwrap();
+      }
+      for (_, message) in self
+        .uncaught_errors
+        .iter()
+        .filter(|(error_origin, _)| error_origin == origin)
+      {
+        writeln!(
+          report,
+          r#"    <testcase name="(uncaught error)" classname="{}">"#,
+          suite_name
+        )
+        .unwrap();
+        writeln!(
+          report,
+          r#"      <error message="{}">{}</error>"#,
+          escape_xml(message.lines().next().unwrap_or_default()),
+          escape_xml(message),
+        )
+        .unwrap();
+        writeln!(report, "    </testcase>").unwrap();
+      }
+      writeln!(report, "  </testsuite>").unwrap();
+    }
+    writeln!(report, "</testsuites>").unwrap();
+    report
+  }
+}
+
+impl TestReporter for JunitTestReporter {
+  fn report_register(&mut self, _description: &TestDescription) {}
+
+  fn report_plan(&mut self, _plan: &TestPlan) {}
+
+  fn report_wait(&mut self, _description: &TestDescription) {}
+
+  fn report_output(&mut self, _output: &[u8]) {}
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    let outcome = match result {
+      TestResult::Ok | TestResult::Flaky(_) => JunitOutcome::Passed,
+      TestResult::Ignored => JunitOutcome::Skipped,
+      TestResult::Failed(failure) => JunitOutcome::Failed(failure.to_string()),
+      TestResult::Cancelled => {
+        JunitOutcome::Failed("Test was cancelled.".to_string())
+      }
+    };
+    self.cases.insert(
+      description.id,
+      JunitTestCase {
+        name: description.name.clone(),
+        origin: description.origin.clone(),
+        location: description.location.clone(),
+        is_step: false,
+        root_id: description.id,
+        elapsed,
+        outcome,
+        retry_failures: self
+          .retry_failures
+          .remove(&description.id)
+          .unwrap_or_default(),
+        tags: description.tags.clone(),
+      },
+    );
+  }
+
+  fn report_retry(
+    &mut self,
+    description: &TestDescription,
+    failure: &TestFailure,
+    _attempt: usize,
+    _elapsed: u64,
+  ) {
+    // the steps are reported again by the next attempt
+    self.cases.retain(|_, case| case.root_id != description.id);
+    self
+      .retry_failures
+      .entry(description.id)
+      .or_default()
+      .push(failure.to_string());
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    self
+      .uncaught_errors
+      .push((origin.to_string(), format_test_error(error)));
+  }
+
+  fn report_step_register(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    tests: &IndexMap<usize, TestDescription>,
+    test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    let outcome = match result {
+      TestStepResult::Ok => JunitOutcome::Passed,
+      TestStepResult::Ignored => JunitOutcome::Skipped,
+      TestStepResult::Failed(failure) => {
+        JunitOutcome::Failed(failure.to_string())
+      }
+    };
+    self.cases.insert(
+      desc.id,
+      JunitTestCase {
+        name: format_test_step_ancestry(desc, tests, test_steps),
+        origin: desc.origin.clone(),
+        location: desc.location.clone(),
+        is_step: true,
+        root_id: desc.root_id,
+        elapsed,
+        outcome,
+        retry_failures: Vec::new(),
+        tags: Vec::new(),
+      },
+    );
+  }
+
+  fn report_summary(&mut self, _summary: &TestSummary, _elapsed: &Duration) {}
+
+  fn report_sigint(
+    &mut self,
+    _tests_pending: &HashSet<usize>,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+  }
+
+  fn flush_report(
+    &mut self,
+    elapsed: &Duration,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) -> Result<(), AnyError> {
+    let report = self.build_report(elapsed);
+    match &self.path {
+      Some(path) => {
+        if let Some(parent) = path.parent() {
+          std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, report).with_context(|| {
+          format!("Failed to write JUnit report to {}", path.display())
+        })?;
+      }
+      None => {
+        std::io::stdout().write_all(report.as_bytes())?;
+      }
+    }
+    Ok(())
+  }
+}
+
+fn format_seconds(millis: u64) -> String {
+  format!("{:.3}", millis as f64 / 1000.0)
+}
+
+/// Escapes text for use in XML content and attribute values, removing the
+/// colors of formatted errors and the characters XML doesn't allow.
+fn escape_xml(text: &str) -> String {
+  let text = console_static_text::ansi::strip_ansi_codes(text);
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      '\'' => escaped.push_str("&apos;"),
+      '\t' | '\n' | '\r' => escaped.push(c),
+      c if c.is_control() => {}
+      c => escaped.push(c),
+    }
+  }
+  escaped
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn description(id: usize, name: &str) -> TestDescription {
+    TestDescription {
+      id,
+      name: name.to_string(),
+      ignore: false,
+      only: false,
+      origin: "file:///project/a_test.ts".to_string(),
+      location: TestLocation {
+        file_name: "file:///project/a_test.ts".to_string(),
+        line_number: id as u32,
+        column_number: 1,
+      },
+      retries: None,
+      tags: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn test_escape_xml() {
+    assert_eq!(
+      escape_xml("<a href=\"x\">'b' & c</a>"),
+      "&lt;a href=&quot;x&quot;&gt;&apos;b&apos; &amp; c&lt;/a&gt;"
+    );
+    assert_eq!(escape_xml("\x1b[31mred\x1b[0m\x07\n"), "red\n");
+  }
+
+  #[test]
+  fn test_build_report() {
+    let mut reporter = JunitTestReporter {
+      path: None,
+      cwd: Url::parse("file:///project/").unwrap(),
+      cases: IndexMap::new(),
+      uncaught_errors: Vec::new(),
+      retry_failures: HashMap::new(),
+    };
+    reporter.report_result(&description(1, "passes"), &TestResult::Ok, 12);
+    reporter.report_result(
+      &TestDescription {
+        tags: vec!["slow".to_string()],
+        ..description(5, "is tagged")
+      },
+      &TestResult::Ok,
+      8,
+    );
+    reporter.report_result(
+      &description(2, "is ignored"),
+      &TestResult::Ignored,
+      0,
+    );
+    reporter.report_result(
+      &description(3, "fails"),
+      &TestResult::Failed(TestFailure::FailedSteps(2)),
+      1500,
+    );
+    reporter.report_retry(
+      &description(4, "is flaky"),
+      &TestFailure::FailedSteps(1),
+      1,
+      100,
+    );
+    reporter.report_result(
+      &description(4, "is flaky"),
+      &TestResult::Flaky(1),
+      20,
+    );
+
+    assert_eq!(
+      reporter.build_report(&Duration::from_millis(2000)),
+      r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="deno test" tests="5" failures="1" errors="0" time="2.000">
+  <testsuite name="./a_test.ts" tests="5" skipped="1" failures="1" errors="0" time="1.540">
+    <testcase name="passes" classname="./a_test.ts" file="./a_test.ts" line="1" time="0.012"/>
+    <testcase name="is ignored" classname="./a_test.ts" file="./a_test.ts" line="2" time="0.000">
+      <skipped/>
+    </testcase>
+    <testcase name="fails" classname="./a_test.ts" file="./a_test.ts" line="3" time="1.500">
+      <failure message="2 test steps failed.">2 test steps failed.</failure>
+    </testcase>
+    <testcase name="is flaky" classname="./a_test.ts" file="./a_test.ts" line="4" time="0.020">
+      <flakyFailure message="1 test step failed.">1 test step failed.</flakyFailure>
+    </testcase>
+    <testcase name="is tagged" classname="./a_test.ts" file="./a_test.ts" line="5" time="0.008">
+      <properties>
+        <property name="tag" value="slow"/>
+      </properties>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+    );
+  }
+}
@@ -0,0 +1,264 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::common::to_relative_path_or_remote_url;
+use super::*;
+use deno_core::serde_json::json;
+
+/// Reports the results of a test run in the Test Anything Protocol, where the
+/// steps of a test are reported as a subtest of the test.
+/// <https://testanything.org/tap-version-14-specification.html>
+pub struct TapTestReporter {
+  cwd: Url,
+  parallel: bool,
+  printed_header: bool,
+  /// The number of the last test point of the top level.
+  n: usize,
+  /// The completed steps by the id of the test or step they belong to, which
+  /// are reported once their parent completes.
+  step_results: HashMap<usize, Vec<(TestStepDescription, TestStepResult, u64)>>,
+}
+
+enum TapStatus<'a> {
+  Ok,
+  Skip,
+  NotOk(String, Option<&'a TestLocation>, u64),
+}
+
+impl TapTestReporter {
+  pub fn new(parallel: bool) -> Self {
+    Self {
+      cwd: Url::from_directory_path(std::env::current_dir().unwrap()).unwrap(),
+      parallel,
+      printed_header: false,
+      n: 0,
+      step_results: HashMap::new(),
+    }
+  }
+
+  fn print_header(&mut self) {
+    if !self.printed_header {
+      println!("TAP version 14");
+      self.printed_header = true;
+    }
+  }
+
+  /// Prints the test point of a test or step, preceded by the subtest of the
+  /// steps that belong to it.
+  fn print_test_point(
+    &mut self,
+    id: Option<usize>,
+    level: usize,
+    n: usize,
+    name: &str,
+    status: TapStatus,
+  ) {
+    let indent = "    ".repeat(level);
+    let steps = id
+      .and_then(|id| self.step_results.remove(&id))
+      .unwrap_or_default();
+    if !steps.is_empty() {
+      println!("{indent}# Subtest: {}", escape_description(name));
+      for (i, (desc, result, elapsed)) in steps.iter().enumerate() {
+        let status = match result {
+          TestStepResult::Ok => TapStatus::Ok,
+          TestStepResult::Ignored => TapStatus::Skip,
+          TestStepResult::Failed(failure) => TapStatus::NotOk(
+            failure.to_string(),
+            Some(&desc.location),
+            *elapsed,
+          ),
+        };
+        self.print_test_point(
+          Some(desc.id),
+          level + 1,
+          i + 1,
+          &desc.name,
+          status,
+        );
+      }
+      println!("{indent}    1..{}", steps.len());
+    }
+
+    let name = escape_description(name);
+    match status {
+      TapStatus::Ok => println!("{indent}ok {n} - {name}"),
+      TapStatus::Skip => println!("{indent}ok {n} - {name} # SKIP"),
+      TapStatus::NotOk(message, location, elapsed) => {
+        println!("{indent}not ok {n} - {name}");
+        let message = console_static_text::ansi::strip_ansi_codes(&message);
+        // the diagnostics are YAML, which JSON strings are valid in
+        println!("{indent}  ---");
+        println!("{indent}  message: {}", json!(message));
+        println!("{indent}  severity: fail");
+        println!("{indent}  duration_ms: {elapsed}");
+        if let Some(location) = location {
+          let file =
+            to_relative_path_or_remote_url(&self.cwd, &location.file_name);
+          println!("{indent}  at:");
+          println!("{indent}    file: {}", json!(file));
+          println!("{indent}    line: {}", location.line_number);
+          println!("{indent}    column: {}", location.column_number);
+        }
+        println!("{indent}  ...");
+      }
+    }
+  }
+}
+
+/// Escapes the characters that have a meaning in the description of a test
+/// point, which is on a single line and may be followed by a directive.
+fn escape_description(name: &str) -> String {
+  name
+    .replace('\\', "\\\\")
+    .replace('#', "\\#")
+    .replace(['\r', '\n'], " ")
+}
+
+impl TestReporter for TapTestReporter {
+  fn report_register(&mut self, _description: &TestDescription) {}
+
+  fn report_plan(&mut self, plan: &TestPlan) {
+    self.print_header();
+    if !self.parallel {
+      println!(
+        "# {}",
+        to_relative_path_or_remote_url(&self.cwd, &plan.origin)
+      );
+    }
+  }
+
+  fn report_wait(&mut self, _description: &TestDescription) {}
+
+  fn report_output(&mut self, output: &[u8]) {
+    // the output of the tests is reported as comments to not break the
+    // stream of test points
+    for line in String::from_utf8_lossy(output).lines() {
+      println!("# {line}");
+    }
+  }
+
+  fn report_result(
+    &mut self,
+    description: &TestDescription,
+    result: &TestResult,
+    elapsed: u64,
+  ) {
+    self.print_header();
+    let status = match result {
+      TestResult::Ok | TestResult::Flaky(_) => TapStatus::Ok,
+      TestResult::Ignored => TapStatus::Skip,
+      TestResult::Failed(failure) => TapStatus::NotOk(
+        failure.to_string(),
+        Some(&description.location),
+        elapsed,
+      ),
+      TestResult::Cancelled => TapStatus::NotOk(
+        "Test was cancelled.".to_string(),
+        Some(&description.location),
+        elapsed,
+      ),
+    };
+    self.n += 1;
+    self.print_test_point(
+      Some(description.id),
+      0,
+      self.n,
+      &description.name,
+      status,
+    );
+    if !description.tags.is_empty() {
+      println!("# tags: {}", description.tags.join(", "));
+    }
+    if let TestResult::Flaky(attempts) = result {
+      println!("# flaky: passed after {attempts} failed attempts");
+    }
+  }
+
+  fn report_retry(
+    &mut self,
+    description: &TestDescription,
+    failure: &TestFailure,
+    attempt: usize,
+    _elapsed: u64,
+  ) {
+    // the steps are reported again by the next attempt
+    for steps in self.step_results.values_mut() {
+      steps.retain(|(desc, _, _)| desc.root_id != description.id);
+    }
+    let message =
+      console_static_text::ansi::strip_ansi_codes(&failure.to_string())
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    println!(
+      "# retrying {} after failed attempt {attempt}: {message}",
+      escape_description(&description.name)
+    );
+  }
+
+  fn report_uncaught_error(&mut self, origin: &str, error: &JsError) {
+    self.print_header();
+    self.n += 1;
+    let name = format!(
+      "Uncaught error from {}",
+      to_relative_path_or_remote_url(&self.cwd, origin)
+    );
+    let status = TapStatus::NotOk(format_test_error(error), None, 0);
+    self.print_test_point(None, 0, self.n, &name, status);
+  }
+
+  fn report_step_register(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_wait(&mut self, _description: &TestStepDescription) {}
+
+  fn report_step_result(
+    &mut self,
+    desc: &TestStepDescription,
+    result: &TestStepResult,
+    elapsed: u64,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    self.step_results.entry(desc.parent_id).or_default().push((
+      desc.clone(),
+      result.clone(),
+      elapsed,
+    ));
+  }
+
+  fn report_summary(&mut self, _summary: &TestSummary, _elapsed: &Duration) {
+    self.print_header();
+    println!("1..{}", self.n);
+  }
+
+  fn report_sigint(
+    &mut self,
+    _tests_pending: &HashSet<usize>,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) {
+    println!("Bail out! SIGINT");
+  }
+
+  fn flush_report(
+    &mut self,
+    _elapsed: &Duration,
+    _tests: &IndexMap<usize, TestDescription>,
+    _test_steps: &IndexMap<usize, TestStepDescription>,
+  ) -> Result<(), AnyError> {
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_escape_description() {
+    assert_eq!(escape_description("adds 1 + 1"), "adds 1 + 1");
+    assert_eq!(escape_description("issue #123"), "issue \\#123");
+    assert_eq!(escape_description("a\\b\nc"), "a\\\\b c");
+  }
+}
@@ -0,0 +1,307 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! Snapshots of the values asserted with `t.assertSnapshot()`, which are
+//! stored in a `__snapshots__` directory beside the test module.
+//!
+//! The snapshot files are JavaScript modules in the same format as the ones of
+//! `assertSnapshot()` of the standard library.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use deno_core::anyhow::bail;
+use deno_core::anyhow::Context;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::ModuleSpecifier;
+use indexmap::IndexMap;
+
+use crate::colors;
+use crate::util::diff::diff;
+
+const SNAPSHOT_DIR: &str = "__snapshots__";
+const SNAPSHOT_HEADER: &str = "export const snapshot = {};\n";
+
+pub struct SnapshotFile {
+  path: PathBuf,
+  update: bool,
+  /// The snapshots in the file by their name, which is the name of the test
+  /// followed by the number of the assertion within the test.
+  stored: IndexMap<String, String>,
+  /// The snapshots asserted in this run by their name.
+  asserted: IndexMap<String, String>,
+  assertion_counts: HashMap<String, usize>,
+}
+
+impl SnapshotFile {
+  pub fn load(
+    specifier: &ModuleSpecifier,
+    update: bool,
+  ) -> Result<Self, AnyError> {
+    let test_path = specifier.to_file_path().map_err(|_| {
+      generic_error(format!(
+        "Snapshots are only supported in local test modules, but the test module is {specifier}"
+      ))
+    })?;
+    let file_name = test_path.file_name().unwrap().to_string_lossy();
+    let path = test_path
+      .with_file_name(SNAPSHOT_DIR)
+      .join(format!("{file_name}.snap"));
+    let stored = match std::fs::read_to_string(&path) {
+      Ok(text) => parse_snapshots(&text).with_context(|| {
+        format!("Failed to parse snapshot file {}", path.display())
+      })?,
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => IndexMap::new(),
+      Err(err) => {
+        return Err(err).with_context(|| {
+          format!("Failed to read snapshot file {}", path.display())
+        })
+      }
+    };
+    Ok(Self {
+      path,
+      update,
+      stored,
+      asserted: IndexMap::new(),
+      assertion_counts: HashMap::new(),
+    })
+  }
+
+  /// Compares a serialized value with the next snapshot of the test, returning
+  /// the message of the assertion error if they don't match. The value always
+  /// matches when the snapshots are being updated.
+  pub fn assert(&mut self, test_name: &str, actual: String) -> Option<String> {
+    let count = self
+      .assertion_counts
+      .entry(test_name.to_string())
+      .or_default();
+    *count += 1;
+    let name = format!("{test_name} {count}");
+    let message = if self.update {
+      None
+    } else {
+      match self.stored.get(&name) {
+        Some(expected) if *expected == actual => None,
+        Some(expected) => Some(format!(
+          "Snapshot does not match:\n\n{}\nRun `deno test --update-snapshots` to update the snapshot.",
+          diff(expected, &actual)
+        )),
+        None => Some(format!(
+          "Missing snapshot: {name}\n\nRun `deno test --update-snapshots` to create the snapshot."
+        )),
+      }
+    };
+    self.asserted.insert(name, actual);
+    message
+  }
+
+  /// Forgets the assertions of a test and its steps, so they are counted from
+  /// the start when the test is run again.
+  pub fn reset_test(&mut self, test_name: &str) {
+    let step_prefix = format!("{test_name} ... ");
+    self
+      .assertion_counts
+      .retain(|name, _| name != test_name && !name.starts_with(&step_prefix));
+  }
+
+  /// Writes the asserted snapshots to the file. The snapshots of the tests
+  /// that ran but no longer assert them and of the tests that no longer exist
+  /// are removed, while those of the tests that didn't run are kept.
+  pub fn save(
+    self,
+    registered_tests: &HashSet<String>,
+    ran_tests: &HashSet<String>,
+  ) -> Result<(), AnyError> {
+    let keep_stored = |name: &str| {
+      let test_name = name.rsplit_once(' ').map(|(n, _)| n).unwrap_or(name);
+      let root_name = test_name.split(" ... ").next().unwrap();
+      registered_tests.contains(root_name) && !ran_tests.contains(root_name)
+    };
+
+    // keep the order of the file so updates only show up as changed lines
+    let mut snapshots = IndexMap::new();
+    for (name, value) in &self.stored {
+      let value = match self.asserted.get(name) {
+        Some(asserted) => asserted,
+        None if keep_stored(name) => value,
+        None => continue,
+      };
+      snapshots.insert(name.clone(), value.clone());
+    }
+    for (name, value) in &self.asserted {
+      if !snapshots.contains_key(name) {
+        snapshots.insert(name.clone(), value.clone());
+      }
+    }
+
+    let written = self
+      .asserted
+      .iter()
+      .filter(|(name, value)| self.stored.get(*name) != Some(value))
+      .count();
+    let removed = self
+      .stored
+      .keys()
+      .filter(|name| !snapshots.contains_key(*name))
+      .count();
+    if written == 0 && removed == 0 {
+      return Ok(());
+    }
+
+    if snapshots.is_empty() {
+      std::fs::remove_file(&self.path).with_context(|| {
+        format!("Failed to remove snapshot file {}", self.path.display())
+      })?;
+    } else {
+      std::fs::create_dir_all(self.path.parent().unwrap())?;
+      std::fs::write(&self.path, serialize_snapshots(&snapshots))
+        .with_context(|| {
+          format!("Failed to write snapshot file {}", self.path.display())
+        })?;
+    }
+    log::info!(
+      "{} {} ({} written, {} removed)",
+      colors::green("Updated snapshots"),
+      self.path.display(),
+      written,
+      removed,
+    );
+    Ok(())
+  }
+}
+
+fn serialize_snapshots(snapshots: &IndexMap<String, String>) -> String {
+  let mut text = SNAPSHOT_HEADER.to_string();
+  for (name, value) in snapshots {
+    write!(
+      text,
+      "\nsnapshot[`{}`] = `\n{}\n`;\n",
+      escape_template(name),
+      escape_template(value)
+    )
+    .unwrap();
+  }
+  text
+}
+
+fn parse_snapshots(text: &str) -> Result<IndexMap<String, String>, AnyError> {
+  const ENTRY_START: &str = "snapshot[`";
+  const VALUE_START: &str = "] = `";
+
+  let mut snapshots = IndexMap::new();
+  let mut rest = text;
+  while let Some(index) = rest.find(ENTRY_START) {
+    let (name, after_name) = read_template(&rest[index + ENTRY_START.len()..])?;
+    let Some(after_name) = after_name.strip_prefix(VALUE_START) else {
+      bail!("Expected the snapshot of `{name}` to be a template literal");
+    };
+    let (value, after_value) = read_template(after_name)?;
+    // the values are wrapped in new lines to be readable in the file
+    let value = value
+      .strip_prefix('\n')
+      .and_then(|value| value.strip_suffix('\n'))
+      .map(ToString::to_string)
+      .unwrap_or(value);
+    snapshots.insert(name, value);
+    rest = after_value;
+  }
+  Ok(snapshots)
+}
+
+/// Reads the content of a template literal up to its closing backtick,
+/// returning the unescaped content and the text after the literal.
+fn read_template(text: &str) -> Result<(String, &str), AnyError> {
+  let mut content = String::new();
+  let mut chars = text.char_indices();
+  while let Some((index, c)) = chars.next() {
+    match c {
+      '`' => return Ok((content, &text[index + 1..])),
+      '\\' => match chars.next() {
+        Some((_, c)) => content.push(c),
+        None => break,
+      },
+      c => content.push(c),
+    }
+  }
+  bail!("Unterminated template literal in snapshot file")
+}
+
+fn escape_template(text: &str) -> String {
+  text
+    .replace('\\', "\\\\")
+    .replace('`', "\\`")
+    .replace("${", "\\${")
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn serialize_and_parse() {
+    let mut snapshots = IndexMap::new();
+    snapshots.insert("test 1".to_string(), "{\n  a: 1,\n}".to_string());
+    snapshots.insert(
+      "test ... step 1".to_string(),
+      "`quoted` ${value} \\n".to_string(),
+    );
+    let text = serialize_snapshots(&snapshots);
+    assert_eq!(
+      text,
+      r#"export const snapshot = {};
+
+snapshot[`test 1`] = `
+{
+  a: 1,
+}
+`;
+
+snapshot[`test ... step 1`] = `
+\`quoted\` \${value} \\n
+`;
+"#
+    );
+    assert_eq!(parse_snapshots(&text).unwrap(), snapshots);
+    assert!(parse_snapshots("snapshot[`test 1`] = `unterminated").is_err());
+  }
+
+  #[test]
+  fn assert_and_save() {
+    let dir = tempfile::tempdir().unwrap();
+    let test_path = dir.path().join("a_test.ts");
+    let specifier = ModuleSpecifier::from_file_path(&test_path).unwrap();
+    let snapshot_path = dir.path().join("__snapshots__/a_test.ts.snap");
+
+    // a missing snapshot fails unless the snapshots are being updated
+    let mut file = SnapshotFile::load(&specifier, false).unwrap();
+    assert!(file.assert("a", "1".to_string()).is_some());
+    let mut file = SnapshotFile::load(&specifier, true).unwrap();
+    assert!(file.assert("a", "1".to_string()).is_none());
+    assert!(file.assert("a", "2".to_string()).is_none());
+    assert!(file.assert("b", "3".to_string()).is_none());
+    let tests = HashSet::from(["a".to_string(), "b".to_string()]);
+    file.save(&tests, &tests).unwrap();
+    assert!(snapshot_path.exists());
+
+    let mut file = SnapshotFile::load(&specifier, false).unwrap();
+    assert!(file.assert("a", "1".to_string()).is_none());
+    assert!(file.assert("a", "3".to_string()).is_some());
+
+    // the snapshots of "b" are kept as it didn't run, while the second
+    // snapshot of "a" is removed
+    let mut file = SnapshotFile::load(&specifier, true).unwrap();
+    file.assert("a", "1".to_string());
+    file
+      .save(&tests, &HashSet::from(["a".to_string()]))
+      .unwrap();
+    let file = SnapshotFile::load(&specifier, false).unwrap();
+    assert_eq!(file.stored.keys().collect::<Vec<_>>(), vec!["a 1", "b 1"]);
+
+    // the snapshots of tests that no longer exist are removed
+    let file = SnapshotFile::load(&specifier, true).unwrap();
+    file.save(&HashSet::new(), &HashSet::new()).unwrap();
+    assert!(!snapshot_path.exists());
+  }
+}
@@ -15,6 +15,7 @@ use std::fmt;
 const MAX_SOURCE_LINE_LENGTH: usize = 150;
 
 const UNSTABLE_DENO_PROPS: &[&str] = &[
+  "CookieJar",
   "CreateHttpClientOptions",
   "DatagramConn",
   "HttpClient",
@@ -22,6 +23,9 @@ const UNSTABLE_DENO_PROPS: &[&str] = &[
   "UnixListenOptions",
   "connect",
   "createHttpClient",
+  "cron",
+  "CronOptions",
+  "openBlob",
   "kill",
   "listen",
   "listenDatagram",
@@ -32,6 +36,7 @@ const UNSTABLE_DENO_PROPS: &[&str] = &[
   "serve",
   "ServeInit",
   "ServeTlsInit",
+  "ServerSentEventStream",
   "Handler",
   "osUptime",
 ];
@@ -709,6 +709,32 @@ declare namespace Deno {
      * ```
      */
     step(fn: (t: TestContext) => void | Promise<void>): Promise<boolean>;
+
+    /** Assert that a value matches its snapshot, which is stored in the
+     * `__snapshots__` directory beside the test module. A test can assert
+     * several snapshots, which are matched in the order they are asserted.
+     *
+     * Run `deno test --update-snapshots` to write the snapshots after adding
+     * or changing an assertion, which also removes the snapshots that are no
+     * longer asserted.
+     *
+     * ```ts
+     * Deno.test("a snapshot test", (t) => {
+     *   t.assertSnapshot({ hello: "world" });
+     * });
+     * ```
+     */
+    assertSnapshot(actual: unknown, options?: AssertSnapshotOptions): void;
+  }
+
+  /** Options of {@linkcode TestContext.assertSnapshot}.
+   *
+   * @category Testing */
+  export interface AssertSnapshotOptions {
+    /** Serializes the value to the string stored as its snapshot, which
+     * defaults to the output of {@linkcode Deno.inspect} without any
+     * abbreviations. */
+    serializer?: (value: unknown) => string;
   }
 
   /** @category Testing */
@@ -779,6 +805,13 @@ declare namespace Deno {
      *
      * @default {true} */
     sanitizeExit?: boolean;
+    /** The number of times the test is run again after it fails, overriding
+     * the `--retries` flag of `deno test`. A test that passes on a later
+     * attempt is reported as flaky. */
+    retries?: number;
+    /** Labels of the test, which `deno test --tags` and `--exclude-tags`
+     * select the tests to run by. */
+    tags?: string[];
     /** Specifies the permissions that should be used to run the test.
      *
      * Set this to "inherit" to keep the calling runtime permissions, set this
@@ -3239,12 +3272,37 @@ declare namespace Deno {
    */
   export function readDirSync(path: string | URL): Iterable<DirEntry>;
 
+  /**
+   * Options which can be set when using {@linkcode Deno.copyFile} and
+   * {@linkcode Deno.copyFileSync}.
+   *
+   * @category File System */
+  export interface CopyFileOptions {
+    /** Fail with {@linkcode Deno.errors.AlreadyExists} if the target path
+     * exists, instead of overwriting it.
+     *
+     * @default {false} */
+    exclusive?: boolean;
+    /** Whether to create a copy-on-write clone of the file, which shares its
+     * data with the original until either of them is modified. Cloning is
+     * supported by Btrfs and XFS on Linux, APFS on macOS and ReFS on Windows.
+     *
+     * - `"auto"` clones larger files if the file system supports it, and
+     *   copies the contents otherwise.
+     * - `"force"` fails if the file can't be cloned.
+     * - `"never"` always copies the contents.
+     *
+     * @default {"auto"} */
+    clone?: "auto" | "force" | "never";
+  }
+
   /** Copies the contents and permissions of one file to another specified path,
    * by default creating a new file if needed, else overwriting. Fails if target
    * path is a directory or is unwritable.
    *
    * ```ts
    * await Deno.copyFile("from.txt", "to.txt");
+   * await Deno.copyFile("from.txt", "clone.txt", { clone: "force" });
    * ```
    *
    * Requires `allow-read` permission on `fromPath`.
@@ -3257,6 +3315,7 @@ declare namespace Deno {
   export function copyFile(
     fromPath: string | URL,
     toPath: string | URL,
+    options?: CopyFileOptions,
   ): Promise<void>;
 
   /** Synchronously copies the contents and permissions of one file to another
@@ -3277,6 +3336,7 @@ declare namespace Deno {
   export function copyFileSync(
     fromPath: string | URL,
     toPath: string | URL,
+    options?: CopyFileOptions,
   ): void;
 
   /** Resolves to the full path destination of the named symbolic link.
@@ -3640,8 +3700,19 @@ declare namespace Deno {
    *
    * @category File System */
   export interface FsEvent {
-    /** The kind/type of the file system event. */
-    kind: "any" | "access" | "create" | "modify" | "remove" | "other";
+    /** The kind/type of the file system event.
+     *
+     * `"rename"` is only reported with the `coalesceRenames` option of
+     * {@linkcode Deno.watchFs}, and has the old path followed by the new path
+     * in `paths`. */
+    kind:
+      | "any"
+      | "access"
+      | "create"
+      | "modify"
+      | "rename"
+      | "remove"
+      | "other";
     /** An array of paths that are associated with the file system event. */
     paths: string[];
     /** Any additional flags associated with the event. */
@@ -3669,6 +3740,35 @@ declare namespace Deno {
     [Symbol.asyncIterator](): AsyncIterableIterator<FsEvent>;
   }
 
+  /** Options which can be set when calling {@linkcode Deno.watchFs}.
+   *
+   * @category File System */
+  export interface WatchFsOptions {
+    /** Watch the sub directories of the given directories as well.
+     *
+     * @default {true} */
+    recursive?: boolean;
+    /** Glob patterns of the paths to report events for. A pattern matches
+     * either the full path or the path relative to the watched path it is in,
+     * and `*` doesn't match across `/`. All paths are reported if empty.
+     *
+     * @default {[]} */
+    include?: string[];
+    /** Glob patterns of the paths not to report events for, matched like
+     * `include`. Events with no remaining paths are dropped.
+     *
+     * @default {[]} */
+    exclude?: string[];
+    /** Milliseconds to wait after an event for more events, which are then
+     * reported together with identical events reported only once. */
+    debounce?: number;
+    /** Report a rename as a single `"rename"` event with the old and the new
+     * path, instead of separate `"modify"` events.
+     *
+     * @default {false} */
+    coalesceRenames?: boolean;
+  }
+
   /** Watch for file system events against one or more `paths`, which can be
    * files or directories. These paths must exist already. One user action (e.g.
    * `touch test.file`) can generate multiple file system events. Likewise,
@@ -3710,7 +3810,7 @@ declare namespace Deno {
    */
   export function watchFs(
     paths: string | string[],
-    options?: { recursive: boolean },
+    options?: WatchFsOptions,
   ): FsWatcher;
 
   /**
@@ -3927,8 +4027,8 @@ declare namespace Deno {
    * );
    * ```
    *
-   * _Note_: On Windows only `"SIGINT"` (CTRL+C) and `"SIGBREAK"` (CTRL+Break)
-   * are supported.
+   * _Note_: On Windows only `"SIGINT"` (CTRL+C), `"SIGBREAK"` (CTRL+Break)
+   * and `"SIGHUP"` (closing the console window) are supported.
    *
    * @category Runtime Environment
    */
@@ -3945,8 +4045,8 @@ declare namespace Deno {
    * Deno.removeSignalListener("SIGTERM", listener);
    * ```
    *
-   * _Note_: On Windows only `"SIGINT"` (CTRL+C) and `"SIGBREAK"` (CTRL+Break)
-   * are supported.
+   * _Note_: On Windows only `"SIGINT"` (CTRL+C), `"SIGBREAK"` (CTRL+Break)
+   * and `"SIGHUP"` (closing the console window) are supported.
    *
    * @category Runtime Environment
    */
@@ -4112,6 +4212,8 @@ declare namespace Deno {
     get stdin(): WritableStream<Uint8Array>;
     get stdout(): ReadableStream<Uint8Array>;
     get stderr(): ReadableStream<Uint8Array>;
+    /** The pseudo-terminal allocated with {@linkcode CommandOptions.pty}. */
+    get pty(): ChildPty;
     readonly pid: number;
     /** Get the status of the child. */
     readonly status: Promise<CommandStatus>;
@@ -4120,6 +4222,9 @@ declare namespace Deno {
      * status. */
     output(): Promise<CommandOutput>;
     /** Kills the process with given {@linkcode Deno.Signal}.
+     *
+     * On Windows, only `"SIGKILL"`, `"SIGTERM"`, `"SIGINT"` and `"SIGBREAK"`
+     * are supported, see {@linkcode Deno.kill}.
      *
      * @param [signo="SIGTERM"]
      */
@@ -4133,6 +4238,21 @@ declare namespace Deno {
     unref(): void;
   }
 
+  /**
+   * The parent's end of a pseudo-terminal allocated for a child process.
+   *
+   * @category Sub Process
+   */
+  export interface ChildPty {
+    /** What the child writes to its terminal. */
+    readonly readable: ReadableStream<Uint8Array>;
+    /** Input to the child, as if typed into its terminal. */
+    readonly writable: WritableStream<Uint8Array>;
+    /** Changes the size of the terminal, which sends `SIGWINCH` to the
+     * child. */
+    resize(columns: number, rows: number): void;
+  }
+
   /**
    * Options which can be set when calling {@linkcode Deno.Command}.
    *
@@ -4195,6 +4315,34 @@ declare namespace Deno {
      *
      * @default {false} */
     windowsRawArguments?: boolean;
+
+    /** Lets the child outlive the Deno process. It is started in a new
+     * session on Unix and as a detached process on Windows, and isn't killed
+     * when Deno exits. Call
+     * {@linkcode ChildProcess.unref} to not wait for it to exit.
+     *
+     * @default {false} */
+    detached?: boolean;
+    /** Starts the child in a new process group, which on Unix can be
+     * signalled as a whole with `Deno.kill(-child.pid, signo)`.
+     *
+     * @default {false} */
+    newProcessGroup?: boolean;
+    /** Starts the child in a new session, without a controlling terminal. A
+     * new session also has a new process group. On Windows, only a new
+     * process group is created.
+     *
+     * @default {false} */
+    newSession?: boolean;
+    /** Allocates a pseudo-terminal of the given size, 80 by 24 characters by
+     * default, to be the child's stdin, stdout, stderr and controlling
+     * terminal. The `stdin`, `stdout` and `stderr` options are ignored and
+     * the terminal is accessed with {@linkcode ChildProcess.pty}.
+     *
+     * Only supported on Unix, and only with {@linkcode Deno.Command.spawn}.
+     *
+     * @default {false} */
+    pty?: boolean | { columns?: number; rows?: number };
   }
 
   /**
@@ -4396,12 +4544,19 @@ declare namespace Deno {
    * @category Permissions */
   export interface NetPermissionDescriptor {
     name: "net";
-    /** Optional host string of the form `"<hostname>[:<port>]"`. Examples:
+    /** Optional host string of the form `"<hostname>[:<port>]"`. The
+     * hostname may start with a `*.` wildcard to match its subdomains.
+     * Examples:
      *
      *      "github.com"
      *      "deno.land:8080"
+     *      "*.internal.corp:443"
      */
     host?: string;
+    /** Optional URL to query the permission for. Unlike `host`, this also
+     * takes path-scoped patterns such as `https://api.example.com/v1/*` into
+     * account. Takes precedence over `host` when both are given. */
+    url?: string | URL;
   }
 
   /** The permission descriptor for the `allow-env` permissions, which controls
@@ -5223,6 +5378,12 @@ declare namespace Deno {
    * identified by `pid`. An error will be thrown if a negative `pid` is used on
    * Windows.
    *
+   * On Windows, `"SIGKILL"` and `"SIGTERM"` terminate the process, while
+   * `"SIGINT"` and `"SIGBREAK"` both send a CTRL+Break event. The latter only
+   * reaches processes attached to the same console that were started with
+   * {@linkcode CommandOptions.newProcessGroup}. Other signals are not
+   * supported.
+   *
    * ```ts
    * const p = Deno.run({
    *   cmd: ["sleep", "10000"]
@@ -103,6 +103,24 @@ declare namespace Deno {
    */
   type NativeStructType = { readonly struct: readonly NativeType[] };
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The native union type for interfacing with foreign functions. Like
+   * structs, unions are passed and returned by value as buffers of the size
+   * of their largest member.
+   *
+   * @category FFI
+   */
+  type NativeUnionType = { readonly union: readonly NativeType[] };
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The native types passed and returned by value as buffers.
+   *
+   * @category FFI
+   */
+  type NativeAggregateType = NativeStructType | NativeUnionType;
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * All supported types for interfacing with foreign functions.
@@ -116,7 +134,8 @@ declare namespace Deno {
     | NativePointerType
     | NativeBufferType
     | NativeFunctionType
-    | NativeStructType;
+    | NativeStructType
+    | NativeUnionType;
 
   /** **UNSTABLE**: New API, yet to be vetted.
    *
@@ -147,8 +166,8 @@ declare namespace Deno {
    * @category FFI
    */
   type ToNativeType<T extends NativeType = NativeType> = T extends
-    NativeStructType ? BufferSource
-    : ToNativeTypeMap[Exclude<T, NativeStructType>];
+    NativeAggregateType ? BufferSource
+    : ToNativeTypeMap[Exclude<T, NativeAggregateType>];
 
   /** **UNSTABLE**: New API, yet to be vetted.
    *
@@ -165,8 +184,8 @@ declare namespace Deno {
    * @category FFI
    */
   type ToNativeResultType<T extends NativeResultType = NativeResultType> =
-    T extends NativeStructType ? BufferSource
-      : ToNativeResultTypeMap[Exclude<T, NativeStructType>];
+    T extends NativeAggregateType ? BufferSource
+      : ToNativeResultTypeMap[Exclude<T, NativeAggregateType>];
 
   /** **UNSTABLE**: New API, yet to be vetted.
    *
@@ -207,8 +226,8 @@ declare namespace Deno {
    * @category FFI
    */
   type FromNativeType<T extends NativeType = NativeType> = T extends
-    NativeStructType ? Uint8Array
-    : FromNativeTypeMap[Exclude<T, NativeStructType>];
+    NativeAggregateType ? Uint8Array
+    : FromNativeTypeMap[Exclude<T, NativeAggregateType>];
 
   /** **UNSTABLE**: New API, yet to be vetted.
    *
@@ -227,8 +246,8 @@ declare namespace Deno {
    * @category FFI
    */
   type FromNativeResultType<T extends NativeResultType = NativeResultType> =
-    T extends NativeStructType ? Uint8Array
-      : FromNativeResultTypeMap[Exclude<T, NativeStructType>];
+    T extends NativeAggregateType ? Uint8Array
+      : FromNativeResultTypeMap[Exclude<T, NativeAggregateType>];
 
   /** **UNSTABLE**: New API, yet to be vetted.
    *
@@ -267,7 +286,13 @@ declare namespace Deno {
     /** The result (return value) of the foreign function. */
     result: Result;
     /** When `true`, function calls will run on a dedicated blocking thread and
-     * will return a `Promise` resolving to the `result`. */
+     * will return a `Promise` resolving to the `result`.
+     *
+     * The memory of the `buffer`, struct and union arguments stays allocated
+     * until the call returns, even if the buffers are garbage collected,
+     * detached or transferred while it runs. Pointers obtained with
+     * `Deno.UnsafePointer.of()` aren't tracked and must be kept alive by the
+     * caller. */
     nonblocking?: NonBlocking;
     /** When `true`, function calls can safely callback into JavaScript or
      * trigger a garbage collection event.
@@ -279,6 +304,13 @@ declare namespace Deno {
      *
      * @default {false} */
     optional?: boolean;
+    /** When `true`, the symbol is not looked up by dlopen but on the first
+     * call of the function, which throws if the library doesn't export it.
+     *
+     * Cannot be combined with `optional`.
+     *
+     * @default {false} */
+    lazy?: boolean;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -512,6 +544,15 @@ declare namespace Deno {
     parameters: Parameters;
     /** The current result of the callback. */
     result: Result;
+    /** When `true`, calls from foreign threads queue the callback on the Deno
+     * event loop and return immediately instead of waiting for it to run.
+     * Such callbacks must return `"void"`.
+     *
+     * Calls from the thread of the isolate always run the callback
+     * synchronously.
+     *
+     * @default {false} */
+    nonblocking?: boolean;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -548,6 +589,10 @@ declare namespace Deno {
    * a ref'ed callback does not stop it from waking up the Deno event loop when
    * called from foreign threads.
    *
+   * A foreign thread calling a callback blocks until the callback has run on
+   * the Deno event loop and returned its result, unless the callback is
+   * defined as `nonblocking`.
+   *
    * @category FFI
    */
   export class UnsafeCallback<
@@ -635,6 +680,13 @@ declare namespace Deno {
   export interface DynamicLibrary<S extends ForeignLibraryInterface> {
     /** All of the registered library along with functions for calling them. */
     symbols: StaticForeignLibraryInterface<S>;
+    /** Returns the sorted names of the symbols exported by the library.
+     *
+     * The names are read from the library file at the path it was opened
+     * with, so this throws if the library was found through the system's
+     * library search paths.
+     */
+    exportedSymbols(): string[];
     /** Removes the pointers associated with the library symbols.
      *
      * Continuing to use symbols that are part of the library will lead to
@@ -799,10 +851,66 @@ declare namespace Deno {
   export interface HttpClient {
     /** The resource ID associated with the client. */
     rid: number;
+    /** Replace the root certificates or certificate revocation lists used to
+     * verify the peer's certificate. Requests made afterwards don't reuse the
+     * connections opened before.
+     *
+     * ```ts
+     * const client = Deno.createHttpClient({ caCerts: [caCert] });
+     * // ...
+     * client.updateTls({ caCerts: [newCaCert], crl: [crl] });
+     * ```
+     */
+    updateTls(options: UpdateHttpClientTlsOptions): void;
+    /** Returns counters of the requests sent with the client and of the
+     * connections used for them, to tune `poolMaxIdlePerHost` and
+     * `poolIdleTimeout`. */
+    poolMetrics(): HttpClientPoolMetrics;
     /** Close the HTTP client. */
     close(): void;
   }
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Connection pool metrics of a {@linkcode Deno.HttpClient}, returned by
+   * {@linkcode Deno.HttpClient.poolMetrics}.
+   *
+   * @category Fetch API
+   */
+  export interface HttpClientPoolMetrics {
+    /** The number of requests sent with the client. */
+    requests: number;
+    /** The number of requests waiting for their response headers. */
+    inFlight: number;
+    /** The number of responses received over a newly opened connection. */
+    newConnections: number;
+    /** The number of responses received over a connection that was used
+     * before, either taken from the pool or shared with other requests over
+     * HTTP/2. */
+    reusedConnections: number;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * The TLS options of a {@linkcode Deno.HttpClient} that can be changed
+   * with {@linkcode Deno.HttpClient.updateTls}. Omitted options are left
+   * unchanged.
+   *
+   * @category Fetch API
+   */
+  export interface UpdateHttpClientTlsOptions {
+    /** A list of root certificates that will be used in addition to the
+     * default root certificates to verify the peer's certificate.
+     *
+     * Must be in PEM format. */
+    caCerts?: string[];
+    /** A list of certificate revocation lists. Connections to peers whose
+     * certificate chain contains a revoked certificate fail.
+     *
+     * Must be in PEM format. */
+    crl?: string[];
+  }
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * The options used when creating a {@linkcode Deno.HttpClient}.
@@ -815,8 +923,15 @@ declare namespace Deno {
      *
      * Must be in PEM format. */
     caCerts?: string[];
-    /** A HTTP proxy to use for new connections. */
-    proxy?: Proxy;
+    /** A list of certificate revocation lists. Connections to peers whose
+     * certificate chain contains a revoked certificate fail. The revocation
+     * lists are trusted like `caCerts`, their signatures aren't verified.
+     *
+     * Must be in PEM format. */
+    crl?: string[];
+    /** An HTTP(S) or SOCKS5 proxy to use for new connections, or a unix
+     * domain socket to send all requests to. */
+    proxy?: Proxy | UnixProxy;
     /** PEM formatted client certificate chain. */
     certChain?: string;
     /** PEM formatted (RSA or PKCS8) private key of client certificate. */
@@ -837,22 +952,109 @@ declare namespace Deno {
      * @default {true}
      */
     http2?: boolean;
+    /** The DNS resolver used instead of the runtime's default one.
+     *
+     * Requires `allow-net` permission for the name servers. */
+    resolver?: DnsResolverOptions;
+    /** A jar the cookies of responses are stored in and the cookies of
+     * requests are taken from, including across redirects. Cookies aren't
+     * stored without a jar. */
+    cookieJar?: CookieJar;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Configures a DNS resolver that is used instead of the system's stub
+   * resolver, by {@linkcode Deno.connect}, {@linkcode Deno.connectTls},
+   * {@linkcode Deno.resolveDns} and {@linkcode Deno.createHttpClient}.
+   *
+   * Resolvers are shared between calls with the same options, so records are
+   * cached across them.
+   *
+   * ```ts
+   * const resolver: Deno.DnsResolverOptions = {
+   *   nameServers: [{ ipAddr: "1.1.1.1", port: 443 }],
+   *   protocol: "https",
+   *   tlsName: "cloudflare-dns.com",
+   * };
+   * const conn = await Deno.connect({ hostname: "deno.land", port: 80, resolver });
+   * ```
+   *
+   * The runtime's default resolver can be set with the `--dns-server` and
+   * `--dns-over-https` flags.
+   *
+   * @category Network
+   */
+  export interface DnsResolverOptions {
+    /** The name servers to query. If not specified, the system's name servers
+     * are used. */
+    nameServers?: {
+      /** The IP address of the name server. */
+      ipAddr: string;
+      /** The port number the queries will be sent to.
+       *
+       * @default {53} */
+      port?: number;
+    }[];
+    /** Whether to query the name servers with plain DNS or with
+     * DNS-over-HTTPS. DNS-over-HTTPS requires `nameServers` and `tlsName`.
+     *
+     * @default {"udp"} */
+    protocol?: "udp" | "https";
+    /** The name the TLS certificates of the name servers are verified
+     * against. */
+    tlsName?: string;
+    /** The number of records to cache, or `0` to disable caching.
+     *
+     * @default {32} */
+    cacheSize?: number;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * The definition of a proxy when specifying
-   * {@linkcode Deno.CreateHttpClientOptions}.
+   * {@linkcode Deno.CreateHttpClientOptions}, or for a single request with
+   * the `proxy` option of {@linkcode fetch}.
    *
    * @category Fetch API
    */
   export interface Proxy {
-    /** The string URL of the proxy server to use. */
+    /** The string URL of the proxy server to use. The scheme is one of:
+     *
+     * - `http` or `https`: an HTTP proxy. HTTPS requests are tunneled through
+     *   it with `CONNECT`.
+     * - `socks5`: a SOCKS5 proxy, host names are resolved locally.
+     * - `socks5h`: a SOCKS5 proxy, host names are resolved by the proxy.
+     */
     url: string;
     /** The basic auth credentials to be used against the proxy server. */
     basicAuth?: BasicAuth;
   }
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A unix domain socket that a client created with
+   * {@linkcode Deno.createHttpClient} sends all of its requests to, over
+   * plain HTTP/1.1. The host of a request URL is only used for its `Host`
+   * header.
+   *
+   * ```ts
+   * const client = Deno.createHttpClient({
+   *   proxy: { transport: "unix", path: "/var/run/docker.sock" },
+   * });
+   * const res = await fetch("http://localhost/containers/json", { client });
+   * ```
+   *
+   * Requires `allow-read` and `allow-write` permission on the path.
+   *
+   * @category Fetch API
+   */
+  export interface UnixProxy {
+    transport: "unix";
+    /** The path of the unix domain socket. */
+    path: string;
+  }
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * Basic authentication credentials to be used with a {@linkcode Deno.Proxy}
@@ -892,6 +1094,64 @@ declare namespace Deno {
     options: CreateHttpClientOptions,
   ): HttpClient;
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A cookie stored in a {@linkcode Deno.CookieJar}.
+   *
+   * @category Fetch API
+   */
+  export interface Cookie {
+    name: string;
+    value: string;
+    /** The domain the cookie is sent to, including its subdomains unless
+     * `hostOnly` is set. */
+    domain: string;
+    /** Set for cookies without a `Domain` attribute, which are only sent to
+     * the host that set them. */
+    hostOnly?: boolean;
+    path: string;
+    /** The expiry time in milliseconds since the UNIX epoch, or `null` for
+     * session cookies. */
+    expires: number | null;
+    secure?: boolean;
+    httpOnly?: boolean;
+    sameSite: "Strict" | "Lax" | "None" | null;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A cookie jar for clients created with {@linkcode Deno.createHttpClient}.
+   * Cookies of responses are stored following RFC 6265 and sent with later
+   * requests whose URL matches their domain, path and `Secure` attribute.
+   *
+   * ```ts
+   * const cookieJar = new Deno.CookieJar();
+   * const client = Deno.createHttpClient({ cookieJar });
+   * await fetch("https://example.com/login", { client, method: "POST" });
+   * // The session cookie set by the login is sent along.
+   * await fetch("https://example.com/account", { client });
+   * await Deno.writeTextFile("cookies.json", JSON.stringify(cookieJar));
+   * ```
+   *
+   * @category Fetch API
+   */
+  export class CookieJar {
+    /** Creates a jar, for example with the cookies of a serialized one. */
+    constructor(cookies?: Cookie[]);
+    /** Returns the cookies sent with a request to `url`, in the order they
+     * are sent in, or all cookies if no URL is given. */
+    getCookies(url?: string | URL): Cookie[];
+    /** Stores a cookie as if `setCookie` was received in a `Set-Cookie`
+     * header of a response to `url`. Throws if it would have been ignored. */
+    setCookie(url: string | URL, setCookie: string): void;
+    /** Removes all cookies. */
+    clear(): void;
+    /** Returns all cookies, which can be passed to the constructor. */
+    toJSON(): Cookie[];
+    /** Releases the jar. Clients created with it keep using it. */
+    close(): void;
+  }
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * Represents membership of a IPv4 multicast group.
@@ -918,6 +1178,8 @@ declare namespace Deno {
     leave: () => Promise<void>;
     /** Sets the multicast loopback option. If enabled, multicast packets will be looped back to the local socket. */
     setLoopback: (loopback: boolean) => Promise<void>;
+    /** Sets the hop limit of outgoing multicast packets for this socket. */
+    setTTL: (ttl: number) => Promise<void>;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -939,6 +1201,19 @@ declare namespace Deno {
       networkInterface: number,
     ): Promise<MulticastV6Membership>;
 
+    /** Sets whether sent multicast packets are looped back to the local
+     * socket, without having to join a group. Only supported for `"udp"`. */
+    setMulticastLoopback(loopback: boolean): Promise<void>;
+
+    /** Sets the time-to-live, or hop limit for IPv6, of outgoing multicast
+     * packets, e.g. to keep SSDP or mDNS queries on the local network. Only
+     * supported for `"udp"`. */
+    setMulticastTTL(ttl: number): Promise<void>;
+
+    /** Sets the `SO_BROADCAST` option, which allows sending to the broadcast
+     * address (255.255.255.255). Only supported for `"udp"`. */
+    setBroadcast(broadcast: boolean): Promise<void>;
+
     /** Waits for and resolves to the next message to the instance.
      *
      * Messages are received in the format of a tuple containing the data array
@@ -972,6 +1247,45 @@ declare namespace Deno {
      *
      * @default {false} */
     reusePort?: boolean;
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The size in bytes of the send buffer (`SO_SNDBUF`) of the listener.
+     * Accepted connections inherit it. Defaults to the system default. */
+    sendBufferSize?: number;
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The size in bytes of the receive buffer (`SO_RCVBUF`) of the listener.
+     * Accepted connections inherit it, and it has to be set before listening
+     * for large TCP windows to be negotiated. Defaults to the system default.
+     */
+    recvBufferSize?: number;
+  }
+
+  /** @category Network */
+  export interface TcpConn {
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Sets the size in bytes of the send buffer (`SO_SNDBUF`). */
+    setSendBufferSize(size: number): void;
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Sets the size in bytes of the receive buffer (`SO_RCVBUF`). */
+    setRecvBufferSize(size: number): void;
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Returns the size in bytes of the send buffer. The operating system may
+     * use a different size than requested, e.g. Linux doubles it. */
+    sendBufferSize(): number;
+
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Returns the size in bytes of the receive buffer. The operating system
+     * may use a different size than requested, e.g. Linux doubles it. */
+    recvBufferSize(): number;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1005,6 +1319,12 @@ declare namespace Deno {
      *
      * @default {false} */
     loopback?: boolean;
+
+    /** When `true`, the `SO_BROADCAST` option is set, so messages can be
+     * sent to the broadcast address (255.255.255.255).
+     *
+     * @default {true} */
+    broadcast?: boolean;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1139,6 +1459,69 @@ declare namespace Deno {
      * TLS handshake.
      */
     alpnProtocols?: string[];
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Whether to race connection attempts to the resolved IPv6 and IPv4
+     * addresses, as described by RFC 8305 ("Happy Eyeballs"). When disabled,
+     * the addresses are tried one after another.
+     *
+     * @default {true} */
+    happyEyeballs?: boolean;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The delay in milliseconds before the next address is tried while an
+     * earlier attempt is still pending.
+     *
+     * @default {250} */
+    happyEyeballsDelay?: number;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The DNS resolver used instead of the runtime's default one. Requires
+     * `allow-net` permission for the name servers. */
+    resolver?: DnsResolverOptions;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * @category Network
+   */
+  export interface ConnectOptions {
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Whether to race connection attempts to the resolved IPv6 and IPv4
+     * addresses, as described by RFC 8305 ("Happy Eyeballs"), so that a host
+     * with a broken IPv6 route doesn't wait for it to time out. When disabled,
+     * the addresses are tried one after another.
+     *
+     * `fetch()` always races connection attempts, with a delay of 300
+     * milliseconds.
+     *
+     * @default {true} */
+    happyEyeballs?: boolean;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The delay in milliseconds before the next address is tried while an
+     * earlier attempt is still pending.
+     *
+     * @default {250} */
+    happyEyeballsDelay?: number;
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The DNS resolver used instead of the runtime's default one. Requires
+     * `allow-net` permission for the name servers. */
+    resolver?: DnsResolverOptions;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * @category Network
+   */
+  export interface ResolveDnsOptions {
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * The DNS resolver used instead of the runtime's default one. Ignored
+     * if `nameServer` is specified. */
+    resolver?: DnsResolverOptions;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1200,6 +1583,40 @@ declare namespace Deno {
      * TLS handshake.
      */
     alpnProtocols?: string[];
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * Certificates to present to clients depending on the server name they
+     * request with the SNI extension, so that one listener can serve many
+     * domains. The keys are server names, which may be wildcards covering
+     * one label like `*.example.com`. Clients that don't request a known
+     * server name are presented `cert`, or fail the handshake if it isn't
+     * specified.
+     *
+     * ```ts
+     * const listener = Deno.listenTls({
+     *   port: 443,
+     *   sni: {
+     *     "example.com": { cert: exampleCert, key: exampleKey },
+     *     "*.example.org": { cert: wildcardCert, key: wildcardKey },
+     *   },
+     * });
+     * ```
+     */
+    sni?: Record<string, SniCertificate>;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A certificate chain and private key presented for a server name, see
+   * {@linkcode Deno.ListenTlsOptions.sni}.
+   *
+   * @category Network
+   */
+  export interface SniCertificate {
+    /** Cert chain in PEM format */
+    cert: string;
+    /** Server private key in PEM format */
+    key: string;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1250,6 +1667,148 @@ declare namespace Deno {
    */
   export function funlockSync(rid: number): void;
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returns the value of the extended attribute `name` of the file at `path`,
+   * or `null` if the file doesn't have it.
+   *
+   * On Linux and macOS these are the file's extended attributes, which are not
+   * read through symlinks. On Windows they are stored as alternate data
+   * streams of the file.
+   *
+   * ```ts
+   * const value = await Deno.getXattr("my_file.txt", "user.checksum");
+   * ```
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function getXattr(
+    path: string | URL,
+    name: string,
+  ): Promise<Uint8Array | null>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously returns the value of the extended attribute `name` of the
+   * file at `path`, or `null` if the file doesn't have it.
+   *
+   * ```ts
+   * const value = Deno.getXattrSync("my_file.txt", "user.checksum");
+   * ```
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function getXattrSync(
+    path: string | URL,
+    name: string,
+  ): Uint8Array | null;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Sets the extended attribute `name` of the file at `path` to `value`,
+   * replacing its previous value.
+   *
+   * On Linux, attributes set by unprivileged users must be in the `user.`
+   * namespace.
+   *
+   * ```ts
+   * await Deno.setXattr(
+   *   "my_file.txt",
+   *   "user.checksum",
+   *   new TextEncoder().encode("abc123"),
+   * );
+   * ```
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function setXattr(
+    path: string | URL,
+    name: string,
+    value: Uint8Array,
+  ): Promise<void>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously sets the extended attribute `name` of the file at `path` to
+   * `value`, replacing its previous value.
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function setXattrSync(
+    path: string | URL,
+    name: string,
+    value: Uint8Array,
+  ): void;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Returns the names of the extended attributes of the file at `path`.
+   *
+   * ```ts
+   * for (const name of await Deno.listXattr("my_file.txt")) {
+   *   console.log(name, await Deno.getXattr("my_file.txt", name));
+   * }
+   * ```
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function listXattr(path: string | URL): Promise<string[]>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously returns the names of the extended attributes of the file at
+   * `path`.
+   *
+   * Requires `allow-read` permission.
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function listXattrSync(path: string | URL): string[];
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Removes the extended attribute `name` from the file at `path`. Throws if
+   * the file doesn't have it.
+   *
+   * ```ts
+   * await Deno.removeXattr("my_file.txt", "user.checksum");
+   * ```
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function removeXattr(path: string | URL, name: string): Promise<void>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Synchronously removes the extended attribute `name` from the file at
+   * `path`. Throws if the file doesn't have it.
+   *
+   * Requires `allow-write` permission.
+   *
+   * @tags allow-write
+   * @category File System
+   */
+  export function removeXattrSync(path: string | URL, name: string): void;
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * Information for a HTTP request.
@@ -1294,6 +1853,61 @@ declare namespace Deno {
 
     /** The callback which is called when the server starts listening. */
     onListen?: (params: { hostname: string; port: number }) => void;
+
+    /** How responses are compressed, `false` to never compress them.
+     * Responses are compressed by default.
+     *
+     * @default {true} */
+    compression?: boolean | ServeCompressionOptions;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options for the compression of the responses of {@linkcode Deno.serve}.
+   * A response is compressed when the request accepts one of the
+   * `encodings`, its body isn't known to be smaller than `minSize`, and it
+   * has a compressible `Content-Type` and no `Content-Encoding`,
+   * `Content-Range` or `Cache-Control: no-transform` header.
+   *
+   * ```ts
+   * Deno.serve({
+   *   compression: {
+   *     encodings: ["zstd", "gzip"],
+   *     contentTypes: ["text/*", "application/json"],
+   *     zstdLevel: 6,
+   *   },
+   * }, (_req) => Response.json({ hello: "world" }));
+   * ```
+   *
+   * @category HTTP Server
+   */
+  export interface ServeCompressionOptions {
+    /** The encodings to compress with, in order of preference among the
+     * encodings a client accepts equally.
+     *
+     * @default {["br", "zstd", "gzip"]} */
+    encodings?: ("br" | "zstd" | "gzip")[];
+    /** Bodies known to be smaller than this many bytes are sent
+     * uncompressed.
+     *
+     * @default {64} */
+    minSize?: number;
+    /** The media types to compress, such as `"application/json"` or
+     * `"text/*"`. By default, all types known to be compressible are. */
+    contentTypes?: string[];
+    /** The gzip compression level, between 0 and 9.
+     *
+     * @default {1} */
+    gzipLevel?: number;
+    /** The brotli quality, between 0 and 11.
+     *
+     * @default {6} */
+    brotliQuality?: number;
+    /** The zstd compression level, up to 22. Negative levels trade
+     * compression for speed.
+     *
+     * @default {3} */
+    zstdLevel?: number;
   }
 
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1310,6 +1924,60 @@ declare namespace Deno {
     key: string;
   }
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options which are used when opening a TLS (HTTPS) server that selects
+   * its certificate by the server name clients request, see
+   * {@linkcode Deno.ListenTlsOptions.sni}.
+   *
+   * @category HTTP Server
+   */
+  export interface ServeSniOptions extends ServeOptions {
+    /** Certificates to present by the server name clients request. */
+    sni: Record<string, SniCertificate>;
+
+    /** Cert chain in PEM format, presented to clients that don't request a
+     * known server name. */
+    cert?: string;
+
+    /** Server private key in PEM format, for `cert`. */
+    key?: string;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options which are used to serve HTTP on a unix domain socket instead of a
+   * TCP port, e.g. behind a reverse proxy.
+   *
+   * ```ts
+   * Deno.serve(
+   *   { path: "/tmp/app.sock" },
+   *   (_req) => new Response("Hello, world"),
+   * );
+   * ```
+   *
+   * Requires `allow-read` and `allow-write` permission on the path.
+   *
+   * @category HTTP Server
+   */
+  export interface ServeUnixOptions {
+    /** The path of the unix domain socket to listen on. */
+    path: string;
+
+    /** An {@linkcode AbortSignal} to close the server and all connections. */
+    signal?: AbortSignal;
+
+    /** The handler to invoke when route handlers throw an error. */
+    onError?: (error: unknown) => Response | Promise<Response>;
+
+    /** The callback which is called when the server starts listening. */
+    onListen?: (params: { path: string }) => void;
+
+    /** How responses are compressed, see
+     * {@linkcode Deno.ServeOptions.compression}. */
+    compression?: boolean | ServeCompressionOptions;
+  }
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * @category HTTP Server
@@ -1329,6 +1997,25 @@ declare namespace Deno {
      */
     finished: Promise<void>;
 
+    /** Gracefully shut down the server: stop accepting connections, close
+     * idle connections, and close the others once their in-flight requests
+     * are done, with a `Connection: close` header on HTTP/1.1 and a GOAWAY
+     * frame on HTTP/2. Connections still open after `gracePeriodMs` are
+     * aborted.
+     *
+     * Returns {@linkcode Server.finished}, which resolves once all
+     * connections are closed.
+     *
+     * ```ts
+     * const server = Deno.serve((_req) => new Response("Hello, world"));
+     * Deno.addSignalListener("SIGTERM", async () => {
+     *   await server.shutdown({ gracePeriodMs: 10_000 });
+     *   Deno.exit();
+     * });
+     * ```
+     */
+    shutdown(options?: { gracePeriodMs?: number }): Promise<void>;
+
     /**
      * Make the server block the event loop from finishing.
      *
@@ -1467,7 +2154,11 @@ declare namespace Deno {
    * @category HTTP Server
    */
   export function serve(
-    options: ServeOptions | ServeTlsOptions,
+    options:
+      | ServeOptions
+      | ServeTlsOptions
+      | ServeSniOptions
+      | ServeUnixOptions,
     handler: ServeHandler,
   ): Server;
   /** **UNSTABLE**: New API, yet to be vetted.
@@ -1533,9 +2224,87 @@ declare namespace Deno {
    * @category HTTP Server
    */
   export function serve(
-    options: ServeInit & (ServeOptions | ServeTlsOptions),
+    options:
+      & ServeInit
+      & (
+        | ServeOptions
+        | ServeTlsOptions
+        | ServeSniOptions
+        | ServeUnixOptions
+      ),
   ): Server;
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * An event sent with {@linkcode ServerSentEventStream.send}.
+   *
+   * @category HTTP Server
+   */
+  export interface ServerSentEvent {
+    /** The event type, `"message"` if not set. */
+    event?: string;
+    /** The data of the event. Multi-line data is sent as several `data`
+     * fields, which clients join back together. */
+    data?: string;
+    /** The id clients send back in the `Last-Event-ID` header when they
+     * reconnect. */
+    id?: string | number;
+    /** How long clients wait before reconnecting, in milliseconds. */
+    retry?: number;
+    /** A comment, which clients ignore. */
+    comment?: string;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * @category HTTP Server
+   */
+  export interface ServerSentEventStreamOptions {
+    /** After how many milliseconds without events a comment is sent to keep
+     * the connection from being closed by proxies, `false` to never send
+     * one.
+     *
+     * @default {15000} */
+    keepAlive?: number | false;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A stream of server-sent events to respond with. Events are formatted and
+   * buffered in Rust: `send()` waits while the client doesn't keep up, and
+   * rejects once the client disconnected.
+   *
+   * ```ts
+   * Deno.serve((req) => {
+   *   const stream = new Deno.ServerSentEventStream();
+   *   let id = Number(req.headers.get("last-event-id") ?? 0);
+   *   const timer = setInterval(() => {
+   *     stream.send({ id: ++id, data: new Date().toISOString() })
+   *       .catch(() => {});
+   *   }, 1000);
+   *   stream.closed.then(() => clearInterval(timer));
+   *   return stream.toResponse();
+   * });
+   * ```
+   *
+   * @category HTTP Server
+   */
+  export class ServerSentEventStream {
+    constructor(options?: ServerSentEventStreamOptions);
+    /** The formatted events, to use as a response body. */
+    readonly readable: ReadableStream<Uint8Array>;
+    /** Resolves once the client disconnected or the stream was closed. */
+    readonly closed: Promise<void>;
+    /** Sends an event, or a message with the given data. */
+    send(event: ServerSentEvent | string): Promise<void>;
+    /** Ends the stream once the events sent so far are delivered. */
+    close(): void;
+    /** Creates a response streaming the events, with `Content-Type:
+     * text/event-stream` and `Cache-Control: no-cache` unless set in
+     * `init`. */
+    toResponse(init?: ResponseInit): Response;
+  }
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * Allows "hijacking" the connection that the request is associated with. This
@@ -1577,11 +2346,104 @@ declare namespace Deno {
    * `localStorage` persistence). More information about the origin storage key
    * can be found in the Deno Manual.
    *
-   * @tags allow-read, allow-write
+   * When the path is an `http:` or `https:` URL, the database is a remote
+   * database served at that URL. Network access to the URL is required. If the
+   * `DENO_KV_ACCESS_TOKEN` environment variable is set, it is sent to the
+   * server as a bearer token. Remote databases don't support queues.
+   *
+   * @tags allow-read, allow-write, allow-net, allow-env
    * @category KV
    */
   export function openKv(path?: string): Promise<Deno.Kv>;
 
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Opens the file at `path` as a {@linkcode File}, without reading it. The
+   * contents are read from disk when the blob, or a slice of it, is read, so
+   * large files can be passed to `fetch()` or put in a {@linkcode FormData}
+   * without loading them into memory.
+   *
+   * Reading the blob fails with a `NotReadableError` if the file changed
+   * after it was opened.
+   *
+   * ```ts
+   * const file = await Deno.openBlob("./video.mp4", { type: "video/mp4" });
+   * const form = new FormData();
+   * form.append("video", file);
+   * await fetch("https://example.com/upload", { method: "POST", body: form });
+   * ```
+   *
+   * @tags allow-read
+   * @category File System
+   */
+  export function openBlob(
+    path: string | URL,
+    options?: BlobPropertyBag,
+  ): Promise<File>;
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Options for {@linkcode Deno.cron}.
+   *
+   * @category Cron
+   */
+  export interface CronOptions {
+    /** What to do when the schedule is due while the handler of the last run
+     * is still running:
+     *
+     * - `"skip"` skips the run.
+     * - `"queue"` runs the handler once more after the running one finished.
+     * - `"allow"` runs the handlers concurrently.
+     *
+     * @default {"skip"}
+     */
+    overlap?: "skip" | "queue" | "allow";
+    /** Unregisters the cron when aborted. */
+    signal?: AbortSignal;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Registers a cron that runs `handler` whenever `schedule` is due. The
+   * schedule is a cron expression of five fields (minute, hour, day of the
+   * month, month and day of the week), evaluated in UTC, or one of `@yearly`,
+   * `@monthly`, `@weekly`, `@daily` and `@hourly`. Names must be unique.
+   *
+   * The program is kept alive while a cron is registered. The returned
+   * promise resolves once the cron was unregistered by aborting
+   * `options.signal` and its last run finished.
+   *
+   * Like `localStorage`, crons are registered in the storage of the origin
+   * of the program. The registration remembers when the cron last ran, so a
+   * restarted program doesn't run it again for the same minute.
+   *
+   * ```ts
+   * Deno.cron("cleanup", "0 * * * *", async () => {
+   *   await removeExpiredSessions();
+   * });
+   * ```
+   *
+   * @category Cron
+   */
+  export function cron(
+    name: string,
+    schedule: string,
+    handler: () => Promise<void> | void,
+  ): Promise<void>;
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Registers a cron that runs `handler` whenever `schedule` is due. See
+   * {@linkcode CronOptions} for the available options.
+   *
+   * @category Cron
+   */
+  export function cron(
+    name: string,
+    schedule: string,
+    options: CronOptions,
+    handler: () => Promise<void> | void,
+  ): Promise<void>;
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * A key to be persisted in a {@linkcode Deno.Kv}. A key is a sequence
@@ -1920,7 +2782,11 @@ declare namespace Deno {
      */
     enqueue(
       value: unknown,
-      options?: { delay?: number; keysIfUndelivered?: Deno.KvKey[] },
+      options?: {
+        delay?: number;
+        keysIfUndelivered?: Deno.KvKey[];
+        backoffSchedule?: number[];
+      },
     ): this;
     /**
      * Commit the operation to the KV store. Returns a value indicating whether
@@ -2122,10 +2988,25 @@ declare namespace Deno {
      * const db = await Deno.openKv();
      * await db.enqueue("bar", { keysIfUndelivered: [["foo", "bar"]] });
      * ```
+     *
+     * The `backoffSchedule` option can be used to specify the delays (in
+     * milliseconds) between the attempts to deliver the value when the
+     * handler throws. It can have at most 5 intervals of up to an hour. The
+     * default is `[100, 1000, 5000, 30000, 60000]`; an empty schedule means
+     * the value is only delivered once.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * await db.enqueue("bar", { backoffSchedule: [1000, 5000, 10000] });
+     * ```
      */
     enqueue(
       value: unknown,
-      options?: { delay?: number; keysIfUndelivered?: Deno.KvKey[] },
+      options?: {
+        delay?: number;
+        keysIfUndelivered?: Deno.KvKey[];
+        backoffSchedule?: number[];
+      },
     ): Promise<KvCommitResult>;
 
     /**
@@ -2146,6 +3027,29 @@ declare namespace Deno {
       handler: (value: unknown) => Promise<void> | void,
     ): Promise<void>;
 
+    /**
+     * Watch the given keys for changes. The returned stream yields the
+     * entries of all the keys right away, and again whenever the versionstamp
+     * of any of them changes. Up to 10 keys can be watched at once.
+     *
+     * Changes made close together may be yielded together, so not every
+     * intermediate value is seen. Writes through other connections to the
+     * same database are noticed too, but with a small delay.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * for await (const [foo, bar] of db.watch([["foo"], ["bar"]])) {
+     *   console.log(foo.value, bar.value);
+     * }
+     * ```
+     *
+     * Cancel the stream, for example by breaking out of the loop, to stop
+     * watching. The stream ends when the database is closed.
+     */
+    watch<T extends readonly unknown[]>(
+      keys: readonly [...{ [K in keyof T]: KvKey }],
+    ): ReadableStream<{ [K in keyof T]: KvEntryMaybe<T[K]> }>;
+
     /**
      * Create a new {@linkcode Deno.AtomicOperation} object which can be used to
      * perform an atomic transaction on the database. This does not perform any
@@ -2185,12 +3089,21 @@ declare namespace Deno {
  * which also supports setting a {@linkcode Deno.HttpClient} which provides a
  * way to connect via proxies and use custom TLS certificates.
  *
+ * A `proxy` sends a single request through that proxy instead of the one of
+ * the client, if any. The rest of the client's configuration still applies.
+ *
+ * ```ts
+ * const res = await fetch("https://example.com", {
+ *   proxy: { url: "socks5h://localhost:1080" },
+ * });
+ * ```
+ *
  * @tags allow-net, allow-read
  * @category Fetch API
  */
 declare function fetch(
   input: Request | URL | string,
-  init?: RequestInit & { client: Deno.HttpClient },
+  init?: RequestInit & { client?: Deno.HttpClient; proxy?: Deno.Proxy },
 ): Promise<Response>;
 
 /** **UNSTABLE**: New API, yet to be vetted.
@@ -2230,6 +3143,14 @@ declare interface WorkerOptions {
   deno?: {
     /** Set to `"none"` to disable all the permissions in the worker. */
     permissions?: Deno.PermissionOptions;
+    /** Maximum size of the worker's heap, in megabytes. A worker that
+     * reaches it is terminated and an `error` event is dispatched on the
+     * `Worker` object. */
+    memoryLimitMb?: number;
+    /** Maximum CPU time the worker's thread may use, in milliseconds. A
+     * worker that exceeds it is terminated and an `error` event is
+     * dispatched on the `Worker` object. */
+    cpuTimeLimitMs?: number;
   };
 }
 
@@ -2241,6 +3162,11 @@ declare interface WebSocketStreamOptions {
   protocols?: string[];
   signal?: AbortSignal;
   headers?: HeadersInit;
+  /** PEM formatted client certificate chain, presented to `wss:` servers
+   * that request client authentication. */
+  certChain?: string;
+  /** PEM formatted (RSA or PKCS8) private key of the client certificate. */
+  privateKey?: string;
 }
 
 /** **UNSTABLE**: New API, yet to be vetted.
@@ -2249,7 +3175,7 @@ declare interface WebSocketStreamOptions {
  */
 declare interface WebSocketConnection {
   readable: ReadableStream<string | Uint8Array>;
-  writable: WritableStream<string | Uint8Array>;
+  writable: WritableStream<string | BufferSource>;
   extensions: string;
   protocol: string;
 }
@@ -2259,6 +3185,9 @@ declare interface WebSocketConnection {
  * @category Web Sockets
  */
 declare interface WebSocketCloseInfo {
+  /** The close code, takes precedence over `code`. */
+  closeCode?: number;
+  /** The close code, the name used by earlier versions of the proposal. */
   code?: number;
   reason?: string;
 }
@@ -2271,6 +3200,10 @@ declare interface WebSocketCloseInfo {
 declare class WebSocketStream {
   constructor(url: string, options?: WebSocketStreamOptions);
   url: string;
+  /** Resolves once the connection is established. */
+  opened: Promise<WebSocketConnection>;
+  /** The same as `opened`, the name used by earlier versions of the
+   * proposal. */
   connection: Promise<WebSocketConnection>;
   closed: Promise<WebSocketCloseInfo>;
   close(closeInfo?: WebSocketCloseInfo): void;
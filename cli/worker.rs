@@ -22,6 +22,7 @@ use deno_lockfile::Lockfile;
 use deno_runtime::colors;
 use deno_runtime::deno_broadcast_channel::InMemoryBroadcastChannel;
 use deno_runtime::deno_fs;
+use deno_runtime::deno_net::resolver::DnsConfig;
 use deno_runtime::deno_node;
 use deno_runtime::deno_node::NodeResolution;
 use deno_runtime::deno_node::NodeResolver;
@@ -31,6 +32,7 @@ use deno_runtime::fmt_errors::format_js_error;
 use deno_runtime::inspector_server::InspectorServer;
 use deno_runtime::ops::worker_host::CreateWebWorkerCb;
 use deno_runtime::ops::worker_host::WorkerEventCb;
+use deno_runtime::permissions::PermissionAuditLog;
 use deno_runtime::permissions::PermissionsContainer;
 use deno_runtime::web_worker::WebWorker;
 use deno_runtime::web_worker::WebWorkerOptions;
@@ -76,6 +78,7 @@ pub struct CliMainWorkerOptions {
   pub argv: Vec<String>,
   pub log_level: WorkerLogLevel,
   pub coverage_dir: Option<String>,
+  pub dns_config: Option<DnsConfig>,
   pub enable_testing_features: bool,
   pub has_node_modules_dir: bool,
   pub inspect_brk: bool,
@@ -85,6 +88,7 @@ pub struct CliMainWorkerOptions {
   pub location: Option<Url>,
   pub maybe_binary_npm_command_name: Option<String>,
   pub origin_data_folder_path: Option<PathBuf>,
+  pub permission_audit_log: Option<PermissionAuditLog>,
   pub seed: Option<u64>,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
   pub unstable: bool,
@@ -327,7 +331,7 @@ impl CliMainWorkerFactory {
         node_resolver,
         has_node_specifier_checker,
         blob_store,
-        broadcast_channel: Default::default(),
+        broadcast_channel: create_broadcast_channel(),
         shared_array_buffer_store: Default::default(),
         compiled_wasm_module_store: Default::default(),
         module_loader_factory,
@@ -357,11 +361,14 @@ impl CliMainWorkerFactory {
   pub async fn create_custom_worker(
     &self,
     main_module: ModuleSpecifier,
-    permissions: PermissionsContainer,
+    mut permissions: PermissionsContainer,
     mut custom_extensions: Vec<Extension>,
     stdio: deno_runtime::deno_io::Stdio,
   ) -> Result<CliMainWorker, AnyError> {
     let shared = &self.shared;
+    if let Some(audit_log) = &shared.options.permission_audit_log {
+      permissions.set_audit_log(Some(audit_log.clone()));
+    }
     let (main_module, is_main_cjs) = if let Ok(package_ref) =
       NpmPackageReqReference::from_specifier(&main_module)
     {
@@ -419,8 +426,8 @@ impl CliMainWorkerFactory {
         .join(checksum::gen(&[key.as_bytes()]))
     });
     let cache_storage_dir = maybe_storage_key.map(|key| {
-      // TODO(@satyarohith): storage quota management
-      // Note: we currently use temp_dir() to avoid managing storage size.
+      // Note: we use temp_dir() so that the storage gets cleaned up by the OS,
+      // its size can additionally be capped with DENO_CACHE_STORAGE_MAX_SIZE.
       std::env::temp_dir()
         .join("deno_cache")
         .join(checksum::gen(&[key.as_bytes()]))
@@ -454,6 +461,7 @@ impl CliMainWorkerFactory {
         .options
         .unsafely_ignore_certificate_errors
         .clone(),
+      dns_config: shared.options.dns_config.clone(),
       root_cert_store_provider: Some(shared.root_cert_store_provider.clone()),
       seed: shared.options.seed,
       source_map_getter: maybe_source_map_getter,
@@ -469,6 +477,7 @@ impl CliMainWorkerFactory {
       npm_resolver: Some(shared.npm_resolver.clone()),
       get_error_class_fn: Some(&errors::get_error_class_name),
       cache_storage_dir,
+      cache_storage_max_size: cache_storage_max_size(),
       origin_storage_dir,
       blob_store: shared.blob_store.clone(),
       broadcast_channel: shared.broadcast_channel.clone(),
@@ -496,6 +505,43 @@ impl CliMainWorkerFactory {
 
 // TODO(bartlomieju): this callback could have default value
 // and not be required
+/// Creates the channel shared by the workers of this process. When
+/// `DENO_BROADCAST_CHANNEL_DIR` is set, messages are also exchanged with the
+/// other processes that use the same directory.
+fn create_broadcast_channel() -> InMemoryBroadcastChannel {
+  #[cfg(unix)]
+  if let Some(dir) = std::env::var_os("DENO_BROADCAST_CHANNEL_DIR") {
+    use deno_runtime::deno_broadcast_channel::UnixSocketTransport;
+    let result = UnixSocketTransport::new(dir).and_then(|transport| {
+      InMemoryBroadcastChannel::with_transport(Arc::new(transport))
+    });
+    match result {
+      Ok(channel) => return channel,
+      Err(err) => log::warn!(
+        "{} Could not share BroadcastChannel messages with other processes: {:#}",
+        colors::yellow("Warning"),
+        err
+      ),
+    }
+  }
+  InMemoryBroadcastChannel::default()
+}
+
+fn cache_storage_max_size() -> Option<u64> {
+  let value = std::env::var("DENO_CACHE_STORAGE_MAX_SIZE").ok()?;
+  match value.parse() {
+    Ok(max_size) => Some(max_size),
+    Err(_) => {
+      log::warn!(
+        "{} Ignoring invalid DENO_CACHE_STORAGE_MAX_SIZE, expected a number of bytes: {}",
+        colors::yellow("Warning"),
+        value
+      );
+      None
+    }
+  }
+}
+
 fn create_web_worker_preload_module_callback(
   _shared: &Arc<SharedWorkerState>,
 ) -> Arc<WorkerEventCb> {
@@ -551,8 +597,8 @@ fn create_web_worker_callback(
       .storage_key_resolver
       .resolve_storage_key(&args.main_module);
     let cache_storage_dir = maybe_storage_key.map(|key| {
-      // TODO(@satyarohith): storage quota management
-      // Note: we currently use temp_dir() to avoid managing storage size.
+      // Note: we use temp_dir() so that the storage gets cleaned up by the OS,
+      // its size can additionally be capped with DENO_CACHE_STORAGE_MAX_SIZE.
       std::env::temp_dir()
         .join("deno_cache")
         .join(checksum::gen(&[key.as_bytes()]))
@@ -582,6 +628,7 @@ fn create_web_worker_callback(
         .options
         .unsafely_ignore_certificate_errors
         .clone(),
+      dns_config: shared.options.dns_config.clone(),
       root_cert_store_provider: Some(shared.root_cert_store_provider.clone()),
       seed: shared.options.seed,
       create_web_worker_cb,
@@ -603,6 +650,8 @@ fn create_web_worker_callback(
       ),
       stdio: stdio.clone(),
       cache_storage_dir,
+      cache_storage_max_size: cache_storage_max_size(),
+      resource_limits: args.resource_limits,
     };
 
     WebWorker::bootstrap_from_options(
@@ -206,7 +206,7 @@
     errorMap[className] = errorBuilder;
   }
 
-  function buildCustomError(className, message, code) {
+  function buildCustomError(className, message, code, properties) {
     let error;
     try {
       error = errorMap[className]?.(message);
@@ -221,6 +221,10 @@
       if (code) {
         error.code = code;
       }
+      // Set properties from the error class registry, see error.rs
+      if (properties) {
+        ObjectAssign(error, properties);
+      }
     }
     return error;
   }
@@ -241,6 +245,10 @@
       if (res.code) {
         err.code = res.code;
       }
+      // Set properties from the error class registry, see error.rs
+      if (res.properties) {
+        ObjectAssign(err, res.properties);
+      }
       // Strip unwrapOpResult() and errorBuilder() calls from stack trace
       ErrorCaptureStackTrace(err, hideFunction);
       throw err;
@@ -259,6 +267,10 @@
       if (res.code) {
         err.code = res.code;
       }
+      // Set properties from the error class registry, see error.rs
+      if (res.properties) {
+        ObjectAssign(err, res.properties);
+      }
       // Strip unwrapOpResult() and errorBuilder() calls from stack trace
       ErrorCaptureStackTrace(err, hideFunction);
       return PromiseReject(err);
@@ -627,6 +639,10 @@ for (let i = 0; i < 10; i++) {
     return ObjectFromEntries(ops.op_resources());
   }
 
+  function eventLoopStats() {
+    return ops.op_event_loop_stats();
+  }
+
   function metrics() {
     const { 0: aggregate, 1: perOps } = ops.op_metrics();
     aggregate.ops = ObjectFromEntries(ArrayPrototypeMap(
@@ -785,6 +801,9 @@ for (let i = 0; i < 10; i++) {
     op_read_all: readAll,
     op_write: write,
     op_write_all: writeAll,
+    op_readv: readv,
+    op_writev: writev,
+    op_writev_all: writevAll,
     op_read_sync: readSync,
     op_write_sync: writeSync,
     op_shutdown: shutdown,
@@ -797,6 +816,7 @@ for (let i = 0; i < 10; i++) {
     opAsync,
     resources,
     metrics,
+    eventLoopStats,
     registerErrorBuilder,
     registerErrorClass,
     buildCustomError,
@@ -818,6 +838,9 @@ for (let i = 0; i < 10; i++) {
     readAll,
     write,
     writeAll,
+    readv,
+    writev,
+    writevAll,
     readSync,
     writeSync,
     shutdown,
@@ -0,0 +1,30 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+"use strict";
+
+// Only executed when the runtime was created with a custom clock: routes
+// `Date.now()` and `new Date()` through it.
+((window) => {
+  const { ops } = window.Deno.core;
+  const OriginalDate = window.Date;
+
+  const now = () => ops.op_clock_now();
+
+  const Date = new Proxy(OriginalDate, {
+    apply() {
+      return new OriginalDate(now()).toString();
+    },
+    construct(target, args, newTarget) {
+      if (args.length === 0) {
+        args = [now()];
+      }
+      return Reflect.construct(
+        target,
+        args,
+        newTarget === Date ? target : newTarget,
+      );
+    },
+  });
+  OriginalDate.now = now;
+  OriginalDate.prototype.constructor = Date;
+  window.Date = Date;
+})(globalThis);
@@ -0,0 +1,193 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+/// The time source of a runtime, available to ops as [`crate::OpState::clock`].
+///
+/// Timers and `performance.now()` are expected to be driven by `elapsed()` and
+/// `sleep()`. When a clock is passed in [`crate::RuntimeOptions::clock`],
+/// `Date.now()` and `new Date()` read `system_time()` as well.
+pub trait Clock {
+  /// Monotonic time elapsed since a fixed, arbitrary origin.
+  fn elapsed(&self) -> Duration;
+
+  /// The current wall-clock time.
+  fn system_time(&self) -> SystemTime;
+
+  /// Returns a future that resolves once `elapsed()` has advanced by
+  /// `duration`.
+  fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>>;
+}
+
+/// A [`Clock`] backed by the operating system's clocks and tokio timers. This
+/// is the clock used when none is passed in [`crate::RuntimeOptions::clock`].
+pub struct SystemClock {
+  origin: Instant,
+}
+
+impl SystemClock {
+  pub fn new() -> Self {
+    Self {
+      origin: Instant::now(),
+    }
+  }
+}
+
+impl Default for SystemClock {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Clock for SystemClock {
+  fn elapsed(&self) -> Duration {
+    self.origin.elapsed()
+  }
+
+  fn system_time(&self) -> SystemTime {
+    SystemTime::now()
+  }
+
+  fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+    Box::pin(tokio::time::sleep(duration))
+  }
+}
+
+/// A [`Clock`] that only moves when it is told to, for deterministic tests and
+/// simulations.
+///
+/// Clones share the same time, so one clone can be passed to the runtime and
+/// another kept to call [`VirtualClock::advance`]. Advancing the clock wakes
+/// the sleeps that are due; the runtime's event loop must still be polled for
+/// the timers to fire.
+#[derive(Clone)]
+pub struct VirtualClock(Rc<RefCell<VirtualClockState>>);
+
+struct VirtualClockState {
+  elapsed: Duration,
+  epoch: SystemTime,
+  next_sleep_id: u64,
+  sleeps: BTreeMap<(Duration, u64), Option<Waker>>,
+  /// Woken whenever a sleep starts, see [`VirtualClock::register_sleep_waker`].
+  sleep_waker: Option<Waker>,
+}
+
+impl VirtualClock {
+  /// Creates a clock whose wall-clock time starts at `epoch`.
+  pub fn new(epoch: SystemTime) -> Self {
+    Self(Rc::new(RefCell::new(VirtualClockState {
+      elapsed: Duration::ZERO,
+      epoch,
+      next_sleep_id: 0,
+      sleeps: BTreeMap::new(),
+      sleep_waker: None,
+    })))
+  }
+
+  /// Moves the clock forward by `duration`, waking the sleeps that are due.
+  pub fn advance(&self, duration: Duration) {
+    let wakers = {
+      let mut state = self.0.borrow_mut();
+      state.elapsed += duration;
+      let pending = state.sleeps.split_off(&(state.elapsed, u64::MAX));
+      std::mem::replace(&mut state.sleeps, pending)
+    };
+    for waker in wakers.into_values().flatten() {
+      waker.wake();
+    }
+  }
+
+  /// Moves the clock forward to the deadline of the earliest pending sleep.
+  /// Returns false if there are no pending sleeps.
+  pub fn advance_to_next_sleep(&self) -> bool {
+    let next = self
+      .0
+      .borrow()
+      .sleeps
+      .keys()
+      .next()
+      .map(|(deadline, _)| *deadline);
+    match next {
+      Some(deadline) => {
+        let elapsed = self.0.borrow().elapsed;
+        self.advance(deadline.saturating_sub(elapsed));
+        true
+      }
+      None => false,
+    }
+  }
+
+  /// Number of sleeps that haven't resolved yet.
+  pub fn pending_sleeps(&self) -> usize {
+    self.0.borrow().sleeps.len()
+  }
+
+  /// Wakes `waker` the next time a sleep starts, so the event loop can
+  /// decide whether to advance the clock.
+  pub(crate) fn register_sleep_waker(&self, waker: &Waker) {
+    self.0.borrow_mut().sleep_waker = Some(waker.clone());
+  }
+}
+
+impl Clock for VirtualClock {
+  fn elapsed(&self) -> Duration {
+    self.0.borrow().elapsed
+  }
+
+  fn system_time(&self) -> SystemTime {
+    let state = self.0.borrow();
+    state.epoch + state.elapsed
+  }
+
+  fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+    let mut state = self.0.borrow_mut();
+    let key = (state.elapsed + duration, state.next_sleep_id);
+    state.next_sleep_id += 1;
+    if !duration.is_zero() {
+      state.sleeps.insert(key, None);
+      if let Some(waker) = state.sleep_waker.take() {
+        waker.wake();
+      }
+    }
+    Box::pin(VirtualSleep {
+      clock: self.clone(),
+      key,
+    })
+  }
+}
+
+struct VirtualSleep {
+  clock: VirtualClock,
+  key: (Duration, u64),
+}
+
+impl Future for VirtualSleep {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+    let mut state = self.clock.0.borrow_mut();
+    match state.sleeps.get_mut(&self.key) {
+      Some(waker) => {
+        *waker = Some(cx.waker().clone());
+        Poll::Pending
+      }
+      None => Poll::Ready(()),
+    }
+  }
+}
+
+impl Drop for VirtualSleep {
+  fn drop(&mut self) {
+    self.clock.0.borrow_mut().sleeps.remove(&self.key);
+  }
+}
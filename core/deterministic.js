@@ -0,0 +1,38 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+"use strict";
+
+// Only executed in the deterministic mode: replaces `Math.random()` with a
+// small fast counter generator (sfc32) seeded from a 64-bit seed.
+((seed) => {
+  let a = Number(seed & 0xffffffffn) | 0;
+  let b = Number((seed >> 32n) & 0xffffffffn) | 0;
+  let c = 0x9e3779b9 | 0;
+  let d = 1;
+
+  function next() {
+    const t = (((a + b) | 0) + d) | 0;
+    d = (d + 1) | 0;
+    a = b ^ (b >>> 9);
+    b = (c + (c << 3)) | 0;
+    c = (c << 21) | (c >>> 11);
+    c = (c + t) | 0;
+    return t >>> 0;
+  }
+
+  // Mix the seed into the state before handing out numbers.
+  for (let i = 0; i < 15; i++) {
+    next();
+  }
+
+  function random() {
+    // 53 random bits, like V8's own implementation.
+    return ((next() >>> 5) * 67108864 + (next() >>> 6)) / 9007199254740992;
+  }
+
+  Object.defineProperty(Math, "random", {
+    value: random,
+    writable: true,
+    enumerable: false,
+    configurable: true,
+  });
+})
@@ -6,6 +6,8 @@ use std::fmt;
 use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::rc::Rc;
+use std::time::Duration;
 
 use anyhow::Error;
 
@@ -13,6 +15,7 @@ use crate::runtime::JsRealm;
 use crate::runtime::JsRuntime;
 use crate::source_map::apply_source_map;
 use crate::source_map::get_source_line;
+use crate::source_map::SourceMapper;
 use crate::url::Url;
 
 /// A generic wrapper that can encapsulate any concrete error type.
@@ -22,6 +25,83 @@ pub type AnyError = anyhow::Error;
 pub type JsErrorCreateFn = dyn Fn(JsError) -> Error;
 pub type GetErrorClassFn = &'static dyn for<'e> Fn(&'e Error) -> &'static str;
 
+/// Extra properties set on a JS error built from a Rust error, in addition to
+/// `message` and `code`.
+pub type ErrorProperties = serde_json::Map<String, serde_json::Value>;
+
+/// Maps Rust error types to JS error classes. When an op returns an error
+/// that `anyhow` can downcast to a registered type, the JS error is built
+/// with the registered class instead of the one from [`GetErrorClassFn`].
+///
+/// The class must also be registered on the JS side with
+/// `Deno.core.registerErrorClass()` or `Deno.core.registerErrorBuilder()`.
+#[derive(Default)]
+pub struct ErrorClassRegistry {
+  entries: Vec<ErrorClassEntry>,
+}
+
+struct ErrorClassEntry {
+  class: &'static str,
+  // Returns the properties of the JS error if the error is of the registered
+  // type.
+  matcher: Box<dyn Fn(&Error) -> Option<ErrorProperties>>,
+}
+
+impl ErrorClassRegistry {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Build errors of type `E` as instances of `class`.
+  pub fn register<E>(&mut self, class: &'static str) -> &mut Self
+  where
+    E: Display + Debug + Send + Sync + 'static,
+  {
+    self.register_with_properties::<E, _>(class, |_| ErrorProperties::new())
+  }
+
+  /// Build errors of type `E` as instances of `class`, and set the properties
+  /// returned by `properties` on them.
+  pub fn register_with_properties<E, F>(
+    &mut self,
+    class: &'static str,
+    properties: F,
+  ) -> &mut Self
+  where
+    E: Display + Debug + Send + Sync + 'static,
+    F: Fn(&E) -> ErrorProperties + 'static,
+  {
+    self.entries.push(ErrorClassEntry {
+      class,
+      matcher: Box::new(move |error: &Error| {
+        error.downcast_ref::<E>().map(&properties)
+      }),
+    });
+    self
+  }
+
+  /// Returns the class and properties of the first registration `error` can
+  /// be downcast to.
+  pub fn lookup(
+    &self,
+    error: &Error,
+  ) -> Option<(&'static str, ErrorProperties)> {
+    self
+      .entries
+      .iter()
+      .find_map(|entry| Some((entry.class, (entry.matcher)(error)?)))
+  }
+
+  pub(crate) fn from_scope(
+    scope: &mut v8::HandleScope,
+  ) -> Option<Rc<ErrorClassRegistry>> {
+    JsRuntime::state_from(scope)
+      .borrow()
+      .error_class_registry
+      .clone()
+  }
+}
+
 /// Creates a new error with a caller-specified error class name and message.
 pub fn custom_error(
   class: &'static str,
@@ -66,6 +146,24 @@ pub fn not_supported() -> Error {
   custom_error("NotSupported", "The operation is not supported")
 }
 
+/// The error an async op resolves to when its dispatch was canceled through
+/// [`crate::OpState::set_op_cancel_handle`].
+pub fn op_canceled(op_name: &str) -> Error {
+  custom_error("Interrupted", format!("Op \"{op_name}\" was canceled"))
+}
+
+/// The error an async op resolves to when it runs past the deadline set with
+/// [`crate::OpState::set_op_deadline`].
+pub fn op_timed_out(op_name: &str, deadline: Duration) -> Error {
+  custom_error(
+    "TimedOut",
+    format!(
+      "Op \"{op_name}\" did not complete within {}ms",
+      deadline.as_millis()
+    ),
+  )
+}
+
 pub fn resource_unavailable() -> Error {
   custom_error(
     "Busy",
@@ -110,12 +208,28 @@ pub fn to_v8_error<'a>(
     .expect("Custom error builder must be set");
   let cb = cb.open(tc_scope);
   let this = v8::undefined(tc_scope).into();
-  let class = v8::String::new(tc_scope, get_class(error)).unwrap();
+  let (class, properties) = match ErrorClassRegistry::from_scope(tc_scope)
+    .and_then(|registry| registry.lookup(error))
+  {
+    Some((class, properties)) => (class, Some(properties)),
+    None => (get_class(error), None),
+  };
+  let class = v8::String::new(tc_scope, class).unwrap();
   let message = v8::String::new(tc_scope, &format!("{error:#}")).unwrap();
   let mut args = vec![class.into(), message.into()];
-  if let Some(code) = crate::error_codes::get_error_code(error) {
+  let code = crate::error_codes::get_error_code(error);
+  if let Some(code) = code {
     args.push(v8::String::new(tc_scope, code).unwrap().into());
   }
+  if let Some(properties) = properties.filter(|p| !p.is_empty()) {
+    if code.is_none() {
+      args.push(this);
+    }
+    args.push(
+      serde_v8::to_v8(tc_scope, properties)
+        .unwrap_or_else(|_| v8::undefined(tc_scope).into()),
+    );
+  }
   let maybe_exception = cb.call(tc_scope, this, &args);
 
   match maybe_exception {
@@ -211,19 +325,9 @@ impl JsStackFrame {
     let l = message.get_line_number(scope)? as i64;
     // V8's column numbers are 0-based, we want 1-based.
     let c = message.get_start_column() as i64 + 1;
-    let state_rc = JsRuntime::state_from(scope);
-    let (getter, cache) = {
-      let state = state_rc.borrow();
-      (
-        state.source_map_getter.clone(),
-        state.source_map_cache.clone(),
-      )
-    };
-
-    if let Some(source_map_getter) = getter {
+    if let Some((source_mapper, cache)) = SourceMapper::from_scope(scope) {
       let mut cache = cache.borrow_mut();
-      let (f, l, c) =
-        apply_source_map(f, l, c, &mut cache, &**source_map_getter);
+      let (f, l, c) = apply_source_map(f, l, c, &mut cache, &source_mapper);
       Some(JsStackFrame::from_location(Some(f), Some(l), Some(c)))
     } else {
       Some(JsStackFrame::from_location(Some(f), Some(l), Some(c)))
@@ -284,15 +388,7 @@ impl JsError {
       frames = vec![stack_frame];
     }
     {
-      let state_rc = JsRuntime::state_from(scope);
-      let (getter, cache) = {
-        let state = state_rc.borrow();
-        (
-          state.source_map_getter.clone(),
-          state.source_map_cache.clone(),
-        )
-      };
-      if let Some(source_map_getter) = getter {
+      if let Some((source_mapper, cache)) = SourceMapper::from_scope(scope) {
         let mut cache = cache.borrow_mut();
         for (i, frame) in frames.iter().enumerate() {
           if let (Some(file_name), Some(line_number)) =
@@ -303,7 +399,7 @@ impl JsError {
                 file_name,
                 line_number,
                 &mut cache,
-                &**source_map_getter,
+                &source_mapper,
               );
               source_line_frame_index = Some(i);
               break;
@@ -416,15 +512,7 @@ impl JsError {
         }
       }
       {
-        let state_rc = JsRuntime::state_from(scope);
-        let (getter, cache) = {
-          let state = state_rc.borrow();
-          (
-            state.source_map_getter.clone(),
-            state.source_map_cache.clone(),
-          )
-        };
-        if let Some(source_map_getter) = getter {
+        if let Some((source_mapper, cache)) = SourceMapper::from_scope(scope) {
           let mut cache = cache.borrow_mut();
 
           for (i, frame) in frames.iter().enumerate() {
@@ -436,7 +524,7 @@ impl JsError {
                   file_name,
                   line_number,
                   &mut cache,
-                  &**source_map_getter,
+                  &source_mapper,
                 );
                 source_line_frame_index = Some(i);
                 break;
@@ -690,7 +778,8 @@ pub(crate) fn exception_to_err_result<T>(
     // Resume exception termination.
     scope.terminate_execution();
   }
-  scope.set_microtasks_policy(v8::MicrotasksPolicy::Auto);
+  let microtask_policy = state_rc.borrow().microtask_policy;
+  scope.set_microtasks_policy(microtask_policy.into());
 
   Err(js_error.into())
 }
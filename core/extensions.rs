@@ -169,6 +169,7 @@ macro_rules! ops {
 ///  * ops: a comma-separated list of [`OpDecl`]s to provide, eg: `ops = [ op_foo, op_bar ]`
 ///  * esm: a comma-separated list of ESM module filenames (see [`include_js_files`]), eg: `esm = [ dir "dir", "my_file.js" ]`
 ///  * esm_setup_script: see [`ExtensionBuilder::esm_setup_script`]
+///  * lazy_esm: like `esm`, but the modules are only evaluated when imported (see [`ExtensionBuilder::lazy_esm`]), eg: `lazy_esm = [ "optional.js" ]`
 ///  * js: a comma-separated list of JS filenames (see [`include_js_files`]), eg: `js = [ dir "dir", "my_file.js" ]`
 ///  * config: a structure-like definition for configuration parameters which will be required when initializing this extension, eg: `config = { my_param: Option<usize> }`
 ///  * middleware: an [`OpDecl`] middleware function with the signature `fn (OpDecl) -> OpDecl`
@@ -186,6 +187,7 @@ macro_rules! extension {
     $(, esm_entry_point = $esm_entry_point:literal )?
     $(, esm = [ $( dir $dir_esm:literal , )? $( $esm:literal ),* $(,)? ] )?
     $(, esm_setup_script = $esm_setup_script:expr )?
+    $(, lazy_esm = [ $( dir $dir_lazy_esm:literal , )? $( $lazy_esm:literal ),* $(,)? ] )?
     $(, js = [ $( dir $dir_js:literal , )? $( $js:literal ),* $(,)? ] )?
     $(, options = { $( $options_id:ident : $options_type:ty ),* $(,)? } )?
     $(, middleware = $middleware_fn:expr )?
@@ -223,6 +225,9 @@ macro_rules! extension {
         $(
           ext.esm_entry_point($esm_entry_point);
         )?
+        $( ext.lazy_esm(
+          $crate::include_js_files!( $name $( dir $dir_lazy_esm , )? $( $lazy_esm , )* )
+        ); )?
         $( ext.js(
           $crate::include_js_files!( $name $( dir $dir_js , )? $( $js , )* )
         ); )?
@@ -351,6 +356,7 @@ pub struct Extension {
   pub(crate) name: &'static str,
   js_files: Option<Vec<ExtensionFileSource>>,
   esm_files: Option<Vec<ExtensionFileSource>>,
+  lazy_esm_files: Option<Vec<ExtensionFileSource>>,
   esm_entry_point: Option<&'static str>,
   ops: Option<Vec<OpDecl>>,
   opstate_fn: Option<Box<OpStateFn>>,
@@ -414,6 +420,12 @@ impl Extension {
     self.esm_files.as_ref()
   }
 
+  /// Returns the ESM files that are only evaluated when imported, see
+  /// [`ExtensionBuilder::lazy_esm`].
+  pub fn get_lazy_esm_sources(&self) -> Option<&Vec<ExtensionFileSource>> {
+    self.lazy_esm_files.as_ref()
+  }
+
   pub fn get_esm_entry_point(&self) -> Option<&'static str> {
     self.esm_entry_point
   }
@@ -475,6 +487,7 @@ impl Extension {
 pub struct ExtensionBuilder {
   js: Vec<ExtensionFileSource>,
   esm: Vec<ExtensionFileSource>,
+  lazy_esm: Vec<ExtensionFileSource>,
   esm_entry_point: Option<&'static str>,
   ops: Vec<OpDecl>,
   state: Option<Box<OpStateFn>>,
@@ -496,6 +509,16 @@ impl ExtensionBuilder {
     self
   }
 
+  /// Add ESM files that are loaded into the module map like the ones passed
+  /// to [`ExtensionBuilder::esm`], but not evaluated until they are imported,
+  /// either by another module of the extension or by user code through their
+  /// `ext:` specifier. This keeps optional extension code from running at
+  /// startup or while creating a snapshot.
+  pub fn lazy_esm(&mut self, esm_files: Vec<ExtensionFileSource>) -> &mut Self {
+    self.lazy_esm.extend(esm_files);
+    self
+  }
+
   pub fn esm_entry_point(&mut self, entry_point: &'static str) -> &mut Self {
     self.esm_entry_point = Some(entry_point);
     self
@@ -534,11 +557,13 @@ impl ExtensionBuilder {
   pub fn take(self) -> Extension {
     let js_files = Some(self.js);
     let esm_files = Some(self.esm);
+    let lazy_esm_files = Some(self.lazy_esm);
     let ops = Some(self.ops);
     let deps = Some(self.deps);
     Extension {
       js_files,
       esm_files,
+      lazy_esm_files,
       esm_entry_point: self.esm_entry_point,
       ops,
       opstate_fn: self.state,
@@ -555,11 +580,13 @@ impl ExtensionBuilder {
   pub fn build(&mut self) -> Extension {
     let js_files = Some(std::mem::take(&mut self.js));
     let esm_files = Some(std::mem::take(&mut self.esm));
+    let lazy_esm_files = Some(std::mem::take(&mut self.lazy_esm));
     let ops = Some(std::mem::take(&mut self.ops));
     let deps = Some(std::mem::take(&mut self.deps));
     Extension {
       js_files,
       esm_files,
+      lazy_esm_files,
       esm_entry_point: self.esm_entry_point.take(),
       ops,
       opstate_fn: self.state.take(),
@@ -17,6 +17,7 @@ use crate::futures::stream::StreamExt;
 use crate::futures::task;
 use crate::futures::task::Context;
 use crate::futures::task::Poll;
+use crate::module_specifier::ModuleSpecifier;
 use crate::serde_json;
 use crate::serde_json::json;
 use crate::serde_json::Value;
@@ -420,12 +421,13 @@ impl JsRuntimeInspector {
 
   /// Create a channel that notifies the frontend when inspector is dropped.
   ///
-  /// NOTE: Only a single handler is currently available.
+  /// NOTE: Only a single handler is currently available. A new handler can
+  /// only be added once the receiver of the previous one was dropped.
   pub fn add_deregister_handler(&mut self) -> oneshot::Receiver<()> {
     let (tx, rx) = oneshot::channel::<()>();
     let prev = self.deregister_tx.replace(tx);
     assert!(
-      prev.is_none(),
+      prev.map_or(true, |prev| prev.is_canceled()),
       "Only a single deregister handler is allowed"
     );
     rx
@@ -458,6 +460,23 @@ impl JsRuntimeInspector {
 
     LocalInspectorSession::new(inbound_tx, outbound_rx)
   }
+
+  /// Create a [`DebuggerSession`] to drive the debugger from Rust.
+  ///
+  /// While execution is paused the isolate's thread is blocked, so unlike a
+  /// [`LocalInspectorSession`], the debugger session must be used from
+  /// another thread. The session is connected the next time the inspector
+  /// polls its sessions, e.g. while the event loop runs.
+  pub fn create_debugger_session(&self) -> DebuggerSession {
+    let (outbound_tx, outbound_rx) = mpsc::unbounded();
+    let (inbound_tx, inbound_rx) = mpsc::unbounded();
+    let proxy = InspectorSessionProxy {
+      tx: outbound_tx,
+      rx: inbound_rx,
+    };
+    self.new_session_tx.unbounded_send(proxy).unwrap();
+    DebuggerSession::new(LocalInspectorSession::new(inbound_tx, outbound_rx))
+  }
 }
 
 #[derive(Default)]
@@ -787,7 +806,10 @@ impl LocalInspectorSession {
     loop {
       let receive_fut = self.receive_from_v8_session().boxed_local();
       match select(receive_fut, &mut response_rx).await {
-        Either::Left(_) => continue,
+        Either::Left((true, _)) => continue,
+        Either::Left((false, _)) => {
+          return Err(generic_error("Inspector session closed"));
+        }
         Either::Right((result, _)) => {
           let response = result?;
           if let Some(error) = response.get("error") {
@@ -801,8 +823,12 @@ impl LocalInspectorSession {
     }
   }
 
-  async fn receive_from_v8_session(&mut self) {
-    let inspector_msg = self.v8_session_rx.next().await.unwrap();
+  /// Receives one message from the session, returning false once the
+  /// inspector was dropped.
+  async fn receive_from_v8_session(&mut self) -> bool {
+    let Some(inspector_msg) = self.v8_session_rx.next().await else {
+      return false;
+    };
     if let InspectorMsgKind::Message(msg_id) = inspector_msg.kind {
       let message: serde_json::Value =
         match serde_json::from_str(&inspector_msg.content) {
@@ -839,6 +865,283 @@ impl LocalInspectorSession {
       // Ignore if the receiver has been dropped.
       let _ = self.notification_tx.unbounded_send(message);
     }
+    true
+  }
+}
+
+/// A position in a script, with 1-based line and column numbers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebuggerLocation {
+  pub script_id: String,
+  pub line_number: u32,
+  pub column_number: u32,
+}
+
+/// A breakpoint set with [`DebuggerSession::set_breakpoint`].
+#[derive(Clone, Debug)]
+pub struct Breakpoint {
+  pub id: String,
+  /// Where the breakpoint resolved to in the scripts loaded so far. Scripts
+  /// loaded later get the breakpoint too.
+  pub locations: Vec<DebuggerLocation>,
+}
+
+/// A frame of the stack execution is paused in.
+#[derive(Clone, Debug)]
+pub struct DebuggerCallFrame {
+  /// Identifies the frame in [`DebuggerSession::evaluate_on_call_frame`].
+  pub call_frame_id: String,
+  pub function_name: String,
+  pub url: String,
+  pub location: DebuggerLocation,
+}
+
+#[derive(Clone, Debug)]
+pub enum DebuggerEvent {
+  /// Execution paused, e.g. at a breakpoint or a `debugger` statement. Call
+  /// frames are listed from the innermost one.
+  Paused {
+    reason: String,
+    hit_breakpoints: Vec<String>,
+    call_frames: Vec<DebuggerCallFrame>,
+  },
+  Resumed,
+}
+
+/// Drives the debugger of a [`JsRuntimeInspector`] from Rust, created with
+/// [`JsRuntimeInspector::create_debugger_session`].
+///
+/// This wraps the `Debugger` domain of the
+/// [Chrome DevTools Protocol](https://chromedevtools.github.io/devtools-protocol/v8/Debugger/);
+/// use [`DebuggerSession::post_message`] for anything it doesn't cover.
+pub struct DebuggerSession {
+  session: LocalInspectorSession,
+  notification_rx: UnboundedReceiver<Value>,
+}
+
+impl DebuggerSession {
+  fn new(mut session: LocalInspectorSession) -> Self {
+    let notification_rx = session.take_notification_rx();
+    Self {
+      session,
+      notification_rx,
+    }
+  }
+
+  /// Enable the debugger. Breakpoints are only hit once it is enabled.
+  pub async fn enable(&mut self) -> Result<(), Error> {
+    self.post_message::<()>("Debugger.enable", None).await?;
+    Ok(())
+  }
+
+  /// Set a breakpoint on a 1-based line of the script loaded from
+  /// `specifier`, at its first statement or at `column_number`.
+  pub async fn set_breakpoint(
+    &mut self,
+    specifier: &ModuleSpecifier,
+    line_number: u32,
+    column_number: Option<u32>,
+  ) -> Result<Breakpoint, Error> {
+    let result = self
+      .post_message(
+        "Debugger.setBreakpointByUrl",
+        Some(json!({
+          "url": specifier.as_str(),
+          "lineNumber": line_number.saturating_sub(1),
+          "columnNumber": column_number.map(|column| column.saturating_sub(1)),
+        })),
+      )
+      .await?;
+    let result: cdp::SetBreakpointByUrlResult = serde_json::from_value(result)?;
+    Ok(Breakpoint {
+      id: result.breakpoint_id,
+      locations: result.locations.into_iter().map(Into::into).collect(),
+    })
+  }
+
+  pub async fn remove_breakpoint(&mut self, id: &str) -> Result<(), Error> {
+    self
+      .post_message(
+        "Debugger.removeBreakpoint",
+        Some(json!({ "breakpointId": id })),
+      )
+      .await?;
+    Ok(())
+  }
+
+  /// Evaluate `expression` in the scope of a paused call frame. Returns the
+  /// result serialized as JSON, or an error with the description of the
+  /// exception it threw.
+  pub async fn evaluate_on_call_frame(
+    &mut self,
+    call_frame_id: &str,
+    expression: &str,
+  ) -> Result<Value, Error> {
+    let result = self
+      .post_message(
+        "Debugger.evaluateOnCallFrame",
+        Some(json!({
+          "callFrameId": call_frame_id,
+          "expression": expression,
+          "returnByValue": true,
+        })),
+      )
+      .await?;
+    let result: cdp::EvaluateResult = serde_json::from_value(result)?;
+    if let Some(exception_details) = result.exception_details {
+      let message = exception_details
+        .exception
+        .and_then(|exception| exception.description)
+        .unwrap_or(exception_details.text);
+      return Err(generic_error(message));
+    }
+    Ok(result.result.value.unwrap_or(Value::Null))
+  }
+
+  pub async fn pause(&mut self) -> Result<(), Error> {
+    self.post_message::<()>("Debugger.pause", None).await?;
+    Ok(())
+  }
+
+  pub async fn resume(&mut self) -> Result<(), Error> {
+    self.post_message::<()>("Debugger.resume", None).await?;
+    Ok(())
+  }
+
+  pub async fn step_over(&mut self) -> Result<(), Error> {
+    self.post_message::<()>("Debugger.stepOver", None).await?;
+    Ok(())
+  }
+
+  pub async fn step_into(&mut self) -> Result<(), Error> {
+    self.post_message::<()>("Debugger.stepInto", None).await?;
+    Ok(())
+  }
+
+  pub async fn step_out(&mut self) -> Result<(), Error> {
+    self.post_message::<()>("Debugger.stepOut", None).await?;
+    Ok(())
+  }
+
+  /// Wait for the debugger to pause or resume. Returns `None` once the
+  /// inspector was dropped.
+  pub async fn next_event(&mut self) -> Option<DebuggerEvent> {
+    loop {
+      while let Ok(Some(notification)) = self.notification_rx.try_next() {
+        if let Some(event) = cdp::debugger_event(notification) {
+          return Some(event);
+        }
+      }
+      if !self.session.receive_from_v8_session().await {
+        return None;
+      }
+    }
+  }
+
+  /// Send a raw protocol message, see [`LocalInspectorSession::post_message`].
+  pub async fn post_message<T: serde::Serialize>(
+    &mut self,
+    method: &str,
+    params: Option<T>,
+  ) -> Result<Value, Error> {
+    self.session.post_message(method, params).await
+  }
+}
+
+/// Deserialization of the protocol messages used by [`DebuggerSession`].
+mod cdp {
+  use super::DebuggerCallFrame;
+  use super::DebuggerEvent;
+  use super::DebuggerLocation;
+  use crate::serde_json::Value;
+  use serde::Deserialize;
+
+  #[derive(Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct Location {
+    script_id: String,
+    line_number: u32,
+    #[serde(default)]
+    column_number: u32,
+  }
+
+  impl From<Location> for DebuggerLocation {
+    fn from(location: Location) -> Self {
+      Self {
+        script_id: location.script_id,
+        line_number: location.line_number + 1,
+        column_number: location.column_number + 1,
+      }
+    }
+  }
+
+  #[derive(Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct SetBreakpointByUrlResult {
+    pub breakpoint_id: String,
+    pub locations: Vec<Location>,
+  }
+
+  #[derive(Deserialize)]
+  pub struct RemoteObject {
+    pub value: Option<Value>,
+    pub description: Option<String>,
+  }
+
+  #[derive(Deserialize)]
+  pub struct ExceptionDetails {
+    pub text: String,
+    pub exception: Option<RemoteObject>,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  pub struct EvaluateResult {
+    pub result: RemoteObject,
+    pub exception_details: Option<ExceptionDetails>,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  struct CallFrame {
+    call_frame_id: String,
+    function_name: String,
+    url: String,
+    location: Location,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(rename_all = "camelCase")]
+  struct Paused {
+    reason: String,
+    #[serde(default)]
+    hit_breakpoints: Vec<String>,
+    call_frames: Vec<CallFrame>,
+  }
+
+  pub fn debugger_event(notification: Value) -> Option<DebuggerEvent> {
+    match notification.get("method")?.as_str()? {
+      "Debugger.paused" => {
+        let params = notification.get("params")?.clone();
+        let paused: Paused = serde_json::from_value(params).ok()?;
+        Some(DebuggerEvent::Paused {
+          reason: paused.reason,
+          hit_breakpoints: paused.hit_breakpoints,
+          call_frames: paused
+            .call_frames
+            .into_iter()
+            .map(|frame| DebuggerCallFrame {
+              call_frame_id: frame.call_frame_id,
+              function_name: frame.function_name,
+              url: frame.url,
+              location: frame.location.into(),
+            })
+            .collect(),
+        })
+      }
+      "Debugger.resumed" => Some(DebuggerEvent::Resumed),
+      _ => None,
+    }
   }
 }
 
@@ -39,6 +39,22 @@ declare namespace Deno {
      */
     function resources(): Record<string, string>;
 
+    /**
+     * Describe the work that keeps the event loop alive: pending async ops by
+     * op name, open resources, and pending dynamic imports and module
+     * evaluations.
+     */
+    function eventLoopStats(): {
+      pendingOps: Record<string, number>;
+      unrefedOps: number;
+      resources: Record<string, string>;
+      pendingDynamicImports: number;
+      pendingDynamicModuleEvaluations: number;
+      hasPendingModuleEvaluation: boolean;
+      hasPendingBackgroundTasks: boolean;
+      hasTickScheduled: boolean;
+    };
+
     /**
      * Close the resource with the specified op id. Throws `BadResource` error
      * if resource doesn't exist in resource table.
@@ -66,6 +82,24 @@ declare namespace Deno {
      */
     function writeAll(rid: number, buf: Uint8Array): Promise<void>;
 
+    /**
+     * Read from a (stream) resource into several buffers, in order, with a
+     * single read.
+     */
+    function readv(rid: number, bufs: Uint8Array[]): Promise<number>;
+
+    /**
+     * Write several buffers, in order, to a (stream) resource with a single
+     * write.
+     */
+    function writev(rid: number, bufs: Uint8Array[]): Promise<number>;
+
+    /**
+     * Write several buffers, in order, to a (stream) resource until all of
+     * them are written.
+     */
+    function writevAll(rid: number, bufs: Uint8Array[]): Promise<void>;
+
     /**
      * Synchronously read from a (stream) resource that implements readSync().
      */
@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 mod async_cancel;
 mod async_cell;
+mod clock;
 pub mod error;
 mod error_codes;
 mod extensions;
@@ -20,6 +21,7 @@ mod path;
 mod resources;
 mod runtime;
 mod source_map;
+mod structured_clone;
 pub mod task;
 mod task_queue;
 
@@ -33,6 +35,7 @@ pub use serde_v8;
 pub use serde_v8::ByteString;
 pub use serde_v8::DetachedBuffer;
 pub use serde_v8::StringOrBuffer;
+pub use serde_v8::ToJsBuffer;
 pub use serde_v8::U16String;
 pub use serde_v8::ZeroCopyBuf;
 pub use sourcemap;
@@ -54,6 +57,11 @@ pub use crate::async_cell::AsyncRefCell;
 pub use crate::async_cell::AsyncRefFuture;
 pub use crate::async_cell::RcLike;
 pub use crate::async_cell::RcRef;
+pub use crate::clock::Clock;
+pub use crate::clock::SystemClock;
+pub use crate::clock::VirtualClock;
+pub use crate::error::ErrorClassRegistry;
+pub use crate::error::ErrorProperties;
 pub use crate::error::GetErrorClassFn;
 pub use crate::error::JsErrorCreateFn;
 pub use crate::extensions::Extension;
@@ -64,6 +72,11 @@ pub use crate::extensions::OpDecl;
 pub use crate::extensions::OpMiddlewareFn;
 pub use crate::fast_string::FastString;
 pub use crate::flags::v8_set_flags;
+pub use crate::inspector::Breakpoint;
+pub use crate::inspector::DebuggerCallFrame;
+pub use crate::inspector::DebuggerEvent;
+pub use crate::inspector::DebuggerLocation;
+pub use crate::inspector::DebuggerSession;
 pub use crate::inspector::InspectorMsg;
 pub use crate::inspector::InspectorMsgKind;
 pub use crate::inspector::InspectorSessionProxy;
@@ -78,11 +91,16 @@ pub use crate::module_specifier::resolve_url;
 pub use crate::module_specifier::resolve_url_or_path;
 pub use crate::module_specifier::ModuleResolutionError;
 pub use crate::module_specifier::ModuleSpecifier;
+pub use crate::modules::ArchiveModuleLoader;
 pub use crate::modules::ExtModuleLoaderCb;
+pub use crate::modules::FinalSpecifier;
 pub use crate::modules::FsModuleLoader;
+pub use crate::modules::ImportMetaResolution;
+pub use crate::modules::ModuleArchiveWriter;
 pub use crate::modules::ModuleCode;
 pub use crate::modules::ModuleId;
 pub use crate::modules::ModuleLoader;
+pub use crate::modules::ModuleResolutionFuture;
 pub use crate::modules::ModuleSource;
 pub use crate::modules::ModuleSourceFuture;
 pub use crate::modules::ModuleType;
@@ -100,23 +118,56 @@ pub use crate::ops_builtin::op_print;
 pub use crate::ops_builtin::op_resources;
 pub use crate::ops_builtin::op_void_async;
 pub use crate::ops_builtin::op_void_sync;
+pub use crate::ops_metrics::OpDetailedMetrics;
+pub use crate::ops_metrics::OpLatencyHistogram;
+pub use crate::ops_metrics::OpMetricsEvent;
+pub use crate::ops_metrics::OpMetricsHook;
 pub use crate::ops_metrics::OpsTracker;
+pub use crate::ops_metrics::OP_LATENCY_BUCKETS_US;
 pub use crate::path::strip_unc_prefix;
 pub use crate::resources::AsyncResult;
+pub use crate::resources::LeakedResource;
 pub use crate::resources::Resource;
 pub use crate::resources::ResourceId;
 pub use crate::resources::ResourceTable;
+pub use crate::resources::TypedResourceId;
+pub use crate::runtime::current_stack_trace;
 pub use crate::runtime::CompiledWasmModuleStore;
+pub use crate::runtime::CoverageRange;
+pub use crate::runtime::CpuProfile;
 pub use crate::runtime::CrossIsolateStore;
+pub use crate::runtime::DeterministicOptions;
+pub use crate::runtime::EventLoopStats;
+pub use crate::runtime::FunctionCoverage;
+pub use crate::runtime::IsolateConfig;
 pub use crate::runtime::JsRealm;
 pub use crate::runtime::JsRuntime;
 pub use crate::runtime::JsRuntimeForSnapshot;
+pub use crate::runtime::JsRuntimePool;
+pub use crate::runtime::JsRuntimePoolOptions;
+pub use crate::runtime::MemoryPressureLevel;
+pub use crate::runtime::MicrotaskPolicy;
+pub use crate::runtime::ModuleCodeCache;
+pub use crate::runtime::NearHeapLimitAction;
+pub use crate::runtime::NearHeapLimitCallback;
+pub use crate::runtime::NearHeapLimitInfo;
+pub use crate::runtime::PooledJsRuntime;
+pub use crate::runtime::PromiseHook;
 pub use crate::runtime::RuntimeOptions;
 pub use crate::runtime::SharedArrayBufferStore;
 pub use crate::runtime::Snapshot;
+pub use crate::runtime::UnhandledRejectionAction;
+pub use crate::runtime::UnhandledRejectionCallback;
+pub use crate::runtime::WasmModuleCache;
 pub use crate::runtime::V8_WRAPPER_OBJECT_INDEX;
 pub use crate::runtime::V8_WRAPPER_TYPE_INDEX;
 pub use crate::source_map::SourceMapGetter;
+pub use crate::structured_clone::structured_deserialize;
+pub use crate::structured_clone::structured_serialize;
+pub use crate::structured_clone::SharedArrayBufferHandle;
+pub use crate::structured_clone::StructuredCloneData;
+pub use crate::structured_clone::StructuredCloneOptions;
+pub use crate::structured_clone::TransferredArrayBuffer;
 pub use crate::task_queue::TaskQueue;
 pub use crate::task_queue::TaskQueuePermit;
 
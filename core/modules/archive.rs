@@ -0,0 +1,315 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::error::generic_error;
+use crate::module_specifier::ModuleSpecifier;
+use crate::modules::ModuleCode;
+use crate::modules::ModuleLoader;
+use crate::modules::ModuleSource;
+use crate::modules::ModuleSourceFuture;
+use crate::modules::ModuleType;
+use crate::modules::ResolutionKind;
+use crate::resolve_import;
+use crate::ModuleCodeCache;
+use anyhow::Error;
+use futures::future::FutureExt;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Magic bytes at the start of every module archive.
+const MODULE_ARCHIVE_MAGIC: &[u8; 4] = b"DMAR";
+
+/// Version of the module archive encoding. Bump this whenever the layout
+/// written by [`ModuleArchiveWriter::finish`] changes.
+const MODULE_ARCHIVE_VERSION: u32 = 1;
+
+/// Upper bound on the number of redirects followed when loading a module, to
+/// guard against cycles in a malformed archive.
+const MAX_REDIRECTS: usize = 16;
+
+const ENTRY_JAVASCRIPT: u8 = 0;
+const ENTRY_JSON: u8 = 1;
+const ENTRY_REDIRECT: u8 = 2;
+
+struct ArchiveModule {
+  module_type: ModuleType,
+  code: Arc<str>,
+  code_cache: Option<Vec<u8>>,
+}
+
+enum ArchiveEntry {
+  Module(ArchiveModule),
+  Redirect(String),
+}
+
+/// Builds a module archive: a single buffer containing many pre-resolved
+/// modules that can be served by [`ArchiveModuleLoader`].
+///
+/// The encoding is little-endian and length-prefixed:
+///
+/// ```text
+/// header:   magic [u8; 4] | version u32 | entry count u32
+/// entry:    specifier str | kind u8 (0 = JavaScript, 1 = JSON, 2 = redirect)
+/// module:   code bytes | has code cache u8 | code cache bytes (if present)
+/// redirect: target str
+/// bytes:    byte length u32 | bytes
+/// ```
+///
+/// Entries are sorted by specifier so the same set of modules always produces
+/// the same bytes.
+#[derive(Default)]
+pub struct ModuleArchiveWriter {
+  entries: BTreeMap<String, ArchiveEntry>,
+}
+
+impl ModuleArchiveWriter {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Add a module to the archive, replacing any previous entry for
+  /// `specifier`.
+  pub fn add_module(
+    &mut self,
+    specifier: &ModuleSpecifier,
+    module_type: ModuleType,
+    code: &str,
+    code_cache: Option<Vec<u8>>,
+  ) {
+    self.entries.insert(
+      specifier.to_string(),
+      ArchiveEntry::Module(ArchiveModule {
+        module_type,
+        code: code.into(),
+        code_cache,
+      }),
+    );
+  }
+
+  /// Record that loading `from` should serve the module stored at `to`.
+  pub fn add_redirect(&mut self, from: &ModuleSpecifier, to: &ModuleSpecifier) {
+    self
+      .entries
+      .insert(from.to_string(), ArchiveEntry::Redirect(to.to_string()));
+  }
+
+  pub fn finish(self) -> Vec<u8> {
+    let mut out = vec![];
+    out.extend_from_slice(MODULE_ARCHIVE_MAGIC);
+    out.extend_from_slice(&MODULE_ARCHIVE_VERSION.to_le_bytes());
+    write_len(&mut out, self.entries.len());
+    for (specifier, entry) in self.entries {
+      write_bytes(&mut out, specifier.as_bytes());
+      match entry {
+        ArchiveEntry::Module(ArchiveModule {
+          module_type,
+          code,
+          code_cache,
+        }) => {
+          out.push(match module_type {
+            ModuleType::JavaScript => ENTRY_JAVASCRIPT,
+            ModuleType::Json => ENTRY_JSON,
+          });
+          write_bytes(&mut out, code.as_bytes());
+          match code_cache {
+            Some(code_cache) => {
+              out.push(1);
+              write_bytes(&mut out, &code_cache);
+            }
+            None => out.push(0),
+          }
+        }
+        ArchiveEntry::Redirect(target) => {
+          out.push(ENTRY_REDIRECT);
+          write_bytes(&mut out, target.as_bytes());
+        }
+      }
+    }
+    out
+  }
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize) {
+  let len = u32::try_from(len).expect("module archive entry too large");
+  out.extend_from_slice(&len.to_le_bytes());
+}
+
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+  write_len(out, bytes.len());
+  out.extend_from_slice(bytes);
+}
+
+/// A [`ModuleLoader`] that serves modules out of an archive produced by
+/// [`ModuleArchiveWriter`] (or [`crate::JsRuntime::write_module_archive`]).
+///
+/// Specifiers are resolved relative to the referrer with no import map, and
+/// loading never touches the file system or the network. Loading a specifier
+/// that isn't in the archive is an error.
+pub struct ArchiveModuleLoader {
+  entries: HashMap<String, ArchiveEntry>,
+}
+
+impl ArchiveModuleLoader {
+  pub fn new(data: &[u8]) -> Result<Self, Error> {
+    let mut reader = Reader { data };
+    if reader.data.len() < 12 || &reader.data[..4] != MODULE_ARCHIVE_MAGIC {
+      return Err(generic_error("Not a module archive"));
+    }
+    reader.data = &reader.data[4..];
+    let version = reader.u32()?;
+    if version != MODULE_ARCHIVE_VERSION {
+      return Err(generic_error(format!(
+        "Module archive version mismatch: expected {MODULE_ARCHIVE_VERSION}, found {version}"
+      )));
+    }
+
+    let count = reader.u32()? as usize;
+    let mut entries = HashMap::with_capacity(count);
+    for _ in 0..count {
+      let specifier = reader.str()?.to_string();
+      let entry = match reader.u8()? {
+        kind @ (ENTRY_JAVASCRIPT | ENTRY_JSON) => {
+          let module_type = if kind == ENTRY_JSON {
+            ModuleType::Json
+          } else {
+            ModuleType::JavaScript
+          };
+          let code = reader.str()?.into();
+          let code_cache = match reader.u8()? {
+            0 => None,
+            1 => Some(reader.bytes()?.to_vec()),
+            n => return Err(corrupted(format!("invalid code cache flag {n}"))),
+          };
+          ArchiveEntry::Module(ArchiveModule {
+            module_type,
+            code,
+            code_cache,
+          })
+        }
+        ENTRY_REDIRECT => ArchiveEntry::Redirect(reader.str()?.to_string()),
+        n => return Err(corrupted(format!("unknown entry kind {n}"))),
+      };
+      entries.insert(specifier, entry);
+    }
+
+    if !reader.data.is_empty() {
+      return Err(corrupted("trailing bytes".to_string()));
+    }
+    Ok(Self { entries })
+  }
+
+  /// Returns the V8 code cache stored for `specifier`, if any.
+  pub fn code_cache(&self, specifier: &ModuleSpecifier) -> Option<&[u8]> {
+    let (_, module) = self.find(specifier).ok()?;
+    module.code_cache.as_deref()
+  }
+
+  /// Add the V8 code cache stored in the archive to `code_cache`. Pass the
+  /// same cache as [`crate::RuntimeOptions::module_code_cache`] so the
+  /// modules are compiled from it instead of being parsed again. Code cache
+  /// that the running V8 rejects is replaced the first time it's used.
+  pub fn populate_code_cache(&self, code_cache: &ModuleCodeCache) {
+    for (specifier, entry) in &self.entries {
+      if let ArchiveEntry::Module(ArchiveModule {
+        code,
+        code_cache: Some(data),
+        ..
+      }) = entry
+      {
+        let source_hash = ModuleCodeCache::hash_source(code.as_bytes());
+        code_cache.insert(specifier, source_hash, data, false);
+      }
+    }
+  }
+
+  /// Look up `specifier`, following redirects. Returns the specifier the
+  /// module was found at along with the module.
+  fn find(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<(ModuleSpecifier, &ArchiveModule), Error> {
+    let mut found = specifier.clone();
+    for _ in 0..MAX_REDIRECTS {
+      match self.entries.get(found.as_str()) {
+        Some(ArchiveEntry::Redirect(target)) => {
+          found = ModuleSpecifier::parse(target)?;
+        }
+        Some(ArchiveEntry::Module(module)) => return Ok((found, module)),
+        None => {
+          return Err(generic_error(format!(
+            "Module not found in archive: \"{specifier}\""
+          )))
+        }
+      }
+    }
+    Err(generic_error(format!(
+      "Too many redirects in archive while loading \"{specifier}\""
+    )))
+  }
+}
+
+impl ModuleLoader for ArchiveModuleLoader {
+  fn resolve(
+    &self,
+    specifier: &str,
+    referrer: &str,
+    _kind: ResolutionKind,
+  ) -> Result<ModuleSpecifier, Error> {
+    Ok(resolve_import(specifier, referrer)?)
+  }
+
+  fn load(
+    &self,
+    module_specifier: &ModuleSpecifier,
+    _maybe_referrer: Option<&ModuleSpecifier>,
+    _is_dyn_import: bool,
+    _import_assertions: &HashMap<String, String>,
+  ) -> Pin<Box<ModuleSourceFuture>> {
+    let result = self.find(module_specifier).map(|(found, module)| {
+      ModuleSource::new_with_redirect(
+        module.module_type,
+        ModuleCode::from(module.code.clone()),
+        module_specifier,
+        &found,
+      )
+    });
+    async move { result }.boxed_local()
+  }
+}
+
+fn corrupted(reason: String) -> Error {
+  generic_error(format!("Corrupted module archive: {reason}"))
+}
+
+struct Reader<'a> {
+  data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+  fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+    if self.data.len() < len {
+      return Err(corrupted("unexpected end of data".to_string()));
+    }
+    let (head, tail) = self.data.split_at(len);
+    self.data = tail;
+    Ok(head)
+  }
+
+  fn u8(&mut self) -> Result<u8, Error> {
+    Ok(self.take(1)?[0])
+  }
+
+  fn u32(&mut self) -> Result<u32, Error> {
+    Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+  }
+
+  fn bytes(&mut self) -> Result<&'a [u8], Error> {
+    let len = self.u32()? as usize;
+    self.take(len)
+  }
+
+  fn str(&mut self) -> Result<&'a str, Error> {
+    std::str::from_utf8(self.bytes()?)
+      .map_err(|_| corrupted("invalid utf-8 in string".to_string()))
+  }
+}
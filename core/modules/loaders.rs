@@ -3,6 +3,7 @@ use crate::error::generic_error;
 use crate::error::AnyError;
 use crate::extensions::ExtensionFileSource;
 use crate::module_specifier::ModuleSpecifier;
+use crate::modules::ImportMetaResolution;
 use crate::modules::ModuleCode;
 use crate::modules::ModuleSource;
 use crate::modules::ModuleSourceFuture;
@@ -42,11 +43,18 @@ pub trait ModuleLoader {
   ///
   /// `is_dyn_import` can be used to check permissions or deny
   /// dynamic imports altogether.
+  ///
+  /// `import_assertions` are the import assertions of the static import or
+  /// `import()` call that requested the module (eg. `{ type: "json" }`). They
+  /// are empty for main and side modules. An `integrity` assertion is
+  /// verified against the returned code after loading, so loaders don't need
+  /// to check it themselves.
   fn load(
     &self,
     module_specifier: &ModuleSpecifier,
     maybe_referrer: Option<&ModuleSpecifier>,
     is_dyn_import: bool,
+    import_assertions: &HashMap<String, String>,
   ) -> Pin<Box<ModuleSourceFuture>>;
 
   /// This hook can be used by implementors to do some preparation
@@ -65,6 +73,31 @@ pub trait ModuleLoader {
   ) -> Pin<Box<dyn Future<Output = Result<(), Error>>>> {
     async { Ok(()) }.boxed_local()
   }
+
+  /// Resolves a specifier passed to `import.meta.resolve()` from the module
+  /// named `referrer`.
+  ///
+  /// The default implementation calls [`ModuleLoader::resolve`] with
+  /// [`ResolutionKind::Import`]. Implementors that need to do
+  /// asynchronous work to resolve some specifiers (eg. npm package resolution)
+  /// can return [`ImportMetaResolution::Async`], in which case
+  /// `import.meta.resolve()` returns a promise of the resolved URL instead.
+  fn resolve_import_meta(
+    &self,
+    specifier: &str,
+    referrer: &str,
+  ) -> ImportMetaResolution {
+    if specifier.starts_with("npm:") {
+      return ImportMetaResolution::Sync(Err(generic_error(
+        "\"npm:\" specifiers are currently not supported in import.meta.resolve()",
+      )));
+    }
+    ImportMetaResolution::Sync(self.resolve(
+      specifier,
+      referrer,
+      ResolutionKind::Import,
+    ))
+  }
 }
 
 /// Placeholder structure used when creating
@@ -88,6 +121,7 @@ impl ModuleLoader for NoopModuleLoader {
     module_specifier: &ModuleSpecifier,
     maybe_referrer: Option<&ModuleSpecifier>,
     _is_dyn_import: bool,
+    _import_assertions: &HashMap<String, String>,
   ) -> Pin<Box<ModuleSourceFuture>> {
     let err = generic_error(
       format!(
@@ -118,7 +152,11 @@ impl ExtModuleLoader {
     sources.extend(
       extensions
         .iter()
-        .flat_map(|e| e.get_esm_sources())
+        .flat_map(|e| {
+          e.get_esm_sources()
+            .into_iter()
+            .chain(e.get_lazy_esm_sources())
+        })
         .flatten()
         .map(|s| (s.specifier.to_string(), s.clone())),
     );
@@ -145,6 +183,7 @@ impl ModuleLoader for ExtModuleLoader {
     specifier: &ModuleSpecifier,
     _maybe_referrer: Option<&ModuleSpecifier>,
     _is_dyn_import: bool,
+    _import_assertions: &HashMap<String, String>,
   ) -> Pin<Box<ModuleSourceFuture>> {
     let sources = self.sources.borrow();
     let source = match sources.get(specifier.as_str()) {
@@ -224,6 +263,7 @@ impl ModuleLoader for FsModuleLoader {
     module_specifier: &ModuleSpecifier,
     _maybe_referrer: Option<&ModuleSpecifier>,
     _is_dynamic: bool,
+    _import_assertions: &HashMap<String, String>,
   ) -> Pin<Box<ModuleSourceFuture>> {
     fn load(
       module_specifier: &ModuleSpecifier,
@@ -5,12 +5,14 @@ use crate::modules::get_asserted_module_type_from_assertions;
 use crate::modules::parse_import_assertions;
 use crate::modules::validate_import_assertions;
 use crate::modules::ImportAssertionsKind;
+use crate::modules::ImportMetaResolveFuture;
 use crate::modules::ModuleCode;
 use crate::modules::ModuleError;
 use crate::modules::ModuleId;
 use crate::modules::ModuleInfo;
 use crate::modules::ModuleLoadId;
 use crate::modules::ModuleLoader;
+use crate::modules::ModuleMapSnapshotData;
 use crate::modules::ModuleName;
 use crate::modules::ModuleRequest;
 use crate::modules::ModuleType;
@@ -19,6 +21,7 @@ use crate::modules::PrepareLoadFuture;
 use crate::modules::RecursiveModuleLoad;
 use crate::modules::ResolutionKind;
 use crate::runtime::JsRuntime;
+use crate::runtime::ModuleCodeCache;
 use crate::runtime::SnapshottedData;
 use anyhow::Error;
 use futures::future::FutureExt;
@@ -28,6 +31,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use super::AssertedModuleType;
 
@@ -42,6 +46,37 @@ fn strip_bom(source_code: &[u8]) -> &[u8] {
   }
 }
 
+/// Whether V8 rejected the `consumed` code cache data, given the `produced`
+/// code cache of the module compiled with it.
+///
+/// The v8 crate doesn't expose the `rejected` flag V8 sets on consumed cached
+/// data, so this repeats V8's sanity check of the data instead: it's rejected
+/// unless the magic number, version hash, source hash and flag hash in the
+/// header (the first 16 bytes) match the ones V8 produces in this isolate, and
+/// the payload length fits the data.
+fn code_cache_rejected(consumed: &[u8], produced: &[u8]) -> bool {
+  const HEADER_SIZE: usize = 24;
+  const PAYLOAD_LENGTH_OFFSET: usize = 16;
+  if consumed.len() < HEADER_SIZE || produced.len() < HEADER_SIZE {
+    return true;
+  }
+  let payload_length = u32::from_ne_bytes(
+    consumed[PAYLOAD_LENGTH_OFFSET..PAYLOAD_LENGTH_OFFSET + 4]
+      .try_into()
+      .unwrap(),
+  ) as usize;
+  consumed[..PAYLOAD_LENGTH_OFFSET] != produced[..PAYLOAD_LENGTH_OFFSET]
+    || payload_length > consumed.len() - HEADER_SIZE
+}
+
+/// The source of a module along with the code cache of the compiled module,
+/// kept if the runtime was created with
+/// [`crate::RuntimeOptions::retain_module_sources`].
+pub(crate) struct RetainedSource {
+  pub code: ModuleCode,
+  pub code_cache: Option<Arc<[u8]>>,
+}
+
 /// A symbolic module entity.
 #[derive(Debug, PartialEq)]
 pub(crate) enum SymbolicModule {
@@ -61,6 +96,20 @@ pub(crate) struct ModuleMap {
   pub(crate) by_name_js: HashMap<ModuleName, SymbolicModule>,
   pub(crate) by_name_json: HashMap<ModuleName, SymbolicModule>,
   pub(crate) next_load_id: ModuleLoadId,
+  // Source maps returned by the loader alongside module code, used to remap
+  // locations in stack traces.
+  source_maps: HashMap<ModuleName, Vec<u8>>,
+  // Sources of the modules, see `RuntimeOptions::retain_module_sources`.
+  retained_sources: HashMap<ModuleId, RetainedSource>,
+  // Reverse import edges: the modules that import a given (resolved)
+  // specifier, maintained as modules are registered.
+  dependents: HashMap<(AssertedModuleType, String), Vec<ModuleId>>,
+  // Reverse redirect edges: the aliases that redirect directly to a given
+  // specifier, maintained as aliases are registered.
+  aliases_to: HashMap<(AssertedModuleType, String), Vec<String>>,
+  // Import assertions of each module's requests, in the same order as
+  // `ModuleInfo::requests`. Only modules with non-empty assertions are stored.
+  import_assertions: HashMap<ModuleId, Vec<HashMap<String, String>>>,
 
   // Handling of futures for loading module sources
   pub loader: Rc<dyn ModuleLoader>,
@@ -70,6 +119,10 @@ pub(crate) struct ModuleMap {
     FuturesUnordered<Pin<Box<PrepareLoadFuture>>>,
   pub(crate) pending_dynamic_imports:
     FuturesUnordered<StreamFuture<RecursiveModuleLoad>>,
+  // Asynchronous `import.meta.resolve()` calls, see
+  // `ModuleLoader::resolve_import_meta`.
+  pub(crate) pending_import_meta_resolves:
+    FuturesUnordered<Pin<Box<ImportMetaResolveFuture>>>,
 
   // This store is used temporarly, to forward parsed JSON
   // value from `new_json_module` to `json_module_evaluation_steps`
@@ -99,12 +152,15 @@ impl ModuleMap {
   pub(crate) fn assert_all_modules_evaluated(
     &self,
     scope: &mut v8::HandleScope,
+    lazy_specifiers: &[&str],
   ) {
     let mut not_evaluated = vec![];
 
     for (i, handle) in self.handles.iter().enumerate() {
       let module = v8::Local::new(scope, handle);
-      if !matches!(module.get_status(), v8::ModuleStatus::Evaluated) {
+      if !matches!(module.get_status(), v8::ModuleStatus::Evaluated)
+        && !lazy_specifiers.contains(&self.info[i].name.as_str())
+      {
         not_evaluated.push(self.info[i].name.as_str().to_string());
       }
     }
@@ -118,253 +174,75 @@ impl ModuleMap {
     }
   }
 
-  pub fn serialize_for_snapshotting(
-    &self,
-    scope: &mut v8::HandleScope,
-  ) -> SnapshottedData {
-    let array = v8::Array::new(scope, 3);
-
-    let next_load_id = v8::Integer::new(scope, self.next_load_id);
-    array.set_index(scope, 0, next_load_id.into());
-
-    let info_arr = v8::Array::new(scope, self.info.len() as i32);
-    for (i, info) in self.info.iter().enumerate() {
-      let module_info_arr = v8::Array::new(scope, 5);
-
-      let id = v8::Integer::new(scope, info.id as i32);
-      module_info_arr.set_index(scope, 0, id.into());
-
-      let main = v8::Boolean::new(scope, info.main);
-      module_info_arr.set_index(scope, 1, main.into());
-
-      let name = info.name.v8(scope);
-      module_info_arr.set_index(scope, 2, name.into());
-
-      let array_len = 2 * info.requests.len() as i32;
-      let requests_arr = v8::Array::new(scope, array_len);
-      for (i, request) in info.requests.iter().enumerate() {
-        let specifier = v8::String::new_from_one_byte(
-          scope,
-          request.specifier.as_bytes(),
-          v8::NewStringType::Normal,
-        )
-        .unwrap();
-        requests_arr.set_index(scope, 2 * i as u32, specifier.into());
-
-        let asserted_module_type =
-          v8::Integer::new(scope, request.asserted_module_type as i32);
-        requests_arr.set_index(
-          scope,
-          (2 * i) as u32 + 1,
-          asserted_module_type.into(),
-        );
-      }
-      module_info_arr.set_index(scope, 3, requests_arr.into());
-
-      let module_type = v8::Integer::new(scope, info.module_type as i32);
-      module_info_arr.set_index(scope, 4, module_type.into());
-
-      info_arr.set_index(scope, i as u32, module_info_arr.into());
-    }
-    array.set_index(scope, 1, info_arr.into());
-
-    let by_name = self.collect_modules();
-    let by_name_array = v8::Array::new(scope, by_name.len() as i32);
-    {
-      for (i, (module_type, name, module)) in by_name.into_iter().enumerate() {
-        let arr = v8::Array::new(scope, 3);
-
-        let specifier = name.v8(scope);
-        arr.set_index(scope, 0, specifier.into());
-
-        let asserted_module_type = v8::Integer::new(scope, module_type as i32);
-        arr.set_index(scope, 1, asserted_module_type.into());
-
-        let symbolic_module: v8::Local<v8::Value> = match module {
+  pub fn serialize_for_snapshotting(&self) -> SnapshottedData {
+    let info = self
+      .info
+      .iter()
+      .map(|info| ModuleInfo {
+        id: info.id,
+        main: info.main,
+        name: info.name.as_str().to_owned().into(),
+        requests: info.requests.clone(),
+        module_type: info.module_type,
+      })
+      .collect();
+
+    let by_name = self
+      .collect_modules()
+      .into_iter()
+      .map(|(module_type, name, module)| {
+        let module = match module {
           SymbolicModule::Alias(alias) => {
-            let alias = v8::String::new_from_one_byte(
-              scope,
-              alias.as_bytes(),
-              v8::NewStringType::Normal,
-            )
-            .unwrap();
-            alias.into()
-          }
-          SymbolicModule::Mod(id) => {
-            let id = v8::Integer::new(scope, *id as i32);
-            id.into()
+            SymbolicModule::Alias(alias.as_str().to_owned().into())
           }
+          SymbolicModule::Mod(id) => SymbolicModule::Mod(*id),
         };
-        arr.set_index(scope, 2, symbolic_module);
+        (module_type, name.as_str().to_owned().into(), module)
+      })
+      .collect();
 
-        by_name_array.set_index(scope, i as u32, arr.into());
-      }
-    }
-    array.set_index(scope, 2, by_name_array.into());
-
-    let array_global = v8::Global::new(scope, array);
-
-    let handles = self.handles.clone();
     SnapshottedData {
-      module_map_data: array_global,
-      module_handles: handles,
+      module_map_data: ModuleMapSnapshotData {
+        next_load_id: self.next_load_id,
+        info,
+        by_name,
+      },
+      module_handles: self.handles.clone(),
     }
   }
 
   pub fn update_with_snapshotted_data(
     &mut self,
-    scope: &mut v8::HandleScope,
     snapshotted_data: SnapshottedData,
   ) {
-    let local_data: v8::Local<v8::Array> =
-      v8::Local::new(scope, snapshotted_data.module_map_data);
-
-    {
-      let next_load_id = local_data.get_index(scope, 0).unwrap();
-      assert!(next_load_id.is_int32());
-      let integer = next_load_id.to_integer(scope).unwrap();
-      let val = integer.int32_value(scope).unwrap();
-      self.next_load_id = val;
-    }
-
-    {
-      let info_val = local_data.get_index(scope, 1).unwrap();
-
-      let info_arr: v8::Local<v8::Array> = info_val.try_into().unwrap();
-      let len = info_arr.length() as usize;
-      // Over allocate so executing a few scripts doesn't have to resize this vec.
-      let mut info = Vec::with_capacity(len + 16);
-
-      for i in 0..len {
-        let module_info_arr: v8::Local<v8::Array> = info_arr
-          .get_index(scope, i as u32)
-          .unwrap()
-          .try_into()
-          .unwrap();
-        let id = module_info_arr
-          .get_index(scope, 0)
-          .unwrap()
-          .to_integer(scope)
-          .unwrap()
-          .value() as ModuleId;
-
-        let main = module_info_arr
-          .get_index(scope, 1)
-          .unwrap()
-          .to_boolean(scope)
-          .is_true();
-
-        let name = module_info_arr
-          .get_index(scope, 2)
-          .unwrap()
-          .to_rust_string_lossy(scope)
-          .into();
-
-        let requests_arr: v8::Local<v8::Array> = module_info_arr
-          .get_index(scope, 3)
-          .unwrap()
-          .try_into()
-          .unwrap();
-        let len = (requests_arr.length() as usize) / 2;
-        let mut requests = Vec::with_capacity(len);
-        for i in 0..len {
-          let specifier = requests_arr
-            .get_index(scope, (2 * i) as u32)
-            .unwrap()
-            .to_rust_string_lossy(scope);
-          let asserted_module_type_no = requests_arr
-            .get_index(scope, (2 * i + 1) as u32)
-            .unwrap()
-            .to_integer(scope)
-            .unwrap()
-            .value();
-          let asserted_module_type = match asserted_module_type_no {
-            0 => AssertedModuleType::JavaScriptOrWasm,
-            1 => AssertedModuleType::Json,
-            _ => unreachable!(),
-          };
-          requests.push(ModuleRequest {
-            specifier,
-            asserted_module_type,
-          });
-        }
-
-        let module_type_no = module_info_arr
-          .get_index(scope, 4)
-          .unwrap()
-          .to_integer(scope)
-          .unwrap()
-          .value();
-        let module_type = match module_type_no {
-          0 => ModuleType::JavaScript,
-          1 => ModuleType::Json,
-          _ => unreachable!(),
-        };
-
-        let module_info = ModuleInfo {
-          id,
-          main,
-          name,
-          requests,
-          module_type,
-        };
-        info.push(module_info);
-      }
-
-      self.info = info;
+    let ModuleMapSnapshotData {
+      next_load_id,
+      info,
+      by_name,
+    } = snapshotted_data.module_map_data;
+
+    self.next_load_id = next_load_id;
+    self.info = info;
+
+    self.dependents.clear();
+    for id in 0..self.info.len() {
+      self.index_dependents(id);
     }
 
     self
       .by_name_mut(AssertedModuleType::JavaScriptOrWasm)
       .clear();
     self.by_name_mut(AssertedModuleType::Json).clear();
-
-    {
-      let by_name_arr: v8::Local<v8::Array> =
-        local_data.get_index(scope, 2).unwrap().try_into().unwrap();
-      let len = by_name_arr.length() as usize;
-
-      for i in 0..len {
-        let arr: v8::Local<v8::Array> = by_name_arr
-          .get_index(scope, i as u32)
-          .unwrap()
-          .try_into()
-          .unwrap();
-
-        let specifier =
-          arr.get_index(scope, 0).unwrap().to_rust_string_lossy(scope);
-        let asserted_module_type = match arr
-          .get_index(scope, 1)
-          .unwrap()
-          .to_integer(scope)
-          .unwrap()
-          .value()
-        {
-          0 => AssertedModuleType::JavaScriptOrWasm,
-          1 => AssertedModuleType::Json,
-          _ => unreachable!(),
-        };
-
-        let symbolic_module_val = arr.get_index(scope, 2).unwrap();
-        let val = if symbolic_module_val.is_number() {
-          SymbolicModule::Mod(
-            symbolic_module_val
-              .to_integer(scope)
-              .unwrap()
-              .value()
-              .try_into()
-              .unwrap(),
-          )
-        } else {
-          SymbolicModule::Alias(
-            symbolic_module_val.to_rust_string_lossy(scope).into(),
-          )
-        };
-
+    self.aliases_to.clear();
+    for (asserted_module_type, name, module) in by_name {
+      if let SymbolicModule::Alias(target) = &module {
         self
-          .by_name_mut(asserted_module_type)
-          .insert(specifier.into(), val);
+          .aliases_to
+          .entry((asserted_module_type, target.as_str().to_owned()))
+          .or_default()
+          .push(name.as_str().to_owned());
       }
+      self.by_name_mut(asserted_module_type).insert(name, module);
     }
 
     self.handles = snapshotted_data.module_handles;
@@ -377,10 +255,16 @@ impl ModuleMap {
       by_name_js: HashMap::new(),
       by_name_json: HashMap::new(),
       next_load_id: 1,
+      source_maps: HashMap::new(),
+      retained_sources: HashMap::new(),
+      dependents: HashMap::new(),
+      aliases_to: HashMap::new(),
+      import_assertions: HashMap::new(),
       loader,
       dynamic_import_map: HashMap::new(),
       preparing_dynamic_imports: FuturesUnordered::new(),
       pending_dynamic_imports: FuturesUnordered::new(),
+      pending_import_meta_resolves: FuturesUnordered::new(),
       json_value_store: HashMap::new(),
     }
   }
@@ -410,6 +294,24 @@ impl ModuleMap {
     }
   }
 
+  /// Follow the aliases starting at `name`, returning the names of the
+  /// redirect targets in order. The last entry is the name the module is
+  /// registered under; the chain is empty if `name` wasn't redirected.
+  pub(crate) fn get_alias_chain(
+    &self,
+    name: &str,
+    asserted_module_type: AssertedModuleType,
+  ) -> Option<Vec<&ModuleName>> {
+    let map = self.by_name(asserted_module_type);
+    let mut chain = vec![];
+    let mut symbolic_module = map.get(name)?;
+    while let SymbolicModule::Alias(target) = symbolic_module {
+      chain.push(target);
+      symbolic_module = map.get(target.as_str())?;
+    }
+    Some(chain)
+  }
+
   pub(crate) fn new_json_module(
     &mut self,
     scope: &mut v8::HandleScope,
@@ -450,6 +352,18 @@ impl ModuleMap {
 
     let id =
       self.create_module_info(name, ModuleType::Json, handle, false, vec![]);
+    if JsRuntime::state_from(tc_scope)
+      .borrow()
+      .retain_module_sources
+    {
+      self.retained_sources.insert(
+        id,
+        RetainedSource {
+          code: source,
+          code_cache: None,
+        },
+      );
+    }
 
     Ok(id)
   }
@@ -464,14 +378,41 @@ impl ModuleMap {
     is_dynamic_import: bool,
   ) -> Result<ModuleId, ModuleError> {
     let name_str = name.v8(scope);
+    let source_hash = ModuleCodeCache::hash_source(source.as_bytes());
     let source_str = source.v8(scope);
 
-    let origin = module_origin(scope, name_str);
-    let source = v8::script_compiler::Source::new(source_str, Some(&origin));
+    let (code_cache, retain_source) = {
+      let state_rc = JsRuntime::state_from(scope);
+      let state = state_rc.borrow();
+      (state.module_code_cache.clone(), state.retain_module_sources)
+    };
+    let cached_data = code_cache
+      .as_ref()
+      .and_then(|code_cache| code_cache.get(name.as_str(), source_hash));
 
+    let origin = module_origin(scope, name_str);
     let tc_scope = &mut v8::TryCatch::new(scope);
 
-    let maybe_module = v8::script_compiler::compile_module(tc_scope, source);
+    let maybe_module = match &cached_data {
+      Some(cached_data) => {
+        let source = v8::script_compiler::Source::new_with_cached_data(
+          source_str,
+          Some(&origin),
+          v8::CachedData::new(&cached_data.data),
+        );
+        v8::script_compiler::compile_module2(
+          tc_scope,
+          source,
+          v8::script_compiler::CompileOptions::ConsumeCodeCache,
+          v8::script_compiler::NoCacheReason::NoReason,
+        )
+      }
+      None => {
+        let source =
+          v8::script_compiler::Source::new(source_str, Some(&origin));
+        v8::script_compiler::compile_module(tc_scope, source)
+      }
+    };
 
     if tc_scope.has_caught() {
       assert!(maybe_module.is_none());
@@ -482,7 +423,49 @@ impl ModuleMap {
 
     let module = maybe_module.unwrap();
 
+    if let Some(code_cache) = &code_cache {
+      match &cached_data {
+        None => {
+          let unbound_module_script =
+            module.get_unbound_module_script(tc_scope);
+          if let Some(data) = unbound_module_script.create_code_cache() {
+            code_cache.insert(name.as_str(), source_hash, &data, true);
+          }
+        }
+        Some(cached_data) if !cached_data.verified => {
+          // V8 silently compiles the module from source when it rejects the
+          // cached data. Compare it with the code cache of the compiled module
+          // to find out, and replace it if it was rejected.
+          let unbound_module_script =
+            module.get_unbound_module_script(tc_scope);
+          if let Some(data) = unbound_module_script.create_code_cache() {
+            if code_cache_rejected(&cached_data.data, &data) {
+              code_cache.insert(name.as_str(), source_hash, &data, true);
+            } else {
+              code_cache.mark_verified(name.as_str(), source_hash);
+            }
+          }
+        }
+        Some(_) => {}
+      }
+    }
+
+    let retained_code_cache = if retain_source {
+      match &code_cache {
+        Some(code_cache) => code_cache
+          .get(name.as_str(), source_hash)
+          .map(|entry| entry.data),
+        None => module
+          .get_unbound_module_script(tc_scope)
+          .create_code_cache()
+          .map(|data| data[..].into()),
+      }
+    } else {
+      None
+    };
+
     let mut requests: Vec<ModuleRequest> = vec![];
+    let mut requests_assertions: Vec<HashMap<String, String>> = vec![];
     let module_requests = module.get_module_requests();
     for i in 0..module_requests.length() {
       let module_request = v8::Local::<v8::ModuleRequest>::try_from(
@@ -529,6 +512,7 @@ impl ModuleMap {
         asserted_module_type,
       };
       requests.push(request);
+      requests_assertions.push(assertions);
     }
 
     if main {
@@ -550,10 +534,39 @@ impl ModuleMap {
       main,
       requests,
     );
+    if requests_assertions
+      .iter()
+      .any(|assertions| !assertions.is_empty())
+    {
+      self.import_assertions.insert(id, requests_assertions);
+    }
+    if retain_source {
+      self.retained_sources.insert(
+        id,
+        RetainedSource {
+          code: source,
+          code_cache: retained_code_cache,
+        },
+      );
+    }
 
     Ok(id)
   }
 
+  /// Returns the import assertions of the `index`-th request of module `id`.
+  pub(crate) fn get_import_assertions(
+    &self,
+    id: ModuleId,
+    index: usize,
+  ) -> HashMap<String, String> {
+    self
+      .import_assertions
+      .get(&id)
+      .and_then(|assertions| assertions.get(index))
+      .cloned()
+      .unwrap_or_default()
+  }
+
   pub(crate) fn clear(&mut self) {
     *self = Self::new(self.loader.clone())
   }
@@ -598,10 +611,62 @@ impl ModuleMap {
       requests,
       module_type,
     });
+    self.index_dependents(id);
 
     id
   }
 
+  fn index_dependents(&mut self, id: ModuleId) {
+    for request in &self.info[id].requests {
+      let dependents = self
+        .dependents
+        .entry((request.asserted_module_type, request.specifier.clone()))
+        .or_default();
+      if !dependents.contains(&id) {
+        dependents.push(id);
+      }
+    }
+  }
+
+  /// Returns the ids of the modules that import the module `name`, including
+  /// imports through a redirect to it.
+  pub(crate) fn get_dependents(
+    &self,
+    name: &str,
+    asserted_module_type: AssertedModuleType,
+  ) -> Vec<ModuleId> {
+    let id = match self.get_id(name, asserted_module_type) {
+      Some(id) => id,
+      None => return vec![],
+    };
+    // Collect the module's name along with every alias that redirects to it,
+    // following chains of redirects. An alias may have been registered again
+    // as something else since, so check that it still resolves to the module.
+    let mut names = vec![self.info[id].name.as_str().to_owned()];
+    let mut i = 0;
+    while i < names.len() {
+      let key = (asserted_module_type, names[i].clone());
+      for alias in self.aliases_to.get(&key).into_iter().flatten() {
+        if !names.contains(alias)
+          && self.get_id(alias, asserted_module_type) == Some(id)
+        {
+          names.push(alias.clone());
+        }
+      }
+      i += 1;
+    }
+    let mut dependents: Vec<ModuleId> = vec![];
+    for name in names {
+      let key = (asserted_module_type, name);
+      for dependent in self.dependents.get(&key).into_iter().flatten() {
+        if !dependents.contains(dependent) {
+          dependents.push(*dependent);
+        }
+      }
+    }
+    dependents
+  }
+
   pub(crate) fn get_requested_modules(
     &self,
     id: ModuleId,
@@ -649,6 +714,11 @@ impl ModuleMap {
     target: FastString,
   ) {
     debug_assert_ne!(name, target);
+    self
+      .aliases_to
+      .entry((asserted_module_type, target.as_str().to_owned()))
+      .or_default()
+      .push(name.as_str().to_owned());
     self
       .by_name_mut(asserted_module_type)
       .insert(name, SymbolicModule::Alias(target));
@@ -706,18 +776,106 @@ impl ModuleMap {
     Ok(load)
   }
 
+  /// Drop the "main" designation of the current main module, if any, so that
+  /// another main module can be registered. The old module's name is
+  /// forgotten too, so loading the same specifier again creates a fresh
+  /// module instead of returning the already evaluated one. Modules that
+  /// already imported the old main module keep referring to it.
+  pub(crate) fn clear_main_module(&mut self) -> Option<ModuleId> {
+    let info = self.info.iter_mut().find(|info| info.main)?;
+    info.main = false;
+    let id = info.id;
+    let name = info.name.as_str().to_owned();
+    let module_type = info.module_type;
+    let by_name = self.by_name_mut(module_type.into());
+    if let Some(SymbolicModule::Mod(mod_id)) = by_name.get(name.as_str()) {
+      if *mod_id == id {
+        by_name.remove(name.as_str());
+      }
+    }
+    Some(id)
+  }
+
+  /// Undo [`ModuleMap::clear_main_module`] after its replacement failed to
+  /// load, making `id` the main module again. The failed replacement, if it
+  /// got registered, is unmarked and forgotten.
+  pub(crate) fn restore_main_module(&mut self, id: ModuleId) {
+    let failed = self.info.iter().find(|info| info.main).map(|info| info.id);
+    if let Some(failed) = failed {
+      self.info[failed].main = false;
+      self.forget_module(failed);
+    }
+    let info = &mut self.info[id];
+    info.main = true;
+    let name = info.name.as_str().to_owned();
+    let module_type = info.module_type;
+    self
+      .by_name_mut(module_type.into())
+      .insert(name.into(), SymbolicModule::Mod(id));
+  }
+
+  /// Remove a module that was replaced from the name and dependents indexes.
+  /// It stays in `info` and `handles`, as ids are indexes into those.
+  pub(crate) fn forget_module(&mut self, id: ModuleId) {
+    let info = &self.info[id];
+    let module_type: AssertedModuleType = info.module_type.into();
+    let by_name = match module_type {
+      AssertedModuleType::Json => &mut self.by_name_json,
+      AssertedModuleType::JavaScriptOrWasm => &mut self.by_name_js,
+    };
+    if let Some(SymbolicModule::Mod(mod_id)) = by_name.get(info.name.as_str()) {
+      if *mod_id == id {
+        by_name.remove(info.name.as_str());
+      }
+    }
+    for request in &info.requests {
+      let key = (request.asserted_module_type, request.specifier.clone());
+      if let Some(dependents) = self.dependents.get_mut(&key) {
+        dependents.retain(|dependent| *dependent != id);
+      }
+    }
+  }
+
+  /// Start loading the module graph rooted at `specifier` so that it can be
+  /// fetched and compiled without being instantiated or evaluated.
+  pub(crate) async fn prefetch(
+    module_map_rc: Rc<RefCell<ModuleMap>>,
+    specifier: impl AsRef<str>,
+  ) -> Result<RecursiveModuleLoad, Error> {
+    Self::load_side(module_map_rc, specifier).await
+  }
+
+  /// Mark an already registered module as the main module. This happens when
+  /// a prefetched module is later loaded with `JsRuntime::load_main_module`.
+  pub(crate) fn set_main_module(&mut self, id: ModuleId) -> Result<(), Error> {
+    if let Some(main_module) = self.info.iter().find(|module| module.main) {
+      if main_module.id == id {
+        return Ok(());
+      }
+      return Err(generic_error(format!(
+        "Trying to create \"main\" module ({:?}), when one already exists ({:?})",
+        self.info[id].name.as_ref(),
+        main_module.name,
+      )));
+    }
+    self.info[id].main = true;
+    Ok(())
+  }
+
   // Initiate loading of a module graph imported using `import()`.
   pub(crate) fn load_dynamic_import(
     module_map_rc: Rc<RefCell<ModuleMap>>,
     specifier: &str,
     referrer: &str,
     asserted_module_type: AssertedModuleType,
+    import_assertions: HashMap<String, String>,
     resolver_handle: v8::Global<v8::PromiseResolver>,
   ) {
     let load = RecursiveModuleLoad::dynamic_import(
       specifier,
       referrer,
       asserted_module_type,
+      import_assertions,
       module_map_rc.clone(),
     );
     module_map_rc
@@ -748,11 +906,57 @@ impl ModuleMap {
       .push(fut);
   }
 
+  /// Drops all in-flight dynamic imports along with the promise resolvers
+  /// they would settle. Used before taking a snapshot, as the snapshot can't
+  /// contain V8 handles owned by the embedder.
+  pub(crate) fn clear_pending_dynamic_imports(&mut self) {
+    self.dynamic_import_map.clear();
+    self.preparing_dynamic_imports = FuturesUnordered::new();
+    self.pending_dynamic_imports = FuturesUnordered::new();
+    self.pending_import_meta_resolves = FuturesUnordered::new();
+    self.json_value_store.clear();
+  }
+
   pub(crate) fn has_pending_dynamic_imports(&self) -> bool {
     !(self.preparing_dynamic_imports.is_empty()
       && self.pending_dynamic_imports.is_empty())
   }
 
+  pub(crate) fn num_pending_dynamic_imports(&self) -> usize {
+    self.preparing_dynamic_imports.len() + self.pending_dynamic_imports.len()
+  }
+
+  pub(crate) fn has_pending_import_meta_resolves(&self) -> bool {
+    !self.pending_import_meta_resolves.is_empty()
+  }
+
+  pub(crate) fn add_source_map(
+    &mut self,
+    name: ModuleName,
+    source_map: Vec<u8>,
+  ) {
+    self.source_maps.insert(name, source_map);
+  }
+
+  /// Returns the raw source map registered for the module `name`, if any.
+  pub(crate) fn get_source_map(&self, name: &str) -> Option<&[u8]> {
+    self
+      .source_maps
+      .get(name)
+      .map(|source_map| source_map.as_slice())
+  }
+
+  pub(crate) fn get_retained_source(
+    &self,
+    id: ModuleId,
+  ) -> Option<&RetainedSource> {
+    self.retained_sources.get(&id)
+  }
+
+  pub(crate) fn has_source_maps(&self) -> bool {
+    !self.source_maps.is_empty()
+  }
+
   /// Called by `module_resolve_callback` during module instantiation.
   pub(crate) fn resolve_callback<'s>(
     &self,
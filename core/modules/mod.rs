@@ -11,6 +11,7 @@ use futures::stream::TryStreamExt;
 use log::debug;
 use serde::Deserialize;
 use serde::Serialize;
+use sha2::Digest;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -21,20 +22,24 @@ use std::rc::Rc;
 use std::task::Context;
 use std::task::Poll;
 
+mod archive;
 mod loaders;
 mod map;
+mod snapshot;
 
 #[cfg(test)]
 mod tests;
 
+pub use archive::ArchiveModuleLoader;
+pub use archive::ModuleArchiveWriter;
 pub(crate) use loaders::ExtModuleLoader;
 pub use loaders::ExtModuleLoaderCb;
 pub use loaders::FsModuleLoader;
 pub use loaders::ModuleLoader;
 pub use loaders::NoopModuleLoader;
 pub(crate) use map::ModuleMap;
-#[cfg(test)]
 pub(crate) use map::SymbolicModule;
+pub(crate) use snapshot::ModuleMapSnapshotData;
 
 pub type ModuleId = usize;
 pub(crate) type ModuleLoadId = i32;
@@ -59,9 +64,72 @@ pub(crate) fn validate_import_assertions(
       scope.throw_exception(exception);
       return;
     }
+    if key == "integrity" && parse_integrity(value).next().is_none() {
+      let message = v8::String::new(
+        scope,
+        &format!("\"{value}\" is not a valid integrity metadata."),
+      )
+      .unwrap();
+      let exception = v8::Exception::type_error(scope, message);
+      scope.throw_exception(exception);
+      return;
+    }
   }
 }
 
+/// Parses the hashes of an `integrity` import assertion, using the same
+/// syntax as Subresource Integrity (eg. `"sha384-<base64 digest>"`, several
+/// separated by whitespace). Hashes with unsupported algorithms are skipped.
+fn parse_integrity(
+  integrity: &str,
+) -> impl Iterator<Item = (&'static str, &str)> {
+  integrity.split_ascii_whitespace().filter_map(|hash| {
+    let (algorithm, digest) = hash.split_once('-')?;
+    let algorithm = ["sha256", "sha384", "sha512"]
+      .into_iter()
+      .find(|a| *a == algorithm)?;
+    // Options (`?...`) are reserved by the spec and ignored.
+    let digest = digest.split_once('?').map_or(digest, |(d, _)| d);
+    Some((algorithm, digest))
+  })
+}
+
+/// Checks the code of a loaded module against the `integrity` import
+/// assertion of the request, if any. This runs before the module is
+/// registered in the module map, so a mismatched source is never
+/// instantiated.
+fn check_integrity(
+  specifier: &ModuleSpecifier,
+  module_source: &ModuleSource,
+  import_assertions: &HashMap<String, String>,
+) -> Result<(), Error> {
+  let Some(integrity) = import_assertions.get("integrity") else {
+    return Ok(());
+  };
+  let code = module_source.code.as_bytes();
+  let mut actual = None;
+  for (algorithm, expected) in parse_integrity(integrity) {
+    let digest = match algorithm {
+      "sha256" => sha2::Sha256::digest(code).to_vec(),
+      "sha384" => sha2::Sha384::digest(code).to_vec(),
+      _ => sha2::Sha512::digest(code).to_vec(),
+    };
+    let digest = base64::encode(digest);
+    if digest == expected {
+      return Ok(());
+    }
+    actual.get_or_insert_with(|| format!("{algorithm}-{digest}"));
+  }
+  let Some(actual) = actual else {
+    return Err(generic_error(format!(
+      "\"{integrity}\" is not a valid integrity metadata."
+    )));
+  };
+  Err(generic_error(format!(
+    "Integrity check failed for \"{specifier}\".\n  Expected: {integrity}\n  Actual: {actual}"
+  )))
+}
+
 #[derive(Debug)]
 pub(crate) enum ImportAssertionsKind {
   StaticImport,
@@ -161,6 +229,10 @@ impl std::fmt::Display for ModuleType {
 pub struct ModuleSource {
   pub code: ModuleCode,
   pub module_type: ModuleType,
+  /// Raw source map for `code`, if it was generated from another source (eg.
+  /// transpiled from TypeScript). Locations in stack traces that point into
+  /// this module are remapped using it.
+  pub source_map: Option<Vec<u8>>,
   module_url_specified: ModuleName,
   /// If the module was found somewhere other than the specified address, this will be [`Some`].
   module_url_found: Option<ModuleName>,
@@ -177,6 +249,7 @@ impl ModuleSource {
     Self {
       code,
       module_type: module_type.into(),
+      source_map: None,
       module_url_specified,
       module_url_found: None,
     }
@@ -199,16 +272,24 @@ impl ModuleSource {
     Self {
       code,
       module_type: module_type.into(),
+      source_map: None,
       module_url_specified,
       module_url_found,
     }
   }
 
+  /// Attach a source map to this [`ModuleSource`].
+  pub fn with_source_map(mut self, source_map: Vec<u8>) -> Self {
+    self.source_map = Some(source_map);
+    self
+  }
+
   #[cfg(test)]
   pub fn for_test(code: &'static str, file: impl AsRef<str>) -> Self {
     Self {
       code: ModuleCode::from_static(code),
       module_type: ModuleType::JavaScript,
+      source_map: None,
       module_url_specified: file.as_ref().to_owned().into(),
       module_url_found: None,
     }
@@ -231,6 +312,7 @@ impl ModuleSource {
     Self {
       code: ModuleCode::from_static(code),
       module_type: ModuleType::JavaScript,
+      source_map: None,
       module_url_specified: specified.into(),
       module_url_found: found,
     }
@@ -240,6 +322,35 @@ impl ModuleSource {
 pub(crate) type PrepareLoadFuture =
   dyn Future<Output = (ModuleLoadId, Result<RecursiveModuleLoad, Error>)>;
 pub type ModuleSourceFuture = dyn Future<Output = Result<ModuleSource, Error>>;
+pub type ModuleResolutionFuture =
+  dyn Future<Output = Result<ModuleSpecifier, Error>>;
+pub(crate) type ImportMetaResolveFuture = dyn Future<
+  Output = (
+    v8::Global<v8::PromiseResolver>,
+    Result<ModuleSpecifier, Error>,
+  ),
+>;
+
+/// The module a specifier ended up at after following the redirects the
+/// module loader reported, see [`crate::JsRuntime::resolved_specifier`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FinalSpecifier {
+  /// The specifier the module is registered under.
+  pub specifier: ModuleSpecifier,
+  /// Every redirect target visited on the way, in order, ending with
+  /// `specifier`. Empty if the specifier wasn't redirected.
+  pub redirects: Vec<ModuleSpecifier>,
+}
+
+/// The result of resolving a specifier passed to `import.meta.resolve()`.
+pub enum ImportMetaResolution {
+  /// The specifier was resolved synchronously, `import.meta.resolve()` returns
+  /// the resolved URL (or throws).
+  Sync(Result<ModuleSpecifier, Error>),
+  /// Resolution requires asynchronous work, `import.meta.resolve()` returns a
+  /// promise that settles once the future completes.
+  Async(Pin<Box<ModuleResolutionFuture>>),
+}
 
 type ModuleLoadFuture =
   dyn Future<Output = Result<(ModuleRequest, ModuleSource), Error>>;
@@ -266,9 +377,10 @@ enum LoadInit {
   Main(String),
   /// Module specifier for side module.
   Side(String),
-  /// Dynamic import specifier with referrer and expected
-  /// module type (which is determined by import assertion).
-  DynamicImport(String, String, AssertedModuleType),
+  /// Dynamic import specifier with referrer, expected
+  /// module type (which is determined by import assertion) and
+  /// all import assertions.
+  DynamicImport(String, String, AssertedModuleType, HashMap<String, String>),
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -315,6 +427,7 @@ impl RecursiveModuleLoad {
     specifier: &str,
     referrer: &str,
     asserted_module_type: AssertedModuleType,
+    import_assertions: HashMap<String, String>,
     module_map_rc: Rc<RefCell<ModuleMap>>,
   ) -> Self {
     Self::new(
@@ -322,6 +435,7 @@ impl RecursiveModuleLoad {
         specifier.to_string(),
         referrer.to_string(),
         asserted_module_type,
+        import_assertions,
       ),
       module_map_rc,
     )
@@ -336,7 +450,7 @@ impl RecursiveModuleLoad {
     };
     let loader = module_map_rc.borrow().loader.clone();
     let asserted_module_type = match init {
-      LoadInit::DynamicImport(_, _, module_type) => module_type,
+      LoadInit::DynamicImport(_, _, module_type, _) => module_type,
       _ => AssertedModuleType::JavaScriptOrWasm,
     };
     let mut load = Self {
@@ -382,7 +496,7 @@ impl RecursiveModuleLoad {
       LoadInit::Side(ref specifier) => {
         self.loader.resolve(specifier, ".", ResolutionKind::Import)
       }
-      LoadInit::DynamicImport(ref specifier, ref referrer, _) => self
+      LoadInit::DynamicImport(ref specifier, ref referrer, _, _) => self
         .loader
         .resolve(specifier, referrer, ResolutionKind::DynamicImport),
     }
@@ -404,7 +518,7 @@ impl RecursiveModuleLoad {
             .resolve(specifier, ".", ResolutionKind::Import)?;
         (spec, None)
       }
-      LoadInit::DynamicImport(ref specifier, ref referrer, _) => {
+      LoadInit::DynamicImport(ref specifier, ref referrer, _, _) => {
         let spec = self.loader.resolve(
           specifier,
           referrer,
@@ -449,7 +563,8 @@ impl RecursiveModuleLoad {
 
     // Register the module in the module map unless it's already there. If the
     // specified URL and the "true" URL are different, register the alias.
-    let module_url_found = if let Some(module_url_found) = module_url_found {
+    let mut module_url_found = if let Some(module_url_found) = module_url_found
+    {
       let (module_url_found1, module_url_found2) =
         module_url_found.into_cheap_copy();
       self.module_map_rc.borrow_mut().alias(
@@ -474,22 +589,33 @@ impl RecursiveModuleLoad {
         );
         id
       }
-      None => match module_source.module_type {
-        ModuleType::JavaScript => {
-          self.module_map_rc.borrow_mut().new_es_module(
+      None => {
+        if let Some(source_map) = module_source.source_map {
+          let (name, module_url_found_copy) =
+            module_url_found.into_cheap_copy();
+          module_url_found = module_url_found_copy;
+          self
+            .module_map_rc
+            .borrow_mut()
+            .add_source_map(name, source_map);
+        }
+        match module_source.module_type {
+          ModuleType::JavaScript => {
+            self.module_map_rc.borrow_mut().new_es_module(
+              scope,
+              self.is_currently_loading_main_module(),
+              module_url_found,
+              module_source.code,
+              self.is_dynamic_import(),
+            )?
+          }
+          ModuleType::Json => self.module_map_rc.borrow_mut().new_json_module(
             scope,
-            self.is_currently_loading_main_module(),
             module_url_found,
             module_source.code,
-            self.is_dynamic_import(),
-          )?
+          )?,
         }
-        ModuleType::Json => self.module_map_rc.borrow_mut().new_json_module(
-          scope,
-          module_url_found,
-          module_source.code,
-        )?,
-      },
+      }
     };
 
     // Recurse the module's imports. There are two cases for each import:
@@ -512,7 +638,7 @@ impl RecursiveModuleLoad {
         .get_requested_modules(module_id)
         .unwrap()
         .clone();
-      for module_request in imports {
+      for (index, module_request) in imports.into_iter().enumerate() {
         if !self.visited.contains(&module_request) {
           if let Some(module_id) = self.module_map_rc.borrow().get_id(
             module_request.specifier.as_str(),
@@ -526,11 +652,22 @@ impl RecursiveModuleLoad {
             let referrer = referrer.clone();
             let loader = self.loader.clone();
             let is_dynamic_import = self.is_dynamic_import();
+            let import_assertions = self
+              .module_map_rc
+              .borrow()
+              .get_import_assertions(module_id, index);
             let fut = async move {
               let load_result = loader
-                .load(&specifier, Some(&referrer), is_dynamic_import)
+                .load(
+                  &specifier,
+                  Some(&referrer),
+                  is_dynamic_import,
+                  &import_assertions,
+                )
                 .await;
-              load_result.map(|s| (request, s))
+              let module_source = load_result?;
+              check_integrity(&specifier, &module_source, &import_assertions)?;
+              Ok((request, module_source))
             };
             self.pending.push(fut.boxed_local());
           }
@@ -593,19 +730,25 @@ impl Stream for RecursiveModuleLoad {
           futures::future::ok((module_request, module_source)).boxed()
         } else {
           let maybe_referrer = match inner.init {
-            LoadInit::DynamicImport(_, ref referrer, _) => {
+            LoadInit::DynamicImport(_, ref referrer, _, _) => {
               resolve_url(referrer).ok()
             }
             _ => None,
           };
           let asserted_module_type = match inner.init {
-            LoadInit::DynamicImport(_, _, module_type) => module_type,
+            LoadInit::DynamicImport(_, _, module_type, _) => module_type,
             _ => AssertedModuleType::JavaScriptOrWasm,
           };
           let module_request = ModuleRequest {
             specifier: module_specifier.to_string(),
             asserted_module_type,
           };
+          let import_assertions = match inner.init {
+            LoadInit::DynamicImport(_, _, _, ref import_assertions) => {
+              import_assertions.clone()
+            }
+            _ => HashMap::new(),
+          };
           let loader = inner.loader.clone();
           let is_dynamic_import = inner.is_dynamic_import();
           async move {
@@ -614,9 +757,16 @@ impl Stream for RecursiveModuleLoad {
                 &module_specifier,
                 maybe_referrer.as_ref(),
                 is_dynamic_import,
+                &import_assertions,
               )
               .await;
-            result.map(|s| (module_request, s))
+            let module_source = result?;
+            check_integrity(
+              &module_specifier,
+              &module_source,
+              &import_assertions,
+            )?;
+            Ok((module_request, module_source))
           }
           .boxed_local()
         };
@@ -0,0 +1,299 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::error::generic_error;
+use crate::modules::AssertedModuleType;
+use crate::modules::ModuleId;
+use crate::modules::ModuleInfo;
+use crate::modules::ModuleLoadId;
+use crate::modules::ModuleName;
+use crate::modules::ModuleRequest;
+use crate::modules::ModuleType;
+use anyhow::Error;
+
+use super::map::SymbolicModule;
+
+/// Magic bytes at the start of every serialized module map.
+const MODULE_MAP_MAGIC: &[u8; 4] = b"DMMP";
+
+/// Version of the binary module map encoding. Bump this whenever the layout
+/// written by [`ModuleMapSnapshotData::encode`] changes.
+const MODULE_MAP_SNAPSHOT_VERSION: u32 = 1;
+
+/// Size of the fixed header: magic, version and module count.
+const HEADER_LEN: usize = 12;
+
+/// Smallest encoded sizes of the repeated entries, used to reject counts that
+/// the remaining data can't possibly hold before allocating for them.
+const MIN_MODULE_LEN: usize = 14;
+const MIN_REQUEST_LEN: usize = 5;
+const MIN_BY_NAME_LEN: usize = 10;
+
+/// Plain-data representation of a `ModuleMap`, detached from any V8 handles,
+/// that can be stored in a snapshot.
+///
+/// The encoding is little-endian and length-prefixed:
+///
+/// ```text
+/// header:   magic [u8; 4] | version u32 | module count u32
+/// body:     next_load_id i32
+///           module count x (id u32 | main u8 | module type u8 | name str
+///                           | request count u32
+///                           | request count x (asserted type u8 | specifier str))
+///           by_name count u32
+///           by_name count x (asserted type u8 | name str
+///                            | 0u8 id u32 or 1u8 alias str)
+/// str:      byte length u32 | utf-8 bytes
+/// ```
+///
+/// `by_name` entries are sorted before encoding so the same module map always
+/// produces the same bytes.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ModuleMapSnapshotData {
+  pub next_load_id: ModuleLoadId,
+  pub info: Vec<ModuleInfo>,
+  pub by_name: Vec<(AssertedModuleType, ModuleName, SymbolicModule)>,
+}
+
+impl ModuleMapSnapshotData {
+  pub fn encode(&self) -> Vec<u8> {
+    let mut writer = Writer::default();
+    writer.bytes(MODULE_MAP_MAGIC);
+    writer.u32(MODULE_MAP_SNAPSHOT_VERSION);
+    writer.len(self.info.len());
+
+    writer.i32(self.next_load_id);
+    for info in &self.info {
+      writer.len(info.id);
+      writer.u8(info.main as u8);
+      writer.u8(info.module_type as u8);
+      writer.str(info.name.as_str());
+      writer.len(info.requests.len());
+      for request in &info.requests {
+        writer.u8(request.asserted_module_type as u8);
+        writer.str(&request.specifier);
+      }
+    }
+
+    let mut by_name = self.by_name.iter().collect::<Vec<_>>();
+    by_name.sort_by(|a, b| {
+      (a.0 as u8, a.1.as_str()).cmp(&(b.0 as u8, b.1.as_str()))
+    });
+    writer.len(by_name.len());
+    for (asserted_module_type, name, symbolic_module) in by_name {
+      writer.u8(*asserted_module_type as u8);
+      writer.str(name.as_str());
+      match symbolic_module {
+        SymbolicModule::Mod(id) => {
+          writer.u8(0);
+          writer.len(*id);
+        }
+        SymbolicModule::Alias(target) => {
+          writer.u8(1);
+          writer.str(target.as_str());
+        }
+      }
+    }
+
+    writer.0
+  }
+
+  pub fn decode(data: &[u8]) -> Result<Self, Error> {
+    let mut reader = Reader::new(data);
+    let module_count = reader.header()?;
+
+    let next_load_id = reader.i32()?;
+    reader.check_count(module_count, MIN_MODULE_LEN)?;
+    // Over allocate so executing a few scripts doesn't have to resize this vec.
+    let capacity = module_count
+      .checked_add(16)
+      .ok_or_else(|| corrupted("module count overflows".to_string()))?;
+    let mut info = Vec::with_capacity(capacity);
+    for _ in 0..module_count {
+      let id = reader.len()?;
+      let main = reader.bool()?;
+      let module_type = match reader.u8()? {
+        0 => ModuleType::JavaScript,
+        1 => ModuleType::Json,
+        n => return Err(corrupted(format!("unknown module type {n}"))),
+      };
+      let name = reader.str()?.into();
+      let request_count = reader.count(MIN_REQUEST_LEN)?;
+      let mut requests = Vec::with_capacity(request_count);
+      for _ in 0..request_count {
+        let asserted_module_type = reader.asserted_module_type()?;
+        let specifier = reader.str()?;
+        requests.push(ModuleRequest {
+          specifier,
+          asserted_module_type,
+        });
+      }
+      info.push(ModuleInfo {
+        id,
+        main,
+        name,
+        requests,
+        module_type,
+      });
+    }
+
+    let by_name_count = reader.count(MIN_BY_NAME_LEN)?;
+    let mut by_name = Vec::with_capacity(by_name_count);
+    for _ in 0..by_name_count {
+      let asserted_module_type = reader.asserted_module_type()?;
+      let name = reader.str()?.into();
+      let symbolic_module = match reader.u8()? {
+        0 => {
+          let id: ModuleId = reader.len()?;
+          if id >= module_count {
+            return Err(corrupted(format!(
+              "module id {id} out of range ({module_count} modules)"
+            )));
+          }
+          SymbolicModule::Mod(id)
+        }
+        1 => SymbolicModule::Alias(reader.str()?.into()),
+        n => return Err(corrupted(format!("unknown symbolic module tag {n}"))),
+      };
+      by_name.push((asserted_module_type, name, symbolic_module));
+    }
+
+    if !reader.is_empty() {
+      return Err(corrupted("trailing bytes".to_string()));
+    }
+
+    Ok(Self {
+      next_load_id,
+      info,
+      by_name,
+    })
+  }
+}
+
+fn corrupted(reason: String) -> Error {
+  generic_error(format!("Corrupted module map in snapshot: {reason}"))
+}
+
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+  fn bytes(&mut self, bytes: &[u8]) {
+    self.0.extend_from_slice(bytes);
+  }
+
+  fn u8(&mut self, value: u8) {
+    self.0.push(value);
+  }
+
+  fn u32(&mut self, value: u32) {
+    self.bytes(&value.to_le_bytes());
+  }
+
+  fn i32(&mut self, value: i32) {
+    self.bytes(&value.to_le_bytes());
+  }
+
+  fn len(&mut self, value: usize) {
+    self.u32(u32::try_from(value).expect("module map too large to snapshot"));
+  }
+
+  fn str(&mut self, value: &str) {
+    self.len(value.len());
+    self.bytes(value.as_bytes());
+  }
+}
+
+struct Reader<'a> {
+  data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+  fn new(data: &'a [u8]) -> Self {
+    Self { data }
+  }
+
+  fn is_empty(&self) -> bool {
+    self.data.is_empty()
+  }
+
+  fn header(&mut self) -> Result<usize, Error> {
+    if self.data.len() < HEADER_LEN || &self.data[..4] != MODULE_MAP_MAGIC {
+      return Err(generic_error(
+        "Snapshot does not contain a module map in a recognized format",
+      ));
+    }
+    self.data = &self.data[4..];
+    let version = self.u32()?;
+    if version != MODULE_MAP_SNAPSHOT_VERSION {
+      return Err(generic_error(format!(
+        "Module map snapshot version mismatch: expected {}, found {}. The snapshot was created by an incompatible version of deno_core and must be rebuilt.",
+        MODULE_MAP_SNAPSHOT_VERSION, version
+      )));
+    }
+    self.len()
+  }
+
+  fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+    if self.data.len() < len {
+      return Err(corrupted("unexpected end of data".to_string()));
+    }
+    let (head, tail) = self.data.split_at(len);
+    self.data = tail;
+    Ok(head)
+  }
+
+  fn u8(&mut self) -> Result<u8, Error> {
+    Ok(self.take(1)?[0])
+  }
+
+  fn bool(&mut self) -> Result<bool, Error> {
+    match self.u8()? {
+      0 => Ok(false),
+      1 => Ok(true),
+      n => Err(corrupted(format!("invalid boolean {n}"))),
+    }
+  }
+
+  fn u32(&mut self) -> Result<u32, Error> {
+    Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+  }
+
+  fn i32(&mut self) -> Result<i32, Error> {
+    Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+  }
+
+  fn len(&mut self) -> Result<usize, Error> {
+    Ok(self.u32()? as usize)
+  }
+
+  /// Reads the number of entries that follow, each at least `min_len` bytes.
+  fn count(&mut self, min_len: usize) -> Result<usize, Error> {
+    let count = self.len()?;
+    self.check_count(count, min_len)?;
+    Ok(count)
+  }
+
+  fn check_count(&self, count: usize, min_len: usize) -> Result<(), Error> {
+    match count.checked_mul(min_len) {
+      Some(len) if len <= self.data.len() => Ok(()),
+      _ => Err(corrupted(format!(
+        "{count} entries don't fit in the remaining {} bytes",
+        self.data.len()
+      ))),
+    }
+  }
+
+  fn str(&mut self) -> Result<String, Error> {
+    let len = self.len()?;
+    let bytes = self.take(len)?;
+    String::from_utf8(bytes.to_vec())
+      .map_err(|_| corrupted("invalid utf-8 in string".to_string()))
+  }
+
+  fn asserted_module_type(&mut self) -> Result<AssertedModuleType, Error> {
+    match self.u8()? {
+      0 => Ok(AssertedModuleType::JavaScriptOrWasm),
+      1 => Ok(AssertedModuleType::Json),
+      n => Err(corrupted(format!("unknown asserted module type {n}"))),
+    }
+  }
+}
@@ -3,6 +3,7 @@ use crate::ascii_str;
 use crate::resolve_import;
 use crate::runtime::JsRuntime;
 use crate::runtime::JsRuntimeForSnapshot;
+use crate::ModuleCodeCache;
 use crate::RuntimeOptions;
 use crate::Snapshot;
 use deno_ops::op;
@@ -216,6 +217,7 @@ impl ModuleLoader for MockLoader {
     module_specifier: &ModuleSpecifier,
     _maybe_referrer: Option<&ModuleSpecifier>,
     _is_dyn_import: bool,
+    _import_assertions: &HashMap<String, String>,
   ) -> Pin<Box<ModuleSourceFuture>> {
     let mut loads = self.loads.lock();
     loads.push(module_specifier.to_string());
@@ -322,6 +324,7 @@ fn test_mods() {
       _module_specifier: &ModuleSpecifier,
       _maybe_referrer: Option<&ModuleSpecifier>,
       _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
     ) -> Pin<Box<ModuleSourceFuture>> {
       unreachable!()
     }
@@ -447,6 +450,7 @@ fn test_json_module() {
       _module_specifier: &ModuleSpecifier,
       _maybe_referrer: Option<&ModuleSpecifier>,
       _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
     ) -> Pin<Box<ModuleSourceFuture>> {
       unreachable!()
     }
@@ -553,6 +557,7 @@ async fn dyn_import_err() {
       _module_specifier: &ModuleSpecifier,
       _maybe_referrer: Option<&ModuleSpecifier>,
       _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
     ) -> Pin<Box<ModuleSourceFuture>> {
       async { Err(io::Error::from(io::ErrorKind::NotFound).into()) }.boxed()
     }
@@ -616,6 +621,7 @@ impl ModuleLoader for DynImportOkLoader {
     specifier: &ModuleSpecifier,
     _maybe_referrer: Option<&ModuleSpecifier>,
     _is_dyn_import: bool,
+    _import_assertions: &HashMap<String, String>,
   ) -> Pin<Box<ModuleSourceFuture>> {
     self.load_count.fetch_add(1, Ordering::Relaxed);
     let info =
@@ -743,6 +749,7 @@ fn dyn_concurrent_circular_import() {
       specifier: &ModuleSpecifier,
       _maybe_referrer: Option<&ModuleSpecifier>,
       _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
     ) -> Pin<Box<ModuleSourceFuture>> {
       self.load_count.fetch_add(1, Ordering::Relaxed);
       let filename = PathBuf::from(specifier.to_string())
@@ -1111,6 +1118,7 @@ fn main_and_side_module() {
       module_specifier: &ModuleSpecifier,
       _maybe_referrer: Option<&ModuleSpecifier>,
       _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
     ) -> Pin<Box<ModuleSourceFuture>> {
       let module_source = match module_specifier.as_str() {
         "file:///main_module.js" => ModuleSource::for_test(
@@ -1250,3 +1258,945 @@ fn import_meta_snapshot() {
     )
     .unwrap();
 }
+
+#[test]
+fn module_map_snapshot_data_roundtrip() {
+  let data = ModuleMapSnapshotData {
+    next_load_id: 7,
+    info: vec![
+      ModuleInfo {
+        id: 0,
+        main: true,
+        name: ascii_str!("file:///main.js"),
+        requests: vec![ModuleRequest {
+          specifier: "file:///data.json".to_string(),
+          asserted_module_type: AssertedModuleType::Json,
+        }],
+        module_type: ModuleType::JavaScript,
+      },
+      ModuleInfo {
+        id: 1,
+        main: false,
+        name: ascii_str!("file:///data.json"),
+        requests: vec![],
+        module_type: ModuleType::Json,
+      },
+    ],
+    by_name: vec![
+      (
+        AssertedModuleType::JavaScriptOrWasm,
+        ascii_str!("file:///main.js"),
+        SymbolicModule::Mod(0),
+      ),
+      (
+        AssertedModuleType::JavaScriptOrWasm,
+        ascii_str!("file:///alias.js"),
+        SymbolicModule::Alias(ascii_str!("file:///main.js")),
+      ),
+      (
+        AssertedModuleType::Json,
+        ascii_str!("file:///data.json"),
+        SymbolicModule::Mod(1),
+      ),
+    ],
+  };
+
+  let encoded = data.encode();
+  let decoded = ModuleMapSnapshotData::decode(&encoded).unwrap();
+  assert_eq!(decoded.next_load_id, data.next_load_id);
+  assert_eq!(decoded.info, data.info);
+  assert_eq!(decoded.by_name.len(), 3);
+  // Encoding is deterministic regardless of `by_name` ordering.
+  assert_eq!(decoded.encode(), encoded);
+}
+
+#[test]
+fn module_map_snapshot_data_version_mismatch() {
+  let mut encoded = ModuleMapSnapshotData::default().encode();
+  encoded[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+  let err = ModuleMapSnapshotData::decode(&encoded).unwrap_err();
+  assert!(err.to_string().contains("version mismatch"), "{err}");
+
+  let err = ModuleMapSnapshotData::decode(b"not a module map").unwrap_err();
+  assert!(err.to_string().contains("recognized format"), "{err}");
+
+  let encoded = ModuleMapSnapshotData::default().encode();
+  let err =
+    ModuleMapSnapshotData::decode(&encoded[..encoded.len() - 1]).unwrap_err();
+  assert!(err.to_string().contains("Corrupted"), "{err}");
+}
+
+#[test]
+fn module_map_snapshot_data_oversized_counts() {
+  // A module count far beyond what the data holds must be rejected before
+  // anything is allocated for it.
+  let mut encoded = ModuleMapSnapshotData::default().encode();
+  encoded[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+  let err = ModuleMapSnapshotData::decode(&encoded).unwrap_err();
+  assert!(err.to_string().contains("don't fit"), "{err}");
+
+  // Same for the by_name count, which follows next_load_id.
+  let mut encoded = ModuleMapSnapshotData::default().encode();
+  encoded[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+  let err = ModuleMapSnapshotData::decode(&encoded).unwrap_err();
+  assert!(err.to_string().contains("don't fit"), "{err}");
+}
+
+#[test]
+fn snapshot_with_pending_state() {
+  let (snapshot, next_load_id) = {
+    const MAIN_WITH_CODE_SRC: ModuleCode = ascii_str!(
+      r#"
+  import("/never_ready.js");
+  globalThis.value = 42;
+  "#
+    );
+
+    let loader = MockLoader::new();
+    let mut runtime = JsRuntime::new(RuntimeOptions {
+      module_loader: Some(loader),
+      will_snapshot: true,
+      ..Default::default()
+    });
+    let spec = resolve_url("file:///main_with_code.js").unwrap();
+    let main_id_fut = runtime
+      .load_main_module(&spec, Some(MAIN_WITH_CODE_SRC))
+      .boxed_local();
+    let main_id = futures::executor::block_on(main_id_fut).unwrap();
+    #[allow(clippy::let_underscore_future)]
+    let _ = runtime.mod_evaluate(main_id);
+
+    let spec = resolve_url("file:///redirect1.js").unwrap();
+    let side_id_fut = runtime.load_side_module(&spec, None).boxed_local();
+    let side_id = futures::executor::block_on(side_id_fut).unwrap();
+    #[allow(clippy::let_underscore_future)]
+    let _ = runtime.mod_evaluate(side_id);
+
+    // The dynamic import of `never_ready.js` never settles, so just spin the
+    // event loop once and snapshot with the import still pending.
+    futures::executor::block_on(poll_fn(|cx| {
+      let _ = runtime.poll_event_loop(cx, false);
+      Poll::Ready(())
+    }));
+    assert!(runtime.module_map.borrow().has_pending_dynamic_imports());
+
+    let next_load_id = runtime.module_map.borrow().next_load_id;
+    (runtime.snapshot().unwrap(), next_load_id)
+  };
+
+  let mut runtime2 = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(MockLoader::new()),
+    startup_snapshot: Some(Snapshot::JustCreated(snapshot)),
+    ..Default::default()
+  });
+
+  {
+    let module_map = runtime2.module_map.borrow();
+    assert_eq!(module_map.next_load_id, next_load_id);
+    assert!(!module_map.has_pending_dynamic_imports());
+    assert!(module_map
+      .get_id(
+        "file:///main_with_code.js",
+        AssertedModuleType::JavaScriptOrWasm
+      )
+      .is_some());
+    assert!(module_map
+      .is_alias("file:///redirect2.js", AssertedModuleType::JavaScriptOrWasm));
+    assert_eq!(
+      module_map
+        .get_id("file:///redirect2.js", AssertedModuleType::JavaScriptOrWasm),
+      module_map.get_id(
+        "file:///dir/redirect2.js",
+        AssertedModuleType::JavaScriptOrWasm
+      )
+    );
+  }
+
+  runtime2
+    .execute_script_static(
+      "check.js",
+      "if (globalThis.value !== 42) throw Error('x')",
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn import_meta_resolve_async() {
+  struct AsyncResolveLoader;
+
+  impl ModuleLoader for AsyncResolveLoader {
+    fn resolve(
+      &self,
+      specifier: &str,
+      referrer: &str,
+      _kind: ResolutionKind,
+    ) -> Result<ModuleSpecifier, Error> {
+      Ok(resolve_import(specifier, referrer)?)
+    }
+
+    fn load(
+      &self,
+      _module_specifier: &ModuleSpecifier,
+      _maybe_referrer: Option<&ModuleSpecifier>,
+      _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
+    ) -> Pin<Box<ModuleSourceFuture>> {
+      unreachable!()
+    }
+
+    fn resolve_import_meta(
+      &self,
+      specifier: &str,
+      referrer: &str,
+    ) -> ImportMetaResolution {
+      let specifier = match specifier.strip_prefix("async:") {
+        Some(specifier) => specifier,
+        None => {
+          return ImportMetaResolution::Sync(self.resolve(
+            specifier,
+            referrer,
+            ResolutionKind::Import,
+          ))
+        }
+      };
+      let result = resolve_import(specifier, referrer).map_err(Error::from);
+      ImportMetaResolution::Async(
+        async move {
+          tokio::task::yield_now().await;
+          result
+        }
+        .boxed_local(),
+      )
+    }
+  }
+
+  const MAIN_SRC: ModuleCode = ascii_str!(
+    r#"
+globalThis.syncResolved = import.meta.resolve("./sync.js");
+globalThis.asyncResolved = await import.meta.resolve("async:./async.js");
+try {
+  await import.meta.resolve("async:bare");
+} catch (e) {
+  globalThis.asyncRejected = e instanceof TypeError;
+}
+"#
+  );
+
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(Rc::new(AsyncResolveLoader)),
+    ..Default::default()
+  });
+  let spec = resolve_url("file:///dir/main.js").unwrap();
+  let main_id = runtime
+    .load_main_module(&spec, Some(MAIN_SRC))
+    .await
+    .unwrap();
+  let receiver = runtime.mod_evaluate(main_id);
+  runtime.run_event_loop(false).await.unwrap();
+  receiver.await.unwrap().unwrap();
+
+  let value = runtime
+    .execute_script_static(
+      "check.js",
+      "[globalThis.syncResolved, globalThis.asyncResolved, globalThis.asyncRejected].join()",
+    )
+    .unwrap();
+  let scope = &mut runtime.handle_scope();
+  let value = v8::Local::new(scope, value);
+  assert_eq!(
+    value.to_rust_string_lossy(scope),
+    "file:///dir/sync.js,file:///dir/async.js,true"
+  );
+}
+
+#[tokio::test]
+async fn loader_source_map_applied_to_stack() {
+  struct SourceMapLoader;
+
+  impl ModuleLoader for SourceMapLoader {
+    fn resolve(
+      &self,
+      specifier: &str,
+      referrer: &str,
+      _kind: ResolutionKind,
+    ) -> Result<ModuleSpecifier, Error> {
+      Ok(resolve_import(specifier, referrer)?)
+    }
+
+    fn load(
+      &self,
+      module_specifier: &ModuleSpecifier,
+      _maybe_referrer: Option<&ModuleSpecifier>,
+      _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
+    ) -> Pin<Box<ModuleSourceFuture>> {
+      // Maps the first line of the generated code to line 10 of
+      // `original.ts`.
+      const SOURCE_MAP: &str = r#"{"version":3,"sources":["file:///original.ts"],"names":[],"mappings":"AASA"}"#;
+      let source = ModuleSource::new(
+        ModuleType::JavaScript,
+        ascii_str!("throw new Error('boom');"),
+        module_specifier,
+      )
+      .with_source_map(SOURCE_MAP.as_bytes().to_vec());
+      async move { Ok(source) }.boxed_local()
+    }
+  }
+
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(Rc::new(SourceMapLoader)),
+    ..Default::default()
+  });
+  let spec = resolve_url("file:///main.js").unwrap();
+  let main_id = runtime.load_main_module(&spec, None).await.unwrap();
+  let receiver = runtime.mod_evaluate(main_id);
+  runtime.run_event_loop(false).await.unwrap();
+  let err = receiver.await.unwrap().unwrap_err();
+
+  let js_error = err.downcast::<crate::error::JsError>().unwrap();
+  let frame = &js_error.frames[0];
+  assert_eq!(frame.file_name.as_deref(), Some("file:///original.ts"));
+  assert_eq!(frame.line_number, Some(10));
+  assert_eq!(frame.column_number, Some(1));
+}
+
+#[test]
+fn prefetch_module_graph() {
+  let loader = MockLoader::new();
+  let loads = loader.loads.clone();
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(loader),
+    ..Default::default()
+  });
+  let spec = resolve_url("file:///a.js").unwrap();
+  let a_id =
+    futures::executor::block_on(runtime.prefetch_module(&spec)).unwrap();
+  assert_eq!(
+    loads.lock().to_vec(),
+    vec![
+      "file:///a.js",
+      "file:///b.js",
+      "file:///c.js",
+      "file:///d.js"
+    ]
+  );
+
+  {
+    let module_map_rc = runtime.module_map().clone();
+    let module_map = module_map_rc.borrow();
+    assert!(!module_map.get_info_by_id(a_id).unwrap().main);
+    let scope = &mut runtime.handle_scope();
+    for handle in &module_map.handles {
+      let module = v8::Local::new(scope, handle);
+      assert_eq!(module.get_status(), v8::ModuleStatus::Uninstantiated);
+    }
+  }
+
+  // Loading the prefetched module as the main module reuses the compiled
+  // graph and marks it as main.
+  let main_id =
+    futures::executor::block_on(runtime.load_main_module(&spec, None)).unwrap();
+  assert_eq!(main_id, a_id);
+  assert_eq!(loads.lock().len(), 4);
+  assert!(
+    runtime
+      .module_map()
+      .borrow()
+      .get_info_by_id(a_id)
+      .unwrap()
+      .main
+  );
+
+  let receiver = runtime.mod_evaluate(main_id);
+  futures::executor::block_on(runtime.run_event_loop(false)).unwrap();
+  futures::executor::block_on(receiver).unwrap().unwrap();
+}
+
+#[test]
+fn archive_module_loader() {
+  let main = resolve_url("file:///main.js").unwrap();
+  let dep = resolve_url("file:///dep.js").unwrap();
+  let moved = resolve_url("file:///moved.js").unwrap();
+  let data = resolve_url("file:///data.json").unwrap();
+
+  let mut writer = ModuleArchiveWriter::new();
+  writer.add_module(
+    &main,
+    ModuleType::JavaScript,
+    r#"
+import { value } from "./moved.js";
+import data from "./data.json" assert { type: "json" };
+if (value !== 42 || data.ok !== true) throw Error();
+"#,
+    None,
+  );
+  writer.add_module(
+    &dep,
+    ModuleType::JavaScript,
+    "export const value = 42;",
+    Some(vec![1, 2, 3]),
+  );
+  writer.add_redirect(&moved, &dep);
+  writer.add_module(&data, ModuleType::Json, r#"{ "ok": true }"#, None);
+  let archive = writer.finish();
+
+  let loader = ArchiveModuleLoader::new(&archive).unwrap();
+  assert_eq!(loader.code_cache(&moved), Some(&[1, 2, 3][..]));
+  assert_eq!(loader.code_cache(&main), None);
+
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(Rc::new(loader)),
+    ..Default::default()
+  });
+  let main_id =
+    futures::executor::block_on(runtime.load_main_module(&main, None)).unwrap();
+  let receiver = runtime.mod_evaluate(main_id);
+  futures::executor::block_on(runtime.run_event_loop(false)).unwrap();
+  futures::executor::block_on(receiver).unwrap().unwrap();
+  assert!(runtime
+    .module_map()
+    .borrow()
+    .is_alias("file:///moved.js", AssertedModuleType::JavaScriptOrWasm));
+
+  let missing = resolve_url("file:///missing.js").unwrap();
+  let err =
+    futures::executor::block_on(runtime.load_side_module(&missing, None))
+      .unwrap_err();
+  assert_eq!(
+    err.to_string(),
+    "Module not found in archive: \"file:///missing.js\""
+  );
+
+  assert!(ArchiveModuleLoader::new(b"not an archive").is_err());
+  let mut truncated = archive;
+  truncated.pop();
+  assert!(ArchiveModuleLoader::new(&truncated).is_err());
+}
+
+#[test]
+fn write_module_archive_from_module_map() {
+  let loader = MockLoader::new();
+  let loads = loader.loads.clone();
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(loader),
+    retain_module_sources: true,
+    ..Default::default()
+  });
+  let spec = resolve_url("file:///a.js").unwrap();
+  futures::executor::block_on(runtime.load_main_module(&spec, None)).unwrap();
+  let load_count = loads.lock().len();
+  let mut writer = ModuleArchiveWriter::new();
+  runtime.write_module_archive(&mut writer).unwrap();
+  let archive = writer.finish();
+  // The retained sources are written, the loader isn't asked for them again.
+  assert_eq!(loads.lock().len(), load_count);
+
+  // The archive is enough to run the same graph without the original loader,
+  // and V8 accepts the code cache stored in it.
+  let loader = ArchiveModuleLoader::new(&archive).unwrap();
+  let archived_code_cache = loader.code_cache(&spec).unwrap().to_vec();
+  let code_cache = ModuleCodeCache::new();
+  loader.populate_code_cache(&code_cache);
+  assert_eq!(code_cache.len(), 4);
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(Rc::new(loader)),
+    module_code_cache: Some(code_cache.clone()),
+    ..Default::default()
+  });
+  let a_id =
+    futures::executor::block_on(runtime.load_main_module(&spec, None)).unwrap();
+  let receiver = runtime.mod_evaluate(a_id);
+  futures::executor::block_on(runtime.run_event_loop(false)).unwrap();
+  futures::executor::block_on(receiver).unwrap().unwrap();
+  assert_eq!(runtime.module_map().borrow().info.len(), 4);
+  let a_source = mock_source_code(spec.as_str()).unwrap().0;
+  let entry = code_cache
+    .get(
+      spec.as_str(),
+      ModuleCodeCache::hash_source(a_source.as_bytes()),
+    )
+    .unwrap();
+  assert!(entry.verified);
+  assert_eq!(&*entry.data, &archived_code_cache[..]);
+
+  // Without retained sources there's nothing to write.
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(MockLoader::new()),
+    ..Default::default()
+  });
+  futures::executor::block_on(runtime.load_main_module(&spec, None)).unwrap();
+  let err = runtime
+    .write_module_archive(&mut ModuleArchiveWriter::new())
+    .unwrap_err();
+  assert!(err.to_string().contains("retain_module_sources"), "{err}");
+}
+
+#[test]
+fn replace_main_module() {
+  let loader = MockLoader::new();
+  let loads = loader.loads.clone();
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(loader),
+    ..Default::default()
+  });
+  let spec = resolve_url("file:///main_with_code.js").unwrap();
+
+  let run = |runtime: &mut JsRuntime, id: ModuleId| {
+    let receiver = runtime.mod_evaluate(id);
+    futures::executor::block_on(runtime.run_event_loop(false)).unwrap();
+    futures::executor::block_on(receiver).unwrap().unwrap();
+  };
+
+  let first_id = futures::executor::block_on(runtime.load_main_module(
+    &spec,
+    Some(ascii_str!(
+      "import { b } from '/b.js'; globalThis.count = 1; b();"
+    )),
+  ))
+  .unwrap();
+  run(&mut runtime, first_id);
+
+  // Another main module can't be loaded alongside the current one.
+  let other = resolve_url("file:///other_main.js").unwrap();
+  assert!(futures::executor::block_on(
+    runtime.load_main_module(&other, Some(ascii_str!("")))
+  )
+  .is_err());
+
+  let second_id = futures::executor::block_on(runtime.replace_main_module(
+    &spec,
+    Some(ascii_str!(
+      "import { b } from '/b.js'; if (!import.meta.main) throw Error(); globalThis.count += 1; b();"
+    )),
+  ))
+  .unwrap();
+  assert_ne!(first_id, second_id);
+  run(&mut runtime, second_id);
+
+  {
+    let module_map_rc = runtime.module_map().clone();
+    let module_map = module_map_rc.borrow();
+    assert!(!module_map.get_info_by_id(first_id).unwrap().main);
+    assert!(module_map.get_info_by_id(second_id).unwrap().main);
+    assert_eq!(
+      module_map.get_id(
+        "file:///main_with_code.js",
+        AssertedModuleType::JavaScriptOrWasm
+      ),
+      Some(second_id)
+    );
+  }
+  // The replaced module is no longer a dependent.
+  assert_eq!(
+    runtime.get_module_dependents(&resolve_url("file:///b.js").unwrap()),
+    vec![spec.clone()]
+  );
+  // Dependencies were not loaded again.
+  assert_eq!(
+    loads.lock().to_vec(),
+    vec!["file:///b.js", "file:///c.js", "file:///d.js"]
+  );
+
+  runtime
+    .execute_script_static(
+      "check.js",
+      "if (globalThis.count !== 2) throw Error()",
+    )
+    .unwrap();
+}
+
+#[test]
+fn replace_main_module_failure() {
+  let loader = MockLoader::new();
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(loader),
+    ..Default::default()
+  });
+  let spec = resolve_url("file:///main_with_code.js").unwrap();
+  let b_spec = resolve_url("file:///b.js").unwrap();
+
+  let main_id = futures::executor::block_on(runtime.load_main_module(
+    &spec,
+    Some(ascii_str!("import { b } from '/b.js'; b();")),
+  ))
+  .unwrap();
+
+  // The replacement is registered, but one of its imports fails to load.
+  let err = futures::executor::block_on(runtime.replace_main_module(
+    &spec,
+    Some(ascii_str!(
+      "import { b } from '/b.js'; import '/missing.js';"
+    )),
+  ))
+  .unwrap_err();
+  assert!(matches!(
+    err.downcast_ref::<MockError>(),
+    Some(MockError::LoadErr)
+  ));
+
+  {
+    let module_map_rc = runtime.module_map().clone();
+    let module_map = module_map_rc.borrow();
+    assert!(module_map.get_info_by_id(main_id).unwrap().main);
+    assert_eq!(
+      module_map
+        .info
+        .iter()
+        .filter(|info| info.main)
+        .map(|info| info.id)
+        .collect::<Vec<_>>(),
+      vec![main_id]
+    );
+    assert_eq!(
+      module_map.get_id(
+        "file:///main_with_code.js",
+        AssertedModuleType::JavaScriptOrWasm
+      ),
+      Some(main_id)
+    );
+  }
+  assert_eq!(runtime.get_module_dependents(&b_spec), vec![spec.clone()]);
+
+  // The current main module can still be evaluated.
+  let receiver = runtime.mod_evaluate(main_id);
+  futures::executor::block_on(runtime.run_event_loop(false)).unwrap();
+  futures::executor::block_on(receiver).unwrap().unwrap();
+}
+
+#[test]
+fn module_dependents() {
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(MockLoader::new()),
+    ..Default::default()
+  });
+  let spec = resolve_url("file:///a.js").unwrap();
+  futures::executor::block_on(runtime.load_main_module(&spec, None)).unwrap();
+  let spec = resolve_url("file:///redirect1.js").unwrap();
+  futures::executor::block_on(runtime.load_side_module(&spec, None)).unwrap();
+
+  let dependents = |runtime: &JsRuntime, specifier: &str| {
+    let mut dependents = runtime
+      .get_module_dependents(&resolve_url(specifier).unwrap())
+      .into_iter()
+      .map(|specifier| specifier.to_string())
+      .collect::<Vec<_>>();
+    dependents.sort();
+    dependents
+  };
+
+  assert_eq!(dependents(&runtime, "file:///a.js"), Vec::<String>::new());
+  assert_eq!(
+    dependents(&runtime, "file:///c.js"),
+    vec!["file:///a.js", "file:///b.js"]
+  );
+  assert_eq!(dependents(&runtime, "file:///d.js"), vec!["file:///c.js"]);
+  // Imported through redirects.
+  assert_eq!(
+    dependents(&runtime, "file:///dir/redirect2.js"),
+    vec!["file:///redirect1.js"]
+  );
+  assert_eq!(
+    dependents(&runtime, "file:///redirect3.js"),
+    vec!["file:///dir/redirect2.js"]
+  );
+  // Unknown modules have no dependents.
+  assert_eq!(
+    dependents(&runtime, "file:///unknown.js"),
+    Vec::<String>::new()
+  );
+}
+
+#[test]
+fn resolved_specifier_and_aliases() {
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(MockLoader::new()),
+    ..Default::default()
+  });
+  let spec = resolve_url("file:///redirect1.js").unwrap();
+  futures::executor::block_on(runtime.load_main_module(&spec, None)).unwrap();
+
+  assert_eq!(
+    runtime.resolved_specifier(&spec),
+    Some(FinalSpecifier {
+      specifier: spec.clone(),
+      redirects: vec![],
+    })
+  );
+  let redirect2 = resolve_url("file:///redirect2.js").unwrap();
+  let dir_redirect2 = resolve_url("file:///dir/redirect2.js").unwrap();
+  assert_eq!(
+    runtime.resolved_specifier(&redirect2),
+    Some(FinalSpecifier {
+      specifier: dir_redirect2.clone(),
+      redirects: vec![dir_redirect2.clone()],
+    })
+  );
+  assert_eq!(
+    runtime.resolved_specifier(&resolve_url("file:///unknown.js").unwrap()),
+    None
+  );
+
+  let mut aliases = runtime
+    .module_aliases()
+    .map(|(from, to)| (from.to_string(), to.to_string()))
+    .collect::<Vec<_>>();
+  aliases.sort();
+  assert_eq!(
+    aliases,
+    vec![
+      (
+        "file:///dir/redirect3.js".to_string(),
+        "file:///redirect3.js".to_string()
+      ),
+      (
+        "file:///redirect2.js".to_string(),
+        "file:///dir/redirect2.js".to_string()
+      ),
+    ]
+  );
+}
+
+#[tokio::test]
+async fn import_assertions_passed_to_loader() {
+  #[derive(Default)]
+  struct AssertionsLoader {
+    loads: RefCell<Vec<(String, HashMap<String, String>)>>,
+  }
+
+  impl ModuleLoader for AssertionsLoader {
+    fn resolve(
+      &self,
+      specifier: &str,
+      referrer: &str,
+      _kind: ResolutionKind,
+    ) -> Result<ModuleSpecifier, Error> {
+      Ok(resolve_import(specifier, referrer)?)
+    }
+
+    fn load(
+      &self,
+      module_specifier: &ModuleSpecifier,
+      _maybe_referrer: Option<&ModuleSpecifier>,
+      _is_dyn_import: bool,
+      import_assertions: &HashMap<String, String>,
+    ) -> Pin<Box<ModuleSourceFuture>> {
+      self
+        .loads
+        .borrow_mut()
+        .push((module_specifier.to_string(), import_assertions.clone()));
+      let (module_type, code) = match module_specifier.path() {
+        "/main.js" => (
+          ModuleType::JavaScript,
+          ascii_str!(
+            r#"
+import "./dep.js";
+import data from "./static.json" assert { type: "json" };
+const { default: dyn } = await import("./dynamic.json", {
+  assert: { type: "json" },
+});
+if (data.a !== 1 || dyn.b !== 2) throw Error();
+"#
+          ),
+        ),
+        "/dep.js" => (ModuleType::JavaScript, ascii_str!("")),
+        "/static.json" => (ModuleType::Json, ascii_str!(r#"{ "a": 1 }"#)),
+        "/dynamic.json" => (ModuleType::Json, ascii_str!(r#"{ "b": 2 }"#)),
+        _ => unreachable!(),
+      };
+      let source = ModuleSource::new(module_type, code, module_specifier);
+      async move { Ok(source) }.boxed_local()
+    }
+  }
+
+  let loader = Rc::new(AssertionsLoader::default());
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(loader.clone()),
+    ..Default::default()
+  });
+  let spec = resolve_url("file:///main.js").unwrap();
+  let main_id = runtime.load_main_module(&spec, None).await.unwrap();
+  let receiver = runtime.mod_evaluate(main_id);
+  runtime.run_event_loop(false).await.unwrap();
+  receiver.await.unwrap().unwrap();
+
+  let json = HashMap::from([("type".to_string(), "json".to_string())]);
+  let mut loads = loader.loads.borrow().clone();
+  loads.sort_by(|a, b| a.0.cmp(&b.0));
+  assert_eq!(
+    loads,
+    vec![
+      ("file:///dep.js".to_string(), HashMap::new()),
+      ("file:///dynamic.json".to_string(), json.clone()),
+      ("file:///main.js".to_string(), HashMap::new()),
+      ("file:///static.json".to_string(), json),
+    ]
+  );
+}
+
+#[test]
+fn module_code_cache_shared_between_runtimes() {
+  const CODE: &str = "export const value = 40 + 2;";
+  let code_cache = ModuleCodeCache::new();
+  let spec = resolve_url("file:///cached.js").unwrap();
+
+  let load = |code: &'static str| {
+    let mut runtime = JsRuntime::new(RuntimeOptions {
+      module_code_cache: Some(code_cache.clone()),
+      ..Default::default()
+    });
+    let id = futures::executor::block_on(
+      runtime.load_side_module(&spec, Some(ModuleCode::from_static(code))),
+    )
+    .unwrap();
+    let receiver = runtime.mod_evaluate(id);
+    futures::executor::block_on(runtime.run_event_loop(false)).unwrap();
+    futures::executor::block_on(receiver).unwrap().unwrap();
+  };
+
+  assert!(code_cache.is_empty());
+  load(CODE);
+  assert_eq!(code_cache.len(), 1);
+  // The second runtime consumes the cached data instead of adding more.
+  load(CODE);
+  assert_eq!(code_cache.len(), 1);
+  // A different source for the same specifier is cached separately.
+  load("export const value = 42;");
+  assert_eq!(code_cache.len(), 2);
+
+  code_cache.clear();
+  assert!(code_cache.is_empty());
+}
+
+#[test]
+fn module_code_cache_rejected() {
+  const CODE: &str = "globalThis.value = 40 + 2;";
+  let code_cache = ModuleCodeCache::new();
+  let spec = resolve_url("file:///cached.js").unwrap();
+  let source_hash = ModuleCodeCache::hash_source(CODE.as_bytes());
+
+  let load = || {
+    let mut runtime = JsRuntime::new(RuntimeOptions {
+      module_code_cache: Some(code_cache.clone()),
+      ..Default::default()
+    });
+    let id = futures::executor::block_on(
+      runtime.load_side_module(&spec, Some(ModuleCode::from_static(CODE))),
+    )
+    .unwrap();
+    let receiver = runtime.mod_evaluate(id);
+    futures::executor::block_on(runtime.run_event_loop(false)).unwrap();
+    futures::executor::block_on(receiver).unwrap().unwrap();
+    runtime
+      .execute_script_static(
+        "check.js",
+        "if (globalThis.value !== 42) throw Error('x')",
+      )
+      .unwrap();
+  };
+
+  load();
+  let valid = code_cache.get(spec.as_str(), source_hash).unwrap();
+  assert!(valid.verified);
+
+  // Data that V8 accepts is kept as is.
+  code_cache.insert(spec.as_str(), source_hash, &valid.data, false);
+  load();
+  let entry = code_cache.get(spec.as_str(), source_hash).unwrap();
+  assert!(entry.verified);
+  assert_eq!(entry.data, valid.data);
+
+  // Rejected data is replaced by the code cache of the module compiled from
+  // source.
+  let mut version_mismatch = valid.data.to_vec();
+  version_mismatch[4] ^= 0xff;
+  let truncated = valid.data[..valid.data.len() / 2].to_vec();
+  for data in [b"not a code cache".to_vec(), version_mismatch, truncated] {
+    code_cache.insert(spec.as_str(), source_hash, &data, false);
+    load();
+    let entry = code_cache.get(spec.as_str(), source_hash).unwrap();
+    assert!(entry.verified);
+    assert_ne!(&*entry.data, &data[..]);
+  }
+}
+
+#[tokio::test]
+async fn import_assertions_integrity() {
+  struct IntegrityLoader;
+
+  impl ModuleLoader for IntegrityLoader {
+    fn resolve(
+      &self,
+      specifier: &str,
+      referrer: &str,
+      _kind: ResolutionKind,
+    ) -> Result<ModuleSpecifier, Error> {
+      Ok(resolve_import(specifier, referrer)?)
+    }
+
+    fn load(
+      &self,
+      module_specifier: &ModuleSpecifier,
+      _maybe_referrer: Option<&ModuleSpecifier>,
+      _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
+    ) -> Pin<Box<ModuleSourceFuture>> {
+      let code = match module_specifier.path() {
+        // Both imports expect the sha256 hash of "export default 1;".
+        "/main.js" => ascii_str!(
+          r#"
+const integrity = "sha256-VjMuClVzS8K3PfVqLfhjXtXFskttekVrQd58q5ovOBQ=";
+const { default: a } = await import("./a.js", { assert: { integrity } });
+if (a !== 1) throw Error("unexpected a.js");
+globalThis.error = await import("./b.js", { assert: { integrity } }).then(
+  () => null,
+  (error) => error.message,
+);
+"#
+        ),
+        "/a.js" => ascii_str!("export default 1;"),
+        "/b.js" => ascii_str!("export default 2;"),
+        _ => unreachable!(),
+      };
+      let source =
+        ModuleSource::new(ModuleType::JavaScript, code, module_specifier);
+      async move { Ok(source) }.boxed_local()
+    }
+  }
+
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(Rc::new(IntegrityLoader)),
+    ..Default::default()
+  });
+  let spec = resolve_url("file:///main.js").unwrap();
+  let main_id = runtime.load_main_module(&spec, None).await.unwrap();
+  let receiver = runtime.mod_evaluate(main_id);
+  runtime.run_event_loop(false).await.unwrap();
+  receiver.await.unwrap().unwrap();
+
+  let error = runtime
+    .execute_script_static("check.js", "globalThis.error")
+    .unwrap();
+  let error = {
+    let scope = &mut runtime.handle_scope();
+    v8::Local::new(scope, error).to_rust_string_lossy(scope)
+  };
+  assert!(
+    error.starts_with("Integrity check failed for \"file:///b.js\"."),
+    "{error}"
+  );
+  assert!(
+    error
+      .contains("Actual: sha256-qQO6IA4dQ+/ZpJ9Kj1fGIu+yynK5oAIi2s0W1rpvO6A="),
+    "{error}"
+  );
+
+  // The mismatched module was never registered, let alone instantiated.
+  let module_map_rc = runtime.module_map();
+  let modules = module_map_rc.borrow();
+  assert!(modules
+    .get_id("file:///a.js", AssertedModuleType::JavaScriptOrWasm)
+    .is_some());
+  assert!(modules
+    .get_id("file:///b.js", AssertedModuleType::JavaScriptOrWasm)
+    .is_none());
+}
@@ -1,11 +1,19 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::clock::Clock;
+use crate::clock::SystemClock;
+use crate::error::op_canceled;
+use crate::error::op_timed_out;
 use crate::error::AnyError;
+use crate::error::ErrorClassRegistry;
+use crate::error::ErrorProperties;
 use crate::error::GetErrorClassFn;
 use crate::gotham_state::GothamState;
 use crate::resources::ResourceTable;
 use crate::runtime::ContextState;
 use crate::runtime::JsRuntimeState;
+use crate::CancelFuture;
+use crate::CancelHandle;
 use crate::OpDecl;
 use crate::OpsTracker;
 use anyhow::Error;
@@ -16,6 +24,7 @@ use futures::FutureExt;
 use pin_project::pin_project;
 use serde::Serialize;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ops::Deref;
 use std::ops::DerefMut;
 use std::pin::Pin;
@@ -23,6 +32,8 @@ use std::ptr::NonNull;
 use std::rc::Rc;
 use std::rc::Weak;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 use v8::fast_api::CFunctionInfo;
 use v8::fast_api::CTypeInfo;
 
@@ -33,6 +44,8 @@ pub type OpId = u16;
 pub struct OpCall {
   promise_id: PromiseId,
   op_id: OpId,
+  /// When the op was dispatched, if op latency is being tracked.
+  started: Option<Instant>,
   /// Future is not necessarily Unpin, so we need to pin_project.
   #[pin]
   fut: MaybeDone<Pin<Box<dyn Future<Output = OpResult>>>>,
@@ -43,28 +56,38 @@ impl OpCall {
   pub fn pending(
     op_ctx: &OpCtx,
     promise_id: PromiseId,
+    started: Option<Instant>,
     fut: Pin<Box<dyn Future<Output = OpResult> + 'static>>,
   ) -> Self {
     Self {
       op_id: op_ctx.id,
       promise_id,
+      started,
       fut: MaybeDone::Future(fut),
     }
   }
 
   /// Create a future by specifying its output. This is basically the same as
   /// `async { value }` or `futures::future::ready(value)`.
-  pub fn ready(op_ctx: &OpCtx, promise_id: PromiseId, value: OpResult) -> Self {
+  pub fn ready(
+    op_ctx: &OpCtx,
+    promise_id: PromiseId,
+    started: Option<Instant>,
+    value: OpResult,
+  ) -> Self {
     Self {
       op_id: op_ctx.id,
       promise_id,
+      started,
       fut: MaybeDone::Done(value),
     }
   }
 }
 
 impl Future for OpCall {
-  type Output = (PromiseId, OpId, OpResult);
+  /// The op's result, along with the time since it was dispatched if op
+  /// latency is being tracked.
+  type Output = (PromiseId, OpId, OpResult, Option<Duration>);
 
   fn poll(
     self: std::pin::Pin<&mut Self>,
@@ -72,13 +95,13 @@ impl Future for OpCall {
   ) -> std::task::Poll<Self::Output> {
     let promise_id = self.promise_id;
     let op_id = self.op_id;
+    let started = self.started;
     let fut = &mut *self.project().fut;
     match fut {
       MaybeDone::Done(_) => {
         // Let's avoid using take_output as it keeps our Pin::box
         let res = std::mem::replace(fut, MaybeDone::Gone);
-        let MaybeDone::Done(res) = res
-        else {
+        let MaybeDone::Done(res) = res else {
           unreachable!()
         };
         std::task::Poll::Ready(res)
@@ -86,7 +109,14 @@ impl Future for OpCall {
       MaybeDone::Future(f) => f.poll_unpin(cx),
       MaybeDone::Gone => std::task::Poll::Pending,
     }
-    .map(move |res| (promise_id, op_id, res))
+    .map(move |res| {
+      (
+        promise_id,
+        op_id,
+        res,
+        started.map(|started| started.elapsed()),
+      )
+    })
   }
 }
 
@@ -102,7 +132,10 @@ impl OpResult {
   ) -> Result<v8::Local<'a, v8::Value>, serde_v8::Error> {
     match self {
       Self::Ok(x) => x.to_v8(scope),
-      Self::Err(err) => serde_v8::to_v8(scope, err),
+      Self::Err(err) => {
+        err.apply_registry(scope);
+        serde_v8::to_v8(scope, err)
+      }
     }
   }
 }
@@ -114,6 +147,12 @@ pub struct OpError {
   class_name: &'static str,
   message: String,
   code: Option<&'static str>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  properties: Option<ErrorProperties>,
+  // Kept until the error is sent to JS, to look it up in the runtime's
+  // `ErrorClassRegistry`.
+  #[serde(skip)]
+  source: Option<Error>,
 }
 
 impl OpError {
@@ -122,6 +161,23 @@ impl OpError {
       class_name: (get_class)(&err),
       message: format!("{err:#}"),
       code: crate::error_codes::get_error_code(&err),
+      properties: None,
+      source: Some(err),
+    }
+  }
+
+  fn apply_registry(&mut self, scope: &mut v8::HandleScope) {
+    let Some(source) = self.source.take() else {
+      return;
+    };
+    let Some(registry) = ErrorClassRegistry::from_scope(scope) else {
+      return;
+    };
+    if let Some((class_name, properties)) = registry.lookup(&source) {
+      self.class_name = class_name;
+      if !properties.is_empty() {
+        self.properties = Some(properties);
+      }
     }
   }
 }
@@ -187,6 +243,10 @@ pub struct OpState {
   pub last_fast_op_error: Option<AnyError>,
   pub(crate) gotham_state: GothamState,
   pub waker: Arc<AtomicWaker>,
+  pub clock: Rc<dyn Clock>,
+  op_deadline: Option<Duration>,
+  op_deadline_overrides: HashMap<&'static str, Option<Duration>>,
+  op_cancel_handle: Option<Rc<CancelHandle>>,
 }
 
 impl OpState {
@@ -198,11 +258,91 @@ impl OpState {
       last_fast_op_error: None,
       tracker: OpsTracker::new(ops_count),
       waker: Arc::new(AtomicWaker::new()),
+      clock: Rc::new(SystemClock::new()),
+      op_deadline: None,
+      op_deadline_overrides: HashMap::new(),
+      op_cancel_handle: None,
+    }
+  }
+
+  /// Limit how long every async op dispatched from now on may run. An op
+  /// that doesn't complete in time is dropped, releasing any resources it
+  /// borrowed from the resource table, and its promise rejects with
+  /// [`op_timed_out`](crate::error::op_timed_out).
+  ///
+  /// Deadlines are enforced with Tokio timers, so ops must be dispatched
+  /// from within a Tokio runtime.
+  pub fn set_op_deadline(&mut self, deadline: Option<Duration>) {
+    self.op_deadline = deadline;
+  }
+
+  /// Override the deadline set with [`OpState::set_op_deadline`] for a single
+  /// op. `None` exempts the op from any deadline.
+  pub fn set_op_deadline_for(
+    &mut self,
+    op_name: &'static str,
+    deadline: Option<Duration>,
+  ) {
+    self.op_deadline_overrides.insert(op_name, deadline);
+  }
+
+  /// Returns the deadline that applies to `op_name`.
+  pub fn op_deadline(&self, op_name: &str) -> Option<Duration> {
+    match self.op_deadline_overrides.get(op_name) {
+      Some(deadline) => *deadline,
+      None => self.op_deadline,
+    }
+  }
+
+  /// Bind every async op dispatched from now on to `handle`. Canceling the
+  /// handle, or closing it if it was added to the resource table, drops the
+  /// pending ops and rejects their promises with
+  /// [`op_canceled`](crate::error::op_canceled).
+  pub fn set_op_cancel_handle(&mut self, handle: Option<Rc<CancelHandle>>) {
+    self.op_cancel_handle = handle;
+  }
+
+  /// Wrap the future of an async op being dispatched so it honors the current
+  /// deadline and cancel handle.
+  pub(crate) fn apply_op_dispatch_policy(
+    &self,
+    op_name: &'static str,
+    fut: Pin<Box<dyn Future<Output = OpResult>>>,
+  ) -> Pin<Box<dyn Future<Output = OpResult>>> {
+    let get_class = self.get_error_class_fn;
+    let fut = match self.op_deadline(op_name) {
+      Some(deadline) => async move {
+        match tokio::time::timeout(deadline, fut).await {
+          Ok(result) => result,
+          Err(_) => OpResult::Err(OpError::new(
+            get_class,
+            op_timed_out(op_name, deadline),
+          )),
+        }
+      }
+      .boxed_local(),
+      None => fut,
+    };
+    match &self.op_cancel_handle {
+      Some(handle) => {
+        let handle = handle.clone();
+        async move {
+          match fut.or_cancel(handle).await {
+            Ok(result) => result,
+            Err(_) => {
+              OpResult::Err(OpError::new(get_class, op_canceled(op_name)))
+            }
+          }
+        }
+        .boxed_local()
+      }
+      None => fut,
     }
   }
 
   /// Clear all user-provided resources and state.
   pub(crate) fn clear(&mut self) {
+    self.resource_table.report_leaks();
     std::mem::take(&mut self.gotham_state);
     std::mem::take(&mut self.resource_table);
   }
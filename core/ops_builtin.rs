@@ -8,6 +8,7 @@ use crate::ops_metrics::OpMetrics;
 use crate::resources::ResourceId;
 use crate::OpState;
 use crate::Resource;
+use crate::WasmModuleCache;
 use crate::ZeroCopyBuf;
 use anyhow::Error;
 use deno_ops::op;
@@ -16,6 +17,7 @@ use std::io::stderr;
 use std::io::stdout;
 use std::io::Write;
 use std::rc::Rc;
+use std::time::UNIX_EPOCH;
 
 crate::extension!(
   core,
@@ -23,9 +25,11 @@ crate::extension!(
     op_close,
     op_try_close,
     op_print,
+    op_clock_now,
     op_resources,
     op_wasm_streaming_feed,
     op_wasm_streaming_set_url,
+    op_wasm_streaming_stream,
     op_void_sync,
     op_error_async,
     op_error_async_deferred,
@@ -40,6 +44,9 @@ crate::extension!(
     op_read_sync,
     op_write_sync,
     op_write_all,
+    op_readv,
+    op_writev,
+    op_writev_all,
     op_shutdown,
     op_metrics,
     op_format_file_name,
@@ -72,6 +79,7 @@ crate::extension!(
     ops_builtin_v8::op_apply_source_map,
     ops_builtin_v8::op_set_format_exception_callback,
     ops_builtin_v8::op_event_loop_has_more_work,
+    ops_builtin_v8::op_event_loop_stats,
     ops_builtin_v8::op_store_pending_promise_rejection,
     ops_builtin_v8::op_remove_pending_promise_rejection,
     ops_builtin_v8::op_has_pending_promise_rejection,
@@ -170,7 +178,56 @@ pub fn op_print(msg: &str, is_err: bool) -> Result<(), Error> {
   Ok(())
 }
 
-pub struct WasmStreamingResource(pub(crate) RefCell<v8::WasmStreaming>);
+/// Milliseconds since the Unix epoch according to [`OpState::clock`]. Backs
+/// `Date.now()` when the runtime was created with a custom clock.
+#[op(fast)]
+fn op_clock_now(state: &mut OpState) -> f64 {
+  match state.clock.system_time().duration_since(UNIX_EPOCH) {
+    Ok(since_epoch) => since_epoch.as_millis() as f64,
+    Err(before_epoch) => -(before_epoch.duration().as_millis() as f64),
+  }
+}
+
+pub struct WasmStreamingResource {
+  streaming: RefCell<v8::WasmStreaming>,
+  // The runtime's cache, along with the URL and the bytes received so far, to
+  // store the module once streaming finishes.
+  cache: Option<WasmModuleCache>,
+  url: RefCell<Option<String>>,
+  bytes: RefCell<Vec<u8>>,
+}
+
+impl WasmStreamingResource {
+  pub(crate) fn new(
+    streaming: v8::WasmStreaming,
+    cache: Option<WasmModuleCache>,
+  ) -> Self {
+    Self {
+      streaming: RefCell::new(streaming),
+      cache,
+      url: Default::default(),
+      bytes: Default::default(),
+    }
+  }
+
+  fn feed(&self, bytes: &[u8]) {
+    self.streaming.borrow_mut().on_bytes_received(bytes);
+    if self.cache.is_some() {
+      self.bytes.borrow_mut().extend_from_slice(bytes);
+    }
+  }
+
+  fn finish(self) {
+    if let (Some(cache), Some(url)) = (&self.cache, self.url.take()) {
+      cache.insert(url, self.bytes.take());
+    }
+    self.streaming.into_inner().finish();
+  }
+
+  pub(crate) fn abort(self, exception: Option<v8::Local<v8::Value>>) {
+    self.streaming.into_inner().abort(exception);
+  }
+}
 
 impl Resource for WasmStreamingResource {
   fn close(self: Rc<Self>) {
@@ -178,7 +235,7 @@ impl Resource for WasmStreamingResource {
     // resource table, and no one should own a reference outside of the stack.
     // Therefore, we can be sure `self` is the only reference.
     if let Ok(wsr) = Rc::try_unwrap(self) {
-      wsr.0.into_inner().finish();
+      wsr.finish();
     } else {
       panic!("Couldn't consume WasmStreamingResource.");
     }
@@ -195,23 +252,68 @@ pub fn op_wasm_streaming_feed(
   let wasm_streaming =
     state.resource_table.get::<WasmStreamingResource>(rid)?;
 
-  wasm_streaming.0.borrow_mut().on_bytes_received(bytes);
+  wasm_streaming.feed(bytes);
 
   Ok(())
 }
 
+/// Set the URL of the module being streamed. Returns true if the module was
+/// found in the runtime's `WasmModuleCache`, in which case the cached bytes
+/// were fed, streaming finished, and the resource was closed.
 #[op]
 pub fn op_wasm_streaming_set_url(
   state: &mut OpState,
   rid: ResourceId,
   url: &str,
-) -> Result<(), Error> {
+) -> Result<bool, Error> {
   let wasm_streaming =
     state.resource_table.get::<WasmStreamingResource>(rid)?;
 
-  wasm_streaming.0.borrow_mut().set_url(url);
+  wasm_streaming.streaming.borrow_mut().set_url(url);
+
+  let cached = wasm_streaming
+    .cache
+    .as_ref()
+    .and_then(|cache| cache.get(url));
+  if let Some(bytes) = cached {
+    drop(wasm_streaming);
+    let wasm_streaming =
+      state.resource_table.take::<WasmStreamingResource>(rid)?;
+    let wasm_streaming = match Rc::try_unwrap(wasm_streaming) {
+      Ok(wsr) => wsr,
+      Err(_) => panic!("Couldn't consume WasmStreamingResource."),
+    };
+    let mut streaming = wasm_streaming.streaming.into_inner();
+    streaming.on_bytes_received(&bytes);
+    streaming.finish();
+    return Ok(true);
+  }
 
-  Ok(())
+  wasm_streaming.url.replace(Some(url.to_string()));
+  Ok(false)
+}
+
+/// Feed the contents of the readable resource `stream_rid` to
+/// WasmStreamingResource as they are read, then finish streaming.
+#[op]
+async fn op_wasm_streaming_stream(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  stream_rid: ResourceId,
+) -> Result<(), Error> {
+  let stream = state.borrow().resource_table.get_any(stream_rid)?;
+  loop {
+    let chunk = stream.clone().read(64 * 1024).await?;
+    if chunk.is_empty() {
+      break;
+    }
+    let wasm_streaming = state
+      .borrow()
+      .resource_table
+      .get::<WasmStreamingResource>(rid)?;
+    wasm_streaming.feed(&chunk);
+  }
+  state.borrow_mut().resource_table.close(rid)
 }
 
 #[op]
@@ -339,6 +441,43 @@ async fn op_write_all(
   Ok(())
 }
 
+#[op]
+async fn op_readv(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  bufs: Vec<ZeroCopyBuf>,
+) -> Result<u32, Error> {
+  let resource = state.borrow().resource_table.get_any(rid)?;
+  let views = bufs.into_iter().map(BufMutView::from).collect();
+  resource
+    .read_byob_vectored(views)
+    .await
+    .map(|(n, _)| n as u32)
+}
+
+#[op]
+async fn op_writev(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  bufs: Vec<ZeroCopyBuf>,
+) -> Result<u32, Error> {
+  let resource = state.borrow().resource_table.get_any(rid)?;
+  let views = bufs.into_iter().map(BufView::from).collect();
+  let (nwritten, _) = resource.write_vectored(views).await?;
+  Ok(nwritten as u32)
+}
+
+#[op]
+async fn op_writev_all(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  bufs: Vec<ZeroCopyBuf>,
+) -> Result<(), Error> {
+  let resource = state.borrow().resource_table.get_any(rid)?;
+  let views = bufs.into_iter().map(BufView::from).collect();
+  resource.write_all_vectored(views).await
+}
+
 #[op]
 async fn op_shutdown(
   state: Rc<RefCell<OpState>>,
@@ -1,5 +1,4 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
-use crate::error::custom_error;
 use crate::error::is_instance_of_error;
 use crate::error::range_error;
 use crate::error::type_error;
@@ -9,17 +8,20 @@ use crate::resolve_url;
 use crate::runtime::script_origin;
 use crate::serde_v8::from_v8;
 use crate::source_map::apply_source_map;
+use crate::source_map::SourceMapper;
+use crate::structured_clone;
+use crate::structured_clone::structured_deserialize;
+use crate::structured_clone::StructuredCloneOptions;
+use crate::EventLoopStats;
 use crate::JsRealm;
 use crate::JsRuntime;
+use crate::OpState;
 use crate::ZeroCopyBuf;
 use anyhow::Error;
 use deno_ops::op;
 use serde::Deserialize;
 use serde::Serialize;
-use std::cell::RefCell;
 use std::rc::Rc;
-use v8::ValueDeserializerHelper;
-use v8::ValueSerializerHelper;
 
 fn to_v8_fn(
   scope: &mut v8::HandleScope,
@@ -208,158 +210,6 @@ fn op_decode<'a>(
   }
 }
 
-struct SerializeDeserialize<'a> {
-  host_objects: Option<v8::Local<'a, v8::Array>>,
-  error_callback: Option<v8::Local<'a, v8::Function>>,
-  for_storage: bool,
-}
-
-impl<'a> v8::ValueSerializerImpl for SerializeDeserialize<'a> {
-  #[allow(unused_variables)]
-  fn throw_data_clone_error<'s>(
-    &mut self,
-    scope: &mut v8::HandleScope<'s>,
-    message: v8::Local<'s, v8::String>,
-  ) {
-    if let Some(cb) = self.error_callback {
-      let scope = &mut v8::TryCatch::new(scope);
-      let undefined = v8::undefined(scope).into();
-      cb.call(scope, undefined, &[message.into()]);
-      if scope.has_caught() || scope.has_terminated() {
-        scope.rethrow();
-        return;
-      };
-    }
-    let error = v8::Exception::type_error(scope, message);
-    scope.throw_exception(error);
-  }
-
-  fn get_shared_array_buffer_id<'s>(
-    &mut self,
-    scope: &mut v8::HandleScope<'s>,
-    shared_array_buffer: v8::Local<'s, v8::SharedArrayBuffer>,
-  ) -> Option<u32> {
-    if self.for_storage {
-      return None;
-    }
-    let state_rc = JsRuntime::state_from(scope);
-    let state = state_rc.borrow_mut();
-    if let Some(shared_array_buffer_store) = &state.shared_array_buffer_store {
-      let backing_store = shared_array_buffer.get_backing_store();
-      let id = shared_array_buffer_store.insert(backing_store);
-      Some(id)
-    } else {
-      None
-    }
-  }
-
-  fn get_wasm_module_transfer_id(
-    &mut self,
-    scope: &mut v8::HandleScope<'_>,
-    module: v8::Local<v8::WasmModuleObject>,
-  ) -> Option<u32> {
-    if self.for_storage {
-      let message = v8::String::new(scope, "Wasm modules cannot be stored")?;
-      self.throw_data_clone_error(scope, message);
-      return None;
-    }
-    let state_rc = JsRuntime::state_from(scope);
-    let state = state_rc.borrow_mut();
-    if let Some(compiled_wasm_module_store) = &state.compiled_wasm_module_store
-    {
-      let compiled_wasm_module = module.get_compiled_module();
-      let id = compiled_wasm_module_store.insert(compiled_wasm_module);
-      Some(id)
-    } else {
-      None
-    }
-  }
-
-  fn write_host_object<'s>(
-    &mut self,
-    scope: &mut v8::HandleScope<'s>,
-    object: v8::Local<'s, v8::Object>,
-    value_serializer: &mut dyn v8::ValueSerializerHelper,
-  ) -> Option<bool> {
-    if let Some(host_objects) = self.host_objects {
-      for i in 0..host_objects.length() {
-        let value = host_objects.get_index(scope, i).unwrap();
-        if value == object {
-          value_serializer.write_uint32(i);
-          return Some(true);
-        }
-      }
-    }
-    let message = v8::String::new(scope, "Unsupported object type").unwrap();
-    self.throw_data_clone_error(scope, message);
-    None
-  }
-}
-
-impl<'a> v8::ValueDeserializerImpl for SerializeDeserialize<'a> {
-  fn get_shared_array_buffer_from_id<'s>(
-    &mut self,
-    scope: &mut v8::HandleScope<'s>,
-    transfer_id: u32,
-  ) -> Option<v8::Local<'s, v8::SharedArrayBuffer>> {
-    if self.for_storage {
-      return None;
-    }
-    let state_rc = JsRuntime::state_from(scope);
-    let state = state_rc.borrow_mut();
-    if let Some(shared_array_buffer_store) = &state.shared_array_buffer_store {
-      let backing_store = shared_array_buffer_store.take(transfer_id)?;
-      let shared_array_buffer =
-        v8::SharedArrayBuffer::with_backing_store(scope, &backing_store);
-      Some(shared_array_buffer)
-    } else {
-      None
-    }
-  }
-
-  fn get_wasm_module_from_id<'s>(
-    &mut self,
-    scope: &mut v8::HandleScope<'s>,
-    clone_id: u32,
-  ) -> Option<v8::Local<'s, v8::WasmModuleObject>> {
-    if self.for_storage {
-      return None;
-    }
-    let state_rc = JsRuntime::state_from(scope);
-    let state = state_rc.borrow_mut();
-    if let Some(compiled_wasm_module_store) = &state.compiled_wasm_module_store
-    {
-      let compiled_module = compiled_wasm_module_store.take(clone_id)?;
-      v8::WasmModuleObject::from_compiled_module(scope, &compiled_module)
-    } else {
-      None
-    }
-  }
-
-  fn read_host_object<'s>(
-    &mut self,
-    scope: &mut v8::HandleScope<'s>,
-    value_deserializer: &mut dyn v8::ValueDeserializerHelper,
-  ) -> Option<v8::Local<'s, v8::Object>> {
-    if let Some(host_objects) = self.host_objects {
-      let mut i = 0;
-      if !value_deserializer.read_uint32(&mut i) {
-        return None;
-      }
-      let maybe_value = host_objects.get_index(scope, i);
-      if let Some(value) = maybe_value {
-        return value.to_object(scope);
-      }
-    }
-
-    let message: v8::Local<v8::String> =
-      v8::String::new(scope, "Failed to deserialize host object").unwrap();
-    let error = v8::Exception::error(scope, message);
-    scope.throw_exception(error);
-    None
-  }
-}
-
 #[derive(Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SerializeDeserializeOptions<'a> {
@@ -399,61 +249,40 @@ fn op_serialize(
     None => None,
   };
 
-  let serialize_deserialize = Box::new(SerializeDeserialize {
-    host_objects,
-    error_callback,
-    for_storage: options.for_storage,
-  });
-  let mut value_serializer =
-    v8::ValueSerializer::new(scope, serialize_deserialize);
-  value_serializer.write_header();
-
+  let mut transfer = vec![];
   if let Some(transferred_array_buffers) = transferred_array_buffers {
-    let state_rc = JsRuntime::state_from(scope);
-    let state = state_rc.borrow_mut();
     for index in 0..transferred_array_buffers.length() {
-      let i = v8::Number::new(scope, index as f64).into();
-      let buf = transferred_array_buffers.get(scope, i).unwrap();
+      let buf = transferred_array_buffers.get_index(scope, index).unwrap();
       let buf = v8::Local::<v8::ArrayBuffer>::try_from(buf).map_err(|_| {
         type_error("item in transferredArrayBuffers not an ArrayBuffer")
       })?;
-      if let Some(shared_array_buffer_store) = &state.shared_array_buffer_store
-      {
-        if !buf.is_detachable() {
-          return Err(type_error(
-            "item in transferredArrayBuffers is not transferable",
-          ));
-        }
+      transfer.push(buf);
+    }
+  }
 
-        if buf.was_detached() {
-          return Err(custom_error(
-            "DOMExceptionOperationError",
-            format!("ArrayBuffer at index {index} is already detached"),
-          ));
+  let options = StructuredCloneOptions {
+    host_objects,
+    error_callback,
+    for_storage: options.for_storage,
+  };
+  match structured_clone::serialize(
+    scope,
+    value.v8_value,
+    &transfer,
+    options,
+    true,
+  )? {
+    Some(data) => {
+      if let Some(transferred_array_buffers) = transferred_array_buffers {
+        for (index, id) in data.transferred_array_buffers.iter().enumerate() {
+          let id = v8::Number::new(scope, *id as f64).into();
+          transferred_array_buffers.set_index(scope, index as u32, id);
         }
-
-        let backing_store = buf.get_backing_store();
-        buf.detach(None);
-        let id = shared_array_buffer_store.insert(backing_store);
-        value_serializer.transfer_array_buffer(id, buf);
-        let id = v8::Number::new(scope, id as f64).into();
-        transferred_array_buffers.set(scope, i, id);
       }
+      Ok(data.data.into())
     }
-  }
-
-  let scope = &mut v8::TryCatch::new(scope);
-  let ret =
-    value_serializer.write_value(scope.get_current_context(), value.v8_value);
-  if scope.has_caught() || scope.has_terminated() {
-    scope.rethrow();
     // Dummy value, this result will be discarded because an error was thrown.
-    Ok(ZeroCopyBuf::empty())
-  } else if let Some(true) = ret {
-    let vector = value_serializer.release();
-    Ok(vector.into())
-  } else {
-    Err(type_error("Failed to serialize response"))
+    None => Ok(ZeroCopyBuf::empty()),
   }
 }
 
@@ -479,54 +308,33 @@ fn op_deserialize<'a>(
     None => None,
   };
 
-  let serialize_deserialize = Box::new(SerializeDeserialize {
-    host_objects,
-    error_callback: None,
-    for_storage: options.for_storage,
-  });
-  let mut value_deserializer =
-    v8::ValueDeserializer::new(scope, serialize_deserialize, &zero_copy);
-  let parsed_header = value_deserializer
-    .read_header(scope.get_current_context())
-    .unwrap_or_default();
-  if !parsed_header {
-    return Err(range_error("could not deserialize value"));
-  }
-
+  let mut ids = vec![];
   if let Some(transferred_array_buffers) = transferred_array_buffers {
-    let state_rc = JsRuntime::state_from(scope);
-    let state = state_rc.borrow_mut();
-    if let Some(shared_array_buffer_store) = &state.shared_array_buffer_store {
-      for i in 0..transferred_array_buffers.length() {
-        let i = v8::Number::new(scope, i as f64).into();
-        let id_val = transferred_array_buffers.get(scope, i).unwrap();
-        let id = match id_val.number_value(scope) {
-          Some(id) => id as u32,
-          None => {
-            return Err(type_error(
-              "item in transferredArrayBuffers not number",
-            ))
-          }
-        };
-        if let Some(backing_store) = shared_array_buffer_store.take(id) {
-          let array_buffer =
-            v8::ArrayBuffer::with_backing_store(scope, &backing_store);
-          value_deserializer.transfer_array_buffer(id, array_buffer);
-          transferred_array_buffers.set(scope, id_val, array_buffer.into());
-        } else {
-          return Err(type_error(
-            "transferred array buffer not present in shared_array_buffer_store",
-          ));
+    for i in 0..transferred_array_buffers.length() {
+      let id_val = transferred_array_buffers.get_index(scope, i).unwrap();
+      match id_val.number_value(scope) {
+        Some(id) => ids.push(id as u32),
+        None => {
+          return Err(type_error("item in transferredArrayBuffers not number"))
         }
-      }
+      };
     }
   }
 
-  let value = value_deserializer.read_value(scope.get_current_context());
-  match value {
-    Some(deserialized) => Ok(deserialized.into()),
-    None => Err(range_error("could not deserialize value")),
+  let options = StructuredCloneOptions {
+    host_objects,
+    error_callback: None,
+    for_storage: options.for_storage,
+  };
+  let (value, array_buffers) =
+    structured_deserialize(scope, &zero_copy, &ids, options)?;
+  if let Some(transferred_array_buffers) = transferred_array_buffers {
+    for (id, array_buffer) in ids.iter().zip(array_buffers) {
+      let id = v8::Number::new(scope, *id as f64).into();
+      transferred_array_buffers.set(scope, id, array_buffer.into());
+    }
   }
+  Ok(value.into())
 }
 
 #[derive(Serialize)]
@@ -688,7 +496,11 @@ struct MemoryUsage {
 }
 
 #[op(v8)]
-fn op_memory_usage(scope: &mut v8::HandleScope) -> MemoryUsage {
+fn op_memory_usage(
+  scope: &mut v8::HandleScope,
+  state: &mut OpState,
+) -> MemoryUsage {
+  state.resource_table.report_external_memory(scope);
   let mut s = v8::HeapStatistics::default();
   scope.get_heap_statistics(&mut s);
   MemoryUsage {
@@ -725,12 +537,13 @@ fn op_set_wasm_streaming_callback(
         .unwrap()
         .clone();
       let state_rc = JsRuntime::state_from(scope);
-      let streaming_rid = state_rc
-        .borrow()
-        .op_state
-        .borrow_mut()
-        .resource_table
-        .add(WasmStreamingResource(RefCell::new(wasm_streaming)));
+      let state = state_rc.borrow();
+      let streaming_rid = state.op_state.borrow_mut().resource_table.add(
+        WasmStreamingResource::new(
+          wasm_streaming,
+          state.wasm_module_cache.clone(),
+        ),
+      );
       (cb_handle, streaming_rid)
     };
 
@@ -767,7 +580,7 @@ fn op_abort_wasm_streaming(
   if let Ok(wsr) = std::rc::Rc::try_unwrap(wasm_streaming) {
     // NOTE: v8::WasmStreaming::abort can't be called while `state` is borrowed;
     // see https://github.com/denoland/deno/issues/13917
-    wsr.0.into_inner().abort(Some(error.v8_value));
+    wsr.abort(Some(error.v8_value));
   } else {
     panic!("Couldn't consume WasmStreamingResource.");
   }
@@ -828,16 +641,7 @@ fn op_apply_source_map(
   scope: &mut v8::HandleScope,
   location: Location,
 ) -> Result<Location, Error> {
-  let state_rc = JsRuntime::state_from(scope);
-  let (getter, cache) = {
-    let state = state_rc.borrow();
-    (
-      state.source_map_getter.clone(),
-      state.source_map_cache.clone(),
-    )
-  };
-
-  if let Some(source_map_getter) = getter {
+  if let Some((source_mapper, cache)) = SourceMapper::from_scope(scope) {
     let mut cache = cache.borrow_mut();
     let mut location = location;
     let (f, l, c) = apply_source_map(
@@ -845,7 +649,7 @@ fn op_apply_source_map(
       location.line_number.into(),
       location.column_number.into(),
       &mut cache,
-      &**source_map_getter,
+      &source_mapper,
     );
     location.file_name = f;
     location.line_number = l as u32;
@@ -880,6 +684,13 @@ fn op_event_loop_has_more_work(scope: &mut v8::HandleScope) -> bool {
   JsRuntime::event_loop_pending_state_from_scope(scope).is_pending()
 }
 
+#[op(v8)]
+fn op_event_loop_stats(scope: &mut v8::HandleScope) -> EventLoopStats {
+  let state_rc = JsRuntime::state_from(scope);
+  let state = state_rc.borrow();
+  EventLoopStats::new(scope, &state)
+}
+
 #[op(v8)]
 fn op_store_pending_promise_rejection<'a>(
   scope: &mut v8::HandleScope<'a>,
@@ -4,6 +4,10 @@ use crate::serde::Serialize;
 use crate::OpId;
 use std::cell::RefCell;
 use std::cell::RefMut;
+use std::fmt;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::Instant;
 
 // TODO(@AaronO): split into AggregateMetrics & PerOpMetrics
 #[derive(Clone, Default, Debug, Serialize)]
@@ -24,19 +28,173 @@ pub struct OpMetrics {
   pub bytes_received: u64,
 }
 
-// TODO(@AaronO): track errors
-#[derive(Default, Debug)]
+/// Upper bounds, in microseconds, of the buckets of an
+/// [`OpLatencyHistogram`]. Latencies above the last bound fall into an extra
+/// overflow bucket.
+pub const OP_LATENCY_BUCKETS_US: [u64; 10] = [
+  10, 50, 100, 500, 1_000, 5_000, 10_000, 50_000, 100_000, 1_000_000,
+];
+
+/// Distribution of the time async ops took from dispatch to completion.
+#[derive(Clone, Default, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpLatencyHistogram {
+  /// Counts per bucket of [`OP_LATENCY_BUCKETS_US`], plus the overflow bucket.
+  pub buckets: [u64; OP_LATENCY_BUCKETS_US.len() + 1],
+  pub count: u64,
+  pub sum_us: u64,
+  pub max_us: u64,
+}
+
+impl OpLatencyHistogram {
+  fn record(&mut self, latency: Duration) {
+    let us = latency.as_micros().try_into().unwrap_or(u64::MAX);
+    let bucket = OP_LATENCY_BUCKETS_US
+      .iter()
+      .position(|bound| us <= *bound)
+      .unwrap_or(OP_LATENCY_BUCKETS_US.len());
+    self.buckets[bucket] += 1;
+    self.count += 1;
+    self.sum_us = self.sum_us.saturating_add(us);
+    self.max_us = self.max_us.max(us);
+  }
+}
+
+/// Metrics recorded for a single op when detailed op metrics are enabled
+/// with [`crate::RuntimeOptions::op_metrics`].
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpDetailedMetrics {
+  pub name: &'static str,
+  pub dispatched: u64,
+  pub completed: u64,
+  /// Async ops that completed with an error. Errors thrown by sync ops are
+  /// not counted.
+  pub errors: u64,
+  /// Latency of async ops. Sync ops are not timed.
+  pub latency: OpLatencyHistogram,
+}
+
+/// An op event reported to an [`OpMetricsHook`].
+#[derive(Clone, Copy, Debug)]
+pub enum OpMetricsEvent {
+  /// A sync op ran to completion.
+  CompletedSync,
+  /// An async op was dispatched.
+  DispatchedAsync,
+  /// An async op completed successfully after the given time.
+  CompletedAsync(Duration),
+  /// An async op completed with an error after the given time.
+  ErrorAsync(Duration),
+}
+
+/// Receives every op event recorded by the [`OpsTracker`], so embedders can
+/// forward them to `tracing`, OpenTelemetry or a similar system.
+///
+/// The hook runs synchronously on the op dispatch path and must not call
+/// back into the runtime.
+pub trait OpMetricsHook {
+  fn on_op_event(
+    &self,
+    op_id: OpId,
+    op_name: &'static str,
+    event: OpMetricsEvent,
+  );
+}
+
+struct DetailedOpsTracker {
+  ops: RefCell<Vec<OpDetailedMetrics>>,
+  hook: Option<Rc<dyn OpMetricsHook>>,
+}
+
+impl DetailedOpsTracker {
+  fn record(&self, id: OpId, event: OpMetricsEvent) {
+    let name = {
+      let mut ops = self.ops.borrow_mut();
+      let metrics = &mut ops[id as usize];
+      match event {
+        OpMetricsEvent::CompletedSync => {
+          metrics.dispatched += 1;
+          metrics.completed += 1;
+        }
+        OpMetricsEvent::DispatchedAsync => metrics.dispatched += 1,
+        OpMetricsEvent::CompletedAsync(latency) => {
+          metrics.completed += 1;
+          metrics.latency.record(latency);
+        }
+        OpMetricsEvent::ErrorAsync(latency) => {
+          metrics.completed += 1;
+          metrics.errors += 1;
+          metrics.latency.record(latency);
+        }
+      }
+      metrics.name
+    };
+    if let Some(hook) = &self.hook {
+      hook.on_op_event(id, name, event);
+    }
+  }
+}
+
+#[derive(Default)]
 pub struct OpsTracker {
   ops: RefCell<Vec<OpMetrics>>,
+  detailed: Option<DetailedOpsTracker>,
+}
+
+impl fmt::Debug for OpsTracker {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("OpsTracker")
+      .field("ops", &self.ops)
+      .field("detailed", &self.detailed.as_ref().map(|d| &d.ops))
+      .finish()
+  }
 }
 
 impl OpsTracker {
   pub fn new(ops_count: usize) -> Self {
     Self {
       ops: RefCell::new(vec![Default::default(); ops_count]),
+      detailed: None,
     }
   }
 
+  /// Start recording per-op error counts and latency histograms, and report
+  /// every op event to `hook`. `op_names` must be indexed by [`OpId`].
+  pub fn enable_detailed(
+    &mut self,
+    op_names: &[&'static str],
+    hook: Option<Rc<dyn OpMetricsHook>>,
+  ) {
+    let ops = op_names
+      .iter()
+      .copied()
+      .map(|name| OpDetailedMetrics {
+        name,
+        dispatched: 0,
+        completed: 0,
+        errors: 0,
+        latency: Default::default(),
+      })
+      .collect();
+    self.detailed = Some(DetailedOpsTracker {
+      ops: RefCell::new(ops),
+      hook,
+    });
+  }
+
+  /// Returns the detailed metrics of every op, or `None` if they are not
+  /// being recorded.
+  pub fn per_op_detailed(&self) -> Option<Vec<OpDetailedMetrics>> {
+    self.detailed.as_ref().map(|d| d.ops.borrow().clone())
+  }
+
+  /// Returns the current time if async op latency is being recorded.
+  #[inline]
+  pub fn start_timer(&self) -> Option<Instant> {
+    self.detailed.as_ref().map(|_| Instant::now())
+  }
+
   pub fn per_op(&self) -> Vec<OpMetrics> {
     self.ops.borrow().clone()
   }
@@ -73,6 +231,10 @@ impl OpsTracker {
     metrics.ops_completed += 1;
     metrics.ops_dispatched_sync += 1;
     metrics.ops_completed_sync += 1;
+    drop(metrics);
+    if let Some(detailed) = &self.detailed {
+      detailed.record(id, OpMetricsEvent::CompletedSync);
+    }
   }
 
   #[inline]
@@ -80,12 +242,32 @@ impl OpsTracker {
     let mut metrics = self.metrics_mut(id);
     metrics.ops_dispatched += 1;
     metrics.ops_dispatched_async += 1;
+    drop(metrics);
+    if let Some(detailed) = &self.detailed {
+      detailed.record(id, OpMetricsEvent::DispatchedAsync);
+    }
   }
 
+  /// `latency` is the time since [`OpsTracker::start_timer`] was called for
+  /// this op; it is only present when detailed metrics are enabled.
   #[inline]
-  pub fn track_async_completed(&self, id: OpId) {
+  pub fn track_async_completed(
+    &self,
+    id: OpId,
+    latency: Option<Duration>,
+    is_error: bool,
+  ) {
     let mut metrics = self.metrics_mut(id);
     metrics.ops_completed += 1;
     metrics.ops_completed_async += 1;
+    drop(metrics);
+    if let (Some(detailed), Some(latency)) = (&self.detailed, latency) {
+      let event = if is_error {
+        OpMetricsEvent::ErrorAsync(latency)
+      } else {
+        OpMetricsEvent::CompletedAsync(latency)
+      };
+      detailed.record(id, event);
+    }
   }
 }
@@ -13,12 +13,19 @@ use crate::io::BufView;
 use crate::io::WriteOutcome;
 use anyhow::Error;
 use futures::Future;
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
 use std::any::type_name;
 use std::any::Any;
 use std::any::TypeId;
+use std::backtrace::Backtrace;
 use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::collections::HashMap;
 use std::iter::Iterator;
+use std::marker::PhantomData;
 use std::pin::Pin;
 use std::rc::Rc;
 
@@ -154,6 +161,83 @@ pub trait Resource: Any + 'static {
     })
   }
 
+  /// Read a single chunk of data from the resource, scattering it across the
+  /// provided buffers in order. Returns the total number of bytes read along
+  /// with the buffers. If zero bytes are read, it indicates that the resource
+  /// has reached EOF.
+  ///
+  /// By default this method reads into the first non-empty buffer with
+  /// `read_byob()`. Resources that can fill several buffers in a single
+  /// operation (e.g. with `readv(2)`) should override this method.
+  fn read_byob_vectored(
+    self: Rc<Self>,
+    mut bufs: Vec<BufMutView>,
+  ) -> AsyncResult<(usize, Vec<BufMutView>)> {
+    Box::pin(async move {
+      let Some(index) = bufs.iter().position(|buf| !buf.is_empty()) else {
+        return Ok((0, bufs));
+      };
+      let buf = std::mem::replace(&mut bufs[index], BufMutView::new(0));
+      let (nread, buf) = self.read_byob(buf).await?;
+      bufs[index] = buf;
+      Ok((nread, bufs))
+    })
+  }
+
+  /// Write data gathered from the provided buffers, in order, to the resource.
+  /// The operation may not be able to write all of the buffers, in which case
+  /// it should return the total number of bytes written. Additionally it should
+  /// return the buffers with the written bytes removed: fully written buffers
+  /// may be dropped or left empty, and the cursor of a partially written
+  /// buffer is advanced.
+  ///
+  /// By default this method writes the first non-empty buffer with `write()`.
+  /// Resources that can write several buffers in a single operation (e.g. with
+  /// `writev(2)`) should override this method.
+  fn write_vectored(
+    self: Rc<Self>,
+    mut bufs: Vec<BufView>,
+  ) -> AsyncResult<(usize, Vec<BufView>)> {
+    Box::pin(async move {
+      let Some(index) = bufs.iter().position(|buf| !buf.is_empty()) else {
+        return Ok((0, bufs));
+      };
+      let buf = std::mem::replace(&mut bufs[index], BufView::empty());
+      let nwritten = match self.write(buf).await? {
+        WriteOutcome::Partial { nwritten, mut view } => {
+          view.advance_cursor(nwritten);
+          bufs[index] = view;
+          nwritten
+        }
+        WriteOutcome::Full { nwritten } => nwritten,
+      };
+      Ok((nwritten, bufs))
+    })
+  }
+
+  /// Write all of the provided buffers, in order, to the resource. Unlike
+  /// `write_vectored()`, this will ensure every buffer is written entirely.
+  ///
+  /// By default this method will call `write_vectored()` repeatedly until all
+  /// buffers are written.
+  fn write_all_vectored(self: Rc<Self>, bufs: Vec<BufView>) -> AsyncResult<()> {
+    Box::pin(async move {
+      let mut bufs = bufs;
+      bufs.retain(|buf| !buf.is_empty());
+      while !bufs.is_empty() {
+        let (nwritten, new_bufs) = self.clone().write_vectored(bufs).await?;
+        if nwritten == 0 {
+          return Err(
+            std::io::Error::from(std::io::ErrorKind::WriteZero).into(),
+          );
+        }
+        bufs = new_bufs;
+        bufs.retain(|buf| !buf.is_empty());
+      }
+      Ok(())
+    })
+  }
+
   /// The same as [`read_byob()`][Resource::read_byob], but synchronous.
   fn read_byob_sync(self: Rc<Self>, data: &mut [u8]) -> Result<usize, Error> {
     _ = data;
@@ -226,6 +310,84 @@ impl dyn Resource {
 // TODO: use `u64` instead?
 pub type ResourceId = u32;
 
+/// A [`ResourceId`] that remembers the type of the resource it refers to, so
+/// the resource can't be retrieved as a different type by mistake. Returned
+/// by [`ResourceTable::add_typed`].
+///
+/// It serializes as the plain rid, so ops can accept and return it in place
+/// of a `ResourceId`. A rid received from JavaScript may still refer to a
+/// resource of another type; [`ResourceTable::get_typed`] checks the type.
+pub struct TypedResourceId<T: Resource> {
+  rid: ResourceId,
+  _resource: PhantomData<fn() -> T>,
+}
+
+impl<T: Resource> TypedResourceId<T> {
+  /// Wraps `rid` without checking the type of the resource it refers to.
+  pub fn new(rid: ResourceId) -> Self {
+    Self {
+      rid,
+      _resource: PhantomData,
+    }
+  }
+
+  pub fn rid(self) -> ResourceId {
+    self.rid
+  }
+}
+
+impl<T: Resource> Clone for TypedResourceId<T> {
+  fn clone(&self) -> Self {
+    *self
+  }
+}
+
+impl<T: Resource> Copy for TypedResourceId<T> {}
+
+impl<T: Resource> PartialEq for TypedResourceId<T> {
+  fn eq(&self, other: &Self) -> bool {
+    self.rid == other.rid
+  }
+}
+
+impl<T: Resource> Eq for TypedResourceId<T> {}
+
+impl<T: Resource> std::fmt::Debug for TypedResourceId<T> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "TypedResourceId<{}>({})", type_name::<T>(), self.rid)
+  }
+}
+
+impl<T: Resource> From<TypedResourceId<T>> for ResourceId {
+  fn from(id: TypedResourceId<T>) -> Self {
+    id.rid
+  }
+}
+
+impl<T: Resource> Serialize for TypedResourceId<T> {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    self.rid.serialize(serializer)
+  }
+}
+
+impl<'de, T: Resource> Deserialize<'de> for TypedResourceId<T> {
+  fn deserialize<D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Self, D::Error> {
+    ResourceId::deserialize(deserializer).map(Self::new)
+  }
+}
+
+/// A resource that was still in the [`ResourceTable`] when it was dropped,
+/// as reported by [`ResourceTable::leaked_resources`].
+#[derive(Debug)]
+pub struct LeakedResource {
+  pub rid: ResourceId,
+  pub name: String,
+  /// Where the resource was added to the table.
+  pub backtrace: String,
+}
+
 /// Map-like data structure storing Deno's resources (equivalent to file
 /// descriptors).
 ///
@@ -235,10 +397,19 @@ pub type ResourceId = u32;
 ///
 /// Each resource is identified through a _resource ID (rid)_, which acts as
 /// the key in the map.
+///
+/// Resources holding large native allocations can account for them with
+/// [`ResourceTable::adjust_external_memory`], so V8 takes them into account
+/// when scheduling garbage collection.
 #[derive(Default)]
 pub struct ResourceTable {
   index: BTreeMap<ResourceId, Rc<dyn Resource>>,
   next_rid: ResourceId,
+  external_memory: HashMap<ResourceId, u64>,
+  // Change in external memory not yet reported to V8.
+  pending_external_memory: i64,
+  // Where each resource was added, if leak tracking is enabled.
+  creation_backtraces: Option<HashMap<ResourceId, Backtrace>>,
 }
 
 impl ResourceTable {
@@ -268,9 +439,18 @@ impl ResourceTable {
     let removed_resource = self.index.insert(rid, resource);
     assert!(removed_resource.is_none());
     self.next_rid += 1;
+    if let Some(creation_backtraces) = &mut self.creation_backtraces {
+      creation_backtraces.insert(rid, Backtrace::force_capture());
+    }
     rid
   }
 
+  /// Inserts resource into the resource table like [`ResourceTable::add`],
+  /// returning a resource ID that can only be used to retrieve it as `T`.
+  pub fn add_typed<T: Resource>(&mut self, resource: T) -> TypedResourceId<T> {
+    TypedResourceId::new(self.add(resource))
+  }
+
   /// Returns true if any resource with the given `rid` exists.
   pub fn has(&self, rid: ResourceId) -> bool {
     self.index.contains_key(&rid)
@@ -288,6 +468,15 @@ impl ResourceTable {
       .ok_or_else(bad_resource_id)
   }
 
+  /// Returns the resource referred to by a typed resource ID, see
+  /// [`ResourceTable::get`].
+  pub fn get_typed<T: Resource>(
+    &self,
+    id: TypedResourceId<T>,
+  ) -> Result<Rc<T>, Error> {
+    self.get::<T>(id.rid)
+  }
+
   pub fn get_any(&self, rid: ResourceId) -> Result<Rc<dyn Resource>, Error> {
     self
       .index
@@ -304,6 +493,7 @@ impl ResourceTable {
       .index
       .insert(rid, Rc::new(resource) as Rc<dyn Resource>);
     assert!(result.is_some());
+    self.release_external_memory(rid);
   }
 
   /// Removes a resource of type `T` from the resource table and returns it.
@@ -319,9 +509,19 @@ impl ResourceTable {
   pub fn take<T: Resource>(&mut self, rid: ResourceId) -> Result<Rc<T>, Error> {
     let resource = self.get::<T>(rid)?;
     self.index.remove(&rid);
+    self.release(rid);
     Ok(resource)
   }
 
+  /// Removes the resource referred to by a typed resource ID, see
+  /// [`ResourceTable::take`].
+  pub fn take_typed<T: Resource>(
+    &mut self,
+    id: TypedResourceId<T>,
+  ) -> Result<Rc<T>, Error> {
+    self.take::<T>(id.rid)
+  }
+
   /// Removes a resource from the resource table and returns it. Note that the
   /// resource's `close()` method is *not* called.
   ///
@@ -334,7 +534,9 @@ impl ResourceTable {
     &mut self,
     rid: ResourceId,
   ) -> Result<Rc<dyn Resource>, Error> {
-    self.index.remove(&rid).ok_or_else(bad_resource_id)
+    let resource = self.index.remove(&rid).ok_or_else(bad_resource_id)?;
+    self.release(rid);
+    Ok(resource)
   }
 
   /// Removes the resource with the given `rid` from the resource table. If the
@@ -344,11 +546,116 @@ impl ResourceTable {
   /// may implement the `close()` method to perform clean-ups such as canceling
   /// ops.
   pub fn close(&mut self, rid: ResourceId) -> Result<(), Error> {
-    self
-      .index
-      .remove(&rid)
-      .ok_or_else(bad_resource_id)
-      .map(|resource| resource.close())
+    let resource = self.index.remove(&rid).ok_or_else(bad_resource_id)?;
+    self.release(rid);
+    resource.close();
+    Ok(())
+  }
+
+  /// Records that the resource with the given `rid` allocated
+  /// (`change_in_bytes > 0`) or freed (`change_in_bytes < 0`) memory outside
+  /// of the V8 heap. The amount accounted to a resource never goes below zero,
+  /// and whatever is left is released when the resource is removed from the
+  /// table.
+  ///
+  /// The changes are reported to V8 by
+  /// [`ResourceTable::report_external_memory`], which the runtime calls on
+  /// every event loop tick.
+  pub fn adjust_external_memory(
+    &mut self,
+    rid: ResourceId,
+    change_in_bytes: i64,
+  ) -> Result<(), Error> {
+    if !self.index.contains_key(&rid) {
+      return Err(bad_resource_id());
+    }
+    let current = self.external_memory.get(&rid).copied().unwrap_or(0);
+    let updated = current.saturating_add_signed(change_in_bytes);
+    if updated == 0 {
+      self.external_memory.remove(&rid);
+    } else {
+      self.external_memory.insert(rid, updated);
+    }
+    self.pending_external_memory += updated as i64 - current as i64;
+    Ok(())
+  }
+
+  /// Returns the external memory accounted to the resource with the given
+  /// `rid`, in bytes.
+  pub fn external_memory(&self, rid: ResourceId) -> u64 {
+    self.external_memory.get(&rid).copied().unwrap_or(0)
+  }
+
+  /// Returns the external memory accounted to all resources in the table, in
+  /// bytes.
+  pub fn total_external_memory(&self) -> u64 {
+    self.external_memory.values().sum()
+  }
+
+  /// Reports the change in external memory since the last call to V8, so it
+  /// is counted by `v8::HeapStatistics::external_memory` and by the garbage
+  /// collector's heuristics.
+  pub fn report_external_memory(&mut self, isolate: &mut v8::Isolate) {
+    let change = std::mem::take(&mut self.pending_external_memory);
+    if change != 0 {
+      isolate.adjust_amount_of_external_allocated_memory(change);
+    }
+  }
+
+  fn release_external_memory(&mut self, rid: ResourceId) {
+    if let Some(bytes) = self.external_memory.remove(&rid) {
+      self.pending_external_memory -= bytes as i64;
+    }
+  }
+
+  /// Forgets everything recorded about a resource removed from the table.
+  fn release(&mut self, rid: ResourceId) {
+    self.release_external_memory(rid);
+    if let Some(creation_backtraces) = &mut self.creation_backtraces {
+      creation_backtraces.remove(&rid);
+    }
+  }
+
+  /// Record where every resource added from now on is created, so resources
+  /// that are never closed can be found with
+  /// [`ResourceTable::leaked_resources`]. Capturing backtraces is slow, so
+  /// this is meant for debugging and tests.
+  pub fn track_leaks(&mut self) {
+    self.creation_backtraces.get_or_insert_with(HashMap::new);
+  }
+
+  /// Returns the resources added since [`ResourceTable::track_leaks`] was
+  /// called that are still in the table, ordered by rid.
+  pub fn leaked_resources(&self) -> Vec<LeakedResource> {
+    let Some(creation_backtraces) = &self.creation_backtraces else {
+      return vec![];
+    };
+    let mut leaked = creation_backtraces
+      .iter()
+      .filter_map(|(rid, backtrace)| {
+        let resource = self.index.get(rid)?;
+        Some(LeakedResource {
+          rid: *rid,
+          name: resource.name().into_owned(),
+          backtrace: backtrace.to_string(),
+        })
+      })
+      .collect::<Vec<_>>();
+    leaked.sort_by_key(|leaked| leaked.rid);
+    leaked
+  }
+
+  /// Logs a warning for every resource that leaked, see
+  /// [`ResourceTable::leaked_resources`]. Called when the runtime is dropped.
+  pub(crate) fn report_leaks(&self) {
+    for leaked in self.leaked_resources() {
+      log::warn!(
+        "Resource \"{}\" (rid {}) was never closed. It was created at:\n{}",
+        leaked.name,
+        leaked.rid,
+        leaked.backtrace
+      );
+    }
   }
 
   /// Returns an iterator that yields a `(id, name)` pair for every resource
@@ -1,9 +1,11 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use futures::future::FutureExt;
 use log::debug;
 use std::fmt::Write;
 use std::option::Option;
 use std::os::raw::c_void;
+use std::rc::Rc;
 use v8::MapFnTo;
 
 use crate::error::is_instance_of_error;
@@ -12,10 +14,11 @@ use crate::modules::get_asserted_module_type_from_assertions;
 use crate::modules::parse_import_assertions;
 use crate::modules::validate_import_assertions;
 use crate::modules::ImportAssertionsKind;
+use crate::modules::ImportMetaResolution;
 use crate::modules::ModuleMap;
-use crate::modules::ResolutionKind;
 use crate::ops::OpCtx;
 use crate::runtime::InitMode;
+use crate::runtime::PromiseHook;
 use crate::JsRealm;
 use crate::JsRuntime;
 
@@ -271,6 +274,7 @@ pub fn host_import_module_dynamically_callback<'s>(
       &specifier_str,
       &referrer_name_str,
       asserted_module_type,
+      assertions,
       resolver_handle,
     );
     state_rc.borrow_mut().notify_new_dynamic_import();
@@ -343,20 +347,27 @@ fn import_meta_resolve(
   let loader = module_map_rc.borrow().loader.clone();
   let specifier_str = specifier.to_rust_string_lossy(scope);
 
-  if specifier_str.starts_with("npm:") {
-    throw_type_error(scope, "\"npm:\" specifiers are currently not supported in import.meta.resolve()");
-    return;
-  }
-
-  match loader.resolve(&specifier_str, &referrer, ResolutionKind::DynamicImport)
-  {
-    Ok(resolved) => {
+  match loader.resolve_import_meta(&specifier_str, &referrer) {
+    ImportMetaResolution::Sync(Ok(resolved)) => {
       let resolved_val = serde_v8::to_v8(scope, resolved.as_str()).unwrap();
       rv.set(resolved_val);
     }
-    Err(err) => {
+    ImportMetaResolution::Sync(Err(err)) => {
       throw_type_error(scope, &err.to_string());
     }
+    ImportMetaResolution::Async(fut) => {
+      let resolver = v8::PromiseResolver::new(scope).unwrap();
+      let promise = resolver.get_promise(scope);
+      let resolver = v8::Global::new(scope, resolver);
+      module_map_rc
+        .borrow_mut()
+        .pending_import_meta_resolves
+        .push(fut.map(move |result| (resolver, result)).boxed_local());
+      JsRuntime::state_from(scope)
+        .borrow_mut()
+        .notify_new_dynamic_import();
+      rv.set(promise.into());
+    }
   };
 }
 
@@ -420,6 +431,30 @@ fn catch_dynamic_import_promise_error(
   scope.throw_exception(arg);
 }
 
+/// Isolate slot holding the hook set with
+/// [`crate::RuntimeOptions::promise_hook`].
+pub(crate) struct PromiseHookSlot(pub Rc<dyn PromiseHook>);
+
+pub extern "C" fn promise_hook_callback(
+  type_: v8::PromiseHookType,
+  promise: v8::Local<v8::Promise>,
+  parent: v8::Local<v8::Value>,
+) {
+  // SAFETY: `CallbackScope` can be safely constructed from `Local<Promise>`
+  let scope = &mut unsafe { v8::CallbackScope::new(promise) };
+  let hook = match scope.get_slot::<PromiseHookSlot>() {
+    Some(slot) => slot.0.clone(),
+    None => return,
+  };
+  let scope = &mut v8::HandleScope::new(scope);
+  match type_ {
+    v8::PromiseHookType::Init => hook.init(scope, promise, parent),
+    v8::PromiseHookType::Resolve => hook.resolve(scope, promise),
+    v8::PromiseHookType::Before => hook.before(scope, promise),
+    v8::PromiseHookType::After => hook.after(scope, promise),
+  }
+}
+
 pub extern "C" fn promise_reject_callback(message: v8::PromiseRejectMessage) {
   use v8::PromiseRejectEvent::*;
 
@@ -1,9 +1,12 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 use super::bindings;
 use crate::error::exception_to_err_result;
+use crate::error::JsError;
 use crate::modules::ModuleCode;
+use crate::modules::ModuleMap;
 use crate::ops::OpCtx;
 use crate::runtime::JsRuntimeState;
+use crate::runtime::UnhandledRejectionAction;
 use crate::task::MaskResultAsSend;
 use crate::JsRuntime;
 use crate::OpId;
@@ -17,6 +20,7 @@ use std::hash::BuildHasherDefault;
 use std::hash::Hasher;
 use std::option::Option;
 use std::rc::Rc;
+use std::time::Duration;
 use tokio::task::JoinSet;
 use v8::HandleScope;
 use v8::Local;
@@ -51,11 +55,16 @@ pub(crate) struct ContextState {
     VecDeque<(v8::Global<v8::Promise>, v8::Global<v8::Value>)>,
   pub(crate) unrefed_ops: HashSet<i32, BuildHasherDefault<IdentityHasher>>,
   pub(crate) pending_ops:
-    JoinSet<MaskResultAsSend<(PromiseId, OpId, OpResult)>>,
+    JoinSet<MaskResultAsSend<(PromiseId, OpId, OpResult, Option<Duration>)>>,
   // We don't explicitly re-read this prop but need the slice to live alongside
   // the context
   pub(crate) op_ctxs: Box<[OpCtx]>,
   pub(crate) isolate: Option<*mut v8::OwnedIsolate>,
+  /// Modules loaded in this realm. Each realm resolves and caches its
+  /// imports independently of all other realms.
+  pub(crate) module_map: Option<Rc<RefCell<ModuleMap>>>,
+  /// Number of scripts run with [`JsRealm::execute_script`] in this realm.
+  pub(crate) executed_scripts: usize,
 }
 
 /// A representation of a JavaScript realm tied to a [`JsRuntime`], that allows
@@ -147,6 +156,16 @@ impl JsRealmInner {
     self.context_state.clone()
   }
 
+  #[inline(always)]
+  pub(crate) fn module_map(&self) -> Rc<RefCell<ModuleMap>> {
+    self
+      .context_state
+      .borrow()
+      .module_map
+      .clone()
+      .expect("Realm has no module map")
+  }
+
   /// For info on the [`v8::Isolate`] parameter, check [`JsRealm#panics`].
   #[inline(always)]
   pub fn handle_scope<'s>(
@@ -160,21 +179,35 @@ impl JsRealmInner {
     &self,
     scope: &mut v8::HandleScope,
   ) -> Result<(), Error> {
-    let Some((_, handle)) = self.context_state.borrow_mut().pending_promise_rejections.pop_front() else {
-      return Ok(());
-    };
-
-    let exception = v8::Local::new(scope, handle);
-    let state_rc = JsRuntime::state_from(scope);
-    let state = state_rc.borrow();
-    if let Some(inspector) = &state.inspector {
-      let inspector = inspector.borrow();
-      inspector.exception_thrown(scope, exception, true);
-      if inspector.has_blocking_sessions() {
+    loop {
+      let Some((_, handle)) = self.context_state.borrow_mut().pending_promise_rejections.pop_front() else {
         return Ok(());
+      };
+
+      let exception = v8::Local::new(scope, handle);
+      let state_rc = JsRuntime::state_from(scope);
+      let state = state_rc.borrow();
+      if let Some(inspector) = &state.inspector {
+        let inspector = inspector.borrow();
+        inspector.exception_thrown(scope, exception, true);
+        if inspector.has_blocking_sessions() {
+          return Ok(());
+        }
+      }
+      let unhandled_rejection_cb = state.unhandled_rejection_cb.clone();
+      drop(state);
+
+      let err =
+        exception_to_err_result::<()>(scope, exception, true).unwrap_err();
+      let action =
+        match (&unhandled_rejection_cb, err.downcast_ref::<JsError>()) {
+          (Some(cb), Some(js_error)) => cb(js_error),
+          _ => UnhandledRejectionAction::Escalate,
+        };
+      if action == UnhandledRejectionAction::Escalate {
+        return Err(err);
       }
     }
-    exception_to_err_result(scope, exception, true)
   }
 
   pub(crate) fn is_same(&self, other: &Rc<v8::Global<v8::Context>>) -> bool {
@@ -195,6 +228,15 @@ impl JsRealmInner {
     std::mem::take(&mut realm_state.js_wasm_streaming_cb);
     // The OpCtx slice may contain a circular reference
     std::mem::take(&mut realm_state.op_ctxs);
+    // Snapshots may be taken while promises are still unhandled or ops are
+    // still in flight; release whatever they hold on to.
+    realm_state.pending_promise_rejections.clear();
+    realm_state.unrefed_ops.clear();
+    realm_state.pending_ops.abort_all();
+    // The module map holds module handles and dynamic import resolvers
+    if let Some(module_map) = realm_state.module_map.take() {
+      module_map.borrow_mut().clear_pending_dynamic_imports();
+    }
 
     self.context().open(isolate).clear_all_slots(isolate);
 
@@ -220,6 +262,18 @@ impl JsRealm {
       .clone()
   }
 
+  /// Makes a context that was created outside of the runtime, such as a
+  /// `node:vm` context, share the state of the realm that is current in
+  /// `scope`. Promise rejections in that context are then reported to this
+  /// realm.
+  pub fn share_state_with_context(
+    scope: &mut v8::HandleScope,
+    context: v8::Local<v8::Context>,
+  ) {
+    let state = Self::state_from_scope(scope);
+    context.set_slot(scope, state);
+  }
+
   #[inline(always)]
   pub fn num_pending_ops(&self) -> usize {
     self.0.num_pending_ops()
@@ -311,6 +365,7 @@ impl JsRealm {
     name: &'static str,
     source_code: ModuleCode,
   ) -> Result<v8::Global<v8::Value>, Error> {
+    self.0.state().borrow_mut().executed_scripts += 1;
     let scope = &mut self.0.handle_scope(isolate);
 
     let source = Self::string_from_code(scope, &source_code).unwrap();
@@ -341,8 +396,6 @@ impl JsRealm {
       }
     }
   }
-
-  // TODO(andreubotella): `mod_evaluate`, `load_main_module`, `load_side_module`
 }
 
 impl Drop for JsRealm {
@@ -3,18 +3,23 @@
 use super::bindings;
 use super::jsrealm::JsRealmInner;
 use super::snapshot_util;
+use super::stack_trace;
 use crate::error::exception_to_err_result;
 use crate::error::generic_error;
 use crate::error::to_v8_type_error;
+use crate::error::ErrorClassRegistry;
 use crate::error::GetErrorClassFn;
 use crate::error::JsError;
 use crate::extensions::OpDecl;
 use crate::extensions::OpEventLoopFn;
 use crate::inspector::JsRuntimeInspector;
+use crate::inspector::LocalInspectorSession;
 use crate::module_specifier::ModuleSpecifier;
 use crate::modules::AssertedModuleType;
 use crate::modules::ExtModuleLoader;
 use crate::modules::ExtModuleLoaderCb;
+use crate::modules::FinalSpecifier;
+use crate::modules::ModuleArchiveWriter;
 use crate::modules::ModuleCode;
 use crate::modules::ModuleError;
 use crate::modules::ModuleId;
@@ -22,17 +27,23 @@ use crate::modules::ModuleLoadId;
 use crate::modules::ModuleLoader;
 use crate::modules::ModuleMap;
 use crate::modules::ModuleName;
+use crate::modules::RecursiveModuleLoad;
+use crate::modules::SymbolicModule;
 use crate::ops::*;
 use crate::runtime::ContextState;
 use crate::runtime::JsRealm;
 use crate::source_map::SourceMapCache;
 use crate::source_map::SourceMapGetter;
+use crate::Clock;
 use crate::Extension;
 use crate::ModuleType;
 use crate::NoopModuleLoader;
+use crate::OpMetricsHook;
 use crate::OpMiddlewareFn;
 use crate::OpResult;
 use crate::OpState;
+use crate::ResourceId;
+use crate::VirtualClock;
 use crate::V8_WRAPPER_OBJECT_INDEX;
 use crate::V8_WRAPPER_TYPE_INDEX;
 use anyhow::Context as AnyhowContext;
@@ -40,12 +51,19 @@ use anyhow::Error;
 use futures::channel::oneshot;
 use futures::future::poll_fn;
 use futures::future::Future;
+use futures::future::FutureExt;
 use futures::stream::StreamExt;
+use serde::Deserialize;
+use serde::Serialize;
 use smallvec::SmallVec;
 use std::any::Any;
 use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::ffi::c_void;
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::mem::ManuallyDrop;
 use std::ops::Deref;
 use std::ops::DerefMut;
@@ -58,9 +76,10 @@ use std::sync::Mutex;
 use std::sync::Once;
 use std::task::Context;
 use std::task::Poll;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 const STATE_DATA_OFFSET: u32 = 0;
-const MODULE_MAP_DATA_OFFSET: u32 = 1;
 
 pub enum Snapshot {
   Static(&'static [u8]),
@@ -135,17 +154,13 @@ impl InnerIsolateState {
 
   pub fn cleanup(&mut self) {
     self.prepare_for_cleanup();
+    stack_trace::unregister_isolate(&mut self.v8_isolate);
 
     let state_ptr = self.v8_isolate.get_data(STATE_DATA_OFFSET);
     // SAFETY: We are sure that it's a valid pointer for whole lifetime of
     // the runtime.
     _ = unsafe { Rc::from_raw(state_ptr as *const RefCell<JsRuntimeState>) };
 
-    let module_map_ptr = self.v8_isolate.get_data(MODULE_MAP_DATA_OFFSET);
-    // SAFETY: We are sure that it's a valid pointer for whole lifetime of
-    // the runtime.
-    _ = unsafe { Rc::from_raw(module_map_ptr as *const RefCell<ModuleMap>) };
-
     self.state.borrow_mut().destroy_all_realms();
 
     debug_assert_eq!(Rc::strong_count(&self.state), 1);
@@ -199,13 +214,219 @@ impl InitMode {
   }
 }
 
+/// A CPU profile recorded with [`JsRuntime::start_cpu_profile`].
+#[derive(Debug)]
+pub struct CpuProfile {
+  /// The name the profile was started with.
+  pub name: String,
+  /// The profile in the `.cpuprofile` format understood by Chrome DevTools.
+  pub profile: serde_json::Value,
+}
+
+/// Execution counts of a function, as collected with
+/// [`JsRuntime::take_precise_coverage`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionCoverage {
+  pub function_name: String,
+  /// The ranges of the function, starting with the whole function. With block
+  /// coverage, the nested ranges cover the blocks whose count differs from
+  /// the enclosing range.
+  pub ranges: Vec<CoverageRange>,
+  /// Whether `ranges` includes block coverage, or only covers the function.
+  pub is_block_coverage: bool,
+}
+
+/// A range of source code, as UTF-16 code unit offsets into the script, and
+/// how many times it was executed.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverageRange {
+  pub start_offset: usize,
+  pub end_offset: usize,
+  pub count: u64,
+}
+
+#[derive(Deserialize)]
+struct ScriptCoverage {
+  url: String,
+  functions: Vec<FunctionCoverage>,
+}
+
+/// The state of the heap passed to a [`NearHeapLimitCallback`]. All sizes are
+/// in bytes.
+#[derive(Clone, Copy, Debug)]
+pub struct NearHeapLimitInfo {
+  /// The limit the heap is about to reach.
+  pub current_heap_limit: usize,
+  /// The limit the isolate was created with.
+  pub initial_heap_limit: usize,
+  /// Size of the objects on the heap, including garbage not yet collected.
+  pub used_heap_size: usize,
+  /// Size of the memory reserved for the heap.
+  pub total_heap_size: usize,
+  /// Size of the memory held outside the heap, e.g. by `ArrayBuffer`s.
+  pub external_memory: usize,
+}
+
+/// What to do once the heap is about to reach its limit, as decided by a
+/// [`NearHeapLimitCallback`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NearHeapLimitAction {
+  /// Set the heap limit to this many bytes. If the new limit isn't larger
+  /// than the current one, V8 aborts the process once it is reached.
+  SetLimit(usize),
+  /// Terminate JavaScript execution instead of aborting the process. The
+  /// limit is raised by half the initial limit so the termination has room to
+  /// unwind.
+  Terminate,
+}
+
+/// Called by V8 when the heap is close to its limit, before the process is
+/// aborted with an out of memory error. See
+/// [`RuntimeOptions::near_heap_limit_callback`].
+pub type NearHeapLimitCallback =
+  Box<dyn FnMut(NearHeapLimitInfo) -> NearHeapLimitAction>;
+
+/// V8 settings of a single isolate, for hosts that run several runtimes with
+/// different limits in one process. See [`RuntimeOptions::isolate_config`].
+///
+/// Flags set with [`crate::v8_set_flags`] apply to every isolate in the
+/// process instead. This includes the stack size, which V8 only supports
+/// configuring process-wide through `--stack-size`.
+#[derive(Clone, Debug, Default)]
+pub struct IsolateConfig {
+  /// Initial size of the heap, in bytes. Only used together with
+  /// `max_heap_size`.
+  pub initial_heap_size: Option<usize>,
+  /// Maximum size of the heap, in bytes. Reaching it aborts the process,
+  /// unless [`RuntimeOptions::near_heap_limit_callback`] raises the limit or
+  /// terminates execution. Overrides the heap limits of
+  /// [`RuntimeOptions::create_params`].
+  pub max_heap_size: Option<usize>,
+  /// Number of frames captured in stack traces, like V8's
+  /// `--stack-trace-limit` flag. Sets `Error.stackTraceLimit` in the main
+  /// realm. Defaults to 10.
+  pub stack_trace_limit: Option<u32>,
+}
+
+/// What to do with a promise rejection that wasn't handled by the end of an
+/// event loop tick, as decided by an [`UnhandledRejectionCallback`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnhandledRejectionAction {
+  /// Discard the rejection and keep running the event loop.
+  Swallow,
+  /// Fail the event loop with the rejection, as happens without a callback.
+  Escalate,
+}
+
+/// Called with the error of every unhandled promise rejection, after any
+/// JavaScript `unhandledrejection` handler had a chance to handle it. See
+/// [`RuntimeOptions::unhandled_rejection_callback`].
+pub type UnhandledRejectionCallback =
+  Rc<dyn Fn(&JsError) -> UnhandledRejectionAction>;
+
+/// How urgently memory should be freed, as signaled with
+/// [`JsRuntime::memory_pressure_notification`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryPressureLevel {
+  Moderate,
+  Critical,
+}
+
+/// When V8 runs queued microtasks. See [`RuntimeOptions::microtask_policy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MicrotaskPolicy {
+  /// Run microtasks whenever the JavaScript call depth drops to zero, e.g.
+  /// after every op callback or script.
+  #[default]
+  Auto,
+  /// Only run microtasks at the checkpoints of the event loop and when
+  /// JavaScript calls `Deno.core.runMicrotasks()`.
+  Explicit,
+}
+
+impl From<MicrotaskPolicy> for v8::MicrotasksPolicy {
+  fn from(policy: MicrotaskPolicy) -> Self {
+    match policy {
+      MicrotaskPolicy::Auto => v8::MicrotasksPolicy::Auto,
+      MicrotaskPolicy::Explicit => v8::MicrotasksPolicy::Explicit,
+    }
+  }
+}
+
+/// Options of the deterministic execution mode, see
+/// [`RuntimeOptions::deterministic`].
+#[derive(Clone, Copy, Debug)]
+pub struct DeterministicOptions {
+  /// Seed of the generator behind `Math.random()`.
+  pub seed: u64,
+  /// Wall-clock time the runtime starts at, unless a clock is passed in
+  /// [`RuntimeOptions::clock`].
+  pub epoch: SystemTime,
+}
+
+impl Default for DeterministicOptions {
+  fn default() -> Self {
+    Self {
+      seed: 0,
+      epoch: UNIX_EPOCH,
+    }
+  }
+}
+
+/// Receives the lifecycle events of every promise created in the isolate,
+/// for example to propagate async context (like `AsyncLocalStorage`) or to
+/// count the reactions run per event loop tick. See
+/// [`RuntimeOptions::promise_hook`].
+///
+/// V8 calls the hooks synchronously while running JavaScript, so they must
+/// be cheap and must not call back into JavaScript.
+#[allow(unused_variables)]
+pub trait PromiseHook {
+  /// A promise was created. `parent` is the promise it was chained from, or
+  /// `undefined`.
+  fn init(
+    &self,
+    scope: &mut v8::HandleScope,
+    promise: v8::Local<v8::Promise>,
+    parent: v8::Local<v8::Value>,
+  ) {
+  }
+
+  /// A promise was resolved or rejected.
+  fn resolve(
+    &self,
+    scope: &mut v8::HandleScope,
+    promise: v8::Local<v8::Promise>,
+  ) {
+  }
+
+  /// A reaction of `promise` is about to run as a microtask.
+  fn before(
+    &self,
+    scope: &mut v8::HandleScope,
+    promise: v8::Local<v8::Promise>,
+  ) {
+  }
+
+  /// A reaction of `promise` finished running.
+  fn after(
+    &self,
+    scope: &mut v8::HandleScope,
+    promise: v8::Local<v8::Promise>,
+  ) {
+  }
+}
+
 /// A single execution context of JavaScript. Corresponds roughly to the "Web
 /// Worker" concept in the DOM.
 ////
 /// The JsRuntime future completes when there is an error or when all
 /// pending ops have completed.
 ///
-/// Use [`JsRuntimeForSnapshot`] to be able to create a snapshot.
+/// Set [`RuntimeOptions::will_snapshot`] to be able to create a snapshot with
+/// [`JsRuntime::snapshot`].
 pub struct JsRuntime {
   pub(crate) inner: InnerIsolateState,
   pub(crate) module_map: Rc<RefCell<ModuleMap>>,
@@ -215,6 +436,13 @@ pub struct JsRuntime {
   init_mode: InitMode,
   // Marks if this is considered the top-level runtime. Used only be inspector.
   is_main: bool,
+  // Name and inspector session of the CPU profile started with
+  // `start_cpu_profile`, if any.
+  cpu_profile: Option<(String, LocalInspectorSession)>,
+  // Inspector session collecting the coverage started with
+  // `start_precise_coverage`, if any.
+  precise_coverage: Option<LocalInspectorSession>,
+  memory_pressure_listener: Option<Box<dyn FnMut(MemoryPressureLevel)>>,
 }
 
 /// The runtime type used for snapshot creation.
@@ -235,6 +463,7 @@ impl DerefMut for JsRuntimeForSnapshot {
 }
 
 pub(crate) struct DynImportModEvaluate {
+  realm: JsRealmInner,
   load_id: ModuleLoadId,
   module_id: ModuleId,
   promise: v8::Global<v8::Promise>,
@@ -290,6 +519,129 @@ pub type SharedArrayBufferStore =
 
 pub type CompiledWasmModuleStore = CrossIsolateStore<v8::CompiledWasmModule>;
 
+/// A cache of V8 code cache data for ES modules that can be shared between
+/// isolates, possibly on different threads.
+///
+/// Entries are keyed by module specifier and a hash of the module source, so
+/// a module whose source changed is compiled from scratch. When an ES module
+/// is compiled and no entry exists, the code cache produced by V8 is stored;
+/// subsequent compilations of the same module in any isolate using this cache
+/// consume it instead of parsing the source again.
+///
+/// V8 rejects code cache data produced by a different V8 version or with
+/// different flags, in which case the module is compiled from its source and
+/// the entry is replaced with data produced by this isolate.
+#[derive(Clone, Default)]
+pub struct ModuleCodeCache(
+  Arc<Mutex<HashMap<(String, u64), ModuleCodeCacheEntry>>>,
+);
+
+#[derive(Clone)]
+pub(crate) struct ModuleCodeCacheEntry {
+  pub data: Arc<[u8]>,
+  /// Whether V8 is known to accept `data`. Data produced by V8 in this
+  /// process is; data that came from elsewhere is checked the first time it's
+  /// consumed.
+  pub verified: bool,
+}
+
+impl ModuleCodeCache {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Number of modules that have code cache data stored.
+  pub fn len(&self) -> usize {
+    self.0.lock().unwrap().len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn clear(&self) {
+    self.0.lock().unwrap().clear();
+  }
+
+  pub(crate) fn get(
+    &self,
+    specifier: &str,
+    source_hash: u64,
+  ) -> Option<ModuleCodeCacheEntry> {
+    let cache = self.0.lock().unwrap();
+    cache.get(&(specifier.to_string(), source_hash)).cloned()
+  }
+
+  pub(crate) fn insert(
+    &self,
+    specifier: &str,
+    source_hash: u64,
+    data: &[u8],
+    verified: bool,
+  ) {
+    let mut cache = self.0.lock().unwrap();
+    cache.insert(
+      (specifier.to_string(), source_hash),
+      ModuleCodeCacheEntry {
+        data: data.into(),
+        verified,
+      },
+    );
+  }
+
+  pub(crate) fn mark_verified(&self, specifier: &str, source_hash: u64) {
+    let mut cache = self.0.lock().unwrap();
+    if let Some(entry) = cache.get_mut(&(specifier.to_string(), source_hash)) {
+      entry.verified = true;
+    }
+  }
+
+  pub(crate) fn hash_source(source: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+  }
+}
+
+/// Stores the bytes of WebAssembly modules compiled with
+/// `WebAssembly.compileStreaming()` and `WebAssembly.instantiateStreaming()`,
+/// keyed by the URL of the response they were streamed from. It can be shared
+/// between isolates, possibly on different threads.
+///
+/// When a response for a cached URL is passed to the streaming APIs, the
+/// cached bytes are compiled right away instead of waiting for the response
+/// body. V8 doesn't let embedders supply compiled code to streaming
+/// compilation, so the module itself is still compiled.
+#[derive(Clone, Default)]
+pub struct WasmModuleCache(Arc<Mutex<HashMap<String, Arc<[u8]>>>>);
+
+impl WasmModuleCache {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Number of modules stored.
+  pub fn len(&self) -> usize {
+    self.0.lock().unwrap().len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  pub fn clear(&self) {
+    self.0.lock().unwrap().clear();
+  }
+
+  pub(crate) fn get(&self, url: &str) -> Option<Arc<[u8]>> {
+    self.0.lock().unwrap().get(url).cloned()
+  }
+
+  pub(crate) fn insert(&self, url: String, bytes: Vec<u8>) {
+    self.0.lock().unwrap().insert(url, bytes.into());
+  }
+}
+
 /// Internal state for JsRuntime which is stored in one of v8::Isolate's
 /// embedder slots.
 pub struct JsRuntimeState {
@@ -306,13 +658,25 @@ pub struct JsRuntimeState {
   pub(crate) op_state: Rc<RefCell<OpState>>,
   pub(crate) shared_array_buffer_store: Option<SharedArrayBufferStore>,
   pub(crate) compiled_wasm_module_store: Option<CompiledWasmModuleStore>,
+  pub(crate) module_code_cache: Option<ModuleCodeCache>,
+  pub(crate) retain_module_sources: bool,
+  pub(crate) wasm_module_cache: Option<WasmModuleCache>,
+  pub(crate) custom_clock: bool,
+  pub(crate) deterministic: Option<DeterministicOptions>,
+  /// The virtual clock created for the deterministic mode, advanced by the
+  /// event loop.
+  pub(crate) deterministic_clock: Option<VirtualClock>,
   /// The error that was passed to an `op_dispatch_exception` call.
   /// It will be retrieved by `exception_to_err_result` and used as an error
   /// instead of any other exceptions.
   // TODO(nayeemrmn): This is polled in `exception_to_err_result()` which is
   // flimsy. Try to poll it similarly to `pending_promise_rejections`.
   pub(crate) dispatched_exception: Option<v8::Global<v8::Value>>,
+  pub(crate) unhandled_rejection_cb: Option<UnhandledRejectionCallback>,
+  pub(crate) error_class_registry: Option<Rc<ErrorClassRegistry>>,
+  pub(crate) microtask_policy: MicrotaskPolicy,
   pub(crate) inspector: Option<Rc<RefCell<JsRuntimeInspector>>>,
+  is_main: bool,
 }
 
 impl JsRuntimeState {
@@ -375,6 +739,10 @@ pub struct RuntimeOptions {
   /// error in JavaScript.
   pub get_error_class_fn: Option<GetErrorClassFn>,
 
+  /// Maps Rust error types to JS error classes, taking precedence over
+  /// `get_error_class_fn` for the errors it knows about.
+  pub error_class_registry: Option<ErrorClassRegistry>,
+
   /// Implementation of `ModuleLoader` which will be
   /// called when V8 requests to load ES modules.
   ///
@@ -397,6 +765,9 @@ pub struct RuntimeOptions {
   /// Isolate creation parameters.
   pub create_params: Option<v8::CreateParams>,
 
+  /// Heap limits and V8 settings for this isolate only.
+  pub isolate_config: IsolateConfig,
+
   /// V8 platform instance to use. Used when Deno initializes V8
   /// (which it only does once), otherwise it's silenty dropped.
   pub v8_platform: Option<v8::SharedRef<v8::Platform>>,
@@ -416,12 +787,84 @@ pub struct RuntimeOptions {
   /// `WebAssembly.Module` objects cannot be serialized.
   pub compiled_wasm_module_store: Option<CompiledWasmModuleStore>,
 
+  /// The code cache to consult when compiling ES modules, and to populate
+  /// with the code cache of newly compiled ones. Isolates that load the same
+  /// modules should use the same [ModuleCodeCache] to skip parsing them.
+  pub module_code_cache: Option<ModuleCodeCache>,
+
+  /// Keep the source and the code cache of every module after it's compiled,
+  /// so that the module graph can be written out with
+  /// [`JsRuntime::write_module_archive`].
+  pub retain_module_sources: bool,
+
+  /// The cache to serve WebAssembly modules streamed from the same URL from,
+  /// see [WasmModuleCache].
+  pub wasm_module_cache: Option<WasmModuleCache>,
+
+  /// Record per-op error counts and latency histograms, available through
+  /// [`OpsTracker::per_op_detailed`](crate::OpsTracker::per_op_detailed).
+  pub op_metrics: bool,
+
+  /// Receives every op event as it is recorded. Setting a hook implies
+  /// `op_metrics`.
+  pub op_metrics_hook: Option<Rc<dyn OpMetricsHook>>,
+
+  /// Record where resources are created and log a warning for every
+  /// resource still open when the runtime is dropped. See
+  /// [`ResourceTable::track_leaks`](crate::ResourceTable::track_leaks).
+  pub track_resource_leaks: bool,
+
+  /// The time source driving timers and `performance.now()`, available to ops
+  /// as [`OpState::clock`]. When set, `Date.now()` and `new Date()` read it as
+  /// well. Defaults to a [`SystemClock`](crate::SystemClock); pass a
+  /// [`VirtualClock`](crate::VirtualClock) to control time from Rust.
+  pub clock: Option<Rc<dyn Clock>>,
+
+  /// Called when the heap is close to its limit. The callback can raise the
+  /// limit, terminate execution, or record diagnostics before deciding.
+  /// Without it, reaching the limit aborts the process.
+  pub near_heap_limit_callback: Option<NearHeapLimitCallback>,
+
+  /// Called with the level of every notification sent with
+  /// [`JsRuntime::memory_pressure_notification`].
+  pub memory_pressure_listener: Option<Box<dyn FnMut(MemoryPressureLevel)>>,
+
+  /// Decides what happens to unhandled promise rejections. The callback can
+  /// log the error and swallow the rejection to keep the event loop running.
+  /// Without it, an unhandled rejection fails the event loop.
+  pub unhandled_rejection_callback: Option<UnhandledRejectionCallback>,
+
+  /// When queued microtasks run. Defaults to [`MicrotaskPolicy::Auto`].
+  pub microtask_policy: MicrotaskPolicy,
+
+  /// Receives the init, resolve, before and after events of every promise.
+  pub promise_hook: Option<Rc<dyn PromiseHook>>,
+
+  /// Run JavaScript deterministically, so the same input produces the same
+  /// output:
+  ///
+  /// - `Math.random()` is seeded with [`DeterministicOptions::seed`].
+  /// - Unless [`RuntimeOptions::clock`] is set, time is virtual: it starts at
+  ///   [`DeterministicOptions::epoch`] and only moves forward, straight to
+  ///   the next deadline, once every pending op is waiting on the clock.
+  /// - Ops completed in the same event loop tick are resolved in the order
+  ///   they were dispatched.
+  ///
+  /// Ops doing real I/O may still complete in a different tick from run to
+  /// run.
+  pub deterministic: Option<DeterministicOptions>,
+
   /// Start inspector instance to allow debuggers to connect.
   pub inspector: bool,
 
   /// Describe if this is the main runtime instance, used by debuggers in some
   /// situation - like disconnecting when program finishes running.
   pub is_main: bool,
+
+  /// Create the isolate in snapshotting mode, so that [`JsRuntime::snapshot`]
+  /// can be called on the runtime at any point. V8 can only serialize
+  /// isolates that were created this way.
+  pub will_snapshot: bool,
 }
 
 #[derive(Default)]
@@ -435,8 +878,9 @@ pub struct RuntimeSnapshotOptions {
 impl JsRuntime {
   /// Only constructor, configuration is done through `options`.
   pub fn new(mut options: RuntimeOptions) -> JsRuntime {
-    JsRuntime::init_v8(options.v8_platform.take(), cfg!(test));
-    JsRuntime::new_inner(options, false, None)
+    let will_snapshot = options.will_snapshot;
+    JsRuntime::init_v8(options.v8_platform.take(), cfg!(test) || will_snapshot);
+    JsRuntime::new_inner(options, will_snapshot, None)
   }
 
   pub(crate) fn state_from(
@@ -452,29 +896,20 @@ impl JsRuntime {
     state
   }
 
+  /// Returns the module map of the realm that `scope` is entered in.
   pub(crate) fn module_map_from(
-    isolate: &v8::Isolate,
+    scope: &mut v8::HandleScope,
   ) -> Rc<RefCell<ModuleMap>> {
-    let module_map_ptr = isolate.get_data(MODULE_MAP_DATA_OFFSET);
-    let module_map_rc =
-      // SAFETY: We are sure that it's a valid pointer for whole lifetime of
-      // the runtime.
-      unsafe { Rc::from_raw(module_map_ptr as *const RefCell<ModuleMap>) };
-    let module_map = module_map_rc.clone();
-    std::mem::forget(module_map_rc);
-    module_map
+    let context_state = JsRealm::state_from_scope(scope);
+    let module_map = context_state.borrow().module_map.clone();
+    module_map.expect("Realm has no module map")
   }
 
   pub(crate) fn event_loop_pending_state_from_scope(
     scope: &mut v8::HandleScope,
   ) -> EventLoopPendingState {
     let state = JsRuntime::state_from(scope);
-    let module_map = JsRuntime::module_map_from(scope);
-    let state = EventLoopPendingState::new(
-      scope,
-      &mut state.borrow_mut(),
-      &module_map.borrow(),
-    );
+    let state = EventLoopPendingState::new(scope, &mut state.borrow_mut());
     state
   }
 
@@ -502,6 +937,14 @@ impl JsRuntime {
     maybe_load_callback: Option<ExtModuleLoaderCb>,
   ) -> JsRuntime {
     let init_mode = InitMode::from_options(&options);
+    let mut deterministic_clock = None;
+    if let Some(deterministic) = &options.deterministic {
+      if options.clock.is_none() {
+        let clock = VirtualClock::new(deterministic.epoch);
+        options.clock = Some(Rc::new(clock.clone()));
+        deterministic_clock = Some(clock);
+      }
+    }
     let (op_state, ops) = Self::create_opstate(&mut options, init_mode);
     let op_state = Rc::new(RefCell::new(op_state));
 
@@ -534,8 +977,18 @@ impl JsRuntime {
       source_map_cache: Default::default(),
       shared_array_buffer_store: options.shared_array_buffer_store,
       compiled_wasm_module_store: options.compiled_wasm_module_store,
+      module_code_cache: options.module_code_cache,
+      retain_module_sources: options.retain_module_sources,
+      wasm_module_cache: options.wasm_module_cache,
+      custom_clock: options.clock.is_some(),
+      deterministic: options.deterministic,
+      deterministic_clock,
       op_state: op_state.clone(),
       dispatched_exception: None,
+      unhandled_rejection_cb: options.unhandled_rejection_callback.take(),
+      error_class_registry: options.error_class_registry.take().map(Rc::new),
+      microtask_policy: options.microtask_policy,
+      is_main: options.is_main,
       // Some fields are initialized later after isolate is created
       inspector: None,
       global_realm: None,
@@ -580,6 +1033,12 @@ impl JsRuntime {
           V8_WRAPPER_OBJECT_INDEX,
         )
         .external_references(&**refs);
+      if let Some(max_heap_size) = options.isolate_config.max_heap_size {
+        params = params.heap_limits(
+          options.isolate_config.initial_heap_size.unwrap_or(0),
+          max_heap_size,
+        );
+      }
       if let Some(snapshot) = options.startup_snapshot.take() {
         params = match snapshot {
           Snapshot::Static(data) => params.snapshot_blob(data),
@@ -589,8 +1048,20 @@ impl JsRuntime {
       }
       v8::Isolate::new(params)
     };
-    isolate.set_capture_stack_trace_for_uncaught_exceptions(true, 10);
+    let stack_trace_limit = options.isolate_config.stack_trace_limit;
+    isolate.set_capture_stack_trace_for_uncaught_exceptions(
+      true,
+      stack_trace_limit
+        .unwrap_or(10)
+        .try_into()
+        .unwrap_or(i32::MAX),
+    );
     isolate.set_promise_reject_callback(bindings::promise_reject_callback);
+    isolate.set_microtasks_policy(options.microtask_policy.into());
+    if let Some(promise_hook) = options.promise_hook.take() {
+      isolate.set_slot(bindings::PromiseHookSlot(promise_hook));
+      isolate.set_promise_hook(bindings::promise_hook_callback);
+    }
     isolate.set_host_initialize_import_meta_object_callback(
       bindings::host_initialize_import_meta_object_callback,
     );
@@ -604,6 +1075,9 @@ impl JsRuntime {
     let (global_context, snapshotted_data) = {
       let scope = &mut v8::HandleScope::new(&mut isolate);
       let context = v8::Context::new(scope);
+      if let Some(limit) = stack_trace_limit {
+        set_stack_trace_limit(scope, context, limit);
+      }
 
       // Get module map data from the snapshot
       let snapshotted_data = if init_mode == InitMode::FromSnapshot {
@@ -646,6 +1120,12 @@ impl JsRuntime {
     let loader = options
       .module_loader
       .unwrap_or_else(|| Rc::new(NoopModuleLoader));
+    let module_map_rc = Rc::new(RefCell::new(ModuleMap::new(loader)));
+    if let Some(snapshotted_data) = snapshotted_data {
+      let mut module_map = module_map_rc.borrow_mut();
+      module_map.update_with_snapshotted_data(snapshotted_data);
+    }
+    context_state.borrow_mut().module_map = Some(module_map_rc.clone());
 
     {
       let global_realm = JsRealmInner::new(
@@ -663,15 +1143,6 @@ impl JsRuntime {
       STATE_DATA_OFFSET,
       Rc::into_raw(state_rc.clone()) as *mut c_void,
     );
-    let module_map_rc = Rc::new(RefCell::new(ModuleMap::new(loader)));
-    if let Some(snapshotted_data) = snapshotted_data {
-      let mut module_map = module_map_rc.borrow_mut();
-      module_map.update_with_snapshotted_data(scope, snapshotted_data);
-    }
-    scope.set_data(
-      MODULE_MAP_DATA_OFFSET,
-      Rc::into_raw(module_map_rc.clone()) as *mut c_void,
-    );
 
     drop(context_scope);
 
@@ -687,8 +1158,18 @@ impl JsRuntime {
       extensions: options.extensions,
       module_map: module_map_rc,
       is_main: options.is_main,
+      cpu_profile: None,
+      precise_coverage: None,
+      memory_pressure_listener: options.memory_pressure_listener,
     };
 
+    if let Some(cb) = options.near_heap_limit_callback {
+      js_runtime.set_near_heap_limit_callback(cb);
+    }
+
+    let global_context = js_runtime.global_context();
+    stack_trace::register_isolate(js_runtime.v8_isolate(), global_context);
+
     let realm = js_runtime.global_realm();
     // TODO(mmastrac): We should thread errors back out of the runtime
     js_runtime
@@ -741,7 +1222,22 @@ impl JsRuntime {
   /// pre-initialized with all of the extensions that were passed in
   /// [`RuntimeOptions::extensions`] when the [`JsRuntime`] was
   /// constructed.
+  ///
+  /// The new realm gets its own module map, using the same module loader as
+  /// the global realm.
   pub fn create_realm(&mut self) -> Result<JsRealm, Error> {
+    let loader = self.module_map.borrow().loader.clone();
+    self.create_realm_with_loader(loader)
+  }
+
+  /// Creates a new realm like [`JsRuntime::create_realm`], but with its own
+  /// module loader. Modules imported from the new realm, either statically or
+  /// through `import()`, are resolved and loaded using `loader` and are never
+  /// shared with other realms.
+  pub fn create_realm_with_loader(
+    &mut self,
+    loader: Rc<dyn ModuleLoader>,
+  ) -> Result<JsRealm, Error> {
     let realm = {
       let context_state = Rc::new(RefCell::new(ContextState::default()));
       let op_ctxs: Box<[OpCtx]> = self
@@ -763,6 +1259,8 @@ impl JsRuntime {
         .collect();
       context_state.borrow_mut().op_ctxs = op_ctxs;
       context_state.borrow_mut().isolate = Some(self.v8_isolate() as _);
+      context_state.borrow_mut().module_map =
+        Some(Rc::new(RefCell::new(ModuleMap::new(loader))));
 
       let raw_ptr = self.v8_isolate() as *mut v8::OwnedIsolate;
       // SAFETY: Having the scope tied to self's lifetime makes it impossible to
@@ -818,29 +1316,40 @@ impl JsRuntime {
     // Take extensions temporarily so we can avoid have a mutable reference to self
     let extensions = std::mem::take(&mut self.extensions);
 
-    // TODO(nayeemrmn): Module maps should be per-realm.
-    let loader = self.module_map.borrow().loader.clone();
+    let module_map_rc = realm.0.module_map();
+    let loader = module_map_rc.borrow().loader.clone();
     let ext_loader = Rc::new(ExtModuleLoader::new(
       &extensions,
       maybe_load_callback.map(Rc::new),
     ));
-    self.module_map.borrow_mut().loader = ext_loader;
+    module_map_rc.borrow_mut().loader = ext_loader;
 
     let mut esm_entrypoints = vec![];
+    let mut lazy_specifiers = vec![];
 
     futures::executor::block_on(async {
       for extension in &extensions {
         let maybe_esm_entry_point = extension.get_esm_entry_point();
 
-        if let Some(esm_files) = extension.get_esm_sources() {
-          for file_source in esm_files {
-            self
-              .load_side_module(
-                &ModuleSpecifier::parse(file_source.specifier)?,
-                None,
-              )
-              .await?;
-          }
+        if let Some(lazy_esm_files) = extension.get_lazy_esm_sources() {
+          lazy_specifiers
+            .extend(lazy_esm_files.iter().map(|file| file.specifier));
+        }
+
+        // Lazy modules are loaded like the others, but are only evaluated
+        // once something imports them.
+        let esm_files = extension.get_esm_sources().into_iter().flatten();
+        let lazy_esm_files =
+          extension.get_lazy_esm_sources().into_iter().flatten();
+        for file_source in esm_files.chain(lazy_esm_files) {
+          self
+            .load_module_in_realm(
+              &realm.0,
+              &ModuleSpecifier::parse(file_source.specifier)?,
+              None,
+              false,
+            )
+            .await?;
         }
 
         if let Some(entry_point) = maybe_esm_entry_point {
@@ -859,20 +1368,39 @@ impl JsRuntime {
 
         if extension.is_core {
           self.init_cbs(realm);
+          if self.inner.state.borrow().custom_clock {
+            realm.execute_script(
+              self.v8_isolate(),
+              "ext:core/clock.js",
+              crate::include_ascii_string!("../clock.js"),
+            )?;
+          }
+          let deterministic = self.inner.state.borrow().deterministic;
+          if let Some(deterministic) = deterministic {
+            realm.execute_script(
+              self.v8_isolate(),
+              "ext:core/deterministic.js",
+              format!(
+                "{}({}n);",
+                include_str!("../deterministic.js"),
+                deterministic.seed
+              )
+              .into(),
+            )?;
+          }
         }
       }
 
       for specifier in esm_entrypoints {
         let mod_id = {
-          self
-            .module_map
+          module_map_rc
             .borrow()
             .get_id(specifier, AssertedModuleType::JavaScriptOrWasm)
             .unwrap_or_else(|| {
               panic!("{} not present in the module map", specifier)
             })
         };
-        let receiver = self.mod_evaluate(mod_id);
+        let receiver = self.mod_evaluate_in_realm_inner(&realm.0, mod_id);
         self.run_event_loop(false).await?;
         receiver
           .await?
@@ -881,17 +1409,16 @@ impl JsRuntime {
 
       #[cfg(debug_assertions)]
       {
-        let module_map_rc = self.module_map.clone();
         let mut scope = realm.handle_scope(self.v8_isolate());
         let module_map = module_map_rc.borrow();
-        module_map.assert_all_modules_evaluated(&mut scope);
+        module_map.assert_all_modules_evaluated(&mut scope, &lazy_specifiers);
       }
 
       Ok::<_, anyhow::Error>(())
     })?;
 
     self.extensions = extensions;
-    self.module_map.borrow_mut().loader = loader;
+    module_map_rc.borrow_mut().loader = loader;
     Ok(())
   }
 
@@ -973,11 +1500,25 @@ impl JsRuntime {
     let ops = Self::collect_ops(&mut options.extensions);
 
     let mut op_state = OpState::new(ops.len());
+    if options.op_metrics || options.op_metrics_hook.is_some() {
+      let op_names = ops.iter().map(|decl| decl.name).collect::<Vec<_>>();
+      op_state
+        .tracker
+        .enable_detailed(&op_names, options.op_metrics_hook.take());
+    }
 
     if let Some(get_error_class_fn) = options.get_error_class_fn {
       op_state.get_error_class_fn = get_error_class_fn;
     }
 
+    if options.track_resource_leaks {
+      op_state.resource_table.track_leaks();
+    }
+
+    if let Some(clock) = &options.clock {
+      op_state.clock = clock.clone();
+    }
+
     // Setup state
     for e in &mut options.extensions {
       // ops are already registered during in bindings::initialize_context();
@@ -1135,6 +1676,81 @@ impl JsRuntime {
     self.resolve_value(promise).await
   }
 
+  /// Returns the specifier that `specifier` was registered under in the global
+  /// realm's module map after following redirects, along with the redirect
+  /// chain. Returns `None` if no module was loaded for `specifier`.
+  pub fn resolved_specifier(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Option<FinalSpecifier> {
+    let module_map_rc = self.global_realm_inner().module_map();
+    let module_map = module_map_rc.borrow();
+    let chain = [
+      AssertedModuleType::JavaScriptOrWasm,
+      AssertedModuleType::Json,
+    ]
+    .into_iter()
+    .find_map(|module_type| {
+      module_map.get_alias_chain(specifier.as_str(), module_type)
+    })?;
+    let redirects = chain
+      .into_iter()
+      .map(|name| ModuleSpecifier::parse(name.as_str()))
+      .collect::<Result<Vec<_>, _>>()
+      .ok()?;
+    Some(FinalSpecifier {
+      specifier: redirects.last().unwrap_or(specifier).clone(),
+      redirects,
+    })
+  }
+
+  /// Returns every redirect recorded in the global realm's module map, as
+  /// `(specified, target)` pairs. A target may itself be redirected.
+  pub fn module_aliases(
+    &self,
+  ) -> impl Iterator<Item = (ModuleSpecifier, ModuleSpecifier)> {
+    let module_map_rc = self.global_realm_inner().module_map();
+    let module_map = module_map_rc.borrow();
+    let aliases = module_map
+      .collect_modules()
+      .into_iter()
+      .filter_map(|(_, name, module)| match module {
+        SymbolicModule::Alias(target) => Some((
+          ModuleSpecifier::parse(name.as_str()).ok()?,
+          ModuleSpecifier::parse(target.as_str()).ok()?,
+        )),
+        SymbolicModule::Mod(_) => None,
+      })
+      .collect::<Vec<_>>();
+    aliases.into_iter()
+  }
+
+  /// Returns the specifiers of the modules in the global realm that import
+  /// `specifier` directly, either by its own name or through a redirect.
+  ///
+  /// The reverse import and redirect edges are indexed as modules are
+  /// registered, so this doesn't have to scan the module graph.
+  pub fn get_module_dependents(
+    &self,
+    specifier: &ModuleSpecifier,
+  ) -> Vec<ModuleSpecifier> {
+    let module_map_rc = self.global_realm_inner().module_map();
+    let module_map = module_map_rc.borrow();
+    [
+      AssertedModuleType::JavaScriptOrWasm,
+      AssertedModuleType::Json,
+    ]
+    .into_iter()
+    .flat_map(|module_type| {
+      module_map.get_dependents(specifier.as_str(), module_type)
+    })
+    .filter_map(|id| {
+      let info = module_map.get_info_by_id(id)?;
+      ModuleSpecifier::parse(info.name.as_str()).ok()
+    })
+    .collect()
+  }
+
   /// Returns the namespace object of a module.
   ///
   /// This is only available after module evaluation has completed.
@@ -1206,6 +1822,67 @@ impl JsRuntime {
     }
   }
 
+  /// Registers a callback on the isolate when the memory limits are approached,
+  /// replacing any callback added before.
+  ///
+  /// Unlike [`JsRuntime::add_near_heap_limit_callback`], the closure is passed
+  /// the heap statistics at the time of the call and can terminate execution
+  /// rather than raise the limit.
+  pub fn set_near_heap_limit_callback(
+    &mut self,
+    mut cb: NearHeapLimitCallback,
+  ) {
+    let isolate_ptr: *mut v8::Isolate = &mut **self.v8_isolate();
+    let handle = self.v8_isolate().thread_safe_handle();
+    self.add_near_heap_limit_callback(
+      move |current_heap_limit, initial_heap_limit| {
+        let mut stats = v8::HeapStatistics::default();
+        // SAFETY: V8 calls this on the isolate's thread, and the callback is
+        // removed together with the isolate.
+        unsafe { &mut *isolate_ptr }.get_heap_statistics(&mut stats);
+        let info = NearHeapLimitInfo {
+          current_heap_limit,
+          initial_heap_limit,
+          used_heap_size: stats.used_heap_size(),
+          total_heap_size: stats.total_heap_size(),
+          external_memory: stats.external_memory(),
+        };
+        match cb(info) {
+          NearHeapLimitAction::SetLimit(limit) => limit,
+          NearHeapLimitAction::Terminate => {
+            handle.terminate_execution();
+            current_heap_limit + initial_heap_limit / 2
+          }
+        }
+      },
+    );
+  }
+
+  /// Returns what is keeping the event loop alive: pending async ops by name,
+  /// open resources, and pending module loads and evaluations. Use this to
+  /// diagnose why the event loop doesn't finish, or to check that a test
+  /// didn't leak ops or resources.
+  pub fn event_loop_stats(&mut self) -> EventLoopStats {
+    let state = self.inner.state.clone();
+    let state = state.borrow();
+    EventLoopStats::new(self.v8_isolate(), &state)
+  }
+
+  /// Signal that the process is running low on memory. At
+  /// [`MemoryPressureLevel::Critical`], V8 immediately collects as much
+  /// garbage as it can.
+  ///
+  /// The level is also passed to
+  /// [`RuntimeOptions::memory_pressure_listener`], if set.
+  pub fn memory_pressure_notification(&mut self, level: MemoryPressureLevel) {
+    if let Some(listener) = &mut self.memory_pressure_listener {
+      listener(level);
+    }
+    if level == MemoryPressureLevel::Critical {
+      self.v8_isolate().low_memory_notification();
+    }
+  }
+
   fn pump_v8_message_loop(&mut self) -> Result<(), Error> {
     let scope = &mut self.handle_scope();
     while v8::Platform::pump_message_loop(
@@ -1241,6 +1918,170 @@ impl JsRuntime {
       Some(JsRuntimeInspector::new(scope, context, self.is_main));
   }
 
+  /// Returns the inspector of the runtime that owns `scope`, creating it
+  /// first like [`JsRuntime::maybe_init_inspector`] if the runtime was
+  /// started without one. Used by ops that connect to the inspector on demand.
+  pub fn inspector_from_scope(
+    scope: &mut v8::HandleScope,
+  ) -> Rc<RefCell<JsRuntimeInspector>> {
+    let state_rc = JsRuntime::state_from(scope);
+    if let Some(inspector) = &state_rc.borrow().inspector {
+      return inspector.clone();
+    }
+
+    let (context, is_main) = {
+      let state = state_rc.borrow();
+      let context = state.global_realm.as_ref().unwrap().context().clone();
+      (context, state.is_main)
+    };
+    let context = v8::Local::new(scope, context);
+    let inspector = JsRuntimeInspector::new(scope, context, is_main);
+    state_rc.borrow_mut().inspector = Some(inspector.clone());
+    inspector
+  }
+
+  /// Start sampling the CPU usage of JavaScript running in this runtime.
+  ///
+  /// The profile is collected through a local inspector session, so no
+  /// DevTools client needs to be attached. Only one profile can be recorded
+  /// at a time; call [`JsRuntime::stop_cpu_profile`] to finish it.
+  pub async fn start_cpu_profile(&mut self, name: &str) -> Result<(), Error> {
+    if let Some((running, _)) = &self.cpu_profile {
+      return Err(generic_error(format!(
+        "CPU profile \"{running}\" is already running"
+      )));
+    }
+
+    self.maybe_init_inspector();
+    let mut session = self.inspector().borrow().create_local_session();
+    self
+      .post_inspector_message(&mut session, "Profiler.enable", None)
+      .await?;
+    self
+      .post_inspector_message(&mut session, "Profiler.start", None)
+      .await?;
+    self.cpu_profile = Some((name.to_string(), session));
+    Ok(())
+  }
+
+  /// Stop the CPU profile started with [`JsRuntime::start_cpu_profile`] and
+  /// return it.
+  pub async fn stop_cpu_profile(&mut self) -> Result<CpuProfile, Error> {
+    let (name, mut session) = match self.cpu_profile.take() {
+      Some(profile) => profile,
+      None => return Err(generic_error("No CPU profile is running")),
+    };
+
+    let mut result = self
+      .post_inspector_message(&mut session, "Profiler.stop", None)
+      .await?;
+    self
+      .post_inspector_message(&mut session, "Profiler.disable", None)
+      .await?;
+    Ok(CpuProfile {
+      name,
+      profile: result["profile"].take(),
+    })
+  }
+
+  /// Start collecting precise coverage of the code run in this runtime.
+  /// With `detailed`, blocks are counted as well as functions, which makes
+  /// code run slower.
+  pub async fn start_precise_coverage(
+    &mut self,
+    detailed: bool,
+  ) -> Result<(), Error> {
+    if self.precise_coverage.is_some() {
+      return Err(generic_error("Precise coverage is already being collected"));
+    }
+
+    self.maybe_init_inspector();
+    let mut session = self.inspector().borrow().create_local_session();
+    self
+      .post_inspector_message(&mut session, "Profiler.enable", None)
+      .await?;
+    self
+      .post_inspector_message(
+        &mut session,
+        "Profiler.startPreciseCoverage",
+        Some(serde_json::json!({ "callCount": true, "detailed": detailed })),
+      )
+      .await?;
+    self.precise_coverage = Some(session);
+    Ok(())
+  }
+
+  /// Take the coverage collected since coverage was started or last taken,
+  /// and reset the counts. Returns the functions of each script by URL, which
+  /// for modules is their specifier. Scripts without a URL are skipped.
+  pub async fn take_precise_coverage(
+    &mut self,
+  ) -> Result<BTreeMap<String, Vec<FunctionCoverage>>, Error> {
+    let mut session = match self.precise_coverage.take() {
+      Some(session) => session,
+      None => return Err(generic_error("Precise coverage is not started")),
+    };
+    let result = self
+      .post_inspector_message(
+        &mut session,
+        "Profiler.takePreciseCoverage",
+        None,
+      )
+      .await;
+    self.precise_coverage = Some(session);
+
+    let mut result = result?;
+    let scripts: Vec<ScriptCoverage> =
+      serde_json::from_value(result["result"].take())?;
+    let mut coverage = BTreeMap::new();
+    for script in scripts {
+      if !script.url.is_empty() {
+        coverage.insert(script.url, script.functions);
+      }
+    }
+    Ok(coverage)
+  }
+
+  /// Stop collecting the coverage started with
+  /// [`JsRuntime::start_precise_coverage`].
+  pub async fn stop_precise_coverage(&mut self) -> Result<(), Error> {
+    let mut session = match self.precise_coverage.take() {
+      Some(session) => session,
+      None => return Err(generic_error("Precise coverage is not started")),
+    };
+    self
+      .post_inspector_message(
+        &mut session,
+        "Profiler.stopPreciseCoverage",
+        None,
+      )
+      .await?;
+    self
+      .post_inspector_message(&mut session, "Profiler.disable", None)
+      .await?;
+    Ok(())
+  }
+
+  /// Send a message through a local inspector session, dispatching it to V8
+  /// while waiting for the response.
+  async fn post_inspector_message(
+    &mut self,
+    session: &mut LocalInspectorSession,
+    method: &str,
+    params: Option<serde_json::Value>,
+  ) -> Result<serde_json::Value, Error> {
+    let inspector = self.inspector();
+    let mut response = session.post_message(method, params).boxed_local();
+    poll_fn(|cx| {
+      if let Poll::Ready(result) = response.poll_unpin(cx) {
+        return Poll::Ready(result);
+      }
+      let _ = inspector.borrow().poll_sessions(Some(cx)).unwrap();
+      response.poll_unpin(cx)
+    })
+    .await
+  }
+
   pub fn poll_value(
     &mut self,
     global: &v8::Global<v8::Value>,
@@ -1325,6 +2166,14 @@ impl JsRuntime {
 
     self.pump_v8_message_loop()?;
 
+    {
+      let op_state = self.inner.state.borrow().op_state.clone();
+      op_state
+        .borrow_mut()
+        .resource_table
+        .report_external_memory(self.v8_isolate());
+    }
+
     // Dynamic module loading - ie. modules loaded using "import()"
     {
       // Run in a loop so that dynamic imports that only depend on another
@@ -1339,11 +2188,18 @@ impl JsRuntime {
       //    console.log("test")
       //
       loop {
-        let poll_imports = self.prepare_dyn_imports(cx)?;
-        assert!(poll_imports.is_ready());
+        // Each realm has its own module map, so imports have to be driven
+        // separately for each of them.
+        let realms = self.inner.state.borrow().known_realms.clone();
+        for realm in &realms {
+          let poll_imports = self.prepare_dyn_imports(realm, cx)?;
+          assert!(poll_imports.is_ready());
+
+          let poll_imports = self.poll_dyn_imports(realm, cx)?;
+          assert!(poll_imports.is_ready());
 
-        let poll_imports = self.poll_dyn_imports(cx)?;
-        assert!(poll_imports.is_ready());
+          self.poll_import_meta_resolves(realm, cx);
+        }
 
         if !self.evaluate_dyn_imports() {
           break;
@@ -1397,6 +2253,14 @@ impl JsRuntime {
       return Poll::Ready(Ok(()));
     }
 
+    if !pending_state.has_tick_scheduled
+      && !pending_state.has_pending_background_tasks
+      && !pending_state.has_pending_dyn_imports
+      && !maybe_scheduling
+    {
+      self.advance_deterministic_clock(cx);
+    }
+
     let state = self.inner.state.borrow();
 
     // Check if more async ops have been dispatched
@@ -1470,13 +2334,40 @@ impl JsRuntime {
     Poll::Pending
   }
 
-  fn event_loop_pending_state(&mut self) -> EventLoopPendingState {
+  pub(crate) fn event_loop_pending_state(&mut self) -> EventLoopPendingState {
     let mut scope = v8::HandleScope::new(self.inner.v8_isolate.as_mut());
-    EventLoopPendingState::new(
-      &mut scope,
-      &mut self.inner.state.borrow_mut(),
-      &self.module_map.borrow(),
-    )
+    EventLoopPendingState::new(&mut scope, &mut self.inner.state.borrow_mut())
+  }
+
+  /// Number of modules registered in the global realm.
+  pub(crate) fn num_modules(&self) -> usize {
+    self.module_map.borrow().info.len()
+  }
+
+  /// Number of scripts executed in the global realm.
+  pub(crate) fn num_executed_scripts(&self) -> usize {
+    self.global_realm().0.state().borrow().executed_scripts
+  }
+
+  /// Number of realms, including the global one.
+  pub(crate) fn num_realms(&self) -> usize {
+    self.inner.state.borrow().known_realms.len()
+  }
+
+  /// Destroys every realm but the first `keep` ones, which always include the
+  /// global realm.
+  pub(crate) fn destroy_realms_after(&mut self, keep: usize) {
+    let realms = {
+      let mut state = self.inner.state.borrow_mut();
+      let keep = keep.max(1);
+      if state.known_realms.len() <= keep {
+        return;
+      }
+      state.known_realms.split_off(keep)
+    };
+    for realm in realms {
+      realm.destroy();
+    }
   }
 }
 
@@ -1494,12 +2385,46 @@ impl JsRuntimeForSnapshot {
   }
 
   /// Takes a snapshot and consumes the runtime.
+  pub fn snapshot(self) -> v8::StartupData {
+    self
+      .0
+      .snapshot()
+      .expect("JsRuntimeForSnapshot is always created in snapshotting mode")
+  }
+}
+
+impl JsRuntime {
+  /// Takes a snapshot and consumes the runtime. The runtime must have been
+  /// created with [`RuntimeOptions::will_snapshot`] set.
   ///
-  /// `Error` can usually be downcast to `JsError`.
-  pub fn snapshot(mut self) -> v8::StartupData {
+  /// The snapshot can be taken at any point, including after evaluating
+  /// main and side modules. The module map (module handles, aliases and
+  /// load ids) is stored in the snapshot and restored by a runtime booted
+  /// from it. Any work still pending at this point (unsettled dynamic
+  /// imports, in-flight ops, module evaluations and unhandled rejections) is
+  /// discarded, so the corresponding promises will never settle in the
+  /// restored runtime.
+  pub fn snapshot(mut self) -> Result<v8::StartupData, Error> {
+    if !self.inner.will_snapshot {
+      return Err(generic_error(
+        "Runtime was not created with `RuntimeOptions::will_snapshot`",
+      ));
+    }
+
     // Ensure there are no live inspectors to prevent crashes.
     self.inner.prepare_for_cleanup();
 
+    // Drop pending state that holds on to V8 handles, which would otherwise
+    // prevent the snapshot from being created.
+    {
+      let mut state = self.inner.state.borrow_mut();
+      state.pending_dyn_mod_evaluate.clear();
+      state.pending_mod_evaluate.take();
+      state.dispatched_exception.take();
+      state.has_tick_scheduled = false;
+    }
+    self.module_map.borrow_mut().clear_pending_dynamic_imports();
+
     // Set the context to be snapshot's default context
     {
       let context = self.global_context();
@@ -1511,12 +2436,12 @@ impl JsRuntimeForSnapshot {
     // Serialize the module map and store its data in the snapshot.
     {
       let snapshotted_data = {
-        // `self.module_map` points directly to the v8 isolate data slot, which
-        // we must explicitly drop before destroying the isolate. We have to
-        // take and drop this `Rc` before that.
+        // `self.module_map` is shared with the global realm's state and holds
+        // V8 handles, which we must explicitly drop before destroying the
+        // isolate. We have to take and drop this `Rc` before that.
         let module_map_rc = std::mem::take(&mut self.module_map);
         let module_map = module_map_rc.borrow();
-        module_map.serialize_for_snapshotting(&mut self.handle_scope())
+        module_map.serialize_for_snapshotting()
       };
 
       let context = self.global_context();
@@ -1528,12 +2453,13 @@ impl JsRuntimeForSnapshot {
       );
     }
 
-    self
-      .0
-      .inner
-      .prepare_for_snapshot()
-      .create_blob(v8::FunctionCodeHandling::Keep)
-      .unwrap()
+    Ok(
+      self
+        .inner
+        .prepare_for_snapshot()
+        .create_blob(v8::FunctionCodeHandling::Keep)
+        .unwrap(),
+    )
   }
 }
 
@@ -1602,18 +2528,22 @@ impl EventLoopPendingState {
   pub fn new(
     scope: &mut v8::HandleScope<()>,
     state: &mut JsRuntimeState,
-    module_map: &ModuleMap,
   ) -> EventLoopPendingState {
     let mut num_unrefed_ops = 0;
     let mut num_pending_ops = 0;
+    let mut has_pending_dyn_imports = false;
     for realm in &state.known_realms {
       num_unrefed_ops += realm.num_unrefed_ops();
       num_pending_ops += realm.num_pending_ops();
+      let module_map = realm.module_map();
+      let module_map = module_map.borrow();
+      has_pending_dyn_imports |= module_map.has_pending_dynamic_imports()
+        || module_map.has_pending_import_meta_resolves();
     }
 
     EventLoopPendingState {
       has_pending_refed_ops: num_pending_ops > num_unrefed_ops,
-      has_pending_dyn_imports: module_map.has_pending_dynamic_imports(),
+      has_pending_dyn_imports,
       has_pending_dyn_module_evaluation: !state
         .pending_dyn_mod_evaluate
         .is_empty(),
@@ -1633,6 +2563,85 @@ impl EventLoopPendingState {
   }
 }
 
+fn set_stack_trace_limit(
+  scope: &mut v8::HandleScope,
+  context: v8::Local<v8::Context>,
+  limit: u32,
+) {
+  let scope = &mut v8::ContextScope::new(scope, context);
+  let global = context.global(scope);
+  let error_key = v8::String::new(scope, "Error").unwrap();
+  let Some(error) = global
+    .get(scope, error_key.into())
+    .and_then(|error| v8::Local::<v8::Object>::try_from(error).ok())
+  else {
+    return;
+  };
+  let limit_key = v8::String::new(scope, "stackTraceLimit").unwrap();
+  let limit = v8::Integer::new_from_unsigned(scope, limit);
+  error.set(scope, limit_key.into(), limit.into());
+}
+
+/// The work that keeps the event loop alive, as returned by
+/// [`JsRuntime::event_loop_stats`] and `Deno.core.eventLoopStats()`.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventLoopStats {
+  /// Number of dispatched async ops that haven't completed yet, by op name.
+  /// Timers show up here as the ops they are implemented with, e.g.
+  /// `op_sleep` for `deno_web` timers.
+  pub pending_ops: BTreeMap<&'static str, u64>,
+  /// How many of the pending ops were unrefed, and don't keep the event loop
+  /// alive.
+  pub unrefed_ops: usize,
+  /// The name of every open resource, by resource id.
+  pub resources: BTreeMap<ResourceId, String>,
+  /// Dynamic imports that are still being loaded.
+  pub pending_dynamic_imports: usize,
+  /// Dynamically imported modules that are still being evaluated.
+  pub pending_dynamic_module_evaluations: usize,
+  pub has_pending_module_evaluation: bool,
+  pub has_pending_background_tasks: bool,
+  pub has_tick_scheduled: bool,
+}
+
+impl EventLoopStats {
+  pub(crate) fn new(isolate: &v8::Isolate, state: &JsRuntimeState) -> Self {
+    let mut stats = EventLoopStats {
+      pending_dynamic_module_evaluations: state.pending_dyn_mod_evaluate.len(),
+      has_pending_module_evaluation: state.pending_mod_evaluate.is_some(),
+      has_pending_background_tasks: isolate.has_pending_background_tasks(),
+      has_tick_scheduled: state.has_tick_scheduled,
+      ..Default::default()
+    };
+    for realm in &state.known_realms {
+      stats.unrefed_ops += realm.num_unrefed_ops();
+      stats.pending_dynamic_imports +=
+        realm.module_map().borrow().num_pending_dynamic_imports();
+    }
+
+    let op_state = state.op_state.borrow();
+    if let Some(realm) = state.known_realms.first() {
+      let context_state = realm.state();
+      let context_state = context_state.borrow();
+      let per_op = op_state.tracker.per_op();
+      for (ctx, metrics) in context_state.op_ctxs.iter().zip(per_op) {
+        let pending =
+          metrics.ops_dispatched_async - metrics.ops_completed_async;
+        if pending > 0 {
+          stats.pending_ops.insert(ctx.decl.name, pending);
+        }
+      }
+    }
+    stats.resources = op_state
+      .resource_table
+      .names()
+      .map(|(rid, name)| (rid, name.into_owned()))
+      .collect();
+    stats
+  }
+}
+
 extern "C" fn near_heap_limit_callback<F>(
   data: *mut c_void,
   current_heap_limit: usize,
@@ -1662,12 +2671,26 @@ impl JsRuntimeState {
 
 // Related to module loading
 impl JsRuntime {
+  /// Returns the internal handle to the global realm.
+  fn global_realm_inner(&self) -> JsRealmInner {
+    self.inner.state.borrow().known_realms[0].clone()
+  }
+
   pub(crate) fn instantiate_module(
     &mut self,
     id: ModuleId,
   ) -> Result<(), v8::Global<v8::Value>> {
-    let module_map_rc = self.module_map.clone();
-    let scope = &mut self.handle_scope();
+    let realm = self.global_realm_inner();
+    self.instantiate_module_in_realm(&realm, id)
+  }
+
+  fn instantiate_module_in_realm(
+    &mut self,
+    realm: &JsRealmInner,
+    id: ModuleId,
+  ) -> Result<(), v8::Global<v8::Value>> {
+    let module_map_rc = realm.module_map();
+    let scope = &mut realm.handle_scope(self.v8_isolate());
     let tc_scope = &mut v8::TryCatch::new(scope);
 
     let module = module_map_rc
@@ -1696,17 +2719,18 @@ impl JsRuntime {
 
   fn dynamic_import_module_evaluate(
     &mut self,
+    realm: &JsRealmInner,
     load_id: ModuleLoadId,
     id: ModuleId,
   ) -> Result<(), Error> {
-    let module_handle = self
-      .module_map
+    let module_handle = realm
+      .module_map()
       .borrow()
       .get_handle(id)
       .expect("ModuleInfo not found");
 
     let status = {
-      let scope = &mut self.handle_scope();
+      let scope = &mut realm.handle_scope(self.v8_isolate());
       let module = module_handle.open(scope);
       module.get_status()
     };
@@ -1727,9 +2751,7 @@ impl JsRuntime {
     // For more details see:
     // https://github.com/denoland/deno/issues/4908
     // https://v8.dev/features/top-level-await#module-execution-order
-    let global_realm =
-      self.inner.state.borrow_mut().global_realm.clone().unwrap();
-    let scope = &mut global_realm.handle_scope(&mut self.inner.v8_isolate);
+    let scope = &mut realm.handle_scope(&mut self.inner.v8_isolate);
     let tc_scope = &mut v8::TryCatch::new(scope);
     let module = v8::Local::new(tc_scope, &module_handle);
     let maybe_value = module.evaluate(tc_scope);
@@ -1750,6 +2772,7 @@ impl JsRuntime {
       let module_global = v8::Global::new(tc_scope, module);
 
       let dyn_import_mod_evaluate = DynImportModEvaluate {
+        realm: realm.clone(),
         load_id,
         module_id: id,
         promise: promise_global,
@@ -1788,10 +2811,30 @@ impl JsRuntime {
     &mut self,
     id: ModuleId,
   ) -> oneshot::Receiver<Result<(), Error>> {
-    let global_realm = self.global_realm();
+    let realm = self.global_realm_inner();
+    self.mod_evaluate_in_realm_inner(&realm, id)
+  }
+
+  /// Evaluates an already instantiated ES module loaded in `realm`, see
+  /// [`JsRuntime::mod_evaluate`].
+  ///
+  /// This function panics if module has not been instantiated.
+  pub fn mod_evaluate_in_realm(
+    &mut self,
+    realm: &JsRealm,
+    id: ModuleId,
+  ) -> oneshot::Receiver<Result<(), Error>> {
+    self.mod_evaluate_in_realm_inner(&realm.0, id)
+  }
+
+  fn mod_evaluate_in_realm_inner(
+    &mut self,
+    realm: &JsRealmInner,
+    id: ModuleId,
+  ) -> oneshot::Receiver<Result<(), Error>> {
     let state_rc = self.inner.state.clone();
-    let module_map_rc = self.module_map.clone();
-    let scope = &mut self.handle_scope();
+    let module_map_rc = realm.module_map();
+    let scope = &mut realm.handle_scope(self.v8_isolate());
     let tc_scope = &mut v8::TryCatch::new(scope);
 
     let module = module_map_rc
@@ -1876,8 +2919,7 @@ impl JsRuntime {
           .handled_promise_rejections
           .contains(&promise_global);
         if !pending_rejection_was_already_handled {
-          global_realm
-            .0
+          realm
             .state()
             .borrow_mut()
             .pending_promise_rejections
@@ -1928,11 +2970,12 @@ impl JsRuntime {
 
   fn dynamic_import_reject(
     &mut self,
+    realm: &JsRealmInner,
     id: ModuleLoadId,
     exception: v8::Global<v8::Value>,
   ) {
-    let module_map_rc = self.module_map.clone();
-    let scope = &mut self.handle_scope();
+    let module_map_rc = realm.module_map();
+    let scope = &mut realm.handle_scope(self.v8_isolate());
 
     let resolver_handle = module_map_rc
       .borrow_mut()
@@ -1950,10 +2993,15 @@ impl JsRuntime {
     scope.perform_microtask_checkpoint();
   }
 
-  fn dynamic_import_resolve(&mut self, id: ModuleLoadId, mod_id: ModuleId) {
+  fn dynamic_import_resolve(
+    &mut self,
+    realm: &JsRealmInner,
+    id: ModuleLoadId,
+    mod_id: ModuleId,
+  ) {
     let state_rc = self.inner.state.clone();
-    let module_map_rc = self.module_map.clone();
-    let scope = &mut self.handle_scope();
+    let module_map_rc = realm.module_map();
+    let scope = &mut realm.handle_scope(self.v8_isolate());
 
     let resolver_handle = module_map_rc
       .borrow_mut()
@@ -1984,20 +3032,16 @@ impl JsRuntime {
 
   fn prepare_dyn_imports(
     &mut self,
+    realm: &JsRealmInner,
     cx: &mut Context,
   ) -> Poll<Result<(), Error>> {
-    if self
-      .module_map
-      .borrow()
-      .preparing_dynamic_imports
-      .is_empty()
-    {
+    let module_map_rc = realm.module_map();
+    if module_map_rc.borrow().preparing_dynamic_imports.is_empty() {
       return Poll::Ready(Ok(()));
     }
 
     loop {
-      let poll_result = self
-        .module_map
+      let poll_result = module_map_rc
         .borrow_mut()
         .preparing_dynamic_imports
         .poll_next_unpin(cx);
@@ -2008,15 +3052,15 @@ impl JsRuntime {
 
         match prepare_result {
           Ok(load) => {
-            self
-              .module_map
+            module_map_rc
               .borrow_mut()
               .pending_dynamic_imports
               .push(load.into_future());
           }
           Err(err) => {
-            let exception = to_v8_type_error(&mut self.handle_scope(), err);
-            self.dynamic_import_reject(dyn_import_id, exception);
+            let exception =
+              to_v8_type_error(&mut realm.handle_scope(self.v8_isolate()), err);
+            self.dynamic_import_reject(realm, dyn_import_id, exception);
           }
         }
         // Continue polling for more prepared dynamic imports.
@@ -2028,14 +3072,18 @@ impl JsRuntime {
     }
   }
 
-  fn poll_dyn_imports(&mut self, cx: &mut Context) -> Poll<Result<(), Error>> {
-    if self.module_map.borrow().pending_dynamic_imports.is_empty() {
+  fn poll_dyn_imports(
+    &mut self,
+    realm: &JsRealmInner,
+    cx: &mut Context,
+  ) -> Poll<Result<(), Error>> {
+    let module_map_rc = realm.module_map();
+    if module_map_rc.borrow().pending_dynamic_imports.is_empty() {
       return Poll::Ready(Ok(()));
     }
 
     loop {
-      let poll_result = self
-        .module_map
+      let poll_result = module_map_rc
         .borrow_mut()
         .pending_dynamic_imports
         .poll_next_unpin(cx);
@@ -2052,7 +3100,7 @@ impl JsRuntime {
               // fetched. Create and register it, and if successful, poll for the
               // next recursive-load event related to this dynamic import.
               let register_result = load.register_and_recurse(
-                &mut self.handle_scope(),
+                &mut realm.handle_scope(self.v8_isolate()),
                 &request,
                 info,
               );
@@ -2060,8 +3108,7 @@ impl JsRuntime {
               match register_result {
                 Ok(()) => {
                   // Keep importing until it's fully drained
-                  self
-                    .module_map
+                  module_map_rc
                     .borrow_mut()
                     .pending_dynamic_imports
                     .push(load.into_future());
@@ -2069,11 +3116,12 @@ impl JsRuntime {
                 Err(err) => {
                   let exception = match err {
                     ModuleError::Exception(e) => e,
-                    ModuleError::Other(e) => {
-                      to_v8_type_error(&mut self.handle_scope(), e)
-                    }
+                    ModuleError::Other(e) => to_v8_type_error(
+                      &mut realm.handle_scope(self.v8_isolate()),
+                      e,
+                    ),
                   };
-                  self.dynamic_import_reject(dyn_import_id, exception)
+                  self.dynamic_import_reject(realm, dyn_import_id, exception)
                 }
               }
             }
@@ -2081,8 +3129,11 @@ impl JsRuntime {
               // A non-javascript error occurred; this could be due to a an invalid
               // module specifier, or a problem with the source map, or a failure
               // to fetch the module source code.
-              let exception = to_v8_type_error(&mut self.handle_scope(), err);
-              self.dynamic_import_reject(dyn_import_id, exception);
+              let exception = to_v8_type_error(
+                &mut realm.handle_scope(self.v8_isolate()),
+                err,
+              );
+              self.dynamic_import_reject(realm, dyn_import_id, exception);
             }
           }
         } else {
@@ -2090,11 +3141,15 @@ impl JsRuntime {
           // Load is done.
           let module_id =
             load.root_module_id.expect("Root module should be loaded");
-          let result = self.instantiate_module(module_id);
+          let result = self.instantiate_module_in_realm(realm, module_id);
           if let Err(exception) = result {
-            self.dynamic_import_reject(dyn_import_id, exception);
+            self.dynamic_import_reject(realm, dyn_import_id, exception);
           }
-          self.dynamic_import_module_evaluate(dyn_import_id, module_id)?;
+          self.dynamic_import_module_evaluate(
+            realm,
+            dyn_import_id,
+            module_id,
+          )?;
         }
 
         // Continue polling for more ready dynamic imports.
@@ -2106,6 +3161,47 @@ impl JsRuntime {
     }
   }
 
+  /// Settles the promises returned by asynchronous `import.meta.resolve()`
+  /// calls whose resolution has completed.
+  fn poll_import_meta_resolves(
+    &mut self,
+    realm: &JsRealmInner,
+    cx: &mut Context,
+  ) {
+    let module_map_rc = realm.module_map();
+    if !module_map_rc.borrow().has_pending_import_meta_resolves() {
+      return;
+    }
+
+    let scope = &mut realm.handle_scope(self.v8_isolate());
+    loop {
+      let poll_result = module_map_rc
+        .borrow_mut()
+        .pending_import_meta_resolves
+        .poll_next_unpin(cx);
+
+      let (resolver, result) = match poll_result {
+        Poll::Ready(Some(resolution)) => resolution,
+        _ => break,
+      };
+
+      let resolver = resolver.open(scope);
+      match result {
+        Ok(specifier) => {
+          let value = serde_v8::to_v8(scope, specifier.as_str()).unwrap();
+          resolver.resolve(scope, value).unwrap();
+        }
+        Err(err) => {
+          let exception = to_v8_type_error(scope, err);
+          let exception = v8::Local::new(scope, exception);
+          resolver.reject(scope, exception).unwrap();
+        }
+      }
+    }
+
+    scope.perform_microtask_checkpoint();
+  }
+
   /// "deno_core" runs V8 with Top Level Await enabled. It means that each
   /// module evaluation returns a promise from V8.
   /// Feature docs: https://v8.dev/features/top-level-await
@@ -2178,8 +3274,9 @@ impl JsRuntime {
     let mut resolved_any = false;
     let mut still_pending = vec![];
     for pending_dyn_evaluate in pending {
+      let realm = pending_dyn_evaluate.realm.clone();
       let maybe_result = {
-        let scope = &mut self.handle_scope();
+        let scope = &mut realm.handle_scope(self.v8_isolate());
 
         let module_id = pending_dyn_evaluate.module_id;
         let promise = pending_dyn_evaluate.promise.open(scope);
@@ -2206,10 +3303,10 @@ impl JsRuntime {
         resolved_any = true;
         match result {
           Ok((dyn_import_id, module_id)) => {
-            self.dynamic_import_resolve(dyn_import_id, module_id);
+            self.dynamic_import_resolve(&realm, dyn_import_id, module_id);
           }
           Err((dyn_import_id, exception)) => {
-            self.dynamic_import_reject(dyn_import_id, exception);
+            self.dynamic_import_reject(&realm, dyn_import_id, exception);
           }
         }
       }
@@ -2230,47 +3327,46 @@ impl JsRuntime {
     specifier: &ModuleSpecifier,
     code: Option<ModuleCode>,
   ) -> Result<ModuleId, Error> {
-    let module_map_rc = self.module_map.clone();
-    if let Some(code) = code {
-      let specifier = specifier.as_str().to_owned().into();
-      let scope = &mut self.handle_scope();
-      // true for main module
-      module_map_rc
-        .borrow_mut()
-        .new_es_module(scope, true, specifier, code, false)
-        .map_err(|e| match e {
-          ModuleError::Exception(exception) => {
-            let exception = v8::Local::new(scope, exception);
-            exception_to_err_result::<()>(scope, exception, false).unwrap_err()
-          }
-          ModuleError::Other(error) => error,
-        })?;
-    }
-
-    let mut load =
-      ModuleMap::load_main(module_map_rc.clone(), &specifier).await?;
+    let realm = self.global_realm_inner();
+    self
+      .load_module_in_realm(&realm, specifier, code, true)
+      .await
+  }
 
-    while let Some(load_result) = load.next().await {
-      let (request, info) = load_result?;
-      let scope = &mut self.handle_scope();
-      load.register_and_recurse(scope, &request, info).map_err(
-        |e| match e {
-          ModuleError::Exception(exception) => {
-            let exception = v8::Local::new(scope, exception);
-            exception_to_err_result::<()>(scope, exception, false).unwrap_err()
-          }
-          ModuleError::Other(error) => error,
-        },
-      )?;
+  /// Asynchronously load specified module and all of its dependencies as the
+  /// new main module, replacing the current one.
+  ///
+  /// This is meant for REPL-style embedders that need to run an updated entry
+  /// point in the same runtime. Even if `specifier` is the same as the current
+  /// main module's, it is loaded and compiled again; its dependencies are
+  /// reused from the module map.
+  ///
+  /// The current main module is only replaced once the new one is loaded and
+  /// instantiated. If that fails, the current main module is kept.
+  ///
+  /// User must call [`JsRuntime::mod_evaluate`] with returned `ModuleId`
+  /// manually after load is finished.
+  pub async fn replace_main_module(
+    &mut self,
+    specifier: &ModuleSpecifier,
+    code: Option<ModuleCode>,
+  ) -> Result<ModuleId, Error> {
+    let realm = self.global_realm_inner();
+    let module_map_rc = realm.module_map();
+    // The current main module has to be hidden while loading, so the same
+    // specifier is compiled again instead of resolving to it.
+    let current = module_map_rc.borrow_mut().clear_main_module();
+    let result = self
+      .load_module_in_realm(&realm, specifier, code, true)
+      .await;
+    if let Some(current) = current {
+      let mut module_map = module_map_rc.borrow_mut();
+      match result {
+        Ok(_) => module_map.forget_module(current),
+        Err(_) => module_map.restore_main_module(current),
+      }
     }
-
-    let root_id = load.root_module_id.expect("Root module should be loaded");
-    self.instantiate_module(root_id).map_err(|e| {
-      let scope = &mut self.handle_scope();
-      let exception = v8::Local::new(scope, e);
-      exception_to_err_result::<()>(scope, exception, false).unwrap_err()
-    })?;
-    Ok(root_id)
+    result
   }
 
   /// Asynchronously load specified ES module and all of its dependencies.
@@ -2285,14 +3381,60 @@ impl JsRuntime {
     specifier: &ModuleSpecifier,
     code: Option<ModuleCode>,
   ) -> Result<ModuleId, Error> {
-    let module_map_rc = self.module_map.clone();
+    let realm = self.global_realm_inner();
+    self
+      .load_module_in_realm(&realm, specifier, code, false)
+      .await
+  }
+
+  /// Asynchronously load specified module and all of its dependencies into
+  /// the module map of `realm`, marking it as the realm's main module.
+  ///
+  /// The returned `ModuleId` is only meaningful for `realm`; user must call
+  /// [`JsRuntime::mod_evaluate_in_realm`] with it manually after load is
+  /// finished.
+  pub async fn load_main_module_in_realm(
+    &mut self,
+    realm: &JsRealm,
+    specifier: &ModuleSpecifier,
+    code: Option<ModuleCode>,
+  ) -> Result<ModuleId, Error> {
+    self
+      .load_module_in_realm(&realm.0, specifier, code, true)
+      .await
+  }
+
+  /// Asynchronously load specified ES module and all of its dependencies
+  /// into the module map of `realm`.
+  ///
+  /// The returned `ModuleId` is only meaningful for `realm`; user must call
+  /// [`JsRuntime::mod_evaluate_in_realm`] with it manually after load is
+  /// finished.
+  pub async fn load_side_module_in_realm(
+    &mut self,
+    realm: &JsRealm,
+    specifier: &ModuleSpecifier,
+    code: Option<ModuleCode>,
+  ) -> Result<ModuleId, Error> {
+    self
+      .load_module_in_realm(&realm.0, specifier, code, false)
+      .await
+  }
+
+  async fn load_module_in_realm(
+    &mut self,
+    realm: &JsRealmInner,
+    specifier: &ModuleSpecifier,
+    code: Option<ModuleCode>,
+    main: bool,
+  ) -> Result<ModuleId, Error> {
+    let module_map_rc = realm.module_map();
     if let Some(code) = code {
       let specifier = specifier.as_str().to_owned().into();
-      let scope = &mut self.handle_scope();
-      // false for side module (not main module)
+      let scope = &mut realm.handle_scope(self.v8_isolate());
       module_map_rc
         .borrow_mut()
-        .new_es_module(scope, false, specifier, code, false)
+        .new_es_module(scope, main, specifier, code, false)
         .map_err(|e| match e {
           ModuleError::Exception(exception) => {
             let exception = v8::Local::new(scope, exception);
@@ -2302,12 +3444,93 @@ impl JsRuntime {
         })?;
     }
 
-    let mut load =
-      ModuleMap::load_side(module_map_rc.clone(), &specifier).await?;
+    let load = if main {
+      ModuleMap::load_main(module_map_rc.clone(), &specifier).await?
+    } else {
+      ModuleMap::load_side(module_map_rc.clone(), &specifier).await?
+    };
+    let root_id = self.fetch_module_graph_in_realm(realm, load).await?;
+
+    if main && !module_map_rc.borrow().info[root_id].main {
+      // The module was already registered by `JsRuntime::prefetch_module`.
+      module_map_rc.borrow_mut().set_main_module(root_id)?;
+    }
+
+    self
+      .instantiate_module_in_realm(realm, root_id)
+      .map_err(|e| {
+        let scope = &mut realm.handle_scope(self.v8_isolate());
+        let exception = v8::Local::new(scope, e);
+        exception_to_err_result::<()>(scope, exception, false).unwrap_err()
+      })?;
+    Ok(root_id)
+  }
+
+  /// Asynchronously fetch and compile specified module and all of its
+  /// dependencies, without instantiating or evaluating any of them.
+  ///
+  /// This can be used to warm up the module map ahead of time. A later
+  /// [`JsRuntime::load_main_module`] or [`JsRuntime::load_side_module`] for
+  /// the same specifier will reuse the compiled modules instead of invoking
+  /// the module loader again.
+  pub async fn prefetch_module(
+    &mut self,
+    specifier: &ModuleSpecifier,
+  ) -> Result<ModuleId, Error> {
+    let realm = self.global_realm_inner();
+    let load = ModuleMap::prefetch(realm.module_map(), &specifier).await?;
+    self.fetch_module_graph_in_realm(&realm, load).await
+  }
 
+  /// Add every module in the global realm's module map, along with its code
+  /// cache and the redirects that were followed to load it, to `writer`.
+  ///
+  /// The runtime must be created with
+  /// [`RuntimeOptions::retain_module_sources`] set, so the modules are
+  /// written as they were loaded, without going through the module loader
+  /// again. Extension modules (`ext:`) are skipped, since they are provided
+  /// by extensions or the snapshot.
+  pub fn write_module_archive(
+    &mut self,
+    writer: &mut ModuleArchiveWriter,
+  ) -> Result<(), Error> {
+    {
+      let module_map_rc = self.global_realm_inner().module_map();
+      let module_map = module_map_rc.borrow();
+      for info in &module_map.info {
+        let name = info.name.as_str();
+        if name.starts_with("ext:") {
+          continue;
+        }
+        let Some(source) = module_map.get_retained_source(info.id) else {
+          return Err(generic_error(format!(
+            "The source of module \"{name}\" was not retained, create the runtime with `RuntimeOptions::retain_module_sources` set"
+          )));
+        };
+        writer.add_module(
+          &ModuleSpecifier::parse(name)?,
+          info.module_type,
+          source.code.as_str(),
+          source.code_cache.as_ref().map(|data| data.to_vec()),
+        );
+      }
+    }
+    for (from, to) in self.module_aliases() {
+      writer.add_redirect(&from, &to);
+    }
+    Ok(())
+  }
+
+  /// Drive `load` to completion, registering every module of the graph in
+  /// the module map of `realm`, and return the id of the root module.
+  async fn fetch_module_graph_in_realm(
+    &mut self,
+    realm: &JsRealmInner,
+    mut load: RecursiveModuleLoad,
+  ) -> Result<ModuleId, Error> {
     while let Some(load_result) = load.next().await {
       let (request, info) = load_result?;
-      let scope = &mut self.handle_scope();
+      let scope = &mut realm.handle_scope(self.v8_isolate());
       load.register_and_recurse(scope, &request, info).map_err(
         |e| match e {
           ModuleError::Exception(exception) => {
@@ -2319,13 +3542,7 @@ impl JsRuntime {
       )?;
     }
 
-    let root_id = load.root_module_id.expect("Root module should be loaded");
-    self.instantiate_module(root_id).map_err(|e| {
-      let scope = &mut self.handle_scope();
-      let exception = v8::Local::new(scope, e);
-      exception_to_err_result::<()>(scope, exception, false).unwrap_err()
-    })?;
-    Ok(root_id)
+    Ok(load.root_module_id.expect("Root module should be loaded"))
   }
 
   fn check_promise_rejections(&mut self) -> Result<(), Error> {
@@ -2338,12 +3555,32 @@ impl JsRuntime {
     Ok(())
   }
 
+  /// In the deterministic mode, moves the virtual clock to the next deadline
+  /// once every pending op is waiting on it.
+  fn advance_deterministic_clock(&self, cx: &mut Context) {
+    let state = self.inner.state.borrow();
+    let Some(clock) = &state.deterministic_clock else {
+      return;
+    };
+    // Ops that start sleeping after this check wake the event loop up again.
+    clock.register_sleep_waker(cx.waker());
+    let pending_ops = state
+      .known_realms
+      .iter()
+      .map(|realm| realm.num_pending_ops())
+      .sum::<usize>();
+    if pending_ops > 0 && clock.pending_sleeps() >= pending_ops {
+      clock.advance_to_next_sleep();
+    }
+  }
+
   // Polls pending ops and then runs `Deno.core.eventLoopTick` callback.
   fn do_js_event_loop_tick(&mut self, cx: &mut Context) -> Result<(), Error> {
     // Handle responses for each realm.
     let state = self.inner.state.clone();
     let isolate = &mut self.inner.v8_isolate;
     let realm_count = state.borrow().known_realms.len();
+    let deterministic = state.borrow().deterministic.is_some();
     for realm_idx in 0..realm_count {
       let realm = state.borrow().known_realms.get(realm_idx).unwrap().clone();
       let context_state = realm.state();
@@ -2363,6 +3600,7 @@ impl JsRuntime {
       let mut args: SmallVec<[v8::Local<v8::Value>; 32]> =
         SmallVec::with_capacity(32);
 
+      let mut completed: SmallVec<[_; 16]> = SmallVec::new();
       loop {
         let item = {
           let next = std::pin::pin!(context_state.pending_ops.join_next());
@@ -2371,13 +3609,24 @@ impl JsRuntime {
           };
           item
         };
-        let (promise_id, op_id, mut resp) = item.unwrap().into_inner();
+        completed.push(item.unwrap().into_inner());
+      }
+      if deterministic {
+        // Promise ids are handed out in dispatch order.
+        completed.sort_unstable_by_key(|(promise_id, ..)| *promise_id);
+      }
+
+      for (promise_id, op_id, mut resp, latency) in completed {
         state
           .borrow()
           .op_state
           .borrow()
           .tracker
-          .track_async_completed(op_id);
+          .track_async_completed(
+            op_id,
+            latency,
+            matches!(resp, OpResult::Err(_)),
+          );
         context_state.unrefed_ops.remove(&promise_id);
         args.push(v8::Integer::new(scope, promise_id).into());
         args.push(match resp.to_v8(scope) {
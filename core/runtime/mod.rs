@@ -4,7 +4,9 @@ mod jsrealm;
 mod jsruntime;
 #[doc(hidden)]
 pub mod ops;
+mod pool;
 mod snapshot_util;
+mod stack_trace;
 
 #[cfg(test)]
 mod tests;
@@ -15,20 +17,40 @@ pub const V8_WRAPPER_OBJECT_INDEX: i32 = 1;
 pub(crate) use jsrealm::ContextState;
 pub use jsrealm::JsRealm;
 pub use jsruntime::CompiledWasmModuleStore;
+pub use jsruntime::CoverageRange;
+pub use jsruntime::CpuProfile;
 pub use jsruntime::CrossIsolateStore;
+pub use jsruntime::DeterministicOptions;
+pub use jsruntime::EventLoopStats;
+pub use jsruntime::FunctionCoverage;
 pub(crate) use jsruntime::InitMode;
+pub use jsruntime::IsolateConfig;
 pub use jsruntime::JsRuntime;
 pub use jsruntime::JsRuntimeForSnapshot;
 pub use jsruntime::JsRuntimeState;
+pub use jsruntime::MemoryPressureLevel;
+pub use jsruntime::MicrotaskPolicy;
+pub use jsruntime::ModuleCodeCache;
+pub use jsruntime::NearHeapLimitAction;
+pub use jsruntime::NearHeapLimitCallback;
+pub use jsruntime::NearHeapLimitInfo;
+pub use jsruntime::PromiseHook;
 pub use jsruntime::RuntimeOptions;
 pub use jsruntime::RuntimeSnapshotOptions;
 pub use jsruntime::SharedArrayBufferStore;
 pub use jsruntime::Snapshot;
+pub use jsruntime::UnhandledRejectionAction;
+pub use jsruntime::UnhandledRejectionCallback;
+pub use jsruntime::WasmModuleCache;
+pub use pool::JsRuntimePool;
+pub use pool::JsRuntimePoolOptions;
+pub use pool::PooledJsRuntime;
 pub use snapshot_util::create_snapshot;
 pub use snapshot_util::get_js_files;
 pub use snapshot_util::CreateSnapshotOptions;
 pub use snapshot_util::CreateSnapshotOutput;
 pub use snapshot_util::FilterFn;
+pub use stack_trace::current_stack_trace;
 pub(crate) use snapshot_util::SnapshottedData;
 
 pub use bindings::script_origin;
@@ -19,17 +19,20 @@ pub fn queue_fast_async_op<R: serde::Serialize + 'static>(
   promise_id: PromiseId,
   op: impl Future<Output = Result<R, Error>> + 'static,
 ) {
-  let get_class = {
-    let state = RefCell::borrow(&ctx.state);
-    state.tracker.track_async(ctx.id);
-    state.get_error_class_fn
-  };
+  let state = RefCell::borrow(&ctx.state);
+  state.tracker.track_async(ctx.id);
+  let get_class = state.get_error_class_fn;
+  let started = state.tracker.start_timer();
   let fut = op
     .map(|result| crate::_ops::to_op_result(get_class, result))
     .boxed_local();
+  let fut = state.apply_op_dispatch_policy(ctx.decl.name, fut);
+  drop(state);
   // SAFETY: this this is guaranteed to be running on a current-thread executor
   ctx.context_state.borrow_mut().pending_ops.spawn(unsafe {
-    crate::task::MaskFutureAsSend::new(OpCall::pending(ctx, promise_id, fut))
+    crate::task::MaskFutureAsSend::new(OpCall::pending(
+      ctx, promise_id, started, fut,
+    ))
   });
 }
 
@@ -119,6 +122,17 @@ pub fn queue_async_op<'s>(
   //   Some(scope.get_current_context())
   // );
 
+  let started = {
+    let state = RefCell::borrow(&ctx.state);
+    op = match op {
+      MaybeDone::Future(fut) => {
+        MaybeDone::Future(state.apply_op_dispatch_policy(ctx.decl.name, fut))
+      }
+      op => op,
+    };
+    state.tracker.start_timer()
+  };
+
   // All ops are polled immediately
   let waker = noop_waker();
   let mut cx = Context::from_waker(&waker);
@@ -129,18 +143,22 @@ pub fn queue_async_op<'s>(
       let MaybeDone::Future(fut) = op else {
         unreachable!()
       };
-      OpCall::pending(ctx, promise_id, fut)
+      OpCall::pending(ctx, promise_id, started, fut)
     }
     Poll::Ready(_) => {
       let mut op_result = Pin::new(&mut op).take_output().unwrap();
       // If the op is ready and is not marked as deferred we can immediately return
       // the result.
       if !deferred {
-        ctx.state.borrow_mut().tracker.track_async_completed(ctx.id);
+        ctx.state.borrow_mut().tracker.track_async_completed(
+          ctx.id,
+          started.map(|started| started.elapsed()),
+          matches!(op_result, OpResult::Err(_)),
+        );
         return Some(op_result.to_v8(scope).unwrap());
       }
 
-      OpCall::ready(ctx, promise_id, op_result)
+      OpCall::ready(ctx, promise_id, started, op_result)
     }
   };
 
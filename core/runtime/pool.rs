@@ -0,0 +1,206 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::JsRealm;
+use crate::JsRuntime;
+use crate::ResourceId;
+use anyhow::Error;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::ops::Deref;
+use std::ops::DerefMut;
+
+/// Options of a [`JsRuntimePool`].
+#[derive(Clone, Copy, Debug)]
+pub struct JsRuntimePoolOptions {
+  /// Number of idle runtimes [`JsRuntimePool::fill`] creates ahead of time.
+  pub size: usize,
+  /// Number of requests a runtime serves before it is retired. `1` gives
+  /// every request a fresh runtime.
+  pub max_uses: usize,
+}
+
+impl Default for JsRuntimePoolOptions {
+  fn default() -> Self {
+    Self {
+      size: 4,
+      max_uses: 1,
+    }
+  }
+}
+
+/// A pool of pre-created runtimes for hosts that run every request in its own
+/// isolate.
+///
+/// Runtimes are created with the factory passed to [`JsRuntimePool::new`],
+/// typically booting from a snapshot. A `JsRuntime` can't move between
+/// threads, so the pool lives on the thread that runs the requests; call
+/// [`JsRuntimePool::fill`] while that thread is idle to create runtimes ahead
+/// of the next requests.
+///
+/// Every acquired runtime comes with a fresh realm, [`PooledJsRuntime::realm`],
+/// that the request's code should run in. A runtime given back with
+/// [`JsRuntimePool::release`] is reset and reused until it reaches
+/// [`JsRuntimePoolOptions::max_uses`]. Resetting destroys the realm of the
+/// request, along with its globals and modules, and any other realm created
+/// while the runtime was acquired, and closes the resources opened meanwhile.
+///
+/// Nothing that runs in the main realm can be undone, so a runtime that
+/// executed scripts or loaded modules in its main realm, or that still has
+/// pending work in its event loop, is retired instead.
+pub struct JsRuntimePool {
+  create: Box<dyn Fn() -> JsRuntime>,
+  options: JsRuntimePoolOptions,
+  idle: VecDeque<PooledJsRuntime>,
+}
+
+impl JsRuntimePool {
+  pub fn new(
+    options: JsRuntimePoolOptions,
+    create: impl Fn() -> JsRuntime + 'static,
+  ) -> Self {
+    Self {
+      create: Box::new(create),
+      options,
+      idle: VecDeque::with_capacity(options.size),
+    }
+  }
+
+  /// Number of idle runtimes ready to be acquired.
+  pub fn idle(&self) -> usize {
+    self.idle.len()
+  }
+
+  /// Creates runtimes until [`JsRuntimePoolOptions::size`] of them are idle.
+  pub fn fill(&mut self) -> Result<(), Error> {
+    while self.idle.len() < self.options.size {
+      let runtime = self.create()?;
+      self.idle.push_back(runtime);
+    }
+    Ok(())
+  }
+
+  /// Takes an idle runtime out of the pool, or creates one if none is idle.
+  pub fn acquire(&mut self) -> Result<PooledJsRuntime, Error> {
+    let mut runtime = match self.idle.pop_front() {
+      Some(runtime) => runtime,
+      None => self.create()?,
+    };
+    runtime.uses += 1;
+    Ok(runtime)
+  }
+
+  /// Gives a runtime back to the pool, resetting it for reuse or retiring it.
+  /// Dropping a [`PooledJsRuntime`] instead retires it.
+  pub fn release(&mut self, mut runtime: PooledJsRuntime) {
+    if runtime.uses >= self.options.max_uses
+      || self.idle.len() >= self.options.size
+      || !runtime.reset()
+    {
+      return;
+    }
+    self.idle.push_back(runtime);
+  }
+
+  fn create(&self) -> Result<PooledJsRuntime, Error> {
+    PooledJsRuntime::new((self.create)())
+  }
+}
+
+/// A runtime acquired from a [`JsRuntimePool`].
+pub struct PooledJsRuntime {
+  runtime: JsRuntime,
+  realm: JsRealm,
+  uses: usize,
+  resources: HashSet<ResourceId>,
+  realms: usize,
+  modules: usize,
+  scripts: usize,
+}
+
+impl PooledJsRuntime {
+  fn new(mut runtime: JsRuntime) -> Result<Self, Error> {
+    let resources = runtime
+      .op_state()
+      .borrow()
+      .resource_table
+      .names()
+      .map(|(rid, _)| rid)
+      .collect();
+    let realms = runtime.num_realms();
+    let modules = runtime.num_modules();
+    let scripts = runtime.num_executed_scripts();
+    let realm = runtime.create_realm()?;
+    Ok(Self {
+      runtime,
+      realm,
+      uses: 0,
+      resources,
+      realms,
+      modules,
+      scripts,
+    })
+  }
+
+  /// The realm created for the current request. Run the request's code in
+  /// this realm rather than in the main one, so the runtime can be reused.
+  pub fn realm(&self) -> &JsRealm {
+    &self.realm
+  }
+
+  /// Number of requests this runtime was acquired for.
+  pub fn uses(&self) -> usize {
+    self.uses
+  }
+
+  /// Consumes the pooled runtime, so it is never returned to the pool.
+  pub fn into_inner(self) -> JsRuntime {
+    self.runtime
+  }
+
+  /// Restores the runtime to the state it was created in and creates a fresh
+  /// realm for the next request. Returns false if that isn't possible because
+  /// the main realm was used.
+  fn reset(&mut self) -> bool {
+    if self.runtime.num_modules() != self.modules
+      || self.runtime.num_executed_scripts() != self.scripts
+      || self.runtime.event_loop_pending_state().is_pending()
+    {
+      return false;
+    }
+    self.runtime.destroy_realms_after(self.realms);
+
+    let op_state = self.runtime.op_state();
+    let mut op_state = op_state.borrow_mut();
+    let resource_table = &mut op_state.resource_table;
+    let opened = resource_table
+      .names()
+      .map(|(rid, _)| rid)
+      .filter(|rid| !self.resources.contains(rid))
+      .collect::<Vec<_>>();
+    for rid in opened {
+      let _ = resource_table.close(rid);
+    }
+    drop(op_state);
+
+    match self.runtime.create_realm() {
+      Ok(realm) => {
+        self.realm = realm;
+        true
+      }
+      Err(_) => false,
+    }
+  }
+}
+
+impl Deref for PooledJsRuntime {
+  type Target = JsRuntime;
+
+  fn deref(&self) -> &JsRuntime {
+    &self.runtime
+  }
+}
+
+impl DerefMut for PooledJsRuntime {
+  fn deref_mut(&mut self) -> &mut JsRuntime {
+    &mut self.runtime
+  }
+}
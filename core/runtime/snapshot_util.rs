@@ -4,6 +4,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::time::Instant;
 
+use crate::modules::ModuleMapSnapshotData;
 use crate::runtime::RuntimeSnapshotOptions;
 use crate::ExtModuleLoaderCb;
 use crate::Extension;
@@ -154,7 +155,7 @@ fn data_error_to_panic(err: v8::DataError) -> ! {
 }
 
 pub(crate) struct SnapshottedData {
-  pub module_map_data: v8::Global<v8::Array>,
+  pub module_map_data: ModuleMapSnapshotData,
   pub module_handles: Vec<v8::Global<v8::Module>>,
 }
 
@@ -166,10 +167,10 @@ pub(crate) fn get_snapshotted_data(
 ) -> SnapshottedData {
   let mut scope = v8::ContextScope::new(scope, context);
 
-  // The 0th element is the module map itself, followed by X number of module
-  // handles. We need to deserialize the "next_module_id" field from the
-  // map to see how many module handles we expect.
-  let result = scope.get_context_data_from_snapshot_once::<v8::Array>(
+  // The 0th element is the encoded module map itself, followed by X number of
+  // module handles. The header of the encoded map tells us how many module
+  // handles we expect.
+  let result = scope.get_context_data_from_snapshot_once::<v8::ArrayBuffer>(
     MODULE_MAP_CONTEXT_DATA_INDEX,
   );
 
@@ -177,17 +178,22 @@ pub(crate) fn get_snapshotted_data(
     Ok(v) => v,
     Err(err) => data_error_to_panic(err),
   };
+  let data = val
+    .get_backing_store()
+    .iter()
+    .map(|byte| byte.get())
+    .collect::<Vec<u8>>();
 
-  let next_module_id = {
-    let info_data: v8::Local<v8::Array> =
-      val.get_index(&mut scope, 1).unwrap().try_into().unwrap();
-    info_data.length()
+  let module_map_data = match ModuleMapSnapshotData::decode(&data) {
+    Ok(module_map_data) => module_map_data,
+    Err(err) => panic!("Failed to restore module map from snapshot: {err}"),
   };
+  let module_count = module_map_data.info.len();
 
   // Over allocate so executing a few scripts doesn't have to resize this vec.
-  let mut module_handles = Vec::with_capacity(next_module_id as usize + 16);
-  for i in 1..=next_module_id {
-    match scope.get_context_data_from_snapshot_once::<v8::Module>(i as usize) {
+  let mut module_handles = Vec::with_capacity(module_count + 16);
+  for i in 1..=module_count {
+    match scope.get_context_data_from_snapshot_once::<v8::Module>(i) {
       Ok(val) => {
         let module_global = v8::Global::new(&mut scope, val);
         module_handles.push(module_global);
@@ -197,7 +203,7 @@ pub(crate) fn get_snapshotted_data(
   }
 
   SnapshottedData {
-    module_map_data: v8::Global::new(&mut scope, val),
+    module_map_data,
     module_handles,
   }
 }
@@ -208,7 +214,10 @@ pub(crate) fn set_snapshotted_data(
   snapshotted_data: SnapshottedData,
 ) {
   let local_context = v8::Local::new(scope, context);
-  let local_data = v8::Local::new(scope, snapshotted_data.module_map_data);
+  let data = snapshotted_data.module_map_data.encode();
+  let backing_store =
+    v8::ArrayBuffer::new_backing_store_from_vec(data).make_shared();
+  let local_data = v8::ArrayBuffer::with_backing_store(scope, &backing_store);
   let offset = scope.add_context_data(local_context, local_data);
   assert_eq!(offset, MODULE_MAP_CONTEXT_DATA_INDEX);
 
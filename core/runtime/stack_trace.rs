@@ -0,0 +1,109 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::error::JsStackFrame;
+use crate::source_map::apply_source_map;
+use crate::source_map::SourceMapper;
+use std::cell::RefCell;
+use std::ptr::NonNull;
+
+/// The isolate of a runtime alive on this thread and the context of its main
+/// realm.
+struct LiveIsolate {
+  isolate: NonNull<v8::Isolate>,
+  context: v8::Global<v8::Context>,
+}
+
+thread_local! {
+  /// The isolates of the runtimes alive on this thread, oldest first.
+  static LIVE_ISOLATES: RefCell<Vec<LiveIsolate>> = RefCell::new(Vec::new());
+}
+
+pub(crate) fn register_isolate(
+  isolate: &mut v8::Isolate,
+  context: v8::Global<v8::Context>,
+) {
+  LIVE_ISOLATES.with(|live| {
+    live.borrow_mut().push(LiveIsolate {
+      isolate: NonNull::from(isolate),
+      context,
+    })
+  });
+}
+
+/// Must be called before the isolate is disposed, as the context handle of
+/// the registration is dropped with it.
+pub(crate) fn unregister_isolate(isolate: &mut v8::Isolate) {
+  let isolate = NonNull::from(isolate);
+  // The runtime might be dropped while the thread locals are destroyed.
+  let _ = LIVE_ISOLATES.try_with(|live| {
+    live.borrow_mut().retain(|entry| entry.isolate != isolate)
+  });
+}
+
+/// Returns the call stack of the JavaScript running on this thread, innermost
+/// frame first, with at most `frame_limit` frames. This is meant for ops and
+/// the code they call, which don't have a scope at hand. The stack is empty
+/// when no JavaScript is running, e.g. when a future of an async op is
+/// polled by the event loop.
+///
+/// Like the frames of a [`JsError`](crate::error::JsError), the frames are
+/// source mapped.
+pub fn current_stack_trace(frame_limit: usize) -> Vec<JsStackFrame> {
+  LIVE_ISOLATES.with(|live| {
+    // Only the isolate running JavaScript has frames on its stack. An op of
+    // one runtime might drive another one, so the newest takes precedence.
+    for entry in live.borrow().iter().rev() {
+      // SAFETY: The runtime unregisters its isolate before disposing it. The
+      // scopes open on the isolate belong to callers further up the stack, so
+      // a callback scope is opened on top of them like V8 callbacks do.
+      let isolate = unsafe { &mut *entry.isolate.as_ptr() };
+      let scope = &mut unsafe { v8::CallbackScope::new(isolate) };
+      let scope = &mut v8::HandleScope::new(scope);
+      let context = v8::Local::new(scope, &entry.context);
+      let scope = &mut v8::ContextScope::new(scope, context);
+      let stack = v8::StackTrace::current_stack_trace(scope, frame_limit);
+      let stack = match stack {
+        Some(stack) if stack.get_frame_count() > 0 => stack,
+        _ => continue,
+      };
+      let mut frames = (0..stack.get_frame_count())
+        .filter_map(|i| stack.get_frame(scope, i))
+        .map(|frame| {
+          let file_name = frame
+            .get_script_name_or_source_url(scope)
+            .map(|name| name.to_rust_string_lossy(scope));
+          let mut js_frame = JsStackFrame::from_location(
+            file_name,
+            Some(frame.get_line_number() as i64),
+            Some(frame.get_column() as i64),
+          );
+          js_frame.function_name = frame
+            .get_function_name(scope)
+            .map(|name| name.to_rust_string_lossy(scope))
+            .filter(|name| !name.is_empty());
+          js_frame
+        })
+        .collect::<Vec<_>>();
+      if let Some((source_mapper, cache)) = SourceMapper::from_scope(scope) {
+        let mut cache = cache.borrow_mut();
+        for frame in &mut frames {
+          let Some(file_name) = frame.file_name.take() else {
+            continue;
+          };
+          let (file_name, line_number, column_number) = apply_source_map(
+            file_name,
+            frame.line_number.unwrap(),
+            frame.column_number.unwrap(),
+            &mut cache,
+            &source_mapper,
+          );
+          frame.file_name = Some(file_name);
+          frame.line_number = Some(line_number);
+          frame.column_number = Some(column_number);
+        }
+      }
+      return frames;
+    }
+    Vec::new()
+  })
+}
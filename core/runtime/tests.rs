@@ -4,6 +4,7 @@ use crate::error::custom_error;
 use crate::error::generic_error;
 use crate::error::AnyError;
 use crate::error::JsError;
+use crate::error::JsStackFrame;
 use crate::extensions::OpDecl;
 use crate::include_ascii_string;
 use crate::module_specifier::ModuleSpecifier;
@@ -26,6 +27,7 @@ use futures::future::poll_fn;
 use futures::future::Future;
 use futures::FutureExt;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::atomic::AtomicUsize;
@@ -33,6 +35,7 @@ use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
 
 // deno_ops macros generate code assuming deno_core in scope.
 mod deno_core {
@@ -502,6 +505,130 @@ async fn test_serialize_deserialize() {
   .await;
 }
 
+#[test]
+fn test_structured_clone_between_runtimes() {
+  let store = SharedArrayBufferStore::default();
+  let mut sender = JsRuntime::new(RuntimeOptions {
+    shared_array_buffer_store: Some(store.clone()),
+    ..Default::default()
+  });
+  let mut receiver = JsRuntime::new(RuntimeOptions {
+    shared_array_buffer_store: Some(store),
+    ..Default::default()
+  });
+
+  let message = sender
+    .execute_script_static(
+      "send.js",
+      "globalThis.buf = new Uint8Array([1, 2, 3]).buffer; \
+       ({ text: 'hello', buf: globalThis.buf, date: new Date(0) })",
+    )
+    .unwrap();
+  let cloned = {
+    let scope = &mut sender.handle_scope();
+    let message = v8::Local::new(scope, message);
+    let global = scope.get_current_context().global(scope);
+    let key = v8::String::new(scope, "buf").unwrap();
+    let buf = global.get(scope, key.into()).unwrap();
+    let buf = v8::Local::<v8::ArrayBuffer>::try_from(buf).unwrap();
+    structured_serialize(scope, message, &[buf], Default::default()).unwrap()
+  };
+  assert_eq!(cloned.transferred_array_buffers.len(), 1);
+  let detached = sender
+    .execute_script_static("check.js", "globalThis.buf.byteLength")
+    .unwrap();
+  {
+    let scope = &mut sender.handle_scope();
+    assert_eq!(detached.open(scope).integer_value(scope), Some(0));
+  }
+
+  let scope = &mut receiver.handle_scope();
+  let (value, array_buffers) = structured_deserialize(
+    scope,
+    &cloned.data,
+    &cloned.transferred_array_buffers,
+    Default::default(),
+  )
+  .unwrap();
+  assert_eq!(array_buffers.len(), 1);
+  assert_eq!(array_buffers[0].byte_length(), 3);
+  let value = v8::Local::<v8::Object>::try_from(value).unwrap();
+  let key = v8::String::new(scope, "text").unwrap();
+  let text = value.get(scope, key.into()).unwrap();
+  assert_eq!(text.to_rust_string_lossy(scope), "hello");
+  let key = v8::String::new(scope, "date").unwrap();
+  assert!(value.get(scope, key.into()).unwrap().is_date());
+}
+
+#[test]
+fn test_transfer_array_buffers_between_threads() {
+  let mut sender = JsRuntime::new(Default::default());
+  let buffers = sender
+    .execute_script_static(
+      "send.js",
+      "globalThis.buf = new Uint8Array([1, 2, 3]).buffer; \
+       globalThis.shared = new SharedArrayBuffer(1); \
+       [globalThis.buf, globalThis.shared]",
+    )
+    .unwrap();
+  let (transferred, shared) = {
+    let scope = &mut sender.handle_scope();
+    let buffers =
+      v8::Local::<v8::Array>::try_from(v8::Local::new(scope, buffers)).unwrap();
+    let buf = buffers.get_index(scope, 0).unwrap();
+    let buf = v8::Local::<v8::ArrayBuffer>::try_from(buf).unwrap();
+    let transferred = TransferredArrayBuffer::detach(buf).unwrap();
+    assert!(TransferredArrayBuffer::detach(buf).is_err());
+    let shared = buffers.get_index(scope, 1).unwrap();
+    let shared = v8::Local::<v8::SharedArrayBuffer>::try_from(shared).unwrap();
+    (transferred, SharedArrayBufferHandle::new(shared))
+  };
+  assert_eq!(transferred.len(), 3);
+
+  std::thread::spawn(move || {
+    let mut receiver = JsRuntime::new(Default::default());
+    let scope = &mut receiver.handle_scope();
+    let buf = transferred.into_array_buffer(scope);
+    let bytes = v8::Uint8Array::new(scope, buf, 0, 3).unwrap();
+    let mut contents = [0; 3];
+    bytes.copy_contents(&mut contents);
+    assert_eq!(contents, [1, 2, 3]);
+    let shared = shared.to_shared_array_buffer(scope);
+    let global = scope.get_current_context().global(scope);
+    let key = v8::String::new(scope, "shared").unwrap();
+    global.set(scope, key.into(), shared.into());
+    let code =
+      v8::String::new(scope, "new Uint8Array(shared)[0] = 42").unwrap();
+    v8::Script::compile(scope, code, None)
+      .unwrap()
+      .run(scope)
+      .unwrap();
+  })
+  .join()
+  .unwrap();
+
+  sender
+    .execute_script_static(
+      "check.js",
+      "if (globalThis.buf.byteLength !== 0) throw new Error('not detached'); \
+       if (new Uint8Array(globalThis.shared)[0] !== 42) throw new Error('not shared');",
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_structured_serialize_error() {
+  let mut runtime = JsRuntime::new(Default::default());
+  let value = runtime
+    .execute_script_static("fn.js", "(function () {})")
+    .unwrap();
+  let scope = &mut runtime.handle_scope();
+  let value = v8::Local::new(scope, value);
+  let err =
+    structured_serialize(scope, value, &[], Default::default()).unwrap_err();
+  assert!(err.to_string().contains("could not be cloned"));
+}
+
 #[tokio::test]
 async fn test_error_builder() {
   #[op]
@@ -534,6 +661,97 @@ async fn test_error_builder() {
   .await;
 }
 
+#[tokio::test]
+async fn test_error_class_registry() {
+  #[derive(Debug)]
+  struct DbError {
+    table: &'static str,
+  }
+
+  impl std::fmt::Display for DbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+      write!(f, "no such table: {}", self.table)
+    }
+  }
+
+  impl std::error::Error for DbError {}
+
+  #[op]
+  fn op_db_sync() -> Result<(), Error> {
+    Err(DbError { table: "users" }.into())
+  }
+
+  #[op]
+  async fn op_db_async() -> Result<(), Error> {
+    Err(DbError { table: "posts" }.into())
+  }
+
+  #[op]
+  fn op_other_err() -> Result<(), Error> {
+    Err(generic_error("other"))
+  }
+
+  let mut error_class_registry = ErrorClassRegistry::new();
+  error_class_registry.register_with_properties::<DbError, _>(
+    "DbError",
+    |err| {
+      let mut properties = ErrorProperties::new();
+      properties.insert("table".to_string(), err.table.into());
+      properties
+    },
+  );
+
+  deno_core::extension!(
+    test_ext,
+    ops = [op_db_sync, op_db_async, op_other_err]
+  );
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    extensions: vec![test_ext::init_ops()],
+    error_class_registry: Some(error_class_registry),
+    ..Default::default()
+  });
+  runtime
+    .execute_script_static(
+      "error_class_registry.js",
+      r#"
+      class DbError extends Error {}
+      Deno.core.registerErrorClass("DbError", DbError);
+
+      try {
+        Deno.core.ops.op_db_sync();
+        throw new Error("op_db_sync didn't throw!");
+      } catch (err) {
+        if (!(err instanceof DbError) || err.table !== "users") {
+          throw new Error("sync error is incorrect");
+        }
+        if (err.message !== "no such table: users") {
+          throw new Error("err.message is incorrect");
+        }
+      }
+
+      try {
+        Deno.core.ops.op_other_err();
+      } catch (err) {
+        if (err instanceof DbError || "table" in err) {
+          throw new Error("unregistered error is incorrect");
+        }
+      }
+
+      Deno.core.opAsync("op_db_async").catch((err) => {
+        globalThis.asyncErrorOk = err instanceof DbError &&
+          err.table === "posts";
+      });
+      "#,
+    )
+    .unwrap();
+  runtime.run_event_loop(false).await.unwrap();
+  let value_global = runtime
+    .execute_script_static("check.js", "globalThis.asyncErrorOk")
+    .unwrap();
+  let scope = &mut runtime.handle_scope();
+  assert!(value_global.open(scope).is_true());
+}
+
 /// Ensure that putting the inspector into OpState doesn't cause crashes. The only valid place we currently allow
 /// the inspector to be stashed without cleanup is the OpState, and this should not actually cause crashes.
 #[test]
@@ -547,6 +765,102 @@ fn inspector() {
   runtime.execute_script_static("check.js", "null").unwrap();
 }
 
+#[test]
+fn inspector_debugger_session() {
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    inspector: true,
+    ..Default::default()
+  });
+  let mut session = runtime.inspector().borrow().create_debugger_session();
+  let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+
+  let debugger = std::thread::spawn(move || {
+    futures::executor::block_on(async move {
+      session.enable().await.unwrap();
+      let specifier = ModuleSpecifier::parse("file:///debugger.js").unwrap();
+      let breakpoint =
+        session.set_breakpoint(&specifier, 3, None).await.unwrap();
+      ready_tx.send(()).unwrap();
+
+      let Some(DebuggerEvent::Paused {
+        hit_breakpoints,
+        call_frames,
+        ..
+      }) = session.next_event().await
+      else {
+        panic!("expected the debugger to pause");
+      };
+      assert_eq!(hit_breakpoints, vec![breakpoint.id]);
+      assert_eq!(call_frames[0].function_name, "add");
+      assert_eq!(call_frames[0].location.line_number, 3);
+      let value = session
+        .evaluate_on_call_frame(&call_frames[0].call_frame_id, "a + b + 1")
+        .await
+        .unwrap();
+      assert_eq!(value, serde_json::json!(6));
+      session.resume().await.unwrap();
+    })
+  });
+
+  // Let the inspector connect the session and answer its messages.
+  while ready_rx.try_recv().is_err() {
+    let _ = runtime.inspector().borrow().poll_sessions(None);
+    std::thread::sleep(std::time::Duration::from_millis(1));
+  }
+  runtime
+    .execute_script_static(
+      "file:///debugger.js",
+      "function add(a, b) {\n  const sum = a + b;\n  return sum;\n}\nglobalThis.result = add(2, 3);",
+    )
+    .unwrap();
+  debugger.join().unwrap();
+}
+
+#[tokio::test]
+async fn cpu_profile() {
+  let mut runtime = JsRuntime::new(Default::default());
+  assert!(runtime.stop_cpu_profile().await.is_err());
+
+  runtime.start_cpu_profile("busy").await.unwrap();
+  assert!(runtime.start_cpu_profile("again").await.is_err());
+  runtime
+    .execute_script_static(
+      "busy.js",
+      "let x = 0; for (let i = 0; i < 1e6; i++) { x += i; }",
+    )
+    .unwrap();
+  let profile = runtime.stop_cpu_profile().await.unwrap();
+  assert_eq!(profile.name, "busy");
+  assert!(profile.profile["nodes"].is_array());
+  assert!(profile.profile["samples"].is_array());
+  assert!(profile.profile["startTime"].is_number());
+}
+
+#[tokio::test]
+async fn precise_coverage() {
+  let mut runtime = JsRuntime::new(Default::default());
+  assert!(runtime.take_precise_coverage().await.is_err());
+
+  runtime.start_precise_coverage(true).await.unwrap();
+  runtime
+    .execute_script_static(
+      "file:///covered.js",
+      "function called() {} function uncalled() {} called(); called();",
+    )
+    .unwrap();
+  let coverage = runtime.take_precise_coverage().await.unwrap();
+  let functions = &coverage["file:///covered.js"];
+  let count = |name: &str| {
+    functions
+      .iter()
+      .find(|function| function.function_name == name)
+      .map(|function| function.ranges[0].count)
+  };
+  assert_eq!(count("called"), Some(2));
+  assert_eq!(count("uncalled").unwrap_or(0), 0);
+  runtime.stop_precise_coverage().await.unwrap();
+}
+
 #[test]
 fn will_snapshot() {
   let snapshot = {
@@ -566,6 +880,29 @@ fn will_snapshot() {
     .unwrap();
 }
 
+#[test]
+fn snapshot_any_runtime() {
+  let snapshot = {
+    let mut runtime = JsRuntime::new(RuntimeOptions {
+      will_snapshot: true,
+      ..Default::default()
+    });
+    runtime.execute_script_static("a.js", "a = 1 + 2").unwrap();
+    runtime.snapshot().unwrap()
+  };
+
+  let mut runtime2 = JsRuntime::new(RuntimeOptions {
+    startup_snapshot: Some(Snapshot::JustCreated(snapshot)),
+    ..Default::default()
+  });
+  runtime2
+    .execute_script_static("check.js", "if (a != 3) throw Error('x')")
+    .unwrap();
+
+  let err = runtime2.snapshot().unwrap_err();
+  assert!(err.to_string().contains("will_snapshot"), "{err}");
+}
+
 #[test]
 fn will_snapshot2() {
   let startup_data = {
@@ -687,6 +1024,7 @@ fn test_get_module_namespace() {
       _module_specifier: &ModuleSpecifier,
       _maybe_referrer: Option<&ModuleSpecifier>,
       _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
     ) -> Pin<Box<ModuleSourceFuture>> {
       async { Err(generic_error("Module loading is not supported")) }
         .boxed_local()
@@ -779,6 +1117,104 @@ fn test_heap_limits() {
   assert!(callback_invoke_count.load(Ordering::SeqCst) > 0)
 }
 
+#[test]
+fn test_isolate_config() {
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    isolate_config: IsolateConfig {
+      max_heap_size: Some(16 * 1024 * 1024),
+      stack_trace_limit: Some(3),
+      ..Default::default()
+    },
+    ..Default::default()
+  });
+  let mut stats = v8::HeapStatistics::default();
+  runtime.v8_isolate().get_heap_statistics(&mut stats);
+  assert!(stats.heap_size_limit() < 64 * 1024 * 1024);
+
+  let err = runtime
+    .execute_script_static(
+      "stack_trace_limit.js",
+      r#"
+      if (Error.stackTraceLimit !== 3) {
+        throw new Error("Error.stackTraceLimit is incorrect");
+      }
+      function recurse(n) {
+        if (n === 0) throw new Error("deep");
+        recurse(n - 1);
+      }
+      recurse(10);
+      "#,
+    )
+    .unwrap_err();
+  let js_error = err.downcast::<JsError>().unwrap();
+  assert_eq!(js_error.exception_message, "Uncaught Error: deep");
+  assert_eq!(js_error.frames.len(), 3);
+}
+
+#[test]
+fn test_microtask_policy_and_promise_hook() {
+  #[derive(Default)]
+  struct RecordingHook(RefCell<Vec<&'static str>>);
+
+  impl PromiseHook for RecordingHook {
+    fn init(
+      &self,
+      _scope: &mut v8::HandleScope,
+      _promise: v8::Local<v8::Promise>,
+      _parent: v8::Local<v8::Value>,
+    ) {
+      self.0.borrow_mut().push("init");
+    }
+
+    fn before(
+      &self,
+      _scope: &mut v8::HandleScope,
+      _promise: v8::Local<v8::Promise>,
+    ) {
+      self.0.borrow_mut().push("before");
+    }
+
+    fn after(
+      &self,
+      _scope: &mut v8::HandleScope,
+      _promise: v8::Local<v8::Promise>,
+    ) {
+      self.0.borrow_mut().push("after");
+    }
+  }
+
+  let hook = Rc::new(RecordingHook::default());
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    microtask_policy: MicrotaskPolicy::Explicit,
+    promise_hook: Some(hook.clone()),
+    ..Default::default()
+  });
+  runtime
+    .execute_script_static(
+      "microtask_policy.js",
+      "globalThis.ran = false; Promise.resolve().then(() => { globalThis.ran = true; });",
+    )
+    .unwrap();
+  runtime
+    .execute_script_static(
+      "check.js",
+      "if (globalThis.ran) throw new Error('microtask ran too early');",
+    )
+    .unwrap();
+
+  hook.0.borrow_mut().clear();
+  runtime.v8_isolate().perform_microtask_checkpoint();
+  runtime
+    .execute_script_static(
+      "check.js",
+      "if (!globalThis.ran) throw new Error('microtask did not run');",
+    )
+    .unwrap();
+  let events = hook.0.borrow();
+  assert!(events.contains(&"before"));
+  assert!(events.contains(&"after"));
+}
+
 #[test]
 fn test_heap_limit_cb_remove() {
   let mut runtime = JsRuntime::new(Default::default());
@@ -829,6 +1265,134 @@ fn test_heap_limit_cb_multiple() {
   assert!(callback_invoke_count_second.load(Ordering::SeqCst) > 0);
 }
 
+#[test]
+fn test_near_heap_limit_callback_option() {
+  let create_params =
+    v8::Isolate::create_params().heap_limits(0, 5 * 1024 * 1024);
+  let infos = Rc::new(RefCell::new(vec![]));
+  let infos_ = infos.clone();
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    create_params: Some(create_params),
+    near_heap_limit_callback: Some(Box::new(move |info| {
+      infos_.borrow_mut().push(info);
+      NearHeapLimitAction::Terminate
+    })),
+    ..Default::default()
+  });
+  let err = runtime
+    .execute_script_static(
+      "script name",
+      r#"let s = ""; while(true) { s += "Hello"; }"#,
+    )
+    .expect_err("script should fail");
+  assert_eq!(
+    "Uncaught Error: execution terminated",
+    err.downcast::<JsError>().unwrap().exception_message
+  );
+  let infos = infos.borrow();
+  assert!(!infos.is_empty());
+  assert!(infos[0].used_heap_size > 0);
+  assert!(infos[0].current_heap_limit >= infos[0].initial_heap_limit);
+}
+
+#[test]
+fn test_memory_pressure_notification() {
+  let levels = Rc::new(RefCell::new(vec![]));
+  let levels_ = levels.clone();
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    memory_pressure_listener: Some(Box::new(move |level| {
+      levels_.borrow_mut().push(level);
+    })),
+    ..Default::default()
+  });
+  runtime.memory_pressure_notification(MemoryPressureLevel::Moderate);
+  runtime.memory_pressure_notification(MemoryPressureLevel::Critical);
+  assert_eq!(
+    *levels.borrow(),
+    vec![MemoryPressureLevel::Moderate, MemoryPressureLevel::Critical]
+  );
+}
+
+#[test]
+fn test_resource_external_memory() {
+  struct NativeBuffer;
+  impl Resource for NativeBuffer {}
+
+  fn external_memory(runtime: &mut JsRuntime) -> usize {
+    let mut stats = v8::HeapStatistics::default();
+    runtime.v8_isolate().get_heap_statistics(&mut stats);
+    stats.external_memory()
+  }
+
+  let mut runtime = JsRuntime::new(Default::default());
+  let op_state = runtime.op_state();
+  let baseline = external_memory(&mut runtime);
+
+  let rid = op_state.borrow_mut().resource_table.add(NativeBuffer);
+  {
+    let mut op_state = op_state.borrow_mut();
+    let resource_table = &mut op_state.resource_table;
+    resource_table.adjust_external_memory(rid, 4 << 20).unwrap();
+    resource_table
+      .adjust_external_memory(rid, -(1 << 20))
+      .unwrap();
+    assert_eq!(resource_table.external_memory(rid), 3 << 20);
+    assert_eq!(resource_table.total_external_memory(), 3 << 20);
+    assert!(resource_table.adjust_external_memory(rid + 1, 1).is_err());
+    resource_table.report_external_memory(runtime.v8_isolate());
+  }
+  assert_eq!(external_memory(&mut runtime), baseline + (3 << 20));
+
+  // Freeing more than was allocated doesn't go below zero.
+  op_state
+    .borrow_mut()
+    .resource_table
+    .adjust_external_memory(rid, -(8 << 20))
+    .unwrap();
+  assert_eq!(op_state.borrow().resource_table.external_memory(rid), 0);
+  op_state
+    .borrow_mut()
+    .resource_table
+    .adjust_external_memory(rid, 2 << 20)
+    .unwrap();
+
+  // Closing the resource releases what's left, reported on the next tick.
+  op_state.borrow_mut().resource_table.close(rid).unwrap();
+  assert_eq!(op_state.borrow().resource_table.total_external_memory(), 0);
+  futures::executor::block_on(runtime.run_event_loop(false)).unwrap();
+  assert_eq!(external_memory(&mut runtime), baseline);
+}
+
+#[test]
+fn test_typed_resource_ids_and_leaks() {
+  struct File;
+  impl Resource for File {}
+  struct Socket;
+  impl Resource for Socket {}
+
+  let mut resource_table = ResourceTable::default();
+  let untracked = resource_table.add(File);
+  resource_table.track_leaks();
+
+  let file = resource_table.add_typed(File);
+  let socket = resource_table.add_typed(Socket);
+  assert!(resource_table.get_typed(file).is_ok());
+  assert!(resource_table.get::<Socket>(file.rid()).is_err());
+  // A rid from JavaScript can't be trusted to refer to the right type.
+  let confused = TypedResourceId::<Socket>::new(file.rid());
+  assert!(resource_table.get_typed(confused).is_err());
+
+  resource_table.take_typed(socket).unwrap();
+  let leaked = resource_table.leaked_resources();
+  assert_eq!(leaked.len(), 1);
+  assert_eq!(leaked[0].rid, file.rid());
+  assert_ne!(leaked[0].rid, untracked);
+  assert!(!leaked[0].backtrace.is_empty());
+
+  resource_table.close(file.rid()).unwrap();
+  assert!(resource_table.leaked_resources().is_empty());
+}
+
 #[test]
 fn es_snapshot() {
   #[derive(Default)]
@@ -850,6 +1414,7 @@ fn es_snapshot() {
       _module_specifier: &ModuleSpecifier,
       _maybe_referrer: Option<&ModuleSpecifier>,
       _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
     ) -> Pin<Box<ModuleSourceFuture>> {
       eprintln!("load() should not be called");
       unreachable!()
@@ -1514,6 +2079,7 @@ fn terminate_during_module_eval() {
       _module_specifier: &ModuleSpecifier,
       _maybe_referrer: Option<&ModuleSpecifier>,
       _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
     ) -> Pin<Box<ModuleSourceFuture>> {
       async move {
         Ok(ModuleSource::for_test(
@@ -1567,34 +2133,119 @@ async fn test_unhandled_rejection_order() {
 }
 
 #[tokio::test]
-async fn test_set_promise_reject_callback() {
-  static PROMISE_REJECT: AtomicUsize = AtomicUsize::new(0);
-
-  #[op]
-  fn op_promise_reject() -> Result<(), AnyError> {
-    PROMISE_REJECT.fetch_add(1, Ordering::Relaxed);
-    Ok(())
-  }
-
-  deno_core::extension!(test_ext, ops = [op_promise_reject]);
+async fn test_unhandled_rejection_callback() {
+  let seen = Rc::new(RefCell::new(vec![]));
+  let seen_ = seen.clone();
   let mut runtime = JsRuntime::new(RuntimeOptions {
-    extensions: vec![test_ext::init_ops()],
+    unhandled_rejection_callback: Some(Rc::new(move |err: &JsError| {
+      seen_.borrow_mut().push(err.exception_message.clone());
+      if err.exception_message.ends_with("fatal") {
+        UnhandledRejectionAction::Escalate
+      } else {
+        UnhandledRejectionAction::Swallow
+      }
+    })),
     ..Default::default()
   });
-
   runtime
     .execute_script_static(
-      "promise_reject_callback.js",
+      "",
       r#"
-      // Note: |promise| is not the promise created below, it's a child.
-      Deno.core.ops.op_set_promise_reject_callback((type, promise, reason) => {
-        if (type !== /* PromiseRejectWithNoHandler */ 0) {
-          throw Error("unexpected type: " + type);
-        }
-        if (reason.message !== "reject") {
-          throw Error("unexpected reason: " + reason);
-        }
-        Deno.core.ops.op_store_pending_promise_rejection(promise);
+      Promise.reject("first");
+      Promise.reject("second");
+      "#,
+    )
+    .unwrap();
+  runtime.run_event_loop(false).await.unwrap();
+  assert_eq!(
+    *seen.borrow(),
+    vec![
+      "Uncaught (in promise) first",
+      "Uncaught (in promise) second"
+    ]
+  );
+
+  runtime
+    .execute_script_static("", r#"Promise.reject("fatal");"#)
+    .unwrap();
+  let err = runtime.run_event_loop(false).await.unwrap_err();
+  assert_eq!(err.to_string(), "Uncaught (in promise) fatal");
+}
+
+#[tokio::test]
+async fn test_event_loop_stats() {
+  struct TestResource;
+  impl Resource for TestResource {}
+
+  let mut runtime = JsRuntime::new(Default::default());
+  let rid = runtime
+    .op_state()
+    .borrow_mut()
+    .resource_table
+    .add(TestResource);
+  runtime
+    .execute_script_static(
+      "event_loop_stats.js",
+      r#"
+      for (let i = 0; i < 3; i++) {
+        Deno.core.opAsync("op_void_async_deferred");
+      }
+      globalThis.stats = Deno.core.eventLoopStats();
+      "#,
+    )
+    .unwrap();
+
+  let stats = runtime.event_loop_stats();
+  assert_eq!(stats.pending_ops.get("op_void_async_deferred"), Some(&3));
+  assert_eq!(stats.unrefed_ops, 0);
+  assert!(stats.resources[&rid].ends_with("TestResource"));
+  assert_eq!(stats.pending_dynamic_imports, 0);
+
+  let value_global = runtime
+    .execute_script_static(
+      "check.js",
+      "globalThis.stats.pendingOps.op_void_async_deferred",
+    )
+    .unwrap();
+  {
+    let scope = &mut runtime.handle_scope();
+    let value = value_global.open(scope);
+    assert_eq!(value.uint32_value(scope), Some(3));
+  }
+
+  runtime.run_event_loop(false).await.unwrap();
+  assert!(runtime.event_loop_stats().pending_ops.is_empty());
+}
+
+#[tokio::test]
+async fn test_set_promise_reject_callback() {
+  static PROMISE_REJECT: AtomicUsize = AtomicUsize::new(0);
+
+  #[op]
+  fn op_promise_reject() -> Result<(), AnyError> {
+    PROMISE_REJECT.fetch_add(1, Ordering::Relaxed);
+    Ok(())
+  }
+
+  deno_core::extension!(test_ext, ops = [op_promise_reject]);
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    extensions: vec![test_ext::init_ops()],
+    ..Default::default()
+  });
+
+  runtime
+    .execute_script_static(
+      "promise_reject_callback.js",
+      r#"
+      // Note: |promise| is not the promise created below, it's a child.
+      Deno.core.ops.op_set_promise_reject_callback((type, promise, reason) => {
+        if (type !== /* PromiseRejectWithNoHandler */ 0) {
+          throw Error("unexpected type: " + type);
+        }
+        if (reason.message !== "reject") {
+          throw Error("unexpected reason: " + reason);
+        }
+        Deno.core.ops.op_store_pending_promise_rejection(promise);
         Deno.core.ops.op_promise_reject();
       });
       new Promise((_, reject) => reject(Error("reject")));
@@ -1703,6 +2354,7 @@ async fn test_set_promise_reject_callback_top_level_await() {
       _module_specifier: &ModuleSpecifier,
       _maybe_referrer: Option<&ModuleSpecifier>,
       _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
     ) -> Pin<Box<ModuleSourceFuture>> {
       let code = r#"
       Deno.core.ops.op_set_promise_reject_callback((type, promise, reason) => {
@@ -1777,6 +2429,162 @@ fn test_op_high_arity() {
   assert_eq!(r.open(scope).integer_value(scope), Some(10));
 }
 
+#[tokio::test]
+async fn test_op_metrics_hook() {
+  #[op]
+  fn op_sync_noop() {}
+
+  #[op]
+  async fn op_async_fail() -> Result<(), anyhow::Error> {
+    tokio::task::yield_now().await;
+    Err(generic_error("failed"))
+  }
+
+  #[derive(Default)]
+  struct Hook(RefCell<Vec<(&'static str, &'static str)>>);
+
+  impl OpMetricsHook for Hook {
+    fn on_op_event(
+      &self,
+      _op_id: OpId,
+      op_name: &'static str,
+      event: OpMetricsEvent,
+    ) {
+      let event = match event {
+        OpMetricsEvent::CompletedSync => "sync",
+        OpMetricsEvent::DispatchedAsync => "dispatched",
+        OpMetricsEvent::CompletedAsync(_) => "completed",
+        OpMetricsEvent::ErrorAsync(_) => "error",
+      };
+      self.0.borrow_mut().push((op_name, event));
+    }
+  }
+
+  deno_core::extension!(test_ext, ops = [op_sync_noop, op_async_fail]);
+  let hook = Rc::new(Hook::default());
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    extensions: vec![test_ext::init_ops()],
+    op_metrics_hook: Some(hook.clone()),
+    ..Default::default()
+  });
+  runtime
+    .execute_script_static(
+      "test.js",
+      r#"
+      Deno.core.ops.op_sync_noop();
+      Deno.core.opAsync("op_async_fail").catch(() => {});
+      "#,
+    )
+    .unwrap();
+  runtime.run_event_loop(false).await.unwrap();
+
+  let events = hook
+    .0
+    .borrow()
+    .iter()
+    .filter(|(name, _)| *name == "op_sync_noop" || *name == "op_async_fail")
+    .copied()
+    .collect::<Vec<_>>();
+  assert_eq!(
+    events,
+    vec![
+      ("op_sync_noop", "sync"),
+      ("op_async_fail", "dispatched"),
+      ("op_async_fail", "error"),
+    ]
+  );
+
+  let state = runtime.op_state();
+  let metrics = state.borrow().tracker.per_op_detailed().unwrap();
+  let op = metrics
+    .iter()
+    .find(|metrics| metrics.name == "op_async_fail")
+    .unwrap();
+  assert_eq!(op.dispatched, 1);
+  assert_eq!(op.completed, 1);
+  assert_eq!(op.errors, 1);
+  assert_eq!(op.latency.count, 1);
+  assert_eq!(op.latency.buckets.iter().sum::<u64>(), 1);
+}
+
+#[test]
+fn test_op_metrics_disabled_by_default() {
+  let mut runtime = JsRuntime::new(Default::default());
+  assert!(runtime
+    .op_state()
+    .borrow()
+    .tracker
+    .per_op_detailed()
+    .is_none());
+}
+
+#[tokio::test]
+async fn test_op_deadline_and_cancellation() {
+  #[op]
+  async fn op_never_resolves() -> Result<(), anyhow::Error> {
+    futures::future::pending().await
+  }
+
+  deno_core::extension!(test_ext, ops = [op_never_resolves]);
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    extensions: vec![test_ext::init_ops()],
+    ..Default::default()
+  });
+
+  runtime
+    .op_state()
+    .borrow_mut()
+    .set_op_deadline(Some(std::time::Duration::from_millis(10)));
+  runtime
+    .execute_script_static(
+      "deadline.js",
+      r#"
+      globalThis.deadlineError = null;
+      Deno.core.opAsync("op_never_resolves").catch((e) => {
+        globalThis.deadlineError = e.message;
+      });
+      "#,
+    )
+    .unwrap();
+  runtime.run_event_loop(false).await.unwrap();
+
+  let cancel_handle = CancelHandle::new_rc();
+  {
+    let state = runtime.op_state();
+    let mut state = state.borrow_mut();
+    state.set_op_deadline(None);
+    state.set_op_cancel_handle(Some(cancel_handle.clone()));
+  }
+  runtime
+    .execute_script_static(
+      "cancel.js",
+      r#"
+      globalThis.cancelError = null;
+      Deno.core.opAsync("op_never_resolves").catch((e) => {
+        globalThis.cancelError = e.message;
+      });
+      "#,
+    )
+    .unwrap();
+  cancel_handle.cancel();
+  runtime.run_event_loop(false).await.unwrap();
+
+  let deadline_error = runtime
+    .execute_script_static("check.js", "globalThis.deadlineError")
+    .unwrap();
+  let cancel_error = runtime
+    .execute_script_static("check.js", "globalThis.cancelError")
+    .unwrap();
+  let scope = &mut runtime.handle_scope();
+  let deadline_error = deadline_error.open(scope).to_rust_string_lossy(scope);
+  let cancel_error = cancel_error.open(scope).to_rust_string_lossy(scope);
+  assert_eq!(
+    deadline_error,
+    "Op \"op_never_resolves\" did not complete within 10ms"
+  );
+  assert_eq!(cancel_error, "Op \"op_never_resolves\" was canceled");
+}
+
 #[test]
 fn test_op_disabled() {
   #[op]
@@ -2155,6 +2963,100 @@ async fn js_realm_gc() {
   assert_eq!(Rc::strong_count(&opstate_drop_detect), 1);
 }
 
+#[tokio::test]
+async fn js_realm_module_maps() {
+  struct TenantLoader(&'static str);
+
+  impl ModuleLoader for TenantLoader {
+    fn resolve(
+      &self,
+      specifier: &str,
+      referrer: &str,
+      _kind: ResolutionKind,
+    ) -> Result<ModuleSpecifier, Error> {
+      Ok(crate::resolve_import(specifier, referrer)?)
+    }
+
+    fn load(
+      &self,
+      module_specifier: &ModuleSpecifier,
+      _maybe_referrer: Option<&ModuleSpecifier>,
+      _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
+    ) -> Pin<Box<ModuleSourceFuture>> {
+      let code = format!("export default {:?};", self.0);
+      let source = ModuleSource::new(
+        ModuleType::JavaScript,
+        code.into(),
+        module_specifier,
+      );
+      futures::future::ready(Ok(source)).boxed_local()
+    }
+  }
+
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(Rc::new(TenantLoader("global"))),
+    ..Default::default()
+  });
+  let tenants = [
+    (
+      runtime
+        .create_realm_with_loader(Rc::new(TenantLoader("a")))
+        .unwrap(),
+      "a",
+    ),
+    (
+      runtime
+        .create_realm_with_loader(Rc::new(TenantLoader("b")))
+        .unwrap(),
+      "b",
+    ),
+  ];
+
+  // Both tenants use the same specifiers, but each realm resolves and loads
+  // them through its own loader.
+  let specifier = crate::resolve_url("file:///main.js").unwrap();
+  for (realm, _) in &tenants {
+    let source_code = ascii_str!(
+      r#"
+      import config from "./config.js";
+      globalThis.config = config;
+      globalThis.dynamicConfig = import("./config.js").then((m) => m.default);
+      "#
+    );
+    let id = runtime
+      .load_main_module_in_realm(realm, &specifier, Some(source_code))
+      .await
+      .unwrap();
+    let receiver = runtime.mod_evaluate_in_realm(realm, id);
+    runtime.run_event_loop(false).await.unwrap();
+    receiver.await.unwrap().unwrap();
+  }
+
+  for (realm, name) in &tenants {
+    let config = realm
+      .execute_script_static(runtime.v8_isolate(), "", "globalThis.config")
+      .unwrap();
+    let dynamic_config = realm
+      .execute_script_static(
+        runtime.v8_isolate(),
+        "",
+        "globalThis.dynamicConfig",
+      )
+      .unwrap();
+    let dynamic_config = runtime.resolve_value(dynamic_config).await.unwrap();
+
+    let scope = &mut realm.handle_scope(runtime.v8_isolate());
+    let config = config.open(scope).to_rust_string_lossy(scope);
+    assert_eq!(&config, name);
+    let dynamic_config = dynamic_config.open(scope).to_rust_string_lossy(scope);
+    assert_eq!(&dynamic_config, name);
+  }
+
+  // Nothing leaked into the global realm's module map.
+  assert!(runtime.module_map().borrow().info.is_empty());
+}
+
 #[tokio::test]
 async fn js_realm_ref_unref_ops() {
   // Never resolves.
@@ -2304,3 +3206,448 @@ fn ops_in_js_have_proper_names() {
   "#;
   runtime.execute_script_static("test", src).unwrap();
 }
+
+#[test]
+fn test_runtime_pool() {
+  struct Request;
+
+  impl Resource for Request {}
+
+  let mut pool = JsRuntimePool::new(
+    JsRuntimePoolOptions {
+      size: 2,
+      max_uses: 2,
+    },
+    || JsRuntime::new(Default::default()),
+  );
+  pool.fill().unwrap();
+  assert_eq!(pool.idle(), 2);
+
+  let mut runtime = pool.acquire().unwrap();
+  assert_eq!(runtime.uses(), 1);
+  assert_eq!(pool.idle(), 1);
+  let rid = runtime.op_state().borrow_mut().resource_table.add(Request);
+  runtime.create_realm().unwrap();
+  let realm = runtime.realm().clone();
+  realm
+    .execute_script_static(
+      runtime.v8_isolate(),
+      "request.js",
+      "globalThis.leaked = 42;",
+    )
+    .unwrap();
+  pool.release(runtime);
+  assert_eq!(pool.idle(), 2);
+
+  // The reset runtime is handed out again after the untouched one.
+  let first = pool.acquire().unwrap();
+  let mut second = pool.acquire().unwrap();
+  assert_eq!(first.uses(), 1);
+  assert_eq!(second.uses(), 2);
+  assert!(!second.op_state().borrow().resource_table.has(rid));
+  assert_eq!(second.num_realms(), 2);
+  // Globals set by the previous request are gone with its realm.
+  let realm = second.realm().clone();
+  realm
+    .execute_script_static(
+      second.v8_isolate(),
+      "check.js",
+      "if (globalThis.leaked !== undefined) throw new Error('leaked');",
+    )
+    .unwrap();
+
+  // Reaching `max_uses` retires the runtime.
+  pool.release(second);
+  pool.release(first);
+  assert_eq!(pool.idle(), 1);
+}
+
+#[test]
+fn test_runtime_pool_main_realm_use() {
+  let mut pool = JsRuntimePool::new(
+    JsRuntimePoolOptions {
+      size: 1,
+      max_uses: 10,
+    },
+    || JsRuntime::new(Default::default()),
+  );
+
+  // Globals set in the main realm can't be removed, so the runtime is
+  // retired instead of leaking them into the next request.
+  let mut runtime = pool.acquire().unwrap();
+  runtime
+    .execute_script_static("request.js", "globalThis.leaked = 42;")
+    .unwrap();
+  pool.release(runtime);
+  assert_eq!(pool.idle(), 0);
+
+  let mut runtime = pool.acquire().unwrap();
+  assert_eq!(runtime.uses(), 1);
+  runtime
+    .execute_script_static(
+      "check.js",
+      "if (globalThis.leaked !== undefined) throw new Error('leaked');",
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_lazy_esm_extension_modules() {
+  let extension = Extension::builder("test_ext")
+    .esm(vec![ExtensionFileSource {
+      specifier: "ext:test_ext/main.js",
+      code: ExtensionFileSourceCode::IncludedInBinary(
+        "globalThis.mainRan = true;",
+      ),
+    }])
+    .lazy_esm(vec![ExtensionFileSource {
+      specifier: "ext:test_ext/lazy.js",
+      code: ExtensionFileSourceCode::IncludedInBinary(
+        "globalThis.lazyRan = true; export const value = 42;",
+      ),
+    }])
+    .esm_entry_point("ext:test_ext/main.js")
+    .build();
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    module_loader: Some(Rc::new(FsModuleLoader)),
+    extensions: vec![extension],
+    ..Default::default()
+  });
+  runtime
+    .execute_script_static(
+      "check.js",
+      "if (!globalThis.mainRan || globalThis.lazyRan) throw new Error();",
+    )
+    .unwrap();
+
+  let specifier = crate::resolve_url("file:///main.js").unwrap();
+  let source_code = ascii_str!(
+    "import { value } from 'ext:test_ext/lazy.js'; globalThis.lazyValue = value;"
+  );
+  let id = runtime
+    .load_main_module(&specifier, Some(source_code))
+    .await
+    .unwrap();
+  let receiver = runtime.mod_evaluate(id);
+  runtime.run_event_loop(false).await.unwrap();
+  receiver.await.unwrap().unwrap();
+  runtime
+    .execute_script_static(
+      "check.js",
+      "if (!globalThis.lazyRan || globalThis.lazyValue !== 42) throw new Error();",
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_wasm_module_cache() {
+  let cache = WasmModuleCache::new();
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    wasm_module_cache: Some(cache.clone()),
+    ..Default::default()
+  });
+  runtime
+    .execute_script_static(
+      "wasm_module_cache.js",
+      r#"
+      const { ops } = Deno.core;
+      // An empty module: the magic number and version.
+      const bytes = new Uint8Array([0, 97, 115, 109, 1, 0, 0, 0]);
+      const cached = [];
+      Deno.core.setWasmStreamingCallback((_source, rid) => {
+        const hit = ops.op_wasm_streaming_set_url(rid, "https://example.com/a.wasm");
+        cached.push(hit);
+        if (!hit) {
+          ops.op_wasm_streaming_feed(rid, bytes);
+          Deno.core.close(rid);
+        }
+      });
+      (async () => {
+        await WebAssembly.compileStreaming(null);
+        await WebAssembly.compileStreaming(null);
+        if (cached[0] !== false || cached[1] !== true) {
+          throw new Error(`unexpected cache hits: ${cached}`);
+        }
+      })();
+      "#,
+    )
+    .unwrap();
+  runtime.run_event_loop(false).await.unwrap();
+  assert_eq!(cache.len(), 1);
+}
+
+#[tokio::test]
+async fn test_vectored_io() {
+  // Writes at most 3 bytes at a time, and reads back what was written.
+  struct Pipe(RefCell<Vec<u8>>);
+  impl Resource for Pipe {
+    fn write(self: Rc<Self>, buf: BufView) -> AsyncResult<WriteOutcome> {
+      let n = buf.len().min(3);
+      self.0.borrow_mut().extend_from_slice(&buf[..n]);
+      let outcome = if n == buf.len() {
+        WriteOutcome::Full { nwritten: n }
+      } else {
+        WriteOutcome::Partial {
+          nwritten: n,
+          view: buf,
+        }
+      };
+      Box::pin(futures::future::ok(outcome))
+    }
+
+    fn read(self: Rc<Self>, limit: usize) -> AsyncResult<BufView> {
+      let mut data = self.0.borrow_mut();
+      let n = limit.min(data.len());
+      let chunk = data.drain(..n).collect::<Vec<_>>();
+      Box::pin(futures::future::ok(chunk.into()))
+    }
+  }
+
+  let mut runtime = JsRuntime::new(Default::default());
+  let rid = runtime
+    .op_state()
+    .borrow_mut()
+    .resource_table
+    .add(Pipe(RefCell::new(vec![])));
+  runtime
+    .execute_script(
+      "vectored_io.js",
+      format!(
+        r#"
+        (async () => {{
+          const header = new Uint8Array([1, 2, 3, 4]);
+          const body = new Uint8Array([5, 6, 7, 8, 9]);
+          const n = await Deno.core.writev({rid}, [new Uint8Array(), header, body]);
+          if (n !== 3) throw new Error(`writev wrote ${{n}} bytes`);
+          await Deno.core.writevAll({rid}, [header.subarray(3), body]);
+
+          const a = new Uint8Array(2);
+          const b = new Uint8Array(8);
+          const nread = await Deno.core.readv({rid}, [a, b]);
+          if (nread !== 2 || a.join() !== "1,2") throw new Error(`readv: ${{a}}`);
+          const rest = await Deno.core.readv({rid}, [new Uint8Array(), b]);
+          if (rest !== 7 || b.subarray(0, 7).join() !== "3,4,5,6,7,8,9") {{
+            throw new Error(`readv: ${{b}}`);
+          }}
+        }})();
+        "#
+      )
+      .into(),
+    )
+    .unwrap();
+  runtime.run_event_loop(false).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_virtual_clock() {
+  #[op]
+  async fn op_test_sleep(state: Rc<RefCell<OpState>>, millis: u64) {
+    let sleep = state.borrow().clock.sleep(Duration::from_millis(millis));
+    sleep.await
+  }
+
+  deno_core::extension!(test_ext, ops = [op_test_sleep]);
+  let epoch = std::time::UNIX_EPOCH + Duration::from_secs(1_000_000);
+  let clock = VirtualClock::new(epoch);
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    extensions: vec![test_ext::init_ops()],
+    clock: Some(Rc::new(clock.clone())),
+    ..Default::default()
+  });
+  runtime
+    .execute_script_static(
+      "virtual_clock.js",
+      r#"
+      if (Date.now() !== 1e9 || new Date().getTime() !== 1e9) {
+        throw new Error(`unexpected time: ${Date.now()}`);
+      }
+      if (new Date(0).getTime() !== 0 || !(new Date() instanceof Date)) {
+        throw new Error("Date constructor is broken");
+      }
+      (async () => {
+        await Deno.core.opAsync("op_test_sleep", 500);
+        if (Date.now() !== 1e9 + 500) {
+          throw new Error(`unexpected time: ${Date.now()}`);
+        }
+      })();
+      "#,
+    )
+    .unwrap();
+
+  let waker = futures::task::noop_waker();
+  let cx = &mut Context::from_waker(&waker);
+  assert!(runtime.poll_event_loop(cx, false).is_pending());
+  assert_eq!(clock.pending_sleeps(), 1);
+
+  assert!(clock.advance_to_next_sleep());
+  assert_eq!(clock.pending_sleeps(), 0);
+  assert_eq!(clock.elapsed(), Duration::from_millis(500));
+  runtime.run_event_loop(false).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_deterministic_mode() {
+  #[op]
+  async fn op_test_sleep(state: Rc<RefCell<OpState>>, millis: u64) {
+    let sleep = state.borrow().clock.sleep(Duration::from_millis(millis));
+    sleep.await
+  }
+
+  deno_core::extension!(test_ext, ops = [op_test_sleep]);
+
+  async fn run(seed: u64) -> String {
+    let mut runtime = JsRuntime::new(RuntimeOptions {
+      extensions: vec![test_ext::init_ops()],
+      deterministic: Some(DeterministicOptions {
+        seed,
+        ..Default::default()
+      }),
+      ..Default::default()
+    });
+    runtime
+      .execute_script_static(
+        "deterministic.js",
+        r#"
+        const events = [Date.now(), Math.random(), Math.random()];
+        Deno.core.opAsync("op_test_sleep", 200).then(() => {
+          events.push("a", Date.now());
+        });
+        Deno.core.opAsync("op_test_sleep", 100).then(() => {
+          events.push("b", Date.now());
+        });
+        Deno.core.opAsync("op_test_sleep", 100).then(() => {
+          events.push("c", Date.now());
+        });
+        globalThis.events = events;
+        "#,
+      )
+      .unwrap();
+    runtime.run_event_loop(false).await.unwrap();
+    let events = runtime
+      .execute_script_static("events.js", "JSON.stringify(events)")
+      .unwrap();
+    let scope = &mut runtime.handle_scope();
+    let events = v8::Local::new(scope, events);
+    events.to_rust_string_lossy(scope)
+  }
+
+  let first = run(42).await;
+  assert_eq!(first, run(42).await);
+  assert_ne!(first, run(43).await);
+
+  let events: Vec<serde_json::Value> = serde_json::from_str(&first).unwrap();
+  assert_eq!(events[0], 0);
+  for random in &events[1..3] {
+    let random = random.as_f64().unwrap();
+    assert!((0.0..1.0).contains(&random));
+  }
+  assert_eq!(
+    events[3..],
+    serde_json::json!(["b", 100, "c", 100, "a", 200])
+      .as_array()
+      .unwrap()[..]
+  );
+}
+
+#[test]
+fn test_current_stack_trace() {
+  #[op]
+  fn op_stack_trace(state: &mut OpState) {
+    state.put(current_stack_trace(10));
+  }
+
+  deno_core::extension!(test_ext, ops = [op_stack_trace]);
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    extensions: vec![test_ext::init_ops()],
+    ..Default::default()
+  });
+  // Another runtime alive on the thread is not running JavaScript.
+  let _other = JsRuntime::new(Default::default());
+  runtime
+    .execute_script_static(
+      "stack_trace.js",
+      r#"
+function outer() {
+  inner();
+}
+function inner() {
+  Deno.core.ops.op_stack_trace();
+}
+outer();
+"#,
+    )
+    .unwrap();
+  let frames = runtime.op_state().borrow_mut().take::<Vec<JsStackFrame>>();
+  let frames = frames
+    .iter()
+    .map(|frame| {
+      (
+        frame.function_name.as_deref(),
+        frame.file_name.as_deref(),
+        frame.line_number,
+      )
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(
+    frames,
+    vec![
+      (Some("inner"), Some("stack_trace.js"), Some(6)),
+      (Some("outer"), Some("stack_trace.js"), Some(3)),
+      (None, Some("stack_trace.js"), Some(8)),
+    ]
+  );
+  assert!(current_stack_trace(10).is_empty());
+}
+
+#[test]
+fn fast_string_op_errors_and_fallback() {
+  struct Calls(usize);
+
+  #[op(fast)]
+  fn op_utf8_len(state: &mut OpState, s: &str) -> Result<u32, AnyError> {
+    state.borrow_mut::<Calls>().0 += 1;
+    if s.is_empty() {
+      return Err(generic_error("empty string"));
+    }
+    Ok(s.len() as u32)
+  }
+
+  deno_core::extension!(
+    test_ext,
+    ops = [op_utf8_len],
+    state = |state| state.put(Calls(0))
+  );
+  let mut runtime = JsRuntime::new(RuntimeOptions {
+    extensions: vec![test_ext::init_ops()],
+    ..Default::default()
+  });
+  // Enough iterations for the op to be optimized into a fast call. "é" is a
+  // one-byte string that isn't valid UTF-8, so it falls back to the slow
+  // call, and so does the empty string to throw the error.
+  runtime
+    .execute_script_static(
+      "fast_string_op.js",
+      r#"
+function utf8Len(s) {
+  return Deno.core.ops.op_utf8_len(s);
+}
+for (let i = 0; i < 10000; i++) {
+  if (utf8Len("abc") !== 3) throw new Error("wrong length for abc");
+  const len = utf8Len("\xe9");
+  if (len !== 2) throw new Error("wrong length for \xe9: " + len);
+  let message;
+  try {
+    utf8Len("");
+  } catch (err) {
+    message = err.message;
+  }
+  if (message !== "empty string") {
+    throw new Error("op_utf8_len didn't throw: " + message);
+  }
+}
+"#,
+    )
+    .unwrap();
+  // The op ran once per call, whichever path it took.
+  assert_eq!(runtime.op_state().borrow().borrow::<Calls>().0, 30000);
+}
@@ -2,8 +2,12 @@
 
 //! This mod provides functions to remap a `JsError` based on a source map.
 
+use crate::modules::ModuleMap;
 use crate::resolve_url;
+use crate::runtime::ContextState;
+use crate::runtime::JsRuntime;
 pub use sourcemap::SourceMap;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::str;
@@ -35,6 +39,63 @@ where
   }
 }
 
+/// Source map getter used when building `JsError`s and by
+/// `op_apply_source_map`. Source maps that the module loader returned with
+/// module code take precedence over the embedder's [`SourceMapGetter`].
+pub(crate) struct SourceMapper {
+  module_map: Option<Rc<RefCell<ModuleMap>>>,
+  getter: Option<Rc<Box<dyn SourceMapGetter>>>,
+}
+
+impl SourceMapper {
+  /// Returns the source mapper for the realm of `scope` along with the
+  /// runtime's source map cache, or `None` if no source maps are available.
+  pub(crate) fn from_scope(
+    scope: &mut v8::HandleScope,
+  ) -> Option<(Self, Rc<RefCell<SourceMapCache>>)> {
+    let (getter, cache) = {
+      let state_rc = JsRuntime::state_from(scope);
+      let state = state_rc.borrow();
+      (
+        state.source_map_getter.clone(),
+        state.source_map_cache.clone(),
+      )
+    };
+    let context = scope.get_current_context();
+    let module_map = context
+      .get_slot::<Rc<RefCell<ContextState>>>(scope)
+      .and_then(|context_state| context_state.borrow().module_map.clone())
+      .filter(|module_map| module_map.borrow().has_source_maps());
+
+    if getter.is_none() && module_map.is_none() {
+      return None;
+    }
+    Some((SourceMapper { module_map, getter }, cache))
+  }
+}
+
+impl SourceMapGetter for SourceMapper {
+  fn get_source_map(&self, file_name: &str) -> Option<Vec<u8>> {
+    if let Some(module_map) = &self.module_map {
+      if let Some(source_map) = module_map.borrow().get_source_map(file_name) {
+        return Some(source_map.to_vec());
+      }
+    }
+    self.getter.as_ref()?.get_source_map(file_name)
+  }
+
+  fn get_source_line(
+    &self,
+    file_name: &str,
+    line_number: usize,
+  ) -> Option<String> {
+    self
+      .getter
+      .as_ref()?
+      .get_source_line(file_name, line_number)
+  }
+}
+
 #[derive(Debug, Default)]
 pub struct SourceMapCache {
   maps: HashMap<String, Option<SourceMap>>,
@@ -0,0 +1,392 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use crate::error::custom_error;
+use crate::error::exception_to_err_result;
+use crate::error::range_error;
+use crate::error::type_error;
+use crate::JsRuntime;
+use anyhow::Error;
+use v8::ValueDeserializerHelper;
+use v8::ValueSerializerHelper;
+
+/// Options shared by [`structured_serialize`] and [`structured_deserialize`].
+#[derive(Clone, Copy, Default)]
+pub struct StructuredCloneOptions<'s> {
+  /// Objects that can't be cloned, which are instead written as their index
+  /// in this array. The same array must be passed when deserializing.
+  pub host_objects: Option<v8::Local<'s, v8::Array>>,
+  /// Called with the message of a `DataCloneError` when serialization fails.
+  /// If it throws, the thrown value becomes the serialization error.
+  pub error_callback: Option<v8::Local<'s, v8::Function>>,
+  /// Serialize for persistent storage rather than another isolate:
+  /// `SharedArrayBuffer`s are not shared and WebAssembly modules are
+  /// rejected.
+  pub for_storage: bool,
+}
+
+/// A value serialized with [`structured_serialize`].
+#[derive(Debug, Default)]
+pub struct StructuredCloneData {
+  /// The V8 serialization of the value.
+  pub data: Vec<u8>,
+  /// Ids in the runtime's [`crate::SharedArrayBufferStore`] of the backing
+  /// stores of the transferred `ArrayBuffer`s, in transfer order.
+  pub transferred_array_buffers: Vec<u32>,
+}
+
+/// The backing store of an `ArrayBuffer` detached with
+/// [`TransferredArrayBuffer::detach`], which can be sent to another thread and
+/// turned back into an `ArrayBuffer` in any isolate without copying the
+/// contents.
+pub struct TransferredArrayBuffer(v8::SharedRef<v8::BackingStore>);
+
+impl TransferredArrayBuffer {
+  /// Detach `buffer`, taking its contents. Afterwards `buffer` is empty in the
+  /// isolate it belongs to, as after a transfer with `postMessage`.
+  pub fn detach(buffer: v8::Local<v8::ArrayBuffer>) -> Result<Self, Error> {
+    if !buffer.is_detachable() {
+      return Err(type_error("ArrayBuffer is not transferable"));
+    }
+    if buffer.was_detached() {
+      return Err(custom_error(
+        "DOMExceptionOperationError",
+        "ArrayBuffer is already detached",
+      ));
+    }
+    let backing_store = buffer.get_backing_store();
+    buffer.detach(None);
+    Ok(Self(backing_store))
+  }
+
+  pub fn len(&self) -> usize {
+    self.0.byte_length()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Create an `ArrayBuffer` owning the contents in the isolate of `scope`.
+  pub fn into_array_buffer<'s>(
+    self,
+    scope: &mut v8::HandleScope<'s>,
+  ) -> v8::Local<'s, v8::ArrayBuffer> {
+    v8::ArrayBuffer::with_backing_store(scope, &self.0)
+  }
+}
+
+/// A handle to the memory of a `SharedArrayBuffer`, which can be sent to
+/// other threads to create `SharedArrayBuffer`s sharing the same memory in
+/// other isolates.
+#[derive(Clone)]
+pub struct SharedArrayBufferHandle(v8::SharedRef<v8::BackingStore>);
+
+impl SharedArrayBufferHandle {
+  pub fn new(buffer: v8::Local<v8::SharedArrayBuffer>) -> Self {
+    Self(buffer.get_backing_store())
+  }
+
+  pub fn len(&self) -> usize {
+    self.0.byte_length()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Create a `SharedArrayBuffer` sharing the memory in the isolate of
+  /// `scope`.
+  pub fn to_shared_array_buffer<'s>(
+    &self,
+    scope: &mut v8::HandleScope<'s>,
+  ) -> v8::Local<'s, v8::SharedArrayBuffer> {
+    v8::SharedArrayBuffer::with_backing_store(scope, &self.0)
+  }
+}
+
+/// Serialize `value` with the HTML structured clone algorithm.
+///
+/// The `ArrayBuffer`s in `transfer` are detached and their backing stores
+/// moved into the runtime's [`crate::SharedArrayBufferStore`], and
+/// `SharedArrayBuffer`s and WebAssembly modules are shared through the
+/// runtime's stores so another isolate using the same stores can deserialize
+/// them. Without a `SharedArrayBufferStore`, nothing is transferred.
+///
+/// Exceptions thrown during serialization are returned as a
+/// [`crate::error::JsError`].
+pub fn structured_serialize<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  value: v8::Local<'s, v8::Value>,
+  transfer: &[v8::Local<'s, v8::ArrayBuffer>],
+  options: StructuredCloneOptions<'s>,
+) -> Result<StructuredCloneData, Error> {
+  match serialize(scope, value, transfer, options, false)? {
+    Some(data) => Ok(data),
+    None => unreachable!("exceptions are converted to errors"),
+  }
+}
+
+/// Deserialize a value serialized with [`structured_serialize`] into the
+/// current context of `scope`. Returns the value along with the transferred
+/// `ArrayBuffer`s, in the order their ids appear in
+/// `transferred_array_buffers`.
+pub fn structured_deserialize<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  data: &[u8],
+  transferred_array_buffers: &[u32],
+  options: StructuredCloneOptions<'s>,
+) -> Result<
+  (
+    v8::Local<'s, v8::Value>,
+    Vec<v8::Local<'s, v8::ArrayBuffer>>,
+  ),
+  Error,
+> {
+  let serialize_deserialize = Box::new(SerializeDeserialize { options });
+  let mut value_deserializer =
+    v8::ValueDeserializer::new(scope, serialize_deserialize, data);
+  let parsed_header = value_deserializer
+    .read_header(scope.get_current_context())
+    .unwrap_or_default();
+  if !parsed_header {
+    return Err(range_error("could not deserialize value"));
+  }
+
+  let mut array_buffers = Vec::with_capacity(transferred_array_buffers.len());
+  let state_rc = JsRuntime::state_from(scope);
+  let state = state_rc.borrow_mut();
+  if let Some(shared_array_buffer_store) = &state.shared_array_buffer_store {
+    for id in transferred_array_buffers {
+      if let Some(backing_store) = shared_array_buffer_store.take(*id) {
+        let array_buffer =
+          v8::ArrayBuffer::with_backing_store(scope, &backing_store);
+        value_deserializer.transfer_array_buffer(*id, array_buffer);
+        array_buffers.push(array_buffer);
+      } else {
+        return Err(type_error(
+          "transferred array buffer not present in shared_array_buffer_store",
+        ));
+      }
+    }
+  }
+  drop(state);
+
+  let value = value_deserializer.read_value(scope.get_current_context());
+  match value {
+    Some(deserialized) => Ok((deserialized, array_buffers)),
+    None => Err(range_error("could not deserialize value")),
+  }
+}
+
+/// Implementation of [`structured_serialize`]. When `rethrow` is set,
+/// exceptions thrown during serialization are left pending in `scope` and
+/// `Ok(None)` is returned, so ops can propagate the original exception.
+pub(crate) fn serialize<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  value: v8::Local<'s, v8::Value>,
+  transfer: &[v8::Local<'s, v8::ArrayBuffer>],
+  options: StructuredCloneOptions<'s>,
+  rethrow: bool,
+) -> Result<Option<StructuredCloneData>, Error> {
+  let serialize_deserialize = Box::new(SerializeDeserialize { options });
+  let mut value_serializer =
+    v8::ValueSerializer::new(scope, serialize_deserialize);
+  value_serializer.write_header();
+
+  let mut transferred_array_buffers = Vec::with_capacity(transfer.len());
+  let state_rc = JsRuntime::state_from(scope);
+  let state = state_rc.borrow_mut();
+  if let Some(shared_array_buffer_store) = &state.shared_array_buffer_store {
+    for (index, buf) in transfer.iter().enumerate() {
+      if !buf.is_detachable() {
+        return Err(type_error(
+          "item in transferredArrayBuffers is not transferable",
+        ));
+      }
+
+      if buf.was_detached() {
+        return Err(custom_error(
+          "DOMExceptionOperationError",
+          format!("ArrayBuffer at index {index} is already detached"),
+        ));
+      }
+
+      let backing_store = buf.get_backing_store();
+      buf.detach(None);
+      let id = shared_array_buffer_store.insert(backing_store);
+      value_serializer.transfer_array_buffer(id, *buf);
+      transferred_array_buffers.push(id);
+    }
+  }
+  drop(state);
+
+  let scope = &mut v8::TryCatch::new(scope);
+  let ret = value_serializer.write_value(scope.get_current_context(), value);
+  if scope.has_caught() || scope.has_terminated() {
+    if rethrow {
+      scope.rethrow();
+      return Ok(None);
+    }
+    return match scope.exception() {
+      Some(exception) => exception_to_err_result(scope, exception, false),
+      None => Err(type_error("Failed to serialize response")),
+    };
+  }
+  if let Some(true) = ret {
+    Ok(Some(StructuredCloneData {
+      data: value_serializer.release(),
+      transferred_array_buffers,
+    }))
+  } else {
+    Err(type_error("Failed to serialize response"))
+  }
+}
+
+struct SerializeDeserialize<'a> {
+  options: StructuredCloneOptions<'a>,
+}
+
+impl<'a> v8::ValueSerializerImpl for SerializeDeserialize<'a> {
+  #[allow(unused_variables)]
+  fn throw_data_clone_error<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    message: v8::Local<'s, v8::String>,
+  ) {
+    if let Some(cb) = self.options.error_callback {
+      let scope = &mut v8::TryCatch::new(scope);
+      let undefined = v8::undefined(scope).into();
+      cb.call(scope, undefined, &[message.into()]);
+      if scope.has_caught() || scope.has_terminated() {
+        scope.rethrow();
+        return;
+      };
+    }
+    let error = v8::Exception::type_error(scope, message);
+    scope.throw_exception(error);
+  }
+
+  fn get_shared_array_buffer_id<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    shared_array_buffer: v8::Local<'s, v8::SharedArrayBuffer>,
+  ) -> Option<u32> {
+    if self.options.for_storage {
+      return None;
+    }
+    let state_rc = JsRuntime::state_from(scope);
+    let state = state_rc.borrow_mut();
+    if let Some(shared_array_buffer_store) = &state.shared_array_buffer_store {
+      let backing_store = shared_array_buffer.get_backing_store();
+      let id = shared_array_buffer_store.insert(backing_store);
+      Some(id)
+    } else {
+      None
+    }
+  }
+
+  fn get_wasm_module_transfer_id(
+    &mut self,
+    scope: &mut v8::HandleScope<'_>,
+    module: v8::Local<v8::WasmModuleObject>,
+  ) -> Option<u32> {
+    if self.options.for_storage {
+      let message = v8::String::new(scope, "Wasm modules cannot be stored")?;
+      self.throw_data_clone_error(scope, message);
+      return None;
+    }
+    let state_rc = JsRuntime::state_from(scope);
+    let state = state_rc.borrow_mut();
+    if let Some(compiled_wasm_module_store) = &state.compiled_wasm_module_store
+    {
+      let compiled_wasm_module = module.get_compiled_module();
+      let id = compiled_wasm_module_store.insert(compiled_wasm_module);
+      Some(id)
+    } else {
+      None
+    }
+  }
+
+  fn write_host_object<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    object: v8::Local<'s, v8::Object>,
+    value_serializer: &mut dyn v8::ValueSerializerHelper,
+  ) -> Option<bool> {
+    if let Some(host_objects) = self.options.host_objects {
+      for i in 0..host_objects.length() {
+        let value = host_objects.get_index(scope, i).unwrap();
+        if value == object {
+          value_serializer.write_uint32(i);
+          return Some(true);
+        }
+      }
+    }
+    let message = v8::String::new(scope, "Unsupported object type").unwrap();
+    self.throw_data_clone_error(scope, message);
+    None
+  }
+}
+
+impl<'a> v8::ValueDeserializerImpl for SerializeDeserialize<'a> {
+  fn get_shared_array_buffer_from_id<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    transfer_id: u32,
+  ) -> Option<v8::Local<'s, v8::SharedArrayBuffer>> {
+    if self.options.for_storage {
+      return None;
+    }
+    let state_rc = JsRuntime::state_from(scope);
+    let state = state_rc.borrow_mut();
+    if let Some(shared_array_buffer_store) = &state.shared_array_buffer_store {
+      let backing_store = shared_array_buffer_store.take(transfer_id)?;
+      let shared_array_buffer =
+        v8::SharedArrayBuffer::with_backing_store(scope, &backing_store);
+      Some(shared_array_buffer)
+    } else {
+      None
+    }
+  }
+
+  fn get_wasm_module_from_id<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    clone_id: u32,
+  ) -> Option<v8::Local<'s, v8::WasmModuleObject>> {
+    if self.options.for_storage {
+      return None;
+    }
+    let state_rc = JsRuntime::state_from(scope);
+    let state = state_rc.borrow_mut();
+    if let Some(compiled_wasm_module_store) = &state.compiled_wasm_module_store
+    {
+      let compiled_module = compiled_wasm_module_store.take(clone_id)?;
+      v8::WasmModuleObject::from_compiled_module(scope, &compiled_module)
+    } else {
+      None
+    }
+  }
+
+  fn read_host_object<'s>(
+    &mut self,
+    scope: &mut v8::HandleScope<'s>,
+    value_deserializer: &mut dyn v8::ValueDeserializerHelper,
+  ) -> Option<v8::Local<'s, v8::Object>> {
+    if let Some(host_objects) = self.options.host_objects {
+      let mut i = 0;
+      if !value_deserializer.read_uint32(&mut i) {
+        return None;
+      }
+      let maybe_value = host_objects.get_index(scope, i);
+      if let Some(value) = maybe_value {
+        return value.to_object(scope);
+      }
+    }
+
+    let message: v8::Local<v8::String> =
+      v8::String::new(scope, "Failed to deserialize host object").unwrap();
+    let error = v8::Exception::error(scope, message);
+    scope.throw_exception(error);
+    None
+  }
+}
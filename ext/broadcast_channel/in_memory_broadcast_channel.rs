@@ -10,9 +10,15 @@ use tokio::sync::mpsc;
 use uuid::Uuid;
 
 use crate::BroadcastChannel;
+use crate::BroadcastChannelTransport;
 
+/// A channel between the workers of a process. With a transport attached,
+/// messages are exchanged with other processes too.
 #[derive(Clone)]
-pub struct InMemoryBroadcastChannel(Arc<Mutex<broadcast::Sender<Message>>>);
+pub struct InMemoryBroadcastChannel {
+  tx: Arc<Mutex<broadcast::Sender<Message>>>,
+  transport: Option<Arc<dyn BroadcastChannelTransport>>,
+}
 
 pub struct InMemoryBroadcastChannelResource {
   rx: tokio::sync::Mutex<(
@@ -33,7 +39,33 @@ struct Message {
 impl Default for InMemoryBroadcastChannel {
   fn default() -> Self {
     let (tx, _) = broadcast::channel(256);
-    Self(Arc::new(Mutex::new(tx)))
+    Self {
+      tx: Arc::new(Mutex::new(tx)),
+      transport: None,
+    }
+  }
+}
+
+impl InMemoryBroadcastChannel {
+  pub fn with_transport(
+    transport: Arc<dyn BroadcastChannelTransport>,
+  ) -> Result<Self, AnyError> {
+    let channel = Self {
+      transport: Some(transport.clone()),
+      ..Default::default()
+    };
+    let tx = channel.tx.clone();
+    transport.listen(Box::new(move |name, data| {
+      // The nil uuid is never a resource's, so every resource receives it.
+      let message = Message {
+        name: Arc::new(name),
+        data: Arc::new(data),
+        uuid: Uuid::nil(),
+      };
+      // Fails only if nobody in this process is subscribed.
+      _ = tx.lock().send(message);
+    }))?;
+    Ok(channel)
   }
 }
 
@@ -43,7 +75,7 @@ impl BroadcastChannel for InMemoryBroadcastChannel {
 
   fn subscribe(&self) -> Result<Self::Resource, AnyError> {
     let (cancel_tx, cancel_rx) = mpsc::unbounded_channel();
-    let broadcast_rx = self.0.lock().subscribe();
+    let broadcast_rx = self.tx.lock().subscribe();
     let rx = tokio::sync::Mutex::new((broadcast_rx, cancel_rx));
     let uuid = Uuid::new_v4();
     Ok(Self::Resource {
@@ -66,7 +98,15 @@ impl BroadcastChannel for InMemoryBroadcastChannel {
     let name = Arc::new(name);
     let data = Arc::new(data);
     let uuid = resource.uuid;
-    self.0.lock().send(Message { name, data, uuid })?;
+    self.tx.lock().send(Message {
+      name: name.clone(),
+      data: data.clone(),
+      uuid,
+    })?;
+    if let Some(transport) = self.transport.clone() {
+      tokio::task::spawn_blocking(move || transport.send(&name, &data))
+        .await??;
+    }
     Ok(())
   }
 
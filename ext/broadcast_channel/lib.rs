@@ -1,9 +1,14 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 mod in_memory_broadcast_channel;
+mod transport;
 
 pub use in_memory_broadcast_channel::InMemoryBroadcastChannel;
 pub use in_memory_broadcast_channel::InMemoryBroadcastChannelResource;
+pub use transport::BroadcastChannelTransport;
+pub use transport::DeliverFn;
+#[cfg(unix)]
+pub use transport::UnixSocketTransport;
 
 use std::cell::RefCell;
 use std::path::PathBuf;
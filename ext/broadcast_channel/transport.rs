@@ -0,0 +1,154 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::AnyError;
+
+/// Called with the name and data of every message received from another
+/// process.
+pub type DeliverFn = Box<dyn Fn(String, Vec<u8>) + Send + Sync>;
+
+/// Carries the messages of an `InMemoryBroadcastChannel` to other processes,
+/// so that `BroadcastChannel`s with the same name talk to each other across
+/// process boundaries.
+pub trait BroadcastChannelTransport: Send + Sync {
+  /// Sends a message posted in this process to the other processes. Called
+  /// on a blocking thread.
+  fn send(&self, name: &str, data: &[u8]) -> Result<(), AnyError>;
+
+  /// Starts receiving messages posted in other processes. Called once, when
+  /// the transport is attached to a channel.
+  fn listen(&self, deliver: DeliverFn) -> Result<(), AnyError>;
+}
+
+#[cfg(unix)]
+pub use unix::UnixSocketTransport;
+
+#[cfg(unix)]
+mod unix {
+  use std::io::ErrorKind;
+  use std::os::unix::net::UnixDatagram;
+  use std::path::Path;
+  use std::path::PathBuf;
+
+  use deno_core::error::type_error;
+  use deno_core::error::AnyError;
+
+  use super::BroadcastChannelTransport;
+  use super::DeliverFn;
+
+  /// The largest datagram received. Larger messages fail to send.
+  const MAX_DATAGRAM_SIZE: usize = 64 * 1024;
+
+  /// A transport for processes on the same machine. Each process binds a
+  /// datagram socket in a shared directory and sends every message to all
+  /// the other sockets in it. Sockets left behind by processes that are gone
+  /// are removed by the first process that fails to reach them.
+  pub struct UnixSocketTransport {
+    dir: PathBuf,
+    path: PathBuf,
+    socket: UnixDatagram,
+  }
+
+  impl UnixSocketTransport {
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, AnyError> {
+      let dir = dir.as_ref().to_path_buf();
+      std::fs::create_dir_all(&dir)?;
+      let path = dir.join(format!("{}.sock", std::process::id()));
+      // A process with the same id that's gone may have left its socket.
+      match std::fs::remove_file(&path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => {
+          return Err(err.into())
+        }
+        _ => {}
+      }
+      let socket = UnixDatagram::bind(&path)?;
+      Ok(Self { dir, path, socket })
+    }
+  }
+
+  /// Frames a message as the length of its name (a big-endian `u32`), the
+  /// name and the data.
+  fn encode(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(4 + name.len() + data.len());
+    frame.extend_from_slice(&(name.len() as u32).to_be_bytes());
+    frame.extend_from_slice(name.as_bytes());
+    frame.extend_from_slice(data);
+    frame
+  }
+
+  fn decode(frame: &[u8]) -> Option<(String, Vec<u8>)> {
+    let len = u32::from_be_bytes(frame.get(..4)?.try_into().ok()?) as usize;
+    let name = frame.get(4..4 + len)?;
+    let name = String::from_utf8(name.to_vec()).ok()?;
+    Some((name, frame[4 + len..].to_vec()))
+  }
+
+  impl BroadcastChannelTransport for UnixSocketTransport {
+    fn send(&self, name: &str, data: &[u8]) -> Result<(), AnyError> {
+      let frame = encode(name, data);
+      if frame.len() > MAX_DATAGRAM_SIZE {
+        return Err(type_error(format!(
+          "BroadcastChannel message of {} bytes is too large to send to other processes",
+          frame.len()
+        )));
+      }
+      for entry in std::fs::read_dir(&self.dir)? {
+        let peer = entry?.path();
+        if peer == self.path || peer.extension() != Some("sock".as_ref()) {
+          continue;
+        }
+        match self.socket.send_to(&frame, &peer) {
+          Ok(_) => {}
+          Err(err)
+            if matches!(
+              err.kind(),
+              ErrorKind::ConnectionRefused | ErrorKind::NotFound
+            ) =>
+          {
+            _ = std::fs::remove_file(&peer);
+          }
+          Err(err) => return Err(err.into()),
+        }
+      }
+      Ok(())
+    }
+
+    fn listen(&self, deliver: DeliverFn) -> Result<(), AnyError> {
+      let socket = self.socket.try_clone()?;
+      std::thread::Builder::new()
+        .name("broadcast-channel-transport".to_string())
+        .spawn(move || {
+          let mut buf = vec![0; MAX_DATAGRAM_SIZE];
+          while let Ok(len) = socket.recv(&mut buf) {
+            // Frames that don't decode weren't sent by us; ignore them.
+            if let Some((name, data)) = decode(&buf[..len]) {
+              deliver(name, data);
+            }
+          }
+        })?;
+      Ok(())
+    }
+  }
+
+  impl Drop for UnixSocketTransport {
+    fn drop(&mut self) {
+      _ = std::fs::remove_file(&self.path);
+    }
+  }
+
+  #[cfg(test)]
+  mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_roundtrip() {
+      let frame = encode("channel", b"data");
+      assert_eq!(
+        decode(&frame),
+        Some(("channel".to_string(), b"data".to_vec()))
+      );
+      assert_eq!(decode(&encode("", b"")), Some((String::new(), vec![])));
+      assert_eq!(decode(&frame[..6]), None);
+      assert_eq!(decode(b"ab"), None);
+    }
+  }
+}
@@ -14,7 +14,9 @@ use deno_core::ByteString;
 use deno_core::OpState;
 use deno_core::Resource;
 use deno_core::ResourceId;
+mod memory;
 mod sqlite;
+pub use memory::InMemoryCache;
 pub use sqlite::SqliteBackedCache;
 
 #[derive(Clone)]
@@ -27,6 +29,7 @@ deno_core::extension!(deno_cache,
     op_cache_storage_open<CA>,
     op_cache_storage_has<CA>,
     op_cache_storage_delete<CA>,
+    op_cache_storage_usage<CA>,
     op_cache_put<CA>,
     op_cache_match<CA>,
     op_cache_delete<CA>,
@@ -86,6 +89,17 @@ pub struct CacheDeleteRequest {
   pub request_url: String,
 }
 
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStorageUsage {
+  /// The total size of the cached responses, in bytes.
+  pub usage: u64,
+  /// The maximum total size of the cached responses, if there is one.
+  pub quota: Option<u64>,
+}
+
+/// The storage behind the Cache API. Embedders can back `caches` with their
+/// own store by implementing this trait and passing a [CreateCache] for it to
+/// the extension.
 #[async_trait]
 pub trait Cache: Clone {
   async fn storage_open(&self, cache_name: String) -> Result<i64, AnyError>;
@@ -105,6 +119,8 @@ pub trait Cache: Clone {
   >;
   async fn delete(&self, request: CacheDeleteRequest)
     -> Result<bool, AnyError>;
+
+  async fn storage_usage(&self) -> Result<CacheStorageUsage, AnyError>;
 }
 
 #[op]
@@ -143,6 +159,17 @@ where
   cache.storage_delete(cache_name).await
 }
 
+#[op]
+pub async fn op_cache_storage_usage<CA>(
+  state: Rc<RefCell<OpState>>,
+) -> Result<CacheStorageUsage, AnyError>
+where
+  CA: Cache + 'static,
+{
+  let cache = get_cache::<CA>(&state)?;
+  cache.storage_usage().await
+}
+
 #[op]
 pub async fn op_cache_put<CA>(
   state: Rc<RefCell<OpState>>,
@@ -0,0 +1,308 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_core::Resource;
+
+use crate::get_header;
+use crate::serialize_headers;
+use crate::vary_header_matches;
+use crate::Cache;
+use crate::CacheDeleteRequest;
+use crate::CacheMatchRequest;
+use crate::CacheMatchResponseMeta;
+use crate::CachePutRequest;
+use crate::CacheStorageUsage;
+
+/// A cache that keeps the responses in memory, and loses them when the last
+/// clone of it is dropped. Useful for tests and for embedders that don't want
+/// responses written to disk.
+#[derive(Clone, Default)]
+pub struct InMemoryCache {
+  storage: Arc<Mutex<InMemoryCacheStorage>>,
+}
+
+#[derive(Default)]
+struct InMemoryCacheStorage {
+  next_cache_id: i64,
+  cache_ids: HashMap<String, i64>,
+  responses: HashMap<(i64, String), CachedResponse>,
+}
+
+struct CachedResponse {
+  meta: CacheMatchResponseMeta,
+  body: Option<Arc<[u8]>>,
+}
+
+impl CachedResponse {
+  fn size(&self) -> u64 {
+    let body_size = self.body.as_ref().map(|body| body.len()).unwrap_or(0);
+    (body_size
+      + serialize_headers(&self.meta.request_headers).len()
+      + serialize_headers(&self.meta.response_headers).len()) as u64
+  }
+}
+
+impl InMemoryCache {
+  fn insert(&self, put: CachePutRequest, body: Option<Arc<[u8]>>) {
+    let response = CachedResponse {
+      meta: CacheMatchResponseMeta {
+        response_status: put.response_status,
+        response_status_text: put.response_status_text,
+        request_headers: put.request_headers,
+        response_headers: put.response_headers,
+      },
+      body,
+    };
+    let mut storage = self.storage.lock();
+    if storage
+      .cache_ids
+      .values()
+      .any(|cache_id| *cache_id == put.cache_id)
+    {
+      storage
+        .responses
+        .insert((put.cache_id, put.request_url), response);
+    }
+  }
+}
+
+#[async_trait]
+impl Cache for InMemoryCache {
+  async fn storage_open(&self, cache_name: String) -> Result<i64, AnyError> {
+    let mut storage = self.storage.lock();
+    if let Some(cache_id) = storage.cache_ids.get(&cache_name) {
+      return Ok(*cache_id);
+    }
+    storage.next_cache_id += 1;
+    let cache_id = storage.next_cache_id;
+    storage.cache_ids.insert(cache_name, cache_id);
+    Ok(cache_id)
+  }
+
+  async fn storage_has(&self, cache_name: String) -> Result<bool, AnyError> {
+    Ok(self.storage.lock().cache_ids.contains_key(&cache_name))
+  }
+
+  async fn storage_delete(&self, cache_name: String) -> Result<bool, AnyError> {
+    let mut storage = self.storage.lock();
+    let Some(cache_id) = storage.cache_ids.remove(&cache_name) else {
+      return Ok(false);
+    };
+    storage.responses.retain(|(id, _), _| *id != cache_id);
+    Ok(true)
+  }
+
+  async fn put(
+    &self,
+    request_response: CachePutRequest,
+  ) -> Result<Option<Rc<dyn Resource>>, AnyError> {
+    if request_response.response_has_body {
+      Ok(Some(Rc::new(InMemoryCachePutResource {
+        cache: self.clone(),
+        put_request: request_response,
+        body: RefCell::new(Vec::new()),
+      })))
+    } else {
+      self.insert(request_response, None);
+      Ok(None)
+    }
+  }
+
+  async fn r#match(
+    &self,
+    request: CacheMatchRequest,
+  ) -> Result<
+    Option<(CacheMatchResponseMeta, Option<Rc<dyn Resource>>)>,
+    AnyError,
+  > {
+    let (meta, body) = {
+      let storage = self.storage.lock();
+      let Some(response) = storage
+        .responses
+        .get(&(request.cache_id, request.request_url))
+      else {
+        return Ok(None);
+      };
+      let meta = CacheMatchResponseMeta {
+        response_status: response.meta.response_status,
+        response_status_text: response.meta.response_status_text.clone(),
+        request_headers: response.meta.request_headers.clone(),
+        response_headers: response.meta.response_headers.clone(),
+      };
+      (meta, response.body.clone())
+    };
+    // From https://w3c.github.io/ServiceWorker/#request-matches-cached-item-algorithm
+    // If there's Vary header in the response, ensure all the
+    // headers of the cached request match the query request.
+    if let Some(vary_header) = get_header("vary", &meta.response_headers) {
+      if !vary_header_matches(
+        &vary_header,
+        &request.request_headers,
+        &meta.request_headers,
+      ) {
+        return Ok(None);
+      }
+    }
+    let body = body.map(|body| {
+      Rc::new(InMemoryCacheResponseResource {
+        body,
+        position: Cell::new(0),
+      }) as Rc<dyn Resource>
+    });
+    Ok(Some((meta, body)))
+  }
+
+  async fn delete(
+    &self,
+    request: CacheDeleteRequest,
+  ) -> Result<bool, AnyError> {
+    let mut storage = self.storage.lock();
+    Ok(
+      storage
+        .responses
+        .remove(&(request.cache_id, request.request_url))
+        .is_some(),
+    )
+  }
+
+  async fn storage_usage(&self) -> Result<CacheStorageUsage, AnyError> {
+    let storage = self.storage.lock();
+    let usage = storage.responses.values().map(CachedResponse::size).sum();
+    Ok(CacheStorageUsage { usage, quota: None })
+  }
+}
+
+struct InMemoryCachePutResource {
+  cache: InMemoryCache,
+  put_request: CachePutRequest,
+  body: RefCell<Vec<u8>>,
+}
+
+impl InMemoryCachePutResource {
+  async fn write(self: Rc<Self>, data: &[u8]) -> Result<usize, AnyError> {
+    self.body.borrow_mut().extend_from_slice(data);
+    Ok(data.len())
+  }
+
+  async fn shutdown(self: Rc<Self>) -> Result<(), AnyError> {
+    let body = self.body.take();
+    self
+      .cache
+      .insert(self.put_request.clone(), Some(body.into()));
+    Ok(())
+  }
+}
+
+impl Resource for InMemoryCachePutResource {
+  fn name(&self) -> Cow<str> {
+    "InMemoryCachePutResource".into()
+  }
+
+  deno_core::impl_writable!();
+
+  fn shutdown(self: Rc<Self>) -> deno_core::AsyncResult<()> {
+    Box::pin(self.shutdown())
+  }
+}
+
+struct InMemoryCacheResponseResource {
+  body: Arc<[u8]>,
+  position: Cell<usize>,
+}
+
+impl InMemoryCacheResponseResource {
+  async fn read(self: Rc<Self>, data: &mut [u8]) -> Result<usize, AnyError> {
+    let position = self.position.get();
+    let remaining = &self.body[position..];
+    let nread = remaining.len().min(data.len());
+    data[..nread].copy_from_slice(&remaining[..nread]);
+    self.position.set(position + nread);
+    Ok(nread)
+  }
+}
+
+impl Resource for InMemoryCacheResponseResource {
+  deno_core::impl_readable_byob!();
+
+  fn name(&self) -> Cow<str> {
+    "InMemoryCacheResponseResource".into()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_core::ByteString;
+
+  fn put_request(cache_id: i64, request_url: &str) -> CachePutRequest {
+    CachePutRequest {
+      cache_id,
+      request_url: request_url.to_string(),
+      request_headers: vec![],
+      response_headers: vec![(
+        ByteString::from("content-type"),
+        ByteString::from("text/plain"),
+      )],
+      response_has_body: true,
+      response_status: 200,
+      response_status_text: "OK".to_string(),
+    }
+  }
+
+  #[tokio::test]
+  async fn put_match_delete() {
+    let cache = InMemoryCache::default();
+    let cache_id = cache.storage_open("v1".to_string()).await.unwrap();
+    assert!(cache.storage_has("v1".to_string()).await.unwrap());
+
+    let resource = cache
+      .put(put_request(cache_id, "https://deno.land/"))
+      .await
+      .unwrap()
+      .unwrap();
+    resource
+      .clone()
+      .write(b"hello".to_vec().into())
+      .await
+      .unwrap();
+    resource.shutdown().await.unwrap();
+
+    let usage = cache.storage_usage().await.unwrap();
+    assert_eq!(
+      usage.usage,
+      5 + "content-type\r\ntext/plain\r\n".len() as u64
+    );
+
+    let match_request = || CacheMatchRequest {
+      cache_id,
+      request_url: "https://deno.land/".to_string(),
+      request_headers: vec![],
+    };
+    let (meta, body) = cache.r#match(match_request()).await.unwrap().unwrap();
+    assert_eq!(meta.response_status, 200);
+    let body = body.unwrap();
+    let buf = deno_core::BufMutView::new(16);
+    let (nread, buf) = body.read_byob(buf).await.unwrap();
+    assert_eq!(&buf[..nread], b"hello");
+
+    assert!(cache
+      .delete(CacheDeleteRequest {
+        cache_id,
+        request_url: "https://deno.land/".to_string(),
+      })
+      .await
+      .unwrap());
+    assert!(cache.r#match(match_request()).await.unwrap().is_none());
+    assert!(cache.storage_delete("v1".to_string()).await.unwrap());
+    assert!(!cache.storage_has("v1".to_string()).await.unwrap());
+  }
+}
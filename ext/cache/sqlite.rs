@@ -1,6 +1,8 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use std::borrow::Cow;
+use std::cell::Cell;
+use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -8,6 +10,7 @@ use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
 use async_trait::async_trait;
+use deno_core::error::custom_error;
 use deno_core::error::AnyError;
 use deno_core::parking_lot::Mutex;
 use deno_core::task::spawn_blocking;
@@ -30,15 +33,19 @@ use crate::CacheDeleteRequest;
 use crate::CacheMatchRequest;
 use crate::CacheMatchResponseMeta;
 use crate::CachePutRequest;
+use crate::CacheStorageUsage;
 
 #[derive(Clone)]
 pub struct SqliteBackedCache {
   pub connection: Arc<Mutex<Connection>>,
   pub cache_storage_dir: PathBuf,
+  /// The maximum total size of the cached responses, in bytes. When a put
+  /// exceeds it, the least recently used responses are evicted.
+  pub max_size: Option<u64>,
 }
 
 impl SqliteBackedCache {
-  pub fn new(cache_storage_dir: PathBuf) -> Self {
+  pub fn new(cache_storage_dir: PathBuf, max_size: Option<u64>) -> Self {
     {
       std::fs::create_dir_all(&cache_storage_dir)
         .expect("failed to create cache dir");
@@ -84,14 +91,33 @@ impl SqliteBackedCache {
           (),
         )
         .expect("failed to create request_response_list table");
+      migrate(&connection).expect("failed to migrate cache db");
       SqliteBackedCache {
         connection: Arc::new(Mutex::new(connection)),
         cache_storage_dir,
+        max_size,
       }
     }
   }
 }
 
+/// Adds the columns used for quota management to databases created before
+/// they existed. Responses cached before count as empty.
+fn migrate(connection: &Connection) -> Result<(), rusqlite::Error> {
+  let version: i64 =
+    connection.query_row("PRAGMA user_version", (), |row| row.get(0))?;
+  if version < 1 {
+    connection.execute_batch(
+      "ALTER TABLE request_response_list
+         ADD COLUMN response_size INTEGER UNSIGNED NOT NULL DEFAULT 0;
+       ALTER TABLE request_response_list
+         ADD COLUMN last_accessed_at INTEGER NOT NULL DEFAULT 0;
+       PRAGMA user_version = 1;",
+    )?;
+  }
+  Ok(())
+}
+
 #[async_trait]
 impl Cache for SqliteBackedCache {
   /// Open a cache storage. Internally, this creates a row in the
@@ -157,6 +183,10 @@ impl Cache for SqliteBackedCache {
         )
         .optional()?;
       if let Some(cache_id) = maybe_cache_id {
+        db.execute(
+          "DELETE FROM request_response_list WHERE cache_id = ?1",
+          params![cache_id],
+        )?;
         let cache_dir = cache_storage_dir.join(cache_id.to_string());
         if cache_dir.exists() {
           std::fs::remove_dir_all(cache_dir)?;
@@ -171,7 +201,6 @@ impl Cache for SqliteBackedCache {
     &self,
     request_response: CachePutRequest,
   ) -> Result<Option<Rc<dyn Resource>>, AnyError> {
-    let db = self.connection.clone();
     let cache_storage_dir = self.cache_storage_dir.clone();
     let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
     let response_body_key = if request_response.response_has_body {
@@ -191,13 +220,14 @@ impl Cache for SqliteBackedCache {
       let file = tokio::fs::File::create(response_path).await?;
       Ok(Some(Rc::new(CachePutResource {
         file: AsyncRefCell::new(file),
-        db,
+        cache: self.clone(),
         put_request: request_response,
         response_body_key: body_key,
+        response_size: Cell::new(0),
         start_time: now.as_secs(),
       })))
     } else {
-      insert_cache_asset(db, request_response, None).await?;
+      insert_cache_asset(self.clone(), request_response, None, 0).await?;
       Ok(None)
     }
   }
@@ -213,6 +243,11 @@ impl Cache for SqliteBackedCache {
     let cache_storage_dir = self.cache_storage_dir.clone();
     let query_result = spawn_blocking(move || {
       let db = db.lock();
+      db.execute(
+        "UPDATE request_response_list SET last_accessed_at = ?3
+             WHERE cache_id = ?1 AND request_url = ?2",
+        (request.cache_id, &request.request_url, now_nanos()?),
+      )?;
       let result = db.query_row(
         "SELECT response_body_key, response_headers, response_status, response_status_text, request_headers
              FROM request_response_list
@@ -229,7 +264,7 @@ impl Cache for SqliteBackedCache {
           Ok((CacheMatchResponseMeta {request_headers, response_headers,response_status,response_status_text}, response_body_key))
         },
       );
-      result.optional()
+      Ok::<_, AnyError>(result.optional()?)
     })
     .await??;
 
@@ -270,51 +305,176 @@ impl Cache for SqliteBackedCache {
     request: CacheDeleteRequest,
   ) -> Result<bool, AnyError> {
     let db = self.connection.clone();
+    let cache_storage_dir = self.cache_storage_dir.clone();
     spawn_blocking(move || {
-      // TODO(@satyarohith): remove the response body from disk if one exists
       let db = db.lock();
-      let rows_effected = db.execute(
-        "DELETE FROM request_response_list WHERE cache_id = ?1 AND request_url = ?2",
-        (request.cache_id, &request.request_url),
-      )?;
-      Ok::<bool, AnyError>(rows_effected > 0)
+      let deleted = db
+        .query_row(
+          "DELETE FROM request_response_list WHERE cache_id = ?1 AND request_url = ?2
+               RETURNING response_body_key",
+          (request.cache_id, &request.request_url),
+          |row| {
+            let response_body_key: Option<String> = row.get(0)?;
+            Ok(response_body_key)
+          },
+        )
+        .optional()?;
+      if let Some(Some(response_body_key)) = &deleted {
+        remove_response_body(
+          &cache_storage_dir,
+          request.cache_id,
+          response_body_key,
+        );
+      }
+      Ok::<bool, AnyError>(deleted.is_some())
+    })
+    .await?
+  }
+
+  async fn storage_usage(&self) -> Result<CacheStorageUsage, AnyError> {
+    let db = self.connection.clone();
+    let quota = self.max_size;
+    spawn_blocking(move || {
+      let db = db.lock();
+      let usage = total_size(&db)?;
+      Ok::<_, AnyError>(CacheStorageUsage { usage, quota })
     })
     .await?
   }
 }
 
+fn now_nanos() -> Result<i64, AnyError> {
+  Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos() as i64)
+}
+
+fn total_size(db: &Connection) -> Result<u64, rusqlite::Error> {
+  db.query_row(
+    "SELECT COALESCE(SUM(response_size), 0) FROM request_response_list",
+    (),
+    |row| row.get(0),
+  )
+}
+
+/// Removes the body of a response that is no longer cached. Failures are
+/// ignored, as the body can't be reached anymore anyway.
+fn remove_response_body(
+  cache_storage_dir: &Path,
+  cache_id: i64,
+  response_body_key: &str,
+) {
+  let path = get_responses_dir(cache_storage_dir.to_path_buf(), cache_id)
+    .join(response_body_key);
+  let _ = std::fs::remove_file(path);
+}
+
+/// Evicts the least recently used responses until the cached responses fit
+/// in `max_size`.
+fn evict(
+  db: &Connection,
+  cache_storage_dir: &Path,
+  max_size: u64,
+) -> Result<(), AnyError> {
+  while total_size(db)? > max_size {
+    let (cache_id, response_body_key) = db.query_row(
+      "DELETE FROM request_response_list WHERE id = (
+           SELECT id FROM request_response_list
+           ORDER BY last_accessed_at ASC, id ASC LIMIT 1
+         )
+         RETURNING cache_id, response_body_key",
+      (),
+      |row| {
+        let cache_id: i64 = row.get(0)?;
+        let response_body_key: Option<String> = row.get(1)?;
+        Ok((cache_id, response_body_key))
+      },
+    )?;
+    if let Some(response_body_key) = response_body_key {
+      remove_response_body(cache_storage_dir, cache_id, &response_body_key);
+    }
+  }
+  Ok(())
+}
+
+/// Inserts a response, replacing the cached response for the same request,
+/// and evicts other responses if the cache storage is over its quota. The
+/// size of the response is the size of its body and headers.
 async fn insert_cache_asset(
-  db: Arc<Mutex<rusqlite::Connection>>,
+  cache: SqliteBackedCache,
   put: CachePutRequest,
   response_body_key: Option<String>,
+  response_body_size: u64,
 ) -> Result<Option<String>, deno_core::anyhow::Error> {
   spawn_blocking(move || {
-    let maybe_response_body = {
-      let db = db.lock();
-      db.query_row(
-        "INSERT OR REPLACE INTO request_response_list
-             (cache_id, request_url, request_headers, response_headers,
-              response_body_key, response_status, response_status_text, last_inserted_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-             RETURNING response_body_key",
-        (
+    let request_headers = serialize_headers(&put.request_headers);
+    let response_headers = serialize_headers(&put.response_headers);
+    let response_size = response_body_size
+      + request_headers.len() as u64
+      + response_headers.len() as u64;
+    if matches!(cache.max_size, Some(max_size) if response_size > max_size) {
+      if let Some(response_body_key) = &response_body_key {
+        remove_response_body(
+          &cache.cache_storage_dir,
           put.cache_id,
-          put.request_url,
-          serialize_headers(&put.request_headers),
-          serialize_headers(&put.response_headers),
           response_body_key,
-          put.response_status,
-          put.response_status_text,
-          SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
-        ),
+        );
+      }
+      return Err(custom_error(
+        "DOMExceptionQuotaExceededError",
+        "The response is larger than the cache storage quota",
+      ));
+    }
+
+    let db = cache.connection.lock();
+    let replaced_body_key = db
+      .query_row(
+        "SELECT response_body_key FROM request_response_list
+             WHERE cache_id = ?1 AND request_url = ?2",
+        (put.cache_id, &put.request_url),
         |row| {
           let response_body_key: Option<String> = row.get(0)?;
           Ok(response_body_key)
         },
-      )?
-    };
-      Ok::<Option<String>, AnyError>(maybe_response_body)
-  }).await?
+      )
+      .optional()?
+      .flatten();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let maybe_response_body = db.query_row(
+      "INSERT OR REPLACE INTO request_response_list
+           (cache_id, request_url, request_headers, response_headers,
+            response_body_key, response_status, response_status_text, last_inserted_at,
+            response_size, last_accessed_at)
+           VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+           RETURNING response_body_key",
+      (
+        put.cache_id,
+        &put.request_url,
+        request_headers,
+        response_headers,
+        response_body_key,
+        put.response_status,
+        put.response_status_text,
+        now.as_secs(),
+        response_size,
+        now.as_nanos() as i64,
+      ),
+      |row| {
+        let response_body_key: Option<String> = row.get(0)?;
+        Ok(response_body_key)
+      },
+    )?;
+    if let Some(replaced_body_key) = replaced_body_key {
+      remove_response_body(
+        &cache.cache_storage_dir,
+        put.cache_id,
+        &replaced_body_key,
+      );
+    }
+    if let Some(max_size) = cache.max_size {
+      evict(&db, &cache.cache_storage_dir, max_size)?;
+    }
+    Ok::<Option<String>, AnyError>(maybe_response_body)
+  })
+  .await?
 }
 
 #[inline]
@@ -331,9 +491,10 @@ impl deno_core::Resource for SqliteBackedCache {
 }
 
 pub struct CachePutResource {
-  pub db: Arc<Mutex<rusqlite::Connection>>,
+  pub cache: SqliteBackedCache,
   pub put_request: CachePutRequest,
   pub response_body_key: String,
+  pub response_size: Cell<u64>,
   pub file: AsyncRefCell<tokio::fs::File>,
   pub start_time: u64,
 }
@@ -343,6 +504,9 @@ impl CachePutResource {
     let resource = deno_core::RcRef::map(&self, |r| &r.file);
     let mut file = resource.borrow_mut().await;
     file.write_all(data).await?;
+    self
+      .response_size
+      .set(self.response_size.get() + data.len() as u64);
     Ok(data.len())
   }
 
@@ -352,9 +516,10 @@ impl CachePutResource {
     file.flush().await?;
     file.sync_all().await?;
     let maybe_body_key = insert_cache_asset(
-      self.db.clone(),
+      self.cache.clone(),
       self.put_request.clone(),
       Some(self.response_body_key.clone()),
+      self.response_size.get(),
     )
     .await?;
     match maybe_body_key {
@@ -11,6 +11,7 @@ const ops = core.ops;
 const primordials = globalThis.__bootstrap.primordials;
 import * as webidl from "ext:deno_webidl/00_webidl.js";
 import DOMException from "ext:deno_web/01_dom_exception.js";
+import { ReadableStreamPrototype } from "ext:deno_web/06_streams.js";
 const {
   ArrayBufferIsView,
   ArrayBufferPrototype,
@@ -49,8 +50,66 @@ const {
   WeakMapPrototypeSet,
 } = primordials;
 
-// P-521 is not yet supported.
-const supportedNamedCurves = ["P-256", "P-384"];
+// P-521 is not yet supported. K-256 is secp256k1.
+const supportedNamedCurves = ["P-256", "P-384", "K-256"];
+
+/**
+ * The curves of RFC 8032 (Ed25519) and RFC 7748 (X25519, X448), the
+ * lengths of their keys and signatures in bytes, and the ops implementing
+ * them. Their keys are stored as the raw key bytes.
+ */
+const OKP_CURVES = {
+  "Ed25519": {
+    keyLength: 32,
+    signatureLength: 64,
+    generateKeypair: ops.op_crypto_generate_ed25519_keypair,
+    sign: ops.op_crypto_sign_ed25519,
+    verify: ops.op_crypto_verify_ed25519,
+    importSpki: ops.op_crypto_import_spki_ed25519,
+    importPkcs8: ops.op_crypto_import_pkcs8_ed25519,
+    exportSpki: ops.op_crypto_export_spki_ed25519,
+    exportPkcs8: (key) => {
+      const pkcs8Der = ops.op_crypto_export_pkcs8_ed25519(
+        new Uint8Array([0x04, 0x22, ...new SafeArrayIterator(key)]),
+      );
+      pkcs8Der[15] = 0x20;
+      return pkcs8Der;
+    },
+    jwkX: ops.op_crypto_jwk_x_ed25519,
+  },
+  "X25519": {
+    keyLength: 32,
+    generateKeypair: (pkey, pubkey) => {
+      ops.op_crypto_generate_x25519_keypair(pkey, pubkey);
+      return true;
+    },
+    deriveBits: ops.op_crypto_derive_bits_x25519,
+    importSpki: ops.op_crypto_import_spki_x25519,
+    importPkcs8: ops.op_crypto_import_pkcs8_x25519,
+    exportSpki: ops.op_crypto_export_spki_x25519,
+    exportPkcs8: (key) => {
+      const pkcs8Der = ops.op_crypto_export_pkcs8_x25519(
+        new Uint8Array([0x04, 0x22, ...new SafeArrayIterator(key)]),
+      );
+      pkcs8Der[15] = 0x20;
+      return pkcs8Der;
+    },
+    jwkX: undefined,
+  },
+  "X448": {
+    keyLength: 56,
+    generateKeypair: (pkey, pubkey) => {
+      ops.op_crypto_generate_x448_keypair(pkey, pubkey);
+      return true;
+    },
+    deriveBits: ops.op_crypto_derive_bits_x448,
+    importSpki: ops.op_crypto_import_spki_x448,
+    importPkcs8: ops.op_crypto_import_pkcs8_x448,
+    exportSpki: ops.op_crypto_export_spki_x448,
+    exportPkcs8: ops.op_crypto_export_pkcs8_x448,
+    jwkX: ops.op_crypto_jwk_x_x448,
+  },
+};
 const recognisedUsages = [
   "encrypt",
   "decrypt",
@@ -100,6 +159,7 @@ const supportedAlgorithms = {
     "AES-KW": "AesKeyGenParams",
     "HMAC": "HmacKeyGenParams",
     "X25519": null,
+    "X448": null,
     "Ed25519": null,
   },
   "sign": {
@@ -131,12 +191,14 @@ const supportedAlgorithms = {
     "AES-KW": null,
     "Ed25519": null,
     "X25519": null,
+    "X448": null,
   },
   "deriveBits": {
     "HKDF": "HkdfParams",
     "PBKDF2": "Pbkdf2Params",
     "ECDH": "EcdhKeyDeriveParams",
     "X25519": "EcdhKeyDeriveParams",
+    "X448": "EcdhKeyDeriveParams",
   },
   "encrypt": {
     "RSA-OAEP": "RsaOaepParams",
@@ -480,6 +542,14 @@ class SubtleCrypto {
       prefix,
       "Argument 1",
     );
+
+    // Deno extension: the data can be a stream of BufferSource chunks, which
+    // are hashed as they arrive.
+    if (ObjectPrototypeIsPrototypeOf(ReadableStreamPrototype, data)) {
+      algorithm = normalizeAlgorithm(algorithm, "digest");
+      return digestStream(algorithm.name, data, prefix);
+    }
+
     data = webidl.converters.BufferSource(data, prefix, "Argument 2");
 
     data = copyBuffer(data);
@@ -844,7 +914,9 @@ class SubtleCrypto {
           (key[_algorithm].namedCurve === "P-256" &&
             hashAlgorithm !== "SHA-256") ||
           (key[_algorithm].namedCurve === "P-384" &&
-            hashAlgorithm !== "SHA-384")
+            hashAlgorithm !== "SHA-384") ||
+          (key[_algorithm].namedCurve === "K-256" &&
+            hashAlgorithm !== "SHA-256")
         ) {
           throw new DOMException(
             "Not implemented",
@@ -881,10 +953,9 @@ class SubtleCrypto {
           );
         }
 
-        // https://briansmith.org/rustdoc/src/ring/ec/curve25519/ed25519/signing.rs.html#260
-        const SIGNATURE_LEN = 32 * 2; // ELEM_LEN + SCALAR_LEN
-        const signature = new Uint8Array(SIGNATURE_LEN);
-        if (!ops.op_crypto_sign_ed25519(keyData, data, signature)) {
+        const curve = OKP_CURVES[algorithmName];
+        const signature = new Uint8Array(curve.signatureLength);
+        if (!curve.sign(keyData, data, signature)) {
           throw new DOMException(
             "Failed to sign",
             "OperationError",
@@ -1010,8 +1081,10 @@ class SubtleCrypto {
           ["wrapKey", "unwrapKey"],
         );
       }
-      case "X25519": {
-        return importKeyX25519(
+      case "X25519":
+      case "X448": {
+        return importKeyXDH(
+          algorithmName,
           format,
           keyData,
           extractable,
@@ -1019,7 +1092,8 @@ class SubtleCrypto {
         );
       }
       case "Ed25519": {
-        return importKeyEd25519(
+        return importKeyEdDSA(
+          algorithmName,
           format,
           keyData,
           extractable,
@@ -1069,11 +1143,12 @@ class SubtleCrypto {
         break;
       }
       case "Ed25519": {
-        result = exportKeyEd25519(format, key, innerKey);
+        result = exportKeyEdDSA(format, key, innerKey);
         break;
       }
-      case "X25519": {
-        result = exportKeyX25519(format, key, innerKey);
+      case "X25519":
+      case "X448": {
+        result = exportKeyXDH(format, key, innerKey);
         break;
       }
       case "AES-CTR":
@@ -1337,7 +1412,8 @@ class SubtleCrypto {
 
         if (
           (key[_algorithm].namedCurve === "P-256" && hash !== "SHA-256") ||
-          (key[_algorithm].namedCurve === "P-384" && hash !== "SHA-384")
+          (key[_algorithm].namedCurve === "P-384" && hash !== "SHA-384") ||
+          (key[_algorithm].namedCurve === "K-256" && hash !== "SHA-256")
         ) {
           throw new DOMException(
             "Not implemented",
@@ -1363,7 +1439,7 @@ class SubtleCrypto {
           );
         }
 
-        return ops.op_crypto_verify_ed25519(keyData, data, signature);
+        return OKP_CURVES[algorithmName].verify(keyData, data, signature);
       }
     }
 
@@ -1986,7 +2062,8 @@ async function generateKey(normalizedAlgorithm, extractable, usages) {
 
       return generateKeyAES(normalizedAlgorithm, extractable, usages);
     }
-    case "X25519": {
+    case "X25519":
+    case "X448": {
       if (
         ArrayPrototypeFind(
           usages,
@@ -1995,9 +2072,10 @@ async function generateKey(normalizedAlgorithm, extractable, usages) {
       ) {
         throw new DOMException("Invalid key usages", "SyntaxError");
       }
-      const privateKeyData = new Uint8Array(32);
-      const publicKeyData = new Uint8Array(32);
-      ops.op_crypto_generate_x25519_keypair(privateKeyData, publicKeyData);
+      const curve = OKP_CURVES[algorithmName];
+      const privateKeyData = new Uint8Array(curve.keyLength);
+      const publicKeyData = new Uint8Array(curve.keyLength);
+      curve.generateKeypair(privateKeyData, publicKeyData);
 
       const handle = {};
       WeakMapPrototypeSet(KEY_STORE, handle, privateKeyData);
@@ -2037,13 +2115,10 @@ async function generateKey(normalizedAlgorithm, extractable, usages) {
         throw new DOMException("Invalid key usages", "SyntaxError");
       }
 
-      const ED25519_SEED_LEN = 32;
-      const ED25519_PUBLIC_KEY_LEN = 32;
-      const privateKeyData = new Uint8Array(ED25519_SEED_LEN);
-      const publicKeyData = new Uint8Array(ED25519_PUBLIC_KEY_LEN);
-      if (
-        !ops.op_crypto_generate_ed25519_keypair(privateKeyData, publicKeyData)
-      ) {
+      const curve = OKP_CURVES[algorithmName];
+      const privateKeyData = new Uint8Array(curve.keyLength);
+      const publicKeyData = new Uint8Array(curve.keyLength);
+      if (!curve.generateKeypair(privateKeyData, publicKeyData)) {
         throw new DOMException("Failed to generate key", "OperationError");
       }
 
@@ -2132,12 +2207,14 @@ async function generateKey(normalizedAlgorithm, extractable, usages) {
   }
 }
 
-function importKeyEd25519(
+function importKeyEdDSA(
+  algorithmName,
   format,
   keyData,
   extractable,
   keyUsages,
 ) {
+  const curve = OKP_CURVES[algorithmName];
   switch (format) {
     case "raw": {
       // 1.
@@ -2150,12 +2227,16 @@ function importKeyEd25519(
         throw new DOMException("Invalid key usages", "SyntaxError");
       }
 
+      if (keyData.byteLength !== curve.keyLength) {
+        throw new DOMException("Invalid key data", "DataError");
+      }
+
       const handle = {};
       WeakMapPrototypeSet(KEY_STORE, handle, keyData);
 
       // 2-3.
       const algorithm = {
-        name: "Ed25519",
+        name: algorithmName,
       };
 
       // 4-6.
@@ -2178,8 +2259,8 @@ function importKeyEd25519(
         throw new DOMException("Invalid key usages", "SyntaxError");
       }
 
-      const publicKeyData = new Uint8Array(32);
-      if (!ops.op_crypto_import_spki_ed25519(keyData, publicKeyData)) {
+      const publicKeyData = new Uint8Array(curve.keyLength);
+      if (!curve.importSpki(keyData, publicKeyData)) {
         throw new DOMException("Invalid key data", "DataError");
       }
 
@@ -2187,7 +2268,7 @@ function importKeyEd25519(
       WeakMapPrototypeSet(KEY_STORE, handle, publicKeyData);
 
       const algorithm = {
-        name: "Ed25519",
+        name: algorithmName,
       };
 
       return constructKey(
@@ -2209,8 +2290,8 @@ function importKeyEd25519(
         throw new DOMException("Invalid key usages", "SyntaxError");
       }
 
-      const privateKeyData = new Uint8Array(32);
-      if (!ops.op_crypto_import_pkcs8_ed25519(keyData, privateKeyData)) {
+      const privateKeyData = new Uint8Array(curve.keyLength);
+      if (!curve.importPkcs8(keyData, privateKeyData)) {
         throw new DOMException("Invalid key data", "DataError");
       }
 
@@ -2218,7 +2299,7 @@ function importKeyEd25519(
       WeakMapPrototypeSet(KEY_STORE, handle, privateKeyData);
 
       const algorithm = {
-        name: "Ed25519",
+        name: algorithmName,
       };
 
       return constructKey(
@@ -2268,7 +2349,7 @@ function importKeyEd25519(
       }
 
       // 4.
-      if (jwk.crv !== "Ed25519") {
+      if (jwk.crv !== algorithmName) {
         throw new DOMException("Invalid curve", "DataError");
       }
 
@@ -2325,12 +2406,15 @@ function importKeyEd25519(
         } catch (_) {
           throw new DOMException("invalid private key data", "DataError");
         }
+        if (privateKeyData.byteLength !== curve.keyLength) {
+          throw new DOMException("invalid private key data", "DataError");
+        }
 
         const handle = {};
         WeakMapPrototypeSet(KEY_STORE, handle, privateKeyData);
 
         const algorithm = {
-          name: "Ed25519",
+          name: algorithmName,
         };
 
         return constructKey(
@@ -2348,12 +2432,15 @@ function importKeyEd25519(
         } catch (_) {
           throw new DOMException("invalid public key data", "DataError");
         }
+        if (publicKeyData.byteLength !== curve.keyLength) {
+          throw new DOMException("invalid public key data", "DataError");
+        }
 
         const handle = {};
         WeakMapPrototypeSet(KEY_STORE, handle, publicKeyData);
 
         const algorithm = {
-          name: "Ed25519",
+          name: algorithmName,
         };
 
         return constructKey(
@@ -2370,12 +2457,14 @@ function importKeyEd25519(
   }
 }
 
-function importKeyX25519(
+function importKeyXDH(
+  algorithmName,
   format,
   keyData,
   extractable,
   keyUsages,
 ) {
+  const curve = OKP_CURVES[algorithmName];
   switch (format) {
     case "raw": {
       // 1.
@@ -2383,12 +2472,16 @@ function importKeyX25519(
         throw new DOMException("Invalid key usages", "SyntaxError");
       }
 
+      if (keyData.byteLength !== curve.keyLength) {
+        throw new DOMException("Invalid key data", "DataError");
+      }
+
       const handle = {};
       WeakMapPrototypeSet(KEY_STORE, handle, keyData);
 
       // 2-3.
       const algorithm = {
-        name: "X25519",
+        name: algorithmName,
       };
 
       // 4-6.
@@ -2406,8 +2499,8 @@ function importKeyX25519(
         throw new DOMException("Invalid key usages", "SyntaxError");
       }
 
-      const publicKeyData = new Uint8Array(32);
-      if (!ops.op_crypto_import_spki_x25519(keyData, publicKeyData)) {
+      const publicKeyData = new Uint8Array(curve.keyLength);
+      if (!curve.importSpki(keyData, publicKeyData)) {
         throw new DOMException("Invalid key data", "DataError");
       }
 
@@ -2415,7 +2508,7 @@ function importKeyX25519(
       WeakMapPrototypeSet(KEY_STORE, handle, publicKeyData);
 
       const algorithm = {
-        name: "X25519",
+        name: algorithmName,
       };
 
       return constructKey(
@@ -2437,8 +2530,8 @@ function importKeyX25519(
         throw new DOMException("Invalid key usages", "SyntaxError");
       }
 
-      const privateKeyData = new Uint8Array(32);
-      if (!ops.op_crypto_import_pkcs8_x25519(keyData, privateKeyData)) {
+      const privateKeyData = new Uint8Array(curve.keyLength);
+      if (!curve.importPkcs8(keyData, privateKeyData)) {
         throw new DOMException("Invalid key data", "DataError");
       }
 
@@ -2446,7 +2539,7 @@ function importKeyX25519(
       WeakMapPrototypeSet(KEY_STORE, handle, privateKeyData);
 
       const algorithm = {
-        name: "X25519",
+        name: algorithmName,
       };
 
       return constructKey(
@@ -2488,7 +2581,7 @@ function importKeyX25519(
       }
 
       // 5.
-      if (jwk.crv !== "X25519") {
+      if (jwk.crv !== algorithmName) {
         throw new DOMException("Invalid curve", "DataError");
       }
 
@@ -2535,12 +2628,15 @@ function importKeyX25519(
       if (jwk.d !== undefined) {
         // https://www.rfc-editor.org/rfc/rfc8037#section-2
         const privateKeyData = ops.op_crypto_base64url_decode(jwk.d);
+        if (privateKeyData.byteLength !== curve.keyLength) {
+          throw new DOMException("invalid private key data", "DataError");
+        }
 
         const handle = {};
         WeakMapPrototypeSet(KEY_STORE, handle, privateKeyData);
 
         const algorithm = {
-          name: "X25519",
+          name: algorithmName,
         };
 
         return constructKey(
@@ -2553,12 +2649,15 @@ function importKeyX25519(
       } else {
         // https://www.rfc-editor.org/rfc/rfc8037#section-2
         const publicKeyData = ops.op_crypto_base64url_decode(jwk.x);
+        if (publicKeyData.byteLength !== curve.keyLength) {
+          throw new DOMException("invalid public key data", "DataError");
+        }
 
         const handle = {};
         WeakMapPrototypeSet(KEY_STORE, handle, publicKeyData);
 
         const algorithm = {
-          name: "X25519",
+          name: algorithmName,
         };
 
         return constructKey(
@@ -3228,6 +3327,10 @@ function importKeyEC(
             algNamedCurve = "P-384";
             break;
           }
+          case "ES256K": {
+            algNamedCurve = "K-256";
+            break;
+          }
           case "ES512": {
             algNamedCurve = "P-521";
             break;
@@ -4042,7 +4145,8 @@ function exportKeyRSA(format, key, innerKey) {
   }
 }
 
-function exportKeyEd25519(format, key, innerKey) {
+function exportKeyEdDSA(format, key, innerKey) {
+  const curve = OKP_CURVES[key[_algorithm].name];
   switch (format) {
     case "raw": {
       // 1.
@@ -4065,7 +4169,7 @@ function exportKeyEd25519(format, key, innerKey) {
         );
       }
 
-      const spkiDer = ops.op_crypto_export_spki_ed25519(innerKey);
+      const spkiDer = curve.exportSpki(innerKey);
       return TypedArrayPrototypeGetBuffer(spkiDer);
     }
     case "pkcs8": {
@@ -4077,20 +4181,17 @@ function exportKeyEd25519(format, key, innerKey) {
         );
       }
 
-      const pkcs8Der = ops.op_crypto_export_pkcs8_ed25519(
-        new Uint8Array([0x04, 0x22, ...new SafeArrayIterator(innerKey)]),
-      );
-      pkcs8Der[15] = 0x20;
+      const pkcs8Der = curve.exportPkcs8(innerKey);
       return TypedArrayPrototypeGetBuffer(pkcs8Der);
     }
     case "jwk": {
       const x = key[_type] === "private"
-        ? ops.op_crypto_jwk_x_ed25519(innerKey)
+        ? curve.jwkX(innerKey)
         : ops.op_crypto_base64url_encode(innerKey);
       const jwk = {
         kty: "OKP",
         alg: "EdDSA",
-        crv: "Ed25519",
+        crv: key[_algorithm].name,
         x,
         "key_ops": key.usages,
         ext: key[_extractable],
@@ -4105,7 +4206,8 @@ function exportKeyEd25519(format, key, innerKey) {
   }
 }
 
-function exportKeyX25519(format, key, innerKey) {
+function exportKeyXDH(format, key, innerKey) {
+  const curve = OKP_CURVES[key[_algorithm].name];
   switch (format) {
     case "raw": {
       // 1.
@@ -4128,7 +4230,7 @@ function exportKeyX25519(format, key, innerKey) {
         );
       }
 
-      const spkiDer = ops.op_crypto_export_spki_x25519(innerKey);
+      const spkiDer = curve.exportSpki(innerKey);
       return TypedArrayPrototypeGetBuffer(spkiDer);
     }
     case "pkcs8": {
@@ -4140,24 +4242,26 @@ function exportKeyX25519(format, key, innerKey) {
         );
       }
 
-      const pkcs8Der = ops.op_crypto_export_pkcs8_x25519(
-        new Uint8Array([0x04, 0x22, ...new SafeArrayIterator(innerKey)]),
-      );
-      pkcs8Der[15] = 0x20;
+      const pkcs8Der = curve.exportPkcs8(innerKey);
       return TypedArrayPrototypeGetBuffer(pkcs8Der);
     }
     case "jwk": {
-      if (key[_type] === "private") {
+      if (key[_type] === "private" && curve.jwkX === undefined) {
         throw new DOMException("Not implemented", "NotSupportedError");
       }
-      const x = ops.op_crypto_base64url_encode(innerKey);
+      const x = key[_type] === "private"
+        ? curve.jwkX(innerKey)
+        : ops.op_crypto_base64url_encode(innerKey);
       const jwk = {
         kty: "OKP",
-        crv: "X25519",
+        crv: key[_algorithm].name,
         x,
         "key_ops": key.usages,
         ext: key[_extractable],
       };
+      if (key[_type] === "private") {
+        jwk.d = ops.op_crypto_base64url_encode(innerKey);
+      }
       return jwk;
     }
     default:
@@ -4165,6 +4269,46 @@ function exportKeyX25519(format, key, innerKey) {
   }
 }
 
+/**
+ * @param {string} algorithmName
+ * @param {ReadableStream<BufferSource>} stream
+ * @param {string} prefix
+ * @returns {Promise<ArrayBuffer>}
+ */
+async function digestStream(algorithmName, stream, prefix) {
+  const reader = stream.getReader();
+  const rid = ops.op_crypto_digest_create(algorithmName);
+  try {
+    while (true) {
+      const { value, done } = await reader.read();
+      if (done) {
+        break;
+      }
+      let chunk;
+      try {
+        chunk = webidl.converters.BufferSource(value, prefix, "Chunk");
+      } catch (err) {
+        await reader.cancel(err);
+        throw err;
+      }
+      ops.op_crypto_digest_update(rid, chunk);
+    }
+    const result = ops.op_crypto_digest_finish(rid);
+    return TypedArrayPrototypeGetBuffer(result);
+  } finally {
+    core.tryClose(rid);
+    reader.releaseLock();
+  }
+}
+
+/**
+ * JWK names secp256k1 differently from WebCrypto.
+ * https://www.rfc-editor.org/rfc/rfc8812#section-3.1
+ */
+function jwkCurveName(namedCurve) {
+  return namedCurve === "K-256" ? "secp256k1" : namedCurve;
+}
+
 function exportKeyEC(format, key, innerKey) {
   switch (format) {
     case "raw": {
@@ -4229,7 +4373,7 @@ function exportKeyEC(format, key, innerKey) {
         };
 
         // 3.1
-        jwk.crv = key[_algorithm].namedCurve;
+        jwk.crv = jwkCurveName(key[_algorithm].namedCurve);
 
         // Missing from spec
         let algNamedCurve;
@@ -4243,6 +4387,10 @@ function exportKeyEC(format, key, innerKey) {
             algNamedCurve = "ES384";
             break;
           }
+          case "K-256": {
+            algNamedCurve = "ES256K";
+            break;
+          }
           case "P-521": {
             algNamedCurve = "ES512";
             break;
@@ -4281,7 +4429,7 @@ function exportKeyEC(format, key, innerKey) {
         jwk.alg = "ECDH";
 
         // 3.1
-        jwk.crv = key[_algorithm].namedCurve;
+        jwk.crv = jwkCurveName(key[_algorithm].namedCurve);
 
         // 3.2 - 3.4
         const data = ops.op_crypto_export_key({
@@ -4459,7 +4607,8 @@ async function deriveBits(normalizedAlgorithm, baseKey, length) {
 
       return TypedArrayPrototypeGetBuffer(buf);
     }
-    case "X25519": {
+    case "X25519":
+    case "X448": {
       // 1.
       if (baseKey[_type] !== "private") {
         throw new DOMException("Invalid key type", "InvalidAccessError");
@@ -4485,11 +4634,11 @@ async function deriveBits(normalizedAlgorithm, baseKey, length) {
       const uHandle = publicKey[_handle];
       const u = WeakMapPrototypeGet(KEY_STORE, uHandle);
 
-      const secret = new Uint8Array(32);
-      const isIdentity = ops.op_crypto_derive_bits_x25519(k, u, secret);
+      const curve = OKP_CURVES[baseKey[_algorithm].name];
+      const secret = new Uint8Array(curve.keyLength);
 
       // 6.
-      if (isIdentity) {
+      if (!curve.deriveBits(k, u, secret)) {
         throw new DOMException("Invalid key", "OperationError");
       }
 
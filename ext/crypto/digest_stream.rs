@@ -0,0 +1,65 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::OpState;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use deno_core::ZeroCopyBuf;
+use ring::digest;
+
+use crate::key::CryptoHash;
+
+/// A digest that is computed incrementally, so that the input of
+/// `crypto.subtle.digest` can be a stream that is never fully buffered.
+struct DigestContext(RefCell<Option<digest::Context>>);
+
+impl Resource for DigestContext {
+  fn name(&self) -> Cow<str> {
+    "cryptoDigest".into()
+  }
+}
+
+#[op]
+pub fn op_crypto_digest_create(
+  state: &mut OpState,
+  algorithm: CryptoHash,
+) -> ResourceId {
+  let context = digest::Context::new(algorithm.into());
+  state
+    .resource_table
+    .add(DigestContext(RefCell::new(Some(context))))
+}
+
+#[op]
+pub fn op_crypto_digest_update(
+  state: &mut OpState,
+  rid: ResourceId,
+  data: ZeroCopyBuf,
+) -> Result<(), AnyError> {
+  let resource = state.resource_table.get::<DigestContext>(rid)?;
+  let mut context = resource.0.borrow_mut();
+  let context = context
+    .as_mut()
+    .ok_or_else(|| type_error("Digest already finished"))?;
+  context.update(&data);
+  Ok(())
+}
+
+#[op]
+pub fn op_crypto_digest_finish(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<ZeroCopyBuf, AnyError> {
+  let resource = state.resource_table.take::<DigestContext>(rid)?;
+  let context = resource
+    .0
+    .borrow_mut()
+    .take()
+    .ok_or_else(|| type_error("Digest already finished"))?;
+  Ok(context.finish().as_ref().to_vec().into())
+}
@@ -245,6 +245,11 @@ fn export_key_ec(
 
           point.as_ref().to_vec()
         }
+        EcNamedCurve::K256 => {
+          let point = key_data.as_ec_public_key_k256()?;
+
+          point.as_ref().to_vec()
+        }
         EcNamedCurve::P521 => {
           return Err(data_error("Unsupported named curve"))
         }
@@ -263,6 +268,11 @@ fn export_key_ec(
 
           point.as_ref().to_vec()
         }
+        EcNamedCurve::K256 => {
+          let point = key_data.as_ec_public_key_k256()?;
+
+          point.as_ref().to_vec()
+        }
         EcNamedCurve::P521 => {
           return Err(data_error("Unsupported named curve"))
         }
@@ -277,6 +287,10 @@ fn export_key_ec(
           oid: elliptic_curve::ALGORITHM_OID,
           parameters: Some((&p384::NistP384::OID).into()),
         },
+        EcNamedCurve::K256 => AlgorithmIdentifier {
+          oid: elliptic_curve::ALGORITHM_OID,
+          parameters: Some((&ID_SECP256K1_OID).into()),
+        },
         EcNamedCurve::P521 => {
           return Err(data_error("Unsupported named curve"))
         }
@@ -343,6 +357,24 @@ fn export_key_ec(
           ))
         }
       }
+      EcNamedCurve::K256 => {
+        let point = key_data.as_ec_public_key_k256()?;
+        let coords = point.coordinates();
+
+        if let k256::elliptic_curve::sec1::Coordinates::Uncompressed { x, y } =
+          coords
+        {
+          Ok(ExportKeyResult::JwkPublicEc {
+            x: bytes_to_b64(x),
+            y: bytes_to_b64(y),
+          })
+        } else {
+          Err(custom_error(
+            "DOMExceptionOperationError",
+            "failed to decode public key",
+          ))
+        }
+      }
       EcNamedCurve::P521 => Err(data_error("Unsupported named curve")),
     },
     ExportKeyFormat::JwkPrivate => {
@@ -394,6 +426,29 @@ fn export_key_ec(
             Err(data_error("expected valid public EC key"))
           }
         }
+
+        EcNamedCurve::K256 => {
+          let ec_key =
+            k256::SecretKey::from_pkcs8_der(private_key).map_err(|_| {
+              custom_error(
+                "DOMExceptionOperationError",
+                "failed to decode private key",
+              )
+            })?;
+
+          let point = ec_key.public_key().to_encoded_point(false);
+          if let elliptic_curve::sec1::Coordinates::Uncompressed { x, y } =
+            point.coordinates()
+          {
+            Ok(ExportKeyResult::JwkPrivateEc {
+              x: bytes_to_b64(x),
+              y: bytes_to_b64(y),
+              d: bytes_to_b64(&ec_key.to_be_bytes()),
+            })
+          } else {
+            Err(data_error("expected valid public EC key"))
+          }
+        }
         _ => Err(not_supported_error("Unsupported namedCurve")),
       }
     }
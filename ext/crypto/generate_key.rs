@@ -5,6 +5,7 @@ use deno_core::op;
 use deno_core::task::spawn_blocking;
 use deno_core::ZeroCopyBuf;
 use elliptic_curve::rand_core::OsRng;
+use k256::pkcs8::EncodePrivateKey;
 use num_traits::FromPrimitive;
 use once_cell::sync::Lazy;
 use ring::rand::SecureRandom;
@@ -84,6 +85,14 @@ fn generate_key_rsa(
 }
 
 fn generate_key_ec(named_curve: EcNamedCurve) -> Result<Vec<u8>, AnyError> {
+  if named_curve == EcNamedCurve::K256 {
+    // ring doesn't support secp256k1.
+    let pkcs8 = k256::SecretKey::random(&mut OsRng)
+      .to_pkcs8_der()
+      .map_err(|_| operation_error("Failed to generate EC key"))?;
+    return Ok(pkcs8.as_bytes().to_vec());
+  }
+
   let curve = match named_curve {
     EcNamedCurve::P256 => &ring::signature::ECDSA_P256_SHA256_FIXED_SIGNING,
     EcNamedCurve::P384 => &ring::signature::ECDSA_P384_SHA384_FIXED_SIGNING,
@@ -4,6 +4,7 @@ use deno_core::error::AnyError;
 use deno_core::op;
 use deno_core::ZeroCopyBuf;
 use elliptic_curve::pkcs8::PrivateKeyInfo;
+use k256::pkcs8::DecodePrivateKey;
 use p256::pkcs8::EncodePrivateKey;
 use ring::signature::EcdsaKeyPair;
 use rsa::pkcs1::UIntRef;
@@ -498,6 +499,12 @@ fn import_key_ec_jwk_to_point(
 
       p384::EncodedPoint::from_affine_coordinates(&x, &y, false).to_bytes()
     }
+    EcNamedCurve::K256 => {
+      let x = decode_b64url_to_field_bytes::<k256::Secp256k1>(&x)?;
+      let y = decode_b64url_to_field_bytes::<k256::Secp256k1>(&y)?;
+
+      k256::EncodedPoint::from_affine_coordinates(&x, &y, false).to_bytes()
+    }
     _ => return Err(not_supported_error("Unsupported named curve")),
   };
 
@@ -532,6 +539,12 @@ fn import_key_ec_jwk(
 
           pk.to_pkcs8_der()?
         }
+        EcNamedCurve::K256 => {
+          let d = decode_b64url_to_field_bytes::<k256::Secp256k1>(&d)?;
+          let pk = k256::SecretKey::from_be_bytes(&d)?;
+
+          pk.to_pkcs8_der()?
+        }
         EcNamedCurve::P521 => {
           return Err(data_error("Unsupported named curve"))
         }
@@ -539,18 +552,23 @@ fn import_key_ec_jwk(
 
       // Import using ring, to validate key
       let key_alg = match named_curve {
-        EcNamedCurve::P256 => CryptoNamedCurve::P256.try_into()?,
-        EcNamedCurve::P384 => CryptoNamedCurve::P256.try_into()?,
+        EcNamedCurve::P256 => Some(CryptoNamedCurve::P256.try_into()?),
+        EcNamedCurve::P384 => Some(CryptoNamedCurve::P256.try_into()?),
+        // ring doesn't support secp256k1, the private key was validated when
+        // it was decoded above.
+        EcNamedCurve::K256 => None,
         EcNamedCurve::P521 => {
           return Err(data_error("Unsupported named curve"))
         }
       };
 
-      let _key_pair = EcdsaKeyPair::from_private_key_and_public_key(
-        key_alg,
-        private_d.as_bytes(),
-        point_bytes.as_ref(),
-      );
+      if let Some(key_alg) = key_alg {
+        let _key_pair = EcdsaKeyPair::from_private_key_and_public_key(
+          key_alg,
+          private_d.as_bytes(),
+          point_bytes.as_ref(),
+        );
+      }
 
       Ok(ImportKeyResult::Ec {
         raw_data: RawKeyData::Private(pkcs8_der.as_bytes().to_vec().into()),
@@ -603,6 +621,15 @@ fn import_key_ec(
             return Err(data_error("invalid P-384 elliptic curve point"));
           }
         }
+        EcNamedCurve::K256 => {
+          // 1-2.
+          let point = k256::EncodedPoint::from_bytes(&data)
+            .map_err(|_| data_error("invalid K-256 elliptic curve point"))?;
+          // 3.
+          if point.is_identity() {
+            return Err(data_error("invalid K-256 elliptic curve point"));
+          }
+        }
         _ => return Err(not_supported_error("Unsupported named curve")),
       };
       Ok(ImportKeyResult::Ec {
@@ -613,7 +640,7 @@ fn import_key_ec(
       // 2-7
       // Deserialize PKCS8 - validate structure, extracts named_curve
       let named_curve_alg = match named_curve {
-        EcNamedCurve::P256 | EcNamedCurve::P384 => {
+        EcNamedCurve::P256 | EcNamedCurve::P384 | EcNamedCurve::K256 => {
           let pk = PrivateKeyInfo::from_der(data.as_ref())
             .map_err(|_| data_error("expected valid PKCS#8 data"))?;
           pk.algorithm
@@ -635,21 +662,29 @@ fn import_key_ec(
         ID_SECP384R1_OID => Some(EcNamedCurve::P384),
         // id-secp521r1
         ID_SECP521R1_OID => Some(EcNamedCurve::P521),
+        // secp256k1
+        ID_SECP256K1_OID => Some(EcNamedCurve::K256),
         _ => None,
       };
 
       // 10.
       if let Some(pk_named_curve) = pk_named_curve {
-        let signing_alg = match pk_named_curve {
-          EcNamedCurve::P256 => CryptoNamedCurve::P256.try_into()?,
-          EcNamedCurve::P384 => CryptoNamedCurve::P384.try_into()?,
-          EcNamedCurve::P521 => {
-            return Err(data_error("Unsupported named curve"))
-          }
-        };
+        if pk_named_curve == EcNamedCurve::K256 {
+          // ring doesn't support secp256k1.
+          k256::SecretKey::from_pkcs8_der(&data)
+            .map_err(|_| data_error("invalid K-256 private key"))?;
+        } else {
+          let signing_alg = match pk_named_curve {
+            EcNamedCurve::P256 => CryptoNamedCurve::P256.try_into()?,
+            EcNamedCurve::P384 => CryptoNamedCurve::P384.try_into()?,
+            EcNamedCurve::P521 | EcNamedCurve::K256 => {
+              return Err(data_error("Unsupported named curve"))
+            }
+          };
 
-        // deserialize pkcs8 using ring crate, to VALIDATE public key
-        let _private_key = EcdsaKeyPair::from_pkcs8(signing_alg, &data)?;
+          // deserialize pkcs8 using ring crate, to VALIDATE public key
+          let _private_key = EcdsaKeyPair::from_pkcs8(signing_alg, &data)?;
+        }
 
         // 11.
         if named_curve != pk_named_curve {
@@ -693,6 +728,8 @@ fn import_key_ec(
         ID_SECP384R1_OID => Some(EcNamedCurve::P384),
         // id-secp521r1
         ID_SECP521R1_OID => Some(EcNamedCurve::P521),
+        // secp256k1
+        ID_SECP256K1_OID => Some(EcNamedCurve::K256),
         _ => None,
       };
 
@@ -728,6 +765,18 @@ fn import_key_ec(
 
             point.as_bytes().len()
           }
+          EcNamedCurve::K256 => {
+            let point =
+              k256::EncodedPoint::from_bytes(&*encoded_key).map_err(|_| {
+                data_error("invalid K-256 elliptic curve SPKI data")
+              })?;
+
+            if point.is_identity() {
+              return Err(data_error("invalid K-256 elliptic curve point"));
+            }
+
+            point.as_bytes().len()
+          }
           _ => return Err(not_supported_error("Unsupported named curve")),
         };
 
@@ -1,5 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::error::not_supported;
+use deno_core::error::AnyError;
 use ring::agreement::Algorithm as RingAlgorithm;
 use ring::digest;
 use ring::hkdf;
@@ -35,35 +37,47 @@ pub enum CryptoNamedCurve {
   P256,
   #[serde(rename = "P-384")]
   P384,
+  /// secp256k1, which ring doesn't support.
+  #[serde(rename = "K-256")]
+  K256,
 }
 
-impl From<CryptoNamedCurve> for &RingAlgorithm {
-  fn from(curve: CryptoNamedCurve) -> &'static RingAlgorithm {
+impl TryFrom<CryptoNamedCurve> for &RingAlgorithm {
+  type Error = AnyError;
+
+  fn try_from(curve: CryptoNamedCurve) -> Result<Self, AnyError> {
     match curve {
-      CryptoNamedCurve::P256 => &ring::agreement::ECDH_P256,
-      CryptoNamedCurve::P384 => &ring::agreement::ECDH_P384,
+      CryptoNamedCurve::P256 => Ok(&ring::agreement::ECDH_P256),
+      CryptoNamedCurve::P384 => Ok(&ring::agreement::ECDH_P384),
+      CryptoNamedCurve::K256 => Err(not_supported()),
     }
   }
 }
 
-impl From<CryptoNamedCurve> for &EcdsaSigningAlgorithm {
-  fn from(curve: CryptoNamedCurve) -> &'static EcdsaSigningAlgorithm {
+impl TryFrom<CryptoNamedCurve> for &EcdsaSigningAlgorithm {
+  type Error = AnyError;
+
+  fn try_from(curve: CryptoNamedCurve) -> Result<Self, AnyError> {
     match curve {
       CryptoNamedCurve::P256 => {
-        &ring::signature::ECDSA_P256_SHA256_FIXED_SIGNING
+        Ok(&ring::signature::ECDSA_P256_SHA256_FIXED_SIGNING)
       }
       CryptoNamedCurve::P384 => {
-        &ring::signature::ECDSA_P384_SHA384_FIXED_SIGNING
+        Ok(&ring::signature::ECDSA_P384_SHA384_FIXED_SIGNING)
       }
+      CryptoNamedCurve::K256 => Err(not_supported()),
     }
   }
 }
 
-impl From<CryptoNamedCurve> for &EcdsaVerificationAlgorithm {
-  fn from(curve: CryptoNamedCurve) -> &'static EcdsaVerificationAlgorithm {
+impl TryFrom<CryptoNamedCurve> for &EcdsaVerificationAlgorithm {
+  type Error = AnyError;
+
+  fn try_from(curve: CryptoNamedCurve) -> Result<Self, AnyError> {
     match curve {
-      CryptoNamedCurve::P256 => &ring::signature::ECDSA_P256_SHA256_FIXED,
-      CryptoNamedCurve::P384 => &ring::signature::ECDSA_P384_SHA384_FIXED,
+      CryptoNamedCurve::P256 => Ok(&ring::signature::ECDSA_P256_SHA256_FIXED),
+      CryptoNamedCurve::P384 => Ok(&ring::signature::ECDSA_P384_SHA384_FIXED),
+      CryptoNamedCurve::K256 => Err(not_supported()),
     }
   }
 }
@@ -290,9 +290,11 @@ interface SubtleCrypto {
     signature: BufferSource,
     data: BufferSource,
   ): Promise<boolean>;
+  /** Hashes `data`. A stream is hashed incrementally as its chunks arrive,
+   * without buffering the whole input. */
   digest(
     algorithm: AlgorithmIdentifier,
-    data: BufferSource,
+    data: BufferSource | ReadableStream<BufferSource>,
   ): Promise<ArrayBuffer>;
   encrypt(
     algorithm:
@@ -51,6 +51,7 @@ use std::path::PathBuf;
 pub use rand; // Re-export rand
 
 mod decrypt;
+mod digest_stream;
 mod ed25519;
 mod encrypt;
 mod export_key;
@@ -59,6 +60,7 @@ mod import_key;
 mod key;
 mod shared;
 mod x25519;
+mod x448;
 
 pub use crate::decrypt::op_crypto_decrypt;
 pub use crate::encrypt::op_crypto_encrypt;
@@ -84,6 +86,9 @@ deno_core::extension!(deno_crypto,
     op_crypto_encrypt,
     op_crypto_decrypt,
     op_crypto_subtle_digest,
+    digest_stream::op_crypto_digest_create,
+    digest_stream::op_crypto_digest_update,
+    digest_stream::op_crypto_digest_finish,
     op_crypto_random_uuid,
     op_crypto_wrap_key,
     op_crypto_unwrap_key,
@@ -103,6 +108,13 @@ deno_core::extension!(deno_crypto,
     ed25519::op_crypto_jwk_x_ed25519,
     x25519::op_crypto_export_spki_x25519,
     x25519::op_crypto_export_pkcs8_x25519,
+    x448::op_crypto_generate_x448_keypair,
+    x448::op_crypto_derive_bits_x448,
+    x448::op_crypto_import_spki_x448,
+    x448::op_crypto_import_pkcs8_x448,
+    x448::op_crypto_export_spki_x448,
+    x448::op_crypto_export_pkcs8_x448,
+    x448::op_crypto_jwk_x_x448,
   ],
   esm = [ "00_crypto.js" ],
   options = {
@@ -257,6 +269,16 @@ pub async fn op_crypto_sign_key(
       }
       .to_vec()
     }
+    Algorithm::Ecdsa
+      if matches!(args.named_curve, Some(CryptoNamedCurve::K256)) =>
+    {
+      // ring doesn't support secp256k1. Signatures use SHA-256.
+      let secret_key = k256::SecretKey::from_pkcs8_der(&args.key.data)
+        .map_err(|_| type_error("Unexpected error decoding private key"))?;
+      let signing_key = k256::ecdsa::SigningKey::from(secret_key);
+      let signature: k256::ecdsa::Signature = signing_key.sign(data);
+      signature.as_ref().to_vec()
+    }
     Algorithm::Ecdsa => {
       let curve: &EcdsaSigningAlgorithm =
         args.named_curve.ok_or_else(not_supported)?.try_into()?;
@@ -373,6 +395,26 @@ pub async fn op_crypto_verify_key(
       let key = HmacKey::new(hash, &args.key.data);
       ring::hmac::verify(&key, data, &args.signature).is_ok()
     }
+    Algorithm::Ecdsa
+      if matches!(args.named_curve, Some(CryptoNamedCurve::K256)) =>
+    {
+      let verifying_key = match args.key.r#type {
+        KeyType::Private => {
+          let secret_key = k256::SecretKey::from_pkcs8_der(&args.key.data)
+            .map_err(|_| type_error("Unexpected error decoding private key"))?;
+          k256::ecdsa::VerifyingKey::from(secret_key.public_key())
+        }
+        KeyType::Public => {
+          k256::ecdsa::VerifyingKey::from_sec1_bytes(&args.key.data)
+            .map_err(|_| type_error("Unexpected error decoding public key"))?
+        }
+        _ => return Err(type_error("Invalid Key format".to_string())),
+      };
+      match k256::ecdsa::Signature::try_from(&*args.signature) {
+        Ok(signature) => verifying_key.verify(data, &signature).is_ok(),
+        Err(_) => false,
+      }
+    }
     Algorithm::Ecdsa => {
       let signing_alg: &EcdsaSigningAlgorithm =
         args.named_curve.ok_or_else(not_supported)?.try_into()?;
@@ -531,6 +573,45 @@ pub async fn op_crypto_derive_bits(
             public_key.as_affine(),
           );
 
+          // raw serialized x-coordinate of the computed point
+          Ok(shared_secret.raw_secret_bytes().to_vec().into())
+        }
+        CryptoNamedCurve::K256 => {
+          let secret_key = k256::SecretKey::from_pkcs8_der(&args.key.data)
+            .map_err(|_| type_error("Unexpected error decoding private key"))?;
+
+          let public_key = match public_key.r#type {
+            KeyType::Private => {
+              k256::SecretKey::from_pkcs8_der(&public_key.data)
+                .map_err(|_| {
+                  type_error("Unexpected error decoding private key")
+                })?
+                .public_key()
+            }
+            KeyType::Public => {
+              let point = k256::EncodedPoint::from_bytes(public_key.data)
+                .map_err(|_| {
+                  type_error("Unexpected error decoding private key")
+                })?;
+
+              let pk = k256::PublicKey::from_encoded_point(&point);
+              // pk is a constant time Option.
+              if pk.is_some().into() {
+                pk.unwrap()
+              } else {
+                return Err(type_error(
+                  "Unexpected error decoding private key",
+                ));
+              }
+            }
+            _ => unreachable!(),
+          };
+
+          let shared_secret = k256::elliptic_curve::ecdh::diffie_hellman(
+            secret_key.to_nonzero_scalar(),
+            public_key.as_affine(),
+          );
+
           // raw serialized x-coordinate of the computed point
           Ok(shared_secret.raw_secret_bytes().to_vec().into())
         }
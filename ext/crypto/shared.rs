@@ -23,6 +23,8 @@ pub const ID_SECP384R1_OID: const_oid::ObjectIdentifier =
   const_oid::ObjectIdentifier::new_unwrap("1.3.132.0.34");
 pub const ID_SECP521R1_OID: const_oid::ObjectIdentifier =
   const_oid::ObjectIdentifier::new_unwrap("1.3.132.0.35");
+pub const ID_SECP256K1_OID: const_oid::ObjectIdentifier =
+  const_oid::ObjectIdentifier::new_unwrap("1.3.132.0.10");
 
 #[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq)]
 pub enum ShaHash {
@@ -44,6 +46,8 @@ pub enum EcNamedCurve {
   P384,
   #[serde(rename = "P-521")]
   P521,
+  #[serde(rename = "K-256")]
+  K256,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -121,6 +125,23 @@ impl RawKeyData {
     }
   }
 
+  pub fn as_ec_public_key_k256(&self) -> Result<k256::EncodedPoint, AnyError> {
+    match self {
+      RawKeyData::Public(data) => {
+        // public_key is a serialized EncodedPoint
+        k256::EncodedPoint::from_bytes(data)
+          .map_err(|_| type_error("expected valid public EC key"))
+      }
+      RawKeyData::Private(data) => {
+        let signing_key = k256::SecretKey::from_pkcs8_der(data)
+          .map_err(|_| type_error("expected valid private EC key"))?;
+        Ok(signing_key.public_key().to_encoded_point(false))
+      }
+      // Should never reach here.
+      RawKeyData::Secret(_) => unreachable!(),
+    }
+  }
+
   pub fn as_ec_private_key(&self) -> Result<&[u8], AnyError> {
     match self {
       RawKeyData::Private(data) => Ok(data),
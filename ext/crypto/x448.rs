@@ -0,0 +1,148 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::ZeroCopyBuf;
+use elliptic_curve::pkcs8::PrivateKeyInfo;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use spki::der::Decode;
+use spki::der::Encode;
+use x448::PublicKey;
+use x448::Secret;
+
+const X448_KEY_LEN: usize = 56;
+
+#[op(fast)]
+pub fn op_crypto_generate_x448_keypair(pkey: &mut [u8], pubkey: &mut [u8]) {
+  let mut rng = OsRng;
+  rng.fill_bytes(pkey);
+  // https://www.rfc-editor.org/rfc/rfc7748#section-6.2
+  // The secret is clamped when it is used, so the random bytes are kept as
+  // the private key.
+  let secret = Secret::from_bytes(pkey).expect("Expected byteLength 56");
+  pubkey.copy_from_slice(PublicKey::from(&secret).as_bytes());
+}
+
+#[op(fast)]
+pub fn op_crypto_derive_bits_x448(
+  k: &[u8],
+  u: &[u8],
+  secret: &mut [u8],
+) -> bool {
+  let Some(k) = Secret::from_bytes(k) else {
+    return false;
+  };
+  // Fails for low order points.
+  let Some(u) = PublicKey::from_bytes(u) else {
+    return false;
+  };
+  // Fails if the shared secret is all zeros.
+  let Some(sh_sec) = k.as_diffie_hellman(&u) else {
+    return false;
+  };
+  secret.copy_from_slice(sh_sec.as_bytes());
+  true
+}
+
+// id-X448 OBJECT IDENTIFIER ::= { 1 3 101 111 }
+pub const X448_OID: const_oid::ObjectIdentifier =
+  const_oid::ObjectIdentifier::new_unwrap("1.3.101.111");
+
+#[op(fast)]
+pub fn op_crypto_import_spki_x448(key_data: &[u8], out: &mut [u8]) -> bool {
+  // 2-3.
+  let pk_info = match spki::SubjectPublicKeyInfo::from_der(key_data) {
+    Ok(pk_info) => pk_info,
+    Err(_) => return false,
+  };
+  // 4.
+  let alg = pk_info.algorithm.oid;
+  if alg != X448_OID {
+    return false;
+  }
+  // 5.
+  if pk_info.algorithm.parameters.is_some() {
+    return false;
+  }
+  if pk_info.subject_public_key.len() != X448_KEY_LEN {
+    return false;
+  }
+  out.copy_from_slice(pk_info.subject_public_key);
+  true
+}
+
+#[op(fast)]
+pub fn op_crypto_import_pkcs8_x448(key_data: &[u8], out: &mut [u8]) -> bool {
+  // 2-3.
+  // This should probably use OneAsymmetricKey instead
+  let pk_info = match PrivateKeyInfo::from_der(key_data) {
+    Ok(pk_info) => pk_info,
+    Err(_) => return false,
+  };
+  // 4.
+  let alg = pk_info.algorithm.oid;
+  if alg != X448_OID {
+    return false;
+  }
+  // 5.
+  if pk_info.algorithm.parameters.is_some() {
+    return false;
+  }
+  // 6.
+  // CurvePrivateKey ::= OCTET STRING
+  if pk_info.private_key.len() != X448_KEY_LEN + 2 {
+    return false;
+  }
+  out.copy_from_slice(&pk_info.private_key[2..]);
+  true
+}
+
+#[op]
+pub fn op_crypto_export_spki_x448(
+  pubkey: &[u8],
+) -> Result<ZeroCopyBuf, AnyError> {
+  let key_info = spki::SubjectPublicKeyInfo {
+    algorithm: spki::AlgorithmIdentifier {
+      // id-X448
+      oid: X448_OID,
+      parameters: None,
+    },
+    subject_public_key: pubkey,
+  };
+  Ok(key_info.to_vec()?.into())
+}
+
+#[op]
+pub fn op_crypto_export_pkcs8_x448(
+  pkey: &[u8],
+) -> Result<ZeroCopyBuf, AnyError> {
+  // CurvePrivateKey ::= OCTET STRING
+  let mut private_key = vec![0x04, pkey.len() as u8];
+  private_key.extend_from_slice(pkey);
+
+  // This should probably use OneAsymmetricKey instead
+  let pk_info = rsa::pkcs8::PrivateKeyInfo {
+    public_key: None,
+    algorithm: rsa::pkcs8::AlgorithmIdentifier {
+      // id-X448
+      oid: X448_OID,
+      parameters: None,
+    },
+    private_key: &private_key,
+  };
+
+  Ok(pk_info.to_vec()?.into())
+}
+
+// 'x' from Section 2 of RFC 8037
+// https://www.rfc-editor.org/rfc/rfc8037#section-2
+#[op]
+pub fn op_crypto_jwk_x_x448(pkey: &[u8]) -> Result<String, AnyError> {
+  let secret = Secret::from_bytes(pkey)
+    .ok_or_else(|| deno_core::error::type_error("invalid X448 private key"))?;
+  Ok(base64::encode_config(
+    PublicKey::from(&secret).as_bytes(),
+    base64::URL_SAFE_NO_PAD,
+  ))
+}
@@ -12,6 +12,61 @@
 
 const core = globalThis.Deno.core;
 const ops = core.ops;
+const primordials = globalThis.__bootstrap.primordials;
+const {
+  ObjectPrototypeIsPrototypeOf,
+  String,
+  Symbol,
+  TypeError,
+} = primordials;
+
+const _rid = Symbol("[[rid]]");
+
+class CookieJar {
+  /**
+   * @param {Deno.Cookie[]} cookies
+   */
+  constructor(cookies = []) {
+    this[_rid] = ops.op_fetch_cookie_jar_new(cookies);
+  }
+
+  /**
+   * @param {string | URL} [url]
+   * @returns {Deno.Cookie[]}
+   */
+  getCookies(url) {
+    return ops.op_fetch_cookie_jar_get_cookies(
+      this[_rid],
+      url === undefined ? null : String(url),
+    );
+  }
+
+  /**
+   * @param {string | URL} url
+   * @param {string} setCookie
+   */
+  setCookie(url, setCookie) {
+    ops.op_fetch_cookie_jar_set_cookie(
+      this[_rid],
+      String(url),
+      String(setCookie),
+    );
+  }
+
+  clear() {
+    ops.op_fetch_cookie_jar_clear(this[_rid]);
+  }
+
+  /** @returns {Deno.Cookie[]} */
+  toJSON() {
+    return this.getCookies();
+  }
+
+  close() {
+    core.close(this[_rid]);
+  }
+}
+const CookieJarPrototype = CookieJar.prototype;
 
 /**
  * @param {Deno.CreateHttpClientOptions} options
@@ -19,6 +74,23 @@ const ops = core.ops;
  */
 function createHttpClient(options) {
   options.caCerts ??= [];
+  if (options.proxy?.transport === "unix") {
+    options = {
+      ...options,
+      proxy: undefined,
+      unixSocketPath: options.proxy.path,
+    };
+  }
+  if (options.cookieJar !== undefined) {
+    if (!ObjectPrototypeIsPrototypeOf(CookieJarPrototype, options.cookieJar)) {
+      throw new TypeError("`cookieJar` must be a Deno.CookieJar");
+    }
+    options = {
+      ...options,
+      cookieJar: undefined,
+      cookieJarRid: options.cookieJar[_rid],
+    };
+  }
   return new HttpClient(
     ops.op_fetch_custom_client(
       options,
@@ -33,10 +105,22 @@ class HttpClient {
   constructor(rid) {
     this.rid = rid;
   }
+  /**
+   * @param {Deno.UpdateHttpClientTlsOptions} options
+   */
+  updateTls(options) {
+    ops.op_fetch_client_update_tls(this.rid, options);
+  }
+  /**
+   * @returns {Deno.HttpClientPoolMetrics}
+   */
+  poolMetrics() {
+    return ops.op_fetch_client_pool_metrics(this.rid);
+  }
   close() {
     core.close(this.rid);
   }
 }
 const HttpClientPrototype = HttpClient.prototype;
 
-export { createHttpClient, HttpClient, HttpClientPrototype };
+export { CookieJar, createHttpClient, HttpClient, HttpClientPrototype };
@@ -37,6 +37,7 @@ const {
   ObjectKeys,
   ObjectPrototypeIsPrototypeOf,
   RegExpPrototypeExec,
+  String,
   StringPrototypeStartsWith,
   Symbol,
   SymbolFor,
@@ -78,6 +79,7 @@ function processUrlList(urlList, urlListProcessed) {
  * @property {(() => string)[]} urlList
  * @property {string[]} urlListProcessed
  * @property {number | null} clientRid NOTE: non standard extension for `Deno.HttpClient`.
+ * @property {Deno.Proxy | null} proxy NOTE: non standard extension, overrides the proxy of the client.
  * @property {Blob | null} blobUrlEntry
  */
 
@@ -126,6 +128,7 @@ function newInnerRequest(method, url, headerList, body, maybeBlob) {
     urlList: [typeof url === "string" ? () => url : url],
     urlListProcessed: [],
     clientRid: null,
+    proxy: null,
     blobUrlEntry,
     url() {
       if (this.urlListProcessed[0] === undefined) {
@@ -177,6 +180,7 @@ function cloneInnerRequest(request, skipBody = false) {
     urlList: request.urlList,
     urlListProcessed: request.urlListProcessed,
     clientRid: request.clientRid,
+    proxy: request.proxy,
     blobUrlEntry: request.blobUrlEntry,
     url() {
       if (this.urlListProcessed[0] === undefined) {
@@ -351,6 +355,29 @@ class Request {
       request.clientRid = init.client?.rid ?? null;
     }
 
+    // NOTE: non standard extension. This handles the per-request proxy
+    if (init.proxy !== undefined) {
+      if (init.proxy === null) {
+        request.proxy = null;
+      } else if (typeof init.proxy?.url === "string") {
+        const basicAuth = init.proxy.basicAuth;
+        request.proxy = {
+          url: init.proxy.url,
+          basicAuth: basicAuth == null ? undefined : {
+            username: String(basicAuth.username),
+            password: String(basicAuth.password),
+          },
+        };
+      } else {
+        throw webidl.makeException(
+          TypeError,
+          "`proxy` must be an object with a `url` string",
+          prefix,
+          "Argument 2",
+        );
+      }
+    }
+
     // 27.
     this[_request] = request;
 
@@ -541,6 +568,7 @@ webidl.converters["RequestInit"] = webidl.createDictionaryConverter(
       ),
     },
     { key: "client", converter: webidl.converters.any },
+    { key: "proxy", converter: webidl.converters.any },
   ],
 );
 
@@ -17,6 +17,7 @@ import { byteLowerCase } from "ext:deno_web/00_infra.js";
 import { BlobPrototype } from "ext:deno_web/09_file.js";
 import {
   errorReadableStream,
+  getReadableStreamResourceBacking,
   readableStreamForRid,
   ReadableStreamPrototype,
 } from "ext:deno_web/06_streams.js";
@@ -65,16 +66,26 @@ const REQUEST_BODY_HEADER_NAMES = [
 const requestBodyReaders = new SafeWeakMap();
 
 /**
- * @param {{ method: string, url: string, headers: [string, string][], clientRid: number | null, hasBody: boolean }} args
+ * @param {{ method: string, url: string, headers: [string, string][], clientRid: number | null, proxy: Deno.Proxy | null, hasBody: boolean }} args
  * @param {Uint8Array | null} body
  * @returns {{ requestRid: number, requestBodyRid: number | null }}
  */
-function opFetch(method, url, headers, clientRid, hasBody, bodyLength, body) {
+function opFetch(
+  method,
+  url,
+  headers,
+  clientRid,
+  proxy,
+  hasBody,
+  bodyLength,
+  body,
+) {
   return ops.op_fetch(
     method,
     url,
     headers,
     clientRid,
+    proxy,
     hasBody,
     bodyLength,
     body,
@@ -184,6 +195,7 @@ async function mainFetch(req, recursive, terminator) {
     req.currentUrl(),
     req.headerList,
     req.clientRid,
+    req.proxy,
     reqBody !== null,
     req.body?.length,
     ObjectPrototypeIsPrototypeOf(Uint8ArrayPrototype, reqBody) ? reqBody : null,
@@ -549,10 +561,42 @@ function handleWasmStreaming(source, rid) {
       throw new TypeError(`HTTP status code ${res.status}`);
     }
 
-    // Pass the resolved URL to v8.
-    ops.op_wasm_streaming_set_url(rid, res.url);
+    // Pass the resolved URL to v8. If the module is in the runtime's
+    // `WasmModuleCache`, it was compiled from the cached bytes and the body is
+    // not needed.
+    if (ops.op_wasm_streaming_set_url(rid, res.url)) {
+      if (res.body !== null) {
+        PromisePrototypeCatch(res.body.cancel(), () => {});
+      }
+      return;
+    }
 
-    if (res.body !== null) {
+    const resourceBacking = res.body !== null
+      ? getReadableStreamResourceBacking(res.body)
+      : undefined;
+    if (resourceBacking) {
+      // 2.6.
+      // The body is backed by a resource, so its chunks are fed to the
+      // compiler from Rust as they are downloaded, without going through JS.
+      // Acquiring a reader locks the stream.
+      res.body.getReader();
+      PromisePrototypeThen(
+        core.opAsync(
+          "op_wasm_streaming_stream",
+          rid,
+          resourceBacking.rid,
+        ),
+        // 2.7
+        () => {
+          if (resourceBacking.autoClose) core.tryClose(resourceBacking.rid);
+        },
+        // 2.8
+        (err) => {
+          if (resourceBacking.autoClose) core.tryClose(resourceBacking.rid);
+          core.abortWasmStreaming(rid, err);
+        },
+      );
+    } else if (res.body !== null) {
       // 2.6.
       // Rather than consuming the body as an ArrayBuffer, this passes each
       // chunk to the feed as soon as it's available.
@@ -0,0 +1,559 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! A cookie jar for `fetch()`, following RFC 6265 with the additions of
+//! RFC 6265bis for `SameSite` and secure cookies. There is no public suffix
+//! list, so a cookie can only be set for a domain with at least two labels,
+//! e.g. `example.com`, unless it's the host of the request itself.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::net::IpAddr;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::url::Url;
+use deno_core::OpState;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SameSite {
+  Strict,
+  Lax,
+  None,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cookie {
+  pub name: String,
+  pub value: String,
+  pub domain: String,
+  /// Whether the cookie is only sent to `domain` itself, and not to its
+  /// subdomains, because it was set without a `Domain` attribute.
+  #[serde(default)]
+  pub host_only: bool,
+  pub path: String,
+  /// Milliseconds since the UNIX epoch. Cookies without an expiry time are
+  /// session cookies, kept for as long as the jar.
+  pub expires: Option<u64>,
+  #[serde(default)]
+  pub secure: bool,
+  #[serde(default)]
+  pub http_only: bool,
+  pub same_site: Option<SameSite>,
+}
+
+fn now_ms() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_millis() as u64)
+    .unwrap_or(0)
+}
+
+impl Cookie {
+  /// Parses a `Set-Cookie` header received in a response to `url`. Returns
+  /// `None` if the cookie must be ignored.
+  pub fn parse(set_cookie: &str, url: &Url, now: u64) -> Option<Self> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    let mut parts = set_cookie.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let (name, value) = (name.trim(), value.trim());
+    if name.is_empty() {
+      return None;
+    }
+
+    let mut expires = None;
+    let mut max_age = None;
+    let mut domain = None;
+    let mut path = None;
+    let mut secure = false;
+    let mut http_only = false;
+    let mut same_site = None;
+    for attribute in parts {
+      let (key, value) = match attribute.split_once('=') {
+        Some((key, value)) => (key.trim(), value.trim()),
+        None => (attribute.trim(), ""),
+      };
+      match key.to_ascii_lowercase().as_str() {
+        "expires" => {
+          if let Some(time) = parse_cookie_date(value) {
+            expires = Some(time);
+          }
+        }
+        "max-age" => {
+          if let Ok(delta) = value.parse::<i64>() {
+            max_age = Some(if delta <= 0 {
+              0
+            } else {
+              now.saturating_add((delta as u64).saturating_mul(1000))
+            });
+          }
+        }
+        "domain" if !value.is_empty() => {
+          let value = value.strip_prefix('.').unwrap_or(value);
+          domain = Some(value.to_ascii_lowercase());
+        }
+        "path" => {
+          path = value.starts_with('/').then(|| value.to_string());
+        }
+        "secure" => secure = true,
+        "httponly" => http_only = true,
+        "samesite" => {
+          same_site = match value.to_ascii_lowercase().as_str() {
+            "strict" => Some(SameSite::Strict),
+            "lax" => Some(SameSite::Lax),
+            "none" => Some(SameSite::None),
+            _ => None,
+          };
+        }
+        _ => {}
+      }
+    }
+
+    let (domain, host_only) = match domain {
+      Some(domain) => {
+        if !domain_match(&host, &domain)
+          || (domain != host && !domain.contains('.'))
+        {
+          return None;
+        }
+        (domain, false)
+      }
+      None => (host, true),
+    };
+    if secure && !is_secure(url) {
+      return None;
+    }
+    if same_site == Some(SameSite::None) && !secure {
+      return None;
+    }
+
+    Some(Self {
+      name: name.to_string(),
+      value: value.to_string(),
+      domain,
+      host_only,
+      path: path.unwrap_or_else(|| default_path(url)),
+      expires: max_age.or(expires),
+      secure,
+      http_only,
+      same_site,
+    })
+  }
+
+  fn is_expired(&self, now: u64) -> bool {
+    matches!(self.expires, Some(expires) if expires <= now)
+  }
+
+  /// Whether the cookie should be sent with a request to `url`.
+  pub fn matches(&self, url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+      return false;
+    };
+    let host = host.to_ascii_lowercase();
+    let domain_matches = if self.host_only {
+      host == self.domain
+    } else {
+      domain_match(&host, &self.domain)
+    };
+    domain_matches
+      && path_match(url.path(), &self.path)
+      && (!self.secure || is_secure(url))
+  }
+}
+
+fn is_secure(url: &Url) -> bool {
+  matches!(url.scheme(), "https" | "wss")
+}
+
+/// RFC 6265 5.1.3
+fn domain_match(host: &str, domain: &str) -> bool {
+  if host == domain {
+    return true;
+  }
+  host.parse::<IpAddr>().is_err()
+    && host.len() > domain.len()
+    && host.ends_with(domain)
+    && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+}
+
+/// RFC 6265 5.1.4
+fn default_path(url: &Url) -> String {
+  let path = url.path();
+  match path.rfind('/') {
+    Some(0) | None => "/".to_string(),
+    Some(idx) => path[..idx].to_string(),
+  }
+}
+
+/// RFC 6265 5.1.4
+fn path_match(request_path: &str, cookie_path: &str) -> bool {
+  request_path == cookie_path
+    || (request_path.starts_with(cookie_path)
+      && (cookie_path.ends_with('/')
+        || request_path.as_bytes()[cookie_path.len()] == b'/'))
+}
+
+/// Parses the date of an `Expires` attribute with the lenient algorithm of
+/// RFC 6265 5.1.1, returning milliseconds since the UNIX epoch.
+fn parse_cookie_date(value: &str) -> Option<u64> {
+  const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct",
+    "nov", "dec",
+  ];
+  let is_delimiter =
+    |c: char| matches!(c, '\t' | ' '..='/' | ';'..='@' | '['..='`' | '{'..='~');
+  // Parses 1 to `max` leading digits, which may be followed by anything but
+  // more digits.
+  let digits = |token: &str, min: usize, max: usize| -> Option<u32> {
+    let len = token
+      .find(|c: char| !c.is_ascii_digit())
+      .unwrap_or(token.len());
+    if len < min || len > max {
+      return None;
+    }
+    token[..len].parse().ok()
+  };
+
+  let mut time = None;
+  let mut day = None;
+  let mut month = None;
+  let mut year = None;
+  for token in value.split(is_delimiter).filter(|t| !t.is_empty()) {
+    if time.is_none() {
+      let mut fields = token.splitn(3, ':');
+      if let (Some(h), Some(m), Some(s)) =
+        (fields.next(), fields.next(), fields.next())
+      {
+        let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if all_digits(h) && all_digits(m) {
+          if let (Some(h), Some(m), Some(s)) =
+            (digits(h, 1, 2), digits(m, 1, 2), digits(s, 1, 2))
+          {
+            time = Some((h, m, s));
+            continue;
+          }
+        }
+      }
+    }
+    if day.is_none() {
+      if let Some(d) = digits(token, 1, 2) {
+        day = Some(d);
+        continue;
+      }
+    }
+    if month.is_none() {
+      let prefix = token.get(..3).unwrap_or_default().to_ascii_lowercase();
+      if let Some(idx) = MONTHS.iter().position(|m| *m == prefix) {
+        month = Some(idx as u32 + 1);
+        continue;
+      }
+    }
+    if year.is_none() {
+      if let Some(y) = digits(token, 2, 4) {
+        year = Some(y);
+        continue;
+      }
+    }
+  }
+
+  let (hour, minute, second) = time?;
+  let (day, month, mut year) = (day?, month?, year?);
+  if (70..=99).contains(&year) {
+    year += 1900;
+  } else if year <= 69 {
+    year += 2000;
+  }
+  if !(1..=31).contains(&day)
+    || year < 1601
+    || hour > 23
+    || minute > 59
+    || second > 59
+  {
+    return None;
+  }
+
+  let days = days_from_civil(year as i64, month, day);
+  let seconds =
+    days * 86400 + hour as i64 * 3600 + minute as i64 * 60 + second as i64;
+  // Dates before the epoch are in the past, which is all that matters.
+  Some(seconds.max(0) as u64 * 1000)
+}
+
+/// The number of days since 1970-01-01 of a date in the proleptic Gregorian
+/// calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+  let year = if month <= 2 { year - 1 } else { year };
+  let era = year.div_euclid(400);
+  let year_of_era = year - era * 400;
+  let month = month as i64;
+  let day_of_year =
+    (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day as i64 - 1;
+  let day_of_era =
+    year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+  era * 146097 + day_of_era - 719468
+}
+
+#[derive(Default)]
+pub struct CookieJar {
+  /// In creation order, which is the order of cookies with the same path
+  /// length in a `Cookie` header.
+  cookies: Vec<Cookie>,
+}
+
+impl CookieJar {
+  /// Stores a cookie, replacing the one with the same name, domain and path.
+  /// An expired cookie removes the one it replaces.
+  pub fn insert(&mut self, cookie: Cookie, now: u64) {
+    let existing = self.cookies.iter().position(|c| {
+      c.name == cookie.name
+        && c.domain == cookie.domain
+        && c.path == cookie.path
+    });
+    match (existing, cookie.is_expired(now)) {
+      (Some(idx), true) => {
+        self.cookies.remove(idx);
+      }
+      (Some(idx), false) => self.cookies[idx] = cookie,
+      (None, true) => {}
+      (None, false) => self.cookies.push(cookie),
+    }
+  }
+
+  /// Stores the cookies of the `Set-Cookie` headers of a response to `url`.
+  pub fn store_response_cookies<'a>(
+    &mut self,
+    url: &Url,
+    set_cookie_headers: impl Iterator<Item = &'a str>,
+  ) {
+    let now = now_ms();
+    for header in set_cookie_headers {
+      if let Some(cookie) = Cookie::parse(header, url, now) {
+        self.insert(cookie, now);
+      }
+    }
+  }
+
+  /// Returns the cookies to send with a request to `url`, in the order they
+  /// appear in its `Cookie` header: longer paths first.
+  pub fn cookies_for(&mut self, url: &Url) -> Vec<&Cookie> {
+    let now = now_ms();
+    self.cookies.retain(|c| !c.is_expired(now));
+    let mut cookies = self
+      .cookies
+      .iter()
+      .filter(|c| c.matches(url))
+      .collect::<Vec<_>>();
+    cookies.sort_by(|a, b| b.path.len().cmp(&a.path.len()));
+    cookies
+  }
+
+  /// The value of the `Cookie` header of a request to `url`, if any cookie
+  /// matches.
+  pub fn cookie_header(&mut self, url: &Url) -> Option<String> {
+    let cookies = self.cookies_for(url);
+    if cookies.is_empty() {
+      return None;
+    }
+    let pairs = cookies
+      .iter()
+      .map(|c| format!("{}={}", c.name, c.value))
+      .collect::<Vec<_>>();
+    Some(pairs.join("; "))
+  }
+
+  /// All cookies that haven't expired.
+  pub fn all(&mut self) -> Vec<Cookie> {
+    let now = now_ms();
+    self.cookies.retain(|c| !c.is_expired(now));
+    self.cookies.clone()
+  }
+
+  pub fn clear(&mut self) {
+    self.cookies.clear();
+  }
+}
+
+pub struct CookieJarResource(pub RefCell<CookieJar>);
+
+impl Resource for CookieJarResource {
+  fn name(&self) -> Cow<str> {
+    "cookieJar".into()
+  }
+}
+
+#[op]
+pub fn op_fetch_cookie_jar_new(
+  state: &mut OpState,
+  cookies: Vec<Cookie>,
+) -> ResourceId {
+  let mut jar = CookieJar::default();
+  let now = now_ms();
+  for cookie in cookies {
+    jar.insert(cookie, now);
+  }
+  state
+    .resource_table
+    .add(CookieJarResource(RefCell::new(jar)))
+}
+
+/// Returns the cookies that would be sent with a request to `url`, or all
+/// cookies if no URL is given.
+#[op]
+pub fn op_fetch_cookie_jar_get_cookies(
+  state: &mut OpState,
+  rid: ResourceId,
+  url: Option<String>,
+) -> Result<Vec<Cookie>, AnyError> {
+  let resource = state.resource_table.get::<CookieJarResource>(rid)?;
+  let mut jar = resource.0.borrow_mut();
+  Ok(match url {
+    Some(url) => {
+      let url = Url::parse(&url)?;
+      jar.cookies_for(&url).into_iter().cloned().collect()
+    }
+    None => jar.all(),
+  })
+}
+
+/// Stores a cookie as if it was received in a `Set-Cookie` header of a
+/// response to `url`.
+#[op]
+pub fn op_fetch_cookie_jar_set_cookie(
+  state: &mut OpState,
+  rid: ResourceId,
+  url: String,
+  set_cookie: String,
+) -> Result<(), AnyError> {
+  let resource = state.resource_table.get::<CookieJarResource>(rid)?;
+  let url = Url::parse(&url)?;
+  let now = now_ms();
+  let cookie = Cookie::parse(&set_cookie, &url, now)
+    .ok_or_else(|| type_error("Invalid cookie for the given URL"))?;
+  resource.0.borrow_mut().insert(cookie, now);
+  Ok(())
+}
+
+#[op]
+pub fn op_fetch_cookie_jar_clear(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<(), AnyError> {
+  let resource = state.resource_table.get::<CookieJarResource>(rid)?;
+  resource.0.borrow_mut().clear();
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn url(s: &str) -> Url {
+    Url::parse(s).unwrap()
+  }
+
+  #[test]
+  fn test_parse_cookie_date() {
+    assert_eq!(
+      parse_cookie_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+      Some(784111777000)
+    );
+    assert_eq!(
+      parse_cookie_date("Sunday, 06-Nov-94 08:49:37 GMT"),
+      Some(784111777000)
+    );
+    assert_eq!(
+      parse_cookie_date("Sun Nov  6 08:49:37 1994"),
+      Some(784111777000)
+    );
+    assert_eq!(parse_cookie_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+    assert_eq!(
+      parse_cookie_date("Wed, 29 Feb 2024 12:00:00 GMT"),
+      Some(1709208000000)
+    );
+    assert_eq!(parse_cookie_date("not a date"), None);
+    assert_eq!(parse_cookie_date("Sun, 06 Nov 1994 25:49:37 GMT"), None);
+  }
+
+  #[test]
+  fn test_parse_cookie() {
+    let now = 1_000_000;
+    let cookie =
+      Cookie::parse("a=b; Path=/docs; HttpOnly", &url("http://a.com/"), now)
+        .unwrap();
+    assert_eq!(cookie.domain, "a.com");
+    assert!(cookie.host_only);
+    assert_eq!(cookie.path, "/docs");
+    assert!(cookie.http_only);
+
+    let cookie = Cookie::parse(
+      "a=b; Domain=.Example.com; Max-Age=10; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+      &url("https://www.example.com/x/y"),
+      now,
+    )
+    .unwrap();
+    assert_eq!(cookie.domain, "example.com");
+    assert!(!cookie.host_only);
+    assert_eq!(cookie.path, "/x");
+    assert_eq!(cookie.expires, Some(now + 10_000));
+
+    // Other domains, top-level domains and insecure secure cookies.
+    assert!(
+      Cookie::parse("a=b; Domain=other.com", &url("http://a.com/"), now)
+        .is_none()
+    );
+    assert!(
+      Cookie::parse("a=b; Domain=com", &url("http://a.com/"), now).is_none()
+    );
+    assert!(Cookie::parse("a=b; Secure", &url("http://a.com/"), now).is_none());
+    assert!(
+      Cookie::parse("a=b; SameSite=None", &url("https://a.com/"), now)
+        .is_none()
+    );
+    assert!(Cookie::parse("a", &url("http://a.com/"), now).is_none());
+  }
+
+  #[test]
+  fn test_cookie_jar() {
+    let mut jar = CookieJar::default();
+    jar.store_response_cookies(
+      &url("https://www.example.com/docs/page"),
+      [
+        "root=1; Path=/",
+        "docs=2",
+        "domain=3; Domain=example.com; Path=/",
+        "secure=4; Secure; Path=/",
+      ]
+      .into_iter(),
+    );
+    assert_eq!(
+      jar.cookie_header(&url("https://www.example.com/docs/other")),
+      Some("docs=2; root=1; domain=3; secure=4".to_string())
+    );
+    assert_eq!(
+      jar.cookie_header(&url("http://www.example.com/")),
+      Some("root=1; domain=3".to_string())
+    );
+    assert_eq!(
+      jar.cookie_header(&url("http://api.example.com/docs")),
+      Some("domain=3".to_string())
+    );
+    assert_eq!(jar.cookie_header(&url("http://example.org/")), None);
+
+    // Replacing and deleting.
+    jar.store_response_cookies(
+      &url("https://www.example.com/"),
+      ["root=5; Path=/", "docs=; Path=/docs; Max-Age=0"].into_iter(),
+    );
+    assert_eq!(
+      jar.cookie_header(&url("https://www.example.com/docs/other")),
+      Some("root=5; domain=3; secure=4".to_string())
+    );
+  }
+}
@@ -0,0 +1,29 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::net::SocketAddr;
+
+use deno_net::resolver::DnsResolver;
+use reqwest::dns::Addrs;
+use reqwest::dns::Name;
+use reqwest::dns::Resolve;
+use reqwest::dns::Resolving;
+
+/// Resolves the host names of a [`reqwest::Client`] with a [`DnsResolver`]
+/// instead of the system's resolver.
+pub struct ReqwestDnsResolver(pub DnsResolver);
+
+impl Resolve for ReqwestDnsResolver {
+  fn resolve(&self, name: Name) -> Resolving {
+    let resolver = self.0.clone();
+    Box::pin(async move {
+      let lookup = resolver.resolver().lookup_ip(name.as_str()).await?;
+      // The port is replaced with the one of the URL.
+      let addrs = lookup
+        .iter()
+        .map(|ip| SocketAddr::new(ip, 0))
+        .collect::<Vec<_>>();
+      let addrs: Addrs = Box::new(addrs.into_iter());
+      Ok(addrs)
+    })
+  }
+}
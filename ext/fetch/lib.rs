@@ -1,11 +1,16 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 mod byte_stream;
+mod cookie_jar;
+mod dns;
 mod fs_fetch_handler;
+mod pool_metrics;
+mod unix_socket;
 
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp::min;
+use std::collections::HashMap;
 use std::convert::From;
 use std::path::Path;
 use std::path::PathBuf;
@@ -37,6 +42,8 @@ use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_core::ZeroCopyBuf;
+use deno_net::resolver::DnsConfig;
+use deno_net::resolver::DnsResolver;
 use deno_tls::rustls::RootCertStore;
 use deno_tls::Proxy;
 use deno_tls::RootCertStoreProvider;
@@ -48,8 +55,10 @@ use reqwest::header::HeaderMap;
 use reqwest::header::HeaderName;
 use reqwest::header::HeaderValue;
 use reqwest::header::ACCEPT_ENCODING;
+use reqwest::header::COOKIE;
 use reqwest::header::HOST;
 use reqwest::header::RANGE;
+use reqwest::header::SET_COOKIE;
 use reqwest::header::USER_AGENT;
 use reqwest::redirect::Policy;
 use reqwest::Body;
@@ -70,6 +79,14 @@ pub use reqwest;
 pub use fs_fetch_handler::FsFetchHandler;
 
 pub use crate::byte_stream::MpscByteStream;
+use crate::cookie_jar::op_fetch_cookie_jar_clear;
+use crate::cookie_jar::op_fetch_cookie_jar_get_cookies;
+use crate::cookie_jar::op_fetch_cookie_jar_new;
+use crate::cookie_jar::op_fetch_cookie_jar_set_cookie;
+use crate::cookie_jar::CookieJarResource;
+use crate::dns::ReqwestDnsResolver;
+use crate::pool_metrics::PoolMetrics;
+use crate::pool_metrics::PoolMetricsSnapshot;
 
 #[derive(Clone)]
 pub struct Options {
@@ -81,6 +98,9 @@ pub struct Options {
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
   pub client_cert_chain_and_key: Option<(String, String)>,
   pub file_fetch_handler: Rc<dyn FetchHandler>,
+  /// The DNS resolver of `fetch()` without a custom client. The system's
+  /// resolver is used if `None`.
+  pub dns_config: Option<DnsConfig>,
 }
 
 impl Options {
@@ -102,6 +122,7 @@ impl Default for Options {
       unsafely_ignore_certificate_errors: None,
       client_cert_chain_and_key: None,
       file_fetch_handler: Rc::new(DefaultFileFetchHandler),
+      dns_config: None,
     }
   }
 }
@@ -115,6 +136,12 @@ deno_core::extension!(deno_fetch,
     op_fetch_response_into_byte_stream,
     op_fetch_response_upgrade,
     op_fetch_custom_client<FP>,
+    op_fetch_client_update_tls,
+    op_fetch_client_pool_metrics,
+    op_fetch_cookie_jar_new,
+    op_fetch_cookie_jar_get_cookies,
+    op_fetch_cookie_jar_set_cookie,
+    op_fetch_cookie_jar_clear,
   ],
   esm = [
     "20_headers.js",
@@ -183,6 +210,7 @@ pub trait FetchPermissions {
     api_name: &str,
   ) -> Result<(), AnyError>;
   fn check_read(&mut self, _p: &Path, api_name: &str) -> Result<(), AnyError>;
+  fn check_write(&mut self, _p: &Path, api_name: &str) -> Result<(), AnyError>;
 }
 
 pub fn get_declaration() -> PathBuf {
@@ -196,6 +224,31 @@ pub struct FetchReturn {
   pub cancel_handle_rid: Option<ResourceId>,
 }
 
+/// The options of the client used by `fetch()` without a custom client.
+fn default_client_options(
+  options: &Options,
+) -> Result<CreateHttpClientOptions, AnyError> {
+  Ok(CreateHttpClientOptions {
+    root_cert_store: options.root_cert_store()?,
+    ca_certs: vec![],
+    crls: vec![],
+    proxy: options.proxy.clone(),
+    unsafely_ignore_certificate_errors: options
+      .unsafely_ignore_certificate_errors
+      .clone(),
+    client_cert_chain_and_key: options.client_cert_chain_and_key.clone(),
+    pool_max_idle_per_host: None,
+    pool_idle_timeout: None,
+    http1: true,
+    http2: true,
+    dns_resolver: options
+      .dns_config
+      .as_ref()
+      .map(DnsResolver::new)
+      .transpose()?,
+  })
+}
+
 pub fn get_or_create_client_from_state(
   state: &mut OpState,
 ) -> Result<reqwest::Client, AnyError> {
@@ -205,25 +258,58 @@ pub fn get_or_create_client_from_state(
     let options = state.borrow::<Options>();
     let client = create_http_client(
       &options.user_agent,
-      CreateHttpClientOptions {
-        root_cert_store: options.root_cert_store()?,
-        ca_certs: vec![],
-        proxy: options.proxy.clone(),
-        unsafely_ignore_certificate_errors: options
-          .unsafely_ignore_certificate_errors
-          .clone(),
-        client_cert_chain_and_key: options.client_cert_chain_and_key.clone(),
-        pool_max_idle_per_host: None,
-        pool_idle_timeout: None,
-        http1: true,
-        http2: true,
-      },
+      default_client_options(options)?,
     )?;
     state.put::<reqwest::Client>(client.clone());
     Ok(client)
   }
 }
 
+/// Clients of `fetch()` calls that set a proxy without a custom client,
+/// keyed by the proxy so that connections to it are reused.
+#[derive(Default)]
+struct ProxiedClients(HashMap<Proxy, Client>);
+
+fn get_or_create_proxied_client_from_state(
+  state: &mut OpState,
+  proxy: Proxy,
+) -> Result<Client, AnyError> {
+  if let Some(client) = state
+    .try_borrow::<ProxiedClients>()
+    .and_then(|clients| clients.0.get(&proxy))
+  {
+    return Ok(client.clone());
+  }
+  let options = state.borrow::<Options>();
+  let client = create_http_client(
+    &options.user_agent,
+    CreateHttpClientOptions {
+      proxy: Some(proxy.clone()),
+      ..default_client_options(options)?
+    },
+  )?;
+  if !state.has::<ProxiedClients>() {
+    state.put(ProxiedClients::default());
+  }
+  state
+    .borrow_mut::<ProxiedClients>()
+    .0
+    .insert(proxy, client.clone());
+  Ok(client)
+}
+
+/// Parses the URL of a proxy, which can be an HTTP(S) proxy, tunneling HTTPS
+/// requests with `CONNECT`, or a SOCKS5 proxy. With `socks5h`, host names are
+/// resolved by the proxy.
+fn parse_proxy_url(proxy: &Proxy) -> Result<Url, AnyError> {
+  let url = Url::parse(&proxy.url)
+    .map_err(|_| type_error(format!("Invalid proxy URL: {}", proxy.url)))?;
+  match url.scheme() {
+    "http" | "https" | "socks5" | "socks5h" => Ok(url),
+    scheme => Err(type_error(format!("Unsupported proxy scheme: {scheme}"))),
+  }
+}
+
 #[op]
 pub fn op_fetch<FP>(
   state: &mut OpState,
@@ -231,6 +317,7 @@ pub fn op_fetch<FP>(
   url: String,
   headers: Vec<(ByteString, ByteString)>,
   client_rid: Option<u32>,
+  proxy: Option<Proxy>,
   has_body: bool,
   body_length: Option<u64>,
   data: Option<ZeroCopyBuf>,
@@ -238,11 +325,29 @@ pub fn op_fetch<FP>(
 where
   FP: FetchPermissions + 'static,
 {
-  let client = if let Some(rid) = client_rid {
+  let proxy_url = proxy.as_ref().map(parse_proxy_url).transpose()?;
+  let mut cookie_jar = None;
+  let mut pool_metrics = None;
+  let (client, unix_socket_path) = if let Some(rid) = client_rid {
     let r = state.resource_table.get::<HttpClientResource>(rid)?;
-    r.client.clone()
+    let client = match proxy {
+      Some(_) if r.unix_socket_path.is_some() => {
+        return Err(type_error(
+          "A proxy can't be used with a client connected to a unix socket",
+        ));
+      }
+      Some(proxy) => {
+        r.proxied_client(&state.borrow::<Options>().user_agent, proxy)?
+      }
+      None => r.client(),
+    };
+    cookie_jar = r.cookie_jar.clone();
+    pool_metrics = Some(r.pool_metrics.clone());
+    (client, r.unix_socket_path.clone())
+  } else if let Some(proxy) = proxy {
+    (get_or_create_proxied_client_from_state(state, proxy)?, None)
   } else {
-    get_or_create_client_from_state(state)?
+    (get_or_create_client_from_state(state)?, None)
   };
 
   let method = Method::from_bytes(&method)?;
@@ -278,9 +383,25 @@ where
 
       (request_rid, maybe_request_body_rid, maybe_cancel_handle_rid)
     }
+    "https" if unix_socket_path.is_some() => {
+      return Err(type_error("HTTPS is not supported over unix sockets."));
+    }
+    "http" if unix_socket_path.is_some() => unix_socket::fetch(
+      state,
+      unix_socket_path.unwrap(),
+      method,
+      url,
+      headers,
+      has_body,
+      body_length,
+      data,
+    )?,
     "http" | "https" => {
       let permissions = state.borrow_mut::<FP>();
       permissions.check_net_url(&url, "fetch()")?;
+      if let Some(proxy_url) = &proxy_url {
+        permissions.check_net_url(proxy_url, "fetch()")?;
+      }
 
       // Make sure that we have a valid URI early, as reqwest's `RequestBuilder::send`
       // internally uses `expect_uri`, which panics instead of returning a usable `Result`.
@@ -288,6 +409,9 @@ where
         return Err(type_error("Invalid URL"));
       }
 
+      let cookies = cookie_jar
+        .as_ref()
+        .and_then(|jar| jar.0.borrow_mut().cookie_header(&url));
       let mut request = client.request(method.clone(), url);
 
       let request_body_rid = if has_body {
@@ -346,6 +470,20 @@ where
         header_map
           .insert(ACCEPT_ENCODING, HeaderValue::from_static("identity"));
       }
+
+      if let Some(cookies) = cookies {
+        // Cookies set on the request itself come first.
+        let value = match header_map.get(COOKIE) {
+          Some(value) => {
+            let mut value = value.as_bytes().to_vec();
+            value.extend_from_slice(b"; ");
+            value.extend_from_slice(cookies.as_bytes());
+            HeaderValue::from_bytes(&value)
+          }
+          None => HeaderValue::from_str(&cookies),
+        };
+        header_map.insert(COOKIE, value?);
+      }
       request = request.headers(header_map);
 
       let options = state.borrow::<Options>();
@@ -358,11 +496,27 @@ where
       let cancel_handle_ = cancel_handle.clone();
 
       let fut = async move {
-        request
-          .send()
-          .or_cancel(cancel_handle_)
-          .await
-          .map(|res| res.map_err(|err| type_error(err.to_string())))
+        let in_flight = pool_metrics.as_ref().map(|m| m.start_request());
+        let res = match request.send().or_cancel(cancel_handle_).await {
+          Ok(res) => res,
+          Err(canceled) => return Err(canceled),
+        };
+        drop(in_flight);
+        if let (Ok(res), Some(pool_metrics)) = (&res, pool_metrics) {
+          pool_metrics.record_response(res);
+        }
+        if let (Ok(res), Some(cookie_jar)) = (&res, cookie_jar) {
+          let set_cookie_headers = res
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok());
+          cookie_jar
+            .0
+            .borrow_mut()
+            .store_response_cookies(res.url(), set_cookie_headers);
+        }
+        Ok(res.map_err(|err| type_error(err.to_string())))
       };
 
       let request_rid = state
@@ -764,7 +918,17 @@ impl Resource for FetchResponseBodyResource {
 }
 
 pub struct HttpClientResource {
-  pub client: Client,
+  client: RefCell<Client>,
+  /// The options the client was created with, to recreate it when its TLS
+  /// configuration is updated.
+  options: RefCell<CreateHttpClientOptions>,
+  /// Variants of the client for requests with their own proxy, keyed by it.
+  proxied_clients: RefCell<HashMap<Proxy, Client>>,
+  /// The jar cookies of requests are taken from and stored in.
+  cookie_jar: Option<Rc<CookieJarResource>>,
+  pool_metrics: Rc<PoolMetrics>,
+  /// The unix domain socket all requests of the client are sent to.
+  pub unix_socket_path: Option<PathBuf>,
 }
 
 impl Resource for HttpClientResource {
@@ -774,8 +938,48 @@ impl Resource for HttpClientResource {
 }
 
 impl HttpClientResource {
-  fn new(client: Client) -> Self {
-    Self { client }
+  fn new(
+    client: Client,
+    options: CreateHttpClientOptions,
+    cookie_jar: Option<Rc<CookieJarResource>>,
+    unix_socket_path: Option<PathBuf>,
+  ) -> Self {
+    Self {
+      client: RefCell::new(client),
+      options: RefCell::new(options),
+      proxied_clients: Default::default(),
+      cookie_jar,
+      pool_metrics: Default::default(),
+      unix_socket_path,
+    }
+  }
+
+  pub fn client(&self) -> Client {
+    self.client.borrow().clone()
+  }
+
+  /// Returns a client with the same options as this one, except that its
+  /// requests are sent through `proxy`.
+  fn proxied_client(
+    &self,
+    user_agent: &str,
+    proxy: Proxy,
+  ) -> Result<Client, AnyError> {
+    if let Some(client) = self.proxied_clients.borrow().get(&proxy) {
+      return Ok(client.clone());
+    }
+    let client = create_http_client(
+      user_agent,
+      CreateHttpClientOptions {
+        proxy: Some(proxy.clone()),
+        ..self.options.borrow().clone()
+      },
+    )?;
+    self
+      .proxied_clients
+      .borrow_mut()
+      .insert(proxy, client.clone());
+    Ok(client)
   }
 }
 
@@ -790,6 +994,8 @@ pub enum PoolIdleTimeout {
 #[serde(rename_all = "camelCase")]
 pub struct CreateHttpClientArgs {
   ca_certs: Vec<String>,
+  #[serde(default)]
+  crl: Vec<String>,
   proxy: Option<Proxy>,
   cert_chain: Option<String>,
   private_key: Option<String>,
@@ -799,6 +1005,9 @@ pub struct CreateHttpClientArgs {
   http1: bool,
   #[serde(default = "default_true")]
   http2: bool,
+  unix_socket_path: Option<PathBuf>,
+  resolver: Option<DnsConfig>,
+  cookie_jar_rid: Option<ResourceId>,
 }
 
 fn default_true() -> bool {
@@ -813,12 +1022,42 @@ pub fn op_fetch_custom_client<FP>(
 where
   FP: FetchPermissions + 'static,
 {
-  if let Some(proxy) = args.proxy.clone() {
+  if let Some(proxy) = &args.proxy {
+    let url = parse_proxy_url(proxy)?;
     let permissions = state.borrow_mut::<FP>();
-    let url = Url::parse(&proxy.url)?;
     permissions.check_net_url(&url, "Deno.createHttpClient()")?;
   }
 
+  if let Some(path) = &args.unix_socket_path {
+    if cfg!(not(unix)) {
+      return Err(type_error(
+        "Unix domain sockets are not supported on this platform",
+      ));
+    }
+    let permissions = state.borrow_mut::<FP>();
+    permissions.check_read(path, "Deno.createHttpClient()")?;
+    permissions.check_write(path, "Deno.createHttpClient()")?;
+  }
+
+  let dns_resolver = match &args.resolver {
+    Some(config) => {
+      let resolver = DnsResolver::new(config)?;
+      let permissions = state.borrow_mut::<FP>();
+      // Queries to the name servers could be used to send data to them.
+      for addr in resolver.name_servers() {
+        let url = Url::parse(&format!("dns://{addr}"))?;
+        permissions.check_net_url(&url, "Deno.createHttpClient()")?;
+      }
+      Some(resolver)
+    }
+    None => state
+      .borrow::<Options>()
+      .dns_config
+      .as_ref()
+      .map(DnsResolver::new)
+      .transpose()?,
+  };
+
   let client_cert_chain_and_key = {
     if args.cert_chain.is_some() || args.private_key.is_some() {
       let cert_chain = args
@@ -840,38 +1079,101 @@ where
     .into_iter()
     .map(|cert| cert.into_bytes())
     .collect::<Vec<_>>();
+  let crls = args
+    .crl
+    .into_iter()
+    .map(|crl| crl.into_bytes())
+    .collect::<Vec<_>>();
 
-  let client = create_http_client(
-    &options.user_agent,
-    CreateHttpClientOptions {
-      root_cert_store: options.root_cert_store()?,
-      ca_certs,
-      proxy: args.proxy,
-      unsafely_ignore_certificate_errors: options
-        .unsafely_ignore_certificate_errors
-        .clone(),
-      client_cert_chain_and_key,
-      pool_max_idle_per_host: args.pool_max_idle_per_host,
-      pool_idle_timeout: args.pool_idle_timeout.and_then(
-        |timeout| match timeout {
-          PoolIdleTimeout::State(true) => None,
-          PoolIdleTimeout::State(false) => Some(None),
-          PoolIdleTimeout::Specify(specify) => Some(Some(specify)),
-        },
-      ),
-      http1: args.http1,
-      http2: args.http2,
-    },
-  )?;
-
-  let rid = state.resource_table.add(HttpClientResource::new(client));
+  let client_options = CreateHttpClientOptions {
+    root_cert_store: options.root_cert_store()?,
+    ca_certs,
+    crls,
+    proxy: args.proxy,
+    unsafely_ignore_certificate_errors: options
+      .unsafely_ignore_certificate_errors
+      .clone(),
+    client_cert_chain_and_key,
+    pool_max_idle_per_host: args.pool_max_idle_per_host,
+    pool_idle_timeout: args.pool_idle_timeout.and_then(
+      |timeout| match timeout {
+        PoolIdleTimeout::State(true) => None,
+        PoolIdleTimeout::State(false) => Some(None),
+        PoolIdleTimeout::Specify(specify) => Some(Some(specify)),
+      },
+    ),
+    http1: args.http1,
+    http2: args.http2,
+    dns_resolver,
+  };
+  let client = create_http_client(&options.user_agent, client_options.clone())?;
+
+  let cookie_jar = args
+    .cookie_jar_rid
+    .map(|rid| state.resource_table.get::<CookieJarResource>(rid))
+    .transpose()?;
+  let rid = state.resource_table.add(HttpClientResource::new(
+    client,
+    client_options,
+    cookie_jar,
+    args.unix_socket_path,
+  ));
   Ok(rid)
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateHttpClientTlsArgs {
+  ca_certs: Option<Vec<String>>,
+  crl: Option<Vec<String>>,
+}
+
+/// Replaces the root certificates and certificate revocation lists of a
+/// client. As the TLS configuration of a `reqwest::Client` can't be changed,
+/// the client is recreated, so connections opened with the previous
+/// configuration aren't reused.
+#[op]
+pub fn op_fetch_client_update_tls(
+  state: &mut OpState,
+  rid: ResourceId,
+  args: UpdateHttpClientTlsArgs,
+) -> Result<(), AnyError> {
+  let resource = state.resource_table.get::<HttpClientResource>(rid)?;
+  let mut client_options = resource.options.borrow().clone();
+  if let Some(ca_certs) = args.ca_certs {
+    client_options.ca_certs =
+      ca_certs.into_iter().map(|cert| cert.into_bytes()).collect();
+  }
+  if let Some(crl) = args.crl {
+    client_options.crls = crl.into_iter().map(|crl| crl.into_bytes()).collect();
+  }
+
+  let user_agent = &state.borrow::<Options>().user_agent;
+  let client = create_http_client(user_agent, client_options.clone())?;
+  *resource.client.borrow_mut() = client;
+  *resource.options.borrow_mut() = client_options;
+  resource.proxied_clients.borrow_mut().clear();
+  Ok(())
+}
+
+/// Returns the number of requests sent with a client, of those waiting for a
+/// response, and of the connections responses were received over, telling
+/// new connections from ones reused from the pool.
+#[op]
+pub fn op_fetch_client_pool_metrics(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<PoolMetricsSnapshot, AnyError> {
+  let resource = state.resource_table.get::<HttpClientResource>(rid)?;
+  Ok(resource.pool_metrics.snapshot())
+}
+
 #[derive(Debug, Clone)]
 pub struct CreateHttpClientOptions {
   pub root_cert_store: Option<RootCertStore>,
   pub ca_certs: Vec<Vec<u8>>,
+  /// PEM encoded certificate revocation lists.
+  pub crls: Vec<Vec<u8>>,
   pub proxy: Option<Proxy>,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
   pub client_cert_chain_and_key: Option<(String, String)>,
@@ -879,6 +1181,8 @@ pub struct CreateHttpClientOptions {
   pub pool_idle_timeout: Option<Option<u64>>,
   pub http1: bool,
   pub http2: bool,
+  /// Resolves host names instead of the system's resolver.
+  pub dns_resolver: Option<DnsResolver>,
 }
 
 impl Default for CreateHttpClientOptions {
@@ -886,6 +1190,7 @@ impl Default for CreateHttpClientOptions {
     CreateHttpClientOptions {
       root_cert_store: None,
       ca_certs: vec![],
+      crls: vec![],
       proxy: None,
       unsafely_ignore_certificate_errors: None,
       client_cert_chain_and_key: None,
@@ -893,6 +1198,7 @@ impl Default for CreateHttpClientOptions {
       pool_idle_timeout: None,
       http1: true,
       http2: true,
+      dns_resolver: None,
     }
   }
 }
@@ -906,6 +1212,7 @@ pub fn create_http_client(
   let mut tls_config = deno_tls::create_client_config(
     options.root_cert_store,
     options.ca_certs,
+    options.crls,
     options.unsafely_ignore_certificate_errors,
     options.client_cert_chain_and_key,
   )?;
@@ -935,6 +1242,10 @@ pub fn create_http_client(
     builder = builder.proxy(reqwest_proxy);
   }
 
+  if let Some(dns_resolver) = options.dns_resolver {
+    builder = builder.dns_resolver(Arc::new(ReqwestDnsResolver(dns_resolver)));
+  }
+
   if let Some(pool_max_idle_per_host) = options.pool_max_idle_per_host {
     builder = builder.pool_max_idle_per_host(pool_max_idle_per_host);
   }
@@ -0,0 +1,96 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+use hyper::client::connect::HttpInfo;
+use reqwest::Response;
+use serde::Serialize;
+
+/// The number of connections remembered to tell reused connections from new
+/// ones. Connections forgotten about are counted as new when reused.
+const MAX_TRACKED_CONNECTIONS: usize = 1024;
+
+/// Connection pool metrics of an `HttpClientResource`.
+///
+/// `reqwest` doesn't say whether a response came over a pooled connection, so
+/// connections are told apart by their local and remote address, which hyper
+/// attaches to every response.
+#[derive(Default)]
+pub struct PoolMetrics {
+  requests: Cell<u64>,
+  in_flight: Cell<u64>,
+  new_connections: Cell<u64>,
+  reused_connections: Cell<u64>,
+  connections: RefCell<TrackedConnections>,
+}
+
+#[derive(Default)]
+struct TrackedConnections {
+  set: HashSet<(SocketAddr, SocketAddr)>,
+  order: VecDeque<(SocketAddr, SocketAddr)>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolMetricsSnapshot {
+  requests: u64,
+  in_flight: u64,
+  new_connections: u64,
+  reused_connections: u64,
+}
+
+/// Counts a request as in flight until it's dropped.
+pub struct InFlightRequest(Rc<PoolMetrics>);
+
+impl Drop for InFlightRequest {
+  fn drop(&mut self) {
+    self.0.in_flight.set(self.0.in_flight.get() - 1);
+  }
+}
+
+impl PoolMetrics {
+  /// Records a request being sent. It's in flight until the returned guard
+  /// is dropped, which should happen once its response arrived.
+  pub fn start_request(self: &Rc<Self>) -> InFlightRequest {
+    self.requests.set(self.requests.get() + 1);
+    self.in_flight.set(self.in_flight.get() + 1);
+    InFlightRequest(self.clone())
+  }
+
+  /// Records which connection a response was received over.
+  pub fn record_response(&self, response: &Response) {
+    let Some(info) = response.extensions().get::<HttpInfo>() else {
+      return;
+    };
+    let key = (info.local_addr(), info.remote_addr());
+    let mut connections = self.connections.borrow_mut();
+    if connections.set.contains(&key) {
+      self
+        .reused_connections
+        .set(self.reused_connections.get() + 1);
+      return;
+    }
+    self.new_connections.set(self.new_connections.get() + 1);
+    if connections.order.len() == MAX_TRACKED_CONNECTIONS {
+      if let Some(oldest) = connections.order.pop_front() {
+        connections.set.remove(&oldest);
+      }
+    }
+    connections.set.insert(key);
+    connections.order.push_back(key);
+  }
+
+  pub fn snapshot(&self) -> PoolMetricsSnapshot {
+    PoolMetricsSnapshot {
+      requests: self.requests.get(),
+      in_flight: self.in_flight.get(),
+      new_connections: self.new_connections.get(),
+      reused_connections: self.reused_connections.get(),
+    }
+  }
+}
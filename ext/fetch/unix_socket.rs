@@ -0,0 +1,161 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::path::PathBuf;
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::url::Position;
+use deno_core::url::Url;
+use deno_core::AsyncRefCell;
+use deno_core::ByteString;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
+use deno_core::OpState;
+use deno_core::ResourceId;
+use deno_core::ZeroCopyBuf;
+use http::header::CONTENT_LENGTH;
+use reqwest::header::HeaderName;
+use reqwest::header::HeaderValue;
+use reqwest::header::ACCEPT_ENCODING;
+use reqwest::header::HOST;
+use reqwest::header::RANGE;
+use reqwest::header::USER_AGENT;
+use reqwest::Method;
+use reqwest::Response;
+
+use crate::FetchCancelHandle;
+use crate::FetchRequestBodyResource;
+use crate::FetchRequestResource;
+use crate::MpscByteStream;
+use crate::Options;
+
+/// Sets up a fetch of `url` over the unix domain socket at `path`, returning
+/// the request, request body and cancel handle resources like `op_fetch`.
+/// The host of `url` is only used for the `Host` header.
+#[allow(clippy::too_many_arguments)]
+pub fn fetch(
+  state: &mut OpState,
+  path: PathBuf,
+  method: Method,
+  url: Url,
+  headers: Vec<(ByteString, ByteString)>,
+  has_body: bool,
+  body_length: Option<u64>,
+  data: Option<ZeroCopyBuf>,
+) -> Result<(ResourceId, Option<ResourceId>, Option<ResourceId>), AnyError> {
+  let host = match (url.host_str(), url.port()) {
+    (Some(host), Some(port)) => format!("{host}:{port}"),
+    (Some(host), None) => host.to_string(),
+    (None, _) => return Err(type_error("Invalid URL")),
+  };
+  let options = state.borrow::<Options>();
+  let mut request = http::Request::builder()
+    .method(method.clone())
+    .uri(&url[Position::BeforePath..Position::AfterQuery])
+    .header(HOST, host)
+    .header(USER_AGENT, &options.user_agent);
+
+  let mut has_range = false;
+  for (key, value) in headers {
+    let name = HeaderName::from_bytes(&key)
+      .map_err(|err| type_error(err.to_string()))?;
+    let v = HeaderValue::from_bytes(&value)
+      .map_err(|err| type_error(err.to_string()))?;
+
+    if !matches!(name, HOST | CONTENT_LENGTH) {
+      has_range |= name == RANGE;
+      request = request.header(name, v);
+    }
+  }
+  if has_range {
+    // https://fetch.spec.whatwg.org/#http-network-or-cache-fetch step 18
+    request = request.header(ACCEPT_ENCODING, "identity");
+  }
+
+  let (body, request_body_rid) = if has_body {
+    match data {
+      None => {
+        let (stream, tx) = MpscByteStream::new();
+        if let Some(body_size) = body_length {
+          request = request.header(CONTENT_LENGTH, body_size);
+        }
+        let request_body_rid =
+          state.resource_table.add(FetchRequestBodyResource {
+            body: AsyncRefCell::new(tx),
+            cancel: CancelHandle::default(),
+          });
+        (hyper::Body::wrap_stream(stream), Some(request_body_rid))
+      }
+      Some(data) => (hyper::Body::from(data.to_vec()), None),
+    }
+  } else {
+    if matches!(method, Method::POST | Method::PUT) {
+      request = request.header(CONTENT_LENGTH, 0);
+    }
+    (hyper::Body::empty(), None)
+  };
+  let request = request
+    .body(body)
+    .map_err(|err| type_error(err.to_string()))?;
+
+  let cancel_handle = CancelHandle::new_rc();
+  let cancel_handle_ = cancel_handle.clone();
+
+  let fut = async move {
+    send_request(path, url, request)
+      .or_cancel(cancel_handle_)
+      .await
+      .map(|res| res.map_err(|err| type_error(err.to_string())))
+  };
+
+  let request_rid = state
+    .resource_table
+    .add(FetchRequestResource(Box::pin(fut)));
+  let cancel_handle_rid =
+    state.resource_table.add(FetchCancelHandle(cancel_handle));
+
+  Ok((request_rid, request_body_rid, Some(cancel_handle_rid)))
+}
+
+/// Sends `request` over a new HTTP/1.1 connection to the unix domain socket
+/// at `path`. reqwest can't connect to unix sockets, so the response is
+/// converted into a reqwest one, with `url` as its URL.
+#[cfg(unix)]
+async fn send_request(
+  path: PathBuf,
+  url: Url,
+  request: http::Request<hyper::Body>,
+) -> Result<Response, AnyError> {
+  use deno_core::task::spawn;
+  use reqwest::ResponseBuilderExt;
+
+  let stream = tokio::net::UnixStream::connect(path).await?;
+  let (mut sender, conn) = hyper::client::conn::handshake(stream).await?;
+  spawn(async move {
+    // Errors of the connection surface through the response or its body.
+    let _ = conn.await;
+  });
+
+  let (parts, body) = sender.send_request(request).await?.into_parts();
+  let mut response = http::Response::builder()
+    .status(parts.status)
+    .version(parts.version)
+    .url(url);
+  if let Some(headers) = response.headers_mut() {
+    *headers = parts.headers;
+  }
+  Ok(Response::from(
+    response.body(reqwest::Body::wrap_stream(body))?,
+  ))
+}
+
+#[cfg(not(unix))]
+async fn send_request(
+  _path: PathBuf,
+  _url: Url,
+  _request: http::Request<hyper::Body>,
+) -> Result<Response, AnyError> {
+  Err(type_error(
+    "Unix domain sockets are not supported on this platform",
+  ))
+}
@@ -24,6 +24,7 @@ const {
   BigInt64Array,
   BigUint64Array,
   Function,
+  ReflectApply,
   ReflectHas,
   PromisePrototypeThen,
   MathMax,
@@ -253,7 +254,7 @@ class UnsafeFnPointer {
   constructor(pointer, definition) {
     this.pointer = pointer;
     this.definition = definition;
-    this.#structSize = isStruct(definition.result)
+    this.#structSize = isAggregate(definition.result)
       ? getTypeSizeAndAlignment(definition.result)[0]
       : null;
   }
@@ -315,26 +316,50 @@ function isStruct(type) {
     typeof type.struct === "object";
 }
 
+function isUnion(type) {
+  return typeof type === "object" && type !== null &&
+    typeof type.union === "object";
+}
+
+// Structs and unions are both passed by value as buffers.
+function isAggregate(type) {
+  return isStruct(type) || isUnion(type);
+}
+
 function getTypeSizeAndAlignment(type, cache = new SafeMap()) {
-  if (isStruct(type)) {
+  if (isAggregate(type)) {
     const cached = cache.get(type);
     if (cached !== undefined) {
       if (cached === null) {
-        throw new TypeError("Recursive struct definition");
+        throw new TypeError(
+          `Recursive ${isStruct(type) ? "struct" : "union"} definition`,
+        );
       }
       return cached;
     }
     cache.set(type, null);
     let size = 0;
     let alignment = 1;
-    for (const field of new SafeArrayIterator(type.struct)) {
-      const { 0: fieldSize, 1: fieldAlign } = getTypeSizeAndAlignment(
-        field,
-        cache,
-      );
-      alignment = MathMax(alignment, fieldAlign);
-      size = MathCeil(size / fieldAlign) * fieldAlign;
-      size += fieldSize;
+    if (isStruct(type)) {
+      for (const field of new SafeArrayIterator(type.struct)) {
+        const { 0: fieldSize, 1: fieldAlign } = getTypeSizeAndAlignment(
+          field,
+          cache,
+        );
+        alignment = MathMax(alignment, fieldAlign);
+        size = MathCeil(size / fieldAlign) * fieldAlign;
+        size += fieldSize;
+      }
+    } else {
+      // All members of a union start at offset 0.
+      for (const member of new SafeArrayIterator(type.union)) {
+        const { 0: memberSize, 1: memberAlign } = getTypeSizeAndAlignment(
+          member,
+          cache,
+        );
+        alignment = MathMax(alignment, memberAlign);
+        size = MathMax(size, memberSize);
+      }
     }
     size = MathCeil(size / alignment) * alignment;
     const result = [size, alignment];
@@ -378,11 +403,6 @@ class UnsafeCallback {
   pointer;
 
   constructor(definition, callback) {
-    if (definition.nonblocking) {
-      throw new TypeError(
-        "Invalid UnsafeCallback, cannot be nonblocking",
-      );
-    }
     const { 0: rid, 1: pointer } = ops.op_ffi_unsafe_callback_create(
       definition,
       callback,
@@ -477,13 +497,23 @@ class DynamicLibrary {
         continue;
       }
       const resultType = symbols[symbol].result;
-      const isStructResult = isStruct(resultType);
+      const isStructResult = isAggregate(resultType);
       const structSize = isStructResult
         ? getTypeSizeAndAlignment(resultType)[0]
         : 0;
       const needsUnpacking = isReturnedAsBigInt(resultType);
 
       const isNonBlocking = symbols[symbol].nonblocking;
+      if (symbols[symbol].lazy && !isNonBlocking) {
+        // The symbol is resolved on the first call, which throws if it is
+        // missing. Nonblocking calls are bound by op_ffi_call_nonblocking.
+        const rid = this.#rid;
+        let call = null;
+        this.symbols[symbol] = (...args) => {
+          call ??= ops.op_ffi_bind_symbol(rid, symbol);
+          return ReflectApply(call, undefined, args);
+        };
+      }
       if (isNonBlocking) {
         ObjectDefineProperty(
           this.symbols,
@@ -568,6 +598,10 @@ class DynamicLibrary {
     }
   }
 
+  exportedSymbols() {
+    return ops.op_ffi_exported_symbols(this.#rid);
+  }
+
   close() {
     core.close(this.#rid);
   }
@@ -9,7 +9,6 @@ use crate::symbol::Symbol;
 use crate::FfiPermissions;
 use crate::ForeignFunction;
 use deno_core::anyhow::anyhow;
-use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::op;
 use deno_core::serde_json::Value;
@@ -106,7 +105,7 @@ where
       NativeType::Buffer => {
         ffi_args.push(ffi_parse_buffer_arg(scope, value)?);
       }
-      NativeType::Struct(_) => {
+      NativeType::Struct(_) | NativeType::Union(_) => {
         ffi_args.push(ffi_parse_struct_arg(scope, value)?);
       }
       NativeType::Pointer => {
@@ -177,7 +176,7 @@ where
           pointer: cif.call::<*mut c_void>(*fun_ptr, &call_args),
         }
       }
-      NativeType::Struct(_) => NativeValue {
+      NativeType::Struct(_) | NativeType::Union(_) => NativeValue {
         void_value: ffi_call_rtype_struct(
           &symbol.cif,
           &symbol.ptr,
@@ -265,7 +264,7 @@ fn ffi_call(
           cif.call::<*mut c_void>(fun_ptr, &call_args),
         ))
       }
-      NativeType::Struct(_) => {
+      NativeType::Struct(_) | NativeType::Union(_) => {
         ffi_call_rtype_struct(cif, &fun_ptr, call_args, out_buffer.unwrap().0);
         FfiValue::Value(Value::Null)
       }
@@ -293,13 +292,16 @@ where
   };
 
   let symbol = PtrSymbol::new(pointer, &def)?;
+  let args = parameters.v8_value;
   let call_args = ffi_parse_args(scope, parameters, &def.parameters)?;
 
   let out_buffer = out_buffer
     .map(|v| v8::Local::<v8::TypedArray>::try_from(v.v8_value).unwrap());
   let out_buffer_ptr = out_buffer_as_ptr(scope, out_buffer);
+  let pinned_buffers = pin_buffers(scope, args, &def.parameters, out_buffer);
 
   let join_handle = spawn_blocking(move || {
+    let _pinned_buffers = pinned_buffers;
     let PtrSymbol { cif, ptr } = symbol.clone();
     ffi_call(
       call_args,
@@ -334,19 +336,19 @@ pub fn op_ffi_call_nonblocking<'scope>(
   let symbol = {
     let state = state.borrow();
     let resource = state.resource_table.get::<DynamicLibraryResource>(rid)?;
-    let symbols = &resource.symbols;
-    *symbols
-      .get(&symbol)
-      .ok_or_else(|| type_error("Invalid FFI symbol name"))?
-      .clone()
+    *resource.symbol(&symbol)?
   };
 
+  let args = parameters.v8_value;
   let call_args = ffi_parse_args(scope, parameters, &symbol.parameter_types)?;
   let out_buffer = out_buffer
     .map(|v| v8::Local::<v8::TypedArray>::try_from(v.v8_value).unwrap());
   let out_buffer_ptr = out_buffer_as_ptr(scope, out_buffer);
+  let pinned_buffers =
+    pin_buffers(scope, args, &symbol.parameter_types, out_buffer);
 
   let join_handle = spawn_blocking(move || {
+    let _pinned_buffers = pinned_buffers;
     let Symbol {
       cif,
       ptr,
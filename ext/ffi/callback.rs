@@ -8,6 +8,7 @@ use crate::ForeignFunction;
 use crate::PendingFfiAsyncWork;
 use crate::MAX_SAFE_INTEGER;
 use crate::MIN_SAFE_INTEGER;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::futures::channel::mpsc;
 use deno_core::futures::task::AtomicWaker;
@@ -31,6 +32,7 @@ use std::ptr;
 use std::ptr::NonNull;
 use std::rc::Rc;
 use std::sync::atomic;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU32;
 use std::sync::mpsc::sync_channel;
 use std::sync::Arc;
@@ -101,6 +103,11 @@ struct CallbackInfo {
   pub result: NativeType,
   pub thread_id: u32,
   pub waker: Arc<AtomicWaker>,
+  /// Calls from foreign threads are queued without waiting for their result.
+  pub nonblocking: bool,
+  /// Cleared when the callback is closed, so that queued nonblocking calls
+  /// don't run afterwards.
+  pub alive: Arc<AtomicBool>,
 }
 
 impl Future for CallbackInfo {
@@ -124,6 +131,34 @@ unsafe extern "C" fn deno_ffi_callback(
     if *s.borrow() == info.thread_id {
       // Own isolate thread, okay to call directly
       do_ffi_callback(cif, info, result, args);
+    } else if info.nonblocking {
+      // The arguments only live until this function returns, so they are
+      // copied for the queued call.
+      let args = copy_args(cif, args);
+      // SAFETY: The callback info and the cif live until the callback is
+      // closed, and the queued call doesn't run after that.
+      let cif: &'static libffi::low::ffi_cif = std::mem::transmute(cif);
+      let info: &'static CallbackInfo = std::mem::transmute(info);
+      let alive = info.alive.clone();
+      let fut = Box::new(move || {
+        if !alive.load(atomic::Ordering::Relaxed) {
+          return;
+        }
+        let arg_ptrs = args
+          .iter()
+          .map(|arg| arg.as_ptr() as *const c_void)
+          .collect::<Vec<_>>();
+        // Nonblocking callbacks return void, so nothing is written here.
+        let mut unused_result = 0u64;
+        do_ffi_callback(
+          cif,
+          info,
+          &mut *(&mut unused_result as *mut u64 as *mut c_void),
+          arg_ptrs.as_ptr(),
+        );
+      });
+      info.async_work_sender.unbounded_send(fut).unwrap();
+      info.waker.wake();
     } else {
       let async_work_sender = &info.async_work_sender;
       // SAFETY: Safe as this function blocks until `do_ffi_callback` completes and a response message is received.
@@ -143,6 +178,29 @@ unsafe extern "C" fn deno_ffi_callback(
   });
 }
 
+/// Copies the arguments of a call into 8-byte aligned buffers.
+unsafe fn copy_args(
+  cif: &libffi::low::ffi_cif,
+  args: *const *const c_void,
+) -> Vec<Vec<u64>> {
+  let arg_types = std::slice::from_raw_parts(cif.arg_types, cif.nargs as usize);
+  let args = std::slice::from_raw_parts(args, cif.nargs as usize);
+  arg_types
+    .iter()
+    .zip(args)
+    .map(|(arg_type, arg)| {
+      let size = (**arg_type).size;
+      let mut copy = vec![0u64; (size + 7) / 8];
+      ptr::copy_nonoverlapping(
+        *arg as *const u8,
+        copy.as_mut_ptr() as *mut u8,
+        size,
+      );
+      copy
+    })
+    .collect()
+}
+
 unsafe fn do_ffi_callback(
   cif: &libffi::low::ffi_cif,
   info: &CallbackInfo,
@@ -242,7 +300,7 @@ unsafe fn do_ffi_callback(
           v8::External::new(scope, result).into()
         }
       }
-      NativeType::Struct(_) => {
+      NativeType::Struct(_) | NativeType::Union(_) => {
         let size = arg_types[index].as_ref().unwrap().size;
         let ptr = (*val) as *const u8;
         let slice = std::slice::from_raw_parts(ptr, size);
@@ -471,7 +529,7 @@ unsafe fn do_ffi_callback(
           as u64;
       }
     }
-    NativeType::Struct(_) => {
+    NativeType::Struct(_) | NativeType::Union(_) => {
       let size;
       let pointer = if let Ok(value) =
         v8::Local::<v8::ArrayBufferView>::try_from(value)
@@ -532,6 +590,8 @@ pub fn op_ffi_unsafe_callback_ref(
 pub struct RegisterCallbackArgs {
   parameters: Vec<NativeType>,
   result: NativeType,
+  #[serde(default)]
+  nonblocking: bool,
 }
 
 #[op(v8)]
@@ -548,6 +608,12 @@ where
   let permissions = state.borrow_mut::<FP>();
   permissions.check(None)?;
 
+  if args.nonblocking && args.result != NativeType::Void {
+    return Err(type_error(
+      "Invalid UnsafeCallback, nonblocking callbacks must return void",
+    ));
+  }
+
   let v8_value = cb.v8_value;
   let cb = v8::Local::<v8::Function>::try_from(v8_value)?;
 
@@ -581,6 +647,8 @@ where
     result: args.result.clone(),
     thread_id,
     waker,
+    nonblocking: args.nonblocking,
+    alive: Arc::new(AtomicBool::new(true)),
   }));
   let cif = Cif::new(
     args
@@ -627,6 +695,7 @@ pub fn op_ffi_unsafe_callback_close(
     let callback_resource =
       state.resource_table.take::<UnsafeCallbackResource>(rid)?;
     let info = Box::from_raw(callback_resource.info);
+    info.alive.store(false, atomic::Ordering::Relaxed);
     let _ = v8::Global::from_raw(scope, info.callback);
     let _ = v8::Global::from_raw(scope, info.context);
     callback_resource.close();
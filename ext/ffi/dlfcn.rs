@@ -7,6 +7,7 @@ use crate::symbol::Symbol;
 use crate::turbocall;
 use crate::FfiPermissions;
 use deno_core::error::generic_error;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::op;
 use deno_core::serde_v8;
@@ -18,6 +19,7 @@ use dlopen::raw::Library;
 use serde::Deserialize;
 use serde_value::ValueDeserializer;
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::c_void;
 use std::path::PathBuf;
@@ -25,7 +27,11 @@ use std::rc::Rc;
 
 pub struct DynamicLibraryResource {
   lib: Library,
-  pub symbols: HashMap<String, Box<Symbol>>,
+  path: String,
+  symbols: RefCell<HashMap<String, Box<Symbol>>>,
+  /// Lazy functions that haven't been bound yet. They are bound on their
+  /// first call.
+  lazy_symbols: RefCell<HashMap<String, ForeignFunction>>,
 }
 
 impl Resource for DynamicLibraryResource {
@@ -52,6 +58,40 @@ impl DynamicLibraryResource {
       ))),
     }?
   }
+
+  /// Returns the function registered under `key`, binding it first if it was
+  /// declared lazy and hasn't been called yet.
+  pub fn symbol(&self, key: &str) -> Result<Box<Symbol>, AnyError> {
+    if let Some(sym) = self.symbols.borrow().get(key) {
+      return Ok(sym.clone());
+    }
+
+    let sym = {
+      let lazy_symbols = self.lazy_symbols.borrow();
+      let foreign_fn = lazy_symbols
+        .get(key)
+        .ok_or_else(|| type_error("Invalid FFI symbol name"))?;
+      let symbol = foreign_fn.name.as_deref().unwrap_or(key);
+      let fn_ptr =
+        // SAFETY: The obtained T symbol is the size of a pointer.
+        match unsafe { self.lib.symbol::<*const c_void>(symbol) } {
+          Ok(value) => value,
+          Err(err) => {
+            return Err(generic_error(format!(
+              "Failed to register symbol {symbol}: {err}"
+            )))
+          }
+        };
+      make_symbol(fn_ptr, foreign_fn)?
+    };
+
+    self.lazy_symbols.borrow_mut().remove(key);
+    self
+      .symbols
+      .borrow_mut()
+      .insert(key.to_string(), sym.clone());
+    Ok(sym)
+  }
 }
 
 pub fn needs_unwrap(rv: &NativeType) -> bool {
@@ -79,6 +119,8 @@ pub struct ForeignFunction {
   #[serde(rename = "optional")]
   #[serde(default = "default_optional")]
   optional: bool,
+  #[serde(default)]
+  lazy: bool,
 }
 
 fn default_callback() -> bool {
@@ -149,12 +191,14 @@ where
   let lib = Library::open(&path).map_err(|e| {
     dlopen::Error::OpeningLibraryError(std::io::Error::new(
       std::io::ErrorKind::Other,
-      format_error(e, path),
+      format_error(e, path.clone()),
     ))
   })?;
   let mut resource = DynamicLibraryResource {
     lib,
-    symbols: HashMap::new(),
+    path,
+    symbols: RefCell::new(HashMap::new()),
+    lazy_symbols: RefCell::new(HashMap::new()),
   };
   let obj = v8::Object::new(scope);
 
@@ -163,6 +207,19 @@ where
       ForeignSymbol::ForeignStatic(_) => {
         // No-op: Statics will be handled separately and are not part of the Rust-side resource.
       }
+      ForeignSymbol::ForeignFunction(foreign_fn) if foreign_fn.lazy => {
+        if foreign_fn.optional {
+          return Err(type_error(format!(
+            "Foreign symbol '{symbol_key}' cannot be both lazy and optional"
+          )));
+        }
+        // Resolved and bound on the first call, see op_ffi_bind_symbol and
+        // DynamicLibraryResource::symbol.
+        resource
+          .lazy_symbols
+          .get_mut()
+          .insert(symbol_key, foreign_fn);
+      }
       ForeignSymbol::ForeignFunction(foreign_fn) => 'register_symbol: {
         let symbol = match &foreign_fn.name {
           Some(symbol) => symbol,
@@ -187,27 +244,10 @@ where
             },
           }?;
 
-        let ptr = libffi::middle::CodePtr::from_ptr(fn_ptr as _);
-        let cif = libffi::middle::Cif::new(
-          foreign_fn
-            .parameters
-            .clone()
-            .into_iter()
-            .map(libffi::middle::Type::try_from)
-            .collect::<Result<Vec<_>, _>>()?,
-          foreign_fn.result.clone().try_into()?,
-        );
-
         let func_key = v8::String::new(scope, &symbol_key).unwrap();
-        let sym = Box::new(Symbol {
-          cif,
-          ptr,
-          parameter_types: foreign_fn.parameters,
-          result_type: foreign_fn.result,
-          can_callback: foreign_fn.callback,
-        });
-
-        resource.symbols.insert(symbol_key, sym.clone());
+        let sym = make_symbol(fn_ptr, &foreign_fn)?;
+
+        resource.symbols.get_mut().insert(symbol_key, sym.clone());
         match foreign_fn.non_blocking {
           // Generate functions for synchronous calls.
           Some(false) | None => {
@@ -230,6 +270,119 @@ where
   ))
 }
 
+fn make_symbol(
+  fn_ptr: *const c_void,
+  foreign_fn: &ForeignFunction,
+) -> Result<Box<Symbol>, AnyError> {
+  let ptr = libffi::middle::CodePtr::from_ptr(fn_ptr as _);
+  let cif = libffi::middle::Cif::new(
+    foreign_fn
+      .parameters
+      .clone()
+      .into_iter()
+      .map(libffi::middle::Type::try_from)
+      .collect::<Result<Vec<_>, _>>()?,
+    foreign_fn.result.clone().try_into()?,
+  );
+
+  Ok(Box::new(Symbol {
+    cif,
+    ptr,
+    parameter_types: foreign_fn.parameters.clone(),
+    result_type: foreign_fn.result.clone(),
+    can_callback: foreign_fn.callback,
+  }))
+}
+
+/// Binds a lazy function on its first call and returns the JavaScript
+/// function to call it synchronously.
+#[op(v8)]
+pub fn op_ffi_bind_symbol<'scope>(
+  scope: &mut v8::HandleScope<'scope>,
+  state: &mut OpState,
+  rid: ResourceId,
+  key: String,
+) -> Result<serde_v8::Value<'scope>, AnyError> {
+  let resource = state.resource_table.get::<DynamicLibraryResource>(rid)?;
+  let sym = resource.symbol(&key)?;
+  let function = make_sync_fn(scope, sym);
+  Ok(serde_v8::Value {
+    v8_value: function.into(),
+  })
+}
+
+/// Returns the names of the symbols exported by the library, read from the
+/// file it was opened from.
+#[op]
+pub fn op_ffi_exported_symbols(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<Vec<String>, AnyError> {
+  let resource = state.resource_table.get::<DynamicLibraryResource>(rid)?;
+  let path = &resource.path;
+  std::fs::read(path)
+    .map_err(AnyError::from)
+    .and_then(|data| exported_symbols(&data))
+    .map_err(|err| {
+      generic_error(format!("Failed to read the symbols of {path}: {err}"))
+    })
+}
+
+fn exported_symbols(data: &[u8]) -> Result<Vec<String>, AnyError> {
+  use object::read::macho::FatHeader;
+  use object::Object;
+
+  let data = match object::FileKind::parse(data)? {
+    object::FileKind::MachOFat32 => {
+      fat_arch_data(FatHeader::parse_arch32(data)?, data)?
+    }
+    object::FileKind::MachOFat64 => {
+      fat_arch_data(FatHeader::parse_arch64(data)?, data)?
+    }
+    _ => data,
+  };
+  let file = object::File::parse(data)?;
+  // C symbols of Mach-O files start with an underscore, which dlsym() adds
+  // back when looking them up.
+  let strip_underscore = file.format() == object::BinaryFormat::MachO;
+
+  let mut names = file
+    .exports()?
+    .into_iter()
+    .filter_map(|export| {
+      let name = std::str::from_utf8(export.name()).ok()?;
+      let name = match strip_underscore {
+        true => name.strip_prefix('_')?,
+        false => name,
+      };
+      Some(name.to_string())
+    })
+    .collect::<Vec<_>>();
+  names.sort();
+  names.dedup();
+  Ok(names)
+}
+
+/// Picks the slice of a universal Mach-O binary that was loaded for the
+/// current architecture.
+fn fat_arch_data<'a, A: object::read::macho::FatArch>(
+  arches: &[A],
+  data: &'a [u8],
+) -> Result<&'a [u8], AnyError> {
+  #[cfg(target_arch = "x86_64")]
+  let current = object::Architecture::X86_64;
+  #[cfg(target_arch = "aarch64")]
+  let current = object::Architecture::Aarch64;
+  #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+  let current = object::Architecture::Unknown;
+
+  let arch = arches
+    .iter()
+    .find(|arch| arch.architecture() == current)
+    .ok_or_else(|| generic_error("no slice for the current architecture"))?;
+  Ok(arch.data(data)?)
+}
+
 // Create a JavaScript function for synchronous FFI call to
 // the given symbol.
 fn make_sync_fn<'s>(
@@ -250,7 +403,7 @@ fn make_sync_fn<'s>(
         false => None,
       };
       let out_buffer = match symbol.result_type {
-        NativeType::Struct(_) => {
+        NativeType::Struct(_) | NativeType::Union(_) => {
           let argc = args.length();
           out_buffer_as_ptr(
             scope,
@@ -34,6 +34,61 @@ pub fn out_buffer_as_ptr(
   }
 }
 
+/// The backing stores of the buffers passed to a nonblocking call. Holding
+/// them keeps the memory that the foreign function works on alive until the
+/// call returns, even if JS drops, detaches or transfers the buffers in the
+/// meantime. V8 never moves a backing store, so the pointers stay valid.
+pub struct PinnedBuffers {
+  _backing_stores: Vec<v8::SharedRef<v8::BackingStore>>,
+}
+
+// SAFETY: Backing stores are reference counted atomically and can be
+// released from any thread.
+unsafe impl Send for PinnedBuffers {}
+
+pub fn pin_buffers(
+  scope: &mut v8::HandleScope,
+  args: v8::Local<v8::Value>,
+  parameter_types: &[NativeType],
+  out_buffer: Option<v8::Local<v8::TypedArray>>,
+) -> PinnedBuffers {
+  let mut backing_stores = vec![];
+  if let Ok(args) = v8::Local::<v8::Array>::try_from(args) {
+    for (index, native_type) in parameter_types.iter().enumerate() {
+      if !matches!(
+        native_type,
+        NativeType::Buffer | NativeType::Struct(_) | NativeType::Union(_)
+      ) {
+        continue;
+      }
+      let Some(value) = args.get_index(scope, index as u32) else {
+        continue;
+      };
+      let buffer =
+        if let Ok(value) = v8::Local::<v8::ArrayBuffer>::try_from(value) {
+          Some(value)
+        } else if let Ok(value) =
+          v8::Local::<v8::ArrayBufferView>::try_from(value)
+        {
+          value.buffer(scope)
+        } else {
+          None
+        };
+      if let Some(buffer) = buffer {
+        backing_stores.push(buffer.get_backing_store());
+      }
+    }
+  }
+  if let Some(buffer) =
+    out_buffer.and_then(|out_buffer| out_buffer.buffer(scope))
+  {
+    backing_stores.push(buffer.get_backing_store());
+  }
+  PinnedBuffers {
+    _backing_stores: backing_stores,
+  }
+}
+
 /// Intermediate format for easy translation from NativeType + V8 value
 /// to libffi argument types.
 #[repr(C)]
@@ -75,7 +130,7 @@ impl NativeValue {
       NativeType::Pointer | NativeType::Buffer | NativeType::Function => {
         Arg::new(&self.pointer)
       }
-      NativeType::Struct(_) => Arg::new(&*self.pointer),
+      NativeType::Struct(_) | NativeType::Union(_) => Arg::new(&*self.pointer),
     }
   }
 
@@ -185,7 +240,7 @@ impl NativeValue {
         };
         local_value.into()
       }
-      NativeType::Struct(_) => {
+      NativeType::Struct(_) | NativeType::Union(_) => {
         let local_value: v8::Local<v8::Value> = v8::null(scope).into();
         local_value.into()
       }
@@ -543,7 +598,7 @@ where
       NativeType::Buffer => {
         ffi_args.push(ffi_parse_buffer_arg(scope, value)?);
       }
-      NativeType::Struct(_) => {
+      NativeType::Struct(_) | NativeType::Union(_) => {
         ffi_args.push(ffi_parse_struct_arg(scope, value)?);
       }
       NativeType::Pointer => {
@@ -26,6 +26,8 @@ use call::op_ffi_call_ptr_nonblocking;
 use callback::op_ffi_unsafe_callback_close;
 use callback::op_ffi_unsafe_callback_create;
 use callback::op_ffi_unsafe_callback_ref;
+use dlfcn::op_ffi_bind_symbol;
+use dlfcn::op_ffi_exported_symbols;
 use dlfcn::op_ffi_load;
 use dlfcn::ForeignFunction;
 use r#static::op_ffi_get_static;
@@ -79,6 +81,8 @@ deno_core::extension!(deno_ffi,
   parameters = [P: FfiPermissions],
   ops = [
     op_ffi_load<P>,
+    op_ffi_bind_symbol,
+    op_ffi_exported_symbols,
     op_ffi_get_static,
     op_ffi_call_nonblocking,
     op_ffi_call_ptr<P>,
@@ -153,5 +153,8 @@ pub fn op_ffi_get_static<'scope>(
     NativeType::Struct(_) => {
       return Err(type_error("Invalid FFI static type 'struct'"));
     }
+    NativeType::Union(_) => {
+      return Err(type_error("Invalid FFI static type 'union'"));
+    }
   })
 }
@@ -26,6 +26,7 @@ pub enum NativeType {
   Buffer,
   Function,
   Struct(Box<[NativeType]>),
+  Union(Box<[NativeType]>),
 }
 
 impl TryFrom<NativeType> for libffi::middle::Type {
@@ -60,10 +61,201 @@ impl TryFrom<NativeType> for libffi::middle::Type {
           }
         })
       }
+      NativeType::Union(members) => {
+        if members.is_empty() {
+          return Err(type_error("Union must have at least one member"));
+        }
+        union_type(&members)?
+      }
+    })
+  }
+}
+
+impl NativeType {
+  /// The size and alignment of the type in C, in bytes.
+  fn size_and_alignment(&self) -> Result<(usize, usize), AnyError> {
+    Ok(match self {
+      NativeType::Void => (0, 1),
+      NativeType::U8 | NativeType::I8 | NativeType::Bool => (1, 1),
+      NativeType::U16 | NativeType::I16 => (2, 2),
+      NativeType::U32 | NativeType::I32 | NativeType::F32 => (4, 4),
+      NativeType::U64 | NativeType::I64 | NativeType::F64 => (8, 8),
+      NativeType::USize
+      | NativeType::ISize
+      | NativeType::Pointer
+      | NativeType::Buffer
+      | NativeType::Function => {
+        (std::mem::size_of::<usize>(), std::mem::align_of::<usize>())
+      }
+      NativeType::Struct(fields) => {
+        let mut size = 0;
+        let mut alignment = 1;
+        for field in fields.iter() {
+          let (field_size, field_alignment) = field.size_and_alignment()?;
+          alignment = alignment.max(field_alignment);
+          size = round_up(size, field_alignment) + field_size;
+        }
+        (round_up(size, alignment), alignment)
+      }
+      NativeType::Union(members) => {
+        let mut size = 0;
+        let mut alignment = 1;
+        for member in members.iter() {
+          let (member_size, member_alignment) = member.size_and_alignment()?;
+          alignment = alignment.max(member_alignment);
+          size = size.max(member_size);
+        }
+        (round_up(size, alignment), alignment)
+      }
     })
   }
 }
 
+fn round_up(size: usize, alignment: usize) -> usize {
+  (size + alignment - 1) / alignment * alignment
+}
+
+/// How the calling conventions treat a scalar inside an aggregate.
+#[derive(Clone, Copy, PartialEq)]
+enum ScalarClass {
+  Integer,
+  F32,
+  F64,
+}
+
+/// A scalar inside an aggregate, at `offset` bytes from its start.
+struct Scalar {
+  offset: usize,
+  size: usize,
+  class: ScalarClass,
+}
+
+impl NativeType {
+  /// Appends the scalars making up the type, placed at `offset`, to `out`.
+  fn scalars(
+    &self,
+    offset: usize,
+    out: &mut Vec<Scalar>,
+  ) -> Result<(), AnyError> {
+    let class = match self {
+      NativeType::Void => return Ok(()),
+      NativeType::F32 => ScalarClass::F32,
+      NativeType::F64 => ScalarClass::F64,
+      NativeType::Struct(fields) => {
+        let mut field_offset = 0;
+        for field in fields.iter() {
+          let (field_size, field_alignment) = field.size_and_alignment()?;
+          field_offset = round_up(field_offset, field_alignment);
+          field.scalars(offset + field_offset, out)?;
+          field_offset += field_size;
+        }
+        return Ok(());
+      }
+      NativeType::Union(members) => {
+        for member in members.iter() {
+          member.scalars(offset, out)?;
+        }
+        return Ok(());
+      }
+      _ => ScalarClass::Integer,
+    };
+    let (size, _) = self.size_and_alignment()?;
+    out.push(Scalar {
+      offset,
+      size,
+      class,
+    });
+    Ok(())
+  }
+}
+
+/// libffi has no union type. As its documentation suggests, a union is
+/// described as a struct with the size and alignment of the whole union. Its
+/// fields are chosen so that libffi assigns the union the registers the
+/// calling convention does, see [`union_fields`].
+fn union_type(
+  members: &[NativeType],
+) -> Result<libffi::middle::Type, AnyError> {
+  let (size, alignment) =
+    NativeType::Union(members.into()).size_and_alignment()?;
+  let mut scalars = Vec::new();
+  for member in members {
+    member.scalars(0, &mut scalars)?;
+  }
+
+  let union_type =
+    libffi::middle::Type::structure(union_fields(size, &scalars));
+  let raw = union_type.as_raw_ptr();
+  // SAFETY: `raw` points to the type created above, which isn't shared yet.
+  // libffi doesn't lay out again an aggregate whose size is set, and its
+  // fields are scalars which need no layout.
+  unsafe {
+    (*raw).size = size;
+    (*raw).alignment = alignment as u16;
+  }
+  Ok(union_type)
+}
+
+/// AAPCS64 passes a union in floating-point registers only if it is a
+/// homogeneous floating-point aggregate: all of its members are made of the
+/// same floating-point type, with at most four of them fitting in it. Any
+/// other union is passed like an array of integers.
+#[cfg(target_arch = "aarch64")]
+fn union_fields(size: usize, scalars: &[Scalar]) -> Vec<libffi::middle::Type> {
+  let (float_type, float_size) = match scalars.first().map(|s| s.class) {
+    Some(ScalarClass::F32) => (libffi::middle::Type::f32(), 4),
+    Some(ScalarClass::F64) => (libffi::middle::Type::f64(), 8),
+    _ => return integer_fields(size),
+  };
+  let homogeneous = scalars.iter().all(|s| s.class == scalars[0].class);
+  if homogeneous && size % float_size == 0 && size / float_size <= 4 {
+    vec![float_type; size / float_size]
+  } else {
+    integer_fields(size)
+  }
+}
+
+/// The System V x86-64 ABI classifies each eightbyte of a union on its own:
+/// it goes in a general purpose register if any member has an integer in it,
+/// and in a vector register if only floats overlap it. Windows passes
+/// aggregates according to their size alone.
+#[cfg(not(target_arch = "aarch64"))]
+fn union_fields(size: usize, scalars: &[Scalar]) -> Vec<libffi::middle::Type> {
+  let mut fields = Vec::new();
+  for start in (0..size).step_by(8) {
+    let len = (size - start).min(8);
+    let mut overlapping = scalars
+      .iter()
+      .filter(|s| s.offset < start + len && s.offset + s.size > start)
+      .peekable();
+    let only_floats = overlapping.peek().is_some()
+      && overlapping.all(|s| s.class != ScalarClass::Integer);
+    match (only_floats, len) {
+      (true, 4) => fields.push(libffi::middle::Type::f32()),
+      (true, 8) => fields.push(libffi::middle::Type::f64()),
+      _ => fields.extend(integer_fields(len)),
+    }
+  }
+  fields
+}
+
+/// Naturally aligned integers covering `size` bytes.
+fn integer_fields(mut size: usize) -> Vec<libffi::middle::Type> {
+  let mut fields = Vec::new();
+  for (len, ty) in [
+    (8, libffi::middle::Type::u64()),
+    (4, libffi::middle::Type::u32()),
+    (2, libffi::middle::Type::u16()),
+    (1, libffi::middle::Type::u8()),
+  ] {
+    while size >= len {
+      fields.push(ty.clone());
+      size -= len;
+    }
+  }
+  fields
+}
+
 #[derive(Clone)]
 pub struct Symbol {
   pub cif: libffi::middle::Cif,
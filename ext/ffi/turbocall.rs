@@ -20,11 +20,14 @@ pub(crate) fn is_compatible(sym: &Symbol) -> bool {
     all(target_arch = "x86_64", target_family = "windows"),
     all(target_arch = "aarch64", target_vendor = "apple")
   )) && !sym.can_callback
-    && !matches!(sym.result_type, NativeType::Struct(_))
+    && !matches!(
+      sym.result_type,
+      NativeType::Struct(_) | NativeType::Union(_)
+    )
     && !sym
       .parameter_types
       .iter()
-      .any(|t| matches!(t, NativeType::Struct(_)))
+      .any(|t| matches!(t, NativeType::Struct(_) | NativeType::Union(_)))
 }
 
 pub(crate) fn compile_trampoline(sym: &Symbol) -> Trampoline {
@@ -95,7 +98,7 @@ impl From<&NativeType> for fast_api::Type {
       NativeType::USize => fast_api::Type::Uint64,
       NativeType::Pointer | NativeType::Function => fast_api::Type::Pointer,
       NativeType::Buffer => fast_api::Type::TypedArray(fast_api::CType::Uint8),
-      NativeType::Struct(_) => {
+      NativeType::Struct(_) | NativeType::Union(_) => {
         fast_api::Type::TypedArray(fast_api::CType::Uint8)
       }
     }
@@ -1504,7 +1507,7 @@ impl From<NativeType> for Param {
       NativeType::I32 => Int(I(DW)),
       NativeType::I64 | NativeType::ISize => Int(I(QW)),
       NativeType::Buffer => Int(Buffer),
-      NativeType::Struct(_) => unimplemented!(),
+      NativeType::Struct(_) | NativeType::Union(_) => unimplemented!(),
     }
   }
 }
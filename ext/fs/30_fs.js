@@ -70,21 +70,25 @@ async function chown(
 function copyFileSync(
   fromPath,
   toPath,
+  options,
 ) {
   ops.op_fs_copy_file_sync(
     pathFromURL(fromPath),
     pathFromURL(toPath),
+    options,
   );
 }
 
 async function copyFile(
   fromPath,
   toPath,
+  options,
 ) {
   await core.opAsync(
     "op_fs_copy_file_async",
     pathFromURL(fromPath),
     pathFromURL(toPath),
+    options,
   );
 }
 
@@ -482,6 +486,38 @@ async function utime(
   );
 }
 
+function getXattrSync(path, name) {
+  return ops.op_fs_get_xattr_sync(pathFromURL(path), name);
+}
+
+function getXattr(path, name) {
+  return core.opAsync("op_fs_get_xattr_async", pathFromURL(path), name);
+}
+
+function setXattrSync(path, name, value) {
+  ops.op_fs_set_xattr_sync(pathFromURL(path), name, value);
+}
+
+async function setXattr(path, name, value) {
+  await core.opAsync("op_fs_set_xattr_async", pathFromURL(path), name, value);
+}
+
+function listXattrSync(path) {
+  return ops.op_fs_list_xattr_sync(pathFromURL(path));
+}
+
+function listXattr(path) {
+  return core.opAsync("op_fs_list_xattr_async", pathFromURL(path));
+}
+
+function removeXattrSync(path, name) {
+  ops.op_fs_remove_xattr_sync(pathFromURL(path), name);
+}
+
+async function removeXattr(path, name) {
+  await core.opAsync("op_fs_remove_xattr_async", pathFromURL(path), name);
+}
+
 function symlinkSync(
   oldpath,
   newpath,
@@ -878,8 +914,12 @@ export {
   funlockSync,
   futime,
   futimeSync,
+  getXattr,
+  getXattrSync,
   link,
   linkSync,
+  listXattr,
+  listXattrSync,
   lstat,
   lstatSync,
   makeTempDir,
@@ -902,10 +942,14 @@ export {
   realPathSync,
   remove,
   removeSync,
+  removeXattr,
+  removeXattrSync,
   rename,
   renameSync,
   seek,
   seekSync,
+  setXattr,
+  setXattrSync,
   stat,
   statSync,
   symlink,
@@ -58,6 +58,29 @@ impl OpenOptions {
   }
 }
 
+/// Whether [`FileSystem::copy_file_sync`] creates a copy-on-write clone of
+/// the file, sharing its data blocks until either file is modified.
+#[derive(Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CloneMode {
+  /// Clone if the file system supports it and copy the contents otherwise.
+  #[default]
+  Auto,
+  /// Fail if the file can't be cloned.
+  Force,
+  /// Always copy the contents.
+  Never,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+#[serde(default)]
+pub struct CopyFileOptions {
+  /// Fail if the destination already exists instead of overwriting it.
+  pub exclusive: bool,
+  pub clone: CloneMode,
+}
+
 #[derive(Deserialize)]
 pub enum FsFileType {
   #[serde(rename = "file")]
@@ -123,11 +146,17 @@ pub trait FileSystem: std::fmt::Debug + MaybeSend + MaybeSync {
   fn remove_sync(&self, path: &Path, recursive: bool) -> FsResult<()>;
   async fn remove_async(&self, path: PathBuf, recursive: bool) -> FsResult<()>;
 
-  fn copy_file_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()>;
+  fn copy_file_sync(
+    &self,
+    oldpath: &Path,
+    newpath: &Path,
+    options: CopyFileOptions,
+  ) -> FsResult<()>;
   async fn copy_file_async(
     &self,
     oldpath: PathBuf,
     newpath: PathBuf,
+    options: CopyFileOptions,
   ) -> FsResult<()>;
 
   fn stat_sync(&self, path: &Path) -> FsResult<FsStat>;
@@ -192,6 +221,40 @@ pub trait FileSystem: std::fmt::Debug + MaybeSend + MaybeSync {
     mtime_nanos: u32,
   ) -> FsResult<()>;
 
+  fn get_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+  ) -> FsResult<Option<Vec<u8>>>;
+  async fn get_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<Option<Vec<u8>>>;
+
+  fn set_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+    value: &[u8],
+  ) -> FsResult<()>;
+  async fn set_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+    value: Vec<u8>,
+  ) -> FsResult<()>;
+
+  fn list_xattr_sync(&self, path: &Path) -> FsResult<Vec<String>>;
+  async fn list_xattr_async(&self, path: PathBuf) -> FsResult<Vec<String>>;
+
+  fn remove_xattr_sync(&self, path: &Path, name: &str) -> FsResult<()>;
+  async fn remove_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<()>;
+
   fn write_file_sync(
     &self,
     path: &Path,
@@ -2,14 +2,18 @@
 
 mod interface;
 mod ops;
+mod rooted_fs;
 mod std_fs;
 pub mod sync;
 
+pub use crate::interface::CloneMode;
+pub use crate::interface::CopyFileOptions;
 pub use crate::interface::FileSystem;
 pub use crate::interface::FileSystemRc;
 pub use crate::interface::FsDirEntry;
 pub use crate::interface::FsFileType;
 pub use crate::interface::OpenOptions;
+pub use crate::rooted_fs::RootedFs;
 pub use crate::std_fs::RealFs;
 pub use crate::sync::MaybeSend;
 pub use crate::sync::MaybeSync;
@@ -124,6 +128,14 @@ deno_core::extension!(deno_fs,
     op_fs_truncate_async<P>,
     op_fs_utime_sync<P>,
     op_fs_utime_async<P>,
+    op_fs_get_xattr_sync<P>,
+    op_fs_get_xattr_async<P>,
+    op_fs_set_xattr_sync<P>,
+    op_fs_set_xattr_async<P>,
+    op_fs_list_xattr_sync<P>,
+    op_fs_list_xattr_async<P>,
+    op_fs_remove_xattr_sync<P>,
+    op_fs_remove_xattr_async<P>,
     op_fs_make_temp_dir_sync<P>,
     op_fs_make_temp_dir_async<P>,
     op_fs_make_temp_file_sync<P>,
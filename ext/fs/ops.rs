@@ -27,6 +27,7 @@ use serde::Serialize;
 
 use crate::check_unstable;
 use crate::check_unstable2;
+use crate::interface::CopyFileOptions;
 use crate::interface::FileSystemRc;
 use crate::interface::FsDirEntry;
 use crate::interface::FsFileType;
@@ -311,6 +312,7 @@ fn op_fs_copy_file_sync<P>(
   state: &mut OpState,
   from: &str,
   to: &str,
+  options: Option<CopyFileOptions>,
 ) -> Result<(), AnyError>
 where
   P: FsPermissions + 'static,
@@ -323,7 +325,7 @@ where
   permissions.check_write(&to, "Deno.copyFileSync()")?;
 
   let fs = state.borrow::<FileSystemRc>();
-  fs.copy_file_sync(&from, &to)
+  fs.copy_file_sync(&from, &to, options.unwrap_or_default())
     .context_two_path("copy", &from, &to)?;
 
   Ok(())
@@ -334,6 +336,7 @@ async fn op_fs_copy_file_async<P>(
   state: Rc<RefCell<OpState>>,
   from: String,
   to: String,
+  options: Option<CopyFileOptions>,
 ) -> Result<(), AnyError>
 where
   P: FsPermissions + 'static,
@@ -349,7 +352,7 @@ where
     state.borrow::<FileSystemRc>().clone()
   };
 
-  fs.copy_file_async(from.clone(), to.clone())
+  fs.copy_file_async(from.clone(), to.clone(), options.unwrap_or_default())
     .await
     .context_two_path("copy", &from, &to)?;
 
@@ -848,6 +851,208 @@ where
   Ok(())
 }
 
+#[op]
+fn op_fs_get_xattr_sync<P>(
+  state: &mut OpState,
+  path: String,
+  name: String,
+) -> Result<Option<ZeroCopyBuf>, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.getXattrSync");
+  let path = PathBuf::from(path);
+
+  state
+    .borrow_mut::<P>()
+    .check_read(&path, "Deno.getXattrSync()")?;
+
+  let fs = state.borrow::<FileSystemRc>();
+  let value = fs
+    .get_xattr_sync(&path, &name)
+    .context_path("getxattr", &path)?;
+
+  Ok(value.map(Into::into))
+}
+
+#[op]
+async fn op_fs_get_xattr_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+  name: String,
+) -> Result<Option<ZeroCopyBuf>, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.getXattr");
+  let path = PathBuf::from(path);
+
+  let fs = {
+    let mut state = state.borrow_mut();
+    state
+      .borrow_mut::<P>()
+      .check_read(&path, "Deno.getXattr()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+
+  let value = fs
+    .get_xattr_async(path.clone(), name)
+    .await
+    .context_path("getxattr", &path)?;
+
+  Ok(value.map(Into::into))
+}
+
+#[op]
+fn op_fs_set_xattr_sync<P>(
+  state: &mut OpState,
+  path: String,
+  name: String,
+  value: ZeroCopyBuf,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.setXattrSync");
+  let path = PathBuf::from(path);
+
+  state
+    .borrow_mut::<P>()
+    .check_write(&path, "Deno.setXattrSync()")?;
+
+  let fs = state.borrow::<FileSystemRc>();
+  fs.set_xattr_sync(&path, &name, &value)
+    .context_path("setxattr", &path)?;
+
+  Ok(())
+}
+
+#[op]
+async fn op_fs_set_xattr_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+  name: String,
+  value: ZeroCopyBuf,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.setXattr");
+  let path = PathBuf::from(path);
+
+  let fs = {
+    let mut state = state.borrow_mut();
+    state
+      .borrow_mut::<P>()
+      .check_write(&path, "Deno.setXattr()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+
+  fs.set_xattr_async(path.clone(), name, value.to_vec())
+    .await
+    .context_path("setxattr", &path)?;
+
+  Ok(())
+}
+
+#[op]
+fn op_fs_list_xattr_sync<P>(
+  state: &mut OpState,
+  path: String,
+) -> Result<Vec<String>, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.listXattrSync");
+  let path = PathBuf::from(path);
+
+  state
+    .borrow_mut::<P>()
+    .check_read(&path, "Deno.listXattrSync()")?;
+
+  let fs = state.borrow::<FileSystemRc>();
+  let names = fs.list_xattr_sync(&path).context_path("listxattr", &path)?;
+
+  Ok(names)
+}
+
+#[op]
+async fn op_fs_list_xattr_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+) -> Result<Vec<String>, AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.listXattr");
+  let path = PathBuf::from(path);
+
+  let fs = {
+    let mut state = state.borrow_mut();
+    state
+      .borrow_mut::<P>()
+      .check_read(&path, "Deno.listXattr()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+
+  let names = fs
+    .list_xattr_async(path.clone())
+    .await
+    .context_path("listxattr", &path)?;
+
+  Ok(names)
+}
+
+#[op]
+fn op_fs_remove_xattr_sync<P>(
+  state: &mut OpState,
+  path: String,
+  name: String,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable(state, "Deno.removeXattrSync");
+  let path = PathBuf::from(path);
+
+  state
+    .borrow_mut::<P>()
+    .check_write(&path, "Deno.removeXattrSync()")?;
+
+  let fs = state.borrow::<FileSystemRc>();
+  fs.remove_xattr_sync(&path, &name)
+    .context_path("removexattr", &path)?;
+
+  Ok(())
+}
+
+#[op]
+async fn op_fs_remove_xattr_async<P>(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+  name: String,
+) -> Result<(), AnyError>
+where
+  P: FsPermissions + 'static,
+{
+  check_unstable2(&state, "Deno.removeXattr");
+  let path = PathBuf::from(path);
+
+  let fs = {
+    let mut state = state.borrow_mut();
+    state
+      .borrow_mut::<P>()
+      .check_write(&path, "Deno.removeXattr()")?;
+    state.borrow::<FileSystemRc>().clone()
+  };
+
+  fs.remove_xattr_async(path.clone(), name)
+    .await
+    .context_path("removexattr", &path)?;
+
+  Ok(())
+}
+
 #[op]
 fn op_fs_make_temp_dir_sync<P>(
   state: &mut OpState,
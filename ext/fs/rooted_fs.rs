@@ -0,0 +1,621 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::io;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use deno_io::fs::File;
+use deno_io::fs::FsError;
+use deno_io::fs::FsResult;
+use deno_io::fs::FsStat;
+
+use crate::interface::CopyFileOptions;
+use crate::interface::FileSystemRc;
+use crate::interface::FsDirEntry;
+use crate::interface::FsFileType;
+use crate::FileSystem;
+use crate::OpenOptions;
+
+/// Maximum number of symlinks followed while resolving a path, like
+/// `MAXSYMLINKS` on Linux.
+const MAX_SYMLINKS: usize = 40;
+
+/// A file system confining every operation to a root directory, independent
+/// of permissions.
+///
+/// Paths keep their meaning, but are resolved before they are handed to the
+/// inner file system: relative paths against the current directory, and
+/// symlinks in them, so that a symlink can't be used to escape the root.
+/// Operations on paths resolving outside of the root fail with a
+/// `PermissionDenied` error.
+///
+/// This is a defense in depth measure: a symlink swapped in by another
+/// process between the resolution of a path and its use is not detected.
+#[derive(Debug, Clone)]
+pub struct RootedFs {
+  root: PathBuf,
+  inner: FileSystemRc,
+}
+
+impl RootedFs {
+  /// Confines `inner` to `root`, which must be an existing directory.
+  pub fn new(root: &Path, inner: FileSystemRc) -> FsResult<Self> {
+    let root = resolve(inner.as_ref(), root, true)?;
+    if !inner.stat_sync(&root)?.is_directory {
+      return Err(FsError::Io(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("File system root {} is not a directory", root.display()),
+      )));
+    }
+    Ok(Self { root, inner })
+  }
+
+  pub fn root(&self) -> &Path {
+    &self.root
+  }
+
+  /// Resolves `path`, following a symlink in its last component if
+  /// `follow_last` is true, and checks that it is within the root.
+  ///
+  /// Resolving only stats and reads links of the components of the path, and
+  /// is done synchronously by the async methods as well.
+  fn confine(&self, path: &Path, follow_last: bool) -> FsResult<PathBuf> {
+    let resolved = resolve(self.inner.as_ref(), path, follow_last)?;
+    if resolved.starts_with(&self.root) {
+      Ok(resolved)
+    } else {
+      Err(FsError::Io(io::Error::new(
+        io::ErrorKind::PermissionDenied,
+        format!(
+          "{} is outside of the file system root {}",
+          path.display(),
+          self.root.display()
+        ),
+      )))
+    }
+  }
+
+  /// Checks the target of a symlink to be created at `newpath`, which is
+  /// relative to the directory of the link.
+  fn confine_symlink_target(
+    &self,
+    oldpath: &Path,
+    newpath: &Path,
+  ) -> FsResult<()> {
+    let target = match newpath.parent() {
+      Some(parent) if oldpath.is_relative() => parent.join(oldpath),
+      _ => oldpath.to_path_buf(),
+    };
+    self.confine(&target, true)?;
+    Ok(())
+  }
+}
+
+/// Resolves `path` to an absolute path without symlinks, except for the last
+/// component if `follow_last` is false. Components that don't exist are kept
+/// as is.
+fn resolve(
+  fs: &dyn FileSystem,
+  path: &Path,
+  follow_last: bool,
+) -> FsResult<PathBuf> {
+  let path = if path.is_absolute() {
+    path.to_path_buf()
+  } else {
+    fs.cwd()?.join(path)
+  };
+  let mut pending: Vec<Component> = path.components().rev().collect();
+  let mut resolved = PathBuf::new();
+  let mut symlinks = 0;
+  while let Some(component) = pending.pop() {
+    let name = match component {
+      Component::Prefix(_) | Component::RootDir => {
+        resolved.push(component);
+        continue;
+      }
+      Component::CurDir => continue,
+      Component::ParentDir => {
+        resolved.pop();
+        continue;
+      }
+      Component::Normal(name) => name,
+    };
+    let candidate = resolved.join(name);
+    if pending.is_empty() && !follow_last {
+      resolved = candidate;
+      break;
+    }
+    let is_symlink = match fs.lstat_sync(&candidate) {
+      Ok(stat) => stat.is_symlink,
+      Err(FsError::Io(err)) if err.kind() == io::ErrorKind::NotFound => false,
+      Err(err) => return Err(err),
+    };
+    if !is_symlink {
+      resolved = candidate;
+      continue;
+    }
+    symlinks += 1;
+    if symlinks > MAX_SYMLINKS {
+      return Err(FsError::Io(io::Error::new(
+        io::ErrorKind::Other,
+        format!("Too many levels of symbolic links: {}", path.display()),
+      )));
+    }
+    // The target replaces the link, and is relative to its directory.
+    let target = fs.read_link_sync(&candidate)?;
+    pending.extend(target.components().rev());
+  }
+  Ok(resolved)
+}
+
+#[async_trait::async_trait(?Send)]
+impl FileSystem for RootedFs {
+  fn cwd(&self) -> FsResult<PathBuf> {
+    self.inner.cwd()
+  }
+
+  fn tmp_dir(&self) -> FsResult<PathBuf> {
+    self.inner.tmp_dir()
+  }
+
+  fn chdir(&self, path: &Path) -> FsResult<()> {
+    let path = self.confine(path, true)?;
+    self.inner.chdir(&path)
+  }
+
+  fn umask(&self, mask: Option<u32>) -> FsResult<u32> {
+    self.inner.umask(mask)
+  }
+
+  fn open_sync(
+    &self,
+    path: &Path,
+    options: OpenOptions,
+  ) -> FsResult<Rc<dyn File>> {
+    let path = self.confine(path, true)?;
+    self.inner.open_sync(&path, options)
+  }
+  async fn open_async(
+    &self,
+    path: PathBuf,
+    options: OpenOptions,
+  ) -> FsResult<Rc<dyn File>> {
+    let path = self.confine(&path, true)?;
+    self.inner.open_async(path, options).await
+  }
+
+  fn mkdir_sync(
+    &self,
+    path: &Path,
+    recursive: bool,
+    mode: u32,
+  ) -> FsResult<()> {
+    let path = self.confine(path, false)?;
+    self.inner.mkdir_sync(&path, recursive, mode)
+  }
+  async fn mkdir_async(
+    &self,
+    path: PathBuf,
+    recursive: bool,
+    mode: u32,
+  ) -> FsResult<()> {
+    let path = self.confine(&path, false)?;
+    self.inner.mkdir_async(path, recursive, mode).await
+  }
+
+  fn chmod_sync(&self, path: &Path, mode: u32) -> FsResult<()> {
+    let path = self.confine(path, true)?;
+    self.inner.chmod_sync(&path, mode)
+  }
+  async fn chmod_async(&self, path: PathBuf, mode: u32) -> FsResult<()> {
+    let path = self.confine(&path, true)?;
+    self.inner.chmod_async(path, mode).await
+  }
+
+  fn chown_sync(
+    &self,
+    path: &Path,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    let path = self.confine(path, true)?;
+    self.inner.chown_sync(&path, uid, gid)
+  }
+  async fn chown_async(
+    &self,
+    path: PathBuf,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    let path = self.confine(&path, true)?;
+    self.inner.chown_async(path, uid, gid).await
+  }
+
+  fn remove_sync(&self, path: &Path, recursive: bool) -> FsResult<()> {
+    let path = self.confine(path, false)?;
+    self.inner.remove_sync(&path, recursive)
+  }
+  async fn remove_async(&self, path: PathBuf, recursive: bool) -> FsResult<()> {
+    let path = self.confine(&path, false)?;
+    self.inner.remove_async(path, recursive).await
+  }
+
+  fn copy_file_sync(
+    &self,
+    oldpath: &Path,
+    newpath: &Path,
+    options: CopyFileOptions,
+  ) -> FsResult<()> {
+    let oldpath = self.confine(oldpath, true)?;
+    let newpath = self.confine(newpath, true)?;
+    self.inner.copy_file_sync(&oldpath, &newpath, options)
+  }
+  async fn copy_file_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+    options: CopyFileOptions,
+  ) -> FsResult<()> {
+    let oldpath = self.confine(&oldpath, true)?;
+    let newpath = self.confine(&newpath, true)?;
+    self.inner.copy_file_async(oldpath, newpath, options).await
+  }
+
+  fn stat_sync(&self, path: &Path) -> FsResult<FsStat> {
+    let path = self.confine(path, true)?;
+    self.inner.stat_sync(&path)
+  }
+  async fn stat_async(&self, path: PathBuf) -> FsResult<FsStat> {
+    let path = self.confine(&path, true)?;
+    self.inner.stat_async(path).await
+  }
+
+  fn lstat_sync(&self, path: &Path) -> FsResult<FsStat> {
+    let path = self.confine(path, false)?;
+    self.inner.lstat_sync(&path)
+  }
+  async fn lstat_async(&self, path: PathBuf) -> FsResult<FsStat> {
+    let path = self.confine(&path, false)?;
+    self.inner.lstat_async(path).await
+  }
+
+  fn realpath_sync(&self, path: &Path) -> FsResult<PathBuf> {
+    let path = self.confine(path, true)?;
+    self.inner.realpath_sync(&path)
+  }
+  async fn realpath_async(&self, path: PathBuf) -> FsResult<PathBuf> {
+    let path = self.confine(&path, true)?;
+    self.inner.realpath_async(path).await
+  }
+
+  fn read_dir_sync(&self, path: &Path) -> FsResult<Vec<FsDirEntry>> {
+    let path = self.confine(path, true)?;
+    self.inner.read_dir_sync(&path)
+  }
+  async fn read_dir_async(&self, path: PathBuf) -> FsResult<Vec<FsDirEntry>> {
+    let path = self.confine(&path, true)?;
+    self.inner.read_dir_async(path).await
+  }
+
+  fn rename_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()> {
+    let oldpath = self.confine(oldpath, false)?;
+    let newpath = self.confine(newpath, false)?;
+    self.inner.rename_sync(&oldpath, &newpath)
+  }
+  async fn rename_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+  ) -> FsResult<()> {
+    let oldpath = self.confine(&oldpath, false)?;
+    let newpath = self.confine(&newpath, false)?;
+    self.inner.rename_async(oldpath, newpath).await
+  }
+
+  fn link_sync(&self, oldpath: &Path, newpath: &Path) -> FsResult<()> {
+    let oldpath = self.confine(oldpath, false)?;
+    let newpath = self.confine(newpath, false)?;
+    self.inner.link_sync(&oldpath, &newpath)
+  }
+  async fn link_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+  ) -> FsResult<()> {
+    let oldpath = self.confine(&oldpath, false)?;
+    let newpath = self.confine(&newpath, false)?;
+    self.inner.link_async(oldpath, newpath).await
+  }
+
+  fn symlink_sync(
+    &self,
+    oldpath: &Path,
+    newpath: &Path,
+    file_type: Option<FsFileType>,
+  ) -> FsResult<()> {
+    self.confine_symlink_target(oldpath, newpath)?;
+    let newpath = self.confine(newpath, false)?;
+    self.inner.symlink_sync(oldpath, &newpath, file_type)
+  }
+  async fn symlink_async(
+    &self,
+    oldpath: PathBuf,
+    newpath: PathBuf,
+    file_type: Option<FsFileType>,
+  ) -> FsResult<()> {
+    self.confine_symlink_target(&oldpath, &newpath)?;
+    let newpath = self.confine(&newpath, false)?;
+    self.inner.symlink_async(oldpath, newpath, file_type).await
+  }
+
+  fn read_link_sync(&self, path: &Path) -> FsResult<PathBuf> {
+    let path = self.confine(path, false)?;
+    self.inner.read_link_sync(&path)
+  }
+  async fn read_link_async(&self, path: PathBuf) -> FsResult<PathBuf> {
+    let path = self.confine(&path, false)?;
+    self.inner.read_link_async(path).await
+  }
+
+  fn truncate_sync(&self, path: &Path, len: u64) -> FsResult<()> {
+    let path = self.confine(path, true)?;
+    self.inner.truncate_sync(&path, len)
+  }
+  async fn truncate_async(&self, path: PathBuf, len: u64) -> FsResult<()> {
+    let path = self.confine(&path, true)?;
+    self.inner.truncate_async(path, len).await
+  }
+
+  fn utime_sync(
+    &self,
+    path: &Path,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    let path = self.confine(path, true)?;
+    self.inner.utime_sync(
+      &path,
+      atime_secs,
+      atime_nanos,
+      mtime_secs,
+      mtime_nanos,
+    )
+  }
+  async fn utime_async(
+    &self,
+    path: PathBuf,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    let path = self.confine(&path, true)?;
+    self
+      .inner
+      .utime_async(path, atime_secs, atime_nanos, mtime_secs, mtime_nanos)
+      .await
+  }
+
+  fn get_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+  ) -> FsResult<Option<Vec<u8>>> {
+    let path = self.confine(path, true)?;
+    self.inner.get_xattr_sync(&path, name)
+  }
+  async fn get_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<Option<Vec<u8>>> {
+    let path = self.confine(&path, true)?;
+    self.inner.get_xattr_async(path, name).await
+  }
+
+  fn set_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+    value: &[u8],
+  ) -> FsResult<()> {
+    let path = self.confine(path, true)?;
+    self.inner.set_xattr_sync(&path, name, value)
+  }
+  async fn set_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+    value: Vec<u8>,
+  ) -> FsResult<()> {
+    let path = self.confine(&path, true)?;
+    self.inner.set_xattr_async(path, name, value).await
+  }
+
+  fn list_xattr_sync(&self, path: &Path) -> FsResult<Vec<String>> {
+    let path = self.confine(path, true)?;
+    self.inner.list_xattr_sync(&path)
+  }
+  async fn list_xattr_async(&self, path: PathBuf) -> FsResult<Vec<String>> {
+    let path = self.confine(&path, true)?;
+    self.inner.list_xattr_async(path).await
+  }
+
+  fn remove_xattr_sync(&self, path: &Path, name: &str) -> FsResult<()> {
+    let path = self.confine(path, true)?;
+    self.inner.remove_xattr_sync(&path, name)
+  }
+  async fn remove_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<()> {
+    let path = self.confine(&path, true)?;
+    self.inner.remove_xattr_async(path, name).await
+  }
+
+  fn write_file_sync(
+    &self,
+    path: &Path,
+    options: OpenOptions,
+    data: &[u8],
+  ) -> FsResult<()> {
+    let path = self.confine(path, true)?;
+    self.inner.write_file_sync(&path, options, data)
+  }
+  async fn write_file_async(
+    &self,
+    path: PathBuf,
+    options: OpenOptions,
+    data: Vec<u8>,
+  ) -> FsResult<()> {
+    let path = self.confine(&path, true)?;
+    self.inner.write_file_async(path, options, data).await
+  }
+
+  fn read_file_sync(&self, path: &Path) -> FsResult<Vec<u8>> {
+    let path = self.confine(path, true)?;
+    self.inner.read_file_sync(&path)
+  }
+  async fn read_file_async(&self, path: PathBuf) -> FsResult<Vec<u8>> {
+    let path = self.confine(&path, true)?;
+    self.inner.read_file_async(path).await
+  }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use super::*;
+  use crate::sync::MaybeArc;
+  use crate::RealFs;
+
+  struct Setup {
+    _dir: tempfile::TempDir,
+    root: PathBuf,
+    outside: PathBuf,
+    fs: RootedFs,
+  }
+
+  fn setup() -> Setup {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("root");
+    let outside = dir.path().join("outside");
+    std::fs::create_dir_all(root.join("sub")).unwrap();
+    std::fs::create_dir(&outside).unwrap();
+    std::fs::write(root.join("sub/file"), "inside").unwrap();
+    std::fs::write(outside.join("secret"), "outside").unwrap();
+    let inner: FileSystemRc = MaybeArc::new(RealFs);
+    let fs = RootedFs::new(&root, inner).unwrap();
+    Setup {
+      _dir: dir,
+      root,
+      outside,
+      fs,
+    }
+  }
+
+  fn is_denied<T>(result: FsResult<T>) -> bool {
+    matches!(
+      result,
+      Err(FsError::Io(err)) if err.kind() == io::ErrorKind::PermissionDenied
+    )
+  }
+
+  #[test]
+  fn symlink_out_of_root() {
+    let Setup {
+      root, outside, fs, ..
+    } = setup();
+    std::os::unix::fs::symlink(&outside, root.join("absolute")).unwrap();
+    std::os::unix::fs::symlink("../outside", root.join("relative")).unwrap();
+    std::os::unix::fs::symlink("sub", root.join("inside")).unwrap();
+
+    for link in ["absolute", "relative"] {
+      assert!(is_denied(
+        fs.read_file_sync(&root.join(link).join("secret"))
+      ));
+      assert!(is_denied(fs.confine(&root.join(link), true)));
+      // The link itself is within the root.
+      assert_eq!(
+        fs.confine(&root.join(link), false).unwrap(),
+        fs.root().join(link)
+      );
+    }
+    assert!(is_denied(
+      fs.read_file_sync(&root.join("sub/../../outside/secret"))
+    ));
+    assert_eq!(
+      fs.read_file_sync(&root.join("inside/file")).unwrap(),
+      b"inside"
+    );
+  }
+
+  #[test]
+  fn symlink_loop() {
+    let Setup { root, fs, .. } = setup();
+    std::os::unix::fs::symlink("b", root.join("a")).unwrap();
+    std::os::unix::fs::symlink("a", root.join("b")).unwrap();
+
+    let err = fs.confine(&root.join("a"), true).unwrap_err();
+    assert_ne!(err.kind(), io::ErrorKind::PermissionDenied);
+    assert!(
+      format!("{err:?}").contains("Too many levels of symbolic links"),
+      "{err:?}"
+    );
+    assert!(fs.confine(&root.join("a"), false).is_ok());
+  }
+
+  #[test]
+  fn symlink_sync_escaping() {
+    let Setup {
+      root, outside, fs, ..
+    } = setup();
+
+    assert!(is_denied(fs.symlink_sync(
+      Path::new("../outside"),
+      &root.join("relative"),
+      None
+    )));
+    assert!(is_denied(fs.symlink_sync(
+      &outside.join("secret"),
+      &root.join("absolute"),
+      None
+    )));
+    assert!(is_denied(fs.symlink_sync(
+      Path::new("sub/file"),
+      &outside.join("link"),
+      None
+    )));
+    assert!(!root.join("relative").exists());
+    assert!(!root.join("absolute").exists());
+
+    fs.symlink_sync(Path::new("sub/file"), &root.join("link"), None)
+      .unwrap();
+    assert_eq!(fs.read_file_sync(&root.join("link")).unwrap(), b"inside");
+  }
+
+  #[test]
+  fn rename_and_link_across_root() {
+    let Setup {
+      root, outside, fs, ..
+    } = setup();
+    let inside = root.join("sub/file");
+    let secret = outside.join("secret");
+
+    assert!(is_denied(fs.rename_sync(&inside, &outside.join("moved"))));
+    assert!(is_denied(fs.rename_sync(&secret, &root.join("moved"))));
+    assert!(is_denied(fs.link_sync(&inside, &outside.join("linked"))));
+    assert!(is_denied(fs.link_sync(&secret, &root.join("linked"))));
+    assert!(inside.exists());
+    assert!(secret.exists());
+
+    fs.link_sync(&inside, &root.join("linked")).unwrap();
+    fs.rename_sync(&inside, &root.join("moved")).unwrap();
+    assert_eq!(fs.read_file_sync(&root.join("moved")).unwrap(), b"inside");
+    assert_eq!(fs.read_file_sync(&root.join("linked")).unwrap(), b"inside");
+  }
+}
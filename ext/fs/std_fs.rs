@@ -15,12 +15,14 @@ use deno_io::fs::FsResult;
 use deno_io::fs::FsStat;
 use deno_io::StdFileResourceInner;
 
+use crate::interface::CloneMode;
+use crate::interface::CopyFileOptions;
 use crate::interface::FsDirEntry;
 use crate::interface::FsFileType;
 use crate::FileSystem;
 use crate::OpenOptions;
 
-#[cfg(not(unix))]
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
 use deno_io::fs::FsError;
 
 #[derive(Debug, Clone)]
@@ -143,11 +145,21 @@ impl FileSystem for RealFs {
     spawn_blocking(move || remove(&path, recursive)).await?
   }
 
-  fn copy_file_sync(&self, from: &Path, to: &Path) -> FsResult<()> {
-    copy_file(from, to)
+  fn copy_file_sync(
+    &self,
+    from: &Path,
+    to: &Path,
+    options: CopyFileOptions,
+  ) -> FsResult<()> {
+    copy_file(from, to, options)
   }
-  async fn copy_file_async(&self, from: PathBuf, to: PathBuf) -> FsResult<()> {
-    spawn_blocking(move || copy_file(&from, &to)).await?
+  async fn copy_file_async(
+    &self,
+    from: PathBuf,
+    to: PathBuf,
+    options: CopyFileOptions,
+  ) -> FsResult<()> {
+    spawn_blocking(move || copy_file(&from, &to, options)).await?
   }
 
   fn stat_sync(&self, path: &Path) -> FsResult<FsStat> {
@@ -265,6 +277,56 @@ impl FileSystem for RealFs {
     .await?
   }
 
+  fn get_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+  ) -> FsResult<Option<Vec<u8>>> {
+    get_xattr(path, name)
+  }
+  async fn get_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<Option<Vec<u8>>> {
+    spawn_blocking(move || get_xattr(&path, &name)).await?
+  }
+
+  fn set_xattr_sync(
+    &self,
+    path: &Path,
+    name: &str,
+    value: &[u8],
+  ) -> FsResult<()> {
+    set_xattr(path, name, value)
+  }
+  async fn set_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+    value: Vec<u8>,
+  ) -> FsResult<()> {
+    spawn_blocking(move || set_xattr(&path, &name, &value)).await?
+  }
+
+  fn list_xattr_sync(&self, path: &Path) -> FsResult<Vec<String>> {
+    list_xattr(path)
+  }
+  async fn list_xattr_async(&self, path: PathBuf) -> FsResult<Vec<String>> {
+    spawn_blocking(move || list_xattr(&path)).await?
+  }
+
+  fn remove_xattr_sync(&self, path: &Path, name: &str) -> FsResult<()> {
+    remove_xattr(path, name)
+  }
+  async fn remove_xattr_async(
+    &self,
+    path: PathBuf,
+    name: String,
+  ) -> FsResult<()> {
+    spawn_blocking(move || remove_xattr(&path, &name)).await?
+  }
+
   fn write_file_sync(
     &self,
     path: &Path,
@@ -396,79 +458,235 @@ fn remove(path: &Path, recursive: bool) -> FsResult<()> {
   res.map_err(Into::into)
 }
 
-fn copy_file(from: &Path, to: &Path) -> FsResult<()> {
-  #[cfg(target_os = "macos")]
+// Smaller files are copied faster than they are cloned.
+const AUTO_CLONE_MIN_SIZE: u64 = 128 * 1024;
+
+fn copy_file(from: &Path, to: &Path, options: CopyFileOptions) -> FsResult<()> {
+  match options.clone {
+    CloneMode::Never => {}
+    CloneMode::Auto => {
+      if fs::metadata(from)?.len() > AUTO_CLONE_MIN_SIZE
+        && clone_file(from, to, options.exclusive).is_ok()
+      {
+        return Ok(());
+      }
+    }
+    CloneMode::Force => return clone_file(from, to, options.exclusive),
+  }
+
+  // std::fs::copy does open() + fcopyfile() on macOS, which is an overkill
+  // for the files that weren't cloned.
+  if options.exclusive || cfg!(target_os = "macos") {
+    copy_file_contents(from, to, options.exclusive)
+  } else {
+    fs::copy(from, to)?;
+    Ok(())
+  }
+}
+
+/// Copies the contents and permissions of `from` to a new or truncated file.
+fn copy_file_contents(from: &Path, to: &Path, exclusive: bool) -> FsResult<()> {
+  let mut from_file = fs::File::open(from)?;
+  let perm = from_file.metadata()?.permissions();
+  let mut to_file = open_copy_destination(to, exclusive, perm)?;
+  io::copy(&mut from_file, &mut to_file)?;
+  Ok(())
+}
+
+fn open_copy_destination(
+  to: &Path,
+  exclusive: bool,
+  perm: fs::Permissions,
+) -> FsResult<fs::File> {
+  let mut to_options = fs::OpenOptions::new();
+  to_options.write(true);
+  if exclusive {
+    to_options.create_new(true);
+  } else {
+    to_options.create(true).truncate(true);
+  }
+  #[cfg(unix)]
   {
-    use libc::clonefile;
-    use libc::stat;
-    use libc::unlink;
-    use std::ffi::CString;
-    use std::io::Read;
     use std::os::unix::fs::OpenOptionsExt;
     use std::os::unix::fs::PermissionsExt;
-    use std::os::unix::prelude::OsStrExt;
-
-    let from_str = CString::new(from.as_os_str().as_bytes()).unwrap();
-    let to_str = CString::new(to.as_os_str().as_bytes()).unwrap();
-
-    // SAFETY: `from` and `to` are valid C strings.
-    // std::fs::copy does open() + fcopyfile() on macOS. We try to use
-    // clonefile() instead, which is more efficient.
-    unsafe {
-      let mut st = std::mem::zeroed();
-      let ret = stat(from_str.as_ptr(), &mut st);
-      if ret != 0 {
-        return Err(io::Error::last_os_error().into());
-      }
+    // create the file with the correct mode right away
+    to_options.mode(perm.mode());
+  }
+  let to_file = to_options.open(to)?;
+  if to_file.metadata()?.is_file() {
+    // Set the correct file permissions, in case the file already existed.
+    // Don't set the permissions on already existing non-files like
+    // pipes/FIFOs or device nodes.
+    to_file.set_permissions(perm)?;
+  }
+  Ok(to_file)
+}
 
-      if st.st_size > 128 * 1024 {
-        // Try unlink. If it fails, we are going to try clonefile() anyway.
-        let _ = unlink(to_str.as_ptr());
-        // Matches rust stdlib behavior for io::copy.
-        // https://github.com/rust-lang/rust/blob/3fdd578d72a24d4efc2fe2ad18eec3b6ba72271e/library/std/src/sys/unix/fs.rs#L1613-L1616
-        if clonefile(from_str.as_ptr(), to_str.as_ptr(), 0) == 0 {
-          return Ok(());
-        }
-      } else {
-        // Do a regular copy. fcopyfile() is an overkill for < 128KB
-        // files.
-        let mut buf = [0u8; 128 * 1024];
-        let mut from_file = fs::File::open(from)?;
-        let perm = from_file.metadata()?.permissions();
-
-        let mut to_file = fs::OpenOptions::new()
-          // create the file with the correct mode right away
-          .mode(perm.mode())
-          .write(true)
-          .create(true)
-          .truncate(true)
-          .open(to)?;
-        let writer_metadata = to_file.metadata()?;
-        if writer_metadata.is_file() {
-          // Set the correct file permissions, in case the file already existed.
-          // Don't set the permissions on already existing non-files like
-          // pipes/FIFOs or device nodes.
-          to_file.set_permissions(perm)?;
-        }
-        loop {
-          let nread = from_file.read(&mut buf)?;
-          if nread == 0 {
-            break;
-          }
-          to_file.write_all(&buf[..nread])?;
-        }
-        return Ok(());
-      }
+#[cfg(target_os = "macos")]
+fn clone_file(from: &Path, to: &Path, exclusive: bool) -> FsResult<()> {
+  use libc::clonefile;
+  use libc::unlink;
+  use std::ffi::CString;
+  use std::os::unix::prelude::OsStrExt;
+
+  let from_str = CString::new(from.as_os_str().as_bytes()).unwrap();
+  let to_str = CString::new(to.as_os_str().as_bytes()).unwrap();
+
+  // SAFETY: `from` and `to` are valid C strings.
+  unsafe {
+    if !exclusive {
+      // clonefile() fails if the destination exists. Try unlink. If it
+      // fails, we are going to try clonefile() anyway.
+      let _ = unlink(to_str.as_ptr());
+    }
+    if clonefile(from_str.as_ptr(), to_str.as_ptr(), 0) != 0 {
+      return Err(io::Error::last_os_error().into());
+    }
+  }
+  Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn clone_file(from: &Path, to: &Path, exclusive: bool) -> FsResult<()> {
+  use std::os::unix::io::AsRawFd;
+
+  // FICLONE is `_IOW(0x94, 9, int)`.
+  nix::ioctl_write_int!(ficlone, 0x94, 9);
+
+  let from_file = fs::File::open(from)?;
+  let to_file =
+    open_copy_destination(to, exclusive, from_file.metadata()?.permissions())?;
+  // SAFETY: both file descriptors are open for the duration of the call.
+  let res = unsafe {
+    ficlone(to_file.as_raw_fd(), from_file.as_raw_fd() as libc::c_ulong)
+  };
+  if let Err(err) = res {
+    if exclusive {
+      let _ = fs::remove_file(to);
     }
+    return Err(io::Error::from(err).into());
+  }
+  Ok(())
+}
+
+/// Clones the file with block cloning, which is supported by ReFS.
+#[cfg(windows)]
+fn clone_file(from: &Path, to: &Path, exclusive: bool) -> FsResult<()> {
+  let from_file = fs::File::open(from)?;
+  let metadata = from_file.metadata()?;
+  let to_file = open_copy_destination(to, exclusive, metadata.permissions())?;
+  let res = duplicate_extents(&from_file, &to_file, metadata.len());
+  if res.is_err() && exclusive {
+    drop(to_file);
+    let _ = fs::remove_file(to);
+  }
+  res
+}
+
+#[cfg(windows)]
+fn duplicate_extents(
+  from_file: &fs::File,
+  to_file: &fs::File,
+  len: u64,
+) -> FsResult<()> {
+  use std::os::windows::io::AsRawHandle;
+  use winapi::shared::minwindef::DWORD;
+  use winapi::um::fileapi::GetDiskFreeSpaceW;
+  use winapi::um::fileapi::GetFinalPathNameByHandleW;
+  use winapi::um::fileapi::GetVolumePathNameW;
+  use winapi::um::ioapiset::DeviceIoControl;
+  use winapi::um::winnt::HANDLE;
+
+  // `CTL_CODE(FILE_DEVICE_FILE_SYSTEM, 209, METHOD_BUFFERED,
+  // FILE_WRITE_ACCESS)`, missing from winapi.
+  const FSCTL_DUPLICATE_EXTENTS_TO_FILE: DWORD = 0x0009_8344;
+
+  #[repr(C)]
+  #[allow(non_snake_case)]
+  struct DUPLICATE_EXTENTS_DATA {
+    FileHandle: HANDLE,
+    SourceFileOffset: i64,
+    TargetFileOffset: i64,
+    ByteCount: i64,
+  }
 
-    // clonefile() failed, fall back to std::fs::copy().
+  to_file.set_len(len)?;
+  if len == 0 {
+    return Ok(());
   }
 
-  fs::copy(from, to)?;
+  // Cloned regions must end at a cluster boundary, the last cluster of the
+  // source included.
+  // SAFETY: winapi calls with buffers of the given sizes.
+  let cluster_size = unsafe {
+    let mut path = vec![0u16; 32768];
+    let path_len = GetFinalPathNameByHandleW(
+      from_file.as_raw_handle() as HANDLE,
+      path.as_mut_ptr(),
+      path.len() as DWORD,
+      0,
+    );
+    if path_len == 0 || path_len as usize >= path.len() {
+      return Err(io::Error::last_os_error().into());
+    }
+    let mut volume = vec![0u16; 32768];
+    if GetVolumePathNameW(
+      path.as_ptr(),
+      volume.as_mut_ptr(),
+      volume.len() as DWORD,
+    ) == 0
+    {
+      return Err(io::Error::last_os_error().into());
+    }
+    let mut sectors_per_cluster = 0;
+    let mut bytes_per_sector = 0;
+    let mut free_clusters = 0;
+    let mut total_clusters = 0;
+    if GetDiskFreeSpaceW(
+      volume.as_ptr(),
+      &mut sectors_per_cluster,
+      &mut bytes_per_sector,
+      &mut free_clusters,
+      &mut total_clusters,
+    ) == 0
+    {
+      return Err(io::Error::last_os_error().into());
+    }
+    sectors_per_cluster as u64 * bytes_per_sector as u64
+  };
+  let byte_count = (len + cluster_size - 1) / cluster_size * cluster_size;
 
+  let mut data = DUPLICATE_EXTENTS_DATA {
+    FileHandle: from_file.as_raw_handle() as HANDLE,
+    SourceFileOffset: 0,
+    TargetFileOffset: 0,
+    ByteCount: byte_count as i64,
+  };
+  let mut bytes_returned = 0;
+  // SAFETY: `data` outlives the synchronous call and both handles are open.
+  let ok = unsafe {
+    DeviceIoControl(
+      to_file.as_raw_handle() as HANDLE,
+      FSCTL_DUPLICATE_EXTENTS_TO_FILE,
+      &mut data as *mut _ as *mut _,
+      std::mem::size_of::<DUPLICATE_EXTENTS_DATA>() as DWORD,
+      std::ptr::null_mut(),
+      0,
+      &mut bytes_returned,
+      std::ptr::null_mut(),
+    )
+  };
+  if ok == 0 {
+    return Err(io::Error::last_os_error().into());
+  }
   Ok(())
 }
 
+#[cfg(not(any(target_os = "macos", target_os = "linux", windows)))]
+fn clone_file(_from: &Path, _to: &Path, _exclusive: bool) -> FsResult<()> {
+  Err(FsError::NotSupported)
+}
+
 #[cfg(not(windows))]
 fn stat(path: &Path) -> FsResult<FsStat> {
   let metadata = fs::metadata(path)?;
@@ -660,6 +878,133 @@ fn truncate(path: &Path, len: u64) -> FsResult<()> {
   Ok(())
 }
 
+#[cfg(unix)]
+fn get_xattr(path: &Path, name: &str) -> FsResult<Option<Vec<u8>>> {
+  Ok(xattr::get(path, name)?)
+}
+
+#[cfg(unix)]
+fn set_xattr(path: &Path, name: &str, value: &[u8]) -> FsResult<()> {
+  Ok(xattr::set(path, name, value)?)
+}
+
+#[cfg(unix)]
+fn list_xattr(path: &Path) -> FsResult<Vec<String>> {
+  Ok(
+    xattr::list(path)?
+      .map(|name| name.to_string_lossy().into_owned())
+      .collect(),
+  )
+}
+
+#[cfg(unix)]
+fn remove_xattr(path: &Path, name: &str) -> FsResult<()> {
+  Ok(xattr::remove(path, name)?)
+}
+
+// On Windows extended attributes are stored as alternate data streams, which
+// are addressed as `path:name`.
+#[cfg(windows)]
+fn stream_path(path: &Path, name: &str) -> FsResult<PathBuf> {
+  if name.is_empty() || name.contains([':', '\\', '/']) {
+    return Err(FsError::Io(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      format!("Invalid extended attribute name \"{name}\""),
+    )));
+  }
+  let mut stream_path = path.as_os_str().to_owned();
+  stream_path.push(":");
+  stream_path.push(name);
+  Ok(PathBuf::from(stream_path))
+}
+
+#[cfg(windows)]
+fn get_xattr(path: &Path, name: &str) -> FsResult<Option<Vec<u8>>> {
+  // Tell a missing file apart from a missing stream.
+  fs::symlink_metadata(path)?;
+  match fs::read(stream_path(path, name)?) {
+    Ok(value) => Ok(Some(value)),
+    Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+    Err(err) => Err(err.into()),
+  }
+}
+
+#[cfg(windows)]
+fn set_xattr(path: &Path, name: &str, value: &[u8]) -> FsResult<()> {
+  fs::symlink_metadata(path)?;
+  fs::write(stream_path(path, name)?, value)?;
+  Ok(())
+}
+
+#[cfg(windows)]
+fn list_xattr(path: &Path) -> FsResult<Vec<String>> {
+  use std::os::windows::ffi::OsStrExt;
+  use std::os::windows::ffi::OsStringExt;
+  use winapi::shared::winerror::ERROR_HANDLE_EOF;
+  use winapi::um::fileapi::FindClose;
+  use winapi::um::fileapi::FindFirstStreamW;
+  use winapi::um::fileapi::FindNextStreamW;
+  use winapi::um::handleapi::INVALID_HANDLE_VALUE;
+  use winapi::um::minwinbase::FindStreamInfoStandard;
+  use winapi::um::minwinbase::WIN32_FIND_STREAM_DATA;
+
+  let wide_path: Vec<u16> = path
+    .as_os_str()
+    .encode_wide()
+    .chain(std::iter::once(0))
+    .collect();
+  let mut names = Vec::new();
+  // SAFETY: winapi calls, `data` is only read after a successful call.
+  unsafe {
+    let mut data: WIN32_FIND_STREAM_DATA = std::mem::zeroed();
+    let handle = FindFirstStreamW(
+      wide_path.as_ptr(),
+      FindStreamInfoStandard,
+      &mut data as *mut _ as *mut _,
+      0,
+    );
+    if handle == INVALID_HANDLE_VALUE {
+      let err = io::Error::last_os_error();
+      if err.raw_os_error() == Some(ERROR_HANDLE_EOF as i32) {
+        return Ok(names);
+      }
+      return Err(err.into());
+    }
+    loop {
+      let len = data
+        .cStreamName
+        .iter()
+        .position(|c| *c == 0)
+        .unwrap_or(data.cStreamName.len());
+      let stream_name = std::ffi::OsString::from_wide(&data.cStreamName[..len]);
+      // Streams are named `:name:$DATA`, the file contents being `::$DATA`.
+      let stream_name = stream_name.to_string_lossy();
+      if let Some(name) = stream_name
+        .strip_prefix(':')
+        .and_then(|name| name.strip_suffix(":$DATA"))
+        .filter(|name| !name.is_empty())
+      {
+        names.push(name.to_string());
+      }
+      if FindNextStreamW(handle, &mut data as *mut _ as *mut _) == 0 {
+        let err = io::Error::last_os_error();
+        FindClose(handle);
+        if err.raw_os_error() == Some(ERROR_HANDLE_EOF as i32) {
+          return Ok(names);
+        }
+        return Err(err.into());
+      }
+    }
+  }
+}
+
+#[cfg(windows)]
+fn remove_xattr(path: &Path, name: &str) -> FsResult<()> {
+  fs::symlink_metadata(path)?;
+  fs::remove_file(stream_path(path, name)?)?;
+  Ok(())
+}
+
 fn open_options(options: OpenOptions) -> fs::OpenOptions {
   let mut open_options = fs::OpenOptions::new();
   if let Some(mode) = options.mode {
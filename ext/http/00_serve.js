@@ -58,6 +58,7 @@ const {
   op_http_read_request_body,
   op_http_serve,
   op_http_serve_on,
+  op_http_serve_shutdown,
   op_http_set_promise_complete,
   op_http_set_response_body_bytes,
   op_http_set_response_body_resource,
@@ -538,6 +539,8 @@ function mapToCallback(context, callback, onError) {
     }
 
     const inner = toInnerResponse(response);
+    // Lets `addTrailers()` find the request that this response belongs to.
+    inner.slabId = req;
     if (innerRequest?.[_upgraded]) {
       // We're done here as the connection has been upgraded during the callback and no longer requires servicing.
       if (response !== UPGRADE_RESPONSE_SENTINEL) {
@@ -605,12 +608,41 @@ function serve(arg1, arg2) {
     options = {};
   }
 
-  const wantsHttps = options.cert || options.key;
+  const wantsHttps = options.cert || options.key || options.sni;
+  const wantsUnix = options.path !== undefined;
   const signal = options.signal;
+  const compression = compressionOptions(options.compression);
   const onError = options.onError ?? function (error) {
     console.error(error);
     return internalServerError();
   };
+
+  if (wantsUnix) {
+    if (wantsHttps) {
+      throw new TypeError("HTTPS is not supported on unix sockets.");
+    }
+    const listener = listen({
+      transport: "unix",
+      path: options.path,
+    });
+    const path = listener.addr.path;
+    const onListen = () => {
+      if (options.onListen) {
+        options.onListen({ path });
+      } else {
+        console.log(`Listening on ${path}`);
+      }
+    };
+    return serveHttpOnListener(
+      listener,
+      signal,
+      handler,
+      onError,
+      onListen,
+      compression,
+    );
+  }
+
   const listenOpts = {
     hostname: options.hostname ?? "0.0.0.0",
     port: options.port ?? (wantsHttps ? 9000 : 8000),
@@ -619,13 +651,14 @@ function serve(arg1, arg2) {
 
   let listener;
   if (wantsHttps) {
-    if (!options.cert || !options.key) {
+    if ((options.cert || options.key) && (!options.cert || !options.key)) {
       throw new TypeError(
         "Both cert and key must be provided to enable HTTPS.",
       );
     }
     listenOpts.cert = options.cert;
     listenOpts.key = options.key;
+    listenOpts.sni = options.sni;
     listenOpts.alpnProtocols = ["h2", "http/1.1"];
     listener = listenTls(listenOpts);
     listenOpts.port = listener.addr.port;
@@ -649,14 +682,49 @@ function serve(arg1, arg2) {
     }
   };
 
-  return serveHttpOnListener(listener, signal, handler, onError, onListen);
+  return serveHttpOnListener(
+    listener,
+    signal,
+    handler,
+    onError,
+    onListen,
+    compression,
+  );
+}
+
+/**
+ * Converts the `compression` option of `Deno.serve()` into the options the
+ * server ops take, with `undefined` for the defaults.
+ */
+function compressionOptions(compression) {
+  if (compression === undefined || compression === true) {
+    return undefined;
+  }
+  if (compression === false) {
+    return { encodings: [] };
+  }
+  return compression;
 }
 
 /**
  * Serve HTTP/1.1 and/or HTTP/2 on an arbitrary listener.
  */
-function serveHttpOnListener(listener, signal, handler, onError, onListen) {
-  const context = new CallbackContext(signal, op_http_serve(listener.rid));
+function serveHttpOnListener(
+  listener,
+  signal,
+  handler,
+  onError,
+  onListen,
+  compression,
+) {
+  let serverInfo;
+  try {
+    serverInfo = op_http_serve(listener.rid, compression);
+  } catch (error) {
+    listener.close();
+    throw error;
+  }
+  const context = new CallbackContext(signal, serverInfo);
   const callback = mapToCallback(context, handler, onError);
 
   onListen(context.scheme);
@@ -667,8 +735,18 @@ function serveHttpOnListener(listener, signal, handler, onError, onListen) {
 /**
  * Serve HTTP/1.1 and/or HTTP/2 on an arbitrary connection.
  */
-function serveHttpOnConnection(connection, signal, handler, onError, onListen) {
-  const context = new CallbackContext(signal, op_http_serve_on(connection.rid));
+function serveHttpOnConnection(
+  connection,
+  signal,
+  handler,
+  onError,
+  onListen,
+  compression,
+) {
+  const context = new CallbackContext(
+    signal,
+    op_http_serve_on(connection.rid, compression),
+  );
   const callback = mapToCallback(context, handler, onError);
 
   onListen(context.scheme);
@@ -726,6 +804,17 @@ function serveHttpOn(context, callback) {
 
   return {
     finished,
+    shutdown({ gracePeriodMs } = {}) {
+      try {
+        op_http_serve_shutdown(context.serverRid, gracePeriodMs);
+      } catch (error) {
+        // The server is already closed
+        if (!ObjectPrototypeIsPrototypeOf(BadResourcePrototype, error)) {
+          throw error;
+        }
+      }
+      return finished;
+    },
     then() {
       throw new Error(
         "Deno.serve no longer returns a promise. await server.finished instead of server.",
@@ -46,6 +46,7 @@ import {
   ReadableStreamPrototype,
 } from "ext:deno_web/06_streams.js";
 import { serve } from "ext:deno_http/00_serve.js";
+import { ServerSentEventStream } from "ext:deno_http/02_sse.js";
 const {
   ArrayPrototypeIncludes,
   ArrayPrototypeMap,
@@ -563,4 +564,11 @@ function buildCaseInsensitiveCommaValueFinder(checkText) {
 internals.buildCaseInsensitiveCommaValueFinder =
   buildCaseInsensitiveCommaValueFinder;
 
-export { _ws, HttpConn, serve, upgradeHttp, upgradeWebSocket };
+export {
+  _ws,
+  HttpConn,
+  serve,
+  ServerSentEventStream,
+  upgradeHttp,
+  upgradeWebSocket,
+};
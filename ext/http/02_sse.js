@@ -0,0 +1,83 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+// deno-lint-ignore-file camelcase
+const core = globalThis.Deno.core;
+const primordials = globalThis.__bootstrap.primordials;
+import { readableStreamForRid } from "ext:deno_web/06_streams.js";
+const {
+  PromisePrototypeThen,
+  String,
+  TypeError,
+} = primordials;
+
+const {
+  op_http_sse_create,
+  op_http_sse_send,
+  op_http_sse_wait_closed,
+} = core.ensureFastOps();
+
+const DEFAULT_KEEP_ALIVE = 15_000;
+
+class ServerSentEventStream {
+  #writerRid;
+  #readable;
+  #closed;
+
+  constructor(options = {}) {
+    const keepAlive = options.keepAlive ?? DEFAULT_KEEP_ALIVE;
+    if (
+      keepAlive !== false && (typeof keepAlive !== "number" || keepAlive < 0)
+    ) {
+      throw new TypeError("keepAlive must be a non-negative number or false");
+    }
+    const { 0: readableRid, 1: writerRid } = op_http_sse_create(
+      keepAlive === false || keepAlive === 0 ? null : keepAlive,
+    );
+    this.#readable = readableStreamForRid(readableRid);
+    this.#writerRid = writerRid;
+  }
+
+  get readable() {
+    return this.#readable;
+  }
+
+  /** Resolves once the client disconnected or the stream was closed. */
+  get closed() {
+    if (this.#closed === undefined) {
+      this.#closed = PromisePrototypeThen(
+        op_http_sse_wait_closed(this.#writerRid),
+        () => undefined,
+      );
+    }
+    return this.#closed;
+  }
+
+  send(event) {
+    if (typeof event === "string") {
+      event = { data: event };
+    }
+    return op_http_sse_send(this.#writerRid, {
+      comment: event.comment,
+      event: event.event,
+      data: event.data === undefined ? undefined : String(event.data),
+      id: event.id === undefined ? undefined : String(event.id),
+      retry: event.retry,
+    });
+  }
+
+  close() {
+    core.tryClose(this.#writerRid);
+  }
+
+  toResponse(init = {}) {
+    const response = new Response(this.#readable, init);
+    if (!response.headers.has("content-type")) {
+      response.headers.set("content-type", "text/event-stream");
+    }
+    if (!response.headers.has("cache-control")) {
+      response.headers.set("cache-control", "no-cache");
+    }
+    return response;
+  }
+}
+
+export { ServerSentEventStream };
@@ -2,17 +2,21 @@
 use crate::compressible::is_content_compressible;
 use crate::extract_network_stream;
 use crate::network_buffered_stream::NetworkStreamPrefixCheck;
+use crate::range::parse_byte_range;
+use crate::range::ByteRange;
 use crate::request_body::HttpRequestBody;
 use crate::request_properties::HttpConnectionProperties;
 use crate::request_properties::HttpListenProperties;
 use crate::request_properties::HttpPropertyExtractor;
 use crate::response_body::Compression;
+use crate::response_body::CompressionOptions;
 use crate::response_body::ResponseBytes;
 use crate::response_body::ResponseBytesInner;
 use crate::response_body::V8StreamHttpResponseBody;
 use crate::slab::slab_drop;
 use crate::slab::slab_get;
 use crate::slab::slab_insert;
+use crate::slab::SlabEntry;
 use crate::slab::SlabId;
 use crate::websocket_upgrade::WebSocketUpgrade;
 use crate::LocalExecutor;
@@ -35,17 +39,22 @@ use deno_core::OpState;
 use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
+use deno_io::fs::File;
+use deno_io::fs::FileResource;
 use deno_net::ops_tls::TlsStream;
 use deno_net::raw::NetworkStream;
 use deno_websocket::ws_create_server_stream;
-use fly_accept_encoding::Encoding;
 use http::header::ACCEPT_ENCODING;
+use http::header::ACCEPT_RANGES;
 use http::header::CACHE_CONTROL;
 use http::header::CONTENT_ENCODING;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_RANGE;
 use http::header::CONTENT_TYPE;
+use http::header::IF_RANGE;
+use http::header::RANGE;
 use http::HeaderMap;
+use http::Method;
 use hyper1::body::Incoming;
 use hyper1::header::COOKIE;
 use hyper1::http::HeaderName;
@@ -60,14 +69,18 @@ use pin_project::pin_project;
 use pin_project::pinned_drop;
 use smallvec::SmallVec;
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::cell::RefCell;
 use std::future::Future;
 use std::io;
+use std::io::SeekFrom;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::time::Duration;
 
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::Notify;
 
 type Request = hyper1::Request<Incoming>;
 type Response = hyper1::Response<ResponseBytes>;
@@ -212,10 +225,52 @@ pub async fn op_http_upgrade_websocket_next(
 pub fn op_http_set_promise_complete(slab_id: SlabId, status: u16) {
   let mut http = slab_get(slab_id);
   // The Javascript code will never provide a status that is invalid here (see 23_response.js)
-  *http.response().status_mut() = StatusCode::from_u16(status).unwrap();
+  let mut status = StatusCode::from_u16(status).unwrap();
+  if status == StatusCode::OK {
+    status = apply_range_request(&mut http).unwrap_or(status);
+  }
+  *http.response().status_mut() = status;
   http.complete();
 }
 
+/// Narrows a file response to the byte range requested by the `Range` header,
+/// returning the status to send instead of 200.
+fn apply_range_request(http: &mut SlabEntry) -> Option<StatusCode> {
+  let request = http.request_parts();
+  // Ignoring `If-Range` and sending the whole file is always correct.
+  if request.method != Method::GET || request.headers.contains_key(IF_RANGE) {
+    return None;
+  }
+  let range = request.headers.get(RANGE)?.to_str().ok()?.to_owned();
+  let response = http.response();
+  if response.headers().contains_key(CONTENT_ENCODING)
+    || response.headers().contains_key(CONTENT_RANGE)
+  {
+    return None;
+  }
+  let size = response.body().file_len()?;
+  let (status, content_range, offset, len) =
+    match parse_byte_range(&range, size)? {
+      ByteRange::Satisfiable(first, last) => (
+        StatusCode::PARTIAL_CONTENT,
+        format!("bytes {first}-{last}/{size}"),
+        first,
+        last - first + 1,
+      ),
+      ByteRange::Unsatisfiable => (
+        StatusCode::RANGE_NOT_SATISFIABLE,
+        format!("bytes */{size}"),
+        0,
+        0,
+      ),
+    };
+  response.body_mut().set_file_range(offset, len).ok()?;
+  let headers = response.headers_mut();
+  headers.insert(CONTENT_RANGE, HeaderValue::try_from(content_range).unwrap());
+  headers.insert(CONTENT_LENGTH, HeaderValue::from(len));
+  Some(status)
+}
+
 #[op(v8)]
 pub fn op_http_get_request_method_and_url<'scope, HTTP>(
   scope: &mut v8::HandleScope<'scope>,
@@ -439,42 +494,69 @@ pub fn op_http_set_response_trailers(
   *http.trailers().borrow_mut() = Some(trailer_map);
 }
 
-fn is_request_compressible(headers: &HeaderMap) -> Compression {
+/// Picks the compression for a response from the request's
+/// `Accept-Encoding`: the encoding the client weights highest, with ties broken
+/// by the order of `options.encodings`.
+fn is_request_compressible(
+  headers: &HeaderMap,
+  options: &CompressionOptions,
+) -> Compression {
   let Some(accept_encoding) = headers.get(ACCEPT_ENCODING) else {
     return Compression::None;
   };
+  let Ok(accept_encoding) = accept_encoding.to_str() else {
+    return Compression::None;
+  };
 
-  match accept_encoding.to_str().unwrap() {
-    // Firefox and Chrome send this -- no need to parse
-    "gzip, deflate, br" => return Compression::Brotli,
-    "gzip" => return Compression::GZip,
-    "br" => return Compression::Brotli,
-    _ => (),
+  let mut best = (Compression::None, 0.0);
+  for encoding in &options.encodings {
+    let weight = accept_encoding_weight(accept_encoding, encoding.name());
+    if weight > best.1 {
+      best = (options.compression(*encoding), weight);
+    }
   }
-
-  // Fall back to the expensive parser
-  let accepted = fly_accept_encoding::encodings_iter(headers).filter(|r| {
-    matches!(
-      r,
-      Ok((
-        Some(Encoding::Identity | Encoding::Gzip | Encoding::Brotli),
-        _
-      ))
-    )
-  });
-  match fly_accept_encoding::preferred(accepted) {
-    Ok(Some(fly_accept_encoding::Encoding::Gzip)) => Compression::GZip,
-    Ok(Some(fly_accept_encoding::Encoding::Brotli)) => Compression::Brotli,
-    _ => Compression::None,
+  // A client may prefer no compression at all.
+  if best.1 < accept_encoding_weight(accept_encoding, "identity") {
+    return Compression::None;
   }
+  best.0
 }
 
-fn is_response_compressible(headers: &HeaderMap) -> bool {
-  if let Some(content_type) = headers.get(CONTENT_TYPE) {
-    if !is_content_compressible(content_type) {
-      return false;
+/// The weight (`q` value) `Accept-Encoding` gives the encoding `name`, 0 if
+/// it isn't accepted.
+fn accept_encoding_weight(accept_encoding: &str, name: &str) -> f32 {
+  let mut wildcard = None;
+  for item in accept_encoding.split(',') {
+    let mut params = item.split(';');
+    let coding = params.next().unwrap_or_default().trim();
+    let weight = params
+      .find_map(|param| param.trim().strip_prefix("q="))
+      .map(|q| q.trim().parse::<f32>().unwrap_or(0.0))
+      .unwrap_or(1.0);
+    if coding.eq_ignore_ascii_case(name) {
+      return weight;
     }
-  } else {
+    if coding == "*" {
+      wildcard = Some(weight);
+    }
+  }
+  // Identity is always acceptable, but only preferred over the other codings
+  // when the client weights it explicitly.
+  wildcard.unwrap_or(0.0)
+}
+
+fn is_response_compressible(
+  headers: &HeaderMap,
+  options: &CompressionOptions,
+) -> bool {
+  let Some(content_type) = headers.get(CONTENT_TYPE) else {
+    return false;
+  };
+  let compressible = match &options.content_types {
+    Some(content_types) => is_content_type_listed(content_type, content_types),
+    None => is_content_compressible(content_type),
+  };
+  if !compressible {
     return false;
   }
   if headers.contains_key(CONTENT_ENCODING) {
@@ -495,30 +577,45 @@ fn is_response_compressible(headers: &HeaderMap) -> bool {
   true
 }
 
+/// Whether the media type of `content_type` is one of `content_types`, which
+/// may contain wildcards like `text/*`.
+fn is_content_type_listed(
+  content_type: &HeaderValue,
+  content_types: &[String],
+) -> bool {
+  let Ok(content_type) = content_type.to_str() else {
+    return false;
+  };
+  let essence = content_type.split(';').next().unwrap_or_default().trim();
+  content_types
+    .iter()
+    .any(|listed| match listed.strip_suffix("/*") {
+      Some(kind) => essence
+        .split_once('/')
+        .map(|(essence_kind, _)| essence_kind.eq_ignore_ascii_case(kind))
+        .unwrap_or(false),
+      None => essence.eq_ignore_ascii_case(listed),
+    })
+}
+
 fn modify_compressibility_from_response(
   compression: Compression,
   length: Option<usize>,
   headers: &mut HeaderMap,
+  options: &CompressionOptions,
 ) -> Compression {
   ensure_vary_accept_encoding(headers);
   if let Some(length) = length {
-    // By the time we add compression headers and Accept-Encoding, it probably doesn't make sense
-    // to compress stuff that's smaller than this.
-    if length < 64 {
+    if length < options.min_size {
       return Compression::None;
     }
   }
-  if compression == Compression::None {
+  let Some(encoding) = compression.content_encoding() else {
     return Compression::None;
-  }
-  if !is_response_compressible(headers) {
+  };
+  if !is_response_compressible(headers, options) {
     return Compression::None;
   }
-  let encoding = match compression {
-    Compression::Brotli => "br",
-    Compression::GZip => "gzip",
-    _ => unreachable!(),
-  };
   weaken_etag(headers);
   headers.remove(CONTENT_LENGTH);
   headers.insert(CONTENT_ENCODING, HeaderValue::from_static(encoding));
@@ -563,16 +660,81 @@ fn set_response(
   response_fn: impl FnOnce(Compression) -> ResponseBytesInner,
 ) {
   let mut http = slab_get(slab_id);
-  let compression = is_request_compressible(&http.request_parts().headers);
+  let options = http.compression_options();
+  let compression =
+    is_request_compressible(&http.request_parts().headers, &options);
   let response = http.response();
   let compression = modify_compressibility_from_response(
     compression,
     length,
     response.headers_mut(),
+    &options,
   );
   response.body_mut().initialize(response_fn(compression))
 }
 
+/// Returns the file backing `resource`, its current position and its size,
+/// if it's a regular file.
+fn regular_file(
+  resource: &Rc<dyn Resource>,
+) -> Option<(Rc<dyn File>, u64, u64)> {
+  let file = resource.downcast_rc::<FileResource>()?.file();
+  let stat = file.clone().stat_sync().ok()?;
+  if !stat.is_file {
+    return None;
+  }
+  let position = file.clone().seek_sync(SeekFrom::Current(0)).ok()?;
+  Some((file, position, stat.size))
+}
+
+/// Like [`set_response`], for a body read straight from a regular file. The
+/// length of the body is known up front, and if it's the whole file, range
+/// requests can be served from it (see [`apply_range_request`]).
+fn set_file_response(
+  slab_id: SlabId,
+  resource: Rc<dyn Resource>,
+  file: Rc<dyn File>,
+  position: u64,
+  size: u64,
+  auto_close: bool,
+) {
+  let len = size.saturating_sub(position);
+  let full_len = (position == 0).then_some(size);
+  let mut http = slab_get(slab_id);
+  let options = http.compression_options();
+  let request_headers = &http.request_parts().headers;
+  // Ranges are sent from the uncompressed file.
+  let compression = if full_len.is_some() && request_headers.contains_key(RANGE)
+  {
+    Compression::None
+  } else {
+    is_request_compressible(request_headers, &options)
+  };
+  let response = http.response();
+  let compression = modify_compressibility_from_response(
+    compression,
+    Some(len as usize),
+    response.headers_mut(),
+    &options,
+  );
+  if full_len.is_some() && compression == Compression::None {
+    response
+      .headers_mut()
+      .entry(ACCEPT_RANGES)
+      .or_insert(HeaderValue::from_static("bytes"));
+  }
+  response
+    .body_mut()
+    .initialize(ResponseBytesInner::from_file(
+      compression,
+      resource,
+      file,
+      len,
+      full_len,
+      auto_close,
+    ))
+}
+
 #[op(fast)]
 pub fn op_http_set_response_body_resource(
   state: &mut OpState,
@@ -587,6 +749,11 @@ pub fn op_http_set_response_body_resource(
     state.resource_table.get_any(stream_rid)?
   };
 
+  if let Some((file, position, size)) = regular_file(&resource) {
+    set_file_response(slab_id, resource, file, position, size, auto_close);
+    return Ok(());
+  }
+
   set_response(
     slab_id,
     resource.size_hint().1.map(|s| s as usize),
@@ -659,9 +826,10 @@ pub struct SlabFuture<F: Future<Output = ()>>(SlabId, #[pin] F);
 pub fn new_slab_future(
   request: Request,
   request_info: HttpConnectionProperties,
+  compression_options: Rc<CompressionOptions>,
   tx: tokio::sync::mpsc::Sender<SlabId>,
 ) -> SlabFuture<impl Future<Output = ()>> {
-  let index = slab_insert(request, request_info);
+  let index = slab_insert(request, request_info, compression_options);
   let rx = slab_get(index).promise();
   SlabFuture(index, async move {
     if tx.send(index).await.is_ok() {
@@ -696,47 +864,108 @@ impl<F: Future<Output = ()>> Future for SlabFuture<F> {
   }
 }
 
+/// Signals the connections of a server to shut down gracefully: HTTP/1.1
+/// connections close after their in-flight request, and HTTP/2 connections
+/// send a GOAWAY frame and close after their in-flight streams.
+#[derive(Default)]
+struct ShutdownSignal {
+  requested: Cell<bool>,
+  notify: Notify,
+}
+
+impl ShutdownSignal {
+  fn request(&self) {
+    self.requested.set(true);
+    self.notify.notify_waiters();
+  }
+
+  async fn requested(&self) {
+    loop {
+      // Register for notifications before checking, to not miss a request.
+      let notified = self.notify.notified();
+      if self.requested.get() {
+        return;
+      }
+      notified.await;
+    }
+  }
+}
+
+/// Drives `conn` to completion, starting a graceful shutdown of it with
+/// `graceful_shutdown` when `shutdown` is requested.
+async fn serve_until_shutdown<C>(
+  conn: C,
+  shutdown: Rc<ShutdownSignal>,
+  graceful_shutdown: impl FnOnce(Pin<&mut C>),
+) -> Result<(), AnyError>
+where
+  C: Future<Output = Result<(), hyper1::Error>>,
+{
+  tokio::pin!(conn);
+  tokio::select! {
+    res = conn.as_mut() => return res.map_err(AnyError::from),
+    _ = shutdown.requested() => graceful_shutdown(conn.as_mut()),
+  }
+  conn.await.map_err(AnyError::from)
+}
+
 fn serve_http11_unconditional(
   io: impl HttpServeStream,
   svc: impl HttpService<Incoming, ResBody = ResponseBytes> + 'static,
+  shutdown: Rc<ShutdownSignal>,
 ) -> impl Future<Output = Result<(), AnyError>> + 'static {
   let conn = http1::Builder::new()
     .keep_alive(true)
     .writev(*USE_WRITEV)
     .serve_connection(io, svc);
 
-  conn.with_upgrades().map_err(AnyError::from)
+  serve_until_shutdown(conn.with_upgrades(), shutdown, |conn| {
+    conn.graceful_shutdown()
+  })
 }
 
 fn serve_http2_unconditional(
   io: impl HttpServeStream,
   svc: impl HttpService<Incoming, ResBody = ResponseBytes> + 'static,
+  shutdown: Rc<ShutdownSignal>,
 ) -> impl Future<Output = Result<(), AnyError>> + 'static {
   let conn = http2::Builder::new(LocalExecutor).serve_connection(io, svc);
-  conn.map_err(AnyError::from)
+  serve_until_shutdown(conn, shutdown, |conn| conn.graceful_shutdown())
 }
 
 async fn serve_http2_autodetect(
   io: impl HttpServeStream,
   svc: impl HttpService<Incoming, ResBody = ResponseBytes> + 'static,
+  shutdown: Rc<ShutdownSignal>,
 ) -> Result<(), AnyError> {
   let prefix = NetworkStreamPrefixCheck::new(io, HTTP2_PREFIX);
-  let (matches, io) = prefix.match_prefix().await?;
+  let (matches, io) = tokio::select! {
+    res = prefix.match_prefix() => res?,
+    // Connections that haven't sent a request yet are closed right away.
+    _ = shutdown.requested() => return Ok(()),
+  };
   if matches {
-    serve_http2_unconditional(io, svc).await
+    serve_http2_unconditional(io, svc, shutdown).await
   } else {
-    serve_http11_unconditional(io, svc).await
+    serve_http11_unconditional(io, svc, shutdown).await
   }
 }
 
 fn serve_https(
   mut io: TlsStream,
   request_info: HttpConnectionProperties,
+  compression_options: Rc<CompressionOptions>,
   cancel: Rc<CancelHandle>,
+  shutdown: Rc<ShutdownSignal>,
   tx: tokio::sync::mpsc::Sender<SlabId>,
 ) -> JoinHandle<Result<(), AnyError>> {
   let svc = service_fn(move |req: Request| {
-    new_slab_future(req, request_info.clone(), tx.clone())
+    new_slab_future(
+      req,
+      request_info.clone(),
+      compression_options.clone(),
+      tx.clone(),
+    )
   });
   spawn(
     async {
@@ -745,11 +974,11 @@ fn serve_https(
       // based on the prefix bytes
       let handshake = io.get_ref().1.alpn_protocol();
       if handshake == Some(TLS_ALPN_HTTP_2) {
-        serve_http2_unconditional(io, svc).await
+        serve_http2_unconditional(io, svc, shutdown).await
       } else if handshake == Some(TLS_ALPN_HTTP_11) {
-        serve_http11_unconditional(io, svc).await
+        serve_http11_unconditional(io, svc, shutdown).await
       } else {
-        serve_http2_autodetect(io, svc).await
+        serve_http2_autodetect(io, svc, shutdown).await
       }
     }
     .try_or_cancel(cancel),
@@ -759,19 +988,28 @@ fn serve_https(
 fn serve_http(
   io: impl HttpServeStream,
   request_info: HttpConnectionProperties,
+  compression_options: Rc<CompressionOptions>,
   cancel: Rc<CancelHandle>,
+  shutdown: Rc<ShutdownSignal>,
   tx: tokio::sync::mpsc::Sender<SlabId>,
 ) -> JoinHandle<Result<(), AnyError>> {
   let svc = service_fn(move |req: Request| {
-    new_slab_future(req, request_info.clone(), tx.clone())
+    new_slab_future(
+      req,
+      request_info.clone(),
+      compression_options.clone(),
+      tx.clone(),
+    )
   });
-  spawn(serve_http2_autodetect(io, svc).try_or_cancel(cancel))
+  spawn(serve_http2_autodetect(io, svc, shutdown).try_or_cancel(cancel))
 }
 
 fn serve_http_on<HTTP>(
   connection: HTTP::Connection,
   listen_properties: &HttpListenProperties,
+  compression_options: Rc<CompressionOptions>,
   cancel: Rc<CancelHandle>,
+  shutdown: Rc<ShutdownSignal>,
   tx: tokio::sync::mpsc::Sender<SlabId>,
 ) -> JoinHandle<Result<(), AnyError>>
 where
@@ -783,16 +1021,31 @@ where
   let network_stream = HTTP::to_network_stream_from_connection(connection);
 
   match network_stream {
-    NetworkStream::Tcp(conn) => {
-      serve_http(conn, connection_properties, cancel, tx)
-    }
-    NetworkStream::Tls(conn) => {
-      serve_https(conn, connection_properties, cancel, tx)
-    }
+    NetworkStream::Tcp(conn) => serve_http(
+      conn,
+      connection_properties,
+      compression_options,
+      cancel,
+      shutdown,
+      tx,
+    ),
+    NetworkStream::Tls(conn) => serve_https(
+      conn,
+      connection_properties,
+      compression_options,
+      cancel,
+      shutdown,
+      tx,
+    ),
     #[cfg(unix)]
-    NetworkStream::Unix(conn) => {
-      serve_http(conn, connection_properties, cancel, tx)
-    }
+    NetworkStream::Unix(conn) => serve_http(
+      conn,
+      connection_properties,
+      compression_options,
+      cancel,
+      shutdown,
+      tx,
+    ),
   }
 }
 
@@ -801,12 +1054,26 @@ struct HttpJoinHandle(
   // Cancel handle must live in a separate Rc to avoid keeping the outer join handle ref'd
   Rc<CancelHandle>,
   AsyncRefCell<tokio::sync::mpsc::Receiver<SlabId>>,
+  Rc<ShutdownSignal>,
 );
 
 impl HttpJoinHandle {
+  fn new(rx: tokio::sync::mpsc::Receiver<SlabId>) -> Self {
+    Self(
+      AsyncRefCell::new(None),
+      CancelHandle::new_rc(),
+      AsyncRefCell::new(rx),
+      Default::default(),
+    )
+  }
+
   fn cancel_handle(self: &Rc<Self>) -> Rc<CancelHandle> {
     self.1.clone()
   }
+
+  fn shutdown_signal(self: &Rc<Self>) -> Rc<ShutdownSignal> {
+    self.3.clone()
+  }
 }
 
 impl Resource for HttpJoinHandle {
@@ -830,37 +1097,42 @@ impl Drop for HttpJoinHandle {
 pub fn op_http_serve<HTTP>(
   state: Rc<RefCell<OpState>>,
   listener_rid: ResourceId,
+  compression_options: Option<CompressionOptions>,
 ) -> Result<(ResourceId, &'static str, String), AnyError>
 where
   HTTP: HttpPropertyExtractor,
 {
+  let compression_options = Rc::new(compression_options.unwrap_or_default());
+  compression_options.validate()?;
   let listener =
     HTTP::get_listener_for_rid(&mut state.borrow_mut(), listener_rid)?;
 
   let listen_properties = HTTP::listen_properties_from_listener(&listener)?;
 
   let (tx, rx) = tokio::sync::mpsc::channel(10);
-  let resource: Rc<HttpJoinHandle> = Rc::new(HttpJoinHandle(
-    AsyncRefCell::new(None),
-    CancelHandle::new_rc(),
-    AsyncRefCell::new(rx),
-  ));
+  let resource: Rc<HttpJoinHandle> = Rc::new(HttpJoinHandle::new(rx));
   let cancel_clone = resource.cancel_handle();
+  let shutdown_clone = resource.shutdown_signal();
 
   let listen_properties_clone: HttpListenProperties = listen_properties.clone();
   let handle = spawn(async move {
     loop {
-      let conn = HTTP::accept_connection_from_listener(&listener)
-        .try_or_cancel(cancel_clone.clone())
-        .await?;
+      let accept = HTTP::accept_connection_from_listener(&listener)
+        .try_or_cancel(cancel_clone.clone());
+      let conn = tokio::select! {
+        conn = accept => conn?,
+        // Stop accepting, the listener is closed when it's dropped.
+        _ = shutdown_clone.requested() => break,
+      };
       serve_http_on::<HTTP>(
         conn,
         &listen_properties_clone,
+        compression_options.clone(),
         cancel_clone.clone(),
+        shutdown_clone.clone(),
         tx.clone(),
       );
     }
-    #[allow(unreachable_code)]
     Ok::<_, AnyError>(())
   });
 
@@ -880,27 +1152,28 @@ where
 pub fn op_http_serve_on<HTTP>(
   state: Rc<RefCell<OpState>>,
   connection_rid: ResourceId,
+  compression_options: Option<CompressionOptions>,
 ) -> Result<(ResourceId, &'static str, String), AnyError>
 where
   HTTP: HttpPropertyExtractor,
 {
+  let compression_options = Rc::new(compression_options.unwrap_or_default());
+  compression_options.validate()?;
   let connection =
     HTTP::get_connection_for_rid(&mut state.borrow_mut(), connection_rid)?;
 
   let listen_properties = HTTP::listen_properties_from_connection(&connection)?;
 
   let (tx, rx) = tokio::sync::mpsc::channel(10);
-  let resource: Rc<HttpJoinHandle> = Rc::new(HttpJoinHandle(
-    AsyncRefCell::new(None),
-    CancelHandle::new_rc(),
-    AsyncRefCell::new(rx),
-  ));
+  let resource: Rc<HttpJoinHandle> = Rc::new(HttpJoinHandle::new(rx));
 
   let handle: JoinHandle<Result<(), deno_core::anyhow::Error>> =
     serve_http_on::<HTTP>(
       connection,
       &listen_properties,
+      compression_options,
       resource.cancel_handle(),
+      resource.shutdown_signal(),
       tx,
     );
 
@@ -916,6 +1189,29 @@ where
   ))
 }
 
+/// Starts a graceful shutdown of a server: it stops accepting connections
+/// and closes its connections once their in-flight requests are done. After
+/// `grace_period_ms`, the remaining connections are aborted. `op_http_wait`
+/// returns once all connections are closed.
+#[op]
+pub fn op_http_serve_shutdown(
+  state: &mut OpState,
+  rid: ResourceId,
+  grace_period_ms: Option<u64>,
+) -> Result<(), AnyError> {
+  let join_handle = state.resource_table.get::<HttpJoinHandle>(rid)?;
+  join_handle.shutdown_signal().request();
+
+  if let Some(grace_period_ms) = grace_period_ms {
+    let cancel = join_handle.cancel_handle();
+    spawn(async move {
+      tokio::time::sleep(Duration::from_millis(grace_period_ms)).await;
+      cancel.cancel();
+    });
+  }
+  Ok(())
+}
+
 /// Synchronous, non-blocking call to see if there are any further HTTP requests. If anything
 /// goes wrong in this method we return [`SlabId::MAX`] and let the async handler pick up the real error.
 #[op(fast)]
@@ -77,11 +77,13 @@ use crate::reader_stream::ShutdownHandle;
 pub mod compressible;
 mod http_next;
 mod network_buffered_stream;
+mod range;
 mod reader_stream;
 mod request_body;
 mod request_properties;
 mod response_body;
 mod slab;
+mod sse;
 mod websocket_upgrade;
 
 pub use request_properties::DefaultHttpPropertyExtractor;
@@ -109,6 +111,7 @@ deno_core::extension!(
     http_next::op_http_read_request_body,
     http_next::op_http_serve_on<HTTP>,
     http_next::op_http_serve<HTTP>,
+    http_next::op_http_serve_shutdown,
     http_next::op_http_set_promise_complete,
     http_next::op_http_set_response_body_bytes,
     http_next::op_http_set_response_body_resource,
@@ -122,8 +125,11 @@ deno_core::extension!(
     http_next::op_http_upgrade_raw,
     http_next::op_http_try_wait,
     http_next::op_http_wait,
+    sse::op_http_sse_create,
+    sse::op_http_sse_send,
+    sse::op_http_sse_wait_closed,
   ],
-  esm = ["00_serve.js", "01_http.js"],
+  esm = ["00_serve.js", "01_http.js", "02_sse.js"],
 );
 
 pub enum HttpSocketAddr {
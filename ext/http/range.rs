@@ -0,0 +1,80 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+/// The result of matching a `Range` header against a body.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ByteRange {
+  /// The inclusive range of bytes to send.
+  Satisfiable(u64, u64),
+  /// None of the requested bytes exist, to be answered with a 416.
+  Unsatisfiable,
+}
+
+/// Parses a `Range` header with a single byte range (RFC 9110 14.2) against
+/// a body of `size` bytes. Returns `None` if the header should be ignored and
+/// the whole body sent, which we do for invalid headers and for multiple
+/// ranges, as those would need a `multipart/byteranges` body.
+pub fn parse_byte_range(value: &str, size: u64) -> Option<ByteRange> {
+  let spec = value.trim().strip_prefix("bytes=")?.trim();
+  if spec.contains(',') {
+    return None;
+  }
+  let (first, last) = spec.split_once('-')?;
+  let (first, last) = (first.trim(), last.trim());
+
+  if first.is_empty() {
+    // A suffix range: the last `len` bytes.
+    let len = last.parse::<u64>().ok()?;
+    if len == 0 || size == 0 {
+      return Some(ByteRange::Unsatisfiable);
+    }
+    return Some(ByteRange::Satisfiable(size - len.min(size), size - 1));
+  }
+
+  let first = first.parse::<u64>().ok()?;
+  let last = if last.is_empty() {
+    u64::MAX
+  } else {
+    last.parse::<u64>().ok()?
+  };
+  if last < first {
+    return None;
+  }
+  if first >= size {
+    return Some(ByteRange::Unsatisfiable);
+  }
+  Some(ByteRange::Satisfiable(first, last.min(size - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_byte_range() {
+    use ByteRange::*;
+    assert_eq!(parse_byte_range("bytes=0-9", 100), Some(Satisfiable(0, 9)));
+    assert_eq!(
+      parse_byte_range("bytes=90-", 100),
+      Some(Satisfiable(90, 99))
+    );
+    assert_eq!(
+      parse_byte_range("bytes=90-200", 100),
+      Some(Satisfiable(90, 99))
+    );
+    assert_eq!(
+      parse_byte_range("bytes=-10", 100),
+      Some(Satisfiable(90, 99))
+    );
+    assert_eq!(
+      parse_byte_range("bytes=-200", 100),
+      Some(Satisfiable(0, 99))
+    );
+    assert_eq!(parse_byte_range("bytes=100-", 100), Some(Unsatisfiable));
+    assert_eq!(parse_byte_range("bytes=-0", 100), Some(Unsatisfiable));
+    assert_eq!(parse_byte_range("bytes=0-", 0), Some(Unsatisfiable));
+    assert_eq!(parse_byte_range("bytes=9-0", 100), None);
+    assert_eq!(parse_byte_range("bytes=0-1,5-6", 100), None);
+    assert_eq!(parse_byte_range("items=0-9", 100), None);
+    assert_eq!(parse_byte_range("bytes=a-b", 100), None);
+  }
+}
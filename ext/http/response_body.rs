@@ -2,6 +2,8 @@
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::future::Future;
+use std::io;
+use std::io::SeekFrom;
 use std::io::Write;
 use std::pin::Pin;
 use std::rc::Rc;
@@ -11,7 +13,9 @@ use brotli::ffi::compressor::BrotliEncoderState;
 use bytes::Bytes;
 use bytes::BytesMut;
 use deno_core::error::bad_resource;
+use deno_core::error::range_error;
 use deno_core::error::AnyError;
+use deno_core::futures::future::LocalBoxFuture;
 use deno_core::futures::ready;
 use deno_core::futures::FutureExt;
 use deno_core::AsyncRefCell;
@@ -22,12 +26,18 @@ use deno_core::CancelTryFuture;
 use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::WriteOutcome;
+use deno_io::fs::File;
+use deno_io::fs::FsResult;
 use flate2::write::GzEncoder;
 use http::HeaderMap;
 use hyper1::body::Body;
 use hyper1::body::Frame;
 use hyper1::body::SizeHint;
 use pin_project::pin_project;
+use serde::Deserialize;
+use zstd::stream::raw::InBuffer;
+use zstd::stream::raw::Operation;
+use zstd::stream::raw::OutBuffer;
 
 /// Simplification for nested types we use for our streams. We provide a way to convert from
 /// this type into Hyper's body [`Frame`].
@@ -115,11 +125,116 @@ trait PollFrame: Unpin {
   fn size_hint(&self) -> SizeHint;
 }
 
-#[derive(PartialEq, Eq)]
+/// How a response body is compressed, with the level to compress it at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Compression {
   None,
+  GZip(u32),
+  Brotli(u32),
+  Zstd(i32),
+}
+
+impl Compression {
+  /// The `Content-Encoding` of a body compressed this way.
+  pub fn content_encoding(self) -> Option<&'static str> {
+    match self {
+      Compression::None => None,
+      Compression::GZip(..) => Some("gzip"),
+      Compression::Brotli(..) => Some("br"),
+      Compression::Zstd(..) => Some("zstd"),
+    }
+  }
+}
+
+/// The encodings a server can compress its responses with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ContentEncoding {
+  #[serde(rename = "gzip")]
   GZip,
+  #[serde(rename = "br")]
   Brotli,
+  #[serde(rename = "zstd")]
+  Zstd,
+}
+
+impl ContentEncoding {
+  pub fn name(self) -> &'static str {
+    match self {
+      ContentEncoding::GZip => "gzip",
+      ContentEncoding::Brotli => "br",
+      ContentEncoding::Zstd => "zstd",
+    }
+  }
+}
+
+/// How a server compresses its responses, from the `compression` option of
+/// `Deno.serve()`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompressionOptions {
+  /// The encodings to use, in order of preference between encodings the
+  /// client accepts equally. Empty to never compress.
+  pub encodings: Vec<ContentEncoding>,
+  /// Bodies known to be smaller than this are sent uncompressed.
+  pub min_size: usize,
+  /// The media types to compress, instead of the built-in list of
+  /// compressible types.
+  pub content_types: Option<Vec<String>>,
+  pub gzip_level: u32,
+  pub brotli_quality: u32,
+  pub zstd_level: i32,
+}
+
+impl Default for CompressionOptions {
+  fn default() -> Self {
+    Self {
+      encodings: vec![
+        ContentEncoding::Brotli,
+        ContentEncoding::Zstd,
+        ContentEncoding::GZip,
+      ],
+      // By the time we add compression headers and Accept-Encoding, it
+      // probably doesn't make sense to compress stuff that's smaller than this.
+      min_size: 64,
+      content_types: None,
+      // Higher levels don't produce significant size differences. This is
+      // probably the reason why nginx's default gzip compression level is
+      // also 1: https://nginx.org/en/docs/http/ngx_http_gzip_module.html#gzip_comp_level
+      gzip_level: 1,
+      // Google's nginx default for on-the-fly compression:
+      // https://github.com/google/ngx_brotli#brotli_comp_level
+      brotli_quality: 6,
+      zstd_level: zstd::DEFAULT_COMPRESSION_LEVEL,
+    }
+  }
+}
+
+impl CompressionOptions {
+  pub fn validate(&self) -> Result<(), AnyError> {
+    if self.gzip_level > 9 {
+      return Err(range_error("gzipLevel must be between 0 and 9"));
+    }
+    if self.brotli_quality > 11 {
+      return Err(range_error("brotliQuality must be between 0 and 11"));
+    }
+    if !zstd::compression_level_range().contains(&self.zstd_level) {
+      return Err(range_error(format!(
+        "zstdLevel must be between {} and {}",
+        zstd::compression_level_range().start(),
+        zstd::compression_level_range().end()
+      )));
+    }
+    Ok(())
+  }
+
+  /// The compression for `encoding` at the configured level.
+  pub fn compression(&self, encoding: ContentEncoding) -> Compression {
+    match encoding {
+      ContentEncoding::GZip => Compression::GZip(self.gzip_level),
+      ContentEncoding::Brotli => Compression::Brotli(self.brotli_quality),
+      ContentEncoding::Zstd => Compression::Zstd(self.zstd_level),
+    }
+  }
 }
 
 pub enum ResponseStream {
@@ -127,6 +242,8 @@ pub enum ResponseStream {
   Resource(ResourceBodyAdapter),
   /// A JS-backed stream, written in JS and transported via pipe.
   V8Stream(tokio::sync::mpsc::Receiver<BufView>),
+  /// A regular file, read directly from the file in large chunks.
+  File(FileBodyAdapter),
 }
 
 #[derive(Default)]
@@ -144,6 +261,8 @@ pub enum ResponseBytesInner {
   GZipStream(GZipResponseStream),
   /// A Brotli stream.
   BrotliStream(BrotliResponseStream),
+  /// A Zstandard stream.
+  ZstdStream(ZstdResponseStream),
 }
 
 impl std::fmt::Debug for ResponseBytesInner {
@@ -155,6 +274,7 @@ impl std::fmt::Debug for ResponseBytesInner {
       Self::UncompressedStream(..) => f.write_str("Uncompressed"),
       Self::GZipStream(..) => f.write_str("GZip"),
       Self::BrotliStream(..) => f.write_str("Brotli"),
+      Self::ZstdStream(..) => f.write_str("Zstd"),
     }
   }
 }
@@ -183,6 +303,27 @@ impl ResponseBytes {
     self.2.clone()
   }
 
+  /// The size of the file this body sends in full, if it's an uncompressed
+  /// file body that can be narrowed with [`Self::set_file_range`].
+  pub fn file_len(&self) -> Option<u64> {
+    match &self.0 {
+      ResponseBytesInner::UncompressedStream(ResponseStream::File(file)) => {
+        file.full_len()
+      }
+      _ => None,
+    }
+  }
+
+  /// Narrows a file body to the `len` bytes starting at `offset`.
+  pub fn set_file_range(&mut self, offset: u64, len: u64) -> FsResult<()> {
+    match &mut self.0 {
+      ResponseBytesInner::UncompressedStream(ResponseStream::File(file)) => {
+        file.set_range(offset, len)
+      }
+      _ => unreachable!(),
+    }
+  }
+
   fn complete(&mut self, success: bool) -> ResponseBytesInner {
     if matches!(self.0, ResponseBytesInner::Done) {
       return ResponseBytesInner::Done;
@@ -203,16 +344,22 @@ impl ResponseBytesInner {
       Self::UncompressedStream(res) => res.size_hint(),
       Self::GZipStream(..) => SizeHint::default(),
       Self::BrotliStream(..) => SizeHint::default(),
+      Self::ZstdStream(..) => SizeHint::default(),
     }
   }
 
   fn from_stream(compression: Compression, stream: ResponseStream) -> Self {
     match compression {
-      Compression::GZip => Self::GZipStream(GZipResponseStream::new(stream)),
-      Compression::Brotli => {
-        Self::BrotliStream(BrotliResponseStream::new(stream))
+      Compression::GZip(level) => {
+        Self::GZipStream(GZipResponseStream::new(level, stream))
+      }
+      Compression::Brotli(quality) => {
+        Self::BrotliStream(BrotliResponseStream::new(quality, stream))
+      }
+      Compression::Zstd(level) => {
+        Self::ZstdStream(ZstdResponseStream::new(level, stream))
       }
-      _ => Self::UncompressedStream(stream),
+      Compression::None => Self::UncompressedStream(stream),
     }
   }
 
@@ -234,50 +381,61 @@ impl ResponseBytesInner {
     )
   }
 
+  /// A body of the `len` bytes following the current position of `file`,
+  /// the backing file of the resource `stm`.
+  pub fn from_file(
+    compression: Compression,
+    stm: Rc<dyn Resource>,
+    file: Rc<dyn File>,
+    len: u64,
+    full_len: Option<u64>,
+    auto_close: bool,
+  ) -> Self {
+    Self::from_stream(
+      compression,
+      ResponseStream::File(FileBodyAdapter::new(
+        stm, file, len, full_len, auto_close,
+      )),
+    )
+  }
+
   pub fn from_slice(compression: Compression, bytes: &[u8]) -> Self {
     match compression {
-      Compression::GZip => {
-        let mut writer =
-          GzEncoder::new(Vec::new(), flate2::Compression::fast());
-        writer.write_all(bytes).unwrap();
-        Self::Bytes(BufView::from(writer.finish().unwrap()))
-      }
-      Compression::Brotli => {
-        // quality level 6 is based on google's nginx default value for
-        // on-the-fly compression
-        // https://github.com/google/ngx_brotli#brotli_comp_level
-        // lgwin 22 is equivalent to brotli window size of (2**22)-16 bytes
-        // (~4MB)
-        let mut writer =
-          brotli::CompressorWriter::new(Vec::new(), 65 * 1024, 6, 22);
-        writer.write_all(bytes).unwrap();
-        writer.flush().unwrap();
-        Self::Bytes(BufView::from(writer.into_inner()))
-      }
-      _ => Self::Bytes(BufView::from(bytes.to_vec())),
+      Compression::None => Self::Bytes(BufView::from(bytes.to_vec())),
+      _ => Self::Bytes(BufView::from(compress_bytes(compression, bytes))),
     }
   }
 
   pub fn from_vec(compression: Compression, vec: Vec<u8>) -> Self {
     match compression {
-      Compression::GZip => {
-        let mut writer =
-          GzEncoder::new(Vec::new(), flate2::Compression::fast());
-        writer.write_all(&vec).unwrap();
-        Self::Bytes(BufView::from(writer.finish().unwrap()))
-      }
-      Compression::Brotli => {
-        let mut writer =
-          brotli::CompressorWriter::new(Vec::new(), 65 * 1024, 6, 22);
-        writer.write_all(&vec).unwrap();
-        writer.flush().unwrap();
-        Self::Bytes(BufView::from(writer.into_inner()))
-      }
-      _ => Self::Bytes(BufView::from(vec)),
+      Compression::None => Self::Bytes(BufView::from(vec)),
+      _ => Self::Bytes(BufView::from(compress_bytes(compression, &vec))),
     }
   }
 }
 
+fn compress_bytes(compression: Compression, bytes: &[u8]) -> Vec<u8> {
+  match compression {
+    Compression::GZip(level) => {
+      let mut writer =
+        GzEncoder::new(Vec::new(), flate2::Compression::new(level));
+      writer.write_all(bytes).unwrap();
+      writer.finish().unwrap()
+    }
+    Compression::Brotli(quality) => {
+      // lgwin 22 is equivalent to brotli window size of (2**22)-16 bytes
+      // (~4MB)
+      let mut writer =
+        brotli::CompressorWriter::new(Vec::new(), 65 * 1024, quality, 22);
+      writer.write_all(bytes).unwrap();
+      writer.flush().unwrap();
+      writer.into_inner()
+    }
+    Compression::Zstd(level) => zstd::bulk::compress(bytes, level).unwrap(),
+    Compression::None => bytes.to_vec(),
+  }
+}
+
 impl Body for ResponseBytes {
   type Data = BufView;
   type Error = AnyError;
@@ -295,7 +453,9 @@ impl Body for ResponseBytes {
           unreachable!()
         }
         ResponseBytesInner::Bytes(..) => {
-          let ResponseBytesInner::Bytes(data) = self.complete(true) else { unreachable!(); };
+          let ResponseBytesInner::Bytes(data) = self.complete(true) else {
+            unreachable!();
+          };
           return std::task::Poll::Ready(Some(Ok(Frame::data(data))));
         }
         ResponseBytesInner::UncompressedStream(stm) => {
@@ -307,6 +467,9 @@ impl Body for ResponseBytes {
         ResponseBytesInner::BrotliStream(stm) => {
           ready!(Pin::new(stm).poll_frame(cx))
         }
+        ResponseBytesInner::ZstdStream(stm) => {
+          ready!(Pin::new(stm).poll_frame(cx))
+        }
       };
       // This is where we retry the NoData response
       if matches!(res, ResponseStreamResult::NoData) {
@@ -368,6 +531,7 @@ impl PollFrame for ResponseStream {
     match &mut *self {
       ResponseStream::Resource(res) => Pin::new(res).poll_frame(cx),
       ResponseStream::V8Stream(res) => Pin::new(res).poll_frame(cx),
+      ResponseStream::File(res) => Pin::new(res).poll_frame(cx),
     }
   }
 
@@ -375,6 +539,7 @@ impl PollFrame for ResponseStream {
     match self {
       ResponseStream::Resource(res) => res.size_hint(),
       ResponseStream::V8Stream(res) => res.size_hint(),
+      ResponseStream::File(res) => res.size_hint(),
     }
   }
 }
@@ -413,6 +578,89 @@ impl PollFrame for ResourceBodyAdapter {
   }
 }
 
+/// The size of the chunks read from files. Files don't have the latency of
+/// sockets, so larger reads mean fewer round trips to the blocking pool.
+const FILE_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Reads a response body straight from a regular file. Unlike
+/// [`ResourceBodyAdapter`] it knows the exact length of the body, so hyper can
+/// send a `Content-Length`, and the range it reads can be narrowed to serve
+/// range requests.
+pub struct FileBodyAdapter {
+  auto_close: bool,
+  stm: Rc<dyn Resource>,
+  file: Rc<dyn File>,
+  remaining: u64,
+  full_len: Option<u64>,
+  future: Option<LocalBoxFuture<'static, FsResult<BufView>>>,
+}
+
+impl FileBodyAdapter {
+  pub fn new(
+    stm: Rc<dyn Resource>,
+    file: Rc<dyn File>,
+    len: u64,
+    full_len: Option<u64>,
+    auto_close: bool,
+  ) -> Self {
+    FileBodyAdapter {
+      auto_close,
+      stm,
+      file,
+      remaining: len,
+      full_len,
+      future: None,
+    }
+  }
+
+  fn full_len(&self) -> Option<u64> {
+    self.full_len
+  }
+
+  fn set_range(&mut self, offset: u64, len: u64) -> FsResult<()> {
+    self.file.clone().seek_sync(SeekFrom::Start(offset))?;
+    self.remaining = len;
+    Ok(())
+  }
+}
+
+impl PollFrame for FileBodyAdapter {
+  fn poll_frame(
+    mut self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+  ) -> std::task::Poll<ResponseStreamResult> {
+    // The range can't change once we started reading.
+    self.full_len = None;
+    if self.remaining == 0 {
+      if self.auto_close {
+        self.stm.clone().close();
+      }
+      return std::task::Poll::Ready(ResponseStreamResult::EndOfStream);
+    }
+    let limit = self.remaining.min(FILE_CHUNK_SIZE) as usize;
+    let file = self.file.clone();
+    let future = self.future.get_or_insert_with(|| file.read(limit));
+    let res = ready!(future.poll_unpin(cx));
+    self.future = None;
+    let res = match res {
+      Err(err) => ResponseStreamResult::Error(err.into()),
+      // The file was truncated while we were sending it.
+      Ok(buf) if buf.is_empty() => ResponseStreamResult::Error(AnyError::msg(
+        "file ended before the response body was sent",
+      )),
+      Ok(buf) => {
+        self.remaining = self.remaining.saturating_sub(buf.len() as u64);
+        ResponseStreamResult::NonEmptyBuf(buf)
+      }
+    };
+    std::task::Poll::Ready(res)
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    SizeHint::with_exact(self.remaining)
+  }
+}
+
 impl PollFrame for tokio::sync::mpsc::Receiver<BufView> {
   fn poll_frame(
     mut self: Pin<&mut Self>,
@@ -451,9 +699,9 @@ pub struct GZipResponseStream {
 }
 
 impl GZipResponseStream {
-  pub fn new(underlying: ResponseStream) -> Self {
+  pub fn new(level: u32, underlying: ResponseStream) -> Self {
     Self {
-      stm: flate2::Compress::new(flate2::Compression::fast(), false),
+      stm: flate2::Compress::new(flate2::Compression::new(level), false),
       crc: flate2::Crc::new(),
       next_buf: None,
       partial: None,
@@ -465,11 +713,6 @@ impl GZipResponseStream {
 
 /// This is a minimal GZip header suitable for serving data from a webserver. We don't need to provide
 /// most of the information. We're skipping header name, CRC, etc, and providing a null timestamp.
-///
-/// We're using compression level 1, as higher levels don't produce significant size differences. This
-/// is probably the reason why nginx's default gzip compression level is also 1:
-///
-/// https://nginx.org/en/docs/http/ngx_http_gzip_module.html#gzip_comp_level
 static GZIP_HEADER: Bytes =
   Bytes::from_static(&[0x1f, 0x8b, 0x08, 0, 0, 0, 0, 0, 0x01, 0xff]);
 
@@ -612,18 +855,27 @@ impl Drop for BrotliEncoderStateWrapper {
 }
 
 impl BrotliResponseStream {
-  pub fn new(underlying: ResponseStream) -> Self {
+  pub fn new(quality: u32, underlying: ResponseStream) -> Self {
+    // SAFETY: creating an FFI instance should be OK with these args.
+    let stm = unsafe {
+      BrotliEncoderStateWrapper {
+        stm: brotli::ffi::compressor::BrotliEncoderCreateInstance(
+          None,
+          None,
+          std::ptr::null_mut(),
+        ),
+      }
+    };
+    // SAFETY: the instance was just created and the quality is in range.
+    unsafe {
+      brotli::ffi::compressor::BrotliEncoderSetParameter(
+        stm.stm,
+        brotli::enc::encode::BrotliEncoderParameter::BROTLI_PARAM_QUALITY,
+        quality,
+      );
+    }
     Self {
-      // SAFETY: creating an FFI instance should be OK with these args.
-      stm: unsafe {
-        BrotliEncoderStateWrapper {
-          stm: brotli::ffi::compressor::BrotliEncoderCreateInstance(
-            None,
-            None,
-            std::ptr::null_mut(),
-          ),
-        }
-      },
+      stm,
       output_written_so_far: 0,
       current_cursor: 0,
       state: BrotliState::Streaming,
@@ -747,6 +999,122 @@ impl PollFrame for BrotliResponseStream {
   }
 }
 
+#[derive(Copy, Clone, Debug)]
+enum ZstdState {
+  Streaming,
+  EndOfStream,
+}
+
+#[pin_project]
+pub struct ZstdResponseStream {
+  stm: zstd::stream::raw::Encoder<'static>,
+  state: ZstdState,
+  #[pin]
+  underlying: ResponseStream,
+}
+
+/// Makes sure `output` has room for the encoder to write to.
+fn reserve_zstd_output(output: &mut Vec<u8>) {
+  if output.capacity() - output.len() < 1024 {
+    output.reserve(64 * 1024);
+  }
+}
+
+impl ZstdResponseStream {
+  pub fn new(level: i32, underlying: ResponseStream) -> Self {
+    Self {
+      stm: zstd::stream::raw::Encoder::new(level).unwrap(),
+      state: ZstdState::Streaming,
+      underlying,
+    }
+  }
+
+  /// Compresses `input` and flushes the encoder, so every chunk the
+  /// underlying stream produces is sent right away.
+  fn compress(&mut self, input: &[u8]) -> io::Result<Vec<u8>> {
+    let mut output =
+      Vec::with_capacity(zstd::zstd_safe::compress_bound(input.len()));
+    let input_len = input.len();
+    let mut input = InBuffer::around(input);
+    while input.pos() < input_len {
+      reserve_zstd_output(&mut output);
+      let pos = output.len();
+      self
+        .stm
+        .run(&mut input, &mut OutBuffer::around_pos(&mut output, pos))?;
+    }
+    loop {
+      reserve_zstd_output(&mut output);
+      let pos = output.len();
+      if self
+        .stm
+        .flush(&mut OutBuffer::around_pos(&mut output, pos))?
+        == 0
+      {
+        return Ok(output);
+      }
+    }
+  }
+
+  /// Writes the end of the zstd frame.
+  fn finish(&mut self) -> io::Result<Vec<u8>> {
+    let mut output = vec![];
+    loop {
+      reserve_zstd_output(&mut output);
+      let pos = output.len();
+      if self
+        .stm
+        .finish(&mut OutBuffer::around_pos(&mut output, pos), false)?
+        == 0
+      {
+        return Ok(output);
+      }
+    }
+  }
+}
+
+impl PollFrame for ZstdResponseStream {
+  fn poll_frame(
+    self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+  ) -> std::task::Poll<ResponseStreamResult> {
+    let this = self.get_mut();
+    let frame = match this.state {
+      ZstdState::Streaming => {
+        ready!(Pin::new(&mut this.underlying).poll_frame(cx))
+      }
+      ZstdState::EndOfStream => {
+        return std::task::Poll::Ready(ResponseStreamResult::EndOfStream);
+      }
+    };
+
+    let res = match frame {
+      ResponseStreamResult::NonEmptyBuf(buf) => match this.compress(&buf) {
+        Ok(output) if output.is_empty() => ResponseStreamResult::NoData,
+        Ok(output) => ResponseStreamResult::NonEmptyBuf(BufView::from(output)),
+        Err(err) => ResponseStreamResult::Error(err.into()),
+      },
+      ResponseStreamResult::EndOfStream => {
+        this.state = ZstdState::EndOfStream;
+        match this.finish() {
+          Ok(output) if output.is_empty() => ResponseStreamResult::EndOfStream,
+          Ok(output) => {
+            ResponseStreamResult::NonEmptyBuf(BufView::from(output))
+          }
+          Err(err) => ResponseStreamResult::Error(err.into()),
+        }
+      }
+      _ => frame,
+    };
+
+    std::task::Poll::Ready(res)
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    SizeHint::default()
+  }
+}
+
 /// A response body object that can be passed to V8. This body will feed byte buffers to a channel which
 /// feed's hyper's HTTP response.
 pub struct V8StreamHttpResponseBody(
@@ -879,7 +1247,7 @@ mod tests {
     }
     let (tx, rx) = tokio::sync::mpsc::channel(1);
     let underlying = ResponseStream::V8Stream(rx);
-    let mut resp = GZipResponseStream::new(underlying);
+    let mut resp = GZipResponseStream::new(1, underlying);
     let handle = tokio::task::spawn(async move {
       for chunk in v {
         tx.send(chunk.into()).await.ok().unwrap();
@@ -921,7 +1289,7 @@ mod tests {
     }
     let (tx, rx) = tokio::sync::mpsc::channel(1);
     let underlying = ResponseStream::V8Stream(rx);
-    let mut resp = BrotliResponseStream::new(underlying);
+    let mut resp = BrotliResponseStream::new(6, underlying);
     let handle = tokio::task::spawn(async move {
       for chunk in v {
         tx.send(chunk.into()).await.ok().unwrap();
@@ -957,22 +1325,65 @@ mod tests {
     handle.await.unwrap();
   }
 
+  async fn test_zstd(i: impl Iterator<Item = Vec<u8>> + Send + 'static) {
+    let v = i.collect::<Vec<_>>();
+    let mut expected: Vec<u8> = vec![];
+    for v in &v {
+      expected.extend(v);
+    }
+    let (tx, rx) = tokio::sync::mpsc::channel(1);
+    let underlying = ResponseStream::V8Stream(rx);
+    let mut resp = ZstdResponseStream::new(3, underlying);
+    let handle = tokio::task::spawn(async move {
+      for chunk in v {
+        tx.send(chunk.into()).await.ok().unwrap();
+      }
+    });
+    // Limit how many times we'll loop
+    const LIMIT: usize = 1000;
+    let mut v: Vec<u8> = vec![];
+    for i in 0..=LIMIT {
+      assert_ne!(i, LIMIT);
+      let frame = poll_fn(|cx| Pin::new(&mut resp).poll_frame(cx)).await;
+      if matches!(frame, ResponseStreamResult::EndOfStream) {
+        break;
+      }
+      if matches!(frame, ResponseStreamResult::NoData) {
+        continue;
+      }
+      let ResponseStreamResult::NonEmptyBuf(buf) = frame else {
+        panic!("Unexpected stream type");
+      };
+      assert_ne!(buf.len(), 0);
+      v.extend(&*buf);
+    }
+
+    let v = zstd::stream::decode_all(&*v).unwrap();
+
+    assert_eq!(v, expected);
+
+    handle.await.unwrap();
+  }
+
   #[tokio::test]
   async fn test_simple() {
     test_brotli(vec![b"hello world".to_vec()].into_iter()).await;
     test_gzip(vec![b"hello world".to_vec()].into_iter()).await;
+    test_zstd(vec![b"hello world".to_vec()].into_iter()).await;
   }
 
   #[tokio::test]
   async fn test_empty() {
     test_brotli(vec![].into_iter()).await;
     test_gzip(vec![].into_iter()).await;
+    test_zstd(vec![].into_iter()).await;
   }
 
   #[tokio::test]
   async fn test_simple_zeros() {
     test_brotli(vec![vec![0; 0x10000]].into_iter()).await;
     test_gzip(vec![vec![0; 0x10000]].into_iter()).await;
+    test_zstd(vec![vec![0; 0x10000]].into_iter()).await;
   }
 
   macro_rules! test {
@@ -984,6 +1395,8 @@ mod tests {
           super::test_gzip(iter).await;
           let br_iter = super::chunk(super::$vec());
           super::test_brotli(br_iter).await;
+          let zstd_iter = super::chunk(super::$vec());
+          super::test_zstd(zstd_iter).await;
         }
 
         #[tokio::test]
@@ -992,6 +1405,8 @@ mod tests {
           super::test_gzip(iter).await;
           let br_iter = super::front_load(super::$vec());
           super::test_brotli(br_iter).await;
+          let zstd_iter = super::front_load(super::$vec());
+          super::test_zstd(zstd_iter).await;
         }
 
         #[tokio::test]
@@ -1000,6 +1415,8 @@ mod tests {
           super::test_gzip(iter).await;
           let br_iter = super::front_load_but_one(super::$vec());
           super::test_brotli(br_iter).await;
+          let zstd_iter = super::front_load_but_one(super::$vec());
+          super::test_zstd(zstd_iter).await;
         }
 
         #[tokio::test]
@@ -1008,6 +1425,8 @@ mod tests {
           super::test_gzip(iter).await;
           let br_iter = super::back_load(super::$vec());
           super::test_brotli(br_iter).await;
+          let zstd_iter = super::back_load(super::$vec());
+          super::test_zstd(zstd_iter).await;
         }
 
         #[tokio::test]
@@ -1016,6 +1435,8 @@ mod tests {
           super::test_gzip(iter).await;
           let br_iter = super::random(super::$vec());
           super::test_brotli(br_iter).await;
+          let zstd_iter = super::random(super::$vec());
+          super::test_zstd(zstd_iter).await;
         }
       }
     };
@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 use crate::request_properties::HttpConnectionProperties;
 use crate::response_body::CompletionHandle;
+use crate::response_body::CompressionOptions;
 use crate::response_body::ResponseBytes;
 use deno_core::error::AnyError;
 use http::request::Parts;
@@ -20,6 +21,7 @@ pub type SlabId = u32;
 
 pub struct HttpSlabRecord {
   request_info: HttpConnectionProperties,
+  compression_options: Rc<CompressionOptions>,
   request_parts: Parts,
   request_body: Option<Incoming>,
   // The response may get taken before we tear this down
@@ -81,6 +83,7 @@ fn slab_insert_raw(
   request_parts: Parts,
   request_body: Option<Incoming>,
   request_info: HttpConnectionProperties,
+  compression_options: Rc<CompressionOptions>,
 ) -> SlabId {
   let index = SLAB.with(|slab| {
     let mut slab = slab.borrow_mut();
@@ -88,6 +91,7 @@ fn slab_insert_raw(
     let trailers = body.trailers();
     slab.insert(HttpSlabRecord {
       request_info,
+      compression_options,
       request_parts,
       request_body,
       response: Some(Response::new(body)),
@@ -105,9 +109,15 @@ fn slab_insert_raw(
 pub fn slab_insert(
   request: Request,
   request_info: HttpConnectionProperties,
+  compression_options: Rc<CompressionOptions>,
 ) -> SlabId {
   let (request_parts, request_body) = request.into_parts();
-  slab_insert_raw(request_parts, Some(request_body), request_info)
+  slab_insert_raw(
+    request_parts,
+    Some(request_body),
+    request_info,
+    compression_options,
+  )
 }
 
 pub fn slab_drop(index: SlabId) {
@@ -203,6 +213,11 @@ impl SlabEntry {
     &self.self_ref().request_info
   }
 
+  /// Get the compression options of the server this request was sent to.
+  pub fn compression_options(&self) -> Rc<CompressionOptions> {
+    self.self_ref().compression_options.clone()
+  }
+
   /// Get a reference to the request parts.
   pub fn request_parts(&self) -> &Parts {
     &self.self_ref().request_parts
@@ -244,6 +259,7 @@ mod tests {
         local_port: None,
         stream_type: NetworkStreamType::Tcp,
       },
+      Default::default(),
     );
     let entry = slab_get(id);
     entry.complete();
@@ -0,0 +1,280 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::AsyncRefCell;
+use deno_core::AsyncResult;
+use deno_core::BufView;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
+use deno_core::CancelTryFuture;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use serde::Deserialize;
+use tokio::sync::mpsc;
+
+/// The number of formatted events buffered before `op_http_sse_send` waits
+/// for the client to catch up.
+const SSE_CHANNEL_CAPACITY: usize = 8;
+
+/// A comment line, ignored by clients, that keeps idle connections from being
+/// closed by proxies.
+static KEEP_ALIVE_COMMENT: Bytes = Bytes::from_static(b":\n\n");
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerSentEvent {
+  pub comment: Option<String>,
+  pub event: Option<String>,
+  pub data: Option<String>,
+  pub id: Option<String>,
+  pub retry: Option<u64>,
+}
+
+fn is_single_line(value: &str) -> bool {
+  !value.contains(['\r', '\n'])
+}
+
+/// Splits `value` into lines the way event stream parsers do: unlike
+/// `str::lines()`, lone carriage returns end lines too.
+fn split_lines(value: &str) -> impl Iterator<Item = &str> {
+  value
+    .split("\r\n")
+    .flat_map(|line| line.split(['\r', '\n']))
+}
+
+impl ServerSentEvent {
+  /// Formats the event in the `text/event-stream` format.
+  pub fn to_bytes(&self) -> Result<Bytes, AnyError> {
+    let mut out = String::new();
+    if let Some(comment) = &self.comment {
+      for line in split_lines(comment) {
+        out.push_str(": ");
+        out.push_str(line);
+        out.push('\n');
+      }
+    }
+    if let Some(event) = &self.event {
+      if !is_single_line(event) {
+        return Err(type_error("The event name must not contain newlines"));
+      }
+      out.push_str("event: ");
+      out.push_str(event);
+      out.push('\n');
+    }
+    if let Some(id) = &self.id {
+      if !is_single_line(id) || id.contains('\0') {
+        return Err(type_error(
+          "The event id must not contain newlines or NUL characters",
+        ));
+      }
+      out.push_str("id: ");
+      out.push_str(id);
+      out.push('\n');
+    }
+    if let Some(retry) = self.retry {
+      out.push_str(&format!("retry: {retry}\n"));
+    }
+    if let Some(data) = &self.data {
+      for line in split_lines(data) {
+        out.push_str("data: ");
+        out.push_str(line);
+        out.push('\n');
+      }
+    }
+    if out.is_empty() {
+      return Err(type_error("An event must have at least one field"));
+    }
+    out.push('\n');
+    Ok(Bytes::from(out))
+  }
+}
+
+struct SseReader {
+  rx: mpsc::Receiver<Bytes>,
+  /// The part of the last event that didn't fit into the last read.
+  pending: Bytes,
+}
+
+/// The readable end of a server-sent event stream, used as a response body.
+/// When there are no events for `keep_alive`, a comment is sent instead.
+pub struct SseStreamResource {
+  reader: AsyncRefCell<SseReader>,
+  keep_alive: Option<Duration>,
+  cancel: CancelHandle,
+}
+
+impl Resource for SseStreamResource {
+  fn name(&self) -> Cow<str> {
+    "serverSentEventStream".into()
+  }
+
+  fn read(self: Rc<Self>, limit: usize) -> AsyncResult<BufView> {
+    Box::pin(async move {
+      let cancel = RcRef::map(&self, |this| &this.cancel);
+      let mut reader = RcRef::map(&self, |this| &this.reader)
+        .borrow_mut()
+        .try_or_cancel(cancel.clone())
+        .await?;
+      let mut chunk = if reader.pending.is_empty() {
+        let next = match self.keep_alive {
+          Some(keep_alive) => {
+            tokio::time::timeout(keep_alive, reader.rx.recv())
+              .or_cancel(cancel)
+              .await?
+              .unwrap_or_else(|_| Some(KEEP_ALIVE_COMMENT.clone()))
+          }
+          None => reader.rx.recv().or_cancel(cancel).await?,
+        };
+        match next {
+          Some(chunk) => chunk,
+          // All writers are gone, end the stream.
+          None => return Ok(BufView::empty()),
+        }
+      } else {
+        std::mem::take(&mut reader.pending)
+      };
+      if chunk.len() > limit {
+        reader.pending = chunk.split_off(limit);
+      }
+      Ok(BufView::from(chunk))
+    })
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel();
+  }
+}
+
+/// The writable end of a server-sent event stream. Closing it ends the
+/// stream once the buffered events are sent.
+pub struct SseWriterResource {
+  tx: mpsc::Sender<Bytes>,
+  cancel: CancelHandle,
+}
+
+impl Resource for SseWriterResource {
+  fn name(&self) -> Cow<str> {
+    "serverSentEventWriter".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    // Pending ops hold references to the sender; they need to let go for
+    // the stream to end.
+    self.cancel.cancel();
+  }
+}
+
+fn client_disconnected() -> AnyError {
+  std::io::Error::new(
+    std::io::ErrorKind::BrokenPipe,
+    "The client closed the event stream",
+  )
+  .into()
+}
+
+/// Creates a server-sent event stream, returning the resource ids of its
+/// readable and writable ends.
+#[op]
+pub fn op_http_sse_create(
+  state: &mut OpState,
+  keep_alive_ms: Option<u64>,
+) -> (ResourceId, ResourceId) {
+  let (tx, rx) = mpsc::channel(SSE_CHANNEL_CAPACITY);
+  let readable_rid = state.resource_table.add(SseStreamResource {
+    reader: AsyncRefCell::new(SseReader {
+      rx,
+      pending: Bytes::new(),
+    }),
+    keep_alive: keep_alive_ms.map(Duration::from_millis),
+    cancel: Default::default(),
+  });
+  let writer_rid = state.resource_table.add(SseWriterResource {
+    tx,
+    cancel: Default::default(),
+  });
+  (readable_rid, writer_rid)
+}
+
+/// Sends an event, waiting while the client is too slow to keep up with the
+/// events buffered before it. Fails once the client disconnected.
+#[op]
+pub async fn op_http_sse_send(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  event: ServerSentEvent,
+) -> Result<(), AnyError> {
+  let bytes = event.to_bytes()?;
+  let writer = state
+    .borrow()
+    .resource_table
+    .get::<SseWriterResource>(rid)?;
+  let cancel = RcRef::map(&writer, |this| &this.cancel);
+  writer
+    .tx
+    .send(bytes)
+    .or_cancel(cancel)
+    .await?
+    .map_err(|_| client_disconnected())
+}
+
+/// Resolves once the client disconnected or the stream was closed.
+#[op]
+pub async fn op_http_sse_wait_closed(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<(), AnyError> {
+  let writer = state.borrow().resource_table.get::<SseWriterResource>(rid);
+  let Ok(writer) = writer else {
+    return Ok(());
+  };
+  let cancel = RcRef::map(&writer, |this| &this.cancel);
+  // Being canceled means the stream was closed.
+  _ = writer.tx.closed().or_cancel(cancel).await;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn format(event: ServerSentEvent) -> String {
+    String::from_utf8(event.to_bytes().unwrap().to_vec()).unwrap()
+  }
+
+  #[test]
+  fn test_format_event() {
+    assert_eq!(
+      format(ServerSentEvent {
+        data: Some("hello".into()),
+        ..Default::default()
+      }),
+      "data: hello\n\n"
+    );
+    assert_eq!(
+      format(ServerSentEvent {
+        comment: Some("note".into()),
+        event: Some("update".into()),
+        data: Some("a\nb\r\nc\rd".into()),
+        id: Some("42".into()),
+        retry: Some(1000),
+      }),
+      ": note\nevent: update\nid: 42\nretry: 1000\ndata: a\ndata: b\ndata: c\ndata: d\n\n"
+    );
+    assert!(ServerSentEvent::default().to_bytes().is_err());
+    assert!(ServerSentEvent {
+      event: Some("a\nb".into()),
+      ..Default::default()
+    }
+    .to_bytes()
+    .is_err());
+  }
+}
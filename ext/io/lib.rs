@@ -29,6 +29,7 @@ use std::io::Read;
 use std::io::Seek;
 use std::io::Write;
 use std::rc::Rc;
+use std::sync::Arc;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWrite;
@@ -46,6 +47,9 @@ use winapi::um::processenv::GetStdHandle;
 use winapi::um::winbase;
 
 pub mod fs;
+mod sink;
+
+pub use sink::StdioSink;
 
 // Store the stdio fd/handles in global statics in order to keep them
 // alive for the duration of the application since the last handle/fd
@@ -97,37 +101,40 @@ deno_core::extension!(deno_io,
       let t = &mut state.resource_table;
 
       let rid = t.add(fs::FileResource::new(
-        Rc::new(match stdio.stdin {
-          StdioPipe::Inherit => StdFileResourceInner::new(
+        match stdio.stdin {
+          StdioPipe::Inherit => Rc::new(StdFileResourceInner::new(
             StdFileResourceKind::Stdin,
             STDIN_HANDLE.try_clone().unwrap(),
-          ),
-          StdioPipe::File(pipe) => StdFileResourceInner::file(pipe),
-        }),
+          )),
+          StdioPipe::File(pipe) => Rc::new(StdFileResourceInner::file(pipe)),
+          StdioPipe::Sink(sink) => Rc::new(sink::SinkFile(sink)),
+        },
         "stdin".to_string(),
       ));
       assert_eq!(rid, 0, "stdin must have ResourceId 0");
 
       let rid = t.add(FileResource::new(
-        Rc::new(match stdio.stdout {
-          StdioPipe::Inherit => StdFileResourceInner::new(
+        match stdio.stdout {
+          StdioPipe::Inherit => Rc::new(StdFileResourceInner::new(
             StdFileResourceKind::Stdout,
             STDOUT_HANDLE.try_clone().unwrap(),
-          ),
-          StdioPipe::File(pipe) => StdFileResourceInner::file(pipe),
-        }),
+          )),
+          StdioPipe::File(pipe) => Rc::new(StdFileResourceInner::file(pipe)),
+          StdioPipe::Sink(sink) => Rc::new(sink::SinkFile(sink)),
+        },
         "stdout".to_string(),
       ));
       assert_eq!(rid, 1, "stdout must have ResourceId 1");
 
       let rid = t.add(FileResource::new(
-        Rc::new(match stdio.stderr {
-          StdioPipe::Inherit => StdFileResourceInner::new(
+        match stdio.stderr {
+          StdioPipe::Inherit => Rc::new(StdFileResourceInner::new(
             StdFileResourceKind::Stderr,
             STDERR_HANDLE.try_clone().unwrap(),
-          ),
-          StdioPipe::File(pipe) => StdFileResourceInner::file(pipe),
-        }),
+          )),
+          StdioPipe::File(pipe) => Rc::new(StdFileResourceInner::file(pipe)),
+          StdioPipe::Sink(sink) => Rc::new(sink::SinkFile(sink)),
+        },
         "stderr".to_string(),
       ));
       assert_eq!(rid, 2, "stderr must have ResourceId 2");
@@ -138,6 +145,8 @@ deno_core::extension!(deno_io,
 pub enum StdioPipe {
   Inherit,
   File(StdFile),
+  /// Forward writes to a [`StdioSink`]. Reading from a sink is not supported.
+  Sink(Arc<dyn StdioSink>),
 }
 
 impl Default for StdioPipe {
@@ -151,6 +160,7 @@ impl Clone for StdioPipe {
     match self {
       StdioPipe::Inherit => StdioPipe::Inherit,
       StdioPipe::File(pipe) => StdioPipe::File(pipe.try_clone().unwrap()),
+      StdioPipe::Sink(sink) => StdioPipe::Sink(sink.clone()),
     }
   }
 }
@@ -0,0 +1,169 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::io;
+use std::rc::Rc;
+
+use deno_core::BufMutView;
+use deno_core::BufView;
+
+use crate::fs::File;
+use crate::fs::FsError;
+use crate::fs::FsResult;
+use crate::fs::FsStat;
+
+/// Receives everything a runtime writes to its stdout or stderr when installed
+/// with [`crate::StdioPipe::Sink`]. That includes the output of `console`, as
+/// this extension overrides `op_print` to write to the stdout and stderr
+/// resources instead of the process's.
+///
+/// Each runtime can be given its own sinks, so an embedder running several
+/// workers can tell their output apart instead of having it interleave on the
+/// process's file descriptors.
+pub trait StdioSink: Send + Sync {
+  fn write_all(&self, buf: &[u8]) -> io::Result<()>;
+}
+
+impl<F> StdioSink for F
+where
+  F: Fn(&[u8]) -> io::Result<()> + Send + Sync,
+{
+  fn write_all(&self, buf: &[u8]) -> io::Result<()> {
+    self(buf)
+  }
+}
+
+/// A write-only file forwarding to a [`StdioSink`]. It has no file
+/// descriptor, so subprocesses can't inherit it and have to be spawned with
+/// piped output instead.
+pub(crate) struct SinkFile(pub std::sync::Arc<dyn StdioSink>);
+
+#[async_trait::async_trait(?Send)]
+impl File for SinkFile {
+  fn read_sync(self: Rc<Self>, _buf: &mut [u8]) -> FsResult<usize> {
+    Err(FsError::NotSupported)
+  }
+  async fn read_byob(
+    self: Rc<Self>,
+    _buf: BufMutView,
+  ) -> FsResult<(usize, BufMutView)> {
+    Err(FsError::NotSupported)
+  }
+
+  fn write_sync(self: Rc<Self>, buf: &[u8]) -> FsResult<usize> {
+    self.0.write_all(buf)?;
+    Ok(buf.len())
+  }
+  async fn write(
+    self: Rc<Self>,
+    view: BufView,
+  ) -> FsResult<deno_core::WriteOutcome> {
+    self.0.write_all(&view)?;
+    Ok(deno_core::WriteOutcome::Full {
+      nwritten: view.len(),
+    })
+  }
+
+  fn write_all_sync(self: Rc<Self>, buf: &[u8]) -> FsResult<()> {
+    Ok(self.0.write_all(buf)?)
+  }
+  async fn write_all(self: Rc<Self>, buf: BufView) -> FsResult<()> {
+    Ok(self.0.write_all(&buf)?)
+  }
+
+  fn read_all_sync(self: Rc<Self>) -> FsResult<Vec<u8>> {
+    Err(FsError::NotSupported)
+  }
+  async fn read_all_async(self: Rc<Self>) -> FsResult<Vec<u8>> {
+    Err(FsError::NotSupported)
+  }
+
+  fn chmod_sync(self: Rc<Self>, _mode: u32) -> FsResult<()> {
+    Err(FsError::NotSupported)
+  }
+  async fn chmod_async(self: Rc<Self>, _mode: u32) -> FsResult<()> {
+    Err(FsError::NotSupported)
+  }
+
+  fn seek_sync(self: Rc<Self>, _pos: io::SeekFrom) -> FsResult<u64> {
+    Err(FsError::NotSupported)
+  }
+  async fn seek_async(self: Rc<Self>, _pos: io::SeekFrom) -> FsResult<u64> {
+    Err(FsError::NotSupported)
+  }
+
+  fn datasync_sync(self: Rc<Self>) -> FsResult<()> {
+    Ok(())
+  }
+  async fn datasync_async(self: Rc<Self>) -> FsResult<()> {
+    Ok(())
+  }
+
+  fn sync_sync(self: Rc<Self>) -> FsResult<()> {
+    Ok(())
+  }
+  async fn sync_async(self: Rc<Self>) -> FsResult<()> {
+    Ok(())
+  }
+
+  fn stat_sync(self: Rc<Self>) -> FsResult<FsStat> {
+    Err(FsError::NotSupported)
+  }
+  async fn stat_async(self: Rc<Self>) -> FsResult<FsStat> {
+    Err(FsError::NotSupported)
+  }
+
+  fn lock_sync(self: Rc<Self>, _exclusive: bool) -> FsResult<()> {
+    Err(FsError::NotSupported)
+  }
+  async fn lock_async(self: Rc<Self>, _exclusive: bool) -> FsResult<()> {
+    Err(FsError::NotSupported)
+  }
+
+  fn unlock_sync(self: Rc<Self>) -> FsResult<()> {
+    Err(FsError::NotSupported)
+  }
+  async fn unlock_async(self: Rc<Self>) -> FsResult<()> {
+    Err(FsError::NotSupported)
+  }
+
+  fn truncate_sync(self: Rc<Self>, _len: u64) -> FsResult<()> {
+    Err(FsError::NotSupported)
+  }
+  async fn truncate_async(self: Rc<Self>, _len: u64) -> FsResult<()> {
+    Err(FsError::NotSupported)
+  }
+
+  fn utime_sync(
+    self: Rc<Self>,
+    _atime_secs: i64,
+    _atime_nanos: u32,
+    _mtime_secs: i64,
+    _mtime_nanos: u32,
+  ) -> FsResult<()> {
+    Err(FsError::NotSupported)
+  }
+  async fn utime_async(
+    self: Rc<Self>,
+    _atime_secs: i64,
+    _atime_nanos: u32,
+    _mtime_secs: i64,
+    _mtime_nanos: u32,
+  ) -> FsResult<()> {
+    Err(FsError::NotSupported)
+  }
+
+  fn as_stdio(self: Rc<Self>) -> FsResult<std::process::Stdio> {
+    Err(FsError::NotSupported)
+  }
+  #[cfg(unix)]
+  fn backing_fd(self: Rc<Self>) -> Option<std::os::unix::prelude::RawFd> {
+    None
+  }
+  #[cfg(windows)]
+  fn backing_fd(self: Rc<Self>) -> Option<std::os::windows::io::RawHandle> {
+    None
+  }
+  fn try_clone_inner(self: Rc<Self>) -> FsResult<Rc<dyn File>> {
+    Ok(self)
+  }
+}
@@ -4,6 +4,7 @@
 const {
   AsyncGeneratorPrototype,
   BigIntPrototypeToString,
+  NumberIsInteger,
   ObjectFreeze,
   ObjectGetPrototypeOf,
   ObjectPrototypeIsPrototypeOf,
@@ -40,6 +41,29 @@ function validateQueueDelay(delay: number) {
   }
 }
 
+const maxQueueBackoffIntervals = 5;
+const maxQueueBackoffInterval = 60 * 60 * 1000;
+
+function validateBackoffSchedule(backoffSchedule: number[]) {
+  if (backoffSchedule.length > maxQueueBackoffIntervals) {
+    throw new TypeError(
+      `backoffSchedule cannot have more than ${maxQueueBackoffIntervals} intervals`,
+    );
+  }
+  for (const interval of backoffSchedule) {
+    if (!NumberIsInteger(interval) || interval < 0) {
+      throw new TypeError(
+        "backoffSchedule intervals must be non-negative integers",
+      );
+    }
+    if (interval > maxQueueBackoffInterval) {
+      throw new TypeError(
+        "backoffSchedule interval cannot be greater than one hour",
+      );
+    }
+  }
+}
+
 interface RawKvEntry {
   key: Deno.KvKey;
   value: RawValue;
@@ -221,18 +245,25 @@ class Kv {
 
   async enqueue(
     message: unknown,
-    opts?: { delay?: number; keysIfUndelivered?: Deno.KvKey[] },
+    opts?: {
+      delay?: number;
+      keysIfUndelivered?: Deno.KvKey[];
+      backoffSchedule?: number[];
+    },
   ) {
     if (opts?.delay !== undefined) {
       validateQueueDelay(opts?.delay);
     }
+    if (opts?.backoffSchedule !== undefined) {
+      validateBackoffSchedule(opts?.backoffSchedule);
+    }
 
     const enqueues = [
       [
         core.serialize(message, { forStorage: true }),
         opts?.delay ?? 0,
         opts?.keysIfUndelivered ?? [],
-        null,
+        opts?.backoffSchedule ?? null,
       ],
     ];
 
@@ -292,6 +323,40 @@ class Kv {
     }
   }
 
+  watch(keys: Deno.KvKey[]) {
+    const rid = ops.op_kv_watch(this.#rid, keys);
+    return new ReadableStream({
+      async pull(controller) {
+        let entries: (RawKvEntry | null)[] | null;
+        try {
+          entries = await core.opAsync("op_kv_watch_next", rid);
+        } catch (error) {
+          core.tryClose(rid);
+          controller.error(error);
+          return;
+        }
+        if (entries === null) {
+          core.tryClose(rid);
+          controller.close();
+          return;
+        }
+        controller.enqueue(entries.map((entry, i) => {
+          if (entry === null) {
+            return {
+              key: keys[i],
+              value: null,
+              versionstamp: null,
+            };
+          }
+          return deserializeValue(entry);
+        }));
+      },
+      cancel() {
+        core.tryClose(rid);
+      },
+    });
+  }
+
   close() {
     core.close(this.#rid);
     this.#closed = true;
@@ -373,16 +438,23 @@ class AtomicOperation {
 
   enqueue(
     message: unknown,
-    opts?: { delay?: number; keysIfUndelivered?: Deno.KvKey[] },
+    opts?: {
+      delay?: number;
+      keysIfUndelivered?: Deno.KvKey[];
+      backoffSchedule?: number[];
+    },
   ): this {
     if (opts?.delay !== undefined) {
       validateQueueDelay(opts?.delay);
     }
+    if (opts?.backoffSchedule !== undefined) {
+      validateBackoffSchedule(opts?.backoffSchedule);
+    }
     this.#enqueues.push([
       core.serialize(message, { forStorage: true }),
       opts?.delay ?? 0,
       opts?.keysIfUndelivered ?? [],
-      null,
+      opts?.backoffSchedule ?? null,
     ]);
     return this;
   }
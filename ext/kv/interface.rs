@@ -26,6 +26,7 @@ pub trait DatabaseHandler {
 #[async_trait(?Send)]
 pub trait Database {
   type QMH: QueueMessageHandle + 'static;
+  type DW: DatabaseWatcher + 'static;
 
   async fn snapshot_read(
     &self,
@@ -40,9 +41,20 @@ pub trait Database {
 
   async fn dequeue_next_message(&self) -> Result<Self::QMH, AnyError>;
 
+  fn watch(&self, keys: Vec<Vec<u8>>) -> Self::DW;
+
   fn close(&self);
 }
 
+#[async_trait(?Send)]
+pub trait DatabaseWatcher {
+  /// Returns the entries of the watched keys, in the order they were given
+  /// in, once the versionstamp of any of them differs from the entries last
+  /// returned. The first call returns right away. Returns `None` once the
+  /// database was closed.
+  async fn next(&mut self) -> Result<Option<Vec<Option<KvEntry>>>, AnyError>;
+}
+
 #[async_trait(?Send)]
 pub trait QueueMessageHandle {
   async fn take_payload(&mut self) -> Result<Vec<u8>, AnyError>;
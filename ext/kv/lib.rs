@@ -2,6 +2,8 @@
 
 pub mod codec;
 mod interface;
+pub mod multi_backend;
+pub mod remote;
 pub mod sqlite;
 
 use std::borrow::Cow;
@@ -18,8 +20,12 @@ use deno_core::error::AnyError;
 use deno_core::op;
 use deno_core::serde_v8::AnyValue;
 use deno_core::serde_v8::BigInt;
+use deno_core::AsyncRefCell;
 use deno_core::ByteString;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
 use deno_core::OpState;
+use deno_core::RcRef;
 use deno_core::Resource;
 use deno_core::ResourceId;
 use deno_core::ZeroCopyBuf;
@@ -36,6 +42,7 @@ const MAX_READ_RANGES: usize = 10;
 const MAX_READ_ENTRIES: usize = 1000;
 const MAX_CHECKS: usize = 10;
 const MAX_MUTATIONS: usize = 10;
+const MAX_WATCHED_KEYS: usize = 10;
 
 struct UnstableChecker {
   pub unstable: bool,
@@ -63,6 +70,8 @@ deno_core::extension!(deno_kv,
     op_kv_encode_cursor,
     op_kv_dequeue_next_message<DBH>,
     op_kv_finish_dequeued_message<DBH>,
+    op_kv_watch<DBH>,
+    op_kv_watch_next<DBH>,
   ],
   esm = [ "01_db.ts" ],
   options = {
@@ -343,6 +352,85 @@ where
   handle.finish(success).await
 }
 
+struct DatabaseWatcherResource<DW: DatabaseWatcher + 'static> {
+  watcher: AsyncRefCell<DW>,
+  cancel: CancelHandle,
+}
+
+impl<DW: DatabaseWatcher + 'static> Resource for DatabaseWatcherResource<DW> {
+  fn name(&self) -> Cow<str> {
+    "databaseWatcher".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel();
+  }
+}
+
+#[op]
+fn op_kv_watch<DBH>(
+  state: &mut OpState,
+  rid: ResourceId,
+  keys: Vec<KvKey>,
+) -> Result<ResourceId, AnyError>
+where
+  DBH: DatabaseHandler + 'static,
+{
+  let db = state
+    .resource_table
+    .get::<DatabaseResource<DBH::DB>>(rid)?
+    .db
+    .clone();
+
+  if keys.len() > MAX_WATCHED_KEYS {
+    return Err(type_error(format!(
+      "too many keys (max {})",
+      MAX_WATCHED_KEYS
+    )));
+  }
+  let keys = keys
+    .into_iter()
+    .map(encode_v8_key)
+    .collect::<std::io::Result<Vec<_>>>()?;
+  for key in &keys {
+    check_read_key_size(key)?;
+  }
+
+  let watcher = db.watch(keys);
+  Ok(state.resource_table.add(DatabaseWatcherResource {
+    watcher: AsyncRefCell::new(watcher),
+    cancel: Default::default(),
+  }))
+}
+
+#[op]
+async fn op_kv_watch_next<DBH>(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<Option<Vec<Option<V8KvEntry>>>, AnyError>
+where
+  DBH: DatabaseHandler + 'static,
+{
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<DatabaseWatcherResource<<DBH::DB as Database>::DW>>(rid)?;
+  let cancel = RcRef::map(&resource, |r| &r.cancel);
+  let mut watcher = RcRef::map(&resource, |r| &r.watcher).borrow_mut().await;
+  // Closing the watcher ends it like closing the database does.
+  let Ok(entries) = watcher.next().or_cancel(cancel).await else {
+    return Ok(None);
+  };
+  let Some(entries) = entries? else {
+    return Ok(None);
+  };
+  let entries = entries
+    .into_iter()
+    .map(|entry| entry.map(TryInto::try_into).transpose())
+    .collect::<Result<Vec<_>, AnyError>>()?;
+  Ok(Some(entries))
+}
+
 type V8KvCheck = (KvKey, Option<ByteString>);
 
 impl TryFrom<V8KvCheck> for KvCheck {
@@ -0,0 +1,174 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use async_trait::async_trait;
+use deno_core::error::AnyError;
+use deno_core::OpState;
+
+use crate::remote::RemoteDb;
+use crate::remote::RemoteDbHandler;
+use crate::remote::RemoteDbHandlerPermissions;
+use crate::remote::RemoteQueueMessageHandle;
+use crate::remote::RemoteWatcher;
+use crate::sqlite::DequeuedMessage;
+use crate::sqlite::SqliteDb;
+use crate::sqlite::SqliteDbHandler;
+use crate::sqlite::SqliteDbHandlerPermissions;
+use crate::sqlite::SqliteWatcher;
+use crate::AtomicWrite;
+use crate::CommitResult;
+use crate::Database;
+use crate::DatabaseHandler;
+use crate::DatabaseWatcher;
+use crate::KvEntry;
+use crate::QueueMessageHandle;
+use crate::ReadRange;
+use crate::ReadRangeOutput;
+use crate::SnapshotReadOptions;
+
+/// Opens `http:` and `https:` URLs as remote databases and everything else
+/// as SQLite databases.
+pub struct MultiBackendDbHandler<P>
+where
+  P: SqliteDbHandlerPermissions + RemoteDbHandlerPermissions + 'static,
+{
+  sqlite: SqliteDbHandler<P>,
+  remote: RemoteDbHandler<P>,
+}
+
+impl<P> MultiBackendDbHandler<P>
+where
+  P: SqliteDbHandlerPermissions + RemoteDbHandlerPermissions,
+{
+  pub fn new(default_storage_dir: Option<PathBuf>) -> Self {
+    Self {
+      sqlite: SqliteDbHandler::new(default_storage_dir),
+      remote: RemoteDbHandler::new(),
+    }
+  }
+}
+
+fn is_remote_path(path: &str) -> bool {
+  path.starts_with("https://") || path.starts_with("http://")
+}
+
+#[async_trait(?Send)]
+impl<P> DatabaseHandler for MultiBackendDbHandler<P>
+where
+  P: SqliteDbHandlerPermissions + RemoteDbHandlerPermissions,
+{
+  type DB = MultiBackendDb;
+
+  async fn open(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+  ) -> Result<Self::DB, AnyError> {
+    if matches!(path.as_deref(), Some(path) if is_remote_path(path)) {
+      let db = self.remote.open(state, path).await?;
+      Ok(MultiBackendDb::Remote(db))
+    } else {
+      let db = self.sqlite.open(state, path).await?;
+      Ok(MultiBackendDb::Sqlite(db))
+    }
+  }
+}
+
+pub enum MultiBackendDb {
+  Sqlite(SqliteDb),
+  Remote(RemoteDb),
+}
+
+pub enum MultiBackendQueueMessageHandle {
+  Sqlite(DequeuedMessage),
+  Remote(RemoteQueueMessageHandle),
+}
+
+pub enum MultiBackendWatcher {
+  Sqlite(SqliteWatcher),
+  Remote(RemoteWatcher),
+}
+
+#[async_trait(?Send)]
+impl Database for MultiBackendDb {
+  type QMH = MultiBackendQueueMessageHandle;
+  type DW = MultiBackendWatcher;
+
+  async fn snapshot_read(
+    &self,
+    requests: Vec<ReadRange>,
+    options: SnapshotReadOptions,
+  ) -> Result<Vec<ReadRangeOutput>, AnyError> {
+    match self {
+      Self::Sqlite(db) => db.snapshot_read(requests, options).await,
+      Self::Remote(db) => db.snapshot_read(requests, options).await,
+    }
+  }
+
+  async fn atomic_write(
+    &self,
+    write: AtomicWrite,
+  ) -> Result<Option<CommitResult>, AnyError> {
+    match self {
+      Self::Sqlite(db) => db.atomic_write(write).await,
+      Self::Remote(db) => db.atomic_write(write).await,
+    }
+  }
+
+  async fn dequeue_next_message(&self) -> Result<Self::QMH, AnyError> {
+    match self {
+      Self::Sqlite(db) => db
+        .dequeue_next_message()
+        .await
+        .map(MultiBackendQueueMessageHandle::Sqlite),
+      Self::Remote(db) => db
+        .dequeue_next_message()
+        .await
+        .map(MultiBackendQueueMessageHandle::Remote),
+    }
+  }
+
+  fn watch(&self, keys: Vec<Vec<u8>>) -> Self::DW {
+    match self {
+      Self::Sqlite(db) => MultiBackendWatcher::Sqlite(db.watch(keys)),
+      Self::Remote(db) => MultiBackendWatcher::Remote(db.watch(keys)),
+    }
+  }
+
+  fn close(&self) {
+    match self {
+      Self::Sqlite(db) => db.close(),
+      Self::Remote(db) => db.close(),
+    }
+  }
+}
+
+#[async_trait(?Send)]
+impl QueueMessageHandle for MultiBackendQueueMessageHandle {
+  async fn take_payload(&mut self) -> Result<Vec<u8>, AnyError> {
+    match self {
+      Self::Sqlite(handle) => handle.take_payload().await,
+      Self::Remote(handle) => handle.take_payload().await,
+    }
+  }
+
+  async fn finish(&self, success: bool) -> Result<(), AnyError> {
+    match self {
+      Self::Sqlite(handle) => handle.finish(success).await,
+      Self::Remote(handle) => handle.finish(success).await,
+    }
+  }
+}
+
+#[async_trait(?Send)]
+impl DatabaseWatcher for MultiBackendWatcher {
+  async fn next(&mut self) -> Result<Option<Vec<Option<KvEntry>>>, AnyError> {
+    match self {
+      Self::Sqlite(watcher) => watcher.next().await,
+      Self::Remote(watcher) => watcher.next().await,
+    }
+  }
+}
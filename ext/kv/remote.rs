@@ -0,0 +1,437 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! A database backend that keeps the data in a remote store, talked to over
+//! HTTP. Every operation is a `POST` of a JSON body to a path under the
+//! database URL, with the access token from `DENO_KV_ACCESS_TOKEN`, if set,
+//! as a bearer token. Keys and bytes are base64 encoded, versionstamps are
+//! hex encoded and `u64` values are decimal strings.
+//!
+//! `snapshot_read` takes `{ "ranges": [{ "start", "end", "limit",
+//! "reverse" }] }` and responds with `{ "ranges": [{ "entries": [{ "key",
+//! "value", "versionstamp" }] }] }`. Values are `{ "kind": "v8" | "bytes" |
+//! "u64", "value" }`.
+//!
+//! `atomic_write` takes `{ "checks": [{ "key", "versionstamp" }],
+//! "mutations": [{ "key", "kind", "value" }] }`, where the kind is one of
+//! `set`, `delete`, `sum`, `min` and `max`. The store must apply the
+//! mutations atomically, and only if all checks pass. It responds with
+//! `{ "ok": true, "versionstamp" }` or, if a check failed, `{ "ok": false }`.
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use deno_core::error::generic_error;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use deno_core::OpState;
+use deno_fetch::reqwest;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::AtomicWrite;
+use crate::CommitResult;
+use crate::Database;
+use crate::DatabaseHandler;
+use crate::DatabaseWatcher;
+use crate::KvEntry;
+use crate::MutationKind;
+use crate::QueueMessageHandle;
+use crate::ReadRange;
+use crate::ReadRangeOutput;
+use crate::SnapshotReadOptions;
+use crate::Value;
+
+const ACCESS_TOKEN_ENV_VAR: &str = "DENO_KV_ACCESS_TOKEN";
+
+/// How often watchers read the watched keys, as the remote store has no way
+/// to push changes.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct RemoteDbHandler<P: RemoteDbHandlerPermissions + 'static> {
+  _permissions: PhantomData<P>,
+}
+
+pub trait RemoteDbHandlerPermissions {
+  fn check_env(&mut self, var: &str) -> Result<(), AnyError>;
+  fn check_net_url(
+    &mut self,
+    url: &Url,
+    api_name: &str,
+  ) -> Result<(), AnyError>;
+}
+
+impl<P: RemoteDbHandlerPermissions> RemoteDbHandler<P> {
+  pub fn new() -> Self {
+    Self {
+      _permissions: PhantomData,
+    }
+  }
+}
+
+impl<P: RemoteDbHandlerPermissions> Default for RemoteDbHandler<P> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[async_trait(?Send)]
+impl<P: RemoteDbHandlerPermissions> DatabaseHandler for RemoteDbHandler<P> {
+  type DB = RemoteDb;
+
+  async fn open(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+  ) -> Result<Self::DB, AnyError> {
+    let Some(path) = path else {
+      return Err(type_error("Missing database URL"));
+    };
+    let url = Url::parse(&path)
+      .map_err(|_| type_error(format!("Invalid database URL: {path}")))?;
+    if !matches!(url.scheme(), "http" | "https") {
+      return Err(type_error(format!(
+        "Unsupported database URL scheme: {}",
+        url.scheme()
+      )));
+    }
+
+    let mut state = state.borrow_mut();
+    let permissions = state.borrow_mut::<P>();
+    permissions.check_net_url(&url, "Deno.openKv")?;
+    permissions.check_env(ACCESS_TOKEN_ENV_VAR)?;
+    let access_token = std::env::var(ACCESS_TOKEN_ENV_VAR).ok();
+    let client = deno_fetch::get_or_create_client_from_state(&mut state)?;
+
+    Ok(RemoteDb {
+      client,
+      url,
+      access_token,
+      closed: Default::default(),
+    })
+  }
+}
+
+#[derive(Clone)]
+pub struct RemoteDb {
+  client: reqwest::Client,
+  url: Url,
+  access_token: Option<String>,
+  closed: Rc<Cell<bool>>,
+}
+
+impl RemoteDb {
+  async fn call<Req: Serialize, Res: for<'de> Deserialize<'de>>(
+    &self,
+    method: &str,
+    body: &Req,
+  ) -> Result<Res, AnyError> {
+    let mut url = self.url.clone();
+    url
+      .path_segments_mut()
+      .map_err(|_| type_error("Invalid database URL"))?
+      .pop_if_empty()
+      .push(method);
+    let mut request = self
+      .client
+      .post(url)
+      .header(reqwest::header::CONTENT_TYPE, "application/json")
+      .body(serde_json::to_vec(body)?);
+    if let Some(access_token) = &self.access_token {
+      request = request.bearer_auth(access_token);
+    }
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+      let message = response.text().await.unwrap_or_default();
+      return Err(generic_error(format!(
+        "Remote database request '{method}' failed with status {status}: {message}"
+      )));
+    }
+    Ok(serde_json::from_slice(&response.bytes().await?)?)
+  }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
+enum RemoteValue {
+  V8(String),
+  Bytes(String),
+  U64(String),
+}
+
+impl From<&Value> for RemoteValue {
+  fn from(value: &Value) -> Self {
+    match value {
+      Value::V8(buf) => RemoteValue::V8(base64::encode(buf)),
+      Value::Bytes(buf) => RemoteValue::Bytes(base64::encode(buf)),
+      Value::U64(n) => RemoteValue::U64(n.to_string()),
+    }
+  }
+}
+
+impl TryFrom<RemoteValue> for Value {
+  type Error = AnyError;
+  fn try_from(value: RemoteValue) -> Result<Self, AnyError> {
+    Ok(match value {
+      RemoteValue::V8(buf) => Value::V8(base64::decode(buf)?),
+      RemoteValue::Bytes(buf) => Value::Bytes(base64::decode(buf)?),
+      RemoteValue::U64(n) => Value::U64(n.parse()?),
+    })
+  }
+}
+
+fn decode_versionstamp(versionstamp: &str) -> Result<[u8; 10], AnyError> {
+  let mut out = [0u8; 10];
+  hex::decode_to_slice(versionstamp, &mut out)
+    .map_err(|_| type_error("Invalid versionstamp from remote database"))?;
+  Ok(out)
+}
+
+#[derive(Serialize)]
+struct SnapshotReadRequest {
+  ranges: Vec<RemoteReadRange>,
+}
+
+#[derive(Serialize)]
+struct RemoteReadRange {
+  start: String,
+  end: String,
+  limit: u32,
+  reverse: bool,
+}
+
+#[derive(Deserialize)]
+struct SnapshotReadResponse {
+  ranges: Vec<RemoteReadRangeOutput>,
+}
+
+#[derive(Deserialize)]
+struct RemoteReadRangeOutput {
+  entries: Vec<RemoteKvEntry>,
+}
+
+#[derive(Deserialize)]
+struct RemoteKvEntry {
+  key: String,
+  value: RemoteValue,
+  versionstamp: String,
+}
+
+impl TryFrom<RemoteKvEntry> for KvEntry {
+  type Error = AnyError;
+  fn try_from(entry: RemoteKvEntry) -> Result<Self, AnyError> {
+    Ok(KvEntry {
+      key: base64::decode(entry.key)?,
+      value: entry.value.try_into()?,
+      versionstamp: decode_versionstamp(&entry.versionstamp)?,
+    })
+  }
+}
+
+#[derive(Serialize)]
+struct AtomicWriteRequest {
+  checks: Vec<RemoteCheck>,
+  mutations: Vec<RemoteMutation>,
+}
+
+#[derive(Serialize)]
+struct RemoteCheck {
+  key: String,
+  versionstamp: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RemoteMutation {
+  key: String,
+  kind: &'static str,
+  value: Option<RemoteValue>,
+}
+
+#[derive(Deserialize)]
+struct AtomicWriteResponse {
+  ok: bool,
+  versionstamp: Option<String>,
+}
+
+#[async_trait(?Send)]
+impl Database for RemoteDb {
+  type QMH = RemoteQueueMessageHandle;
+  type DW = RemoteWatcher;
+
+  async fn snapshot_read(
+    &self,
+    requests: Vec<ReadRange>,
+    _options: SnapshotReadOptions,
+  ) -> Result<Vec<ReadRangeOutput>, AnyError> {
+    let expected_ranges = requests.len();
+    let request = SnapshotReadRequest {
+      ranges: requests
+        .into_iter()
+        .map(|range| RemoteReadRange {
+          start: base64::encode(range.start),
+          end: base64::encode(range.end),
+          limit: range.limit.get(),
+          reverse: range.reverse,
+        })
+        .collect(),
+    };
+    let response: SnapshotReadResponse =
+      self.call("snapshot_read", &request).await?;
+    if response.ranges.len() != expected_ranges {
+      return Err(generic_error(
+        "Remote database returned the wrong number of ranges",
+      ));
+    }
+    response
+      .ranges
+      .into_iter()
+      .map(|range| {
+        Ok(ReadRangeOutput {
+          entries: range
+            .entries
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<_, AnyError>>()?,
+        })
+      })
+      .collect()
+  }
+
+  async fn atomic_write(
+    &self,
+    write: AtomicWrite,
+  ) -> Result<Option<CommitResult>, AnyError> {
+    if !write.enqueues.is_empty() {
+      return Err(type_error("Queues are not supported by remote databases"));
+    }
+    let request = AtomicWriteRequest {
+      checks: write
+        .checks
+        .into_iter()
+        .map(|check| RemoteCheck {
+          key: base64::encode(check.key),
+          versionstamp: check.versionstamp.map(hex::encode),
+        })
+        .collect(),
+      mutations: write
+        .mutations
+        .into_iter()
+        .map(|mutation| {
+          let kind = match mutation.kind {
+            MutationKind::Set(_) => "set",
+            MutationKind::Delete => "delete",
+            MutationKind::Sum(_) => "sum",
+            MutationKind::Min(_) => "min",
+            MutationKind::Max(_) => "max",
+          };
+          RemoteMutation {
+            value: mutation.kind.value().map(Into::into),
+            key: base64::encode(mutation.key),
+            kind,
+          }
+        })
+        .collect(),
+    };
+    let response: AtomicWriteResponse =
+      self.call("atomic_write", &request).await?;
+    if !response.ok {
+      return Ok(None);
+    }
+    let Some(versionstamp) = response.versionstamp else {
+      return Err(generic_error(
+        "Remote database didn't return the versionstamp of the commit",
+      ));
+    };
+    Ok(Some(CommitResult {
+      versionstamp: decode_versionstamp(&versionstamp)?,
+    }))
+  }
+
+  async fn dequeue_next_message(&self) -> Result<Self::QMH, AnyError> {
+    Err(type_error("Queues are not supported by remote databases"))
+  }
+
+  fn watch(&self, keys: Vec<Vec<u8>>) -> Self::DW {
+    RemoteWatcher {
+      db: self.clone(),
+      keys,
+      versionstamps: None,
+    }
+  }
+
+  fn close(&self) {
+    self.closed.set(true);
+  }
+}
+
+/// Remote databases have no queues, so there are never messages to handle.
+pub enum RemoteQueueMessageHandle {}
+
+#[async_trait(?Send)]
+impl QueueMessageHandle for RemoteQueueMessageHandle {
+  async fn take_payload(&mut self) -> Result<Vec<u8>, AnyError> {
+    match *self {}
+  }
+
+  async fn finish(&self, _success: bool) -> Result<(), AnyError> {
+    match *self {}
+  }
+}
+
+pub struct RemoteWatcher {
+  db: RemoteDb,
+  keys: Vec<Vec<u8>>,
+  /// The versionstamps of the entries last returned.
+  versionstamps: Option<Vec<Option<[u8; 10]>>>,
+}
+
+#[async_trait(?Send)]
+impl DatabaseWatcher for RemoteWatcher {
+  async fn next(&mut self) -> Result<Option<Vec<Option<KvEntry>>>, AnyError> {
+    loop {
+      if self.versionstamps.is_some() {
+        tokio::time::sleep(WATCH_POLL_INTERVAL).await;
+      }
+      if self.db.closed.get() {
+        return Ok(None);
+      }
+      let ranges = self
+        .keys
+        .iter()
+        .map(|key| {
+          let mut end = key.clone();
+          end.push(0);
+          ReadRange {
+            start: key.clone(),
+            end,
+            limit: 1.try_into().unwrap(),
+            reverse: false,
+          }
+        })
+        .collect();
+      let options = SnapshotReadOptions {
+        consistency: crate::Consistency::Strong,
+      };
+      let entries = self
+        .db
+        .snapshot_read(ranges, options)
+        .await?
+        .into_iter()
+        .map(|range| range.entries.into_iter().next())
+        .collect::<Vec<_>>();
+      let versionstamps = entries
+        .iter()
+        .map(|entry| entry.as_ref().map(|entry| entry.versionstamp))
+        .collect::<Vec<_>>();
+      if self.versionstamps.as_ref() != Some(&versionstamps) {
+        self.versionstamps = Some(versionstamps);
+        return Ok(Some(entries));
+      }
+    }
+  }
+}
@@ -36,6 +36,7 @@ use crate::AtomicWrite;
 use crate::CommitResult;
 use crate::Database;
 use crate::DatabaseHandler;
+use crate::DatabaseWatcher;
 use crate::KvEntry;
 use crate::MutationKind;
 use crate::QueueMessageHandle;
@@ -46,6 +47,8 @@ use crate::Value;
 
 const STATEMENT_INC_AND_GET_DATA_VERSION: &str =
   "update data_version set version = version + 1 where k = 0 returning version";
+const STATEMENT_GET_DATA_VERSION: &str =
+  "select version from data_version where k = 0";
 const STATEMENT_KV_RANGE_SCAN: &str =
   "select k, v, v_encoding, version from kv where k >= ? and k < ? order by k asc limit ?";
 const STATEMENT_KV_RANGE_SCAN_REVERSE: &str =
@@ -54,6 +57,8 @@ const STATEMENT_KV_POINT_GET_VALUE_ONLY: &str =
   "select v, v_encoding from kv where k = ?";
 const STATEMENT_KV_POINT_GET_VERSION_ONLY: &str =
   "select version from kv where k = ?";
+const STATEMENT_KV_POINT_GET: &str =
+  "select v, v_encoding, version from kv where k = ?";
 const STATEMENT_KV_POINT_SET: &str =
   "insert into kv (k, v, v_encoding, version) values (:k, :v, :v_encoding, :version) on conflict(k) do update set v = :v, v_encoding = :v_encoding, version = :version";
 const STATEMENT_KV_POINT_DELETE: &str = "delete from kv where k = ?";
@@ -114,6 +119,9 @@ create table queue_running(
 ];
 
 const DISPATCH_CONCURRENCY_LIMIT: usize = 100;
+/// How often watchers check for writes they weren't told about, which are
+/// those made through other connections to the database.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
 const DEFAULT_BACKOFF_SCHEDULE: [u32; 5] = [100, 1000, 5000, 30000, 60000];
 
 pub struct SqliteDbHandler<P: SqliteDbHandlerPermissions + 'static> {
@@ -214,6 +222,7 @@ impl<P: SqliteDbHandlerPermissions> DatabaseHandler for SqliteDbHandler<P> {
     Ok(SqliteDb {
       conn: Rc::new(AsyncRefCell::new(Cell::new(Some(conn)))),
       queue: OnceCell::new(),
+      changes: watch::channel(false).0,
     })
   }
 }
@@ -221,6 +230,9 @@ impl<P: SqliteDbHandlerPermissions> DatabaseHandler for SqliteDbHandler<P> {
 pub struct SqliteDb {
   conn: Rc<AsyncRefCell<Cell<Option<rusqlite::Connection>>>>,
   queue: OnceCell<SqliteQueue>,
+  /// Notified of every write through this connection. Holds whether the
+  /// database was closed.
+  changes: watch::Sender<bool>,
 }
 
 impl SqliteDb {
@@ -304,6 +316,84 @@ impl QueueMessageHandle for DequeuedMessage {
   }
 }
 
+pub struct SqliteWatcher {
+  conn: Weak<AsyncRefCell<Cell<Option<rusqlite::Connection>>>>,
+  keys: Vec<Vec<u8>>,
+  changes: watch::Receiver<bool>,
+  /// The data version the watched keys were last read at.
+  data_version: Option<i64>,
+  /// The versionstamps of the entries last returned.
+  versionstamps: Option<Vec<Option<[u8; 10]>>>,
+}
+
+#[async_trait(?Send)]
+impl DatabaseWatcher for SqliteWatcher {
+  async fn next(&mut self) -> Result<Option<Vec<Option<KvEntry>>>, AnyError> {
+    loop {
+      if *self.changes.borrow_and_update() {
+        return Ok(None);
+      }
+      let Some(conn) = self.conn.upgrade() else {
+        return Ok(None);
+      };
+
+      // Every write bumps the data version, so the keys only need to be read
+      // again when it changed.
+      let keys = self.keys.clone();
+      let last_data_version = self.data_version;
+      let (data_version, entries) = SqliteDb::run_tx(conn, move |tx| {
+        let data_version: i64 = tx
+          .prepare_cached(STATEMENT_GET_DATA_VERSION)?
+          .query_row([], |row| row.get(0))?;
+        if Some(data_version) == last_data_version {
+          return Ok((data_version, None));
+        }
+        let mut stmt = tx.prepare_cached(STATEMENT_KV_POINT_GET)?;
+        let entries = keys
+          .into_iter()
+          .map(|key| {
+            stmt
+              .query_row([key.as_slice()], |row| {
+                let value: Vec<u8> = row.get(0)?;
+                let encoding: i64 = row.get(1)?;
+                let version: i64 = row.get(2)?;
+                Ok(KvEntry {
+                  key: key.clone(),
+                  value: decode_value(value, encoding),
+                  versionstamp: version_to_versionstamp(version),
+                })
+              })
+              .optional()
+          })
+          .collect::<Result<Vec<_>, rusqlite::Error>>()?;
+        Ok((data_version, Some(entries)))
+      })
+      .await?;
+      self.data_version = Some(data_version);
+
+      if let Some(entries) = entries {
+        let versionstamps = entries
+          .iter()
+          .map(|entry| entry.as_ref().map(|entry| entry.versionstamp))
+          .collect::<Vec<_>>();
+        if self.versionstamps.as_ref() != Some(&versionstamps) {
+          self.versionstamps = Some(versionstamps);
+          return Ok(Some(entries));
+        }
+      }
+
+      tokio::select! {
+        changed = self.changes.changed() => {
+          if changed.is_err() {
+            return Ok(None);
+          }
+        }
+        _ = tokio::time::sleep(WATCH_POLL_INTERVAL) => {}
+      }
+    }
+  }
+}
+
 type DequeueReceiver = mpsc::Receiver<(Vec<u8>, String)>;
 
 struct SqliteQueue {
@@ -573,6 +663,7 @@ impl SqliteQueue {
 #[async_trait(?Send)]
 impl Database for SqliteDb {
   type QMH = DequeuedMessage;
+  type DW = SqliteWatcher;
 
   async fn snapshot_read(
     &self,
@@ -727,6 +818,9 @@ impl Database for SqliteDb {
       })
       .await?;
 
+    if commit_result.is_some() {
+      self.changes.send_modify(|_| {});
+    }
     if has_enqueues {
       if let Some(queue) = self.queue.get() {
         queue.wake().await?;
@@ -744,10 +838,21 @@ impl Database for SqliteDb {
     Ok(handle)
   }
 
+  fn watch(&self, keys: Vec<Vec<u8>>) -> Self::DW {
+    SqliteWatcher {
+      conn: Rc::downgrade(&self.conn),
+      keys,
+      changes: self.changes.subscribe(),
+      data_version: None,
+      versionstamps: None,
+    }
+  }
+
   fn close(&self) {
     if let Some(queue) = self.queue.get() {
       queue.shutdown();
     }
+    self.changes.send_replace(true);
   }
 }
 
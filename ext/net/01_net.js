@@ -17,6 +17,7 @@ const {
   Error,
   ObjectPrototypeIsPrototypeOf,
   PromiseResolve,
+  StringPrototypeIncludes,
   SymbolAsyncIterator,
   SymbolFor,
   TypeError,
@@ -168,6 +169,22 @@ class TcpConn extends Conn {
   setKeepAlive(keepAlive = true) {
     return ops.op_set_keepalive(this.rid, keepAlive);
   }
+
+  setSendBufferSize(size) {
+    return ops.op_set_send_buffer_size(this.rid, size);
+  }
+
+  setRecvBufferSize(size) {
+    return ops.op_set_recv_buffer_size(this.rid, size);
+  }
+
+  sendBufferSize() {
+    return ops.op_get_send_buffer_size(this.rid);
+  }
+
+  recvBufferSize() {
+    return ops.op_get_recv_buffer_size(this.rid);
+  }
 }
 
 class UnixConn extends Conn {}
@@ -311,6 +328,7 @@ class Datagram {
         core.opAsync(
           "op_net_set_multi_ttl_udp",
           this.rid,
+          true,
           ttl,
         ),
     };
@@ -339,9 +357,42 @@ class Datagram {
           false,
           loopback,
         ),
+      setTTL: (ttl) =>
+        core.opAsync(
+          "op_net_set_multi_ttl_udp",
+          this.rid,
+          false,
+          ttl,
+        ),
     };
   }
 
+  setMulticastLoopback(loopback) {
+    return core.opAsync(
+      "op_net_set_multi_loopback_udp",
+      this.rid,
+      this.#isV4(),
+      loopback,
+    );
+  }
+
+  setMulticastTTL(ttl) {
+    return core.opAsync(
+      "op_net_set_multi_ttl_udp",
+      this.rid,
+      this.#isV4(),
+      ttl,
+    );
+  }
+
+  setBroadcast(broadcast) {
+    return core.opAsync("op_net_set_broadcast_udp", this.rid, broadcast);
+  }
+
+  #isV4() {
+    return !StringPrototypeIncludes(this.addr.hostname, ":");
+  }
+
   async receive(p) {
     const buf = p || new Uint8Array(this.bufSize);
     let nread;
@@ -421,7 +472,7 @@ function listen(args) {
       const { 0: rid, 1: addr } = ops.op_net_listen_tcp({
         hostname: args.hostname ?? "0.0.0.0",
         port: args.port,
-      }, args.reusePort);
+      }, args.reusePort, args.sendBufferSize, args.recvBufferSize);
       addr.transport = "tcp";
       return new Listener(rid, addr);
     }
@@ -449,6 +500,7 @@ function createListenDatagram(udpOpFn, unixOpFn) {
           },
           args.reuseAddress ?? false,
           args.loopback ?? false,
+          args.broadcast ?? true,
         );
         addr.transport = "udp";
         return new Datagram(rid, addr);
@@ -476,6 +528,9 @@ async function connect(args) {
           hostname: args.hostname ?? "127.0.0.1",
           port: args.port,
         },
+        args.happyEyeballs,
+        args.happyEyeballsDelay,
+        args.resolver,
       );
       localAddr.transport = "tcp";
       remoteAddr.transport = "tcp";
@@ -29,6 +29,9 @@ async function connectTls({
   certChain = undefined,
   privateKey = undefined,
   alpnProtocols = undefined,
+  happyEyeballs = undefined,
+  happyEyeballsDelay = undefined,
+  resolver = undefined,
 }) {
   if (transport !== "tcp") {
     throw new TypeError(`Unsupported transport: '${transport}'`);
@@ -36,7 +39,16 @@ async function connectTls({
   const { 0: rid, 1: localAddr, 2: remoteAddr } = await core.opAsync(
     "op_net_connect_tls",
     { hostname, port },
-    { certFile, caCerts, certChain, privateKey, alpnProtocols },
+    {
+      certFile,
+      caCerts,
+      certChain,
+      privateKey,
+      alpnProtocols,
+      happyEyeballs,
+      happyEyeballsDelay,
+      resolver,
+    },
   );
   localAddr.transport = "tcp";
   remoteAddr.transport = "tcp";
@@ -65,13 +77,14 @@ function listenTls({
   transport = "tcp",
   alpnProtocols = undefined,
   reusePort = false,
+  sni = undefined,
 }) {
   if (transport !== "tcp") {
     throw new TypeError(`Unsupported transport: '${transport}'`);
   }
   const { 0: rid, 1: localAddr } = ops.op_net_listen_tls(
     { hostname, port },
-    { cert, certFile, key, keyFile, alpnProtocols, reusePort },
+    { cert, certFile, key, keyFile, alpnProtocols, reusePort, sni },
   );
   return new TlsListener(rid, localAddr);
 }
@@ -0,0 +1,134 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::futures::stream::FuturesUnordered;
+use deno_core::futures::StreamExt;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+/// The "Connection Attempt Delay" recommended by RFC 8305.
+pub const DEFAULT_CONNECTION_ATTEMPT_DELAY: Duration =
+  Duration::from_millis(250);
+
+/// Connects to the first of `addrs` that accepts a connection.
+///
+/// With a `delay`, the addresses are tried as described by RFC 8305 ("Happy
+/// Eyeballs"): address families are interleaved, and a new attempt is started
+/// whenever the previous one fails or hasn't completed within `delay`, while
+/// the earlier attempts keep going. This way a host with a broken IPv6 route
+/// doesn't have to wait for the IPv6 connection to time out. Without a
+/// `delay`, the addresses are tried one after another in the resolved order.
+pub async fn connect(
+  addrs: impl IntoIterator<Item = SocketAddr>,
+  delay: Option<Duration>,
+) -> io::Result<TcpStream> {
+  let mut last_err = None;
+
+  let Some(delay) = delay else {
+    for addr in addrs {
+      match TcpStream::connect(addr).await {
+        Ok(stream) => return Ok(stream),
+        Err(err) => last_err = Some(err),
+      }
+    }
+    return Err(last_err.unwrap_or_else(no_addresses));
+  };
+
+  let mut addrs = interleave_families(addrs).into_iter();
+  let mut attempts = FuturesUnordered::new();
+  let mut next_addr = addrs.next();
+  loop {
+    if let Some(addr) = next_addr.take() {
+      attempts.push(TcpStream::connect(addr));
+    }
+    if attempts.is_empty() {
+      return Err(last_err.unwrap_or_else(no_addresses));
+    }
+    let has_more_addrs = !addrs.as_slice().is_empty();
+    tokio::select! {
+      Some(result) = attempts.next() => match result {
+        Ok(stream) => return Ok(stream),
+        Err(err) => {
+          last_err = Some(err);
+          next_addr = addrs.next();
+        }
+      },
+      _ = tokio::time::sleep(delay), if has_more_addrs => {
+        next_addr = addrs.next();
+      }
+    }
+  }
+}
+
+/// Orders addresses alternating between address families, starting with the
+/// family of the first address, as the resolver's preference.
+fn interleave_families(
+  addrs: impl IntoIterator<Item = SocketAddr>,
+) -> Vec<SocketAddr> {
+  let addrs = addrs.into_iter().collect::<Vec<_>>();
+  let Some(first) = addrs.first() else {
+    return addrs;
+  };
+  let first_is_ipv4 = first.is_ipv4();
+  let (preferred, fallback): (Vec<_>, Vec<_>) = addrs
+    .iter()
+    .partition(|addr| addr.is_ipv4() == first_is_ipv4);
+  let mut preferred = preferred.into_iter();
+  let mut fallback = fallback.into_iter();
+  let mut ordered = Vec::with_capacity(addrs.len());
+  loop {
+    match (preferred.next(), fallback.next()) {
+      (None, None) => break,
+      (a, b) => ordered.extend(a.into_iter().chain(b).copied()),
+    }
+  }
+  ordered
+}
+
+fn no_addresses() -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidInput, "No resolved address found")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tokio::net::TcpListener;
+
+  #[test]
+  fn interleave_families_alternates() {
+    let addrs: Vec<SocketAddr> = [
+      "[::1]:80",
+      "[::2]:80",
+      "[::3]:80",
+      "127.0.0.1:80",
+      "127.0.0.2:80",
+    ]
+    .iter()
+    .map(|addr| addr.parse().unwrap())
+    .collect();
+    let ordered = interleave_families(addrs.clone());
+    assert_eq!(
+      ordered,
+      vec![addrs[0], addrs[3], addrs[1], addrs[4], addrs[2]]
+    );
+  }
+
+  #[tokio::test]
+  async fn connect_skips_unreachable_address() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    // Nothing listens on the port of a dropped listener.
+    let closed_addr = TcpListener::bind("127.0.0.1:0")
+      .await
+      .unwrap()
+      .local_addr()
+      .unwrap();
+
+    for delay in [None, Some(DEFAULT_CONNECTION_ATTEMPT_DELAY)] {
+      let stream = connect([closed_addr, addr], delay).await.unwrap();
+      assert_eq!(stream.peer_addr().unwrap(), addr);
+    }
+    assert!(connect(Vec::new(), None).await.is_err());
+  }
+}
@@ -99,6 +99,13 @@ impl Resource for TcpStreamResource {
     "tcpStream".into()
   }
 
+  #[cfg(unix)]
+  fn backing_fd(self: Rc<Self>) -> Option<std::os::unix::prelude::RawFd> {
+    use std::os::unix::io::AsRawFd;
+    let wr = RcRef::map(self, |r| &r.wr).try_borrow()?;
+    Some(wr.as_ref().as_ref().as_raw_fd())
+  }
+
   fn shutdown(self: Rc<Self>) -> AsyncResult<()> {
     Box::pin(self.shutdown())
   }
@@ -121,11 +128,39 @@ impl TcpStreamResource {
       .map_socket(Box::new(move |socket| Ok(socket.set_keepalive(keepalive)?)))
   }
 
-  #[allow(clippy::type_complexity)]
-  fn map_socket(
+  pub fn set_send_buffer_size(
     self: Rc<Self>,
-    map: Box<dyn FnOnce(SockRef) -> Result<(), AnyError>>,
+    size: usize,
   ) -> Result<(), AnyError> {
+    self.map_socket(Box::new(move |socket| {
+      Ok(socket.set_send_buffer_size(size)?)
+    }))
+  }
+
+  pub fn set_recv_buffer_size(
+    self: Rc<Self>,
+    size: usize,
+  ) -> Result<(), AnyError> {
+    self.map_socket(Box::new(move |socket| {
+      Ok(socket.set_recv_buffer_size(size)?)
+    }))
+  }
+
+  /// The size the kernel actually uses, which may differ from the requested
+  /// one (Linux doubles it for bookkeeping).
+  pub fn send_buffer_size(self: Rc<Self>) -> Result<usize, AnyError> {
+    self.map_socket(Box::new(|socket| Ok(socket.send_buffer_size()?)))
+  }
+
+  pub fn recv_buffer_size(self: Rc<Self>) -> Result<usize, AnyError> {
+    self.map_socket(Box::new(|socket| Ok(socket.recv_buffer_size()?)))
+  }
+
+  #[allow(clippy::type_complexity)]
+  fn map_socket<R>(
+    self: Rc<Self>,
+    map: Box<dyn FnOnce(SockRef) -> Result<R, AnyError>>,
+  ) -> Result<R, AnyError> {
     if let Some(wr) = RcRef::map(self, |r| &r.wr).try_borrow() {
       let stream = wr.as_ref().as_ref();
       let socket = socket2::SockRef::from(stream);
@@ -168,6 +203,13 @@ impl Resource for UnixStreamResource {
     "unixStream".into()
   }
 
+  #[cfg(unix)]
+  fn backing_fd(self: Rc<Self>) -> Option<std::os::unix::prelude::RawFd> {
+    use std::os::unix::io::AsRawFd;
+    let wr = RcRef::map(self, |r| &r.wr).try_borrow()?;
+    Some(wr.as_ref().as_ref().as_raw_fd())
+  }
+
   fn shutdown(self: Rc<Self>) -> AsyncResult<()> {
     Box::pin(self.shutdown())
   }
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+pub mod happy_eyeballs;
 pub mod io;
 pub mod ops;
 pub mod ops_tls;
@@ -7,7 +8,11 @@ pub mod ops_tls;
 pub mod ops_unix;
 pub mod raw;
 pub mod resolve_addr;
+pub mod resolver;
+pub mod sni;
 
+use crate::resolver::DefaultDnsConfig;
+use crate::resolver::DnsConfig;
 use deno_core::error::AnyError;
 use deno_core::OpState;
 use deno_tls::rustls::RootCertStore;
@@ -104,9 +109,14 @@ deno_core::extension!(deno_net,
     ops::op_net_leave_multi_v6_udp,
     ops::op_net_set_multi_loopback_udp,
     ops::op_net_set_multi_ttl_udp,
+    ops::op_net_set_broadcast_udp,
     ops::op_dns_resolve<P>,
     ops::op_set_nodelay,
     ops::op_set_keepalive,
+    ops::op_set_send_buffer_size,
+    ops::op_set_recv_buffer_size,
+    ops::op_get_send_buffer_size,
+    ops::op_get_recv_buffer_size,
 
     ops_tls::op_tls_start<P>,
     ops_tls::op_net_connect_tls<P>,
@@ -127,6 +137,7 @@ deno_core::extension!(deno_net,
     root_cert_store_provider: Option<Arc<dyn RootCertStoreProvider>>,
     unstable: bool,
     unsafely_ignore_certificate_errors: Option<Vec<String>>,
+    dns_config: Option<DnsConfig>,
   },
   state = |state, options| {
     state.put(DefaultTlsOptions {
@@ -136,5 +147,6 @@ deno_core::extension!(deno_net,
     state.put(UnsafelyIgnoreCertificateErrors(
       options.unsafely_ignore_certificate_errors,
     ));
+    state.put(DefaultDnsConfig(options.dns_config));
   },
 );
@@ -1,8 +1,13 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::happy_eyeballs;
 use crate::io::TcpStreamResource;
 use crate::resolve_addr::resolve_addr;
 use crate::resolve_addr::resolve_addr_sync;
+use crate::resolver::default_dns_config;
+use crate::resolver::get_or_create_resolver;
+use crate::resolver::DnsConfig;
+use crate::resolver::NameServer;
 use crate::NetPermissions;
 use deno_core::error::bad_resource;
 use deno_core::error::custom_error;
@@ -33,18 +38,13 @@ use std::net::Ipv6Addr;
 use std::net::SocketAddr;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::time::Duration;
 use tokio::net::TcpListener;
-use tokio::net::TcpStream;
 use tokio::net::UdpSocket;
 use trust_dns_proto::rr::rdata::caa::Value;
 use trust_dns_proto::rr::record_data::RData;
 use trust_dns_proto::rr::record_type::RecordType;
-use trust_dns_resolver::config::NameServerConfigGroup;
-use trust_dns_resolver::config::ResolverConfig;
-use trust_dns_resolver::config::ResolverOpts;
 use trust_dns_resolver::error::ResolveErrorKind;
-use trust_dns_resolver::system_conf;
-use trust_dns_resolver::AsyncResolver;
 
 #[derive(Serialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -158,6 +158,57 @@ where
   Ok(nwritten)
 }
 
+/// Resolves the address of `Deno.connect()` or `Deno.connectTls()` with the
+/// given resolver, the runtime's default resolver, or the system's resolver,
+/// in that order.
+pub(crate) async fn resolve_connect_addr<NP>(
+  state: &Rc<RefCell<OpState>>,
+  hostname: &str,
+  port: u16,
+  resolver: Option<DnsConfig>,
+  api_name: &str,
+) -> Result<Vec<SocketAddr>, AnyError>
+where
+  NP: NetPermissions + 'static,
+{
+  let resolver = {
+    let mut state = state.borrow_mut();
+    if let Some(config) = resolver {
+      let resolver = get_or_create_resolver(&mut state, &config)?;
+      resolver.check_name_servers(state.borrow_mut::<NP>(), api_name)?;
+      Some(resolver)
+    } else if let Some(config) = default_dns_config(&state) {
+      Some(get_or_create_resolver(&mut state, &config)?)
+    } else {
+      None
+    }
+  };
+  let addrs = match resolver {
+    Some(resolver) => resolver.resolve_addr(hostname, port).await?,
+    None => resolve_addr(hostname, port).await?.collect(),
+  };
+  if addrs.is_empty() {
+    return Err(generic_error("No resolved address found"));
+  }
+  Ok(addrs)
+}
+
+/// The delay between connection attempts of `Deno.connect()` and
+/// `Deno.connectTls()`, or `None` if Happy Eyeballs is disabled.
+pub(crate) fn connection_attempt_delay(
+  happy_eyeballs: Option<bool>,
+  happy_eyeballs_delay: Option<u64>,
+) -> Option<Duration> {
+  if happy_eyeballs == Some(false) {
+    return None;
+  }
+  Some(
+    happy_eyeballs_delay
+      .map(Duration::from_millis)
+      .unwrap_or(happy_eyeballs::DEFAULT_CONNECTION_ATTEMPT_DELAY),
+  )
+}
+
 #[op]
 async fn op_net_join_multi_v4_udp(
   state: Rc<RefCell<OpState>>,
@@ -271,6 +322,7 @@ async fn op_net_set_multi_loopback_udp(
 async fn op_net_set_multi_ttl_udp(
   state: Rc<RefCell<OpState>>,
   rid: ResourceId,
+  is_v4_membership: bool,
   ttl: u32,
 ) -> Result<(), AnyError> {
   let resource = state
@@ -280,7 +332,30 @@ async fn op_net_set_multi_ttl_udp(
     .map_err(|_| bad_resource("Socket has been closed"))?;
   let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
 
-  socket.set_multicast_ttl_v4(ttl)?;
+  if is_v4_membership {
+    socket.set_multicast_ttl_v4(ttl)?;
+  } else {
+    // tokio has no setter for the IPv6 hop limit.
+    socket2::SockRef::from(&*socket).set_multicast_hops_v6(ttl)?;
+  }
+
+  Ok(())
+}
+
+#[op]
+async fn op_net_set_broadcast_udp(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  broadcast: bool,
+) -> Result<(), AnyError> {
+  let resource = state
+    .borrow_mut()
+    .resource_table
+    .get::<UdpSocketResource>(rid)
+    .map_err(|_| bad_resource("Socket has been closed"))?;
+  let socket = RcRef::map(&resource, |r| &r.socket).borrow().await;
+
+  socket.set_broadcast(broadcast)?;
 
   Ok(())
 }
@@ -289,10 +364,19 @@ async fn op_net_set_multi_ttl_udp(
 pub async fn op_net_connect_tcp<NP>(
   state: Rc<RefCell<OpState>>,
   addr: IpAddr,
+  happy_eyeballs: Option<bool>,
+  happy_eyeballs_delay: Option<u64>,
+  resolver: Option<DnsConfig>,
 ) -> Result<(ResourceId, IpAddr, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
 {
+  if happy_eyeballs.is_some() || happy_eyeballs_delay.is_some() {
+    super::check_unstable2(&state, "ConnectOptions.happyEyeballs");
+  }
+  if resolver.is_some() {
+    super::check_unstable2(&state, "ConnectOptions.resolver");
+  }
   {
     let mut state_ = state.borrow_mut();
     state_
@@ -300,11 +384,16 @@ where
       .check_net(&(&addr.hostname, Some(addr.port)), "Deno.connect()")?;
   }
 
-  let addr = resolve_addr(&addr.hostname, addr.port)
-    .await?
-    .next()
-    .ok_or_else(|| generic_error("No resolved address found"))?;
-  let tcp_stream = TcpStream::connect(&addr).await?;
+  let addrs = resolve_connect_addr::<NP>(
+    &state,
+    &addr.hostname,
+    addr.port,
+    resolver,
+    "Deno.connect()",
+  )
+  .await?;
+  let delay = connection_attempt_delay(happy_eyeballs, happy_eyeballs_delay);
+  let tcp_stream = happy_eyeballs::connect(addrs, delay).await?;
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
 
@@ -351,6 +440,8 @@ fn op_net_listen_tcp<NP>(
   state: &mut OpState,
   addr: IpAddr,
   reuse_port: bool,
+  send_buffer_size: Option<usize>,
+  recv_buffer_size: Option<usize>,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
@@ -358,6 +449,12 @@ where
   if reuse_port {
     super::check_unstable(state, "Deno.listen({ reusePort: true })");
   }
+  if send_buffer_size.is_some() || recv_buffer_size.is_some() {
+    super::check_unstable(
+      state,
+      "Deno.listen({ sendBufferSize, recvBufferSize })",
+    );
+  }
   state
     .borrow_mut::<NP>()
     .check_net(&(&addr.hostname, Some(addr.port)), "Deno.listen()")?;
@@ -376,6 +473,14 @@ where
     #[cfg(target_os = "linux")]
     socket.set_reuse_port(true)?;
   }
+  // Set before listening, so accepted connections inherit the sizes and the
+  // TCP window scale is negotiated accordingly.
+  if let Some(size) = send_buffer_size {
+    socket.set_send_buffer_size(size)?;
+  }
+  if let Some(size) = recv_buffer_size {
+    socket.set_recv_buffer_size(size)?;
+  }
   let socket_addr = socket2::SockAddr::from(addr);
   socket.bind(&socket_addr)?;
   socket.listen(128)?;
@@ -397,6 +502,7 @@ fn net_listen_udp<NP>(
   addr: IpAddr,
   reuse_address: bool,
   loopback: bool,
+  broadcast: bool,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
@@ -433,8 +539,9 @@ where
   socket_tmp.bind(&socket_addr)?;
   socket_tmp.set_nonblocking(true)?;
 
-  // Enable messages to be sent to the broadcast address (255.255.255.255) by default
-  socket_tmp.set_broadcast(true)?;
+  // Messages can be sent to the broadcast address (255.255.255.255) unless
+  // disabled.
+  socket_tmp.set_broadcast(broadcast)?;
 
   if domain == Domain::IPV4 {
     socket_tmp.set_multicast_loop_v4(loopback)?;
@@ -461,12 +568,13 @@ fn op_net_listen_udp<NP>(
   addr: IpAddr,
   reuse_address: bool,
   loopback: bool,
+  broadcast: bool,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
 {
   super::check_unstable(state, "Deno.listenDatagram");
-  net_listen_udp::<NP>(state, addr, reuse_address, loopback)
+  net_listen_udp::<NP>(state, addr, reuse_address, loopback, broadcast)
 }
 
 #[op]
@@ -475,11 +583,12 @@ fn op_node_unstable_net_listen_udp<NP>(
   addr: IpAddr,
   reuse_address: bool,
   loopback: bool,
+  broadcast: bool,
 ) -> Result<(ResourceId, IpAddr), AnyError>
 where
   NP: NetPermissions + 'static,
 {
-  net_listen_udp::<NP>(state, addr, reuse_address, loopback)
+  net_listen_udp::<NP>(state, addr, reuse_address, loopback, broadcast)
 }
 
 #[derive(Serialize, Eq, PartialEq, Debug)]
@@ -539,18 +648,7 @@ pub struct ResolveAddrArgs {
 #[serde(rename_all = "camelCase")]
 pub struct ResolveDnsOption {
   name_server: Option<NameServer>,
-}
-
-fn default_port() -> u16 {
-  53
-}
-
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct NameServer {
-  ip_addr: String,
-  #[serde(default = "default_port")]
-  port: u16,
+  resolver: Option<DnsConfig>,
 }
 
 #[op]
@@ -568,38 +666,34 @@ where
     cancel_rid,
   } = args;
 
-  let (config, opts) = if let Some(name_server) =
-    options.as_ref().and_then(|o| o.name_server.as_ref())
-  {
-    let group = NameServerConfigGroup::from_ips_clear(
-      &[name_server.ip_addr.parse()?],
-      name_server.port,
-      true,
-    );
-    (
-      ResolverConfig::from_parts(None, vec![], group),
-      ResolverOpts::default(),
-    )
-  } else {
-    system_conf::read_system_conf()?
+  if options.as_ref().map_or(false, |o| o.resolver.is_some()) {
+    super::check_unstable2(&state, "Deno.resolveDns({ resolver })");
+  }
+  let config = match options {
+    Some(ResolveDnsOption {
+      name_server: Some(name_server),
+      ..
+    }) => DnsConfig {
+      name_servers: vec![name_server],
+      ..Default::default()
+    },
+    Some(ResolveDnsOption {
+      resolver: Some(config),
+      ..
+    }) => config,
+    _ => default_dns_config(&state.borrow()).unwrap_or_default(),
   };
 
-  {
+  // Resolvers are reused across calls, so that records are cached.
+  let resolver = {
     let mut s = state.borrow_mut();
-    let perm = s.borrow_mut::<NP>();
-
+    let resolver = get_or_create_resolver(&mut s, &config)?;
     // Checks permission against the name servers which will be actually queried.
-    for ns in config.name_servers() {
-      let socker_addr = &ns.socket_addr;
-      let ip = socker_addr.ip().to_string();
-      let port = socker_addr.port();
-      perm.check_net(&(ip, Some(port)), "Deno.resolveDns()")?;
-    }
-  }
-
-  let resolver = AsyncResolver::tokio(config, opts)?;
+    resolver.check_name_servers(s.borrow_mut::<NP>(), "Deno.resolveDns()")?;
+    resolver
+  };
 
-  let lookup_fut = resolver.lookup(query, record_type);
+  let lookup_fut = resolver.resolver().lookup(query, record_type);
 
   let cancel_handle = cancel_rid.and_then(|rid| {
     state
@@ -662,6 +756,52 @@ pub fn op_set_keepalive(
   resource.set_keepalive(keepalive)
 }
 
+#[op]
+pub fn op_set_send_buffer_size(
+  state: &mut OpState,
+  rid: ResourceId,
+  size: usize,
+) -> Result<(), AnyError> {
+  super::check_unstable(state, "Deno.TcpConn.setSendBufferSize");
+  let resource: Rc<TcpStreamResource> =
+    state.resource_table.get::<TcpStreamResource>(rid)?;
+  resource.set_send_buffer_size(size)
+}
+
+#[op]
+pub fn op_set_recv_buffer_size(
+  state: &mut OpState,
+  rid: ResourceId,
+  size: usize,
+) -> Result<(), AnyError> {
+  super::check_unstable(state, "Deno.TcpConn.setRecvBufferSize");
+  let resource: Rc<TcpStreamResource> =
+    state.resource_table.get::<TcpStreamResource>(rid)?;
+  resource.set_recv_buffer_size(size)
+}
+
+#[op]
+pub fn op_get_send_buffer_size(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<usize, AnyError> {
+  super::check_unstable(state, "Deno.TcpConn.sendBufferSize");
+  let resource: Rc<TcpStreamResource> =
+    state.resource_table.get::<TcpStreamResource>(rid)?;
+  resource.send_buffer_size()
+}
+
+#[op]
+pub fn op_get_recv_buffer_size(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<usize, AnyError> {
+  super::check_unstable(state, "Deno.TcpConn.recvBufferSize");
+  let resource: Rc<TcpStreamResource> =
+    state.resource_table.get::<TcpStreamResource>(rid)?;
+  resource.recv_buffer_size()
+}
+
 fn rdata_to_return_record(
   ty: RecordType,
 ) -> impl Fn(&RData) -> Result<Option<DnsReturnRecord>, AnyError> {
@@ -1046,9 +1186,10 @@ mod tests {
       port: server_addr[1].parse().unwrap(),
     };
 
-    let mut connect_fut =
-      op_net_connect_tcp::call::<TestPermission>(conn_state, ip_addr)
-        .boxed_local();
+    let mut connect_fut = op_net_connect_tcp::call::<TestPermission>(
+      conn_state, ip_addr, None, None, None,
+    )
+    .boxed_local();
     let mut rid = None;
 
     tokio::select! {
@@ -1,10 +1,15 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use crate::happy_eyeballs;
 use crate::io::TcpStreamResource;
+use crate::ops::connection_attempt_delay;
+use crate::ops::resolve_connect_addr;
 use crate::ops::IpAddr;
 use crate::ops::TlsHandshakeInfo;
-use crate::resolve_addr::resolve_addr;
 use crate::resolve_addr::resolve_addr_sync;
+use crate::resolver::DnsConfig;
+use crate::sni::SniCertResolver;
+use crate::sni::SniCertificate;
 use crate::DefaultTlsOptions;
 use crate::NetPermissions;
 use crate::UnsafelyIgnoreCertificateErrors;
@@ -56,6 +61,7 @@ use socket2::Socket;
 use socket2::Type;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::From;
 use std::convert::TryFrom;
 use std::fs::File;
@@ -768,6 +774,9 @@ pub struct ConnectTlsArgs {
   cert_chain: Option<String>,
   private_key: Option<String>,
   alpn_protocols: Option<Vec<String>>,
+  happy_eyeballs: Option<bool>,
+  happy_eyeballs_delay: Option<u64>,
+  resolver: Option<DnsConfig>,
 }
 
 #[derive(Deserialize)]
@@ -836,6 +845,7 @@ where
   let mut tls_config = create_client_config(
     root_cert_store,
     ca_certs,
+    vec![],
     unsafely_ignore_certificate_errors,
     None,
   )?;
@@ -882,6 +892,12 @@ where
   if args.private_key.is_some() {
     super::check_unstable2(&state, "ConnectTlsOptions.privateKey");
   }
+  if args.happy_eyeballs.is_some() || args.happy_eyeballs_delay.is_some() {
+    super::check_unstable2(&state, "ConnectTlsOptions.happyEyeballs");
+  }
+  if args.resolver.is_some() {
+    super::check_unstable2(&state, "ConnectTlsOptions.resolver");
+  }
 
   {
     let mut s = state.borrow_mut();
@@ -911,11 +927,17 @@ where
     .root_cert_store()?;
   let hostname_dns = ServerName::try_from(&*addr.hostname)
     .map_err(|_| invalid_hostname(&addr.hostname))?;
-  let connect_addr = resolve_addr(&addr.hostname, addr.port)
-    .await?
-    .next()
-    .ok_or_else(|| generic_error("No resolved address found"))?;
-  let tcp_stream = TcpStream::connect(connect_addr).await?;
+  let connect_addrs = resolve_connect_addr::<NP>(
+    &state,
+    &addr.hostname,
+    addr.port,
+    args.resolver,
+    "Deno.connectTls()",
+  )
+  .await?;
+  let delay =
+    connection_attempt_delay(args.happy_eyeballs, args.happy_eyeballs_delay);
+  let tcp_stream = happy_eyeballs::connect(connect_addrs, delay).await?;
   let local_addr = tcp_stream.local_addr()?;
   let remote_addr = tcp_stream.peer_addr()?;
 
@@ -935,6 +957,7 @@ where
   let mut tls_config = create_client_config(
     root_cert_store,
     ca_certs,
+    vec![],
     unsafely_ignore_certificate_errors,
     cert_chain_and_key,
   )?;
@@ -1000,6 +1023,7 @@ pub struct ListenTlsArgs {
   key_file: Option<String>,
   alpn_protocols: Option<Vec<String>>,
   reuse_port: bool,
+  sni: Option<HashMap<String, SniCertificate>>,
 }
 
 #[op]
@@ -1035,29 +1059,45 @@ where
   let cert_chain = if cert_file.is_some() && cert.is_some() {
     return Err(generic_error("Both cert and certFile is specified. You can specify either one of them."));
   } else if let Some(path) = cert_file {
-    load_certs_from_file(path)?
+    Some(load_certs_from_file(path)?)
   } else if let Some(cert) = cert {
-    load_certs(&mut BufReader::new(cert.as_bytes()))?
+    Some(load_certs(&mut BufReader::new(cert.as_bytes()))?)
   } else {
-    return Err(generic_error("`cert` is not specified."));
+    None
   };
   let key_der = if key_file.is_some() && key.is_some() {
     return Err(generic_error(
       "Both key and keyFile is specified. You can specify either one of them.",
     ));
   } else if let Some(path) = key_file {
-    load_private_keys_from_file(path)?.remove(0)
+    Some(load_private_keys_from_file(path)?.remove(0))
   } else if let Some(key) = key {
-    load_private_keys(key.as_bytes())?.remove(0)
+    Some(load_private_keys(key.as_bytes())?.remove(0))
   } else {
-    return Err(generic_error("`key` is not specified."));
+    None
   };
 
-  let mut tls_config = ServerConfig::builder()
+  let tls_config = ServerConfig::builder()
     .with_safe_defaults()
-    .with_no_client_auth()
-    .with_single_cert(cert_chain, key_der)
-    .expect("invalid key or certificate");
+    .with_no_client_auth();
+  let mut tls_config = match (args.sni, cert_chain, key_der) {
+    (_, Some(_), None) => return Err(generic_error("`key` is not specified.")),
+    (None, None, _) | (Some(_), None, Some(_)) => {
+      return Err(generic_error("`cert` is not specified."))
+    }
+    (None, Some(cert_chain), Some(key_der)) => tls_config
+      .with_single_cert(cert_chain, key_der)
+      .expect("invalid key or certificate"),
+    (Some(sni), cert_chain, key_der) => {
+      super::check_unstable(state, "Deno.listenTls({ sni })");
+      let default = cert_chain.zip(key_der);
+      tls_config
+        .with_cert_resolver(Arc::new(SniCertResolver::new(sni, default)?))
+    }
+  };
+  if let Some(key_log) = deno_tls::key_log() {
+    tls_config.key_log = key_log;
+  }
   if let Some(alpn_protocols) = args.alpn_protocols {
     super::check_unstable(state, "Deno.listenTls#alpn_protocols");
     tls_config.alpn_protocols =
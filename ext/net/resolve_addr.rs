@@ -25,7 +25,7 @@ pub fn resolve_addr_sync(
   Ok(result)
 }
 
-fn make_addr_port_pair(hostname: &str, port: u16) -> (&str, u16) {
+pub(crate) fn make_addr_port_pair(hostname: &str, port: u16) -> (&str, u16) {
   // Default to localhost if given just the port. Example: ":80"
   if hostname.is_empty() {
     return ("0.0.0.0", port);
@@ -0,0 +1,202 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::resolve_addr::make_addr_port_pair;
+use crate::NetPermissions;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::OpState;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use trust_dns_resolver::config::NameServerConfigGroup;
+use trust_dns_resolver::config::ResolverConfig;
+use trust_dns_resolver::config::ResolverOpts;
+use trust_dns_resolver::system_conf;
+use trust_dns_resolver::TokioAsyncResolver;
+
+fn default_port() -> u16 {
+  53
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct NameServer {
+  pub ip_addr: String,
+  #[serde(default = "default_port")]
+  pub port: u16,
+}
+
+#[derive(
+  Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum DnsProtocol {
+  /// Plain DNS over UDP, falling back to TCP for large responses.
+  #[default]
+  Udp,
+  /// DNS-over-HTTPS (RFC 8484).
+  Https,
+}
+
+/// Configuration of a DNS resolver, used instead of the system's stub
+/// resolver by `Deno.connect()`, `Deno.connectTls()`, `Deno.resolveDns()` and
+/// `fetch()`.
+#[derive(
+  Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsConfig {
+  /// The name servers to query. The system's name servers are used if empty.
+  #[serde(default)]
+  pub name_servers: Vec<NameServer>,
+  #[serde(default)]
+  pub protocol: DnsProtocol,
+  /// The name the TLS certificates of the name servers are verified against,
+  /// required for DNS-over-HTTPS.
+  pub tls_name: Option<String>,
+  /// The number of records to cache, 0 to disable caching.
+  pub cache_size: Option<usize>,
+}
+
+/// The DNS configuration used when an API isn't given one, see
+/// [`crate::deno_net::init_ops`].
+pub struct DefaultDnsConfig(pub Option<DnsConfig>);
+
+pub fn default_dns_config(state: &OpState) -> Option<DnsConfig> {
+  state
+    .try_borrow::<DefaultDnsConfig>()
+    .and_then(|config| config.0.clone())
+}
+
+/// A DNS resolver created from a [`DnsConfig`]. Clones share their record
+/// cache.
+#[derive(Clone)]
+pub struct DnsResolver {
+  resolver: TokioAsyncResolver,
+  name_servers: Vec<SocketAddr>,
+}
+
+impl std::fmt::Debug for DnsResolver {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("DnsResolver")
+      .field("name_servers", &self.name_servers)
+      .finish()
+  }
+}
+
+impl DnsResolver {
+  pub fn new(config: &DnsConfig) -> Result<Self, AnyError> {
+    let (resolver_config, mut opts) = if config.name_servers.is_empty() {
+      if config.protocol != DnsProtocol::Udp {
+        return Err(type_error(
+          "Name servers must be specified for DNS-over-HTTPS",
+        ));
+      }
+      system_conf::read_system_conf()?
+    } else {
+      let mut group = NameServerConfigGroup::new();
+      for name_server in &config.name_servers {
+        let ip_addr = name_server.ip_addr.parse::<IpAddr>().map_err(|_| {
+          type_error(format!(
+            "Invalid name server address: {}",
+            name_server.ip_addr
+          ))
+        })?;
+        group.merge(match config.protocol {
+          DnsProtocol::Udp => NameServerConfigGroup::from_ips_clear(
+            &[ip_addr],
+            name_server.port,
+            true,
+          ),
+          DnsProtocol::Https => {
+            let tls_name = config.tls_name.clone().ok_or_else(|| {
+              type_error("A TLS name is required for DNS-over-HTTPS")
+            })?;
+            NameServerConfigGroup::from_ips_https(
+              &[ip_addr],
+              name_server.port,
+              tls_name,
+              true,
+            )
+          }
+        });
+      }
+      (
+        ResolverConfig::from_parts(None, vec![], group),
+        ResolverOpts::default(),
+      )
+    };
+    if let Some(cache_size) = config.cache_size {
+      opts.cache_size = cache_size;
+    }
+
+    let name_servers = resolver_config
+      .name_servers()
+      .iter()
+      .map(|ns| ns.socket_addr)
+      .collect();
+    let resolver = TokioAsyncResolver::tokio(resolver_config, opts)?;
+    Ok(Self {
+      resolver,
+      name_servers,
+    })
+  }
+
+  pub fn resolver(&self) -> &TokioAsyncResolver {
+    &self.resolver
+  }
+
+  /// The addresses of the name servers that will actually be queried.
+  pub fn name_servers(&self) -> &[SocketAddr] {
+    &self.name_servers
+  }
+
+  /// Resolves `hostname` to socket addresses, like
+  /// [`crate::resolve_addr::resolve_addr`] does with the system's resolver.
+  pub async fn resolve_addr(
+    &self,
+    hostname: &str,
+    port: u16,
+  ) -> Result<Vec<SocketAddr>, AnyError> {
+    let (hostname, port) = make_addr_port_pair(hostname, port);
+    let lookup = self.resolver.lookup_ip(hostname).await?;
+    Ok(lookup.iter().map(|ip| SocketAddr::new(ip, port)).collect())
+  }
+
+  /// Checks net permission for every name server, as queries to them could
+  /// be used to send data to them.
+  pub fn check_name_servers<NP: NetPermissions>(
+    &self,
+    permissions: &mut NP,
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    for addr in &self.name_servers {
+      permissions
+        .check_net(&(addr.ip().to_string(), Some(addr.port())), api_name)?;
+    }
+    Ok(())
+  }
+}
+
+#[derive(Default)]
+struct DnsResolverCache(HashMap<DnsConfig, DnsResolver>);
+
+/// Returns the resolver for `config`, creating it on first use, so that
+/// records are cached across calls.
+pub fn get_or_create_resolver(
+  state: &mut OpState,
+  config: &DnsConfig,
+) -> Result<DnsResolver, AnyError> {
+  if !state.has::<DnsResolverCache>() {
+    state.put(DnsResolverCache::default());
+  }
+  let cache = state.borrow_mut::<DnsResolverCache>();
+  if let Some(resolver) = cache.0.get(config) {
+    return Ok(resolver.clone());
+  }
+  let resolver = DnsResolver::new(config)?;
+  cache.0.insert(config.clone(), resolver.clone());
+  Ok(resolver)
+}
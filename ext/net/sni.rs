@@ -0,0 +1,90 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::custom_error;
+use deno_core::error::AnyError;
+use deno_tls::load_certs;
+use deno_tls::load_private_keys;
+use deno_tls::rustls::server::ClientHello;
+use deno_tls::rustls::server::ResolvesServerCert;
+use deno_tls::rustls::sign::any_supported_type;
+use deno_tls::rustls::sign::CertifiedKey;
+use deno_tls::rustls::Certificate;
+use deno_tls::rustls::PrivateKey;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::sync::Arc;
+
+/// A PEM encoded certificate chain and private key.
+#[derive(Deserialize)]
+pub struct SniCertificate {
+  cert: String,
+  key: String,
+}
+
+/// Selects the certificate of a TLS server based on the server name the
+/// client sends with the SNI extension, so that a single listener can
+/// terminate TLS for many domains.
+///
+/// Server names are matched exactly, then against a wildcard name covering
+/// their first label, e.g. `*.example.com` for `www.example.com`. Clients
+/// that don't send a server name, or send an unknown one, get the default
+/// certificate if there is one, and fail the handshake otherwise.
+pub struct SniCertResolver {
+  certs: HashMap<String, Arc<CertifiedKey>>,
+  default: Option<Arc<CertifiedKey>>,
+}
+
+impl SniCertResolver {
+  pub fn new(
+    certs: HashMap<String, SniCertificate>,
+    default: Option<(Vec<Certificate>, PrivateKey)>,
+  ) -> Result<Self, AnyError> {
+    let certs = certs
+      .into_iter()
+      .map(|(server_name, SniCertificate { cert, key })| {
+        let cert_chain = load_certs(&mut BufReader::new(cert.as_bytes()))?;
+        // The `remove` is safe because load_private_keys checks that there is at least one key.
+        let key = load_private_keys(key.as_bytes())?.remove(0);
+        Ok((
+          server_name.to_ascii_lowercase(),
+          certified_key(cert_chain, key)?,
+        ))
+      })
+      .collect::<Result<_, AnyError>>()?;
+    let default = default
+      .map(|(cert_chain, key)| certified_key(cert_chain, key))
+      .transpose()?;
+    Ok(Self { certs, default })
+  }
+
+  fn lookup(&self, server_name: Option<&str>) -> Option<Arc<CertifiedKey>> {
+    if let Some(server_name) = server_name {
+      let server_name = server_name.to_ascii_lowercase();
+      if let Some(key) = self.certs.get(&server_name) {
+        return Some(key.clone());
+      }
+      if let Some((_, parent)) = server_name.split_once('.') {
+        if let Some(key) = self.certs.get(&format!("*.{parent}")) {
+          return Some(key.clone());
+        }
+      }
+    }
+    self.default.clone()
+  }
+}
+
+impl ResolvesServerCert for SniCertResolver {
+  fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+    self.lookup(client_hello.server_name())
+  }
+}
+
+fn certified_key(
+  cert_chain: Vec<Certificate>,
+  key: PrivateKey,
+) -> Result<Arc<CertifiedKey>, AnyError> {
+  let key = any_supported_type(&key)
+    .map_err(|_| custom_error("InvalidData", "Unsupported private key"))?;
+  Ok(Arc::new(CertifiedKey::new(cert_chain, key)))
+}
@@ -30,6 +30,8 @@ mod path;
 mod polyfill;
 mod resolution;
 
+pub use ops::inspector::NodeInspectorServer;
+pub use ops::inspector::NodeInspectorServerRc;
 pub use package_json::PackageJson;
 pub use path::PathClean;
 pub use polyfill::is_builtin_node_module;
@@ -189,6 +191,8 @@ deno_core::extension!(deno_node,
     ops::crypto::op_node_random_int,
     ops::crypto::op_node_scrypt_sync,
     ops::crypto::op_node_scrypt_async,
+    ops::crypto::op_node_argon2_sync,
+    ops::crypto::op_node_argon2_async,
     ops::crypto::op_node_ecdh_generate_keys,
     ops::crypto::op_node_ecdh_compute_secret,
     ops::crypto::op_node_ecdh_compute_public_key,
@@ -207,6 +211,15 @@ deno_core::extension!(deno_node,
     ops::winerror::op_node_sys_to_uv_error,
     ops::v8::op_v8_cached_data_version_tag,
     ops::v8::op_v8_get_heap_statistics,
+    ops::vm::op_vm_create_context,
+    ops::vm::op_vm_run_in_context,
+    ops::inspector::op_inspector_open<P>,
+    ops::inspector::op_inspector_close,
+    ops::inspector::op_inspector_url,
+    ops::inspector::op_inspector_wait_for_debugger,
+    ops::inspector::op_inspector_connect,
+    ops::inspector::op_inspector_dispatch,
+    ops::inspector::op_inspector_receive,
     ops::idna::op_node_idna_domain_to_ascii,
     ops::idna::op_node_idna_domain_to_unicode,
     ops::idna::op_node_idna_punycode_decode,
@@ -219,6 +232,15 @@ deno_core::extension!(deno_node,
     ops::zlib::op_zlib_init,
     ops::zlib::op_zlib_reset,
     ops::http::op_node_http_request<P>,
+    ops::http2::op_http2_connect,
+    ops::http2::op_http2_poll_client_connection,
+    ops::http2::op_http2_client_request,
+    ops::http2::op_http2_client_send_data,
+    ops::http2::op_http2_client_send_trailers,
+    ops::http2::op_http2_client_reset_stream,
+    ops::http2::op_http2_client_get_response,
+    ops::http2::op_http2_client_get_response_body_chunk,
+    ops::http2::op_http2_client_get_response_trailers,
     op_node_build_os,
     ops::require::op_require_init_paths,
     ops::require::op_require_node_module_paths<P>,
@@ -368,6 +390,7 @@ deno_core::extension!(deno_node,
     "internal/crypto/_randomBytes.ts",
     "internal/crypto/_randomFill.ts",
     "internal/crypto/_randomInt.ts",
+    "internal/crypto/argon2.ts",
     "internal/crypto/certificate.ts",
     "internal/crypto/cipher.ts",
     "internal/crypto/constants.ts",
@@ -833,55 +833,47 @@ pub fn op_node_random_int(min: i32, max: i32) -> Result<i32, AnyError> {
   Ok(dist.sample(&mut rng))
 }
 
-#[allow(clippy::too_many_arguments)]
 fn scrypt(
   password: StringOrBuffer,
   salt: StringOrBuffer,
-  keylen: u32,
   cost: u32,
   block_size: u32,
   parallelization: u32,
-  _maxmem: u32,
   output_buffer: &mut [u8],
 ) -> Result<(), AnyError> {
-  // Construct Params
+  // The parameters are validated in JS, like Node does before calling into
+  // OpenSSL. `scrypt` rejects an empty output, which Node allows.
+  if output_buffer.is_empty() {
+    return Ok(());
+  }
+  // The output length of `Params` is only used for password hash strings.
   let params = scrypt::Params::new(
     cost as u8,
     block_size,
     parallelization,
-    keylen as usize,
+    scrypt::Params::RECOMMENDED_LEN,
   )
-  .unwrap();
-
-  // Call into scrypt
-  let res = scrypt::scrypt(&password, &salt, &params, output_buffer);
-  if res.is_ok() {
-    Ok(())
-  } else {
-    // TODO(lev): key derivation failed, so what?
-    Err(generic_error("scrypt key derivation failed"))
-  }
+  .map_err(|_| type_error("Invalid scrypt parameter"))?;
+
+  scrypt::scrypt(&password, &salt, &params, output_buffer)
+    .map_err(|_| generic_error("scrypt key derivation failed"))
 }
 
 #[op]
 pub fn op_node_scrypt_sync(
   password: StringOrBuffer,
   salt: StringOrBuffer,
-  keylen: u32,
   cost: u32,
   block_size: u32,
   parallelization: u32,
-  maxmem: u32,
   output_buffer: &mut [u8],
 ) -> Result<(), AnyError> {
   scrypt(
     password,
     salt,
-    keylen,
     cost,
     block_size,
     parallelization,
-    maxmem,
     output_buffer,
   )
 }
@@ -894,27 +886,126 @@ pub async fn op_node_scrypt_async(
   cost: u32,
   block_size: u32,
   parallelization: u32,
-  maxmem: u32,
 ) -> Result<ZeroCopyBuf, AnyError> {
   spawn_blocking(move || {
     let mut output_buffer = vec![0u8; keylen as usize];
-    let res = scrypt(
+    scrypt(
       password,
       salt,
-      keylen,
       cost,
       block_size,
       parallelization,
-      maxmem,
       &mut output_buffer,
+    )?;
+    Ok(output_buffer.into())
+  })
+  .await?
+}
+
+#[allow(clippy::too_many_arguments)]
+fn argon2(
+  algorithm: &str,
+  message: StringOrBuffer,
+  nonce: StringOrBuffer,
+  secret: Option<StringOrBuffer>,
+  associated_data: Option<StringOrBuffer>,
+  parallelism: u32,
+  memory: u32,
+  passes: u32,
+  output_buffer: &mut [u8],
+) -> Result<(), AnyError> {
+  let algorithm = match algorithm {
+    "argon2d" => argon2::Algorithm::Argon2d,
+    "argon2i" => argon2::Algorithm::Argon2i,
+    "argon2id" => argon2::Algorithm::Argon2id,
+    _ => return Err(type_error(format!("Unknown algorithm {algorithm}"))),
+  };
+
+  let mut params = argon2::ParamsBuilder::new();
+  params
+    .m_cost(memory)
+    .t_cost(passes)
+    .p_cost(parallelism)
+    .output_len(output_buffer.len());
+  if let Some(associated_data) = &associated_data {
+    params.data(
+      argon2::AssociatedData::new(associated_data)
+        .map_err(|e| type_error(format!("Invalid argon2 parameter: {e}")))?,
     );
+  }
+  let params = params
+    .build()
+    .map_err(|e| type_error(format!("Invalid argon2 parameter: {e}")))?;
+
+  let context = match &secret {
+    Some(secret) => argon2::Argon2::new_with_secret(
+      secret,
+      algorithm,
+      argon2::Version::V0x13,
+      params,
+    )
+    .map_err(|e| type_error(format!("Invalid argon2 parameter: {e}")))?,
+    None => argon2::Argon2::new(algorithm, argon2::Version::V0x13, params),
+  };
 
-    if res.is_ok() {
-      Ok(output_buffer.into())
-    } else {
-      // TODO(lev): rethrow the error?
-      Err(generic_error("scrypt failure"))
-    }
+  context
+    .hash_password_into(&message, &nonce, output_buffer)
+    .map_err(|e| generic_error(format!("argon2 key derivation failed: {e}")))
+}
+
+#[allow(clippy::too_many_arguments)]
+#[op]
+pub fn op_node_argon2_sync(
+  algorithm: &str,
+  message: StringOrBuffer,
+  nonce: StringOrBuffer,
+  secret: Option<StringOrBuffer>,
+  associated_data: Option<StringOrBuffer>,
+  parallelism: u32,
+  memory: u32,
+  passes: u32,
+  output_buffer: &mut [u8],
+) -> Result<(), AnyError> {
+  argon2(
+    algorithm,
+    message,
+    nonce,
+    secret,
+    associated_data,
+    parallelism,
+    memory,
+    passes,
+    output_buffer,
+  )
+}
+
+#[allow(clippy::too_many_arguments)]
+#[op]
+pub async fn op_node_argon2_async(
+  algorithm: String,
+  message: StringOrBuffer,
+  nonce: StringOrBuffer,
+  secret: Option<StringOrBuffer>,
+  associated_data: Option<StringOrBuffer>,
+  parallelism: u32,
+  memory: u32,
+  passes: u32,
+  tag_length: u32,
+) -> Result<ZeroCopyBuf, AnyError> {
+  spawn_blocking(move || {
+    let mut output_buffer = vec![0u8; tag_length as usize];
+    argon2(
+      &algorithm,
+      message,
+      nonce,
+      secret,
+      associated_data,
+      parallelism,
+      memory,
+      passes,
+      &mut output_buffer,
+    )?;
+    Ok(output_buffer.into())
   })
   .await?
 }
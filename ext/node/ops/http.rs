@@ -37,7 +37,7 @@ where
 {
   let client = if let Some(rid) = client_rid {
     let r = state.resource_table.get::<HttpClientResource>(rid)?;
-    r.client.clone()
+    r.client()
   } else {
     get_or_create_client_from_state(state)?
   };
@@ -0,0 +1,327 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::serde::Serialize;
+use deno_core::url::Url;
+use deno_core::AsyncRefCell;
+use deno_core::BufView;
+use deno_core::ByteString;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use deno_core::ZeroCopyBuf;
+use deno_net::raw::take_network_stream_resource;
+use deno_net::raw::NetworkStream;
+use h2::client::Connection;
+use h2::client::ResponseFuture;
+use h2::client::SendRequest;
+use h2::Reason;
+use h2::RecvStream;
+use h2::SendStream;
+use http::header::HeaderName;
+use http::header::HeaderValue;
+use http::HeaderMap;
+use http::Method;
+use http::Request;
+
+pub struct Http2Client {
+  client: AsyncRefCell<SendRequest<BufView>>,
+  url: Url,
+}
+
+impl Resource for Http2Client {
+  fn name(&self) -> Cow<str> {
+    "http2Client".into()
+  }
+}
+
+pub struct Http2ClientConn {
+  conn: AsyncRefCell<Connection<NetworkStream, BufView>>,
+  cancel_handle: CancelHandle,
+}
+
+impl Resource for Http2ClientConn {
+  fn name(&self) -> Cow<str> {
+    "http2ClientConnection".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel_handle.cancel()
+  }
+}
+
+pub struct Http2ClientStream {
+  response: AsyncRefCell<Option<ResponseFuture>>,
+  stream: AsyncRefCell<SendStream<BufView>>,
+}
+
+impl Resource for Http2ClientStream {
+  fn name(&self) -> Cow<str> {
+    "http2ClientStream".into()
+  }
+}
+
+pub struct Http2ClientResponseBody {
+  body: AsyncRefCell<RecvStream>,
+  cancel_handle: CancelHandle,
+}
+
+impl Resource for Http2ClientResponseBody {
+  fn name(&self) -> Cow<str> {
+    "http2ClientResponseBody".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel_handle.cancel()
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Http2ClientResponse {
+  status: u16,
+  headers: Vec<(ByteString, ByteString)>,
+  body_rid: ResourceId,
+  end_stream: bool,
+}
+
+fn header_map(
+  headers: Vec<(ByteString, ByteString)>,
+) -> Result<HeaderMap, AnyError> {
+  let mut map = HeaderMap::new();
+  for (key, value) in headers {
+    let name = HeaderName::from_bytes(&key)
+      .map_err(|err| type_error(err.to_string()))?;
+    let value = HeaderValue::from_bytes(&value)
+      .map_err(|err| type_error(err.to_string()))?;
+    map.append(name, value);
+  }
+  Ok(map)
+}
+
+fn header_list(header_map: &HeaderMap) -> Vec<(ByteString, ByteString)> {
+  header_map
+    .iter()
+    .map(|(name, value)| (name.as_str().into(), value.as_bytes().into()))
+    .collect()
+}
+
+/// Performs the HTTP/2 handshake over an already connected TCP or TLS
+/// stream. The permissions were checked when the stream was opened.
+#[op]
+pub async fn op_http2_connect(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+  url: String,
+) -> Result<(ResourceId, ResourceId), AnyError> {
+  let network_stream =
+    take_network_stream_resource(&mut state.borrow_mut().resource_table, rid)?;
+  let url = Url::parse(&url)?;
+
+  let (client, conn) = h2::client::handshake(network_stream).await?;
+  let mut state = state.borrow_mut();
+  let client_rid = state.resource_table.add(Http2Client {
+    client: AsyncRefCell::new(client),
+    url,
+  });
+  let conn_rid = state.resource_table.add(Http2ClientConn {
+    conn: AsyncRefCell::new(conn),
+    cancel_handle: CancelHandle::new(),
+  });
+  Ok((client_rid, conn_rid))
+}
+
+/// Drives the connection until it is closed by either side.
+#[op]
+pub async fn op_http2_poll_client_connection(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<(), AnyError> {
+  let resource = state.borrow().resource_table.get::<Http2ClientConn>(rid)?;
+  let cancel_handle = RcRef::map(resource.clone(), |r| &r.cancel_handle);
+  let mut conn = RcRef::map(resource, |r| &r.conn).borrow_mut().await;
+  // A canceled future means that the session was closed from JS.
+  if let Ok(result) = (&mut *conn).or_cancel(cancel_handle).await {
+    result?;
+  }
+  Ok(())
+}
+
+#[op]
+pub async fn op_http2_client_request(
+  state: Rc<RefCell<OpState>>,
+  client_rid: ResourceId,
+  pseudo_headers: HashMap<String, String>,
+  headers: Vec<(ByteString, ByteString)>,
+  end_stream: bool,
+) -> Result<(ResourceId, u32), AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<Http2Client>(client_rid)?;
+
+  let method = match pseudo_headers.get(":method") {
+    Some(method) => Method::from_bytes(method.as_bytes())?,
+    None => Method::GET,
+  };
+  let mut url = resource.url.join(
+    pseudo_headers
+      .get(":path")
+      .map(String::as_str)
+      .unwrap_or("/"),
+  )?;
+  if let Some(scheme) = pseudo_headers.get(":scheme") {
+    url
+      .set_scheme(scheme)
+      .map_err(|_| type_error(format!("Invalid scheme: {scheme}")))?;
+  }
+  if let Some(authority) = pseudo_headers.get(":authority") {
+    let authority = Url::parse(&format!("{}://{authority}", url.scheme()))?;
+    url.set_host(authority.host_str())?;
+    url
+      .set_port(authority.port())
+      .map_err(|_| type_error("Invalid authority"))?;
+  }
+
+  let mut request = Request::builder()
+    .method(method)
+    .uri(url.as_str())
+    .body(())?;
+  *request.headers_mut() = header_map(headers)?;
+
+  let client = RcRef::map(&resource, |r| &r.client).borrow().await.clone();
+  let mut client = client.ready().await?;
+  let (response, stream) = client.send_request(request, end_stream)?;
+  let stream_id = stream.stream_id();
+
+  let stream_rid = state.borrow_mut().resource_table.add(Http2ClientStream {
+    response: AsyncRefCell::new(Some(response)),
+    stream: AsyncRefCell::new(stream),
+  });
+  Ok((stream_rid, u32::from(stream_id)))
+}
+
+#[op]
+pub async fn op_http2_client_send_data(
+  state: Rc<RefCell<OpState>>,
+  stream_rid: ResourceId,
+  data: ZeroCopyBuf,
+  end_stream: bool,
+) -> Result<(), AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<Http2ClientStream>(stream_rid)?;
+  let mut stream = RcRef::map(&resource, |r| &r.stream).borrow_mut().await;
+  stream.send_data(data.into(), end_stream)?;
+  Ok(())
+}
+
+#[op]
+pub async fn op_http2_client_send_trailers(
+  state: Rc<RefCell<OpState>>,
+  stream_rid: ResourceId,
+  trailers: Vec<(ByteString, ByteString)>,
+) -> Result<(), AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<Http2ClientStream>(stream_rid)?;
+  let mut stream = RcRef::map(&resource, |r| &r.stream).borrow_mut().await;
+  stream.send_trailers(header_map(trailers)?)?;
+  Ok(())
+}
+
+#[op]
+pub async fn op_http2_client_reset_stream(
+  state: Rc<RefCell<OpState>>,
+  stream_rid: ResourceId,
+  code: u32,
+) -> Result<(), AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<Http2ClientStream>(stream_rid)?;
+  let mut stream = RcRef::map(&resource, |r| &r.stream).borrow_mut().await;
+  stream.send_reset(Reason::from(code));
+  Ok(())
+}
+
+#[op]
+pub async fn op_http2_client_get_response(
+  state: Rc<RefCell<OpState>>,
+  stream_rid: ResourceId,
+) -> Result<Http2ClientResponse, AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<Http2ClientStream>(stream_rid)?;
+  let response_future = RcRef::map(&resource, |r| &r.response)
+    .borrow_mut()
+    .await
+    .take()
+    .ok_or_else(|| type_error("Response was already received"))?;
+
+  let response = response_future.await?;
+  let (parts, body) = response.into_parts();
+  let end_stream = body.is_end_stream();
+  let body_rid =
+    state
+      .borrow_mut()
+      .resource_table
+      .add(Http2ClientResponseBody {
+        body: AsyncRefCell::new(body),
+        cancel_handle: CancelHandle::new(),
+      });
+  Ok(Http2ClientResponse {
+    status: parts.status.as_u16(),
+    headers: header_list(&parts.headers),
+    body_rid,
+    end_stream,
+  })
+}
+
+/// Returns the next chunk of the response body, or `None` once the body is
+/// finished.
+#[op]
+pub async fn op_http2_client_get_response_body_chunk(
+  state: Rc<RefCell<OpState>>,
+  body_rid: ResourceId,
+) -> Result<Option<ZeroCopyBuf>, AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<Http2ClientResponseBody>(body_rid)?;
+  let cancel_handle = RcRef::map(resource.clone(), |r| &r.cancel_handle);
+  let mut body = RcRef::map(resource, |r| &r.body).borrow_mut().await;
+  let Ok(Some(data)) = body.data().or_cancel(cancel_handle).await else {
+    return Ok(None);
+  };
+  let data = data?;
+  body.flow_control().release_capacity(data.len())?;
+  Ok(Some(data.to_vec().into()))
+}
+
+#[op]
+pub async fn op_http2_client_get_response_trailers(
+  state: Rc<RefCell<OpState>>,
+  body_rid: ResourceId,
+) -> Result<Option<Vec<(ByteString, ByteString)>>, AnyError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<Http2ClientResponseBody>(body_rid)?;
+  let mut body = RcRef::map(&resource, |r| &r.body).borrow_mut().await;
+  let trailers = body.trailers().await?;
+  Ok(trailers.as_ref().map(header_list))
+}
@@ -0,0 +1,180 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
+use deno_core::futures::channel::mpsc;
+use deno_core::futures::channel::mpsc::UnboundedReceiver;
+use deno_core::futures::channel::mpsc::UnboundedSender;
+use deno_core::futures::StreamExt;
+use deno_core::op;
+use deno_core::url::Url;
+use deno_core::v8;
+use deno_core::AsyncRefCell;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
+use deno_core::InspectorMsg;
+use deno_core::InspectorSessionProxy;
+use deno_core::JsRuntime;
+use deno_core::JsRuntimeInspector;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+
+use crate::NodePermissions;
+
+/// Starts and stops the inspector server for `inspector.open()` and
+/// `inspector.close()`. Implemented by the embedder, which owns the server.
+pub trait NodeInspectorServer {
+  /// Starts a server on `host` that accepts debugger connections for
+  /// `inspector`.
+  fn open(
+    &self,
+    host: SocketAddr,
+    inspector: &mut JsRuntimeInspector,
+  ) -> Result<(), AnyError>;
+
+  /// Stops the server started by `open()`.
+  fn close(&self);
+
+  /// Returns the websocket URL of the active server, if there is one.
+  fn url(&self) -> Option<String>;
+}
+
+pub type NodeInspectorServerRc = Rc<dyn NodeInspectorServer>;
+
+/// Returns `false` if the embedder doesn't support an inspector server.
+#[op(v8)]
+fn op_inspector_open<P>(
+  scope: &mut v8::HandleScope,
+  state: &mut OpState,
+  host: String,
+  port: u16,
+) -> Result<bool, AnyError>
+where
+  P: NodePermissions + 'static,
+{
+  let Some(server) = state.try_borrow::<NodeInspectorServerRc>().cloned()
+  else {
+    return Ok(false);
+  };
+  let url = Url::parse(&format!("ws://{host}:{port}"))?;
+  state
+    .borrow_mut::<P>()
+    .check_net_url(&url, "inspector.open()")?;
+  let host = url
+    .socket_addrs(|| None)?
+    .into_iter()
+    .next()
+    .ok_or_else(|| generic_error(format!("Invalid host: {host}")))?;
+
+  let inspector = JsRuntime::inspector_from_scope(scope);
+  let mut inspector = inspector.borrow_mut();
+  server.open(host, &mut inspector)?;
+  Ok(true)
+}
+
+#[op]
+fn op_inspector_close(state: &mut OpState) {
+  if let Some(server) = state.try_borrow::<NodeInspectorServerRc>() {
+    server.close();
+  }
+}
+
+#[op]
+fn op_inspector_url(state: &mut OpState) -> Option<String> {
+  state
+    .try_borrow::<NodeInspectorServerRc>()
+    .and_then(|server| server.url())
+}
+
+/// Blocks until a debugger is connected to the inspector.
+#[op(v8)]
+fn op_inspector_wait_for_debugger(scope: &mut v8::HandleScope) {
+  let inspector = JsRuntime::inspector_from_scope(scope);
+  inspector.borrow_mut().wait_for_session();
+}
+
+/// A session of `node:inspector` that exchanges protocol messages with the
+/// inspector of the runtime it was created in.
+struct InspectorSessionResource {
+  tx: UnboundedSender<String>,
+  rx: AsyncRefCell<UnboundedReceiver<InspectorMsg>>,
+  cancel_handle: CancelHandle,
+}
+
+impl Resource for InspectorSessionResource {
+  fn name(&self) -> Cow<str> {
+    "inspectorSession".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel_handle.cancel();
+  }
+}
+
+#[op(v8)]
+fn op_inspector_connect(
+  scope: &mut v8::HandleScope,
+  state: &mut OpState,
+) -> Result<ResourceId, AnyError> {
+  // The 'outbound' channel carries messages sent to the session.
+  let (outbound_tx, outbound_rx) = mpsc::unbounded();
+  // The 'inbound' channel carries messages received from the session.
+  let (inbound_tx, inbound_rx) = mpsc::unbounded();
+
+  let inspector = JsRuntime::inspector_from_scope(scope);
+  // The session is established the next time the inspector polls its
+  // sessions. Messages posted before that are queued in the channel.
+  inspector
+    .borrow()
+    .get_session_sender()
+    .unbounded_send(InspectorSessionProxy {
+      tx: outbound_tx,
+      rx: inbound_rx,
+    })
+    .map_err(|_| generic_error("Inspector is not available"))?;
+
+  Ok(state.resource_table.add(InspectorSessionResource {
+    tx: inbound_tx,
+    rx: AsyncRefCell::new(outbound_rx),
+    cancel_handle: CancelHandle::new(),
+  }))
+}
+
+#[op]
+fn op_inspector_dispatch(
+  state: &mut OpState,
+  rid: ResourceId,
+  message: String,
+) -> Result<(), AnyError> {
+  let session = state.resource_table.get::<InspectorSessionResource>(rid)?;
+  session
+    .tx
+    .unbounded_send(message)
+    .map_err(|_| generic_error("Inspector session closed"))
+}
+
+/// Returns the next response or notification of the session, or `None` once
+/// the session is closed.
+#[op]
+async fn op_inspector_receive(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<Option<String>, AnyError> {
+  let session = state
+    .borrow()
+    .resource_table
+    .get::<InspectorSessionResource>(rid)?;
+  let cancel_handle = RcRef::map(session.clone(), |r| &r.cancel_handle);
+  let mut rx = RcRef::map(session, |r| &r.rx).borrow_mut().await;
+  let Ok(Some(message)) = rx.next().or_cancel(cancel_handle).await else {
+    return Ok(None);
+  };
+  Ok(Some(message.content))
+}
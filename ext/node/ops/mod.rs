@@ -2,8 +2,11 @@
 
 pub mod crypto;
 pub mod http;
+pub mod http2;
 pub mod idna;
+pub mod inspector;
 pub mod require;
 pub mod v8;
+pub mod vm;
 pub mod winerror;
 pub mod zlib;
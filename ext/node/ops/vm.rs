@@ -0,0 +1,244 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::serde::Serialize;
+use deno_core::serde_v8;
+use deno_core::v8;
+use deno_core::JsRealm;
+
+/// Stored in the slot of a context created by `vm.createContext()`. The
+/// global object of the context forwards named properties to the sandbox, the
+/// object that was passed to `vm.createContext()`.
+struct ContextifyContext {
+  sandbox: v8::Weak<v8::Object>,
+}
+
+fn sandbox<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  args: &v8::PropertyCallbackArguments<'s>,
+) -> Option<v8::Local<'s, v8::Object>> {
+  let context = args.this().get_creation_context(scope)?;
+  let contextify = context.get_slot::<Rc<ContextifyContext>>(scope)?.clone();
+  contextify.sandbox.to_local(scope)
+}
+
+fn property_getter<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  key: v8::Local<'s, v8::Name>,
+  args: v8::PropertyCallbackArguments<'s>,
+  mut rv: v8::ReturnValue,
+) {
+  let Some(sandbox) = sandbox(scope, &args) else {
+    return;
+  };
+  // Properties that the sandbox doesn't have are looked up on the global
+  // object, which has the builtins of the context.
+  if sandbox.has(scope, key.into()) != Some(true) {
+    return;
+  }
+  if let Some(value) = sandbox.get(scope, key.into()) {
+    rv.set(value);
+  }
+}
+
+fn property_setter<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  key: v8::Local<'s, v8::Name>,
+  value: v8::Local<'s, v8::Value>,
+  args: v8::PropertyCallbackArguments<'s>,
+  mut rv: v8::ReturnValue,
+) {
+  let Some(sandbox) = sandbox(scope, &args) else {
+    return;
+  };
+  if sandbox.set(scope, key.into(), value).is_some() {
+    rv.set(value);
+  }
+}
+
+fn property_deleter<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  key: v8::Local<'s, v8::Name>,
+  args: v8::PropertyCallbackArguments<'s>,
+  mut rv: v8::ReturnValue,
+) {
+  let Some(sandbox) = sandbox(scope, &args) else {
+    return;
+  };
+  if sandbox.has(scope, key.into()) != Some(true) {
+    return;
+  }
+  if let Some(deleted) = sandbox.delete(scope, key.into()) {
+    rv.set_bool(deleted);
+  }
+}
+
+fn property_enumerator<'s>(
+  scope: &mut v8::HandleScope<'s>,
+  args: v8::PropertyCallbackArguments<'s>,
+  mut rv: v8::ReturnValue,
+) {
+  let Some(sandbox) = sandbox(scope, &args) else {
+    return;
+  };
+  if let Some(names) = sandbox.get_own_property_names(scope, Default::default())
+  {
+    rv.set(names.into());
+  }
+}
+
+/// Creates a new context for `sandbox` and returns its global object, which
+/// identifies the context in the other ops.
+#[op(v8)]
+fn op_vm_create_context<'a>(
+  scope: &mut v8::HandleScope<'a>,
+  sandbox: serde_v8::Value<'a>,
+) -> Result<serde_v8::Value<'a>, AnyError> {
+  let sandbox = v8::Local::<v8::Object>::try_from(sandbox.v8_value)
+    .map_err(|_| type_error("Invalid sandbox"))?;
+
+  let global_template = v8::ObjectTemplate::new(scope);
+  global_template.set_named_property_handler(
+    v8::NamedPropertyHandlerConfiguration::new()
+      .getter(property_getter)
+      .setter(property_setter)
+      .deleter(property_deleter)
+      .enumerator(property_enumerator),
+  );
+  let context = v8::Context::new_from_template(scope, global_template);
+  JsRealm::share_state_with_context(scope, context);
+  context.set_slot(
+    scope,
+    Rc::new(ContextifyContext {
+      sandbox: v8::Weak::new(scope, sandbox),
+    }),
+  );
+
+  let global = context.global(scope);
+  Ok(global.into())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RunError<'s> {
+  thrown: serde_v8::Value<'s>,
+  timed_out: bool,
+}
+
+#[derive(Serialize)]
+struct RunResult<'s>(Option<serde_v8::Value<'s>>, Option<RunError<'s>>);
+
+/// Runs `source` as a script in the context of `context_global`, or in the
+/// current context if it is `undefined`. The script is terminated if it runs
+/// for longer than `timeout` milliseconds.
+#[op(v8)]
+fn op_vm_run_in_context<'a>(
+  scope: &mut v8::HandleScope<'a>,
+  source: serde_v8::Value<'a>,
+  filename: String,
+  line_offset: i32,
+  column_offset: i32,
+  context_global: serde_v8::Value<'a>,
+  timeout: Option<u64>,
+) -> Result<RunResult<'a>, AnyError> {
+  let source = v8::Local::<v8::String>::try_from(source.v8_value)
+    .map_err(|_| type_error("Invalid source"))?;
+  let context = if context_global.v8_value.is_undefined() {
+    scope.get_current_context()
+  } else {
+    v8::Local::<v8::Object>::try_from(context_global.v8_value)
+      .ok()
+      .and_then(|global| global.get_creation_context(scope))
+      .ok_or_else(|| type_error("Invalid context"))?
+  };
+
+  let scope = &mut v8::ContextScope::new(scope, context);
+  let tc_scope = &mut v8::TryCatch::new(scope);
+  let filename = v8::String::new(tc_scope, &filename).unwrap();
+  let source_map_url = v8::String::empty(tc_scope);
+  let origin = v8::ScriptOrigin::new(
+    tc_scope,
+    filename.into(),
+    line_offset,
+    column_offset,
+    false,
+    0,
+    source_map_url.into(),
+    false,
+    false,
+    false,
+  );
+
+  let Some(script) = v8::Script::compile(tc_scope, source, Some(&origin))
+  else {
+    let exception = tc_scope.exception().unwrap();
+    return Ok(RunResult(
+      None,
+      Some(RunError {
+        thrown: exception.into(),
+        timed_out: false,
+      }),
+    ));
+  };
+
+  // Terminates the script from another thread once the timeout expires,
+  // unless the script finished before.
+  let watchdog = timeout.map(|timeout| {
+    let handle = tc_scope.thread_safe_handle();
+    let timed_out = Arc::new(AtomicBool::new(false));
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    let thread = std::thread::spawn({
+      let timed_out = timed_out.clone();
+      move || {
+        if let Err(mpsc::RecvTimeoutError::Timeout) =
+          done_rx.recv_timeout(Duration::from_millis(timeout))
+        {
+          timed_out.store(true, Ordering::SeqCst);
+          handle.terminate_execution();
+        }
+      }
+    });
+    (done_tx, thread, timed_out)
+  });
+
+  let result = script.run(tc_scope);
+
+  if let Some((done_tx, thread, timed_out)) = watchdog {
+    drop(done_tx);
+    thread.join().unwrap();
+    if timed_out.load(Ordering::SeqCst) {
+      tc_scope.cancel_terminate_execution();
+      let thrown = v8::undefined(tc_scope);
+      return Ok(RunResult(
+        None,
+        Some(RunError {
+          thrown: thrown.into(),
+          timed_out: true,
+        }),
+      ));
+    }
+  }
+
+  match result {
+    Some(result) => Ok(RunResult(Some(result.into()), None)),
+    None => {
+      let exception = tc_scope.exception().unwrap();
+      Ok(RunResult(
+        None,
+        Some(RunError {
+          thrown: exception.into(),
+          timed_out: false,
+        }),
+      ))
+    }
+  }
+}
@@ -39,6 +39,7 @@ pub struct PackageJson {
   pub dependencies: Option<HashMap<String, String>>,
   pub dev_dependencies: Option<HashMap<String, String>>,
   pub scripts: Option<IndexMap<String, String>>,
+  pub workspaces: Option<Vec<String>>,
 }
 
 impl PackageJson {
@@ -58,6 +59,7 @@ impl PackageJson {
       dependencies: None,
       dev_dependencies: None,
       scripts: None,
+      workspaces: None,
     }
   }
 
@@ -155,6 +157,13 @@ impl PackageJson {
       .get("scripts")
       .and_then(|d| serde_json::from_value(d.to_owned()).ok());
 
+    // npm declares the workspaces as an array of globs, while yarn also
+    // accepts an object with a `packages` array
+    let workspaces = package_json
+      .get("workspaces")
+      .map(|w| w.get("packages").unwrap_or(w))
+      .and_then(|w| serde_json::from_value(w.to_owned()).ok());
+
     // Ignore unknown types for forwards compatibility
     let typ = if let Some(t) = type_val {
       if let Some(t) = t.as_str() {
@@ -191,6 +200,7 @@ impl PackageJson {
       dependencies,
       dev_dependencies,
       scripts,
+      workspaces,
     };
 
     CACHE.with(|cache| {
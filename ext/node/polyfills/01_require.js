@@ -70,6 +70,7 @@ import http2 from "ext:deno_node/http2.ts";
 import https from "ext:deno_node/https.ts";
 import inspector from "ext:deno_node/inspector.ts";
 import internalCp from "ext:deno_node/internal/child_process.ts";
+import internalCryptoArgon2 from "ext:deno_node/internal/crypto/argon2.ts";
 import internalCryptoCertificate from "ext:deno_node/internal/crypto/certificate.ts";
 import internalCryptoCipher from "ext:deno_node/internal/crypto/cipher.ts";
 import internalCryptoDiffiehellman from "ext:deno_node/internal/crypto/diffiehellman.ts";
@@ -164,6 +165,7 @@ function setupBuiltinModules() {
     https,
     inspector,
     "internal/child_process": internalCp,
+    "internal/crypto/argon2": internalCryptoArgon2,
     "internal/crypto/certificate": internalCryptoCertificate,
     "internal/crypto/cipher": internalCryptoCipher,
     "internal/crypto/diffiehellman": internalCryptoDiffiehellman,
@@ -4,12 +4,23 @@
 import { validateFunction } from "ext:deno_node/internal/validators.mjs";
 import { _exiting } from "ext:deno_node/_process/exiting.ts";
 import { FixedQueue } from "ext:deno_node/internal/fixed_queue.ts";
+import {
+  async_id_symbol,
+  destroyHooksExist,
+  emitAfter,
+  emitBefore,
+  emitDestroy,
+  initAsyncResource,
+  trigger_async_id_symbol,
+} from "ext:deno_node/internal/async_hooks.ts";
 
 const { core } = globalThis.__bootstrap;
 
 interface Tock {
   callback: (...args: Array<unknown>) => void;
   args: Array<unknown>;
+  [async_id_symbol]: number;
+  [trigger_async_id_symbol]: number;
 }
 
 let nextTickEnabled = false;
@@ -24,9 +35,8 @@ export function processTicksAndRejections() {
   do {
     // deno-lint-ignore no-cond-assign
     while (tock = queue.shift()) {
-      // FIXME(bartlomieju): Deno currently doesn't support async hooks
-      // const asyncId = tock[async_id_symbol];
-      // emitBefore(asyncId, tock[trigger_async_id_symbol], tock);
+      const asyncId = (tock as Tock)[async_id_symbol];
+      emitBefore(asyncId, (tock as Tock)[trigger_async_id_symbol], tock);
 
       try {
         const callback = (tock as Tock).callback;
@@ -52,13 +62,13 @@ export function processTicksAndRejections() {
           }
         }
       } finally {
-        // FIXME(bartlomieju): Deno currently doesn't support async hooks
-        // if (destroyHooksExist())
-        // emitDestroy(asyncId);
+        if (destroyHooksExist()) {
+          emitDestroy(asyncId);
+        }
+        // Unlike Node, this also runs when the callback throws, so that the
+        // execution context is always restored.
+        emitAfter(asyncId);
       }
-
-      // FIXME(bartlomieju): Deno currently doesn't support async hooks
-      // emitAfter(asyncId);
     }
     core.runMicrotasks();
     // FIXME(bartlomieju): Deno currently doesn't unhandled rejections
@@ -133,18 +143,10 @@ export function nextTick<T extends Array<unknown>>(
   if (queue.isEmpty()) {
     core.setHasTickScheduled(true);
   }
-  // FIXME(bartlomieju): Deno currently doesn't support async hooks
-  // const asyncId = newAsyncId();
-  // const triggerAsyncId = getDefaultTriggerAsyncId();
   const tickObject = {
-    // FIXME(bartlomieju): Deno currently doesn't support async hooks
-    // [async_id_symbol]: asyncId,
-    // [trigger_async_id_symbol]: triggerAsyncId,
     callback,
     args: args_,
   };
-  // FIXME(bartlomieju): Deno currently doesn't support async hooks
-  // if (initHooksExist())
-  //   emitInit(asyncId, 'TickObject', triggerAsyncId, tickObject);
+  initAsyncResource(tickObject, "TickObject");
   queue.push(tickObject);
 }
@@ -5,6 +5,21 @@
 // https://github.com/cloudflare/workerd/blob/77fd0ed6ddba184414f0216508fc62b06e716cab/src/workerd/api/node/async-hooks.c++#L9
 
 import { validateFunction } from "ext:deno_node/internal/validators.mjs";
+import {
+  async_id_symbol,
+  AsyncHook,
+  emitAfter,
+  emitBefore,
+  emitDestroy,
+  emitInit,
+  executionAsyncId,
+  executionAsyncResource,
+  getDefaultTriggerAsyncId,
+  newAsyncId,
+  setAsyncContext,
+  trigger_async_id_symbol,
+  triggerAsyncId,
+} from "ext:deno_node/internal/async_hooks.ts";
 
 const { core } = globalThis.__bootstrap;
 
@@ -176,12 +191,28 @@ class AsyncContextFrame {
   }
 }
 
+interface AsyncResourceOptions {
+  triggerAsyncId?: number;
+  requireManualDestroy?: boolean;
+}
+
 export class AsyncResource {
   frame: AsyncContextFrame;
   type: string;
-  constructor(type: string) {
+  [async_id_symbol]: number;
+  [trigger_async_id_symbol]: number;
+  constructor(type: string, opts: number | AsyncResourceOptions = {}) {
     this.type = type;
     this.frame = AsyncContextFrame.current();
+
+    if (typeof opts === "number") {
+      opts = { triggerAsyncId: opts };
+    }
+    const triggerAsyncId = opts.triggerAsyncId ?? getDefaultTriggerAsyncId();
+    const asyncId = newAsyncId();
+    this[async_id_symbol] = asyncId;
+    this[trigger_async_id_symbol] = triggerAsyncId;
+    emitInit(asyncId, type, triggerAsyncId, this);
   }
 
   runInAsyncScope(
@@ -189,15 +220,31 @@ export class AsyncResource {
     thisArg: unknown,
     ...args: unknown[]
   ) {
+    const asyncId = this[async_id_symbol];
     Scope.enter(this.frame);
+    emitBefore(asyncId, this[trigger_async_id_symbol], this);
 
     try {
       return fn.apply(thisArg, args);
     } finally {
+      emitAfter(asyncId);
       Scope.exit();
     }
   }
 
+  emitDestroy() {
+    emitDestroy(this[async_id_symbol]);
+    return this;
+  }
+
+  asyncId() {
+    return this[async_id_symbol];
+  }
+
+  triggerAsyncId() {
+    return this[trigger_async_id_symbol];
+  }
+
   bind(fn: (...args: unknown[]) => unknown, thisArg = this) {
     validateFunction(fn, "fn");
     const frame = AsyncContextFrame.current();
@@ -309,20 +356,20 @@ export class AsyncLocalStorage {
   }
 }
 
-export function executionAsyncId() {
-  return 1;
-}
-
-class AsyncHook {
-  enable() {
-  }
+// Lets timers and ticks restore the store that was current when they were
+// scheduled.
+setAsyncContext({
+  current: () => AsyncContextFrame.current(),
+  enter: (frame) => Scope.enter(frame as AsyncContextFrame),
+  exit: () => Scope.exit(),
+});
 
-  disable() {
-  }
-}
+export { executionAsyncId, executionAsyncResource, triggerAsyncId };
 
-export function createHook() {
-  return new AsyncHook();
+export function createHook(
+  fns: ConstructorParameters<typeof AsyncHook>[0],
+) {
+  return new AsyncHook(fns);
 }
 
 // Placing all exports down here because the exported classes won't export
@@ -331,6 +378,8 @@ export default {
   // Embedder API
   AsyncResource,
   executionAsyncId,
+  executionAsyncResource,
+  triggerAsyncId,
   createHook,
   AsyncLocalStorage,
 };
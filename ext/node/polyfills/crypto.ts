@@ -33,6 +33,7 @@ import type {
   NormalizedAlgorithms,
 } from "ext:deno_node/internal/crypto/pbkdf2.ts";
 import { scrypt, scryptSync } from "ext:deno_node/internal/crypto/scrypt.ts";
+import { argon2, argon2Sync } from "ext:deno_node/internal/crypto/argon2.ts";
 import { hkdf, hkdfSync } from "ext:deno_node/internal/crypto/hkdf.ts";
 import {
   generateKey,
@@ -300,6 +301,8 @@ const sign = signOneShot;
 const verify = verifyOneShot;
 
 export default {
+  argon2,
+  argon2Sync,
   Certificate,
   checkPrime,
   checkPrimeSync,
@@ -435,6 +438,8 @@ export type {
 };
 
 export {
+  argon2,
+  argon2Sync,
   Certificate,
   checkPrime,
   checkPrimeSync,
@@ -5,74 +5,187 @@ import { notImplemented, warnNotImplemented } from "ext:deno_node/_utils.ts";
 import { EventEmitter } from "ext:deno_node/events.ts";
 import { Buffer } from "ext:deno_node/buffer.ts";
 import { Server, Socket, TCP } from "ext:deno_node/net.ts";
+import { Duplex, Readable } from "ext:deno_node/stream.ts";
 import { TypedArray } from "ext:deno_node/internal/util/types.ts";
-import { setStreamTimeout } from "ext:deno_node/internal/stream_base_commons.ts";
 import { FileHandle } from "ext:deno_node/fs/promises.ts";
 import { kStreamBaseField } from "ext:deno_node/internal_binding/stream_wrap.ts";
 import { addTrailers, serveHttpOnConnection } from "ext:deno_http/00_serve.js";
 import { type Deferred, deferred } from "ext:deno_node/_util/async.ts";
 import { nextTick } from "ext:deno_node/_next_tick.ts";
-import { TextEncoder } from "ext:deno_web/08_text_encoding.js";
+import {
+  ERR_HTTP2_HEADERS_SENT,
+  ERR_HTTP2_INVALID_PSEUDOHEADER,
+  ERR_HTTP2_INVALID_SESSION,
+  ERR_HTTP2_INVALID_STREAM,
+  ERR_HTTP2_PUSH_DISABLED,
+  ERR_HTTP2_STATUS_INVALID,
+  ERR_HTTP2_STREAM_ERROR,
+  ERR_HTTP2_TRAILERS_ALREADY_SENT,
+  ERR_HTTP2_TRAILERS_NOT_READY,
+  ERR_HTTP2_UNSUPPORTED_PROTOCOL,
+} from "ext:deno_node/internal/errors.ts";
+
+const { core } = globalThis.__bootstrap;
 
-const ENCODER = new TextEncoder();
 type Http2Headers = Record<string, string | string[]>;
+type HeaderList = [string, string][];
+
+const promiseIdSymbol = Symbol.for("Deno.core.internalPromiseId");
+
+// Methods for which a request has no body unless `endStream: false` is
+// passed explicitly.
+const kNoPayloadMethods = new Set(["DELETE", "GET", "HEAD"]);
+
+// Splits `headers` into the pseudo-headers and the list of regular headers
+// sent on the wire. Header names are lowercased, like in Node.
+function toHeaderList(
+  headers: Http2Headers | undefined,
+): [Record<string, string>, HeaderList] {
+  const pseudoHeaders: Record<string, string> = {};
+  const headerList: HeaderList = [];
+  if (headers == null) {
+    return [pseudoHeaders, headerList];
+  }
+  for (const name of Object.keys(headers)) {
+    const value = headers[name];
+    if (value === undefined) {
+      continue;
+    }
+    const lowerName = name.toLowerCase();
+    if (lowerName.startsWith(":")) {
+      if (Array.isArray(value)) {
+        throw new ERR_HTTP2_INVALID_PSEUDOHEADER(lowerName);
+      }
+      pseudoHeaders[lowerName] = String(value);
+    } else if (Array.isArray(value)) {
+      for (const item of value) {
+        headerList.push([lowerName, String(item)]);
+      }
+    } else {
+      headerList.push([lowerName, String(value)]);
+    }
+  }
+  return [pseudoHeaders, headerList];
+}
+
+// Merges a list of headers received on the wire into an object, with
+// `set-cookie` and repeated headers collected into arrays.
+function fromHeaderList(
+  headerList: Iterable<[string, string]>,
+  headers: Http2Headers = {},
+): Http2Headers {
+  for (const [name, value] of headerList) {
+    const existing = headers[name];
+    if (existing === undefined) {
+      headers[name] = name === constants.HTTP2_HEADER_SET_COOKIE
+        ? [value]
+        : value;
+    } else if (Array.isArray(existing)) {
+      existing.push(value);
+    } else {
+      headers[name] = [existing, value];
+    }
+  }
+  return headers;
+}
 
 export class Http2Session extends EventEmitter {
-  constructor() {
+  #type: number;
+  #closed = false;
+  #destroyed = false;
+  #connecting: boolean;
+  #streams = new Set<Http2Stream>();
+  _alpnProtocol: string | undefined = undefined;
+  _encrypted = false;
+  timeout = 0;
+
+  constructor(type: number, connecting: boolean) {
     super();
+    this.#type = type;
+    this.#connecting = connecting;
   }
 
   get alpnProtocol(): string | undefined {
-    notImplemented("Http2Session.alpnProtocol");
-    return undefined;
+    return this._alpnProtocol;
   }
 
-  close(_callback?: () => void) {
-    warnNotImplemented("Http2Session.close");
+  close(callback?: () => void) {
+    if (this.#closed || this.#destroyed) {
+      return;
+    }
+    this.#closed = true;
+    if (typeof callback === "function") {
+      this.once("close", callback);
+    }
+    // Gracefully close the session once all the open streams are done.
+    if (this.#streams.size === 0) {
+      this.destroy();
+    }
   }
 
   get closed(): boolean {
-    return false;
+    return this.#closed;
   }
 
   get connecting(): boolean {
-    notImplemented("Http2Session.connecting");
-    return false;
+    return this.#connecting;
   }
 
-  destroy(_error?: Error, _code?: number) {
-    notImplemented("Http2Session.destroy");
+  destroy(error?: Error, code?: number) {
+    if (this.#destroyed) {
+      return;
+    }
+    this.#destroyed = true;
+    this.#closed = true;
+
+    if (
+      error === undefined && code !== undefined &&
+      code !== constants.NGHTTP2_NO_ERROR
+    ) {
+      error = new ERR_HTTP2_STREAM_ERROR(String(code));
+    }
+    for (const stream of this.#streams) {
+      stream.destroy(error);
+    }
+    this._destroyHandle();
+
+    nextTick(() => {
+      if (error) {
+        this.emit("error", error);
+      }
+      this.emit("close");
+    });
   }
 
   get destroyed(): boolean {
-    return false;
+    return this.#destroyed;
   }
 
-  get encrypted(): boolean {
-    notImplemented("Http2Session.encrypted");
-    return false;
+  get encrypted(): boolean | undefined {
+    if (this.#connecting) {
+      return undefined;
+    }
+    return this._encrypted;
   }
 
   goaway(
-    _code: number,
-    _lastStreamID: number,
-    _opaqueData: Buffer | TypedArray | DataView,
+    code = constants.NGHTTP2_NO_ERROR,
+    _lastStreamID?: number,
+    _opaqueData?: Buffer | TypedArray | DataView,
   ) {
-    notImplemented("Http2Session.goaway");
+    warnNotImplemented("Http2Session.goaway");
+    this.destroy(undefined, code);
   }
 
   get localSettings(): Record<string, unknown> {
-    notImplemented("Http2Session.localSettings");
-    return {};
+    return getDefaultSettings();
   }
 
   get originSet(): string[] | undefined {
-    notImplemented("Http2Session.originSet");
     return undefined;
   }
 
   get pendingSettingsAck(): boolean {
-    notImplemented("Http2Session.pendingSettingsAck");
     return false;
   }
 
@@ -85,12 +198,10 @@ export class Http2Session extends EventEmitter {
   }
 
   ref() {
-    warnNotImplemented("Http2Session.ref");
   }
 
   get remoteSettings(): Record<string, unknown> {
-    notImplemented("Http2Session.remoteSettings");
-    return {};
+    return getDefaultSettings();
   }
 
   setLocalWindowSize(_windowSize: number) {
@@ -98,34 +209,60 @@ export class Http2Session extends EventEmitter {
   }
 
   setTimeout(msecs: number, callback?: () => void) {
-    setStreamTimeout(this, msecs, callback);
+    this.timeout = msecs;
+    if (callback !== undefined) {
+      this.on("timeout", callback);
+    }
   }
 
-  get socket(): Socket /*| TlsSocket*/ {
-    return {};
+  get socket(): Socket /*| TlsSocket*/ | undefined {
+    return undefined;
   }
 
   get state(): Record<string, unknown> {
     return {};
   }
 
-  settings(_settings: Record<string, unknown>, _callback: () => void) {
+  settings(
+    _settings: Record<string, unknown>,
+    _callback: () => void,
+  ) {
     notImplemented("Http2Session.settings");
   }
 
   get type(): number {
-    notImplemented("Http2Session.type");
-    return 0;
+    return this.#type;
   }
 
   unref() {
-    warnNotImplemented("Http2Session.unref");
+  }
+
+  _connected() {
+    this.#connecting = false;
+  }
+
+  _addStream(stream: Http2Stream) {
+    this.#streams.add(stream);
+  }
+
+  _removeStream(stream: Http2Stream) {
+    this.#streams.delete(stream);
+    if (this.#closed && this.#streams.size === 0) {
+      this.destroy();
+    }
+  }
+
+  // Releases the underlying connection.
+  _destroyHandle() {
   }
 }
 
 export class ServerHttp2Session extends Http2Session {
+  #abortController = new AbortController();
+  #server;
+
   constructor() {
-    super();
+    super(constants.NGHTTP2_SESSION_SERVER, false);
   }
 
   altsvc(
@@ -138,196 +275,295 @@ export class ServerHttp2Session extends Http2Session {
   origin(..._origins: (string | URL | { origin: string })[]) {
     notImplemented("ServerHttp2Session.origins");
   }
+
+  _serve(
+    conn: Deno.Conn,
+    onStream: (stream: ServerHttp2Stream, headers: Http2Headers) => void,
+  ) {
+    this._encrypted = false;
+    this._alpnProtocol = "h2c";
+    this.#server = serveHttpOnConnection(
+      conn,
+      this.#abortController.signal,
+      (req: Request) => {
+        const url = new URL(req.url);
+        const headers: Http2Headers = {
+          [constants.HTTP2_HEADER_METHOD]: req.method,
+          [constants.HTTP2_HEADER_SCHEME]: url.protocol.slice(0, -1),
+          [constants.HTTP2_HEADER_AUTHORITY]: req.headers.get("host") ??
+            url.host,
+          [constants.HTTP2_HEADER_PATH]: url.pathname + url.search,
+        };
+        fromHeaderList(req.headers, headers);
+        const stream = new ServerHttp2Stream(this, req);
+        this._addStream(stream);
+        onStream(stream, headers);
+        return stream._response;
+      },
+      (error: Error) => {
+        this.emit("error", error);
+        return new Response("", { status: 500 });
+      },
+      () => {},
+    );
+    this.#server.finished.then(() => this.destroy());
+  }
+
+  _destroyHandle() {
+    this.#abortController.abort();
+  }
 }
 
 export class ClientHttp2Session extends Http2Session {
+  #url: URL;
+  #clientRid: number | undefined = undefined;
+  #connRid: number | undefined = undefined;
+  #connectPromise: Deferred<void> = deferred();
+  #pollPromise: Promise<void> | undefined = undefined;
+  #refed = true;
+
   constructor(
-    _authority: string | URL,
-    _options: Record<string, unknown>,
-    callback: (session: Http2Session) => void,
+    authority: string | URL,
+    options: Record<string, unknown>,
+    callback?: (session: Http2Session) => void,
   ) {
-    super();
-    if (callback) {
-      this.on("connect", callback);
+    super(constants.NGHTTP2_SESSION_CLIENT, true);
+    this.#url = authority instanceof URL ? authority : new URL(authority);
+    if (this.#url.protocol !== "http:" && this.#url.protocol !== "https:") {
+      throw new ERR_HTTP2_UNSUPPORTED_PROTOCOL(this.#url.protocol);
     }
-    nextTick(() => this.emit("connect", this));
-  }
-
-  request(
-    headers: Http2Headers,
-    _options?: Record<string, unknown>,
-  ): ClientHttp2Stream {
-    const reqHeaders: string[][] = [];
-    const controllerPromise: Deferred<
-      ReadableStreamDefaultController<Uint8Array>
-    > = deferred();
-    const body = new ReadableStream({
-      start(controller) {
-        controllerPromise.resolve(controller);
-      },
-    });
-    const request: RequestInit = { headers: reqHeaders, body };
-    let authority = null;
-    let path = null;
-    for (const [name, value] of Object.entries(headers)) {
-      if (name == constants.HTTP2_HEADER_PATH) {
-        path = String(value);
-      } else if (name == constants.HTTP2_HEADER_METHOD) {
-        request.method = String(value);
-      } else if (name == constants.HTTP2_HEADER_AUTHORITY) {
-        authority = String(value);
+    if (typeof callback === "function") {
+      this.once("connect", callback);
+    }
+    // Streams created before the connection is established wait for it, so
+    // a failed connection must not be reported as unhandled.
+    this.#connectPromise.catch(() => {});
+    this.#connect(options ?? {});
+  }
+
+  async #connect(options: Record<string, unknown>) {
+    const encrypted = this.#url.protocol === "https:";
+    const hostname = this.#url.hostname.replace(/^\[(.*)\]$/, "$1");
+    const port = Number(this.#url.port || (encrypted ? 443 : 80));
+
+    let conn;
+    try {
+      if (encrypted) {
+        const ca = options.ca;
+        conn = await Deno.connectTls({
+          hostname,
+          port,
+          caCerts: ca === undefined
+            ? undefined
+            : (Array.isArray(ca) ? ca : [ca]).map(String),
+          alpnProtocols: ["h2"],
+        });
       } else {
-        reqHeaders.push([name, String(value)]);
+        conn = await Deno.connect({ hostname, port });
       }
+      const { 0: clientRid, 1: connRid } = await core.opAsync(
+        "op_http2_connect",
+        conn.rid,
+        this.#url.href,
+      );
+      this.#clientRid = clientRid;
+      this.#connRid = connRid;
+    } catch (error) {
+      try {
+        conn?.close();
+      } catch {
+        // The connection was already handed over to the HTTP/2 client.
+      }
+      this.#connectPromise.reject(error);
+      this.destroy(error);
+      return;
+    }
+    if (this.destroyed) {
+      this._destroyHandle();
+      return;
     }
 
-    const fetchPromise = fetch(`http://${authority}${path}`, request);
-    const readerPromise = deferred();
-    const headersPromise = deferred();
-    (async () => {
-      const fetch = await fetchPromise;
-      readerPromise.resolve(fetch.body);
+    this._encrypted = encrypted;
+    this._alpnProtocol = encrypted ? "h2" : "h2c";
+    this._connected();
+    this.#connectPromise.resolve();
+    this.emit("connect", this, {});
 
-      const headers: Http2Headers = {};
-      for (const [key, value] of fetch.headers) {
-        headers[key] = value;
-      }
-      headers[constants.HTTP2_HEADER_STATUS] = String(fetch.status);
-
-      headersPromise.resolve(headers);
-    })();
-    return new ClientHttp2Stream(
-      this,
-      headersPromise,
-      controllerPromise,
-      readerPromise,
+    this.#pollPromise = core.opAsync(
+      "op_http2_poll_client_connection",
+      this.#connRid,
     );
+    if (!this.#refed) {
+      core.unrefOp(this.#pollPromise[promiseIdSymbol]);
+    }
+    try {
+      await this.#pollPromise;
+    } catch (error) {
+      this.destroy(error);
+      return;
+    }
+    // The server closed the connection.
+    this.destroy();
   }
-}
-
-export class Http2Stream extends EventEmitter {
-  #session: Http2Session;
-  #headers: Deferred<Http2Headers>;
-  #controllerPromise: Deferred<ReadableStreamDefaultController<Uint8Array>>;
-  #readerPromise: Deferred<ReadableStream<Uint8Array>>;
-  #closed: boolean;
-  _response: Response;
 
-  constructor(
-    session: Http2Session,
-    headers: Promise<Http2Headers>,
-    controllerPromise: Promise<ReadableStreamDefaultController<Uint8Array>>,
-    readerPromise: Promise<ReadableStream<Uint8Array>>,
-  ) {
-    super();
-    this.#session = session;
-    this.#headers = headers;
-    this.#controllerPromise = controllerPromise;
-    this.#readerPromise = readerPromise;
-    this.#closed = false;
-    nextTick(() => {
-      (async () => {
-        const headers = await this.#headers;
-        this.emit("headers", headers);
-      })();
-      (async () => {
-        const reader = await this.#readerPromise;
-        if (reader) {
-          for await (const data of reader) {
-            this.emit("data", new Buffer(data));
-          }
-        }
-        this.emit("end");
-      })();
-    });
+  ref() {
+    this.#refed = true;
+    if (this.#pollPromise) {
+      core.refOp(this.#pollPromise[promiseIdSymbol]);
+    }
   }
 
-  // TODO(mmastrac): Implement duplex
-  end() {
-    (async () => {
-      const controller = await this.#controllerPromise;
-      controller.close();
-    })();
+  unref() {
+    this.#refed = false;
+    if (this.#pollPromise) {
+      core.unrefOp(this.#pollPromise[promiseIdSymbol]);
+    }
   }
 
-  write(buffer, callback?: () => void) {
-    (async () => {
-      const controller = await this.#controllerPromise;
-      if (typeof buffer === "string") {
-        controller.enqueue(ENCODER.encode(buffer));
-      } else {
-        controller.enqueue(buffer);
-      }
-      callback?.();
-    })();
-  }
+  request(
+    headers: Http2Headers,
+    options?: Record<string, unknown>,
+  ): ClientHttp2Stream {
+    if (this.destroyed) {
+      throw new ERR_HTTP2_INVALID_SESSION();
+    }
 
-  resume() {
+    const [pseudoHeaders, headerList] = toHeaderList(headers);
+    pseudoHeaders[constants.HTTP2_HEADER_METHOD] ??= "GET";
+    pseudoHeaders[constants.HTTP2_HEADER_SCHEME] ??= this.#url.protocol
+      .slice(0, -1);
+    pseudoHeaders[constants.HTTP2_HEADER_AUTHORITY] ??= this.#url.host;
+    pseudoHeaders[constants.HTTP2_HEADER_PATH] ??= "/";
+
+    const endStream = options?.endStream ??
+      kNoPayloadMethods.has(pseudoHeaders[constants.HTTP2_HEADER_METHOD]);
+    const streamOptions = {
+      endStream: Boolean(endStream),
+      waitForTrailers: Boolean(options?.waitForTrailers),
+    };
+    const stream = new ClientHttp2Stream(this, streamOptions);
+    this._addStream(stream);
+    stream._sentHeaders = { ...headers, ...pseudoHeaders };
+    stream._start(
+      this.#connectPromise,
+      () => this.#clientRid!,
+      pseudoHeaders,
+      headerList,
+      streamOptions.endStream,
+    );
+    return stream;
   }
 
-  pause() {
+  _destroyHandle() {
+    if (this.#connRid !== undefined) {
+      core.tryClose(this.#connRid);
+      this.#connRid = undefined;
+    }
+    if (this.#clientRid !== undefined) {
+      core.tryClose(this.#clientRid);
+      this.#clientRid = undefined;
+    }
   }
+}
 
-  get aborted(): boolean {
-    notImplemented("Http2Stream.aborted");
-    return false;
-  }
+interface Http2StreamOptions {
+  endStream: boolean;
+  waitForTrailers: boolean;
+}
 
-  get bufferSize(): number {
-    notImplemented("Http2Stream.bufferSize");
-    return 0;
+export class Http2Stream extends Duplex {
+  #session: Http2Session;
+  #endStream: boolean;
+  #trailersReady = false;
+  #trailersSent = false;
+  #finalCallback: ((error?: Error | null) => void) | undefined = undefined;
+  _id: number | undefined = undefined;
+  _sentHeaders: Http2Headers = {};
+  _sentTrailers: Http2Headers | undefined = undefined;
+  _aborted = false;
+  _endAfterHeaders = false;
+  _waitForTrailers: boolean;
+  rstCode: number | undefined = undefined;
+  timeout = 0;
+
+  constructor(session: Http2Session, options: Http2StreamOptions) {
+    super({ allowHalfOpen: true, autoDestroy: false });
+    this.#session = session;
+    this.#endStream = options.endStream;
+    this._waitForTrailers = options.waitForTrailers;
+    if (this.#endStream) {
+      // Nothing is ever written to a stream that was ended with the headers.
+      this.end();
+    }
+    // The stream is done once both sides have ended.
+    let readableEnded = false;
+    let writableFinished = this.#endStream;
+    const maybeClose = () => {
+      if (readableEnded && writableFinished && !this.destroyed) {
+        this.rstCode ??= constants.NGHTTP2_NO_ERROR;
+        this.destroy();
+      }
+    };
+    this.once("end", () => {
+      readableEnded = true;
+      maybeClose();
+    });
+    this.once("finish", () => {
+      writableFinished = true;
+      maybeClose();
+    });
   }
 
-  close(_code: number, _callback: () => void) {
-    this.#closed = true;
-    this.emit("close");
+  get aborted(): boolean {
+    return this._aborted;
   }
 
-  get closed(): boolean {
-    return this.#closed;
+  get bufferSize(): number {
+    return this.writableLength;
   }
 
-  get destroyed(): boolean {
-    return false;
+  close(code = constants.NGHTTP2_NO_ERROR, callback?: () => void) {
+    if (this.closed) {
+      return;
+    }
+    if (typeof callback === "function") {
+      this.once("close", callback);
+    }
+    this.rstCode = code;
+    this._reset(code);
+    this.destroy(
+      code === constants.NGHTTP2_NO_ERROR
+        ? undefined
+        : new ERR_HTTP2_STREAM_ERROR(String(code)),
+    );
   }
 
   get endAfterHeaders(): boolean {
-    notImplemented("Http2Stream.endAfterHeaders");
-    return false;
+    return this._endAfterHeaders;
   }
 
   get id(): number | undefined {
-    notImplemented("Http2Stream.id");
-    return undefined;
+    return this._id;
   }
 
   get pending(): boolean {
-    notImplemented("Http2Stream.pending");
-    return false;
+    return this._id === undefined;
   }
 
   priority(_options: Record<string, unknown>) {
-    notImplemented("Http2Stream.priority");
-  }
-
-  get rstCode(): number {
-    // notImplemented("Http2Stream.rstCode");
-    return 0;
+    // Stream priorities are deprecated by RFC 9113.
   }
 
-  get sentHeaders(): boolean {
-    notImplemented("Http2Stream.sentHeaders");
-    return false;
+  get sentHeaders(): Http2Headers {
+    return this._sentHeaders;
   }
 
-  get sentInfoHeaders(): Record<string, unknown> {
-    notImplemented("Http2Stream.sentInfoHeaders");
-    return {};
+  get sentInfoHeaders(): Http2Headers[] {
+    return [];
   }
 
-  get sentTrailers(): Record<string, unknown> {
-    notImplemented("Http2Stream.sentTrailers");
-    return {};
+  get sentTrailers(): Http2Headers | undefined {
+    return this._sentTrailers;
   }
 
   get session(): Http2Session {
@@ -335,97 +571,361 @@ export class Http2Stream extends EventEmitter {
   }
 
   setTimeout(msecs: number, callback?: () => void) {
-    setStreamTimeout(this, msecs, callback);
+    this.timeout = msecs;
+    if (callback !== undefined) {
+      this.on("timeout", callback);
+    }
   }
 
   get state(): Record<string, unknown> {
-    notImplemented("Http2Stream.state");
-    return {};
+    return {
+      localClose: this.writableFinished ? 1 : 0,
+      remoteClose: this.readableEnded ? 1 : 0,
+      state: this.closed
+        ? constants.NGHTTP2_STREAM_STATE_CLOSED
+        : constants.NGHTTP2_STREAM_STATE_OPEN,
+      weight: constants.NGHTTP2_DEFAULT_WEIGHT,
+    };
+  }
+
+  sendTrailers(headers: Http2Headers) {
+    if (this.destroyed || this.closed) {
+      throw new ERR_HTTP2_INVALID_STREAM();
+    }
+    if (this.#trailersSent) {
+      throw new ERR_HTTP2_TRAILERS_ALREADY_SENT();
+    }
+    if (!this.#trailersReady) {
+      throw new ERR_HTTP2_TRAILERS_NOT_READY();
+    }
+    this.#trailersSent = true;
+    this._sentTrailers = headers;
+    const [, headerList] = toHeaderList(headers);
+    const done = headerList.length > 0
+      ? this._writeTrailers(headerList)
+      : this._endData();
+    done.then(
+      () => this.#finalCallback?.(),
+      (error) => this.#finalCallback?.(error),
+    );
+  }
+
+  _read() {
+    this._readData();
+  }
+
+  _write(
+    chunk: Buffer | string,
+    encoding: string,
+    callback: (error?: Error | null) => void,
+  ) {
+    const data = typeof chunk === "string"
+      ? Buffer.from(chunk, encoding)
+      : chunk;
+    this._writeData(new Uint8Array(data.buffer, data.byteOffset, data.length))
+      .then(() => callback(), callback);
+  }
+
+  _final(callback: (error?: Error | null) => void) {
+    if (this.#endStream) {
+      callback();
+      return;
+    }
+    if (this._waitForTrailers) {
+      this.#trailersReady = true;
+      this.#finalCallback = callback;
+      this.emit("wantTrailers");
+      return;
+    }
+    this._endData().then(() => callback(), callback);
   }
 
-  sendTrailers(_headers: Record<string, unknown>) {
-    addTrailers(this._response, [["grpc-status", "0"], ["grpc-message", "OK"]]);
+  _destroy(error: Error | null, callback: (error?: Error | null) => void) {
+    if (this.rstCode === undefined) {
+      this.rstCode = error
+        ? constants.NGHTTP2_INTERNAL_ERROR
+        : constants.NGHTTP2_CANCEL;
+      this._reset(this.rstCode);
+    }
+    this._closeHandle();
+    this.#session._removeStream(this);
+    if (!this.readableEnded && this.rstCode !== constants.NGHTTP2_NO_ERROR) {
+      this._aborted = true;
+      this.emit("aborted");
+    }
+    callback(error);
   }
+
+  // The following methods are implemented by the client and server streams.
+
+  _readData() {}
+
+  async _writeData(_data: Uint8Array) {}
+
+  async _endData() {}
+
+  async _writeTrailers(_headerList: HeaderList) {}
+
+  _reset(_code: number) {}
+
+  _closeHandle() {}
 }
 
 export class ClientHttp2Stream extends Http2Stream {
-  constructor(
-    session: Http2Session,
-    headers: Promise<Http2Headers>,
-    controllerPromise: Deferred<ReadableStreamDefaultController<Uint8Array>>,
-    readerPromise: Deferred<ReadableStream<Uint8Array>>,
+  #rid: number | undefined = undefined;
+  #ready: Deferred<void> = deferred();
+  #bodyRid: number | undefined = undefined;
+  #resetPromise: Promise<void> | undefined = undefined;
+  #reading = false;
+
+  constructor(session: Http2Session, options: Http2StreamOptions) {
+    super(session, options);
+    this.#ready.catch(() => {});
+  }
+
+  async _start(
+    connectPromise: Promise<void>,
+    clientRid: () => number,
+    pseudoHeaders: Record<string, string>,
+    headerList: HeaderList,
+    endStream: boolean,
   ) {
-    super(session, headers, controllerPromise, readerPromise);
+    try {
+      await connectPromise;
+      if (this.destroyed) {
+        return;
+      }
+      const { 0: rid, 1: id } = await core.opAsync(
+        "op_http2_client_request",
+        clientRid(),
+        pseudoHeaders,
+        headerList,
+        endStream,
+      );
+      this.#rid = rid;
+      this._id = id;
+    } catch (error) {
+      this.#ready.reject(error);
+      this.destroy(error);
+      return;
+    }
+    if (this.destroyed) {
+      core.tryClose(this.#rid);
+      return;
+    }
+    this.#ready.resolve();
+    this.emit("ready");
+
+    let response;
+    try {
+      response = await core.opAsync(
+        "op_http2_client_get_response",
+        this.#rid,
+      );
+    } catch (error) {
+      this.destroy(error);
+      return;
+    }
+    if (this.destroyed) {
+      core.tryClose(response.bodyRid);
+      return;
+    }
+    this.#bodyRid = response.bodyRid;
+    this._endAfterHeaders = response.endStream;
+
+    const headers = fromHeaderList(response.headers, {
+      [constants.HTTP2_HEADER_STATUS]: String(response.status),
+    });
+    let flags = constants.NGHTTP2_FLAG_END_HEADERS;
+    if (response.endStream) {
+      flags |= constants.NGHTTP2_FLAG_END_STREAM;
+    }
+    this.emit("response", headers, flags);
+    if (this.#reading) {
+      this._readData();
+    }
+  }
+
+  async _readData() {
+    if (this.#bodyRid === undefined) {
+      // Reading starts once the response headers are received.
+      this.#reading = true;
+      return;
+    }
+    this.#reading = false;
+    try {
+      const chunk = await core.opAsync(
+        "op_http2_client_get_response_body_chunk",
+        this.#bodyRid,
+      );
+      if (this.destroyed) {
+        return;
+      }
+      if (chunk !== null) {
+        this.push(Buffer.from(chunk.buffer, chunk.byteOffset, chunk.length));
+        return;
+      }
+      const trailers = await core.opAsync(
+        "op_http2_client_get_response_trailers",
+        this.#bodyRid,
+      );
+      if (this.destroyed) {
+        return;
+      }
+      if (trailers !== null) {
+        this.emit(
+          "trailers",
+          fromHeaderList(trailers),
+          constants.NGHTTP2_FLAG_END_STREAM,
+        );
+      }
+      this.push(null);
+    } catch (error) {
+      this.destroy(error);
+    }
+  }
+
+  async _writeData(data: Uint8Array) {
+    await this.#ready;
+    await core.opAsync("op_http2_client_send_data", this.#rid, data, false);
+  }
+
+  async _endData() {
+    await this.#ready;
+    await core.opAsync(
+      "op_http2_client_send_data",
+      this.#rid,
+      new Uint8Array(),
+      true,
+    );
+  }
+
+  async _writeTrailers(headerList: HeaderList) {
+    await this.#ready;
+    await core.opAsync(
+      "op_http2_client_send_trailers",
+      this.#rid,
+      headerList,
+    );
+  }
+
+  _reset(code: number) {
+    if (this.#rid !== undefined) {
+      this.#resetPromise = core.opAsync(
+        "op_http2_client_reset_stream",
+        this.#rid,
+        code,
+      ).catch(() => {});
+    }
+  }
+
+  _closeHandle() {
+    if (this.#bodyRid !== undefined) {
+      core.tryClose(this.#bodyRid);
+    }
+    if (this.#rid !== undefined) {
+      // Let a pending reset go out first.
+      const rid = this.#rid;
+      (this.#resetPromise ?? Promise.resolve()).then(() => core.tryClose(rid));
+    }
   }
 }
 
 export class ServerHttp2Stream extends Http2Stream {
-  _promise: Deferred<Response>;
+  #reader: ReadableStreamDefaultReader<Uint8Array> | undefined;
+  #controller: ReadableStreamDefaultController<Uint8Array> | undefined =
+    undefined;
   #body: ReadableStream<Uint8Array>;
-  #waitForTrailers: boolean;
-  #headersSent: boolean;
+  #response: Deferred<Response> = deferred();
+  #headersSent = false;
+
+  constructor(session: Http2Session, request: Request) {
+    super(session, { endStream: false, waitForTrailers: false });
+    this.#reader = request.body?.getReader();
+    this.#body = new ReadableStream({
+      start: (controller) => {
+        this.#controller = controller;
+      },
+    });
+    if (this.#reader === undefined) {
+      // Let the stream close without waiting for the request to be read.
+      this.push(null);
+      this.read(0);
+    }
+  }
 
-  constructor(
-    session: Http2Session,
-    headers: Promise<Http2Headers>,
-    controllerPromise: Promise<ReadableStreamDefaultController<Uint8Array>>,
-    reader: ReadableStream<Uint8Array>,
-    body: ReadableStream<Uint8Array>,
-  ) {
-    super(session, headers, controllerPromise, Promise.resolve(reader));
-    this._promise = new deferred();
-    this.#body = body;
+  get _response(): Promise<Response> {
+    return this.#response;
   }
 
   additionalHeaders(_headers: Record<string, unknown>) {
     notImplemented("ServerHttp2Stream.additionalHeaders");
   }
 
-  end(): void {
-    super.end();
-    if (this.#waitForTrailers) {
-      this.emit("wantTrailers");
-    }
-  }
-
   get headersSent(): boolean {
     return this.#headersSent;
   }
 
   get pushAllowed(): boolean {
-    notImplemented("ServerHttp2Stream.pushAllowed");
     return false;
   }
 
   pushStream(
     _headers: Record<string, unknown>,
-    _options: Record<string, unknown>,
-    _callback: () => unknown,
+    _options: Record<string, unknown> | (() => unknown),
+    callback?: (error: Error | null) => unknown,
   ) {
-    notImplemented("ServerHttp2Stream.pushStream");
+    if (typeof _options === "function") {
+      callback = _options;
+    }
+    const error = new ERR_HTTP2_PUSH_DISABLED();
+    if (typeof callback === "function") {
+      nextTick(() => callback!(error));
+      return;
+    }
+    throw error;
   }
 
   respond(
-    headers: Http2Headers,
-    options: Record<string, unknown>,
+    headers?: Http2Headers,
+    options?: Record<string, unknown>,
   ) {
+    if (this.destroyed || this.closed) {
+      throw new ERR_HTTP2_INVALID_STREAM();
+    }
+    if (this.#headersSent) {
+      throw new ERR_HTTP2_HEADERS_SENT();
+    }
     this.#headersSent = true;
-    const response: ResponseInit = {};
-    if (headers) {
-      for (const [name, value] of Object.entries(headers)) {
-        if (name == constants.HTTP2_HEADER_STATUS) {
-          response.status = Number(value);
-        }
-      }
+
+    const [pseudoHeaders, headerList] = toHeaderList(headers);
+    const status = Number(pseudoHeaders[constants.HTTP2_HEADER_STATUS] ?? 200);
+    if (status < 200 || status > 599) {
+      throw new ERR_HTTP2_STATUS_INVALID(String(status));
     }
-    if (options?.endStream) {
-      this._promise.resolve(this._response = new Response("", response));
-    } else {
-      this.#waitForTrailers = options?.waitForTrailers;
-      this._promise.resolve(
-        this._response = new Response(this.#body, response),
-      );
+    this._sentHeaders = {
+      ...headers,
+      [constants.HTTP2_HEADER_STATUS]: String(status),
+    };
+
+    const endStream = Boolean(options?.endStream);
+    // 204, 205 and 304 responses can't have a body.
+    const body = endStream || status === 204 || status === 205 ||
+        status === 304
+      ? null
+      : this.#body;
+    if (body === null) {
+      this.#controller?.close();
+      this.#controller = undefined;
+      if (!this.writableEnded) {
+        this.end();
+      }
+    } else if (options?.waitForTrailers) {
+      this._waitForTrailers = true;
     }
+    this.#response.resolve(
+      new Response(body, { status, headers: headerList }),
+    );
   }
 
   respondWithFD(
@@ -443,12 +943,74 @@ export class ServerHttp2Stream extends Http2Stream {
   ) {
     notImplemented("ServerHttp2Stream.respondWithFile");
   }
+
+  async _readData() {
+    if (this.#reader === undefined) {
+      return;
+    }
+    try {
+      const { value, done } = await this.#reader.read();
+      if (this.destroyed) {
+        return;
+      }
+      if (done) {
+        this.push(null);
+      } else {
+        this.push(Buffer.from(value.buffer, value.byteOffset, value.length));
+      }
+    } catch (error) {
+      this._aborted = true;
+      this.destroy(error);
+    }
+  }
+
+  async _writeData(data: Uint8Array) {
+    if (!this.#headersSent) {
+      this.respond();
+    }
+    // Copy the chunk, as the writer may reuse its buffer.
+    this.#controller?.enqueue(data.slice());
+  }
+
+  async _endData() {
+    if (!this.#headersSent) {
+      this.respond();
+    }
+    this.#controller?.close();
+    this.#controller = undefined;
+  }
+
+  async _writeTrailers(headerList: HeaderList) {
+    const response = await this.#response;
+    addTrailers(response, headerList);
+    await this._endData();
+  }
+
+  _reset(code: number) {
+    if (!this.#headersSent) {
+      this.#headersSent = true;
+      this.#response.resolve(new Response(null, { status: 500 }));
+    }
+    if (code !== constants.NGHTTP2_NO_ERROR) {
+      this.#controller?.error(new ERR_HTTP2_STREAM_ERROR(String(code)));
+      this.#controller = undefined;
+    }
+  }
+
+  _closeHandle() {
+    this.#reader?.cancel().catch(() => {});
+    try {
+      this.#controller?.close();
+    } catch {
+      // The response body was already closed.
+    }
+    this.#controller = undefined;
+  }
 }
 
 export class Http2Server extends Server {
   #options: Record<string, unknown> = {};
-  #abortController;
-  #server;
+  #sessions = new Set<ServerHttp2Session>();
   timeout = 0;
 
   constructor(
@@ -456,61 +1018,26 @@ export class Http2Server extends Server {
     requestListener: () => unknown,
   ) {
     super(options);
-    this.#abortController = new AbortController();
     this.on(
       "connection",
       (conn: Deno.Conn) => {
-        try {
-          const session = new ServerHttp2Session();
-          this.emit("session", session);
-          this.#server = serveHttpOnConnection(
-            conn,
-            this.#abortController.signal,
-            async (req: Request) => {
-              try {
-                const controllerPromise: Deferred<
-                  ReadableStreamDefaultController<Uint8Array>
-                > = deferred();
-                const body = new ReadableStream({
-                  start(controller) {
-                    controllerPromise.resolve(controller);
-                  },
-                });
-                const headers: Http2Headers = {};
-                for (const [name, value] of req.headers) {
-                  headers[name] = value;
-                }
-                headers[constants.HTTP2_HEADER_PATH] =
-                  new URL(req.url).pathname;
-                const stream = new ServerHttp2Stream(
-                  session,
-                  Promise.resolve(headers),
-                  controllerPromise,
-                  req.body,
-                  body,
-                );
-                session.emit("stream", stream, headers);
-                this.emit("stream", stream, headers);
-                return await stream._promise;
-              } catch (e) {
-                console.log("Error in serveHttpOnConnection", e);
-              }
-              return new Response("");
-            },
-            () => {
-              console.log("error");
-            },
-            () => {},
-          );
-        } catch (e) {
-          console.log("Error in Http2Server", e);
-        }
+        const session = new ServerHttp2Session();
+        this.#sessions.add(session);
+        session.once("close", () => this.#sessions.delete(session));
+        session.on("error", (error) => this.emit("sessionError", error));
+        this.emit("session", session);
+        session._serve(conn, (stream, headers) => {
+          session.emit("stream", stream, headers, 0);
+          this.emit("stream", stream, headers, 0);
+          if (this.listenerCount("request") > 0) {
+            const request = new Http2ServerRequest(stream, headers);
+            const response = new Http2ServerResponse(stream);
+            response._req = request;
+            this.emit("request", request, response);
+          }
+        });
       },
     );
-    this.on(
-      "newListener",
-      (event) => console.log(`Event in newListener: ${event}`),
-    );
     this.#options = options;
     if (typeof requestListener === "function") {
       this.on("request", requestListener);
@@ -526,7 +1053,9 @@ export class Http2Server extends Server {
     if (callback) {
       this.on("close", callback);
     }
-    this.#abortController.abort();
+    for (const session of this.#sessions) {
+      session.close();
+    }
     super.close();
   }
 
@@ -599,6 +1128,10 @@ export function connect(
   options: Record<string, unknown>,
   callback: (session: ClientHttp2Session) => void,
 ): ClientHttp2Session {
+  if (typeof options === "function") {
+    callback = options;
+    options = {};
+  }
   return new ClientHttp2Session(authority, options, callback);
 }
 
@@ -846,219 +1379,337 @@ export const constants = {
   HTTP_STATUS_NETWORK_AUTHENTICATION_REQUIRED: 511,
 };
 
+// The identifiers of the settings in a SETTINGS frame, in the order used by
+// `getPackedSettings()`.
+const kSettingIds: [string, number][] = [
+  ["headerTableSize", 0x1],
+  ["enablePush", 0x2],
+  ["maxConcurrentStreams", 0x3],
+  ["initialWindowSize", 0x4],
+  ["maxFrameSize", 0x5],
+  ["maxHeaderListSize", 0x6],
+  ["enableConnectProtocol", 0x8],
+];
+
 export function getDefaultSettings(): Record<string, unknown> {
-  notImplemented("http2.getDefaultSettings");
-  return {};
+  return {
+    headerTableSize: constants.DEFAULT_SETTINGS_HEADER_TABLE_SIZE,
+    enablePush: true,
+    initialWindowSize: constants.DEFAULT_SETTINGS_INITIAL_WINDOW_SIZE,
+    maxFrameSize: constants.DEFAULT_SETTINGS_MAX_FRAME_SIZE,
+    maxConcurrentStreams: constants.DEFAULT_SETTINGS_MAX_CONCURRENT_STREAMS,
+    maxHeaderListSize: constants.DEFAULT_SETTINGS_MAX_HEADER_LIST_SIZE,
+    maxHeaderSize: constants.DEFAULT_SETTINGS_MAX_HEADER_LIST_SIZE,
+    enableConnectProtocol: false,
+  };
 }
 
-export function getPackedSettings(_settings: Record<string, unknown>): Buffer {
-  notImplemented("http2.getPackedSettings");
-  return {};
+export function getPackedSettings(
+  settings: Record<string, unknown> = {},
+): Buffer {
+  const entries = kSettingIds.filter(([name]) =>
+    settings[name] !== undefined
+  );
+  const buffer = Buffer.alloc(entries.length * 6);
+  entries.forEach(([name, id], i) => {
+    buffer.writeUInt16BE(id, i * 6);
+    buffer.writeUInt32BE(Number(settings[name]), i * 6 + 2);
+  });
+  return buffer;
 }
 
 export function getUnpackedSettings(
-  _buffer: Buffer | TypedArray,
+  buffer: Buffer | TypedArray,
 ): Record<string, unknown> {
-  notImplemented("http2.getUnpackedSettings");
-  return {};
+  const view = Buffer.from(buffer.buffer, buffer.byteOffset, buffer.byteLength);
+  const settings: Record<string, unknown> = {};
+  for (let offset = 0; offset + 6 <= view.length; offset += 6) {
+    const id = view.readUInt16BE(offset);
+    const value = view.readUInt32BE(offset + 2);
+    const setting = kSettingIds.find(([, settingId]) => settingId === id);
+    if (setting === undefined) {
+      continue;
+    }
+    const [name] = setting;
+    settings[name] = name === "enablePush" || name === "enableConnectProtocol"
+      ? value !== 0
+      : value;
+  }
+  return settings;
 }
 
 export const sensitiveHeaders = Symbol("nodejs.http2.sensitiveHeaders");
 
-export class Http2ServerRequest {
-  constructor() {
+export class Http2ServerRequest extends Readable {
+  #stream: ServerHttp2Stream;
+  #headers: Http2Headers;
+  #rawHeaders: string[] = [];
+  #trailers: Http2Headers = {};
+  #aborted = false;
+  #complete = false;
+
+  constructor(stream: ServerHttp2Stream, headers: Http2Headers) {
+    super({ autoDestroy: false });
+    this.#stream = stream;
+    this.#headers = headers;
+    for (const [name, value] of Object.entries(headers)) {
+      for (const item of Array.isArray(value) ? value : [value]) {
+        this.#rawHeaders.push(name, item);
+      }
+    }
+
+    stream.on("data", (chunk) => {
+      if (!this.push(chunk)) {
+        stream.pause();
+      }
+    });
+    stream.on("end", () => {
+      this.#complete = true;
+      this.push(null);
+    });
+    stream.on("aborted", () => {
+      this.#aborted = true;
+      this.emit("aborted");
+    });
+    stream.on("close", () => {
+      this.#complete = true;
+      this.emit("close");
+    });
+    // Like in Node, stream errors are not forwarded to the request.
+    stream.on("error", () => {});
   }
 
   get aborted(): boolean {
-    notImplemented("Http2ServerRequest.aborted");
-    return false;
+    return this.#aborted;
   }
 
   get authority(): string {
-    notImplemented("Http2ServerRequest.authority");
-    return "";
+    return this.#headers[constants.HTTP2_HEADER_AUTHORITY] as string;
   }
 
   get complete(): boolean {
-    notImplemented("Http2ServerRequest.complete");
-    return false;
+    return this.#complete;
   }
 
-  get connection(): Socket /*| TlsSocket*/ {
-    notImplemented("Http2ServerRequest.connection");
-    return {};
+  get connection(): Socket /*| TlsSocket*/ | undefined {
+    return this.#stream.session.socket;
   }
 
-  destroy(_error: Error) {
-    notImplemented("Http2ServerRequest.destroy");
+  destroy(error?: Error) {
+    this.#stream.destroy(error);
+    return super.destroy(error);
   }
 
-  get headers(): Record<string, unknown> {
-    notImplemented("Http2ServerRequest.headers");
-    return {};
+  get headers(): Http2Headers {
+    return this.#headers;
   }
 
   get httpVersion(): string {
-    notImplemented("Http2ServerRequest.httpVersion");
-    return "";
+    return "2.0";
+  }
+
+  get httpVersionMajor(): number {
+    return 2;
+  }
+
+  get httpVersionMinor(): number {
+    return 0;
   }
 
   get method(): string {
-    notImplemented("Http2ServerRequest.method");
-    return "";
+    return this.#headers[constants.HTTP2_HEADER_METHOD] as string;
   }
 
   get rawHeaders(): string[] {
-    notImplemented("Http2ServerRequest.rawHeaders");
-    return [];
+    return this.#rawHeaders;
   }
 
   get rawTrailers(): string[] {
-    notImplemented("Http2ServerRequest.rawTrailers");
     return [];
   }
 
   get scheme(): string {
-    notImplemented("Http2ServerRequest.scheme");
-    return "";
+    return this.#headers[constants.HTTP2_HEADER_SCHEME] as string;
   }
 
   setTimeout(msecs: number, callback?: () => unknown) {
-    this.stream.setTimeout(callback, msecs);
+    this.#stream.setTimeout(msecs, callback);
+    return this;
   }
 
-  get socket(): Socket /*| TlsSocket*/ {
-    notImplemented("Http2ServerRequest.socket");
-    return {};
+  get socket(): Socket /*| TlsSocket*/ | undefined {
+    return this.#stream.session.socket;
   }
 
   get stream(): Http2Stream {
-    notImplemented("Http2ServerRequest.stream");
-    return new Http2Stream();
+    return this.#stream;
   }
 
-  get trailers(): Record<string, unknown> {
-    notImplemented("Http2ServerRequest.trailers");
-    return {};
+  get trailers(): Http2Headers {
+    return this.#trailers;
   }
 
   get url(): string {
-    notImplemented("Http2ServerRequest.url");
-    return "";
+    return this.#headers[constants.HTTP2_HEADER_PATH] as string;
+  }
+
+  set url(url: string) {
+    this.#headers[constants.HTTP2_HEADER_PATH] = url;
+  }
+
+  _read() {
+    this.#stream.resume();
   }
 }
 
-export class Http2ServerResponse {
-  constructor() {
+export class Http2ServerResponse extends EventEmitter {
+  #stream: ServerHttp2Stream;
+  #headers: Map<string, string | string[]> = new Map();
+  #trailers: Http2Headers = {};
+  #statusCode = 200;
+  #ended = false;
+  _req: Http2ServerRequest | undefined = undefined;
+  sendDate = true;
+  statusMessage = "";
+
+  constructor(stream: ServerHttp2Stream) {
+    super();
+    this.#stream = stream;
+    stream.on("wantTrailers", () => stream.sendTrailers(this.#trailers));
+    stream.on("finish", () => this.emit("finish"));
+    stream.on("close", () => this.emit("close"));
   }
 
-  addTrailers(_headers: Record<string, unknown>) {
-    notImplemented("Http2ServerResponse.addTrailers");
+  addTrailers(headers: Http2Headers) {
+    Object.assign(this.#trailers, headers);
   }
 
-  get connection(): Socket /*| TlsSocket*/ {
-    notImplemented("Http2ServerResponse.connection");
-    return {};
+  get connection(): Socket /*| TlsSocket*/ | undefined {
+    return this.#stream.session.socket;
   }
 
   createPushResponse(
     _headers: Record<string, unknown>,
-    _callback: () => unknown,
+    callback: (error: Error | null) => unknown,
   ) {
-    notImplemented("Http2ServerResponse.createPushResponse");
+    this.#stream.pushStream({}, {}, callback);
   }
 
   end(
-    _data: string | Buffer | Uint8Array,
-    _encoding: string,
-    _callback: () => unknown,
+    data?: string | Buffer | Uint8Array | (() => unknown),
+    encoding?: string | (() => unknown),
+    callback?: () => unknown,
   ) {
-    notImplemented("Http2ServerResponse.end");
+    if (typeof data === "function") {
+      callback = data;
+      data = undefined;
+    } else if (typeof encoding === "function") {
+      callback = encoding;
+      encoding = undefined;
+    }
+    if (this.#ended) {
+      if (typeof callback === "function") {
+        nextTick(callback);
+      }
+      return this;
+    }
+    this.#ended = true;
+    if (!this.#stream.headersSent) {
+      this.#respond();
+    }
+    if (data !== undefined) {
+      this.#stream.end(data, encoding as string, callback);
+    } else {
+      this.#stream.end(callback);
+    }
+    return this;
   }
 
   get finished(): boolean {
-    notImplemented("Http2ServerResponse.finished");
-    return false;
+    return this.#ended;
   }
 
-  getHeader(_name: string): string {
-    notImplemented("Http2ServerResponse.getHeader");
-    return "";
+  getHeader(name: string): string | string[] | undefined {
+    return this.#headers.get(name.toLowerCase());
   }
 
   getHeaderNames(): string[] {
-    notImplemented("Http2ServerResponse.getHeaderNames");
-    return [];
+    return [...this.#headers.keys()];
   }
 
-  getHeaders(): Record<string, unknown> {
-    notImplemented("Http2ServerResponse.getHeaders");
-    return {};
+  getHeaders(): Record<string, string | string[]> {
+    return Object.fromEntries(this.#headers);
   }
 
-  hasHeader(_name: string) {
-    notImplemented("Http2ServerResponse.hasHeader");
+  hasHeader(name: string): boolean {
+    return this.#headers.has(name.toLowerCase());
   }
 
   get headersSent(): boolean {
-    notImplemented("Http2ServerResponse.headersSent");
-    return false;
+    return this.#stream.headersSent;
   }
 
-  removeHeader(_name: string) {
-    notImplemented("Http2ServerResponse.removeHeader");
+  removeHeader(name: string) {
+    if (this.headersSent) {
+      throw new ERR_HTTP2_HEADERS_SENT();
+    }
+    this.#headers.delete(name.toLowerCase());
   }
 
-  get req(): Http2ServerRequest {
-    notImplemented("Http2ServerResponse.req");
-    return new Http2ServerRequest();
+  get req(): Http2ServerRequest | undefined {
+    return this._req;
   }
 
-  get sendDate(): boolean {
-    notImplemented("Http2ServerResponse.sendDate");
-    return false;
-  }
-
-  setHeader(_name: string, _value: string | string[]) {
-    notImplemented("Http2ServerResponse.setHeader");
+  setHeader(name: string, value: string | string[]) {
+    if (this.headersSent) {
+      throw new ERR_HTTP2_HEADERS_SENT();
+    }
+    this.#headers.set(name.toLowerCase(), value);
+    return this;
   }
 
   setTimeout(msecs: number, callback?: () => unknown) {
-    this.stream.setTimeout(msecs, callback);
+    this.#stream.setTimeout(msecs, callback);
+    return this;
   }
 
-  get socket(): Socket /*| TlsSocket*/ {
-    notImplemented("Http2ServerResponse.socket");
-    return {};
+  get socket(): Socket /*| TlsSocket*/ | undefined {
+    return this.#stream.session.socket;
   }
 
   get statusCode(): number {
-    notImplemented("Http2ServerResponse.statusCode");
-    return 0;
+    return this.#statusCode;
   }
 
-  get statusMessage(): string {
-    notImplemented("Http2ServerResponse.statusMessage");
-    return "";
+  set statusCode(code: number) {
+    code |= 0;
+    if (code < 200 || code > 599) {
+      throw new ERR_HTTP2_STATUS_INVALID(String(code));
+    }
+    this.#statusCode = code;
   }
 
   get stream(): Http2Stream {
-    notImplemented("Http2ServerResponse.stream");
-    return new Http2Stream();
+    return this.#stream;
   }
 
   get writableEnded(): boolean {
-    notImplemented("Http2ServerResponse.writableEnded");
-    return false;
+    return this.#ended;
   }
 
   write(
-    _chunk: string | Buffer | Uint8Array,
-    _encoding: string,
-    _callback: () => unknown,
-  ) {
-    notImplemented("Http2ServerResponse.write");
-    return this.write;
+    chunk: string | Buffer | Uint8Array,
+    encoding?: string | (() => unknown),
+    callback?: () => unknown,
+  ): boolean {
+    if (typeof encoding === "function") {
+      callback = encoding;
+      encoding = undefined;
+    }
+    if (!this.#stream.headersSent) {
+      this.#respond();
+    }
+    return this.#stream.write(chunk, encoding as string, callback);
   }
 
   writeContinue() {
@@ -1070,11 +1721,33 @@ export class Http2ServerResponse {
   }
 
   writeHead(
-    _statusCode: number,
-    _statusMessage: string,
-    _headers: Record<string, unknown>,
+    statusCode: number,
+    statusMessage?: string | Http2Headers,
+    headers?: Http2Headers,
   ) {
-    notImplemented("Http2ServerResponse.writeHead");
+    if (typeof statusMessage === "object") {
+      headers = statusMessage;
+    }
+    if (this.headersSent) {
+      throw new ERR_HTTP2_HEADERS_SENT();
+    }
+    this.statusCode = statusCode;
+    if (headers) {
+      for (const [name, value] of Object.entries(headers)) {
+        this.setHeader(name, value);
+      }
+    }
+    this.#respond();
+    return this;
+  }
+
+  #respond() {
+    const headers: Http2Headers = this.getHeaders();
+    headers[constants.HTTP2_HEADER_STATUS] = String(this.#statusCode);
+    if (this.sendDate && !this.#headers.has(constants.HTTP2_HEADER_DATE)) {
+      headers[constants.HTTP2_HEADER_DATE] = new Date().toUTCString();
+    }
+    this.#stream.respond(headers, { waitForTrailers: true });
   }
 }
 
@@ -3,25 +3,47 @@
 
 import { EventEmitter } from "ext:deno_node/events.ts";
 import { notImplemented } from "ext:deno_node/_utils.ts";
+import { emitWarning, nextTick } from "ext:deno_node/process.ts";
+import {
+  ERR_INSPECTOR_ALREADY_ACTIVATED,
+  ERR_INSPECTOR_ALREADY_CONNECTED,
+  ERR_INSPECTOR_CLOSED,
+  ERR_INSPECTOR_COMMAND,
+  ERR_INSPECTOR_NOT_ACTIVE,
+  ERR_INSPECTOR_NOT_AVAILABLE,
+  ERR_INSPECTOR_NOT_CONNECTED,
+} from "ext:deno_node/internal/errors.ts";
+import {
+  validateFunction,
+  validateInt32,
+  validateObject,
+  validateString,
+} from "ext:deno_node/internal/validators.mjs";
+
+const { core } = globalThis.__bootstrap;
+const { ops } = core;
+const promiseIdSymbol = Symbol.for("Deno.core.internalPromiseId");
 
 const connectionSymbol = Symbol("connectionProperty");
 const messageCallbacksSymbol = Symbol("messageCallbacks");
 const nextIdSymbol = Symbol("nextId");
 const onMessageSymbol = Symbol("onMessage");
 
-class Session extends EventEmitter {
-  [connectionSymbol]: null;
-  [nextIdSymbol]: number;
-  [messageCallbacksSymbol]: Map<string, (e: Error) => void>;
+type MessageCallback = (error: Error | null, result?: unknown) => void;
 
-  constructor() {
-    super();
-    notImplemented("inspector.Session.prototype.constructor");
-  }
+class Session extends EventEmitter {
+  [connectionSymbol]: number | null = null;
+  [nextIdSymbol] = 1;
+  [messageCallbacksSymbol]: Map<number, MessageCallback> = new Map();
+  #receivePromise: Promise<string | null> | null = null;
 
   /** Connects the session to the inspector back-end. */
   connect() {
-    notImplemented("inspector.Session.prototype.connect");
+    if (this[connectionSymbol] !== null) {
+      throw new ERR_INSPECTOR_ALREADY_CONNECTED("The inspector session");
+    }
+    this[connectionSymbol] = ops.op_inspector_connect();
+    this.#receive(this[connectionSymbol]!);
   }
 
   /** Connects the session to the main thread
@@ -30,17 +52,83 @@ class Session extends EventEmitter {
     notImplemented("inspector.Session.prototype.connectToMainThread");
   }
 
-  [onMessageSymbol](_message: string) {
-    notImplemented("inspector.Session.prototype[Symbol('onMessage')]");
+  async #receive(rid: number) {
+    while (true) {
+      const promise = core.opAsync("op_inspector_receive", rid);
+      this.#receivePromise = promise;
+      // Only posted messages that wait for a response keep the event loop
+      // alive, notifications don't.
+      if (this[messageCallbacksSymbol].size === 0) {
+        core.unrefOp(promise[promiseIdSymbol]);
+      }
+      const message = await promise;
+      if (message === null) {
+        break;
+      }
+      this[onMessageSymbol](message);
+    }
+    this.#receivePromise = null;
+  }
+
+  [onMessageSymbol](message: string) {
+    const parsed = JSON.parse(message);
+    try {
+      if (parsed.id) {
+        const callback = this[messageCallbacksSymbol].get(parsed.id);
+        this[messageCallbacksSymbol].delete(parsed.id);
+        if (callback) {
+          if (parsed.error) {
+            return callback(
+              new ERR_INSPECTOR_COMMAND(
+                parsed.error.code,
+                parsed.error.message,
+              ),
+            );
+          }
+          callback(null, parsed.result);
+        }
+      } else {
+        this.emit(parsed.method, parsed);
+        this.emit("inspectorNotification", parsed);
+      }
+    } catch (error) {
+      emitWarning(error);
+    }
   }
 
   /** Posts a message to the inspector back-end. */
   post(
-    _method: string,
-    _params?: Record<string, unknown>,
-    _callback?: (...args: unknown[]) => void,
+    method: string,
+    params?: Record<string, unknown> | MessageCallback,
+    callback?: MessageCallback,
   ) {
-    notImplemented("inspector.Session.prototype.post");
+    validateString(method, "method");
+    if (!callback && typeof params === "function") {
+      callback = params;
+      params = undefined;
+    }
+    if (params) {
+      validateObject(params, "params");
+    }
+    if (callback) {
+      validateFunction(callback, "callback");
+    }
+
+    if (this[connectionSymbol] === null) {
+      throw new ERR_INSPECTOR_NOT_CONNECTED();
+    }
+    const id = this[nextIdSymbol]++;
+    const message: Record<string, unknown> = { id, method };
+    if (params) {
+      message.params = params;
+    }
+    if (callback) {
+      this[messageCallbacksSymbol].set(id, callback);
+      if (this.#receivePromise !== null) {
+        core.refOp(this.#receivePromise[promiseIdSymbol]);
+      }
+    }
+    ops.op_inspector_dispatch(this[connectionSymbol], JSON.stringify(message));
   }
 
   /** Immediately closes the session, all pending
@@ -48,33 +136,55 @@ class Session extends EventEmitter {
    * error.
    */
   disconnect() {
-    notImplemented("inspector.Session.prototype.disconnect");
+    if (this[connectionSymbol] === null) {
+      return;
+    }
+    core.close(this[connectionSymbol]);
+    this[connectionSymbol] = null;
+    const remainingCallbacks = this[messageCallbacksSymbol].values();
+    for (const callback of remainingCallbacks) {
+      nextTick(callback, new ERR_INSPECTOR_CLOSED());
+    }
+    this[messageCallbacksSymbol].clear();
+    this[nextIdSymbol] = 1;
   }
 }
 
 /** Activates inspector on host and port.
  * See https://nodejs.org/api/inspector.html#inspectoropenport-host-wait */
-function open(_port?: number, _host?: string, _wait?: boolean) {
-  notImplemented("inspector.Session.prototype.open");
+function open(port = 9229, host = "127.0.0.1", wait = false) {
+  if (url() !== undefined) {
+    throw new ERR_INSPECTOR_ALREADY_ACTIVATED();
+  }
+  validateInt32(port, "port", 0, 65535);
+  validateString(host, "host");
+  if (!ops.op_inspector_open(host, port)) {
+    throw new ERR_INSPECTOR_NOT_AVAILABLE();
+  }
+  if (wait) {
+    waitForDebugger();
+  }
 }
 
 /** Deactivate the inspector. Blocks until there are no active connections.
  * See https://nodejs.org/api/inspector.html#inspectorclose */
 function close() {
-  notImplemented("inspector.Session.prototype.close");
+  ops.op_inspector_close();
 }
 
 /** Return the URL of the active inspector, or undefined if there is none.
  * See https://nodejs.org/api/inspector.html#inspectorurl */
-function url() {
-  // TODO(kt3k): returns undefined for now, which means the inspector is not activated.
-  return undefined;
+function url(): string | undefined {
+  return ops.op_inspector_url() ?? undefined;
 }
 
 /** Blocks until a client (existing or connected later) has sent Runtime.runIfWaitingForDebugger command.
  * See https://nodejs.org/api/inspector.html#inspectorwaitfordebugger */
 function waitForDebugger() {
-  notImplemented("inspector.wairForDebugger");
+  if (url() === undefined) {
+    throw new ERR_INSPECTOR_NOT_ACTIVE();
+  }
+  ops.op_inspector_wait_for_debugger();
 }
 
 const console = globalThis.console;
@@ -4,6 +4,8 @@
 // deno-lint-ignore camelcase
 import * as async_wrap from "ext:deno_node/internal_binding/async_wrap.ts";
 import { ERR_ASYNC_CALLBACK } from "ext:deno_node/internal/errors.ts";
+
+const { core } = globalThis.__bootstrap;
 export {
   asyncIdSymbol,
   ownerSymbol,
@@ -200,29 +202,74 @@ function restoreActiveHooks() {
   active_hooks.tmp_fields = null;
 }
 
-// deno-lint-ignore no-unused-vars
-let wantPromiseHook = false;
 function enableHooks() {
   async_hook_fields[kCheck] += 1;
 
-  // TODO(kt3k): Uncomment this
-  // setCallbackTrampoline(callbackTrampoline);
+  enablePromiseHook();
 }
 
 function disableHooks() {
   async_hook_fields[kCheck] -= 1;
 
-  wantPromiseHook = false;
+  // The promise hooks can't be removed from core, they do nothing while no
+  // hooks are enabled.
+}
+
+let promiseHookEnabled = false;
 
-  // TODO(kt3k): Uncomment the below
-  // setCallbackTrampoline();
+// Promises get an async ID only while hooks are enabled. Promises created
+// before keep running without emitting any event.
+function enablePromiseHook() {
+  if (promiseHookEnabled) {
+    return;
+  }
+  promiseHookEnabled = true;
 
-  // Delay the call to `disablePromiseHook()` because we might currently be
-  // between the `before` and `after` calls of a Promise.
-  // TODO(kt3k): Uncomment the below
-  // enqueueMicrotask(disablePromiseHookIfNecessary);
+  core.setPromiseHooks(
+    // deno-lint-ignore no-explicit-any
+    (promise: any, parent: any) => {
+      if (!enabledHooksExist()) {
+        return;
+      }
+      const asyncId = newAsyncId();
+      const triggerAsyncId = parent?.[async_id_symbol] ??
+        getDefaultTriggerAsyncId();
+      promise[async_id_symbol] = asyncId;
+      promise[trigger_async_id_symbol] = triggerAsyncId;
+      emitInitScript(asyncId, "PROMISE", triggerAsyncId, promise);
+      if (destroyHooksExist()) {
+        promiseDestroyRegistry.register(promise, asyncId);
+      }
+    },
+    // deno-lint-ignore no-explicit-any
+    (promise: any) => {
+      const asyncId = promise[async_id_symbol];
+      if (asyncId !== undefined) {
+        emitBefore(asyncId, promise[trigger_async_id_symbol], promise);
+      }
+    },
+    // deno-lint-ignore no-explicit-any
+    (promise: any) => {
+      const asyncId = promise[async_id_symbol];
+      if (asyncId !== undefined) {
+        emitAfter(asyncId);
+      }
+    },
+    // deno-lint-ignore no-explicit-any
+    (promise: any) => {
+      const asyncId = promise[async_id_symbol];
+      if (asyncId !== undefined && promiseResolveHooksExist()) {
+        emitHook(promise_resolve_symbol, asyncId);
+      }
+    },
+  );
 }
 
+// Promises are destroyed when they are garbage collected, like in Node.
+const promiseDestroyRegistry = new FinalizationRegistry((asyncId: number) => {
+  emitDestroy(asyncId);
+});
+
 // Return the triggerAsyncId meant for the constructor calling it. It's up to
 // the user to safeguard this call and make sure it's zero'd out when the
 // constructor is complete.
@@ -303,6 +350,132 @@ function emitInitScript(
 }
 export { emitInitScript as emitInit };
 
+// Calls the `before`, `after`, `destroy` or `promiseResolve` callback of all
+// enabled hooks.
+function emitHook(symbol: symbol, asyncId: number) {
+  active_hooks.call_depth += 1;
+  try {
+    for (let i = 0; i < active_hooks.array.length; i++) {
+      // deno-lint-ignore no-explicit-any
+      const hook = (active_hooks.array[i] as any)[symbol];
+      if (typeof hook === "function") {
+        hook(asyncId);
+      }
+    }
+  } finally {
+    active_hooks.call_depth -= 1;
+  }
+
+  if (active_hooks.call_depth === 0 && active_hooks.tmp_array !== null) {
+    restoreActiveHooks();
+  }
+}
+
+// The context of AsyncLocalStorage, which lives in "node:async_hooks". It is
+// captured by `initAsyncResource()` and restored around the callbacks of the
+// resource, so that the store propagates to timers and ticks.
+interface AsyncContext {
+  current(): unknown;
+  enter(frame: unknown): void;
+  exit(): void;
+}
+
+let asyncContext: AsyncContext | null = null;
+
+export function setAsyncContext(context: AsyncContext) {
+  asyncContext = context;
+}
+
+// deno-lint-ignore camelcase
+const async_context_symbol = Symbol("asyncContext");
+
+interface ExecutionContext {
+  asyncId: number;
+  triggerAsyncId: number;
+  resource: unknown;
+  enteredContext: boolean;
+}
+
+const executionStack: ExecutionContext[] = [];
+// deno-lint-ignore no-explicit-any
+let executionResource: any = {};
+
+// Gives `resource` an async ID and emits its `init` event. Its callbacks
+// must run between `emitBefore()` and `emitAfter()`.
+// deno-lint-ignore no-explicit-any
+export function initAsyncResource(resource: any, type: string) {
+  const asyncId = newAsyncId();
+  const triggerAsyncId = getDefaultTriggerAsyncId();
+  resource[async_id_symbol] = asyncId;
+  resource[trigger_async_id_symbol] = triggerAsyncId;
+  if (asyncContext !== null) {
+    resource[async_context_symbol] = asyncContext.current();
+  }
+  emitInitScript(asyncId, type, triggerAsyncId, resource);
+}
+
+export function emitBefore(
+  asyncId: number,
+  triggerAsyncId: number,
+  // deno-lint-ignore no-explicit-any
+  resource: any,
+) {
+  const enteredContext = asyncContext !== null &&
+    resource?.[async_context_symbol] !== undefined;
+  if (enteredContext) {
+    asyncContext!.enter(resource[async_context_symbol]);
+  }
+  executionStack.push({
+    asyncId: async_id_fields[async_wrap.UidFields.kExecutionAsyncId],
+    triggerAsyncId: async_id_fields[async_wrap.UidFields.kTriggerAsyncId],
+    resource: executionResource,
+    enteredContext,
+  });
+  async_id_fields[async_wrap.UidFields.kExecutionAsyncId] = asyncId;
+  async_id_fields[async_wrap.UidFields.kTriggerAsyncId] = triggerAsyncId;
+  executionResource = resource;
+
+  if (hasHooks(kBefore)) {
+    emitHook(before_symbol, asyncId);
+  }
+}
+
+export function emitAfter(asyncId: number) {
+  if (hasHooks(kAfter)) {
+    emitHook(after_symbol, asyncId);
+  }
+
+  const previous = executionStack.pop();
+  if (previous === undefined) {
+    return;
+  }
+  async_id_fields[async_wrap.UidFields.kExecutionAsyncId] = previous.asyncId;
+  async_id_fields[async_wrap.UidFields.kTriggerAsyncId] =
+    previous.triggerAsyncId;
+  executionResource = previous.resource;
+  if (previous.enteredContext) {
+    asyncContext!.exit();
+  }
+}
+
+export function emitDestroy(asyncId: number) {
+  if (hasHooks(kDestroy)) {
+    emitHook(destroy_symbol, asyncId);
+  }
+}
+
+export function executionAsyncId(): number {
+  return async_id_fields[async_wrap.UidFields.kExecutionAsyncId];
+}
+
+export function triggerAsyncId(): number {
+  return async_id_fields[async_wrap.UidFields.kTriggerAsyncId];
+}
+
+export function executionAsyncResource(): object {
+  return executionResource;
+}
+
 export function hasAsyncIdStack() {
   return hasHooks(kStackLength);
 }
@@ -312,11 +485,11 @@ export { constants };
 type Fn = (...args: unknown[]) => unknown;
 
 export class AsyncHook {
-  [init_symbol]: Fn;
-  [before_symbol]: Fn;
-  [after_symbol]: Fn;
-  [destroy_symbol]: Fn;
-  [promise_resolve_symbol]: Fn;
+  [init_symbol]: Fn | undefined;
+  [before_symbol]: Fn | undefined;
+  [after_symbol]: Fn | undefined;
+  [destroy_symbol]: Fn | undefined;
+  [promise_resolve_symbol]: Fn | undefined;
 
   constructor({
     init,
@@ -325,11 +498,11 @@ export class AsyncHook {
     destroy,
     promiseResolve,
   }: {
-    init: Fn;
-    before: Fn;
-    after: Fn;
-    destroy: Fn;
-    promiseResolve: Fn;
+    init?: Fn;
+    before?: Fn;
+    after?: Fn;
+    destroy?: Fn;
+    promiseResolve?: Fn;
   }) {
     if (init !== undefined && typeof init !== "function") {
       throw new ERR_ASYNC_CALLBACK("hook.init");
@@ -0,0 +1,144 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+import { Buffer } from "ext:deno_node/buffer.ts";
+import { HASH_DATA } from "ext:deno_node/internal/crypto/types.ts";
+import {
+  ERR_INVALID_ARG_VALUE,
+  ERR_OUT_OF_RANGE,
+} from "ext:deno_node/internal/errors.ts";
+import {
+  validateFunction,
+  validateInteger,
+  validateObject,
+  validateOneOf,
+  validateUint32,
+} from "ext:deno_node/internal/validators.mjs";
+
+const { core } = globalThis.__bootstrap;
+const { ops } = core;
+
+export type Argon2Algorithm = "argon2d" | "argon2i" | "argon2id";
+
+export interface Argon2Parameters {
+  message: HASH_DATA;
+  nonce: HASH_DATA;
+  parallelism: number;
+  tagLength: number;
+  memory: number;
+  passes: number;
+  secret?: HASH_DATA;
+  associatedData?: HASH_DATA;
+}
+
+const MIN_NONCE_LENGTH = 8;
+const MAX_PARALLELISM = 2 ** 24 - 1;
+
+function byteLength(data: HASH_DATA): number {
+  return typeof data === "string"
+    ? Buffer.byteLength(data)
+    : (data as ArrayBufferView | ArrayBuffer).byteLength;
+}
+
+// Mirrors the validation of Node, which follows the limits of RFC 9106.
+function check(algorithm: Argon2Algorithm, parameters: Argon2Parameters) {
+  validateOneOf(algorithm, "algorithm", ["argon2d", "argon2i", "argon2id"]);
+  validateObject(parameters, "parameters");
+
+  const {
+    message,
+    nonce,
+    parallelism,
+    tagLength,
+    memory,
+    passes,
+    secret,
+    associatedData,
+  } = parameters;
+
+  if (message === undefined) {
+    throw new ERR_INVALID_ARG_VALUE("parameters.message", message);
+  }
+  if (nonce === undefined) {
+    throw new ERR_INVALID_ARG_VALUE("parameters.nonce", nonce);
+  }
+  if (byteLength(nonce) < MIN_NONCE_LENGTH) {
+    throw new ERR_OUT_OF_RANGE(
+      "parameters.nonce.byteLength",
+      `>= ${MIN_NONCE_LENGTH}`,
+      byteLength(nonce),
+    );
+  }
+  validateInteger(parallelism, "parameters.parallelism", 1, MAX_PARALLELISM);
+  validateInteger(tagLength, "parameters.tagLength", 4, 2 ** 32 - 1);
+  validateInteger(memory, "parameters.memory", 8 * parallelism, 2 ** 32 - 1);
+  validateUint32(passes, "parameters.passes", true);
+
+  return {
+    message,
+    nonce,
+    parallelism,
+    tagLength,
+    memory,
+    passes,
+    secret,
+    associatedData,
+  };
+}
+
+/**
+ * Derives a key with the Argon2 password hashing function of RFC 9106.
+ * `memory` is in KiB.
+ */
+export function argon2Sync(
+  algorithm: Argon2Algorithm,
+  parameters: Argon2Parameters,
+): Buffer {
+  const params = check(algorithm, parameters);
+
+  const buf = Buffer.alloc(params.tagLength);
+  ops.op_node_argon2_sync(
+    algorithm,
+    params.message,
+    params.nonce,
+    params.secret,
+    params.associatedData,
+    params.parallelism,
+    params.memory,
+    params.passes,
+    buf,
+  );
+
+  return buf;
+}
+
+type Callback = (err: unknown, result?: Buffer) => void;
+
+export function argon2(
+  algorithm: Argon2Algorithm,
+  parameters: Argon2Parameters,
+  callback: Callback,
+) {
+  const params = check(algorithm, parameters);
+  validateFunction(callback, "callback");
+
+  core.opAsync(
+    "op_node_argon2_async",
+    algorithm,
+    params.message,
+    params.nonce,
+    params.secret,
+    params.associatedData,
+    params.parallelism,
+    params.memory,
+    params.passes,
+    params.tagLength,
+  ).then(
+    (buf: Uint8Array) => callback(null, Buffer.from(buf.buffer)),
+    (err: unknown) => callback(err),
+  );
+}
+
+export default {
+  argon2,
+  argon2Sync,
+};
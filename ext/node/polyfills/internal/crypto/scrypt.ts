@@ -25,6 +25,16 @@ SOFTWARE.
 
 import { Buffer } from "ext:deno_node/buffer.ts";
 import { HASH_DATA } from "ext:deno_node/internal/crypto/types.ts";
+import {
+  ERR_CRYPTO_SCRYPT_INVALID_PARAMETER,
+  ERR_INCOMPATIBLE_OPTION_PAIR,
+} from "ext:deno_node/internal/errors.ts";
+import {
+  validateFunction,
+  validateInt32,
+  validateInteger,
+  validateUint32,
+} from "ext:deno_node/internal/validators.mjs";
 
 const { core } = globalThis.__bootstrap;
 const { ops } = core;
@@ -39,48 +49,84 @@ type Opts = Partial<{
   maxmem: number;
 }>;
 
-const fixOpts = (opts?: Opts) => {
-  const out = { N: 16384, p: 1, r: 8, maxmem: 32 << 20 };
-  if (!opts) return out;
-
-  if (opts.N) out.N = opts.N;
-  else if (opts.cost) out.N = opts.cost;
-
-  if (opts.p) out.p = opts.p;
-  else if (opts.parallelization) out.p = opts.parallelization;
-
-  if (opts.r) out.r = opts.r;
-  else if (opts.blockSize) out.r = opts.blockSize;
+const defaults = { N: 16384, r: 8, p: 1, maxmem: 32 << 20 };
+
+// Mirrors the validation of Node, which rejects the parameters before
+// deriving anything.
+function check(keylen: number, opts?: Opts | null) {
+  validateInt32(keylen, "keylen", 0);
+
+  let { N, r, p, maxmem } = defaults;
+  if (opts) {
+    const hasN = opts.N !== undefined;
+    if (hasN) {
+      N = opts.N!;
+      validateUint32(N, "N");
+    }
+    if (opts.cost !== undefined) {
+      if (hasN) throw new ERR_INCOMPATIBLE_OPTION_PAIR("N", "cost");
+      N = opts.cost;
+      validateUint32(N, "cost");
+    }
+    const hasR = opts.r !== undefined;
+    if (hasR) {
+      r = opts.r!;
+      validateUint32(r, "r");
+    }
+    if (opts.blockSize !== undefined) {
+      if (hasR) throw new ERR_INCOMPATIBLE_OPTION_PAIR("r", "blockSize");
+      r = opts.blockSize;
+      validateUint32(r, "blockSize");
+    }
+    const hasP = opts.p !== undefined;
+    if (hasP) {
+      p = opts.p!;
+      validateUint32(p, "p");
+    }
+    if (opts.parallelization !== undefined) {
+      if (hasP) throw new ERR_INCOMPATIBLE_OPTION_PAIR("p", "parallelization");
+      p = opts.parallelization;
+      validateUint32(p, "parallelization");
+    }
+    if (opts.maxmem !== undefined) {
+      maxmem = opts.maxmem;
+      validateInteger(maxmem, "maxmem", 0);
+    }
+    if (N === 0) N = defaults.N;
+    if (r === 0) r = defaults.r;
+    if (p === 0) p = defaults.p;
+    if (maxmem === 0) maxmem = defaults.maxmem;
+  }
 
-  if (opts.maxmem) out.maxmem = opts.maxmem;
+  // N must be a power of two greater than 1, p * r must be below 2^30, and
+  // the memory used must fit into maxmem, computed the way OpenSSL does.
+  const blen = p * 128 * r;
+  const vlen = 32 * r * (N + 2) * 4;
+  if (
+    N < 2 || (N & (N - 1)) !== 0 || p * r >= 2 ** 30 || blen + vlen > maxmem
+  ) {
+    throw new ERR_CRYPTO_SCRYPT_INVALID_PARAMETER();
+  }
 
-  return out;
-};
+  return { N, r, p };
+}
 
 export function scryptSync(
   password: HASH_DATA,
   salt: HASH_DATA,
   keylen: number,
-  _opts?: Opts,
+  opts?: Opts,
 ): Buffer {
-  const { N, r, p, maxmem } = fixOpts(_opts);
-
-  const blen = p * 128 * r;
-
-  if (32 * r * (N + 2) * 4 + blen > maxmem) {
-    throw new Error("exceeds max memory");
-  }
+  const { N, r, p } = check(keylen, opts);
 
   const buf = Buffer.alloc(keylen);
   ops.op_node_scrypt_sync(
     password,
     salt,
-    keylen,
     Math.log2(N),
     r,
     p,
-    maxmem,
-    buf.buffer,
+    buf,
   );
 
   return buf;
@@ -92,38 +138,28 @@ export function scrypt(
   password: HASH_DATA,
   salt: HASH_DATA,
   keylen: number,
-  _opts: Opts | null | Callback,
+  opts: Opts | null | Callback,
   cb?: Callback,
 ) {
-  if (!cb) {
-    cb = _opts as Callback;
-    _opts = null;
-  }
-  const { N, r, p, maxmem } = fixOpts(_opts as Opts);
-
-  const blen = p * 128 * r;
-  if (32 * r * (N + 2) * 4 + blen > maxmem) {
-    throw new Error("exceeds max memory");
+  if (cb === undefined) {
+    cb = opts as Callback;
+    opts = null;
   }
+  const { N, r, p } = check(keylen, opts as Opts | null);
+  validateFunction(cb, "callback");
 
-  try {
-    core.opAsync(
-      "op_node_scrypt_async",
-      password,
-      salt,
-      keylen,
-      Math.log2(N),
-      r,
-      p,
-      maxmem,
-    ).then(
-      (buf: Uint8Array) => {
-        cb(null, Buffer.from(buf.buffer));
-      },
-    );
-  } catch (err: unknown) {
-    return cb(err);
-  }
+  core.opAsync(
+    "op_node_scrypt_async",
+    password,
+    salt,
+    keylen,
+    Math.log2(N),
+    r,
+    p,
+  ).then(
+    (buf: Uint8Array) => cb!(null, Buffer.from(buf.buffer)),
+    (err: unknown) => cb!(err),
+  );
 }
 
 export default {
@@ -1793,6 +1793,14 @@ export class ERR_SCRIPT_EXECUTION_INTERRUPTED extends NodeError {
     );
   }
 }
+export class ERR_SCRIPT_EXECUTION_TIMEOUT extends NodeError {
+  constructor(timeout: number) {
+    super(
+      "ERR_SCRIPT_EXECUTION_TIMEOUT",
+      `Script execution timed out after ${timeout}ms`,
+    );
+  }
+}
 export class ERR_SERVER_ALREADY_LISTEN extends NodeError {
   constructor() {
     super(
@@ -2786,6 +2794,7 @@ export default {
   ERR_QUICSTREAM_UNSUPPORTED_PUSH,
   ERR_QUIC_TLS13_REQUIRED,
   ERR_SCRIPT_EXECUTION_INTERRUPTED,
+  ERR_SCRIPT_EXECUTION_TIMEOUT,
   ERR_SERVER_ALREADY_LISTEN,
   ERR_SERVER_NOT_RUNNING,
   ERR_SOCKET_ALREADY_BOUND,
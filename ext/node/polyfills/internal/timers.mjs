@@ -5,6 +5,14 @@ import { inspect } from "ext:deno_node/internal/util/inspect.mjs";
 import { validateFunction, validateNumber } from "ext:deno_node/internal/validators.mjs";
 import { ERR_OUT_OF_RANGE } from "ext:deno_node/internal/errors.ts";
 import { emitWarning } from "ext:deno_node/process.ts";
+import {
+  async_id_symbol,
+  emitAfter,
+  emitBefore,
+  emitDestroy,
+  initAsyncResource,
+  trigger_async_id_symbol,
+} from "ext:deno_node/internal/async_hooks.ts";
 import {
   setTimeout as setTimeout_,
   clearTimeout as clearTimeout_,
@@ -16,7 +24,9 @@ export const TIMEOUT_MAX = 2 ** 31 - 1;
 
 export const kTimerId = Symbol("timerId");
 export const kTimeout = Symbol("timeout");
+export const kDestroy = Symbol("destroy");
 const kRefed = Symbol("refed");
+const kDestroyed = Symbol("destroyed");
 const createTimer = Symbol("createTimer");
 
 // Timer constructor function.
@@ -29,12 +39,26 @@ export function Timeout(callback, after, args, isRepeat, isRefed) {
   this._timerArgs = args;
   this._isRepeat = isRepeat;
   this[kRefed] = isRefed;
+  this[kDestroyed] = false;
+  initAsyncResource(this, "Timeout");
   this[kTimerId] = this[createTimer]();
 }
 
 Timeout.prototype[createTimer] = function () {
   const callback = this._onTimeout;
-  const cb = (...args) => callback.bind(this)(...args);
+  const cb = (...args) => {
+    const asyncId = this[async_id_symbol];
+    emitBefore(asyncId, this[trigger_async_id_symbol], this);
+    try {
+      callback.apply(this, args);
+    } finally {
+      emitAfter(asyncId);
+      if (!this._isRepeat) {
+        this[kDestroyed] = true;
+        emitDestroy(asyncId);
+      }
+    }
+  };
   const id = this._isRepeat
     ? setInterval_(cb, this._idleTimeout, ...this._timerArgs)
     : setTimeout_(cb, this._idleTimeout, ...this._timerArgs);
@@ -61,6 +85,14 @@ Timeout.prototype.refresh = function () {
   return this;
 };
 
+// Called when the timer is cleared.
+Timeout.prototype[kDestroy] = function () {
+  if (!this[kDestroyed]) {
+    this[kDestroyed] = true;
+    emitDestroy(this[async_id_symbol]);
+  }
+};
+
 Timeout.prototype.unref = function () {
   if (this[kRefed]) {
     this[kRefed] = false;
@@ -118,6 +150,7 @@ export function setUnrefTimeout(callback, timeout, ...args) {
 
 export default {
   getTimerDuration,
+  kDestroy,
   kTimerId,
   kTimeout,
   setUnrefTimeout,
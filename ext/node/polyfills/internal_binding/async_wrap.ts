@@ -73,6 +73,9 @@ const asyncIdFields = new Float64Array(Object.keys(UidFields).length);
 // context during bootstrap.
 asyncIdFields[UidFields.kAsyncIdCounter] = 1;
 
+// The main script runs with the async ID 1, triggered by nothing.
+asyncIdFields[UidFields.kExecutionAsyncId] = 1;
+
 // `kDefaultTriggerAsyncId` should be `-1`, this indicates that there is no
 // specified default value and it should fallback to the executionAsyncId.
 // 0 is not used as the magic value, because that indicates a missing
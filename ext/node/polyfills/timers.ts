@@ -1,6 +1,10 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
-import { setUnrefTimeout, Timeout } from "ext:deno_node/internal/timers.mjs";
+import {
+  kDestroy,
+  setUnrefTimeout,
+  Timeout,
+} from "ext:deno_node/internal/timers.mjs";
 import { validateFunction } from "ext:deno_node/internal/validators.mjs";
 import { promisify } from "ext:deno_node/internal/util.mjs";
 export { setUnrefTimeout } from "ext:deno_node/internal/timers.mjs";
@@ -29,6 +33,9 @@ export function clearTimeout(timeout?: Timeout | number) {
   if (timeout == null) {
     return;
   }
+  if (timeout instanceof Timeout) {
+    timeout[kDestroy]();
+  }
   clearTimeout_(+timeout);
 }
 export function setInterval(
@@ -43,6 +50,9 @@ export function clearInterval(timeout?: Timeout | number | string) {
   if (timeout == null) {
     return;
   }
+  if (timeout instanceof Timeout) {
+    timeout[kDestroy]();
+  }
   clearInterval_(+timeout);
 }
 // TODO(bartlomieju): implement the 'NodeJS.Immediate' versions of the timers.
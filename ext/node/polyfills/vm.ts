@@ -3,29 +3,112 @@
 // deno-lint-ignore-file no-explicit-any
 
 import { notImplemented } from "ext:deno_node/_utils.ts";
+import {
+  ERR_INVALID_ARG_TYPE,
+  ERR_SCRIPT_EXECUTION_TIMEOUT,
+} from "ext:deno_node/internal/errors.ts";
+import {
+  validateArray,
+  validateObject,
+  validateString,
+  validateUint32,
+} from "ext:deno_node/internal/validators.mjs";
 
 const { core } = globalThis.__bootstrap;
+const { ops } = core;
+
+// Maps a contextified object to the global object of its V8 context. The
+// global object forwards its properties to the contextified object.
+const contextGlobals = new WeakMap<object, object>();
+
+function getContextGlobal(contextifiedObject: any): object {
+  const contextGlobal = contextGlobals.get(contextifiedObject);
+  if (contextGlobal === undefined) {
+    throw new ERR_INVALID_ARG_TYPE(
+      "contextifiedObject",
+      "vm.Context",
+      contextifiedObject,
+    );
+  }
+  return contextGlobal;
+}
+
+function runScript(
+  code: string,
+  filename: string,
+  lineOffset: number,
+  columnOffset: number,
+  contextGlobal: object | undefined,
+  options: any,
+) {
+  const timeout = options?.timeout;
+  if (timeout !== undefined) {
+    validateUint32(timeout, "options.timeout", true);
+  }
+  const [result, error] = ops.op_vm_run_in_context(
+    code,
+    filename,
+    lineOffset,
+    columnOffset,
+    contextGlobal,
+    timeout,
+  );
+  if (error) {
+    if (error.timedOut) {
+      throw new ERR_SCRIPT_EXECUTION_TIMEOUT(timeout);
+    }
+    throw error.thrown;
+  }
+  return result;
+}
 
 export class Script {
   code: string;
-  constructor(code: string, _options = {}) {
+  #filename: string;
+  #lineOffset: number;
+  #columnOffset: number;
+
+  constructor(code: string, options: any = {}) {
+    if (typeof options === "string") {
+      options = { filename: options };
+    }
+    validateObject(options, "options");
+    const {
+      filename = "evalmachine.<anonymous>",
+      lineOffset = 0,
+      columnOffset = 0,
+    } = options;
+    validateString(filename, "options.filename");
     this.code = `${code}`;
+    this.#filename = filename;
+    this.#lineOffset = lineOffset;
+    this.#columnOffset = columnOffset;
   }
 
-  runInThisContext(_options: any) {
-    const [result, error] = core.evalContext(this.code, "data:");
-    if (error) {
-      throw error.thrown;
-    }
-    return result;
+  runInThisContext(options: any) {
+    return runScript(
+      this.code,
+      this.#filename,
+      this.#lineOffset,
+      this.#columnOffset,
+      undefined,
+      options,
+    );
   }
 
-  runInContext(_contextifiedObject: any, _options: any) {
-    notImplemented("Script.prototype.runInContext");
+  runInContext(contextifiedObject: any, options: any) {
+    return runScript(
+      this.code,
+      this.#filename,
+      this.#lineOffset,
+      this.#columnOffset,
+      getContextGlobal(contextifiedObject),
+      options,
+    );
   }
 
-  runInNewContext(_contextObject: any, _options: any) {
-    notImplemented("Script.prototype.runInNewContext");
+  runInNewContext(contextObject: any, options: any) {
+    return this.runInContext(createContext(contextObject), options);
   }
 
   createCachedData() {
@@ -33,8 +116,13 @@ export class Script {
   }
 }
 
-export function createContext(_contextObject: any, _options: any) {
-  notImplemented("createContext");
+export function createContext(contextObject: any = {}, _options: any) {
+  if (isContext(contextObject)) {
+    return contextObject;
+  }
+  validateObject(contextObject, "contextObject", { allowArray: true });
+  contextGlobals.set(contextObject, ops.op_vm_create_context(contextObject));
+  return contextObject;
 }
 
 export function createScript(code: string, options: any) {
@@ -42,19 +130,19 @@ export function createScript(code: string, options: any) {
 }
 
 export function runInContext(
-  _code: string,
-  _contextifiedObject: any,
-  _options: any,
+  code: string,
+  contextifiedObject: any,
+  options: any,
 ) {
-  notImplemented("runInContext");
+  return createScript(code, options).runInContext(contextifiedObject, options);
 }
 
 export function runInNewContext(
-  _code: string,
-  _contextObject: any,
-  _options: any,
+  code: string,
+  contextObject: any,
+  options: any,
 ) {
-  notImplemented("runInNewContext");
+  return createScript(code, options).runInNewContext(contextObject, options);
 }
 
 export function runInThisContext(
@@ -64,12 +152,44 @@ export function runInThisContext(
   return createScript(code, options).runInThisContext(options);
 }
 
-export function isContext(_maybeContext: any) {
-  notImplemented("isContext");
+export function isContext(maybeContext: any) {
+  validateObject(maybeContext, "contextObject", { allowArray: true });
+  return contextGlobals.has(maybeContext);
 }
 
-export function compileFunction(_code: string, _params: any, _options: any) {
-  notImplemented("compileFunction");
+export function compileFunction(
+  code: string,
+  params: string[] = [],
+  options: any = {},
+) {
+  validateString(code, "code");
+  validateArray(params, "params");
+  validateObject(options, "options");
+  const {
+    filename = "",
+    lineOffset = 0,
+    columnOffset = 0,
+    parsingContext,
+    contextExtensions = [],
+  } = options;
+  validateArray(contextExtensions, "options.contextExtensions");
+  if (contextExtensions.length > 0) {
+    notImplemented("compileFunction with options.contextExtensions");
+  }
+  for (const param of params) {
+    validateString(param, "params");
+  }
+
+  // The body starts on the line after the function header, so the line
+  // offset is adjusted for the positions in stack traces to match the code.
+  return runScript(
+    `(function (${params.join(", ")}) {\n${code}\n})`,
+    filename,
+    lineOffset - 1,
+    columnOffset,
+    parsingContext === undefined ? undefined : getContextGlobal(parsingContext),
+    undefined,
+  );
 }
 
 export function measureMemory(_options: any) {
@@ -5,7 +5,11 @@ import { resolve, toFileUrl } from "ext:deno_node/path.ts";
 import { notImplemented } from "ext:deno_node/_utils.ts";
 import { EventEmitter, once } from "ext:deno_node/events.ts";
 import { BroadcastChannel } from "ext:deno_broadcast_channel/01_broadcast_channel.js";
-import { MessageChannel, MessagePort } from "ext:deno_web/13_message_port.js";
+import {
+  MessageChannel,
+  MessagePort,
+  receiveMessageOnPort as receiveMessageOnPort_,
+} from "ext:deno_web/13_message_port.js";
 
 let environmentData = new Map();
 let threads = 0;
@@ -44,13 +48,14 @@ class _Worker extends EventEmitter {
     stackSizeMb: 4,
   };
   private readonly [kHandle]: Worker;
+  #exitCode: number | null = null;
 
   postMessage: Worker["postMessage"];
 
   constructor(specifier: URL | string, options?: WorkerOptions) {
     super();
     if (options?.eval === true) {
-      specifier = `data:text/javascript,${specifier}`;
+      specifier = `data:text/javascript,${encodeURIComponent(specifier)}`;
     } else if (typeof specifier === "string") {
       specifier = resolve(specifier);
       if (!specifier.toString().endsWith(".mjs")) {
@@ -86,12 +91,20 @@ class _Worker extends EventEmitter {
       workerData: options?.workerData,
     }, options?.transferList || []);
     this.postMessage = handle.postMessage.bind(handle);
-    this.emit("online");
+    // The limits aren't enforced, but are reported like in Node.
+    Object.assign(this.resourceLimits, options?.resourceLimits);
+    // Let the caller add listeners first.
+    queueMicrotask(() => this.emit("online"));
   }
 
-  terminate() {
-    this[kHandle].terminate();
-    this.emit("exit", 0);
+  /** Stops the worker. Resolves with its exit code, which is 1 like in Node. */
+  terminate(): Promise<number> {
+    if (this.#exitCode === null) {
+      this[kHandle].terminate();
+      this.#exitCode = 1;
+      this.emit("exit", this.#exitCode);
+    }
+    return Promise.resolve(this.#exitCode);
   }
 
   readonly getHeapSnapshot = () =>
@@ -235,8 +248,15 @@ export function markAsUntransferable() {
 export function moveMessagePortToContext() {
   notImplemented("moveMessagePortToContext");
 }
-export function receiveMessageOnPort() {
-  notImplemented("receiveMessageOnPort");
+/**
+ * Receives a message that was already sent to `port`, without waiting.
+ * Returns `undefined` if there is none. The port must not have been started
+ * with `port.start()` or an `onmessage` handler.
+ */
+export function receiveMessageOnPort(
+  port: MessagePort,
+): { message: unknown } | undefined {
+  return receiveMessageOnPort_(port);
 }
 export {
   _Worker as Worker,
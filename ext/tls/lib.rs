@@ -6,10 +6,15 @@ pub use rustls_pemfile;
 pub use webpki;
 pub use webpki_roots;
 
+mod revocation;
+
+pub use revocation::RevocationCheckingVerifier;
+
 use deno_core::anyhow::anyhow;
 use deno_core::error::custom_error;
 use deno_core::error::AnyError;
 
+use once_cell::sync::OnceCell;
 use rustls::client::HandshakeSignatureValid;
 use rustls::client::ServerCertVerified;
 use rustls::client::ServerCertVerifier;
@@ -18,6 +23,8 @@ use rustls::Certificate;
 use rustls::ClientConfig;
 use rustls::DigitallySignedStruct;
 use rustls::Error;
+use rustls::KeyLog;
+use rustls::KeyLogFile;
 use rustls::PrivateKey;
 use rustls::RootCertStore;
 use rustls::ServerName;
@@ -39,6 +46,23 @@ pub trait RootCertStoreProvider: Send + Sync {
   fn get_or_try_init(&self) -> Result<&RootCertStore, AnyError>;
 }
 
+static KEY_LOG: OnceCell<Arc<dyn KeyLog>> = OnceCell::new();
+
+/// Makes all TLS connections created afterwards write their secrets to the
+/// file named by the `SSLKEYLOGFILE` environment variable, in the NSS key log
+/// format, so that their traffic can be decrypted with tools like Wireshark.
+///
+/// This defeats the purpose of TLS, so it should only be used for debugging.
+pub fn enable_key_log() {
+  KEY_LOG.get_or_init(|| Arc::new(KeyLogFile::new()));
+}
+
+/// The key log TLS configurations should use, if enabled with
+/// [`enable_key_log`].
+pub fn key_log() -> Option<Arc<dyn KeyLog>> {
+  KEY_LOG.get().cloned()
+}
+
 // This extension has no runtime apis, it only exports some shared native functions.
 deno_core::extension!(deno_tls);
 
@@ -127,7 +151,7 @@ impl ServerCertVerifier for NoCertificateVerification {
   }
 }
 
-#[derive(Deserialize, Default, Debug, Clone)]
+#[derive(Deserialize, Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "camelCase")]
 #[serde(default)]
 pub struct Proxy {
@@ -135,7 +159,7 @@ pub struct Proxy {
   pub basic_auth: Option<BasicAuth>,
 }
 
-#[derive(Deserialize, Default, Debug, Clone)]
+#[derive(Deserialize, Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(default)]
 pub struct BasicAuth {
   pub username: String,
@@ -160,6 +184,7 @@ pub fn create_default_root_cert_store() -> RootCertStore {
 pub fn create_client_config(
   root_cert_store: Option<RootCertStore>,
   ca_certs: Vec<Vec<u8>>,
+  crls: Vec<Vec<u8>>,
   unsafely_ignore_certificate_errors: Option<Vec<String>>,
   client_cert_chain_and_key: Option<(String, String)>,
 ) -> Result<ClientConfig, AnyError> {
@@ -184,7 +209,7 @@ pub fn create_client_config(
     // However it's not really feasible to deduplicate it as the `client_config` instances
     // are not type-compatible - one wants "client cert", the other wants "transparency policy
     // or client cert".
-    let client =
+    let mut client =
       if let Some((cert_chain, private_key)) = maybe_cert_chain_and_key {
         client_config
           .with_single_cert(cert_chain, private_key)
@@ -192,43 +217,62 @@ pub fn create_client_config(
       } else {
         client_config.with_no_client_auth()
       };
+    if let Some(key_log) = key_log() {
+      client.key_log = key_log;
+    }
 
     return Ok(client);
   }
 
-  let client_config = ClientConfig::builder()
-    .with_safe_defaults()
-    .with_root_certificates({
-      let mut root_cert_store =
-        root_cert_store.unwrap_or_else(create_default_root_cert_store);
-      // If custom certs are specified, add them to the store
-      for cert in ca_certs {
-        let reader = &mut BufReader::new(Cursor::new(cert));
-        // This function does not return specific errors, if it fails give a generic message.
-        match rustls_pemfile::certs(reader) {
-          Ok(certs) => {
-            root_cert_store.add_parsable_certificates(&certs);
-          }
-          Err(e) => {
-            return Err(anyhow!(
-              "Unable to add pem file to certificate store: {}",
-              e
-            ));
-          }
-        }
+  let mut root_cert_store =
+    root_cert_store.unwrap_or_else(create_default_root_cert_store);
+  // If custom certs are specified, add them to the store
+  for cert in ca_certs {
+    let reader = &mut BufReader::new(Cursor::new(cert));
+    // This function does not return specific errors, if it fails give a generic message.
+    match rustls_pemfile::certs(reader) {
+      Ok(certs) => {
+        root_cert_store.add_parsable_certificates(&certs);
+      }
+      Err(e) => {
+        return Err(anyhow!(
+          "Unable to add pem file to certificate store: {}",
+          e
+        ));
       }
-      root_cert_store
-    });
-
-  let client = if let Some((cert_chain, private_key)) = maybe_cert_chain_and_key
-  {
-    client_config
-      .with_single_cert(cert_chain, private_key)
-      .expect("invalid client key or certificate")
+    }
+  }
+
+  let revocation_checking_verifier = if crls.is_empty() {
+    None
   } else {
-    client_config.with_no_client_auth()
+    Some(RevocationCheckingVerifier::new(
+      root_cert_store.clone(),
+      crls,
+    )?)
   };
 
+  let client_config = ClientConfig::builder()
+    .with_safe_defaults()
+    .with_root_certificates(root_cert_store);
+
+  let mut client =
+    if let Some((cert_chain, private_key)) = maybe_cert_chain_and_key {
+      client_config
+        .with_single_cert(cert_chain, private_key)
+        .expect("invalid client key or certificate")
+    } else {
+      client_config.with_no_client_auth()
+    };
+  if let Some(verifier) = revocation_checking_verifier {
+    client
+      .dangerous()
+      .set_certificate_verifier(Arc::new(verifier));
+  }
+  if let Some(key_log) = key_log() {
+    client.key_log = key_log;
+  }
+
   Ok(client)
 }
 
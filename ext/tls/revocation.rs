@@ -0,0 +1,109 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::custom_error;
+use deno_core::error::AnyError;
+use rustls::client::ServerCertVerified;
+use rustls::client::ServerCertVerifier;
+use rustls::client::WebPkiVerifier;
+use rustls::Certificate;
+use rustls::CertificateError;
+use rustls::Error;
+use rustls::RootCertStore;
+use rustls::ServerName;
+use std::collections::HashSet;
+use std::time::SystemTime;
+use x509_parser::pem::Pem;
+
+/// The issuer name and serial number of a certificate, both DER encoded, which
+/// together identify it.
+type CertificateId = (Vec<u8>, Vec<u8>);
+
+/// Verifies server certificates like [`WebPkiVerifier`], and additionally
+/// rejects certificate chains that contain a certificate listed in one of the
+/// given certificate revocation lists.
+///
+/// The revocation lists are trusted like the root certificates are, their
+/// signatures aren't verified.
+pub struct RevocationCheckingVerifier {
+  verifier: WebPkiVerifier,
+  revoked: HashSet<CertificateId>,
+}
+
+impl RevocationCheckingVerifier {
+  /// Creates a verifier from PEM encoded certificate revocation lists.
+  pub fn new(
+    root_cert_store: RootCertStore,
+    crls: Vec<Vec<u8>>,
+  ) -> Result<Self, AnyError> {
+    let mut revoked = HashSet::new();
+    for crl in crls {
+      let mut found = false;
+      for pem in Pem::iter_from_buffer(&crl) {
+        let pem = pem.map_err(|_| crl_decode_err())?;
+        if pem.label != "X509 CRL" {
+          continue;
+        }
+        found = true;
+        let (_, crl) = x509_parser::parse_x509_crl(&pem.contents)
+          .map_err(|_| crl_decode_err())?;
+        let issuer = crl.issuer().as_raw();
+        revoked.extend(
+          crl
+            .iter_revoked_certificates()
+            .map(|cert| (issuer.to_vec(), cert.raw_serial().to_vec())),
+        );
+      }
+      if !found {
+        return Err(custom_error(
+          "InvalidData",
+          "No certificate revocation lists found",
+        ));
+      }
+    }
+    Ok(Self {
+      verifier: WebPkiVerifier::new(root_cert_store, None),
+      revoked,
+    })
+  }
+
+  fn is_revoked(&self, cert: &Certificate) -> Result<bool, Error> {
+    let (_, cert) = x509_parser::parse_x509_certificate(&cert.0)
+      .map_err(|_| Error::InvalidCertificate(CertificateError::BadEncoding))?;
+    let id = (cert.issuer().as_raw().to_vec(), cert.raw_serial().to_vec());
+    Ok(self.revoked.contains(&id))
+  }
+}
+
+impl ServerCertVerifier for RevocationCheckingVerifier {
+  fn verify_server_cert(
+    &self,
+    end_entity: &Certificate,
+    intermediates: &[Certificate],
+    server_name: &ServerName,
+    scts: &mut dyn Iterator<Item = &[u8]>,
+    ocsp_response: &[u8],
+    now: SystemTime,
+  ) -> Result<ServerCertVerified, Error> {
+    let verified = self.verifier.verify_server_cert(
+      end_entity,
+      intermediates,
+      server_name,
+      scts,
+      ocsp_response,
+      now,
+    )?;
+    for cert in std::iter::once(end_entity).chain(intermediates) {
+      if self.is_revoked(cert)? {
+        return Err(Error::InvalidCertificate(CertificateError::Revoked));
+      }
+    }
+    Ok(verified)
+  }
+}
+
+fn crl_decode_err() -> AnyError {
+  custom_error(
+    "InvalidData",
+    "Unable to decode certificate revocation list",
+  )
+}
@@ -32,7 +32,7 @@ deno_core::extension!(
 );
 
 /// Parse `href` with a `base_href`. Fills the out `buf` with URL components.
-#[op]
+#[op(fast)]
 pub fn op_url_parse_with_base(
   state: &mut OpState,
   href: &str,
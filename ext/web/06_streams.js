@@ -2434,6 +2434,100 @@ function readableStreamHasDefaultReader(stream) {
  * @param {AbortSignal=} signal
  * @returns {Promise<void>}
  */
+/**
+ * Whether `readableStreamPipeToResource` can be used to pipe `source` into
+ * `dest`: both must be backed by resources and have no chunks buffered in JS.
+ * @param {ReadableStream} source
+ * @param {WritableStream} dest
+ * @param {AbortSignal=} signal
+ * @returns {boolean}
+ */
+function canPipeToResource(source, dest, signal) {
+  return getReadableStreamResourceBacking(source) !== undefined &&
+    getWritableStreamResourceBacking(dest) !== undefined &&
+    source[_state] === "readable" && !source[_disturbed] &&
+    dest[_state] === "writable" && dest[_inFlightWriteRequest] === undefined &&
+    dest[_writeRequests].length === 0 && !signal?.aborted;
+}
+
+/**
+ * Fast path of `readableStreamPipeTo`: the data is copied from the source
+ * resource into the destination resource by a single op, without passing
+ * through JS. On Linux the kernel copies the data when possible.
+ * @param {ReadableStream} source
+ * @param {WritableStream} dest
+ * @param {boolean} preventClose
+ * @param {boolean} preventAbort
+ * @param {boolean} preventCancel
+ * @param {AbortSignal=} signal
+ * @returns {Promise<void>}
+ */
+async function readableStreamPipeToResource(
+  source,
+  dest,
+  preventClose,
+  preventAbort,
+  preventCancel,
+  signal,
+) {
+  const sourceBacking = getReadableStreamResourceBacking(source);
+  const destBacking = getWritableStreamResourceBacking(dest);
+  const reader = acquireReadableStreamDefaultReader(source);
+  const writer = acquireWritableStreamDefaultWriter(dest);
+  readableStreamDisturb(source);
+
+  let cancelRid;
+  let abortAlgorithm;
+  if (signal) {
+    cancelRid = ops.op_cancel_handle();
+    abortAlgorithm = () => core.tryClose(cancelRid);
+    signal[add](abortAlgorithm);
+  }
+
+  let error;
+  try {
+    await core.opAsync(
+      "op_pipe_resources",
+      sourceBacking.rid,
+      destBacking.rid,
+      cancelRid,
+    );
+  } catch (err) {
+    error = signal?.aborted ? signal.reason : err;
+  } finally {
+    if (signal) {
+      signal[remove](abortAlgorithm);
+      core.tryClose(cancelRid);
+    }
+  }
+
+  try {
+    if (error === undefined) {
+      readableStreamClose(source);
+      if (sourceBacking.autoClose) {
+        RESOURCE_REGISTRY.unregister(source);
+        core.tryClose(sourceBacking.rid);
+      }
+      if (preventClose === false) {
+        await writableStreamDefaultWriterCloseWithErrorPropagation(writer);
+      }
+    } else {
+      if (preventAbort === false && dest[_state] === "writable") {
+        await writableStreamAbort(dest, error);
+      }
+      if (preventCancel === false && source[_state] === "readable") {
+        await readableStreamCancel(source, error);
+      }
+    }
+  } finally {
+    writableStreamDefaultWriterRelease(writer);
+    readableStreamDefaultReaderRelease(reader);
+  }
+  if (error !== undefined) {
+    throw error;
+  }
+}
+
 function readableStreamPipeTo(
   source,
   dest,
@@ -4988,6 +5082,16 @@ class ReadableStream {
         new TypeError("destination WritableStream is already locked."),
       );
     }
+    if (canPipeToResource(this, destination, signal)) {
+      return readableStreamPipeToResource(
+        this,
+        destination,
+        preventClose,
+        preventAbort,
+        preventCancel,
+        signal,
+      );
+    }
     return readableStreamPipeTo(
       this,
       destination,
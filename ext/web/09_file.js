@@ -553,7 +553,9 @@ const registry = new SafeFinalizationRegistry((uuid) => {
   ops.op_blob_remove_part(uuid);
 });
 
-// TODO(lucacasonato): get a better stream from Rust in BlobReference#stream
+// The size of the chunks blob parts are read in, so that parts backed by
+// files are never loaded into memory at once.
+const READ_CHUNK_SIZE = 65536;
 
 /**
  * An opaque reference to a blob part in Rust. This could be backed by a file,
@@ -601,20 +603,17 @@ class BlobReference {
   }
 
   /**
-   * Read the entire contents of the reference blob.
+   * Read the entire contents of the reference blob, in chunks.
    * @returns {AsyncGenerator<Uint8Array>}
    */
   async *stream() {
-    yield core.opAsync("op_blob_read_part", this._id);
-
-    // let position = 0;
-    // const end = this.size;
-    // while (position !== end) {
-    //   const size = MathMin(end - position, 65536);
-    //   const chunk = this.slice(position, position + size);
-    //   position += chunk.size;
-    //   yield core.opAsync("op_blob_read_part", chunk._id);
-    // }
+    let position = 0;
+    const end = this.size;
+    while (position !== end) {
+      const size = MathMin(end - position, READ_CHUNK_SIZE);
+      yield core.opAsync("op_blob_read_part_range", this._id, position, size);
+      position += size;
+    }
   }
 }
 
@@ -682,11 +681,33 @@ function revokeObjectURL(url) {
 URL.createObjectURL = createObjectURL;
 URL.revokeObjectURL = revokeObjectURL;
 
+/**
+ * Construct a new File object from a part in the blob store, without copying
+ * its data. This is how blobs backed by files on disk are created.
+ *
+ * @param {string} uuid
+ * @param {number} size
+ * @param {string} name
+ * @param {string} type
+ * @param {number} lastModified
+ * @returns {File}
+ */
+function fileFromBlobPart(uuid, size, name, type, lastModified) {
+  const file = webidl.createBranded(File);
+  file[_type] = normalizeType(type);
+  file[_size] = size;
+  file[_parts] = [new BlobReference(uuid, size)];
+  file[_Name] = name;
+  file[_LastModified] = lastModified;
+  return file;
+}
+
 export {
   Blob,
   blobFromObjectUrl,
   BlobPrototype,
   File,
+  fileFromBlobPart,
   FilePrototype,
   getParts,
 };
@@ -326,6 +326,21 @@ webidl.converters.StructuredSerializeOptions = webidl
     ],
   );
 
+/**
+ * Receives the next message of a port that was already sent to it, without
+ * waiting, like `receiveMessageOnPort()` of Node's `worker_threads`. Throws
+ * if the port was started, because it is then receiving asynchronously.
+ * @param {MessagePort} port
+ * @returns {{ message: any } | undefined}
+ */
+function receiveMessageOnPort(port) {
+  webidl.assertBranded(port, MessagePortPrototype);
+  if (port[_id] === null) return undefined;
+  const data = ops.op_message_port_recv_message_sync(port[_id]);
+  if (data === null) return undefined;
+  return { message: deserializeJsMessageData(data)[0] };
+}
+
 function structuredClone(value, options) {
   const prefix = "Failed to execute 'structuredClone'";
   webidl.requiredArguments(arguments.length, 1, prefix);
@@ -343,6 +358,7 @@ export {
   MessageChannel,
   MessagePort,
   MessagePortPrototype,
+  receiveMessageOnPort,
   serializeJsMessageData,
   structuredClone,
 };
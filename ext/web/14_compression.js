@@ -20,6 +20,8 @@ webidl.converters.CompressionFormat = webidl.createEnumConverter(
     "deflate",
     "deflate-raw",
     "gzip",
+    "br",
+    "zstd",
   ],
 );
 
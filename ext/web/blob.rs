@@ -3,19 +3,27 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use async_trait::async_trait;
+use deno_core::error::custom_error;
 use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::op;
 use deno_core::parking_lot::Mutex;
+use deno_core::task::spawn_blocking;
 use deno_core::url::Url;
 use deno_core::OpState;
 use deno_core::ZeroCopyBuf;
 use serde::Deserialize;
 use serde::Serialize;
+use tokio::sync::OnceCell;
 use uuid::Uuid;
 
 use crate::Location;
@@ -95,8 +103,8 @@ impl Blob {
     let mut bytes = Vec::with_capacity(size);
 
     for part in &self.parts {
-      let chunk = part.read().await?;
-      bytes.extend_from_slice(chunk);
+      let chunk = part.read_range(0, part.size()).await?;
+      bytes.extend_from_slice(&chunk);
     }
 
     assert_eq!(bytes.len(), size);
@@ -118,6 +126,17 @@ pub trait BlobPart: Debug {
   // TODO(lucacsonato): this should be a stream!
   async fn read(&self) -> Result<&[u8], AnyError>;
   fn size(&self) -> usize;
+
+  /// Reads `len` bytes starting at `start`. Parts that aren't kept in memory
+  /// should override this to only read the requested range.
+  async fn read_range(
+    &self,
+    start: usize,
+    len: usize,
+  ) -> Result<Vec<u8>, AnyError> {
+    let bytes = self.read().await?;
+    Ok(bytes[start..start + len].to_vec())
+  }
 }
 
 #[derive(Debug)]
@@ -157,6 +176,87 @@ impl BlobPart for SlicedBlobPart {
   fn size(&self) -> usize {
     self.len
   }
+
+  async fn read_range(
+    &self,
+    start: usize,
+    len: usize,
+  ) -> Result<Vec<u8>, AnyError> {
+    self.part.read_range(self.start + start, len).await
+  }
+}
+
+/// A blob part backed by a file on disk, which is read when the blob is. If
+/// the file changed since the part was created, reading fails.
+#[derive(Debug)]
+pub struct FileBlobPart {
+  path: PathBuf,
+  size: usize,
+  modified: Option<SystemTime>,
+  /// The whole file, only loaded if the part is read at once.
+  contents: OnceCell<Vec<u8>>,
+}
+
+impl FileBlobPart {
+  pub fn new(path: PathBuf, size: usize, modified: Option<SystemTime>) -> Self {
+    Self {
+      path,
+      size,
+      modified,
+      contents: OnceCell::new(),
+    }
+  }
+}
+
+fn read_file_range(
+  path: PathBuf,
+  size: usize,
+  modified: Option<SystemTime>,
+  start: usize,
+  len: usize,
+) -> Result<Vec<u8>, AnyError> {
+  let not_readable = || {
+    custom_error(
+      "DOMExceptionNotReadableError",
+      format!("The file '{}' changed after it was opened", path.display()),
+    )
+  };
+  let mut file = std::fs::File::open(&path)?;
+  let metadata = file.metadata()?;
+  if metadata.len() != size as u64 || metadata.modified().ok() != modified {
+    return Err(not_readable());
+  }
+  file.seek(SeekFrom::Start(start as u64))?;
+  let mut buf = vec![0; len];
+  file.read_exact(&mut buf).map_err(|_| not_readable())?;
+  Ok(buf)
+}
+
+#[async_trait]
+impl BlobPart for FileBlobPart {
+  async fn read(&self) -> Result<&[u8], AnyError> {
+    let contents = self
+      .contents
+      .get_or_try_init(|| self.read_range(0, self.size))
+      .await?;
+    Ok(contents)
+  }
+
+  fn size(&self) -> usize {
+    self.size
+  }
+
+  async fn read_range(
+    &self,
+    start: usize,
+    len: usize,
+  ) -> Result<Vec<u8>, AnyError> {
+    let path = self.path.clone();
+    let size = self.size;
+    let modified = self.modified;
+    spawn_blocking(move || read_file_range(path, size, modified, start, len))
+      .await?
+  }
 }
 
 #[op]
@@ -210,8 +310,30 @@ pub async fn op_blob_read_part(
     blob_store.get_part(&id)
   }
   .ok_or_else(|| type_error("Blob part not found"))?;
-  let buf = part.read().await?;
-  Ok(ZeroCopyBuf::from(buf.to_vec()))
+  let buf = part.read_range(0, part.size()).await?;
+  Ok(ZeroCopyBuf::from(buf))
+}
+
+#[op]
+pub async fn op_blob_read_part_range(
+  state: Rc<RefCell<OpState>>,
+  id: Uuid,
+  start: usize,
+  len: usize,
+) -> Result<ZeroCopyBuf, AnyError> {
+  let part = {
+    let state = state.borrow();
+    let blob_store = state.borrow::<BlobStore>();
+    blob_store.get_part(&id)
+  }
+  .ok_or_else(|| type_error("Blob part not found"))?;
+  if start + len > part.size() {
+    return Err(type_error(
+      "start + len can not be larger than blob part size",
+    ));
+  }
+  let buf = part.read_range(start, len).await?;
+  Ok(ZeroCopyBuf::from(buf))
 }
 
 #[op]
@@ -1,5 +1,6 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::op;
 use deno_core::OpState;
@@ -18,11 +19,14 @@ use std::cell::RefCell;
 use std::io::Write;
 use std::rc::Rc;
 
-#[derive(Debug)]
+// Brotli's default window size of (2**22)-16 bytes (~4MB).
+const BROTLI_LGWIN: u32 = 22;
+const BROTLI_BUFFER_SIZE: usize = 4096;
+
 struct CompressionResource(RefCell<Inner>);
 
-/// https://wicg.github.io/compression/#supported-formats
-#[derive(Debug)]
+/// https://wicg.github.io/compression/#supported-formats, plus the
+/// non-standard "br" and "zstd" formats.
 enum Inner {
   DeflateDecoder(ZlibDecoder<Vec<u8>>),
   DeflateEncoder(ZlibEncoder<Vec<u8>>),
@@ -30,6 +34,32 @@ enum Inner {
   DeflateRawEncoder(DeflateEncoder<Vec<u8>>),
   GzDecoder(GzDecoder<Vec<u8>>),
   GzEncoder(GzEncoder<Vec<u8>>),
+  BrotliDecoder(brotli::DecompressorWriter<Output>, Output),
+  BrotliEncoder(brotli::CompressorWriter<Output>, Output),
+  ZstdDecoder(zstd::stream::write::Decoder<'static, Vec<u8>>),
+  ZstdEncoder(zstd::stream::write::Encoder<'static, Vec<u8>>),
+}
+
+/// The output buffer of the brotli writers, shared with the resource so it
+/// can be drained while the writers hold it.
+#[derive(Clone, Default)]
+struct Output(Rc<RefCell<Vec<u8>>>);
+
+impl Output {
+  fn take(&self) -> Vec<u8> {
+    std::mem::take(&mut *self.0.borrow_mut())
+  }
+}
+
+impl Write for Output {
+  fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    self.0.borrow_mut().extend_from_slice(buf);
+    Ok(buf.len())
+  }
+
+  fn flush(&mut self) -> std::io::Result<()> {
+    Ok(())
+  }
 }
 
 impl Resource for CompressionResource {
@@ -43,7 +73,7 @@ pub fn op_compression_new(
   state: &mut OpState,
   format: &str,
   is_decoder: bool,
-) -> ResourceId {
+) -> Result<ResourceId, AnyError> {
   let w = Vec::new();
   let inner = match (format, is_decoder) {
     ("deflate", true) => Inner::DeflateDecoder(ZlibDecoder::new(w)),
@@ -58,10 +88,33 @@ pub fn op_compression_new(
     ("gzip", false) => {
       Inner::GzEncoder(GzEncoder::new(w, Compression::default()))
     }
+    ("br", true) => {
+      let output = Output::default();
+      let decoder =
+        brotli::DecompressorWriter::new(output.clone(), BROTLI_BUFFER_SIZE);
+      Inner::BrotliDecoder(decoder, output)
+    }
+    ("br", false) => {
+      let output = Output::default();
+      // Quality 6 is nginx's default for compressing on the fly.
+      // https://github.com/google/ngx_brotli#brotli_comp_level
+      let encoder = brotli::CompressorWriter::new(
+        output.clone(),
+        BROTLI_BUFFER_SIZE,
+        6,
+        BROTLI_LGWIN,
+      );
+      Inner::BrotliEncoder(encoder, output)
+    }
+    ("zstd", true) => Inner::ZstdDecoder(zstd::stream::write::Decoder::new(w)?),
+    ("zstd", false) => Inner::ZstdEncoder(zstd::stream::write::Encoder::new(
+      w,
+      zstd::DEFAULT_COMPRESSION_LEVEL,
+    )?),
     _ => unreachable!(),
   };
   let resource = CompressionResource(RefCell::new(inner));
-  state.resource_table.add(resource)
+  Ok(state.resource_table.add(resource))
 }
 
 #[op]
@@ -76,35 +129,54 @@ pub fn op_compression_write(
     Inner::DeflateDecoder(d) => {
       d.write_all(input)?;
       d.flush()?;
-      d.get_mut().drain(..)
+      d.get_mut().drain(..).collect()
     }
     Inner::DeflateEncoder(d) => {
       d.write_all(input)?;
       d.flush()?;
-      d.get_mut().drain(..)
+      d.get_mut().drain(..).collect()
     }
     Inner::DeflateRawDecoder(d) => {
       d.write_all(input)?;
       d.flush()?;
-      d.get_mut().drain(..)
+      d.get_mut().drain(..).collect()
     }
     Inner::DeflateRawEncoder(d) => {
       d.write_all(input)?;
       d.flush()?;
-      d.get_mut().drain(..)
+      d.get_mut().drain(..).collect()
     }
     Inner::GzDecoder(d) => {
       d.write_all(input)?;
       d.flush()?;
-      d.get_mut().drain(..)
+      d.get_mut().drain(..).collect()
     }
     Inner::GzEncoder(d) => {
       d.write_all(input)?;
       d.flush()?;
-      d.get_mut().drain(..)
+      d.get_mut().drain(..).collect()
     }
-  }
-  .collect();
+    Inner::BrotliDecoder(d, output) => {
+      d.write_all(input)?;
+      d.flush()?;
+      output.take()
+    }
+    Inner::BrotliEncoder(d, output) => {
+      d.write_all(input)?;
+      d.flush()?;
+      output.take()
+    }
+    Inner::ZstdDecoder(d) => {
+      d.write_all(input)?;
+      d.flush()?;
+      d.get_mut().drain(..).collect()
+    }
+    Inner::ZstdEncoder(d) => {
+      d.write_all(input)?;
+      d.flush()?;
+      d.get_mut().drain(..).collect()
+    }
+  };
   Ok(out.into())
 }
 
@@ -123,6 +195,19 @@ pub fn op_compression_finish(
     Inner::DeflateRawEncoder(d) => d.finish()?,
     Inner::GzDecoder(d) => d.finish()?,
     Inner::GzEncoder(d) => d.finish()?,
+    Inner::BrotliDecoder(d, output) => {
+      if d.into_inner().is_err() {
+        return Err(type_error("Unexpected end of brotli stream"));
+      }
+      output.take()
+    }
+    Inner::BrotliEncoder(d, output) => {
+      // Finishes the stream.
+      drop(d.into_inner());
+      output.take()
+    }
+    Inner::ZstdDecoder(d) => d.into_inner(),
+    Inner::ZstdEncoder(d) => d.finish()?,
   };
   Ok(out.into())
 }
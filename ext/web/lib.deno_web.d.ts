@@ -980,7 +980,8 @@ declare class CompressionStream {
    * data.
    *
    * Throws a `TypeError` if the format passed to the constructor is not
-   * supported.
+   * supported. The supported formats are `"deflate"`, `"deflate-raw"`,
+   * `"gzip"`, `"br"` (Brotli) and `"zstd"` (Zstandard).
    */
   constructor(format: string);
 
@@ -1009,7 +1010,8 @@ declare class DecompressionStream {
    * data.
    *
    * Throws a `TypeError` if the format passed to the constructor is not
-   * supported.
+   * supported. The supported formats are `"deflate"`, `"deflate-raw"`,
+   * `"gzip"`, `"br"` (Brotli) and `"zstd"` (Zstandard).
    */
   constructor(format: string);
 
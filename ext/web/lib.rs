@@ -4,6 +4,7 @@ mod blob;
 mod compression;
 mod hr_timer_lock;
 mod message_port;
+mod pipe;
 mod timers;
 
 use deno_core::error::range_error;
@@ -35,18 +36,21 @@ use crate::blob::op_blob_create_object_url;
 use crate::blob::op_blob_create_part;
 use crate::blob::op_blob_from_object_url;
 use crate::blob::op_blob_read_part;
+use crate::blob::op_blob_read_part_range;
 use crate::blob::op_blob_remove_part;
 use crate::blob::op_blob_revoke_object_url;
 use crate::blob::op_blob_slice_part;
 pub use crate::blob::Blob;
 pub use crate::blob::BlobPart;
 pub use crate::blob::BlobStore;
+pub use crate::blob::FileBlobPart;
 pub use crate::blob::InMemoryBlobPart;
 
 pub use crate::message_port::create_entangled_message_port;
 use crate::message_port::op_message_port_create_entangled;
 use crate::message_port::op_message_port_post_message;
 use crate::message_port::op_message_port_recv_message;
+use crate::message_port::op_message_port_recv_message_sync;
 pub use crate::message_port::JsMessageData;
 pub use crate::message_port::MessagePort;
 
@@ -74,6 +78,7 @@ deno_core::extension!(deno_web,
     op_blob_create_part,
     op_blob_slice_part,
     op_blob_read_part,
+    op_blob_read_part_range,
     op_blob_remove_part,
     op_blob_create_object_url,
     op_blob_revoke_object_url,
@@ -81,9 +86,11 @@ deno_core::extension!(deno_web,
     op_message_port_create_entangled,
     op_message_port_post_message,
     op_message_port_recv_message,
+    op_message_port_recv_message_sync,
     compression::op_compression_new,
     compression::op_compression_write,
     compression::op_compression_finish,
+    pipe::op_pipe_resources,
     op_now<P>,
     op_timer_handle,
     op_cancel_handle,
@@ -118,7 +125,8 @@ deno_core::extension!(deno_web,
     if let Some(location) = options.maybe_location {
       state.put(Location(location));
     }
-    state.put(StartTime::now());
+    let start_time = state.clock.elapsed();
+    state.put(StartTime(start_time));
   }
 );
 
@@ -262,7 +270,7 @@ fn op_encoding_decode_single(
   }
 }
 
-#[op]
+#[op(fast)]
 fn op_encoding_new_decoder(
   state: &mut OpState,
   label: &str,
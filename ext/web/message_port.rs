@@ -71,6 +71,25 @@ impl MessagePort {
     Ok(None)
   }
 
+  /// Returns the next message if one was already received, without waiting.
+  pub fn try_recv(
+    &self,
+    state: &mut OpState,
+  ) -> Result<Option<JsMessageData>, AnyError> {
+    let mut rx = self
+      .rx
+      .try_borrow_mut()
+      .map_err(|_| type_error("Port receiver is already borrowed"))?;
+    let Ok((data, transferables)) = rx.try_recv() else {
+      return Ok(None);
+    };
+    let js_transferables = serialize_transferables(state, transferables);
+    Ok(Some(JsMessageData {
+      data,
+      transferables: js_transferables,
+    }))
+  }
+
   /// This forcefully disconnects the message port from its paired port. This
   /// will wake up the `.recv` on the paired port, which will return `Ok(None)`.
   pub fn disentangle(&self) {
@@ -225,3 +244,12 @@ pub async fn op_message_port_recv_message(
   let cancel = RcRef::map(resource.clone(), |r| &r.cancel);
   resource.port.recv(state).or_cancel(cancel).await?
 }
+
+#[op]
+pub fn op_message_port_recv_message_sync(
+  state: &mut OpState,
+  rid: ResourceId,
+) -> Result<Option<JsMessageData>, AnyError> {
+  let resource = state.resource_table.get::<MessagePortResource>(rid)?;
+  resource.port.try_recv(state)
+}
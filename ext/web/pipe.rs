@@ -0,0 +1,344 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
+use deno_core::OpState;
+use deno_core::Resource;
+use deno_core::ResourceId;
+
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Copies everything that can be read from the `from_rid` resource into the
+/// `to_rid` resource, and returns the number of bytes copied. The data never
+/// reaches JS; on Linux, the kernel copies it when both resources are backed
+/// by a file descriptor.
+#[op]
+pub async fn op_pipe_resources(
+  state: Rc<RefCell<OpState>>,
+  from_rid: ResourceId,
+  to_rid: ResourceId,
+  cancel_rid: Option<ResourceId>,
+) -> Result<u64, AnyError> {
+  let (from, to, cancel_handle) = {
+    let state = state.borrow();
+    let cancel_handle = match cancel_rid {
+      Some(rid) => state.resource_table.get::<CancelHandle>(rid)?,
+      None => CancelHandle::new_rc(),
+    };
+    (
+      state.resource_table.get_any(from_rid)?,
+      state.resource_table.get_any(to_rid)?,
+      cancel_handle,
+    )
+  };
+  pipe_resources(from, to).or_cancel(cancel_handle).await?
+}
+
+async fn pipe_resources(
+  from: Rc<dyn Resource>,
+  to: Rc<dyn Resource>,
+) -> Result<u64, AnyError> {
+  #[cfg(target_os = "linux")]
+  if let Some(copied) = linux::pipe_fds(from.clone(), to.clone()).await? {
+    return Ok(copied);
+  }
+
+  let mut copied = 0;
+  loop {
+    let buf = from.clone().read(COPY_CHUNK_SIZE).await?;
+    if buf.is_empty() {
+      return Ok(copied);
+    }
+    copied += buf.len() as u64;
+    to.clone().write_all(buf).await?;
+  }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+  use std::io;
+  use std::os::unix::io::AsRawFd;
+  use std::os::unix::io::BorrowedFd;
+  use std::os::unix::io::OwnedFd;
+  use std::os::unix::io::RawFd;
+  use std::rc::Rc;
+  use std::sync::atomic::AtomicBool;
+  use std::sync::atomic::Ordering;
+  use std::sync::Arc;
+
+  use deno_core::error::AnyError;
+  use deno_core::task::spawn_blocking;
+  use deno_core::Resource;
+
+  /// The maximum number of bytes moved by a single syscall, which bounds how
+  /// long a cancelled copy keeps running.
+  const CHUNK_SIZE: usize = 1024 * 1024;
+
+  /// How often a copy waiting for a descriptor to become ready checks whether
+  /// it was cancelled, in milliseconds.
+  const POLL_TIMEOUT: libc::c_int = 100;
+
+  /// Copies between the descriptors backing the resources on a blocking
+  /// thread. Returns `None` if the resources aren't backed by descriptors the
+  /// kernel can copy between, in which case nothing was copied.
+  pub async fn pipe_fds(
+    from: Rc<dyn Resource>,
+    to: Rc<dyn Resource>,
+  ) -> Result<Option<u64>, AnyError> {
+    let (Some(from), Some(to)) = (from.backing_fd(), to.backing_fd()) else {
+      return Ok(None);
+    };
+    // The blocking thread works on duplicates, so that closing the resources
+    // while it runs can't make it copy into an unrelated file that reused the
+    // descriptor.
+    let from = dup(from)?;
+    let to = dup(to)?;
+    let canceled = Arc::new(AtomicBool::new(false));
+    let _guard = CancelOnDrop(canceled.clone());
+    let copied =
+      spawn_blocking(move || copy(from.as_raw_fd(), to.as_raw_fd(), &canceled))
+        .await??;
+    Ok(copied)
+  }
+
+  struct CancelOnDrop(Arc<AtomicBool>);
+
+  impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+      self.0.store(true, Ordering::Relaxed);
+    }
+  }
+
+  fn dup(fd: RawFd) -> io::Result<OwnedFd> {
+    // SAFETY: the descriptor is owned by a resource that outlives this call.
+    unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned()
+  }
+
+  fn fstat(fd: RawFd) -> io::Result<libc::stat> {
+    // SAFETY: `stat` is plain data and is filled in by `fstat`.
+    let mut stat = unsafe { std::mem::zeroed() };
+    // SAFETY: `stat` is a valid pointer.
+    if unsafe { libc::fstat(fd, &mut stat) } < 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(stat)
+  }
+
+  fn is_append(fd: RawFd) -> io::Result<bool> {
+    // SAFETY: F_GETFL takes no argument.
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags < 0 {
+      return Err(io::Error::last_os_error());
+    }
+    Ok(flags & libc::O_APPEND != 0)
+  }
+
+  #[derive(PartialEq)]
+  enum Kind {
+    File,
+    Pipe,
+    Socket,
+    Other,
+  }
+
+  fn kind(stat: &libc::stat) -> Kind {
+    match stat.st_mode & libc::S_IFMT {
+      libc::S_IFREG => Kind::File,
+      libc::S_IFIFO => Kind::Pipe,
+      libc::S_IFSOCK => Kind::Socket,
+      _ => Kind::Other,
+    }
+  }
+
+  fn copy(
+    from: RawFd,
+    to: RawFd,
+    canceled: &AtomicBool,
+  ) -> io::Result<Option<u64>> {
+    let from_stat = fstat(from)?;
+    let from_kind = kind(&from_stat);
+    let to_kind = kind(&fstat(to)?);
+    // The kernel can't append to a file with any of these syscalls, and
+    // character devices such as terminals don't support them.
+    if from_kind == Kind::Other || to_kind == Kind::Other || is_append(to)? {
+      return Ok(None);
+    }
+
+    if from_kind == Kind::File {
+      // Files in procfs and sysfs report a size of 0 and copy_file_range()
+      // copies nothing from them.
+      if to_kind == Kind::File && from_stat.st_size > 0 {
+        if let Some(copied) = copy_with(from, to, canceled, copy_file_range)? {
+          return Ok(Some(copied));
+        }
+      }
+      return copy_with(from, to, canceled, sendfile);
+    }
+    splice_through_pipe(from, to, canceled).map(Some)
+  }
+
+  fn copy_file_range(from: RawFd, to: RawFd) -> isize {
+    // SAFETY: null offsets make the syscall use and update the file offsets.
+    unsafe {
+      libc::copy_file_range(
+        from,
+        std::ptr::null_mut(),
+        to,
+        std::ptr::null_mut(),
+        CHUNK_SIZE,
+        0,
+      ) as isize
+    }
+  }
+
+  fn sendfile(from: RawFd, to: RawFd) -> isize {
+    // SAFETY: a null offset makes the syscall use and update the file offset.
+    unsafe { libc::sendfile(to, from, std::ptr::null_mut(), CHUNK_SIZE) }
+  }
+
+  /// Repeats `step`, which reads from a file, until the end of the file.
+  /// Returns `None` if the first step fails because the kernel doesn't support
+  /// it for these descriptors.
+  fn copy_with(
+    from: RawFd,
+    to: RawFd,
+    canceled: &AtomicBool,
+    step: fn(RawFd, RawFd) -> isize,
+  ) -> io::Result<Option<u64>> {
+    let mut copied = 0;
+    loop {
+      check_canceled(canceled)?;
+      let n = step(from, to);
+      if n < 0 {
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+          Some(libc::EINTR) => continue,
+          Some(libc::EAGAIN) => wait(to, libc::POLLOUT, canceled)?,
+          Some(
+            libc::EINVAL
+            | libc::ENOSYS
+            | libc::EXDEV
+            | libc::EOPNOTSUPP
+            | libc::EBADF,
+          ) if copied == 0 => return Ok(None),
+          _ => return Err(err),
+        }
+        continue;
+      }
+      if n == 0 {
+        return Ok(Some(copied));
+      }
+      copied += n as u64;
+    }
+  }
+
+  /// Moves the data through a pipe with splice(), which works between any
+  /// pipes, sockets and files.
+  fn splice_through_pipe(
+    from: RawFd,
+    to: RawFd,
+    canceled: &AtomicBool,
+  ) -> io::Result<u64> {
+    let (pipe_rd, pipe_wr) = pipe()?;
+    let mut copied = 0;
+    loop {
+      check_canceled(canceled)?;
+      let n = splice(from, pipe_wr.as_raw_fd());
+      if n < 0 {
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+          Some(libc::EINTR) => continue,
+          Some(libc::EAGAIN) => wait(from, libc::POLLIN, canceled)?,
+          _ => return Err(err),
+        }
+        continue;
+      }
+      if n == 0 {
+        return Ok(copied);
+      }
+
+      let mut buffered = n as u64;
+      while buffered > 0 {
+        check_canceled(canceled)?;
+        let n = splice(pipe_rd.as_raw_fd(), to);
+        if n < 0 {
+          let err = io::Error::last_os_error();
+          match err.raw_os_error() {
+            Some(libc::EINTR) => continue,
+            Some(libc::EAGAIN) => wait(to, libc::POLLOUT, canceled)?,
+            _ => return Err(err),
+          }
+          continue;
+        }
+        buffered -= n as u64;
+        copied += n as u64;
+      }
+    }
+  }
+
+  fn pipe() -> io::Result<(OwnedFd, OwnedFd)> {
+    let mut fds = [0; 2];
+    // SAFETY: `fds` has room for the two descriptors.
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0 {
+      return Err(io::Error::last_os_error());
+    }
+    // SAFETY: pipe2() just created the descriptors.
+    unsafe {
+      use std::os::unix::io::FromRawFd;
+      Ok((OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])))
+    }
+  }
+
+  fn splice(from: RawFd, to: RawFd) -> isize {
+    // SAFETY: null offsets make the syscall use and update the file offsets.
+    unsafe {
+      libc::splice(
+        from,
+        std::ptr::null_mut(),
+        to,
+        std::ptr::null_mut(),
+        CHUNK_SIZE,
+        libc::SPLICE_F_MOVE,
+      )
+    }
+  }
+
+  /// Waits for a non-blocking descriptor to become ready.
+  fn wait(
+    fd: RawFd,
+    events: libc::c_short,
+    canceled: &AtomicBool,
+  ) -> io::Result<()> {
+    let mut pollfd = libc::pollfd {
+      fd,
+      events,
+      revents: 0,
+    };
+    loop {
+      check_canceled(canceled)?;
+      // SAFETY: `pollfd` is a valid pointer to a single entry.
+      let n = unsafe { libc::poll(&mut pollfd, 1, POLL_TIMEOUT) };
+      if n < 0 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::EINTR) {
+          return Err(err);
+        }
+      } else if n > 0 {
+        // Errors and hangups are reported by the next syscall.
+        return Ok(());
+      }
+    }
+  }
+
+  fn check_canceled(canceled: &AtomicBool) -> io::Result<()> {
+    if canceled.load(Ordering::Relaxed) {
+      return Err(io::Error::from(io::ErrorKind::Interrupted));
+    }
+    Ok(())
+  }
+}
@@ -14,14 +14,14 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::time::Duration;
-use std::time::Instant;
 
 pub trait TimersPermission {
   fn allow_hrtime(&mut self) -> bool;
   fn check_unstable(&self, state: &OpState, api_name: &'static str);
 }
 
-pub type StartTime = Instant;
+/// The value of [`OpState::clock`]'s elapsed time when the runtime started.
+pub struct StartTime(pub Duration);
 
 // Returns a milliseconds and nanoseconds subsec
 // since the start time of the deno runtime.
@@ -32,8 +32,8 @@ pub fn op_now<TP>(state: &mut OpState, buf: &mut [u8])
 where
   TP: TimersPermission + 'static,
 {
-  let start_time = state.borrow::<StartTime>();
-  let elapsed = start_time.elapsed();
+  let start_time = state.borrow::<StartTime>().0;
+  let elapsed = state.clock.elapsed().saturating_sub(start_time);
   let seconds = elapsed.as_secs();
   let mut subsec_nanos = elapsed.subsec_nanos();
 
@@ -85,7 +85,11 @@ pub async fn op_sleep(
   millis: u64,
   rid: ResourceId,
 ) -> Result<bool, AnyError> {
-  let handle = state.borrow().resource_table.get::<TimerHandle>(rid)?;
+  let (handle, sleep) = {
+    let state = state.borrow();
+    let handle = state.resource_table.get::<TimerHandle>(rid)?;
+    (handle, state.clock.sleep(Duration::from_millis(millis)))
+  };
 
   // If a timer is requested with <=100ms resolution, request the high-res timer. Since the default
   // Windows timer period is 15ms, this means a 100ms timer could fire at 115ms (15% late). We assume that
@@ -100,9 +104,7 @@ pub async fn op_sleep(
     None
   };
 
-  let res = tokio::time::sleep(Duration::from_millis(millis))
-    .or_cancel(handle.0.clone())
-    .await;
+  let res = sleep.or_cancel(handle.0.clone()).await;
 
   // We release the high-res timer lock here, either by being cancelled or resolving.
   Ok(res.is_ok())
@@ -16,6 +16,8 @@ import {
 } from "ext:deno_fetch/20_headers.js";
 const primordials = globalThis.__bootstrap.primordials;
 const {
+  ArrayBufferIsView,
+  ArrayBufferPrototype,
   ArrayPrototypeJoin,
   ArrayPrototypeMap,
   DateNow,
@@ -31,7 +33,6 @@ const {
   SymbolFor,
   TypeError,
   TypedArrayPrototypeGetByteLength,
-  Uint8ArrayPrototype,
 } = primordials;
 const {
   op_ws_send_text_async,
@@ -62,11 +63,23 @@ webidl.converters.WebSocketStreamOptions = webidl.createDictionaryConverter(
       key: "headers",
       converter: webidl.converters.HeadersInit,
     },
+    {
+      key: "certChain",
+      converter: webidl.converters.DOMString,
+    },
+    {
+      key: "privateKey",
+      converter: webidl.converters.DOMString,
+    },
   ],
 );
 webidl.converters.WebSocketCloseInfo = webidl.createDictionaryConverter(
   "WebSocketCloseInfo",
   [
+    {
+      key: "closeCode",
+      converter: webidl.converters["unsigned short"],
+    },
     {
       key: "code",
       converter: webidl.converters["unsigned short"],
@@ -81,6 +94,16 @@ webidl.converters.WebSocketCloseInfo = webidl.createDictionaryConverter(
 
 const CLOSE_RESPONSE_TIMEOUT = 5000;
 
+/**
+ * The reason a stream was closed or aborted with is used as the close info
+ * if it has a close code.
+ */
+function closeInfoFromReason(reason) {
+  return reason?.closeCode !== undefined || reason?.code !== undefined
+    ? reason
+    : {};
+}
+
 const _rid = Symbol("[[rid]]");
 const _url = Symbol("[[url]]");
 const _connection = Symbol("[[connection]]");
@@ -170,6 +193,8 @@ class WebSocketStream {
           options.protocols ? ArrayPrototypeJoin(options.protocols, ", ") : "",
           cancelRid,
           headerListFromHeaders(headers),
+          options.certChain,
+          options.privateKey,
         ),
         (create) => {
           options.signal?.[remove](abort);
@@ -215,18 +240,19 @@ class WebSocketStream {
                 if (typeof chunk === "string") {
                   await op_ws_send_text_async(this[_rid], chunk);
                 } else if (
-                  ObjectPrototypeIsPrototypeOf(Uint8ArrayPrototype, chunk)
+                  ArrayBufferIsView(chunk) ||
+                  ObjectPrototypeIsPrototypeOf(ArrayBufferPrototype, chunk)
                 ) {
                   await op_ws_send_binary_async(this[_rid], chunk);
                 } else {
                   throw new TypeError(
-                    "A chunk may only be either a string or an Uint8Array",
+                    "A chunk may only be either a string or a BufferSource",
                   );
                 }
               },
               close: async (reason) => {
                 try {
-                  this.close(reason?.code !== undefined ? reason : {});
+                  this.close(closeInfoFromReason(reason));
                 } catch (_) {
                   this.close();
                 }
@@ -234,7 +260,7 @@ class WebSocketStream {
               },
               abort: async (reason) => {
                 try {
-                  this.close(reason?.code !== undefined ? reason : {});
+                  this.close(closeInfoFromReason(reason));
                 } catch (_) {
                   this.close();
                 }
@@ -256,7 +282,9 @@ class WebSocketStream {
                 }
                 case 2: {
                   /* pong */
-                  break;
+                  // Nothing was enqueued, so the stream won't pull again on
+                  // its own.
+                  return pull(controller);
                 }
                 case 3: {
                   /* error */
@@ -275,6 +303,7 @@ class WebSocketStream {
                 default: {
                   /* close */
                   this[_closed].resolve({
+                    closeCode: kind,
                     code: kind,
                     reason: op_ws_get_error(this[_rid]),
                   });
@@ -325,7 +354,7 @@ class WebSocketStream {
               pull,
               cancel: async (reason) => {
                 try {
-                  this.close(reason?.code !== undefined ? reason : {});
+                  this.close(closeInfoFromReason(reason));
                 } catch (_) {
                   this.close();
                 }
@@ -361,6 +390,12 @@ class WebSocketStream {
     return this[_connection].promise;
   }
 
+  /** The name of `connection` in the current version of the proposal. */
+  get opened() {
+    webidl.assertBranded(this, WebSocketStreamPrototype);
+    return this[_connection].promise;
+  }
+
   [_earlyClose] = false;
   [_closed] = new Deferred();
   [_closeSent] = new Deferred();
@@ -377,11 +412,8 @@ class WebSocketStream {
       "Argument 1",
     );
 
-    if (
-      closeInfo.code &&
-      !(closeInfo.code === 1000 ||
-        (3000 <= closeInfo.code && closeInfo.code < 5000))
-    ) {
+    let code = closeInfo.closeCode ?? closeInfo.code;
+    if (code && !(code === 1000 || (3000 <= code && code < 5000))) {
       throw new DOMException(
         "The close code must be either 1000 or in the range of 3000 to 4999.",
         "InvalidAccessError",
@@ -399,7 +431,6 @@ class WebSocketStream {
       );
     }
 
-    let code = closeInfo.code;
     if (closeInfo.reason && code === undefined) {
       code = 1000;
     }
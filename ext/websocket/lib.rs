@@ -174,10 +174,19 @@ pub async fn op_ws_create<WP>(
   protocols: String,
   cancel_handle: Option<ResourceId>,
   headers: Option<Vec<(ByteString, ByteString)>>,
+  cert_chain: Option<String>,
+  private_key: Option<String>,
 ) -> Result<CreateResponse, AnyError>
 where
   WP: WebSocketPermissions + 'static,
 {
+  let client_cert_chain_and_key = match (cert_chain, private_key) {
+    (Some(cert_chain), Some(private_key)) => Some((cert_chain, private_key)),
+    (Some(_), None) => return Err(type_error("No private key provided")),
+    (None, Some(_)) => return Err(type_error("No certificate chain provided")),
+    (None, None) => None,
+  };
+
   {
     let mut s = state.borrow_mut();
     s.borrow_mut::<WP>()
@@ -274,8 +283,9 @@ where
       let tls_config = create_client_config(
         root_cert_store,
         vec![],
+        vec![],
         unsafely_ignore_certificate_errors,
-        None,
+        client_cert_chain_and_key,
       )?;
       let tls_connector = TlsConnector::from(Arc::new(tls_config));
       let dnsname = ServerName::try_from(domain.as_str())
@@ -578,7 +588,8 @@ pub async fn op_ws_next_event(
   let Ok(resource) = state
     .borrow_mut()
     .resource_table
-    .get::<ServerWebSocket>(rid) else {
+    .get::<ServerWebSocket>(rid)
+  else {
     // op_ws_get_error will correctly handle a bad resource
     return MessageKind::Error as u16;
   };
@@ -3,5 +3,4 @@ fn op_print(
   msg: &str,
   is_err: bool,
 ) -> Result<(), AnyError> {
-  // TODO(@littledivy): fast compatible https://github.com/denoland/deno/issues/17159
 }
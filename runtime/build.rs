@@ -77,6 +77,14 @@ mod startup_snapshot {
     ) -> Result<(), deno_core::error::AnyError> {
       unreachable!("snapshotting!")
     }
+
+    fn check_write(
+      &mut self,
+      _p: &Path,
+      _api_name: &str,
+    ) -> Result<(), deno_core::error::AnyError> {
+      unreachable!("snapshotting!")
+    }
   }
 
   impl deno_websocket::WebSocketPermissions for Permissions {
@@ -204,6 +212,23 @@ mod startup_snapshot {
     }
   }
 
+  impl deno_kv::remote::RemoteDbHandlerPermissions for Permissions {
+    fn check_env(
+      &mut self,
+      _var: &str,
+    ) -> Result<(), deno_core::error::AnyError> {
+      unreachable!("snapshotting!")
+    }
+
+    fn check_net_url(
+      &mut self,
+      _url: &deno_core::url::Url,
+      _api_name: &str,
+    ) -> Result<(), deno_core::error::AnyError> {
+      unreachable!("snapshotting!")
+    }
+  }
+
   impl deno_kv::sqlite::SqliteDbHandlerPermissions for Permissions {
     fn check_read(
       &mut self,
@@ -253,6 +278,8 @@ mod startup_snapshot {
       "11_workers.js",
       "13_buffer.js",
       "30_os.js",
+      "40_blob.js",
+      "40_cron.js",
       "40_fs_events.js",
       "40_http.js",
       "40_process.js",
@@ -319,11 +346,11 @@ mod startup_snapshot {
       deno_ffi::deno_ffi::init_ops_and_esm::<Permissions>(false),
       deno_net::deno_net::init_ops_and_esm::<Permissions>(
         None, false, // No --unstable.
-        None,
+        None, None,
       ),
       deno_tls::deno_tls::init_ops_and_esm(),
       deno_kv::deno_kv::init_ops_and_esm(
-        deno_kv::sqlite::SqliteDbHandler::<Permissions>::new(None),
+        deno_kv::multi_backend::MultiBackendDbHandler::<Permissions>::new(None),
         false, // No --unstable
       ),
       deno_napi::deno_napi::init_ops_and_esm::<Permissions>(),
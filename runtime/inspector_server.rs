@@ -2,6 +2,8 @@
 
 // Alias for the future `!` type.
 use core::convert::Infallible as Never;
+use deno_core::error::generic_error;
+use deno_core::error::AnyError;
 use deno_core::futures::channel::mpsc;
 use deno_core::futures::channel::mpsc::UnboundedReceiver;
 use deno_core::futures::channel::mpsc::UnboundedSender;
@@ -19,6 +21,7 @@ use deno_core::task::spawn;
 use deno_core::InspectorMsg;
 use deno_core::InspectorSessionProxy;
 use deno_core::JsRuntime;
+use deno_core::JsRuntimeInspector;
 use fastwebsockets::Frame;
 use fastwebsockets::OpCode;
 use fastwebsockets::WebSocket;
@@ -65,14 +68,25 @@ impl InspectorServer {
     }
   }
 
+  /// Registers the inspector of `js_runtime` and returns the websocket URL
+  /// that debuggers connect to.
   pub fn register_inspector(
     &self,
     module_url: String,
     js_runtime: &mut JsRuntime,
     wait_for_session: bool,
-  ) {
+  ) -> String {
     let inspector_rc = js_runtime.inspector();
     let mut inspector = inspector_rc.borrow_mut();
+    self.register(module_url, &mut inspector, wait_for_session)
+  }
+
+  fn register(
+    &self,
+    module_url: String,
+    inspector: &mut JsRuntimeInspector,
+    wait_for_session: bool,
+  ) -> String {
     let session_sender = inspector.get_session_sender();
     let deregister_rx = inspector.add_deregister_handler();
     let info = InspectorInfo::new(
@@ -82,7 +96,80 @@ impl InspectorServer {
       module_url,
       wait_for_session,
     );
+    let url = info.get_websocket_debugger_url();
     self.register_inspector_tx.unbounded_send(info).unwrap();
+    url
+  }
+}
+
+/// Implements `inspector.open()`, `inspector.close()` and `inspector.url()`
+/// of `node:inspector` for a main worker.
+pub struct NodeInspector {
+  module_url: String,
+  active: RefCell<Option<ActiveInspectorServer>>,
+}
+
+enum ActiveInspectorServer {
+  /// A server that was started with `--inspect`, which lives as long as the
+  /// process.
+  Shared(String),
+  /// A server that was started by `inspector.open()`.
+  Owned(InspectorServer, String),
+}
+
+impl NodeInspector {
+  pub fn new(module_url: String) -> Self {
+    Self {
+      module_url,
+      active: Default::default(),
+    }
+  }
+
+  /// Creates a handle for a worker whose inspector was registered with
+  /// `--inspect` and is reachable at `url`.
+  pub fn with_shared_server(module_url: String, url: String) -> Self {
+    Self {
+      module_url,
+      active: RefCell::new(Some(ActiveInspectorServer::Shared(url))),
+    }
+  }
+}
+
+impl deno_node::NodeInspectorServer for NodeInspector {
+  fn open(
+    &self,
+    host: SocketAddr,
+    inspector: &mut JsRuntimeInspector,
+  ) -> Result<(), AnyError> {
+    let mut active = self.active.borrow_mut();
+    if active.is_some() {
+      return Err(generic_error("Inspector is already activated"));
+    }
+    // Bind the address first, so that an unavailable address is reported
+    // as an error instead of terminating the server thread. This also
+    // resolves port 0 to a free port.
+    let host = std::net::TcpListener::bind(host)?.local_addr()?;
+    let server = InspectorServer::new(host, "Deno");
+    let url = server.register(self.module_url.clone(), inspector, false);
+    *active = Some(ActiveInspectorServer::Owned(server, url));
+    Ok(())
+  }
+
+  fn close(&self) {
+    let mut active = self.active.borrow_mut();
+    // The server started with `--inspect` is shared with other workers, so
+    // it is kept running.
+    if let Some(ActiveInspectorServer::Owned(..)) = &*active {
+      active.take();
+    }
+  }
+
+  fn url(&self) -> Option<String> {
+    match &*self.active.borrow() {
+      Some(ActiveInspectorServer::Shared(url))
+      | Some(ActiveInspectorServer::Owned(_, url)) => Some(url.clone()),
+      None => None,
+    }
   }
 }
 
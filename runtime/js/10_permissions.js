@@ -127,6 +127,8 @@ function cache(desc, state) {
     ReflectHas(desc, "path")
   ) {
     key += `-${desc.path}&`;
+  } else if (desc.name === "net" && desc.url) {
+    key += `-${desc.url}&`;
   } else if (desc.name === "net" && desc.host) {
     key += `-${desc.host}&`;
   } else if (desc.name === "run" && desc.command) {
@@ -173,6 +175,8 @@ function formDescriptor(desc) {
     desc.path = pathFromURL(desc.path);
   } else if (desc.name === "run") {
     desc.command = pathFromURL(desc.command);
+  } else if (desc.name === "net" && desc.url !== undefined) {
+    desc.url = String(desc.url);
   }
 }
 
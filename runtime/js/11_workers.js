@@ -34,6 +34,7 @@ function createWorker(
   hasSourceCode,
   sourceCode,
   permissions,
+  resourceLimits,
   name,
   workerType,
 ) {
@@ -41,6 +42,7 @@ function createWorker(
     hasSourceCode,
     name,
     permissions: serializePermissions(permissions),
+    resourceLimits,
     sourceCode,
     specifier,
     workerType,
@@ -111,6 +113,10 @@ class Worker extends EventTarget {
       hasSourceCode,
       sourceCode,
       deno?.permissions,
+      {
+        memoryLimitMb: deno?.memoryLimitMb,
+        cpuTimeLimitMs: deno?.cpuTimeLimitMs,
+      },
       name,
       workerType,
     );
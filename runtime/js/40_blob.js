@@ -0,0 +1,33 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+const core = globalThis.Deno.core;
+const primordials = globalThis.__bootstrap.primordials;
+const {
+  ArrayPrototypePop,
+  DateNow,
+  StringPrototypeSplit,
+} = primordials;
+import { pathFromURL } from "ext:deno_web/00_infra.js";
+import { fileFromBlobPart } from "ext:deno_web/09_file.js";
+
+/**
+ * Opens the file at `path` as a `File` whose contents are read from disk
+ * when the blob is read, instead of being loaded into memory.
+ */
+async function openBlob(path, options = {}) {
+  path = pathFromURL(path);
+  const { uuid, size, lastModified } = await core.opAsync(
+    "op_blob_open_file",
+    path,
+  );
+  const name = ArrayPrototypePop(StringPrototypeSplit(path, /[\\/]/));
+  return fileFromBlobPart(
+    uuid,
+    size,
+    name,
+    options.type ?? "",
+    lastModified ?? DateNow(),
+  );
+}
+
+export { openBlob };
@@ -0,0 +1,92 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+const core = globalThis.Deno.core;
+const ops = core.ops;
+const primordials = globalThis.__bootstrap.primordials;
+const {
+  ArrayPrototypeIncludes,
+  ArrayPrototypeJoin,
+  PromiseResolve,
+  String,
+  TypeError,
+} = primordials;
+
+const OVERLAP_POLICIES = ["skip", "allow", "queue"];
+
+/**
+ * Runs `handler` whenever `schedule` is due until `options.signal` aborts.
+ * While a cron is registered, it keeps the program alive.
+ *
+ * The overlap policy decides what happens when the schedule is due while the
+ * handler of the last run is still running: "skip" (the default) skips the
+ * run, "queue" runs the handler once more after the running one finished and
+ * "allow" runs the handlers concurrently.
+ */
+function cron(name, schedule, optionsOrHandler, maybeHandler) {
+  if (name === undefined) {
+    throw new TypeError("Deno.cron requires a unique name");
+  }
+  if (schedule === undefined) {
+    throw new TypeError("Deno.cron requires a valid schedule");
+  }
+
+  let options = optionsOrHandler;
+  let handler = maybeHandler;
+  if (typeof optionsOrHandler === "function") {
+    options = undefined;
+    handler = optionsOrHandler;
+  }
+  if (typeof handler !== "function") {
+    throw new TypeError("Deno.cron requires a handler");
+  }
+  const overlap = options?.overlap ?? "skip";
+  if (!ArrayPrototypeIncludes(OVERLAP_POLICIES, overlap)) {
+    throw new TypeError(
+      `Invalid overlap policy '${overlap}', expected one of: ${
+        ArrayPrototypeJoin(OVERLAP_POLICIES, ", ")
+      }`,
+    );
+  }
+
+  const signal = options?.signal;
+  if (signal?.aborted) {
+    return PromiseResolve();
+  }
+
+  const rid = ops.op_cron_create(String(name), String(schedule));
+  signal?.addEventListener("abort", () => core.tryClose(rid), { once: true });
+
+  const run = async () => {
+    try {
+      await handler();
+    } catch (error) {
+      console.error(`Exception in cron handler ${name}`, error);
+    }
+  };
+
+  return (async () => {
+    let running = null;
+    let queued = false;
+    while (await core.opAsync("op_cron_next", rid)) {
+      if (overlap === "allow") {
+        run();
+        continue;
+      }
+      if (running !== null) {
+        queued = overlap === "queue";
+        continue;
+      }
+      running = (async () => {
+        do {
+          queued = false;
+          await run();
+        } while (queued);
+        running = null;
+      })();
+    }
+    core.tryClose(rid);
+    await running;
+  })();
+}
+
+export { cron };
@@ -158,7 +158,12 @@ function spawnChildInner(opFn, command, apiName, {
   stderr = "piped",
   signal = undefined,
   windowsRawArguments = false,
+  detached = false,
+  newProcessGroup = false,
+  newSession = false,
+  pty = undefined,
 } = {}) {
+  pty = normalizePtySize(pty);
   const child = opFn({
     cmd: pathFromURL(command),
     args: ArrayPrototypeMap(args, String),
@@ -167,10 +172,15 @@ function spawnChildInner(opFn, command, apiName, {
     env: ObjectEntries(env),
     uid,
     gid,
-    stdin,
-    stdout,
-    stderr,
+    // The pty is the child's stdio.
+    stdin: pty ? "null" : stdin,
+    stdout: pty ? "null" : stdout,
+    stderr: pty ? "null" : stderr,
     windowsRawArguments,
+    detached,
+    newProcessGroup,
+    newSession,
+    pty,
   }, apiName);
   return new ChildProcess(illegalConstructorKey, {
     ...child,
@@ -178,6 +188,14 @@ function spawnChildInner(opFn, command, apiName, {
   });
 }
 
+function normalizePtySize(pty) {
+  if (pty === undefined || pty === false) {
+    return null;
+  }
+  const { columns = 80, rows = 24 } = pty === true ? {} : pty;
+  return { columns, rows };
+}
+
 function spawnChild(command, options = {}) {
   return spawnChildInner(
     ops.op_spawn_child,
@@ -234,6 +252,14 @@ class ChildProcess {
     return this.#stderr;
   }
 
+  #pty = null;
+  get pty() {
+    if (this.#pty == null) {
+      throw new TypeError("pty is not allocated");
+    }
+    return this.#pty;
+  }
+
   constructor(key = null, {
     signal,
     rid,
@@ -241,6 +267,7 @@ class ChildProcess {
     stdinRid,
     stdoutRid,
     stderrRid,
+    ptyRid,
   } = null) {
     if (key !== illegalConstructorKey) {
       throw new TypeError("Illegal constructor.");
@@ -263,6 +290,17 @@ class ChildProcess {
       this.#stderr = readableStreamForRidUnrefable(stderrRid);
     }
 
+    if (ptyRid !== null) {
+      this.#pty = {
+        readable: readableStreamForRidUnrefable(ptyRid),
+        // Closing the input must not close the output.
+        writable: writableStreamForRid(ptyRid, false),
+        resize(columns, rows) {
+          ops.op_spawn_pty_resize(ptyRid, { columns, rows });
+        },
+      };
+    }
+
     const onAbort = () => this.kill("SIGTERM");
     signal?.[abortSignal.add](onAbort);
 
@@ -329,6 +367,7 @@ class ChildProcess {
     core.refOp(this.#waitPromiseId);
     if (this.#stdout) readableStreamForRidUnrefableRef(this.#stdout);
     if (this.#stderr) readableStreamForRidUnrefableRef(this.#stderr);
+    if (this.#pty) readableStreamForRidUnrefableRef(this.#pty.readable);
   }
 
   unref() {
@@ -336,6 +375,7 @@ class ChildProcess {
     core.unrefOp(this.#waitPromiseId);
     if (this.#stdout) readableStreamForRidUnrefableUnref(this.#stdout);
     if (this.#stderr) readableStreamForRidUnrefableUnref(this.#stderr);
+    if (this.#pty) readableStreamForRidUnrefableUnref(this.#pty.readable);
   }
 }
 
@@ -345,6 +385,11 @@ function spawn(command, options) {
       "Piped stdin is not supported for this function, use 'Deno.Command().spawn()' instead",
     );
   }
+  if (options?.pty) {
+    throw new TypeError(
+      "A pty is not supported for this function, use 'Deno.Command().spawn()' instead",
+    );
+  }
   return spawnChildInner(
     ops.op_spawn_child,
     command,
@@ -365,6 +410,10 @@ function spawnSync(command, {
   stdout = "piped",
   stderr = "piped",
   windowsRawArguments = false,
+  detached = false,
+  newProcessGroup = false,
+  newSession = false,
+  pty = undefined,
 } = {}) {
   if (stdin === "piped") {
     throw new TypeError(
@@ -383,6 +432,10 @@ function spawnSync(command, {
     stdout,
     stderr,
     windowsRawArguments,
+    detached,
+    newProcessGroup,
+    newSession,
+    pty: normalizePtySize(pty),
   });
   return {
     success: result.status.success,
@@ -16,6 +16,8 @@ import * as io from "ext:deno_io/12_io.js";
 import * as buffer from "ext:runtime/13_buffer.js";
 import * as fs from "ext:deno_fs/30_fs.js";
 import * as os from "ext:runtime/30_os.js";
+import * as blob from "ext:runtime/40_blob.js";
+import * as cron from "ext:runtime/40_cron.js";
 import * as fsEvents from "ext:runtime/40_fs_events.js";
 import * as process from "ext:runtime/40_process.js";
 import * as signals from "ext:runtime/40_signals.js";
@@ -159,6 +161,7 @@ const denoNsUnstable = {
   umask: fs.umask,
   HttpClient: httpClient.HttpClient,
   createHttpClient: httpClient.createHttpClient,
+  CookieJar: httpClient.CookieJar,
   // TODO(bartlomieju): why is it needed?
   http,
   dlopen: ffi.dlopen,
@@ -170,12 +173,23 @@ const denoNsUnstable = {
   flockSync: fs.flockSync,
   funlock: fs.funlock,
   funlockSync: fs.funlockSync,
+  getXattr: fs.getXattr,
+  getXattrSync: fs.getXattrSync,
+  setXattr: fs.setXattr,
+  setXattrSync: fs.setXattrSync,
+  listXattr: fs.listXattr,
+  listXattrSync: fs.listXattrSync,
+  removeXattr: fs.removeXattr,
+  removeXattrSync: fs.removeXattrSync,
   upgradeHttp: http.upgradeHttp,
   serve: http.serve,
+  ServerSentEventStream: http.ServerSentEventStream,
   openKv: kv.openKv,
   Kv: kv.Kv,
   KvU64: kv.KvU64,
   KvListIterator: kv.KvListIterator,
+  cron: cron.cron,
+  openBlob: blob.openBlob,
 };
 
 export { denoNs, denoNsUnstable };
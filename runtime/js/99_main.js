@@ -293,6 +293,12 @@ core.registerErrorBuilder(
     return new DOMException(msg, "DataError");
   },
 );
+core.registerErrorBuilder(
+  "DOMExceptionNotReadableError",
+  function DOMExceptionNotReadableError(msg) {
+    return new DOMException(msg, "NotReadableError");
+  },
+);
 
 function runtimeStart(
   denoVersion,
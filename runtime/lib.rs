@@ -31,6 +31,7 @@ pub mod ops;
 pub mod permissions;
 pub mod tokio_util;
 pub mod web_worker;
+pub mod web_worker_pool;
 pub mod worker;
 
 mod worker_bootstrap;
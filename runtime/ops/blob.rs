@@ -0,0 +1,65 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::permissions::PermissionsContainer;
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::OpState;
+use deno_web::BlobStore;
+use deno_web::FileBlobPart;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+use uuid::Uuid;
+
+deno_core::extension!(deno_blob, ops = [op_blob_open_file]);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenFileBlob {
+  uuid: Uuid,
+  size: usize,
+  last_modified: Option<u64>,
+}
+
+/// Creates a blob part that reads the file at `path` when the blob is read,
+/// instead of loading it into memory.
+#[op]
+pub async fn op_blob_open_file(
+  state: Rc<RefCell<OpState>>,
+  path: String,
+) -> Result<OpenFileBlob, AnyError> {
+  let path = PathBuf::from(path);
+  {
+    let mut state = state.borrow_mut();
+    super::check_unstable(&state, "Deno.openBlob");
+    state
+      .borrow_mut::<PermissionsContainer>()
+      .check_read(&path, "Deno.openBlob()")?;
+  }
+
+  let metadata = tokio::fs::metadata(&path).await?;
+  if !metadata.is_file() {
+    return Err(type_error(format!(
+      "Cannot open '{}' as a blob, it is not a file",
+      path.display()
+    )));
+  }
+  let size = usize::try_from(metadata.len())?;
+  let modified = metadata.modified().ok();
+  let last_modified = modified
+    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+    .map(|duration| duration.as_millis() as u64);
+
+  let part = FileBlobPart::new(path, size, modified);
+  let state = state.borrow();
+  let uuid = state.borrow::<BlobStore>().insert_part(Arc::new(part));
+  Ok(OpenFileBlob {
+    uuid,
+    size,
+    last_modified,
+  })
+}
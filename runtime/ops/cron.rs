@@ -0,0 +1,494 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use deno_core::error::type_error;
+use deno_core::error::AnyError;
+use deno_core::op;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
+use deno_core::OpState;
+use deno_core::RcRef;
+use deno_core::Resource;
+use deno_core::ResourceId;
+use rusqlite::params;
+use rusqlite::Connection;
+use rusqlite::OptionalExtension;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+deno_core::extension!(
+  deno_cron,
+  ops = [op_cron_create, op_cron_next],
+  options = {
+    storage_dir: Option<PathBuf>,
+  },
+  state = |state, options| {
+    state.put(CronNames::default());
+    if let Some(storage_dir) = options.storage_dir {
+      state.put(CronStorageDir(storage_dir));
+    }
+  },
+);
+
+const MAX_NAME_LENGTH: usize = 64;
+
+/// How long a cron sleeps at most before looking at the clock again, so that
+/// changes to the system time and suspended machines are noticed.
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+/// The names of the crons registered in a runtime, which must be unique.
+#[derive(Clone, Default)]
+struct CronNames(Rc<RefCell<HashSet<String>>>);
+
+/// The directory of the database the crons of the runtime are registered in.
+struct CronStorageDir(PathBuf);
+
+/// The crons registered by name with their schedule and the minute they last
+/// fired at. The registrations outlive the program, so a restarted program
+/// doesn't fire its crons again for a minute they already fired at.
+#[derive(Clone)]
+struct CronStore(Rc<Connection>);
+
+impl CronStore {
+  /// Returns the store of the runtime, opening it on first use, or `None` if
+  /// the runtime has no storage directory.
+  fn get(state: &mut OpState) -> Result<Option<Self>, AnyError> {
+    if let Some(store) = state.try_borrow::<CronStore>() {
+      return Ok(Some(store.clone()));
+    }
+    let Some(storage_dir) = state.try_borrow::<CronStorageDir>() else {
+      return Ok(None);
+    };
+    std::fs::create_dir_all(&storage_dir.0)?;
+    let store = Self::open(&storage_dir.0.join("cron"))?;
+    state.put(store.clone());
+    Ok(Some(store))
+  }
+
+  fn open(path: &Path) -> Result<Self, AnyError> {
+    let conn = Connection::open(path)?;
+    conn.execute_batch(
+      "
+      PRAGMA journal_mode=WAL;
+      PRAGMA synchronous=NORMAL;
+      CREATE TABLE IF NOT EXISTS crons (
+        name TEXT PRIMARY KEY,
+        schedule TEXT NOT NULL,
+        last_run INTEGER NOT NULL
+      );
+      ",
+    )?;
+    Ok(Self(Rc::new(conn)))
+  }
+
+  /// Registers the cron `name` and returns the minute it last fired at, or 0
+  /// if it was never registered with this schedule before.
+  fn register(&self, name: &str, schedule: &str) -> Result<u64, AnyError> {
+    let registered = self
+      .0
+      .query_row(
+        "SELECT schedule, last_run FROM crons WHERE name = ?",
+        params![name],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+      )
+      .optional()?;
+    let last_run = registered
+      .filter(|(registered_schedule, _)| registered_schedule == schedule)
+      .map(|(_, last_run)| last_run as u64)
+      .unwrap_or(0);
+    self.0.execute(
+      "INSERT OR REPLACE INTO crons (name, schedule, last_run) VALUES (?, ?, ?)",
+      params![name, schedule, last_run as i64],
+    )?;
+    Ok(last_run)
+  }
+
+  fn set_last_run(&self, name: &str, last_run: u64) -> Result<(), AnyError> {
+    self.0.execute(
+      "UPDATE crons SET last_run = ? WHERE name = ?",
+      params![last_run as i64, name],
+    )?;
+    Ok(())
+  }
+}
+
+struct CronResource {
+  name: String,
+  schedule: CronSchedule,
+  names: CronNames,
+  store: Option<CronStore>,
+  /// The minute, in seconds since the epoch, the cron last fired at.
+  last_run: Cell<u64>,
+  cancel: CancelHandle,
+}
+
+impl CronResource {
+  fn fired_at(&self, minute: u64) {
+    self.last_run.set(minute);
+    if let Some(store) = &self.store {
+      // The cron still fires if its registration can't be updated.
+      if let Err(err) = store.set_last_run(&self.name, minute) {
+        log::warn!(
+          "Failed to update the registration of cron '{}': {err}",
+          self.name
+        );
+      }
+    }
+  }
+}
+
+impl Resource for CronResource {
+  fn name(&self) -> Cow<str> {
+    "cron".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel.cancel();
+  }
+}
+
+impl Drop for CronResource {
+  fn drop(&mut self) {
+    self.names.0.borrow_mut().remove(&self.name);
+  }
+}
+
+fn now_secs() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_secs())
+    .unwrap_or_default()
+}
+
+fn validate_name(name: &str) -> Result<(), AnyError> {
+  if name.len() > MAX_NAME_LENGTH {
+    return Err(type_error(format!(
+      "Cron name cannot exceed {MAX_NAME_LENGTH} characters"
+    )));
+  }
+  if name.is_empty()
+    || !name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+  {
+    return Err(type_error(
+      "Cron name must only contain alphanumeric characters, whitespace, hyphens, and underscores",
+    ));
+  }
+  Ok(())
+}
+
+#[op]
+fn op_cron_create(
+  state: &mut OpState,
+  name: String,
+  schedule: String,
+) -> Result<ResourceId, AnyError> {
+  super::check_unstable(state, "Deno.cron");
+  validate_name(&name)?;
+  let parsed_schedule = CronSchedule::parse(&schedule)?;
+  if parsed_schedule.next_after(now_secs()).is_none() {
+    return Err(type_error("Cron schedule never matches"));
+  }
+  let names = state.borrow::<CronNames>().clone();
+  if names.0.borrow().contains(&name) {
+    return Err(type_error(format!(
+      "Cron with name '{name}' already exists"
+    )));
+  }
+  let store = CronStore::get(state)?;
+  let last_run = match &store {
+    Some(store) => store.register(&name, schedule.trim())?,
+    None => 0,
+  };
+  names.0.borrow_mut().insert(name.clone());
+  Ok(state.resource_table.add(CronResource {
+    name,
+    schedule: parsed_schedule,
+    names,
+    store,
+    last_run: Cell::new(last_run),
+    cancel: Default::default(),
+  }))
+}
+
+/// Resolves with `true` once the cron's schedule is due, or with `false` if
+/// the cron was closed in the meantime.
+#[op]
+async fn op_cron_next(
+  state: Rc<RefCell<OpState>>,
+  rid: ResourceId,
+) -> Result<bool, AnyError> {
+  let Ok(resource) = state.borrow().resource_table.get::<CronResource>(rid)
+  else {
+    return Ok(false);
+  };
+  let cancel = RcRef::map(&resource, |r| &r.cancel);
+  let wait = async {
+    loop {
+      let now = now_secs();
+      let Some(next) = resource
+        .schedule
+        .next_after(now.max(resource.last_run.get()))
+      else {
+        return false;
+      };
+      let mut remaining = next - now;
+      if remaining == 0 {
+        resource.fired_at(next);
+        return true;
+      }
+      remaining = remaining.min(MAX_SLEEP.as_secs());
+      tokio::time::sleep(Duration::from_secs(remaining)).await;
+      let now = now_secs();
+      if now >= next {
+        resource.fired_at(next);
+        return true;
+      }
+    }
+  };
+  Ok(wait.or_cancel(cancel).await.unwrap_or(false))
+}
+
+/// A parsed cron expression of five fields: minute, hour, day of the month,
+/// month and day of the week. Fields are evaluated in UTC.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CronSchedule {
+  minutes: u64,
+  hours: u64,
+  days_of_month: u64,
+  months: u64,
+  days_of_week: u64,
+  /// When both day fields are restricted, a day matches if either does.
+  days_or: bool,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+  "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV",
+  "DEC",
+];
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+fn invalid_schedule(schedule: &str) -> AnyError {
+  type_error(format!("Invalid cron schedule: '{schedule}'"))
+}
+
+/// Parses a single value of a field, which is a number or, for months and
+/// days of the week, a name.
+fn parse_value(value: &str, min: u32, max: u32, names: &[&str]) -> Option<u32> {
+  if let Ok(n) = value.parse::<u32>() {
+    return (min..=max).contains(&n).then_some(n);
+  }
+  names
+    .iter()
+    .position(|name| name.eq_ignore_ascii_case(value))
+    .map(|i| i as u32 + min)
+}
+
+/// Parses a field into a bit set of the values it matches. Returns the set
+/// and whether the field was `*`, which matters for the day fields.
+fn parse_field(
+  field: &str,
+  min: u32,
+  max: u32,
+  names: &[&str],
+) -> Option<(u64, bool)> {
+  let mut bits = 0u64;
+  for part in field.split(',') {
+    let (range, step) = match part.split_once('/') {
+      Some((range, step)) => (range, step.parse::<u32>().ok()?),
+      None => (part, 1),
+    };
+    if step == 0 {
+      return None;
+    }
+    let (start, end) = if range == "*" {
+      (min, max)
+    } else if let Some((start, end)) = range.split_once('-') {
+      (
+        parse_value(start, min, max, names)?,
+        parse_value(end, min, max, names)?,
+      )
+    } else {
+      let start = parse_value(range, min, max, names)?;
+      // `5/15` means every 15 starting at 5.
+      (start, if part.contains('/') { max } else { start })
+    };
+    if start > end {
+      return None;
+    }
+    for value in (start..=end).step_by(step as usize) {
+      bits |= 1 << value;
+    }
+  }
+  Some((bits, field == "*"))
+}
+
+impl CronSchedule {
+  pub fn parse(schedule: &str) -> Result<Self, AnyError> {
+    let expanded = match schedule.trim() {
+      "@yearly" | "@annually" => "0 0 1 1 *",
+      "@monthly" => "0 0 1 * *",
+      "@weekly" => "0 0 * * 0",
+      "@daily" | "@midnight" => "0 0 * * *",
+      "@hourly" => "0 * * * *",
+      schedule => schedule,
+    };
+    let fields = expanded.split_whitespace().collect::<Vec<_>>();
+    let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+      return Err(invalid_schedule(schedule));
+    };
+    let parse = || {
+      let (minutes, _) = parse_field(minute, 0, 59, &[])?;
+      let (hours, _) = parse_field(hour, 0, 23, &[])?;
+      let (days_of_month, dom_any) = parse_field(day_of_month, 1, 31, &[])?;
+      let (months, _) = parse_field(month, 1, 12, &MONTH_NAMES)?;
+      let (mut days_of_week, dow_any) =
+        parse_field(day_of_week, 0, 7, &DAY_NAMES)?;
+      // Both 0 and 7 are Sunday.
+      if days_of_week & (1 << 7) != 0 {
+        days_of_week = (days_of_week | 1) & !(1 << 7);
+      }
+      Some(Self {
+        minutes,
+        hours,
+        days_of_month,
+        months,
+        days_of_week,
+        days_or: !dom_any && !dow_any,
+      })
+    };
+    parse().ok_or_else(|| invalid_schedule(schedule))
+  }
+
+  fn matches_day(&self, days: i64) -> bool {
+    let (_, month, day) = civil_from_days(days);
+    if self.months & (1 << month) == 0 {
+      return false;
+    }
+    // 1970-01-01 was a Thursday.
+    let weekday = (days + 4).rem_euclid(7);
+    let dom = self.days_of_month & (1 << day) != 0;
+    let dow = self.days_of_week & (1 << weekday) != 0;
+    if self.days_or {
+      dom || dow
+    } else {
+      dom && dow
+    }
+  }
+
+  /// Returns the first minute matching the schedule after `after`, both in
+  /// seconds since the epoch.
+  pub fn next_after(&self, after: u64) -> Option<u64> {
+    let start = after / 60 + 1;
+    let mut days = (start / (24 * 60)) as i64;
+    let mut minute_of_day = start % (24 * 60);
+    // Every schedule that can match does within eight years, the time
+    // between two leap days at worst.
+    for _ in 0..366 * 8 {
+      if self.matches_day(days) {
+        for m in minute_of_day..24 * 60 {
+          if self.hours & (1 << (m / 60)) != 0
+            && self.minutes & (1 << (m % 60)) != 0
+          {
+            return Some((days as u64 * 24 * 60 + m) * 60);
+          }
+        }
+      }
+      days += 1;
+      minute_of_day = 0;
+    }
+    None
+  }
+}
+
+/// Converts days since the epoch to a (year, month, day) date.
+// From http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+  let z = days + 719468;
+  let era = z.div_euclid(146097);
+  let doe = z.rem_euclid(146097);
+  let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+  let year = yoe + era * 400 + i64::from(month <= 2);
+  (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // 2023-06-15T10:30:00Z, a Thursday.
+  const NOW: u64 = 1686825000;
+
+  fn next(schedule: &str) -> Option<u64> {
+    CronSchedule::parse(schedule).unwrap().next_after(NOW)
+  }
+
+  #[test]
+  fn test_cron_store() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cron");
+    let store = CronStore::open(&path).unwrap();
+    assert_eq!(store.register("cleanup", "0 * * * *").unwrap(), 0);
+    store.set_last_run("cleanup", NOW).unwrap();
+    drop(store);
+
+    let store = CronStore::open(&path).unwrap();
+    assert_eq!(store.register("cleanup", "0 * * * *").unwrap(), NOW);
+    assert_eq!(store.register("report", "0 * * * *").unwrap(), 0);
+    // A changed schedule starts over.
+    assert_eq!(store.register("cleanup", "@daily").unwrap(), 0);
+    assert_eq!(store.register("cleanup", "@daily").unwrap(), 0);
+  }
+
+  #[test]
+  fn test_parse_schedule() {
+    assert!(CronSchedule::parse("* * * * *").is_ok());
+    assert!(CronSchedule::parse("*/5 0-6,12 1 JAN-jun mon-FRI").is_ok());
+    assert!(CronSchedule::parse("@daily").is_ok());
+    assert_eq!(
+      CronSchedule::parse("@hourly").unwrap(),
+      CronSchedule::parse("0 * * * *").unwrap()
+    );
+    assert_eq!(
+      CronSchedule::parse("0 0 * * 7").unwrap(),
+      CronSchedule::parse("0 0 * * SUN").unwrap()
+    );
+    assert!(CronSchedule::parse("* * * *").is_err());
+    assert!(CronSchedule::parse("60 * * * *").is_err());
+    assert!(CronSchedule::parse("* 24 * * *").is_err());
+    assert!(CronSchedule::parse("* * 0 * *").is_err());
+    assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    assert!(CronSchedule::parse("5-1 * * * *").is_err());
+    assert!(CronSchedule::parse("* * * FOO *").is_err());
+  }
+
+  #[test]
+  fn test_next_after() {
+    assert_eq!(next("* * * * *"), Some(NOW + 60));
+    assert_eq!(next("*/15 * * * *"), Some(NOW + 15 * 60));
+    assert_eq!(next("0 * * * *"), Some(NOW + 30 * 60));
+    // 2023-06-16T00:00:00Z
+    assert_eq!(next("@daily"), Some(1686873600));
+    // Friday, 2023-06-16T09:00:00Z
+    assert_eq!(next("0 9 * * FRI"), Some(1686906000));
+    // 2023-07-01T00:00:00Z
+    assert_eq!(next("@monthly"), Some(1688169600));
+    // The 20th or any Monday: Monday, 2023-06-19T00:00:00Z
+    assert_eq!(next("0 0 20 * 1"), Some(1687132800));
+    // 2024-02-29T00:00:00Z
+    assert_eq!(next("0 0 29 2 *"), Some(1709164800));
+    assert_eq!(next("0 0 30 2 *"), None);
+  }
+}
@@ -1,6 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
 use crate::permissions::PermissionsContainer;
+use deno_core::error::type_error;
 use deno_core::error::AnyError;
 use deno_core::parking_lot::Mutex;
 use deno_core::AsyncRefCell;
@@ -13,7 +14,11 @@ use deno_core::ResourceId;
 
 use deno_core::op;
 
+use glob::MatchOptions;
+use glob::Pattern;
 use notify::event::Event as NotifyEvent;
+use notify::event::ModifyKind;
+use notify::event::RenameMode;
 use notify::Error as NotifyError;
 use notify::EventKind;
 use notify::RecommendedWatcher;
@@ -23,10 +28,14 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::convert::From;
+use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
 use tokio::sync::mpsc;
+use tokio::time::Instant;
 
 deno_core::extension!(
   deno_fs_events,
@@ -36,7 +45,7 @@ deno_core::extension!(
 struct FsEventsResource {
   #[allow(unused)]
   watcher: RecommendedWatcher,
-  receiver: AsyncRefCell<mpsc::Receiver<Result<FsEvent, AnyError>>>,
+  receiver: AsyncRefCell<FsEventsReceiver>,
   cancel: CancelHandle,
 }
 
@@ -50,6 +59,38 @@ impl Resource for FsEventsResource {
   }
 }
 
+struct FsEventsReceiver {
+  receiver: mpsc::Receiver<Result<NotifyEvent, AnyError>>,
+  options: EventOptions,
+  /// Events of the last batch that have not been polled yet.
+  pending: VecDeque<FsEvent>,
+}
+
+impl FsEventsReceiver {
+  /// Receives the events that are ready, or those arriving within the
+  /// debounce interval of the first one, and turns them into `FsEvent`s.
+  async fn recv_batch(&mut self) -> Result<bool, AnyError> {
+    let Some(first) = self.receiver.recv().await else {
+      return Ok(false);
+    };
+    let mut batch = vec![first?];
+    if let Some(debounce) = self.options.debounce {
+      let deadline = Instant::now() + debounce;
+      while let Ok(Some(event)) =
+        tokio::time::timeout_at(deadline, self.receiver.recv()).await
+      {
+        batch.push(event?);
+      }
+    }
+    while let Ok(event) = self.receiver.try_recv() {
+      batch.push(event?);
+    }
+    let events = self.options.process(batch);
+    self.pending.extend(events);
+    Ok(true)
+  }
+}
+
 /// Represents a file system event.
 ///
 /// We do not use the event directly from the notify crate. We flatten
@@ -58,7 +99,7 @@ impl Resource for FsEventsResource {
 ///
 /// Feel free to expand this struct as long as you can add tests to demonstrate
 /// the complexity.
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, PartialEq)]
 struct FsEvent {
   kind: &'static str,
   paths: Vec<PathBuf>,
@@ -87,9 +128,105 @@ impl From<NotifyEvent> for FsEvent {
 }
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct OpenArgs {
   recursive: bool,
   paths: Vec<String>,
+  include: Vec<String>,
+  exclude: Vec<String>,
+  debounce: Option<u64>,
+  coalesce_renames: bool,
+}
+
+/// How the raw events of a watcher are filtered and combined.
+struct EventOptions {
+  roots: Vec<PathBuf>,
+  include: Vec<Pattern>,
+  exclude: Vec<Pattern>,
+  debounce: Option<Duration>,
+  coalesce_renames: bool,
+}
+
+impl EventOptions {
+  /// Patterns are matched against paths relative to the watched path they
+  /// are in, and against the full path.
+  fn matches(&self, patterns: &[Pattern], path: &Path) -> bool {
+    let options = MatchOptions {
+      require_literal_separator: true,
+      ..Default::default()
+    };
+    let relative_paths = self
+      .roots
+      .iter()
+      .filter_map(|root| path.strip_prefix(root).ok());
+    std::iter::once(path).chain(relative_paths).any(|path| {
+      patterns
+        .iter()
+        .any(|pattern| pattern.matches_path_with(path, options))
+    })
+  }
+
+  fn is_path_included(&self, path: &Path) -> bool {
+    (self.include.is_empty() || self.matches(&self.include, path))
+      && !self.matches(&self.exclude, path)
+  }
+
+  fn process(&self, batch: Vec<NotifyEvent>) -> Vec<FsEvent> {
+    let mut events: Vec<FsEvent> = Vec::with_capacity(batch.len());
+    // Index of a rename's source in `events`, waiting for its destination.
+    let mut rename_from: Option<(Option<usize>, usize)> = None;
+    let mut renamed_trackers = Vec::new();
+    for event in batch {
+      let tracker = event.attrs.tracker();
+      let rename_mode = match event.kind {
+        EventKind::Modify(ModifyKind::Name(mode)) if self.coalesce_renames => {
+          Some(mode)
+        }
+        _ => None,
+      };
+      let mut event = FsEvent::from(event);
+      let had_paths = !event.paths.is_empty();
+      event.paths.retain(|path| self.is_path_included(path));
+      if had_paths && event.paths.is_empty() {
+        continue;
+      }
+
+      match rename_mode {
+        Some(RenameMode::From) => {
+          rename_from = Some((tracker, events.len()));
+        }
+        Some(RenameMode::To) => {
+          // Without trackers, as on Windows, the destination directly follows
+          // the source.
+          let source = rename_from.take().filter(|(from_tracker, index)| {
+            *from_tracker == tracker
+              && (tracker.is_some() || *index + 1 == events.len())
+          });
+          if let Some((_, index)) = source {
+            let from = &mut events[index];
+            from.kind = "rename";
+            from.paths.append(&mut event.paths);
+            renamed_trackers.extend(tracker);
+            continue;
+          }
+        }
+        Some(RenameMode::Both) => {
+          // inotify reports the source and destination separately as well.
+          if tracker.map_or(false, |t| renamed_trackers.contains(&t)) {
+            continue;
+          }
+          event.kind = "rename";
+        }
+        _ => {}
+      }
+
+      if self.debounce.is_some() && events.contains(&event) {
+        continue;
+      }
+      events.push(event);
+    }
+    events
+  }
 }
 
 #[op]
@@ -97,11 +234,29 @@ fn op_fs_events_open(
   state: &mut OpState,
   args: OpenArgs,
 ) -> Result<ResourceId, AnyError> {
-  let (sender, receiver) = mpsc::channel::<Result<FsEvent, AnyError>>(16);
+  let parse_patterns = |patterns: Vec<String>| {
+    patterns
+      .iter()
+      .map(|pattern| {
+        Pattern::new(pattern).map_err(|err| {
+          type_error(format!("Invalid glob pattern \"{pattern}\": {err}"))
+        })
+      })
+      .collect::<Result<Vec<_>, _>>()
+  };
+  let options = EventOptions {
+    roots: args.paths.iter().map(PathBuf::from).collect(),
+    include: parse_patterns(args.include)?,
+    exclude: parse_patterns(args.exclude)?,
+    debounce: args.debounce.map(Duration::from_millis),
+    coalesce_renames: args.coalesce_renames,
+  };
+
+  let (sender, receiver) = mpsc::channel::<Result<NotifyEvent, AnyError>>(16);
   let sender = Mutex::new(sender);
   let mut watcher: RecommendedWatcher = Watcher::new(
     move |res: Result<NotifyEvent, NotifyError>| {
-      let res2 = res.map(FsEvent::from).map_err(AnyError::from);
+      let res2 = res.map_err(AnyError::from);
       let sender = sender.lock();
       // Ignore result, if send failed it means that watcher was already closed,
       // but not all messages have been flushed.
@@ -123,7 +278,11 @@ fn op_fs_events_open(
   }
   let resource = FsEventsResource {
     watcher,
-    receiver: AsyncRefCell::new(receiver),
+    receiver: AsyncRefCell::new(FsEventsReceiver {
+      receiver,
+      options,
+      pending: VecDeque::new(),
+    }),
     cancel: Default::default(),
   };
   let rid = state.resource_table.add(resource);
@@ -138,10 +297,15 @@ async fn op_fs_events_poll(
   let resource = state.borrow().resource_table.get::<FsEventsResource>(rid)?;
   let mut receiver = RcRef::map(&resource, |r| &r.receiver).borrow_mut().await;
   let cancel = RcRef::map(resource, |r| &r.cancel);
-  let maybe_result = receiver.recv().or_cancel(cancel).await?;
-  match maybe_result {
-    Some(Ok(value)) => Ok(Some(value)),
-    Some(Err(err)) => Err(err),
-    None => Ok(None),
-  }
+  let poll = async {
+    loop {
+      if let Some(event) = receiver.pending.pop_front() {
+        return Ok(Some(event));
+      }
+      if !receiver.recv_batch().await? {
+        return Ok(None);
+      }
+    }
+  };
+  poll.or_cancel(cancel).await?
 }
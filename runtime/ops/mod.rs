@@ -1,5 +1,7 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+pub mod blob;
+pub mod cron;
 pub mod fs_events;
 pub mod http;
 pub mod os;
@@ -295,7 +295,11 @@ struct MemoryUsage {
 }
 
 #[op(v8)]
-fn op_runtime_memory_usage(scope: &mut v8::HandleScope) -> MemoryUsage {
+fn op_runtime_memory_usage(
+  scope: &mut v8::HandleScope,
+  state: &mut OpState,
+) -> MemoryUsage {
+  state.resource_table.report_external_memory(scope);
   let mut s = v8::HeapStatistics::default();
   scope.get_heap_statistics(&mut s);
   MemoryUsage {
@@ -25,6 +25,7 @@ pub struct PermissionArgs {
   name: String,
   path: Option<String>,
   host: Option<String>,
+  url: Option<String>,
   variable: Option<String>,
   kind: Option<String>,
   command: Option<String>,
@@ -35,18 +36,21 @@ pub fn op_query_permission(
   state: &mut OpState,
   args: PermissionArgs,
 ) -> Result<String, AnyError> {
-  let permissions = state.borrow::<PermissionsContainer>().0.lock();
+  let permissions = state.borrow::<PermissionsContainer>().lock();
   let path = args.path.as_deref();
   let perm = match args.name.as_ref() {
     "read" => permissions.read.query(path.map(Path::new)),
     "write" => permissions.write.query(path.map(Path::new)),
-    "net" => permissions.net.query(
-      match args.host.as_deref() {
-        None => None,
-        Some(h) => Some(parse_host(h)?),
-      }
-      .as_ref(),
-    ),
+    "net" => match args.url.as_deref() {
+      Some(url) => permissions.net.query_url(&parse_url(url)?),
+      None => permissions.net.query(
+        match args.host.as_deref() {
+          None => None,
+          Some(h) => Some(parse_host(h)?),
+        }
+        .as_ref(),
+      ),
+    },
     "env" => permissions.env.query(args.variable.as_deref()),
     "sys" => permissions
       .sys
@@ -69,18 +73,21 @@ pub fn op_revoke_permission(
   state: &mut OpState,
   args: PermissionArgs,
 ) -> Result<String, AnyError> {
-  let mut permissions = state.borrow_mut::<PermissionsContainer>().0.lock();
+  let mut permissions = state.borrow_mut::<PermissionsContainer>().lock();
   let path = args.path.as_deref();
   let perm = match args.name.as_ref() {
     "read" => permissions.read.revoke(path.map(Path::new)),
     "write" => permissions.write.revoke(path.map(Path::new)),
-    "net" => permissions.net.revoke(
-      match args.host.as_deref() {
-        None => None,
-        Some(h) => Some(parse_host(h)?),
-      }
-      .as_ref(),
-    ),
+    "net" => match args.url.as_deref() {
+      Some(url) => permissions.net.revoke_url(&parse_url(url)?),
+      None => permissions.net.revoke(
+        match args.host.as_deref() {
+          None => None,
+          Some(h) => Some(parse_host(h)?),
+        }
+        .as_ref(),
+      ),
+    },
     "env" => permissions.env.revoke(args.variable.as_deref()),
     "sys" => permissions
       .sys
@@ -103,18 +110,21 @@ pub fn op_request_permission(
   state: &mut OpState,
   args: PermissionArgs,
 ) -> Result<String, AnyError> {
-  let mut permissions = state.borrow_mut::<PermissionsContainer>().0.lock();
+  let mut permissions = state.borrow_mut::<PermissionsContainer>().lock();
   let path = args.path.as_deref();
   let perm = match args.name.as_ref() {
     "read" => permissions.read.request(path.map(Path::new)),
     "write" => permissions.write.request(path.map(Path::new)),
-    "net" => permissions.net.request(
-      match args.host.as_deref() {
-        None => None,
-        Some(h) => Some(parse_host(h)?),
-      }
-      .as_ref(),
-    ),
+    "net" => match args.url.as_deref() {
+      Some(url) => permissions.net.request_url(&parse_url(url)?),
+      None => permissions.net.request(
+        match args.host.as_deref() {
+          None => None,
+          Some(h) => Some(parse_host(h)?),
+        }
+        .as_ref(),
+      ),
+    },
     "env" => permissions.env.request(args.variable.as_deref()),
     "sys" => permissions
       .sys
@@ -133,11 +143,23 @@ pub fn op_request_permission(
 }
 
 fn parse_host(host_str: &str) -> Result<(String, Option<u16>), AnyError> {
+  let (wildcard, host_str) = match host_str.strip_prefix("*.") {
+    Some(rest) => ("*.", rest),
+    None => ("", host_str),
+  };
   let url = url::Url::parse(&format!("http://{host_str}/"))
     .map_err(|_| uri_error("Invalid host"))?;
   if url.path() != "/" {
     return Err(uri_error("Invalid host"));
   }
   let hostname = url.host_str().unwrap();
-  Ok((hostname.to_string(), url.port()))
+  Ok((format!("{wildcard}{hostname}"), url.port()))
+}
+
+fn parse_url(url_str: &str) -> Result<url::Url, AnyError> {
+  let url = url::Url::parse(url_str).map_err(|_| uri_error("Invalid URL"))?;
+  if url.host_str().is_none() {
+    return Err(uri_error("Missing host"));
+  }
+  Ok(url)
 }
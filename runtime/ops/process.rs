@@ -108,6 +108,7 @@ deno_core::extension!(
     op_spawn_wait,
     op_spawn_sync,
     op_spawn_kill,
+    op_spawn_pty_resize,
     deprecated::op_run,
     deprecated::op_run_status,
     deprecated::op_kill,
@@ -138,6 +139,10 @@ pub struct SpawnArgs {
   uid: Option<u32>,
   #[cfg(windows)]
   windows_raw_arguments: bool,
+  detached: bool,
+  new_process_group: bool,
+  new_session: bool,
+  pty: Option<pty::PtySize>,
 
   #[serde(flatten)]
   stdio: ChildStdio,
@@ -252,6 +257,35 @@ fn create_command(
     });
   }
 
+  // A pty needs its own session to become the controlling terminal of.
+  #[cfg(unix)]
+  if args.detached || args.new_session || args.pty.is_some() {
+    // SAFETY: setsid() is async-signal-safe. A new session also starts a new
+    // process group, and setsid() fails for process group leaders, so
+    // `process_group` must not be set as well.
+    unsafe {
+      command.pre_exec(|| {
+        nix::unistd::setsid()?;
+        Ok(())
+      });
+    }
+  } else if args.new_process_group {
+    command.process_group(0);
+  }
+  #[cfg(windows)]
+  {
+    use winapi::um::winbase::CREATE_NEW_PROCESS_GROUP;
+    use winapi::um::winbase::DETACHED_PROCESS;
+
+    let mut flags = 0;
+    if args.detached {
+      flags |= DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP;
+    } else if args.new_session || args.new_process_group {
+      flags |= CREATE_NEW_PROCESS_GROUP;
+    }
+    command.creation_flags(flags);
+  }
+
   command.stdin(args.stdio.stdin.as_stdio());
   command.stdout(match args.stdio.stdout {
     Stdio::Inherit => StdioOrRid::Rid(1).as_stdio(state)?,
@@ -273,19 +307,24 @@ struct Child {
   stdin_rid: Option<ResourceId>,
   stdout_rid: Option<ResourceId>,
   stderr_rid: Option<ResourceId>,
+  pty_rid: Option<ResourceId>,
 }
 
 fn spawn_child(
   state: &mut OpState,
   command: std::process::Command,
+  detached: bool,
+  pty: Option<pty::PtyResource>,
 ) -> Result<Child, AnyError> {
   let mut command = tokio::process::Command::from(command);
-  // TODO(@crowlkats): allow detaching processes.
-  //  currently deno will orphan a process when exiting with an error or Deno.exit()
-  // We want to kill child when it's closed
-  command.kill_on_drop(true);
+  // Deno orphans a process when exiting with an error or Deno.exit(). Unless
+  // the child is detached, we want to kill it when it's closed.
+  command.kill_on_drop(!detached);
 
   let mut child = command.spawn()?;
+  // Drop the command's end of the pty, so reading from it ends once the
+  // child exits.
+  drop(command);
   let pid = child.id().expect("Process ID should be set.");
 
   let stdin_rid = child
@@ -303,6 +342,8 @@ fn spawn_child(
     .take()
     .map(|stderr| state.resource_table.add(ChildStderrResource::from(stderr)));
 
+  let pty_rid = pty.map(|pty| state.resource_table.add(pty));
+
   let child_rid = state
     .resource_table
     .add(ChildResource(RefCell::new(child), pid));
@@ -313,6 +354,7 @@ fn spawn_child(
     stdin_rid,
     stdout_rid,
     stderr_rid,
+    pty_rid,
   })
 }
 
@@ -322,8 +364,13 @@ fn op_spawn_child(
   args: SpawnArgs,
   api_name: String,
 ) -> Result<Child, AnyError> {
-  let command = create_command(state, args, &api_name)?;
-  spawn_child(state, command)
+  let detached = args.detached;
+  let pty_size = args.pty;
+  let mut command = create_command(state, args, &api_name)?;
+  let pty = pty_size
+    .map(|size| pty::PtyResource::attach(&mut command, size))
+    .transpose()?;
+  spawn_child(state, command, detached, pty)
 }
 
 #[op]
@@ -350,6 +397,11 @@ fn op_spawn_sync(
   state: &mut OpState,
   args: SpawnArgs,
 ) -> Result<SpawnOutput, AnyError> {
+  if args.pty.is_some() {
+    return Err(type_error(
+      "A pty is not supported for this function, use 'Deno.Command().spawn()' instead",
+    ));
+  }
   let stdout = matches!(args.stdio.stdout, Stdio::Piped);
   let stderr = matches!(args.stdio.stderr, Stdio::Piped);
   let output =
@@ -383,6 +435,180 @@ fn op_spawn_kill(
   Err(type_error("Child process has already terminated."))
 }
 
+#[op]
+fn op_spawn_pty_resize(
+  state: &mut OpState,
+  rid: ResourceId,
+  size: pty::PtySize,
+) -> Result<(), AnyError> {
+  let resource = state.resource_table.get::<pty::PtyResource>(rid)?;
+  resource.resize(size)
+}
+
+mod pty {
+  use super::*;
+  #[cfg(unix)]
+  use deno_core::AsyncResult;
+  #[cfg(unix)]
+  use deno_core::CancelHandle;
+  #[cfg(unix)]
+  use deno_core::CancelTryFuture;
+
+  /// Size of a pseudo-terminal, in characters.
+  #[derive(Clone, Copy, Deserialize)]
+  #[cfg_attr(windows, allow(dead_code))]
+  pub struct PtySize {
+    columns: u16,
+    rows: u16,
+  }
+
+  /// The parent's end of a pseudo-terminal that is the stdin, stdout, stderr
+  /// and controlling terminal of a child process.
+  #[cfg(unix)]
+  pub struct PtyResource {
+    // Separate handles, so a pending read doesn't hold up writes.
+    reader: AsyncRefCell<tokio::fs::File>,
+    writer: AsyncRefCell<tokio::fs::File>,
+    master: std::fs::File,
+    cancel: CancelHandle,
+  }
+
+  #[cfg(unix)]
+  impl PtyResource {
+    /// Opens a pty of the given size and makes it the stdio of `command`.
+    /// The child must start a new session, see `create_command`.
+    pub fn attach(
+      command: &mut std::process::Command,
+      size: PtySize,
+    ) -> Result<Self, AnyError> {
+      use std::os::unix::io::FromRawFd;
+
+      let pty = nix::pty::openpty(&size.to_winsize(), None)?;
+      // SAFETY: openpty() returned new file descriptors that nothing else
+      // owns.
+      let (master, slave) = unsafe {
+        (
+          std::fs::File::from_raw_fd(pty.master),
+          std::fs::File::from_raw_fd(pty.slave),
+        )
+      };
+      command.stdin(slave.try_clone()?);
+      command.stdout(slave.try_clone()?);
+      command.stderr(slave);
+      // SAFETY: ioctl() is async-signal-safe. Runs after setsid(), so the pty
+      // on stdin becomes the controlling terminal of the new session.
+      unsafe {
+        command.pre_exec(|| {
+          if libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+            return Err(std::io::Error::last_os_error());
+          }
+          Ok(())
+        });
+      }
+
+      Ok(Self {
+        reader: AsyncRefCell::new(tokio::fs::File::from_std(
+          master.try_clone()?,
+        )),
+        writer: AsyncRefCell::new(tokio::fs::File::from_std(
+          master.try_clone()?,
+        )),
+        master,
+        cancel: Default::default(),
+      })
+    }
+
+    pub fn resize(&self, size: PtySize) -> Result<(), AnyError> {
+      use std::os::unix::io::AsRawFd;
+
+      let winsize = size.to_winsize();
+      // SAFETY: `master` is an open pty and `winsize` outlives the call.
+      if unsafe {
+        libc::ioctl(
+          self.master.as_raw_fd(),
+          libc::TIOCSWINSZ as _,
+          &winsize as *const nix::pty::Winsize,
+        )
+      } == -1
+      {
+        return Err(std::io::Error::last_os_error().into());
+      }
+      Ok(())
+    }
+
+    async fn read(self: Rc<Self>, data: &mut [u8]) -> Result<usize, AnyError> {
+      use tokio::io::AsyncReadExt;
+
+      let mut reader = RcRef::map(&self, |r| &r.reader).borrow_mut().await;
+      let cancel = RcRef::map(&self, |r| &r.cancel);
+      match reader.read(data).try_or_cancel(cancel).await {
+        // Linux reports EIO instead of EOF once the child's end is closed.
+        Err(err) if err.raw_os_error() == Some(libc::EIO) => Ok(0),
+        result => Ok(result?),
+      }
+    }
+
+    async fn write(self: Rc<Self>, data: &[u8]) -> Result<usize, AnyError> {
+      use tokio::io::AsyncWriteExt;
+
+      let mut writer = RcRef::map(&self, |r| &r.writer).borrow_mut().await;
+      let nwritten = writer.write(data).await?;
+      writer.flush().await?;
+      Ok(nwritten)
+    }
+  }
+
+  #[cfg(unix)]
+  impl Resource for PtyResource {
+    fn name(&self) -> Cow<str> {
+      "pty".into()
+    }
+
+    deno_core::impl_readable_byob!();
+    deno_core::impl_writable!();
+
+    fn close(self: Rc<Self>) {
+      self.cancel.cancel();
+    }
+  }
+
+  #[cfg(unix)]
+  impl PtySize {
+    fn to_winsize(self) -> nix::pty::Winsize {
+      nix::pty::Winsize {
+        ws_row: self.rows,
+        ws_col: self.columns,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+      }
+    }
+  }
+
+  #[cfg(windows)]
+  pub struct PtyResource;
+
+  #[cfg(windows)]
+  impl PtyResource {
+    pub fn attach(
+      _command: &mut std::process::Command,
+      _size: PtySize,
+    ) -> Result<Self, AnyError> {
+      Err(deno_core::error::not_supported())
+    }
+
+    pub fn resize(&self, _size: PtySize) -> Result<(), AnyError> {
+      Err(deno_core::error::not_supported())
+    }
+  }
+
+  #[cfg(windows)]
+  impl Resource for PtyResource {
+    fn name(&self) -> Cow<str> {
+      "pty".into()
+    }
+  }
+}
+
 mod deprecated {
   use super::*;
 
@@ -604,36 +830,52 @@ mod deprecated {
     use winapi::um::handleapi::CloseHandle;
     use winapi::um::processthreadsapi::OpenProcess;
     use winapi::um::processthreadsapi::TerminateProcess;
+    use winapi::um::wincon::GenerateConsoleCtrlEvent;
+    use winapi::um::wincon::CTRL_BREAK_EVENT;
     use winapi::um::winnt::PROCESS_TERMINATE;
 
-    if !matches!(signal, "SIGKILL" | "SIGTERM") {
-      Err(type_error(format!("Invalid signal: {signal}")))
-    } else if pid <= 0 {
-      Err(type_error("Invalid pid"))
-    } else {
-      let handle =
-        // SAFETY: winapi call
-        unsafe { OpenProcess(PROCESS_TERMINATE, FALSE, pid as DWORD) };
-
-      if handle.is_null() {
-        // SAFETY: winapi call
-        let err = match unsafe { GetLastError() } {
-          ERROR_INVALID_PARAMETER => Error::from(NotFound), // Invalid `pid`.
-          errno => Error::from_raw_os_error(errno as i32),
-        };
-        Err(err.into())
-      } else {
-        // SAFETY: winapi calls
-        unsafe {
-          let is_terminated = TerminateProcess(handle, 1);
-          CloseHandle(handle);
-          match is_terminated {
-            FALSE => Err(Error::last_os_error().into()),
-            TRUE => Ok(()),
-            _ => unreachable!(),
+    if pid <= 0 {
+      return Err(type_error("Invalid pid"));
+    }
+    match signal {
+      "SIGKILL" | "SIGTERM" => {
+        let handle =
+          // SAFETY: winapi call
+          unsafe { OpenProcess(PROCESS_TERMINATE, FALSE, pid as DWORD) };
+
+        if handle.is_null() {
+          // SAFETY: winapi call
+          let err = match unsafe { GetLastError() } {
+            ERROR_INVALID_PARAMETER => Error::from(NotFound), // Invalid `pid`.
+            errno => Error::from_raw_os_error(errno as i32),
+          };
+          Err(err.into())
+        } else {
+          // SAFETY: winapi calls
+          unsafe {
+            let is_terminated = TerminateProcess(handle, 1);
+            CloseHandle(handle);
+            match is_terminated {
+              FALSE => Err(Error::last_os_error().into()),
+              TRUE => Ok(()),
+              _ => unreachable!(),
+            }
           }
         }
       }
+      // Windows can't send ctrl-c to a single process group, so both are
+      // sent as ctrl-break. It only reaches processes that share the console
+      // and were started in a new process group, whose id is their pid.
+      "SIGINT" | "SIGBREAK" => {
+        // SAFETY: winapi call
+        match unsafe {
+          GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid as DWORD)
+        } {
+          FALSE => Err(Error::last_os_error().into()),
+          _ => Ok(()),
+        }
+      }
+      _ => Err(type_error(format!("Invalid signal: {signal}"))),
     }
   }
 
@@ -25,9 +25,13 @@ use tokio::signal::windows::ctrl_break;
 #[cfg(windows)]
 use tokio::signal::windows::ctrl_c;
 #[cfg(windows)]
+use tokio::signal::windows::ctrl_close;
+#[cfg(windows)]
 use tokio::signal::windows::CtrlBreak;
 #[cfg(windows)]
 use tokio::signal::windows::CtrlC;
+#[cfg(windows)]
+use tokio::signal::windows::CtrlClose;
 
 deno_core::extension!(
   deno_signal,
@@ -53,14 +57,20 @@ impl Resource for SignalStreamResource {
   }
 }
 
-// TODO: CtrlClose could be mapped to SIGHUP but that needs a
-// tokio::windows::signal::CtrlClose type, or something from a different crate
 #[cfg(windows)]
 enum WindowsSignal {
+  Sighup(CtrlClose),
   Sigint(CtrlC),
   Sigbreak(CtrlBreak),
 }
 
+#[cfg(windows)]
+impl From<CtrlClose> for WindowsSignal {
+  fn from(ctrl_close: CtrlClose) -> Self {
+    WindowsSignal::Sighup(ctrl_close)
+  }
+}
+
 #[cfg(windows)]
 impl From<CtrlC> for WindowsSignal {
   fn from(ctrl_c: CtrlC) -> Self {
@@ -79,6 +89,7 @@ impl From<CtrlBreak> for WindowsSignal {
 impl WindowsSignal {
   pub async fn recv(&mut self) -> Option<()> {
     match self {
+      WindowsSignal::Sighup(ctrl_close) => ctrl_close.recv().await,
       WindowsSignal::Sigint(ctrl_c) => ctrl_c.recv().await,
       WindowsSignal::Sigbreak(ctrl_break) => ctrl_break.recv().await,
     }
@@ -516,10 +527,11 @@ pub fn signal_int_to_str(s: libc::c_int) -> Result<&'static str, AnyError> {
 #[cfg(target_os = "windows")]
 pub fn signal_str_to_int(s: &str) -> Result<libc::c_int, AnyError> {
   match s {
+    "SIGHUP" => Ok(1),
     "SIGINT" => Ok(2),
     "SIGBREAK" => Ok(21),
     _ => Err(type_error(
-      "Windows only supports ctrl-c (SIGINT) and ctrl-break (SIGBREAK).",
+      "Windows only supports ctrl-c (SIGINT), ctrl-break (SIGBREAK) and ctrl-close (SIGHUP).",
     )),
   }
 }
@@ -527,10 +539,11 @@ pub fn signal_str_to_int(s: &str) -> Result<libc::c_int, AnyError> {
 #[cfg(target_os = "windows")]
 pub fn signal_int_to_str(s: libc::c_int) -> Result<&'static str, AnyError> {
   match s {
+    1 => Ok("SIGHUP"),
     2 => Ok("SIGINT"),
     21 => Ok("SIGBREAK"),
     _ => Err(type_error(
-      "Windows only supports ctrl-c (SIGINT) and ctrl-break (SIGBREAK).",
+      "Windows only supports ctrl-c (SIGINT), ctrl-break (SIGBREAK) and ctrl-close (SIGHUP).",
     )),
   }
 }
@@ -564,6 +577,10 @@ fn op_signal_bind(
   let signo = signal_str_to_int(sig)?;
   let resource = SignalStreamResource {
     signal: AsyncRefCell::new(match signo {
+      // SIGHUP, sent when the console window is closed
+      1 => ctrl_close()
+        .expect("There was an issue creating ctrl+close event stream.")
+        .into(),
       // SIGINT
       2 => ctrl_c()
         .expect("There was an issue creating ctrl+c event stream.")
@@ -11,6 +11,7 @@ use crate::web_worker::WebWorkerHandle;
 use crate::web_worker::WebWorkerType;
 use crate::web_worker::WorkerControlEvent;
 use crate::web_worker::WorkerId;
+use crate::web_worker::WorkerResourceLimits;
 use crate::worker::FormatJsErrorFn;
 use deno_core::error::AnyError;
 use deno_core::futures::future::LocalFutureObj;
@@ -34,6 +35,7 @@ pub struct CreateWebWorkerArgs {
   pub permissions: PermissionsContainer,
   pub main_module: ModuleSpecifier,
   pub worker_type: WebWorkerType,
+  pub resource_limits: WorkerResourceLimits,
 }
 
 pub type CreateWebWorkerCb = dyn Fn(CreateWebWorkerArgs) -> (WebWorker, SendableWebWorkerHandle)
@@ -127,6 +129,7 @@ pub struct CreateWorkerArgs {
   has_source_code: bool,
   name: Option<String>,
   permissions: Option<ChildPermissionsArg>,
+  resource_limits: WorkerResourceLimits,
   source_code: String,
   specifier: String,
   worker_type: WebWorkerType,
@@ -160,13 +163,24 @@ fn op_create_worker(
   if args.permissions.is_some() {
     super::check_unstable(state, "Worker.deno.permissions");
   }
+  let resource_limits = args.resource_limits;
+  if resource_limits.memory_limit_mb.is_some() {
+    super::check_unstable(state, "Worker.deno.memoryLimitMb");
+  }
+  if resource_limits.cpu_time_limit_ms.is_some() {
+    super::check_unstable(state, "Worker.deno.cpuTimeLimitMs");
+  }
   let parent_permissions = state.borrow_mut::<PermissionsContainer>();
   let worker_permissions = if let Some(child_permissions_arg) = args.permissions
   {
-    let mut parent_permissions = parent_permissions.0.lock();
-    let perms =
-      create_child_permissions(&mut parent_permissions, child_permissions_arg)?;
-    PermissionsContainer::new(perms)
+    let perms = {
+      let mut parent_permissions = parent_permissions.lock();
+      create_child_permissions(&mut parent_permissions, child_permissions_arg)?
+    };
+    let mut worker_permissions = PermissionsContainer::new(perms);
+    worker_permissions.set_shared_prompter(parent_permissions.prompter());
+    worker_permissions.set_audit_log(parent_permissions.audit_log());
+    worker_permissions
   } else {
     parent_permissions.clone()
   };
@@ -207,6 +221,7 @@ fn op_create_worker(
         permissions: worker_permissions,
         main_module: module_specifier.clone(),
         worker_type,
+        resource_limits,
       });
 
     // Send thread safe handle from newly created worker to host thread
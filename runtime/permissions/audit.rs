@@ -0,0 +1,128 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use super::current_js_stack;
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use deno_core::serde_json;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// Records every permission check of the containers it is attached to as a
+/// line of JSON, see
+/// [`PermissionsContainer::set_audit_log`](crate::permissions::PermissionsContainer::set_audit_log).
+///
+/// Each line has the form:
+///
+/// ```json
+/// {"timestamp":1681300000000,"name":"read","descriptor":"/etc/hosts","apiName":"Deno.readFile()","decision":"granted","prompted":false,"stack":["readConfig (file:///app/main.ts:4:9)","file:///app/main.ts:8:1"]}
+/// ```
+///
+/// `descriptor` is `null` when access to everything of that kind was checked
+/// and `apiName` is `null` when the API is unknown. `stack` is the JavaScript
+/// call stack of the check, innermost frame first. It is empty for checks
+/// that are not made while JavaScript is running, like those of module
+/// loading.
+#[derive(Clone)]
+pub struct PermissionAuditLog(Arc<Mutex<Box<dyn Write + Send>>>);
+
+impl PermissionAuditLog {
+  pub fn new(writer: Box<dyn Write + Send>) -> Self {
+    Self(Arc::new(Mutex::new(writer)))
+  }
+
+  /// Appends to the file at `path`, creating it if needed.
+  pub fn open(path: &Path) -> Result<Self, AnyError> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    Ok(Self::new(Box::new(file)))
+  }
+
+  fn write(&self, entry: &AuditEntry) {
+    let mut line = serde_json::to_vec(entry).unwrap();
+    line.push(b'\n');
+    let mut writer = self.0.lock();
+    // A failing audit log must not take the program down with it.
+    if let Err(err) = writer.write_all(&line).and_then(|_| writer.flush()) {
+      log::warn!("Failed to write to the permission audit log: {err}");
+    }
+  }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditEntry<'a> {
+  timestamp: u64,
+  name: &'a str,
+  descriptor: Option<&'a str>,
+  api_name: Option<&'a str>,
+  decision: &'a str,
+  prompted: bool,
+  stack: Vec<String>,
+}
+
+thread_local! {
+  /// The audit log of the permission container being checked on this
+  /// thread, if it has one.
+  static SCOPED_AUDIT_LOG: RefCell<Option<PermissionAuditLog>> =
+    RefCell::new(None);
+}
+
+/// Whether permission checks on this thread are currently being recorded.
+pub(crate) fn is_audit_enabled() -> bool {
+  SCOPED_AUDIT_LOG.with(|log| log.borrow().is_some())
+}
+
+/// Records a permission check in the scoped audit log, if there is one.
+/// `info` is the quoted value the check was for, or "all".
+pub(crate) fn audit(
+  name: &str,
+  api_name: Option<&str>,
+  info: Option<&str>,
+  granted: bool,
+  prompted: bool,
+) {
+  let Some(log) = SCOPED_AUDIT_LOG.with(|log| log.borrow().clone()) else {
+    return;
+  };
+  let timestamp = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|d| d.as_millis() as u64)
+    .unwrap_or_default();
+  log.write(&AuditEntry {
+    timestamp,
+    name,
+    descriptor: info
+      .filter(|info| *info != "all")
+      .map(|info| info.trim_matches('"')),
+    api_name,
+    decision: if granted { "granted" } else { "denied" },
+    prompted,
+    stack: current_js_stack(),
+  });
+}
+
+/// Records the permission checks on this thread in `audit_log` until the
+/// returned guard is dropped.
+pub(crate) fn scope_audit_log(
+  audit_log: Option<PermissionAuditLog>,
+) -> ScopedAuditLogGuard {
+  let previous = SCOPED_AUDIT_LOG
+    .with(|scoped| std::mem::replace(&mut *scoped.borrow_mut(), audit_log));
+  ScopedAuditLogGuard { previous }
+}
+
+pub(crate) struct ScopedAuditLogGuard {
+  previous: Option<PermissionAuditLog>,
+}
+
+impl Drop for ScopedAuditLogGuard {
+  fn drop(&mut self) {
+    let previous = self.previous.take();
+    SCOPED_AUDIT_LOG.with(|scoped| *scoped.borrow_mut() = previous);
+  }
+}
@@ -7,6 +7,7 @@ use deno_core::error::type_error;
 use deno_core::error::uri_error;
 use deno_core::error::AnyError;
 use deno_core::parking_lot::Mutex;
+use deno_core::parking_lot::MutexGuard;
 use deno_core::serde::de;
 use deno_core::serde::Deserialize;
 use deno_core::serde::Deserializer;
@@ -22,23 +23,62 @@ use std::borrow::Cow;
 use std::collections::HashSet;
 use std::fmt;
 use std::hash::Hash;
+use std::ops::Deref;
+use std::ops::DerefMut;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::string::ToString;
 use std::sync::Arc;
 
+mod audit;
 mod prompter;
+use audit::audit;
+use audit::is_audit_enabled;
+use audit::scope_audit_log;
+use audit::ScopedAuditLogGuard;
 use prompter::permission_prompt;
-use prompter::PromptResponse;
+use prompter::scope_prompter;
+use prompter::ScopedPrompterGuard;
 use prompter::PERMISSION_EMOJI;
 
+pub use audit::PermissionAuditLog;
 pub use prompter::set_prompt_callbacks;
+pub use prompter::DenyAllPrompter;
+pub use prompter::PermissionPrompt;
+pub use prompter::PermissionPrompter;
 pub use prompter::PromptCallback;
+pub use prompter::PromptResponse;
+pub use prompter::SharedPermissionPrompter;
+pub use prompter::TtyPrompter;
 
 static DEBUG_LOG_ENABLED: Lazy<bool> =
   Lazy::new(|| log::log_enabled!(log::Level::Debug));
 
+/// The number of JavaScript stack frames recorded with a permission check.
+const STACK_FRAME_LIMIT: usize = 16;
+
+/// Returns the JavaScript call stack of the permission check being made on
+/// this thread, innermost frame first. Frames are formatted like those of
+/// `Error.stack`, e.g. `readConfig (file:///app/main.ts:4:9)`.
+fn current_js_stack() -> Vec<String> {
+  deno_core::current_stack_trace(STACK_FRAME_LIMIT)
+    .iter()
+    .map(|frame| {
+      let location = format!(
+        "{}:{}:{}",
+        frame.file_name.as_deref().unwrap_or("<anonymous>"),
+        frame.line_number.unwrap_or_default(),
+        frame.column_number.unwrap_or_default()
+      );
+      match &frame.function_name {
+        Some(function_name) => format!("{function_name} ({location})"),
+        None => location,
+      }
+    })
+    .collect()
+}
+
 /// Tri-state value for storing permission state
 #[derive(
   Eq, PartialEq, Default, Debug, Clone, Copy, Deserialize, PartialOrd,
@@ -109,20 +149,29 @@ impl PermissionState {
     info: impl Fn() -> Option<String>,
     prompt: bool,
   ) -> (Result<(), AnyError>, bool, bool) {
-    match self {
+    let result = match self {
       PermissionState::Granted => {
-        Self::log_perm_access(name, info);
+        Self::log_perm_access(name, &info);
         (Ok(()), false, false)
       }
       PermissionState::Prompt if prompt => {
+        let info = info();
         let msg = format!(
           "{} access{}",
           name,
-          info()
+          info
+            .as_ref()
             .map(|info| { format!(" to {info}") })
             .unwrap_or_default(),
         );
-        match permission_prompt(&msg, name, api_name, true) {
+        // `info` is the quoted value, or "all" when access to everything of
+        // this kind is checked.
+        let value = info
+          .as_deref()
+          .filter(|info| *info != "all")
+          .map(|info| info.trim_matches('"'));
+        let info = || info.clone();
+        match permission_prompt(&msg, name, value, api_name, true) {
           PromptResponse::Allow => {
             Self::log_perm_access(name, info);
             (Ok(()), true, false)
@@ -134,8 +183,18 @@ impl PermissionState {
           PromptResponse::Deny => (Err(Self::error(name, info)), true, false),
         }
       }
-      _ => (Err(Self::error(name, info)), false, false),
+      _ => (Err(Self::error(name, &info)), false, false),
+    };
+    if is_audit_enabled() {
+      audit(
+        name,
+        api_name,
+        info().as_deref(),
+        result.0.is_ok(),
+        result.1,
+      );
     }
+    result
   }
 }
 
@@ -168,6 +227,7 @@ impl UnitPermission {
         == permission_prompt(
           &format!("access to {}", self.description),
           self.name,
+          None,
           Some("Deno.permissions.query()"),
           false,
         )
@@ -233,41 +293,158 @@ pub struct UnaryPermission<T: Eq + Hash> {
   pub global_state: PermissionState,
   pub granted_list: HashSet<T>,
   pub denied_list: HashSet<T>,
+  /// Denied up front, e.g. by a permission set, regardless of what is
+  /// granted or answered at a prompt.
+  pub flag_denied_list: HashSet<T>,
   pub prompt: bool,
 }
 
+impl<T: Eq + Hash> UnaryPermission<T> {
+  /// Whether `query` is denied by `flag_denied_list`. A `query` of `None`
+  /// stands for access to everything of this kind, which is denied as soon as
+  /// anything is.
+  fn is_flag_denied<Q: ?Sized>(
+    &self,
+    query: Option<&Q>,
+    matches: impl Fn(&T, &Q) -> bool,
+  ) -> bool {
+    match query {
+      None => !self.flag_denied_list.is_empty(),
+      Some(query) => self.flag_denied_list.iter().any(|d| matches(d, query)),
+    }
+  }
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct ReadDescriptor(pub PathBuf);
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct WriteDescriptor(pub PathBuf);
 
+/// A network permission entry, as passed to `--allow-net`.
+///
+/// The host may be a wildcard of the form `*.example.com`, which matches any
+/// subdomain of `example.com` (but not `example.com` itself). A descriptor
+/// parsed from a URL pattern such as `https://api.example.com/v1/*` carries a
+/// [`NetUrlScope`] and only grants access to URLs with that scheme and path;
+/// it is ignored by host-only checks such as `Deno.connect()`.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct NetDescriptor(pub String, pub Option<u16>, pub Option<NetUrlScope>);
+
+/// The scheme and path a URL-pattern [`NetDescriptor`] is restricted to.
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
-pub struct NetDescriptor(pub String, pub Option<u16>);
+pub struct NetUrlScope {
+  pub scheme: String,
+  pub path: String,
+  /// Whether `path` is a prefix (the pattern ended in `*`) rather than an
+  /// exact path.
+  pub prefix: bool,
+}
 
 impl NetDescriptor {
   fn new<T: AsRef<str>>(host: &&(T, Option<u16>)) -> Self {
-    NetDescriptor(host.0.as_ref().to_string(), host.1)
+    NetDescriptor(host.0.as_ref().to_string(), host.1, None)
+  }
+
+  fn matches_hostname(&self, hostname: &str) -> bool {
+    match self.0.strip_prefix("*.") {
+      Some(suffix) => hostname
+        .strip_suffix(suffix)
+        .map_or(false, |sub| sub.len() > 1 && sub.ends_with('.')),
+      None => self.0 == hostname,
+    }
+  }
+
+  /// Whether this descriptor covers `hostname` and `port`, regardless of any
+  /// URL scope. A descriptor without a port matches every port, while a
+  /// descriptor with a port never matches a query without one.
+  fn matches_host(&self, hostname: &str, port: Option<u16>) -> bool {
+    self.matches_hostname(hostname) && (self.1.is_none() || self.1 == port)
+  }
+
+  fn matches_url(&self, url: &Url) -> bool {
+    let Some(hostname) = url.host_str() else {
+      return false;
+    };
+    if !self.matches_host(hostname, url.port_or_known_default()) {
+      return false;
+    }
+    match &self.2 {
+      None => true,
+      Some(scope) if scope.scheme != url.scheme() => false,
+      Some(scope) if scope.prefix => url.path().starts_with(&scope.path),
+      Some(scope) => url.path() == scope.path,
+    }
   }
 }
 
+/// Parses `host[:port]`, where the host may start with a `*.` wildcard.
+fn parse_net_host(
+  s: &str,
+  scheme: &str,
+) -> Result<(String, Option<u16>), AnyError> {
+  let (wildcard, s) = match s.strip_prefix("*.") {
+    Some(rest) => ("*.", rest),
+    None => ("", s),
+  };
+  let url = url::Url::parse(&format!("{scheme}://{s}"))?;
+  let hostname = url.host_str().ok_or_else(|| uri_error("Missing host"))?;
+  let port = if scheme == "http" {
+    url.port()
+  } else {
+    url.port_or_known_default()
+  };
+  Ok((format!("{wildcard}{hostname}"), port))
+}
+
 impl FromStr for NetDescriptor {
   type Err = AnyError;
 
   fn from_str(s: &str) -> Result<Self, Self::Err> {
-    let url = url::Url::parse(&format!("http://{s}"))?;
-    let hostname = url.host_str().unwrap().to_string();
-
-    Ok(NetDescriptor(hostname, url.port()))
+    let Some((scheme, rest)) = s.split_once("://") else {
+      let (hostname, port) = parse_net_host(s, "http")?;
+      return Ok(NetDescriptor(hostname, port, None));
+    };
+    let (authority, path) = match rest.find('/') {
+      Some(i) => rest.split_at(i),
+      None => (rest, ""),
+    };
+    let scheme = scheme.to_ascii_lowercase();
+    let (hostname, port) = parse_net_host(authority, &scheme)?;
+    let (path, prefix) = match path.strip_suffix('*') {
+      Some(path) => (path, true),
+      None if path.is_empty() => ("", true),
+      None => (path, false),
+    };
+    Ok(NetDescriptor(
+      hostname,
+      port,
+      Some(NetUrlScope {
+        scheme,
+        path: path.to_string(),
+        prefix,
+      }),
+    ))
   }
 }
 
 impl fmt::Display for NetDescriptor {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    f.write_str(&match self.1 {
+    let host = match self.1 {
       None => self.0.clone(),
       Some(port) => format!("{}:{}", self.0, port),
-    })
+    };
+    match &self.2 {
+      None => f.write_str(&host),
+      Some(scope) => write!(
+        f,
+        "{}://{}{}{}",
+        scope.scheme,
+        host,
+        scope.path,
+        if scope.prefix { "*" } else { "" }
+      ),
+    }
   }
 }
 
@@ -332,10 +509,17 @@ pub struct FfiDescriptor(pub PathBuf);
 
 impl UnaryPermission<ReadDescriptor> {
   pub fn query(&self, path: Option<&Path>) -> PermissionState {
-    if self.global_state == PermissionState::Granted {
+    if self.global_state == PermissionState::Granted
+      && self.flag_denied_list.is_empty()
+    {
       return PermissionState::Granted;
     }
     let path = path.map(|p| resolve_from_cwd(p).unwrap());
+    if self
+      .is_flag_denied(path.as_deref(), |path_, path| path.starts_with(&path_.0))
+    {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match path.as_ref() {
         None => true,
@@ -369,6 +553,7 @@ impl UnaryPermission<ReadDescriptor> {
         match permission_prompt(
           &format!("read access to \"{}\"", display_path.display()),
           self.name,
+          Some(&display_path.to_string_lossy()),
           Some("Deno.permissions.query()"),
           true,
         ) {
@@ -400,6 +585,7 @@ impl UnaryPermission<ReadDescriptor> {
           == permission_prompt(
             "read access",
             self.name,
+            None,
             Some("Deno.permissions.query()"),
             true,
           )
@@ -519,6 +705,7 @@ impl Default for UnaryPermission<ReadDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -526,10 +713,17 @@ impl Default for UnaryPermission<ReadDescriptor> {
 
 impl UnaryPermission<WriteDescriptor> {
   pub fn query(&self, path: Option<&Path>) -> PermissionState {
-    if self.global_state == PermissionState::Granted {
+    if self.global_state == PermissionState::Granted
+      && self.flag_denied_list.is_empty()
+    {
       return PermissionState::Granted;
     }
     let path = path.map(|p| resolve_from_cwd(p).unwrap());
+    if self
+      .is_flag_denied(path.as_deref(), |path_, path| path.starts_with(&path_.0))
+    {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match path.as_ref() {
         None => true,
@@ -563,6 +757,7 @@ impl UnaryPermission<WriteDescriptor> {
         match permission_prompt(
           &format!("write access to \"{}\"", display_path.display()),
           self.name,
+          Some(&display_path.to_string_lossy()),
           Some("Deno.permissions.query()"),
           true,
         ) {
@@ -594,6 +789,7 @@ impl UnaryPermission<WriteDescriptor> {
           == permission_prompt(
             "write access",
             self.name,
+            None,
             Some("Deno.permissions.query()"),
             true,
           )
@@ -713,6 +909,7 @@ impl Default for UnaryPermission<WriteDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -723,6 +920,11 @@ impl UnaryPermission<NetDescriptor> {
     &self,
     host: Option<&(T, Option<u16>)>,
   ) -> PermissionState {
+    if self.is_flag_denied(host, |host_, host| {
+      host_.2.is_none() && host_.matches_host(host.0.as_ref(), host.1)
+    }) {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match host.as_ref() {
         None => true,
@@ -730,8 +932,10 @@ impl UnaryPermission<NetDescriptor> {
           None => self
             .denied_list
             .iter()
-            .any(|host_| host.0.as_ref() == host_.0),
-          Some(_) => self.denied_list.contains(&NetDescriptor::new(host)),
+            .any(|host_| host_.matches_hostname(host.0.as_ref())),
+          Some(_) => self.denied_list.iter().any(|host_| {
+            host_.1 == host.1 && host_.matches_hostname(host.0.as_ref())
+          }),
         },
       }
     {
@@ -739,13 +943,9 @@ impl UnaryPermission<NetDescriptor> {
     } else if self.global_state == PermissionState::Granted
       || match host.as_ref() {
         None => false,
-        Some(host) => {
-          self.granted_list.contains(&NetDescriptor::new(&&(
-            host.0.as_ref().to_string(),
-            None,
-          )))
-            || self.granted_list.contains(&NetDescriptor::new(host))
-        }
+        Some(host) => self.granted_list.iter().any(|host_| {
+          host_.2.is_none() && host_.matches_host(host.0.as_ref(), host.1)
+        }),
       }
     {
       PermissionState::Granted
@@ -754,6 +954,29 @@ impl UnaryPermission<NetDescriptor> {
     }
   }
 
+  /// Like [`Self::query`], but also takes the URL scopes of granted
+  /// descriptors into account, so that `https://api.example.com/v1/*` grants
+  /// `https://api.example.com/v1/users` but not `https://api.example.com/v2`.
+  pub fn query_url(&self, url: &Url) -> PermissionState {
+    if self
+      .flag_denied_list
+      .iter()
+      .any(|host_| host_.matches_url(url))
+    {
+      return PermissionState::Denied;
+    }
+    let hostname = url.host_str().unwrap_or_default();
+    let host = &(hostname, url.port_or_known_default());
+    match self.query(Some(host)) {
+      PermissionState::Prompt
+        if self.granted_list.iter().any(|host_| host_.matches_url(url)) =>
+      {
+        PermissionState::Granted
+      }
+      state => state,
+    }
+  }
+
   pub fn request<T: AsRef<str>>(
     &mut self,
     host: Option<&(T, Option<u16>)>,
@@ -765,6 +988,7 @@ impl UnaryPermission<NetDescriptor> {
         match permission_prompt(
           &format!("network access to \"{host}\""),
           self.name,
+          Some(&host.to_string()),
           Some("Deno.permissions.query()"),
           true,
         ) {
@@ -796,6 +1020,7 @@ impl UnaryPermission<NetDescriptor> {
           == permission_prompt(
             "network access",
             self.name,
+            None,
             Some("Deno.permissions.query()"),
             true,
           )
@@ -818,14 +1043,9 @@ impl UnaryPermission<NetDescriptor> {
     host: Option<&(T, Option<u16>)>,
   ) -> PermissionState {
     if let Some(host) = host {
-      if host.1.is_some() {
-        self
-          .granted_list
-          .remove(&NetDescriptor(host.0.as_ref().to_string(), host.1));
-      }
       self
         .granted_list
-        .remove(&NetDescriptor(host.0.as_ref().to_string(), None));
+        .retain(|host_| !host_.matches_host(host.0.as_ref(), host.1));
     } else {
       self.granted_list.clear();
     }
@@ -835,6 +1055,27 @@ impl UnaryPermission<NetDescriptor> {
     self.query(host)
   }
 
+  /// Requests access to the host of `url`, unless a granted URL pattern
+  /// already covers it.
+  pub fn request_url(&mut self, url: &Url) -> PermissionState {
+    match self.query_url(url) {
+      PermissionState::Granted => PermissionState::Granted,
+      _ => {
+        let hostname = url.host_str().unwrap_or_default();
+        self.request(Some(&(hostname, url.port_or_known_default())))
+      }
+    }
+  }
+
+  /// Revokes every granted host or URL pattern that covers `url`.
+  pub fn revoke_url(&mut self, url: &Url) -> PermissionState {
+    self.granted_list.retain(|host_| !host_.matches_url(url));
+    if self.global_state == PermissionState::Granted {
+      self.global_state = PermissionState::Prompt;
+    }
+    self.query_url(url)
+  }
+
   pub fn check<T: AsRef<str>>(
     &mut self,
     host: &(T, Option<u16>),
@@ -877,7 +1118,7 @@ impl UnaryPermission<NetDescriptor> {
       Some(port) => Cow::Owned(format!("{hostname}:{port}")),
     };
     let host = &(&hostname, url.port_or_known_default());
-    let (result, prompted, is_allow_all) = self.query(Some(host)).check(
+    let (result, prompted, is_allow_all) = self.query_url(url).check(
       self.name,
       api_name,
       Some(&format!("\"{display_host}\"")),
@@ -923,6 +1164,7 @@ impl Default for UnaryPermission<NetDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -931,6 +1173,9 @@ impl Default for UnaryPermission<NetDescriptor> {
 impl UnaryPermission<EnvDescriptor> {
   pub fn query(&self, env: Option<&str>) -> PermissionState {
     let env = env.map(EnvVarName::new);
+    if self.is_flag_denied(env.as_ref(), |env_, env| env_.0 == *env) {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match env.as_ref() {
         None => true,
@@ -957,6 +1202,7 @@ impl UnaryPermission<EnvDescriptor> {
         match permission_prompt(
           &format!("env access to \"{env}\""),
           self.name,
+          Some(env),
           Some("Deno.permissions.query()"),
           true,
         ) {
@@ -988,6 +1234,7 @@ impl UnaryPermission<EnvDescriptor> {
           == permission_prompt(
             "env access",
             self.name,
+            None,
             Some("Deno.permissions.query()"),
             true,
           )
@@ -1064,6 +1311,7 @@ impl Default for UnaryPermission<EnvDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -1071,6 +1319,9 @@ impl Default for UnaryPermission<EnvDescriptor> {
 
 impl UnaryPermission<SysDescriptor> {
   pub fn query(&self, kind: Option<&str>) -> PermissionState {
+    if self.is_flag_denied(kind, |kind_, kind| kind_.0 == kind) {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match kind {
         None => true,
@@ -1104,6 +1355,7 @@ impl UnaryPermission<SysDescriptor> {
       match permission_prompt(
         &format!("sys access to \"{kind}\""),
         self.name,
+        Some(kind),
         Some("Deno.permissions.query()"),
         true,
       ) {
@@ -1127,6 +1379,7 @@ impl UnaryPermission<SysDescriptor> {
         == permission_prompt(
           "sys access",
           self.name,
+          None,
           Some("Deno.permissions.query()"),
           true,
         )
@@ -1203,6 +1456,7 @@ impl Default for UnaryPermission<SysDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -1210,6 +1464,11 @@ impl Default for UnaryPermission<SysDescriptor> {
 
 impl UnaryPermission<RunDescriptor> {
   pub fn query(&self, cmd: Option<&str>) -> PermissionState {
+    if self.is_flag_denied(cmd, |cmd_, cmd| {
+      *cmd_ == RunDescriptor::from_str(cmd).unwrap()
+    }) {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match cmd {
         None => true,
@@ -1240,6 +1499,7 @@ impl UnaryPermission<RunDescriptor> {
         match permission_prompt(
           &format!("run access to \"{cmd}\""),
           self.name,
+          Some(cmd),
           Some("Deno.permissions.query()"),
           true,
         ) {
@@ -1277,6 +1537,7 @@ impl UnaryPermission<RunDescriptor> {
           == permission_prompt(
             "run access",
             self.name,
+            None,
             Some("Deno.permissions.query()"),
             true,
           )
@@ -1363,6 +1624,7 @@ impl Default for UnaryPermission<RunDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -1371,6 +1633,11 @@ impl Default for UnaryPermission<RunDescriptor> {
 impl UnaryPermission<FfiDescriptor> {
   pub fn query(&self, path: Option<&Path>) -> PermissionState {
     let path = path.map(|p| resolve_from_cwd(p).unwrap());
+    if self
+      .is_flag_denied(path.as_deref(), |path_, path| path.starts_with(&path_.0))
+    {
+      return PermissionState::Denied;
+    }
     if self.global_state == PermissionState::Denied
       && match path.as_ref() {
         None => true,
@@ -1404,6 +1671,7 @@ impl UnaryPermission<FfiDescriptor> {
         match permission_prompt(
           &format!("ffi access to \"{}\"", display_path.display()),
           self.name,
+          Some(&display_path.to_string_lossy()),
           Some("Deno.permissions.query()"),
           true,
         ) {
@@ -1435,6 +1703,7 @@ impl UnaryPermission<FfiDescriptor> {
           == permission_prompt(
             "ffi access",
             self.name,
+            None,
             Some("Deno.permissions.query()"),
             true,
           )
@@ -1533,6 +1802,7 @@ impl Default for UnaryPermission<FfiDescriptor> {
       global_state: Default::default(),
       granted_list: Default::default(),
       denied_list: Default::default(),
+      flag_denied_list: Default::default(),
       prompt: false,
     }
   }
@@ -1575,6 +1845,13 @@ pub struct PermissionsOptions {
   pub allow_run: Option<Vec<String>>,
   pub allow_sys: Option<Vec<String>>,
   pub allow_write: Option<Vec<PathBuf>>,
+  pub deny_env: Vec<String>,
+  pub deny_net: Vec<String>,
+  pub deny_ffi: Vec<PathBuf>,
+  pub deny_read: Vec<PathBuf>,
+  pub deny_run: Vec<String>,
+  pub deny_sys: Vec<String>,
+  pub deny_write: Vec<PathBuf>,
   pub prompt: bool,
 }
 
@@ -1719,7 +1996,7 @@ impl Permissions {
   }
 
   pub fn from_options(opts: &PermissionsOptions) -> Result<Self, AnyError> {
-    Ok(Self {
+    let mut permissions = Self {
       read: Permissions::new_read(&opts.allow_read, opts.prompt)?,
       write: Permissions::new_write(&opts.allow_write, opts.prompt)?,
       net: Permissions::new_net(&opts.allow_net, opts.prompt)?,
@@ -1728,7 +2005,23 @@ impl Permissions {
       run: Permissions::new_run(&opts.allow_run, opts.prompt)?,
       ffi: Permissions::new_ffi(&opts.allow_ffi, opts.prompt)?,
       hrtime: Permissions::new_hrtime(opts.allow_hrtime),
-    })
+    };
+    permissions.read.flag_denied_list =
+      Permissions::new_read(&Some(opts.deny_read.clone()), false)?.granted_list;
+    permissions.write.flag_denied_list =
+      Permissions::new_write(&Some(opts.deny_write.clone()), false)?
+        .granted_list;
+    permissions.net.flag_denied_list =
+      Permissions::new_net(&Some(opts.deny_net.clone()), false)?.granted_list;
+    permissions.env.flag_denied_list =
+      Permissions::new_env(&Some(opts.deny_env.clone()), false)?.granted_list;
+    permissions.sys.flag_denied_list =
+      Permissions::new_sys(&Some(opts.deny_sys.clone()), false)?.granted_list;
+    permissions.run.flag_denied_list =
+      Permissions::new_run(&Some(opts.deny_run.clone()), false)?.granted_list;
+    permissions.ffi.flag_denied_list =
+      Permissions::new_ffi(&Some(opts.deny_ffi.clone()), false)?.granted_list;
+    Ok(permissions)
   }
 
   pub fn allow_all() -> Self {
@@ -1770,24 +2063,77 @@ impl Permissions {
 /// passed to a future that will prompt the user for permission (and in such
 /// case might need to be mutated). Also for the Web Worker API we need a way
 /// to send permissions to a new thread.
-#[derive(Clone, Debug)]
-pub struct PermissionsContainer(pub Arc<Mutex<Permissions>>);
+#[derive(Clone)]
+pub struct PermissionsContainer(
+  pub Arc<Mutex<Permissions>>,
+  Option<SharedPermissionPrompter>,
+  Option<PermissionAuditLog>,
+);
+
+impl fmt::Debug for PermissionsContainer {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("PermissionsContainer")
+      .field(&self.0)
+      .finish()
+  }
+}
 
 impl PermissionsContainer {
   pub fn new(perms: Permissions) -> Self {
-    Self(Arc::new(Mutex::new(perms)))
+    Self(Arc::new(Mutex::new(perms)), None, None)
   }
 
   pub fn allow_all() -> Self {
     Self::new(Permissions::allow_all())
   }
 
+  /// Answers the permission prompts of this container with `prompter`
+  /// instead of asking on the terminal. Clones made afterwards, like the
+  /// one handed to the runtime, share the prompter.
+  pub fn set_prompter(&mut self, prompter: Box<dyn PermissionPrompter>) {
+    self.1 = Some(Arc::new(Mutex::new(prompter)));
+  }
+
+  /// Shares the prompter of another container, e.g. the one of the parent of
+  /// a worker. `None` restores the terminal prompt.
+  pub fn set_shared_prompter(
+    &mut self,
+    prompter: Option<SharedPermissionPrompter>,
+  ) {
+    self.1 = prompter;
+  }
+
+  pub fn prompter(&self) -> Option<SharedPermissionPrompter> {
+    self.1.clone()
+  }
+
+  /// Records the permission checks of this container in `audit_log`. Like
+  /// the prompter, the audit log is shared with clones made afterwards.
+  pub fn set_audit_log(&mut self, audit_log: Option<PermissionAuditLog>) {
+    self.2 = audit_log;
+  }
+
+  pub fn audit_log(&self) -> Option<PermissionAuditLog> {
+    self.2.clone()
+  }
+
+  /// Locks the permissions. Prompts issued while the lock is held are
+  /// answered by this container's prompter, and checks are recorded in its
+  /// audit log.
+  pub fn lock(&self) -> PermissionsGuard {
+    PermissionsGuard {
+      _prompter: scope_prompter(self.1.clone()),
+      _audit_log: scope_audit_log(self.2.clone()),
+      permissions: self.0.lock(),
+    }
+  }
+
   #[inline(always)]
   pub fn check_specifier(
     &self,
     specifier: &ModuleSpecifier,
   ) -> Result<(), AnyError> {
-    self.0.lock().check_specifier(specifier)
+    self.lock().check_specifier(specifier)
   }
 
   #[inline(always)]
@@ -1796,7 +2142,7 @@ impl PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check(path, Some(api_name))
+    self.lock().read.check(path, Some(api_name))
   }
 
   #[inline(always)]
@@ -1806,12 +2152,12 @@ impl PermissionsContainer {
     display: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check_blind(path, display, api_name)
+    self.lock().read.check_blind(path, display, api_name)
   }
 
   #[inline(always)]
   pub fn check_read_all(&mut self, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().read.check_all(Some(api_name))
+    self.lock().read.check_all(Some(api_name))
   }
 
   #[inline(always)]
@@ -1820,12 +2166,12 @@ impl PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().write.check(path, Some(api_name))
+    self.lock().write.check(path, Some(api_name))
   }
 
   #[inline(always)]
   pub fn check_write_all(&mut self, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().write.check_all(Some(api_name))
+    self.lock().write.check_all(Some(api_name))
   }
 
   #[inline(always)]
@@ -1835,7 +2181,7 @@ impl PermissionsContainer {
     display: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().write.check_blind(path, display, api_name)
+    self.lock().write.check_blind(path, display, api_name)
   }
 
   #[inline(always)]
@@ -1844,12 +2190,12 @@ impl PermissionsContainer {
     cmd: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().run.check(cmd, Some(api_name))
+    self.lock().run.check(cmd, Some(api_name))
   }
 
   #[inline(always)]
   pub fn check_run_all(&mut self, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().run.check_all(Some(api_name))
+    self.lock().run.check_all(Some(api_name))
   }
 
   #[inline(always)]
@@ -1858,17 +2204,39 @@ impl PermissionsContainer {
     kind: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().sys.check(kind, Some(api_name))
+    self.lock().sys.check(kind, Some(api_name))
   }
 
   #[inline(always)]
   pub fn check_env(&mut self, var: &str) -> Result<(), AnyError> {
-    self.0.lock().env.check(var)
+    self.lock().env.check(var)
   }
 
   #[inline(always)]
   pub fn check_env_all(&mut self) -> Result<(), AnyError> {
-    self.0.lock().env.check_all()
+    self.lock().env.check_all()
+  }
+}
+
+/// The permissions of a [`PermissionsContainer`], locked with
+/// [`PermissionsContainer::lock`].
+pub struct PermissionsGuard<'a> {
+  permissions: MutexGuard<'a, Permissions>,
+  _prompter: ScopedPrompterGuard,
+  _audit_log: ScopedAuditLogGuard,
+}
+
+impl Deref for PermissionsGuard<'_> {
+  type Target = Permissions;
+
+  fn deref(&self) -> &Permissions {
+    &self.permissions
+  }
+}
+
+impl DerefMut for PermissionsGuard<'_> {
+  fn deref_mut(&mut self) -> &mut Permissions {
+    &mut self.permissions
   }
 }
 
@@ -1879,12 +2247,12 @@ impl deno_node::NodePermissions for PermissionsContainer {
     url: &Url,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().net.check_url(url, Some(api_name))
+    self.lock().net.check_url(url, Some(api_name))
   }
 
   #[inline(always)]
   fn check_read(&self, path: &Path) -> Result<(), AnyError> {
-    self.0.lock().read.check(path, None)
+    self.lock().read.check(path, None)
   }
 }
 
@@ -1895,7 +2263,7 @@ impl deno_net::NetPermissions for PermissionsContainer {
     host: &(T, Option<u16>),
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().net.check(host, Some(api_name))
+    self.lock().net.check(host, Some(api_name))
   }
 
   #[inline(always)]
@@ -1904,7 +2272,7 @@ impl deno_net::NetPermissions for PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check(path, Some(api_name))
+    self.lock().read.check(path, Some(api_name))
   }
 
   #[inline(always)]
@@ -1913,7 +2281,7 @@ impl deno_net::NetPermissions for PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().write.check(path, Some(api_name))
+    self.lock().write.check(path, Some(api_name))
   }
 }
 
@@ -1924,7 +2292,7 @@ impl deno_fetch::FetchPermissions for PermissionsContainer {
     url: &url::Url,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().net.check_url(url, Some(api_name))
+    self.lock().net.check_url(url, Some(api_name))
   }
 
   #[inline(always)]
@@ -1933,14 +2301,23 @@ impl deno_fetch::FetchPermissions for PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check(path, Some(api_name))
+    self.lock().read.check(path, Some(api_name))
+  }
+
+  #[inline(always)]
+  fn check_write(
+    &mut self,
+    path: &Path,
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    self.lock().write.check(path, Some(api_name))
   }
 }
 
 impl deno_web::TimersPermission for PermissionsContainer {
   #[inline(always)]
   fn allow_hrtime(&mut self) -> bool {
-    self.0.lock().hrtime.check().is_ok()
+    self.lock().hrtime.check().is_ok()
   }
 
   #[inline(always)]
@@ -1956,7 +2333,7 @@ impl deno_websocket::WebSocketPermissions for PermissionsContainer {
     url: &url::Url,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().net.check_url(url, Some(api_name))
+    self.lock().net.check_url(url, Some(api_name))
   }
 }
 
@@ -1966,7 +2343,7 @@ impl deno_fs::FsPermissions for PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check(path, Some(api_name))
+    self.lock().read.check(path, Some(api_name))
   }
 
   fn check_read_blind(
@@ -1975,7 +2352,7 @@ impl deno_fs::FsPermissions for PermissionsContainer {
     display: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().read.check_blind(path, display, api_name)
+    self.lock().read.check_blind(path, display, api_name)
   }
 
   fn check_write(
@@ -1983,7 +2360,7 @@ impl deno_fs::FsPermissions for PermissionsContainer {
     path: &Path,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().write.check(path, Some(api_name))
+    self.lock().write.check(path, Some(api_name))
   }
 
   fn check_write_blind(
@@ -1992,15 +2369,15 @@ impl deno_fs::FsPermissions for PermissionsContainer {
     display: &str,
     api_name: &str,
   ) -> Result<(), AnyError> {
-    self.0.lock().write.check_blind(p, display, api_name)
+    self.lock().write.check_blind(p, display, api_name)
   }
 
   fn check_read_all(&mut self, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().read.check_all(Some(api_name))
+    self.lock().read.check_all(Some(api_name))
   }
 
   fn check_write_all(&mut self, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().write.check_all(Some(api_name))
+    self.lock().write.check_all(Some(api_name))
   }
 }
 
@@ -2009,26 +2386,42 @@ impl deno_fs::FsPermissions for PermissionsContainer {
 impl deno_napi::NapiPermissions for PermissionsContainer {
   #[inline(always)]
   fn check(&mut self, path: Option<&Path>) -> Result<(), AnyError> {
-    self.0.lock().ffi.check(path)
+    self.lock().ffi.check(path)
   }
 }
 
 impl deno_ffi::FfiPermissions for PermissionsContainer {
   #[inline(always)]
   fn check(&mut self, path: Option<&Path>) -> Result<(), AnyError> {
-    self.0.lock().ffi.check(path)
+    self.lock().ffi.check(path)
+  }
+}
+
+impl deno_kv::remote::RemoteDbHandlerPermissions for PermissionsContainer {
+  #[inline(always)]
+  fn check_env(&mut self, var: &str) -> Result<(), AnyError> {
+    self.lock().env.check(var)
+  }
+
+  #[inline(always)]
+  fn check_net_url(
+    &mut self,
+    url: &url::Url,
+    api_name: &str,
+  ) -> Result<(), AnyError> {
+    self.lock().net.check_url(url, Some(api_name))
   }
 }
 
 impl deno_kv::sqlite::SqliteDbHandlerPermissions for PermissionsContainer {
   #[inline(always)]
   fn check_read(&mut self, p: &Path, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().read.check(p, Some(api_name))
+    self.lock().read.check(p, Some(api_name))
   }
 
   #[inline(always)]
   fn check_write(&mut self, p: &Path, api_name: &str) -> Result<(), AnyError> {
-    self.0.lock().write.check(p, Some(api_name))
+    self.lock().write.check(p, Some(api_name))
   }
 }
 
@@ -2410,6 +2803,7 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.env.denied_list = main_perms.env.denied_list.clone();
+  worker_perms.env.flag_denied_list = main_perms.env.flag_denied_list.clone();
   if main_perms.env.global_state == PermissionState::Denied {
     worker_perms.env.global_state = PermissionState::Denied;
   }
@@ -2439,6 +2833,7 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.sys.denied_list = main_perms.sys.denied_list.clone();
+  worker_perms.sys.flag_denied_list = main_perms.sys.flag_denied_list.clone();
   if main_perms.sys.global_state == PermissionState::Denied {
     worker_perms.sys.global_state = PermissionState::Denied;
   }
@@ -2484,6 +2879,7 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.net.denied_list = main_perms.net.denied_list.clone();
+  worker_perms.net.flag_denied_list = main_perms.net.flag_denied_list.clone();
   if main_perms.net.global_state == PermissionState::Denied {
     worker_perms.net.global_state = PermissionState::Denied;
   }
@@ -2516,6 +2912,7 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.ffi.denied_list = main_perms.ffi.denied_list.clone();
+  worker_perms.ffi.flag_denied_list = main_perms.ffi.flag_denied_list.clone();
   if main_perms.ffi.global_state == PermissionState::Denied {
     worker_perms.ffi.global_state = PermissionState::Denied;
   }
@@ -2548,6 +2945,7 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.read.denied_list = main_perms.read.denied_list.clone();
+  worker_perms.read.flag_denied_list = main_perms.read.flag_denied_list.clone();
   if main_perms.read.global_state == PermissionState::Denied {
     worker_perms.read.global_state = PermissionState::Denied;
   }
@@ -2577,6 +2975,7 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.run.denied_list = main_perms.run.denied_list.clone();
+  worker_perms.run.flag_denied_list = main_perms.run.flag_denied_list.clone();
   if main_perms.run.global_state == PermissionState::Denied {
     worker_perms.run.global_state = PermissionState::Denied;
   }
@@ -2609,6 +3008,8 @@ pub fn create_child_permissions(
     }
   }
   worker_perms.write.denied_list = main_perms.write.denied_list.clone();
+  worker_perms.write.flag_denied_list =
+    main_perms.write.flag_denied_list.clone();
   if main_perms.write.global_state == PermissionState::Denied {
     worker_perms.write.global_state = PermissionState::Denied;
   }
@@ -2619,7 +3020,10 @@ pub fn create_child_permissions(
 #[cfg(test)]
 mod tests {
   use super::*;
+  use deno_core::op;
   use deno_core::serde_json::json;
+  use deno_core::JsRuntime;
+  use deno_core::RuntimeOptions;
   use prompter::tests::*;
 
   // Creates vector of strings, Vec<String>
@@ -2905,6 +3309,104 @@ mod tests {
     }
   }
 
+  #[test]
+  fn test_check_net_patterns() {
+    set_prompter(Box::new(TestPrompter));
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_net: Some(svec![
+        "*.internal.corp:443",
+        "https://api.example.com/v1/*",
+        "https://files.example.com/index.json"
+      ]),
+      ..Default::default()
+    })
+    .unwrap();
+
+    let host_tests = vec![
+      ("a.internal.corp", Some(443), true),
+      ("a.b.internal.corp", Some(443), true),
+      ("a.internal.corp", Some(80), false),
+      ("a.internal.corp", None, false),
+      ("internal.corp", Some(443), false),
+      ("ainternal.corp", Some(443), false),
+      // URL patterns only apply to URL checks.
+      ("api.example.com", Some(443), false),
+    ];
+    for (host, port, is_ok) in host_tests {
+      assert_eq!(is_ok, perms.net.check(&(host, port), None).is_ok());
+    }
+
+    let url_tests = vec![
+      ("https://a.internal.corp/any/path", true),
+      ("http://a.internal.corp/", false),
+      ("https://api.example.com/v1/users", true),
+      ("https://api.example.com/v1/", true),
+      ("https://api.example.com/v2/users", false),
+      ("http://api.example.com/v1/users", false),
+      ("https://api.example.com:8443/v1/users", false),
+      ("https://files.example.com/index.json", true),
+      ("https://files.example.com/index.json.bak", false),
+    ];
+    for (url_str, is_ok) in url_tests {
+      let u = url::Url::parse(url_str).unwrap();
+      assert_eq!(is_ok, perms.net.check_url(&u, None).is_ok(), "{url_str}");
+    }
+
+    let url = url::Url::parse("https://api.example.com/v1/users").unwrap();
+    assert_eq!(perms.net.query_url(&url), PermissionState::Granted);
+    assert_eq!(
+      perms.net.query(Some(&("b.internal.corp", Some(443)))),
+      PermissionState::Granted
+    );
+    assert_eq!(perms.net.revoke_url(&url), PermissionState::Prompt);
+    assert_eq!(
+      perms.net.revoke(Some(&("b.internal.corp", Some(443)))),
+      PermissionState::Prompt
+    );
+  }
+
+  #[test]
+  fn test_flag_denied_list() {
+    set_prompter(Box::new(TestPrompter));
+    let mut perms = Permissions::from_options(&PermissionsOptions {
+      allow_read: Some(vec![PathBuf::from("/data")]),
+      allow_env: Some(vec![]),
+      allow_net: Some(vec![]),
+      deny_read: vec![PathBuf::from("/data/secrets")],
+      deny_env: svec!["AWS_SECRET_ACCESS_KEY"],
+      deny_net: svec!["*.evil.com", "https://api.example.com/admin/*"],
+      prompt: true,
+      ..Default::default()
+    })
+    .unwrap();
+
+    assert!(perms.read.check(Path::new("/data/public"), None).is_ok());
+    assert!(perms.read.check(Path::new("/data/secrets"), None).is_err());
+    assert!(perms
+      .read
+      .check(Path::new("/data/secrets/key"), None)
+      .is_err());
+    assert!(perms.read.check_all(None).is_err());
+    assert!(perms.env.check("HOME").is_ok());
+    assert!(perms.env.check("AWS_SECRET_ACCESS_KEY").is_err());
+    assert!(perms.net.check(&("example.com", Some(443)), None).is_ok());
+    assert!(perms.net.check(&("www.evil.com", Some(443)), None).is_err());
+    let admin = Url::parse("https://api.example.com/admin/users").unwrap();
+    let users = Url::parse("https://api.example.com/users").unwrap();
+    assert!(perms.net.check_url(&admin, None).is_err());
+    assert!(perms.net.check_url(&users, None).is_ok());
+
+    // Denied entries can't be requested or handed to workers.
+    assert_eq!(
+      perms.env.request(Some("AWS_SECRET_ACCESS_KEY")),
+      PermissionState::Denied
+    );
+    let mut worker_perms =
+      create_child_permissions(&mut perms, ChildPermissionsArg::inherit())
+        .unwrap();
+    assert!(worker_perms.env.check("AWS_SECRET_ACCESS_KEY").is_err());
+  }
+
   #[test]
   fn check_specifiers() {
     set_prompter(Box::new(TestPrompter));
@@ -3601,4 +4103,203 @@ mod tests {
     assert!(Permissions::new_net(&Some(svec![String::new()]), false).is_err());
     assert!(Permissions::new_write(&Some(vec![PathBuf::new()]), false).is_err());
   }
+
+  #[test]
+  fn test_container_prompter() {
+    #[derive(Default)]
+    struct RecordingPrompter(Arc<Mutex<Vec<String>>>);
+
+    impl PermissionPrompter for RecordingPrompter {
+      fn prompt(&mut self, prompt: &PermissionPrompt) -> PromptResponse {
+        self.0.lock().push(format!(
+          "{} {:?} {:?}",
+          prompt.name, prompt.value, prompt.api_name
+        ));
+        if prompt.name == "env" {
+          PromptResponse::Allow
+        } else {
+          PromptResponse::Deny
+        }
+      }
+    }
+
+    let prompts = Arc::new(Mutex::new(vec![]));
+    let mut container = PermissionsContainer::new(
+      Permissions::from_options(&PermissionsOptions {
+        prompt: true,
+        ..Default::default()
+      })
+      .unwrap(),
+    );
+    container.set_prompter(Box::new(RecordingPrompter(prompts.clone())));
+
+    let mut runtime_container = container.clone();
+    assert!(runtime_container.check_env("HOME").is_ok());
+    assert!(runtime_container
+      .check_read(Path::new("/foo"), "Deno.readFile()")
+      .is_err());
+    assert!(runtime_container
+      .check_write_all("Deno.writeFile()")
+      .is_err());
+    // Answered permissions are not asked for again.
+    assert!(runtime_container.check_env("HOME").is_ok());
+
+    let mut child = PermissionsContainer::new(
+      create_child_permissions(
+        &mut container.lock(),
+        ChildPermissionsArg::inherit(),
+      )
+      .unwrap(),
+    );
+    child.set_shared_prompter(container.prompter());
+    assert!(child.check_sys("hostname", "Deno.hostname()").is_err());
+
+    assert_eq!(
+      *prompts.lock(),
+      vec![
+        r#"env Some("HOME") None"#,
+        r#"read Some("/foo") Some("Deno.readFile()")"#,
+        r#"write None Some("Deno.writeFile()")"#,
+        r#"sys Some("hostname") Some("Deno.hostname()")"#,
+      ]
+    );
+  }
+
+  #[derive(Clone, Default)]
+  struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+  impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+      self.0.lock().extend_from_slice(buf);
+      Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn test_container_audit_log() {
+    let buffer = SharedBuffer::default();
+    let mut container = PermissionsContainer::new(
+      Permissions::from_options(&PermissionsOptions {
+        allow_env: Some(svec!["HOME"]),
+        ..Default::default()
+      })
+      .unwrap(),
+    );
+    container
+      .set_audit_log(Some(PermissionAuditLog::new(Box::new(buffer.clone()))));
+
+    assert!(container.check_env("HOME").is_ok());
+    assert!(container
+      .check_read(Path::new("/foo"), "Deno.readFile()")
+      .is_err());
+    assert!(container.check_write_all("Deno.writeFile()").is_err());
+    // Checks made without the container are not recorded.
+    assert!(container.0.lock().env.check("HOME").is_ok());
+
+    let output = String::from_utf8(buffer.0.lock().clone()).unwrap();
+    let entries = output
+      .lines()
+      .map(|line| {
+        let mut entry: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert!(entry["timestamp"].as_u64().unwrap() > 0);
+        entry.as_object_mut().unwrap().remove("timestamp");
+        entry
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      entries,
+      vec![
+        json!({
+          "name": "env",
+          "descriptor": "HOME",
+          "apiName": null,
+          "decision": "granted",
+          "prompted": false,
+          "stack": [],
+        }),
+        json!({
+          "name": "read",
+          "descriptor": "/foo",
+          "apiName": "Deno.readFile()",
+          "decision": "denied",
+          "prompted": false,
+          "stack": [],
+        }),
+        json!({
+          "name": "write",
+          "descriptor": null,
+          "apiName": "Deno.writeFile()",
+          "decision": "denied",
+          "prompted": false,
+          "stack": [],
+        }),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_container_stack() {
+    struct StackPrompter(Arc<Mutex<Vec<String>>>);
+
+    impl PermissionPrompter for StackPrompter {
+      fn prompt(&mut self, prompt: &PermissionPrompt) -> PromptResponse {
+        *self.0.lock() = prompt.stack.to_vec();
+        PromptResponse::Allow
+      }
+    }
+
+    #[op]
+    fn op_check_env(state: &mut OpState) -> Result<(), AnyError> {
+      state.borrow_mut::<PermissionsContainer>().check_env("HOME")
+    }
+
+    deno_core::extension!(test_ext,
+      ops = [op_check_env],
+      options = { permissions: PermissionsContainer },
+      state = |state, options| {
+        state.put::<PermissionsContainer>(options.permissions);
+      },
+    );
+
+    let prompted_stack = Arc::new(Mutex::new(vec![]));
+    let buffer = SharedBuffer::default();
+    let mut container = PermissionsContainer::new(
+      Permissions::from_options(&PermissionsOptions {
+        prompt: true,
+        ..Default::default()
+      })
+      .unwrap(),
+    );
+    container.set_prompter(Box::new(StackPrompter(prompted_stack.clone())));
+    container
+      .set_audit_log(Some(PermissionAuditLog::new(Box::new(buffer.clone()))));
+    let mut runtime = JsRuntime::new(RuntimeOptions {
+      extensions: vec![test_ext::init_ops(container)],
+      ..Default::default()
+    });
+    runtime
+      .execute_script_static(
+        "audit_test.js",
+        "function readHome() {\n  Deno.core.ops.op_check_env();\n}\nreadHome();",
+      )
+      .unwrap();
+
+    let output = String::from_utf8(buffer.0.lock().clone()).unwrap();
+    let entry: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let stack = entry["stack"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|frame| frame.as_str().unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(stack.len(), 2);
+    assert!(stack[0].starts_with("readHome (audit_test.js:2:"));
+    assert_eq!(stack[1], "audit_test.js:4:1");
+    assert_eq!(entry["prompted"], true);
+    assert_eq!(*prompted_stack.lock(), stack);
+  }
 }
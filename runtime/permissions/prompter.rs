@@ -1,14 +1,17 @@
 // Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
 
+use super::current_js_stack;
 use crate::colors;
 use deno_core::error::AnyError;
 use deno_core::parking_lot::Mutex;
 use once_cell::sync::Lazy;
+use std::cell::RefCell;
 use std::fmt::Write;
 use std::io::BufRead;
 use std::io::StderrLock;
 use std::io::StdinLock;
 use std::io::Write as IoWrite;
+use std::sync::Arc;
 
 /// Helper function to strip ansi codes and ASCII control characters.
 fn strip_ansi_codes_and_ascii_control(s: &str) -> std::borrow::Cow<str> {
@@ -27,9 +30,20 @@ pub enum PromptResponse {
   AllowAll,
 }
 
+/// A permission prompter that can be shared between permission containers,
+/// see [`crate::permissions::PermissionsContainer::set_prompter`].
+pub type SharedPermissionPrompter = Arc<Mutex<Box<dyn PermissionPrompter>>>;
+
 static PERMISSION_PROMPTER: Lazy<Mutex<Box<dyn PermissionPrompter>>> =
   Lazy::new(|| Mutex::new(Box::new(TtyPrompter)));
 
+thread_local! {
+  /// The prompter of the permission container being checked on this thread,
+  /// if it has one.
+  static SCOPED_PERMISSION_PROMPTER: RefCell<Option<SharedPermissionPrompter>> =
+    RefCell::new(None);
+}
+
 static MAYBE_BEFORE_PROMPT_CALLBACK: Lazy<Mutex<Option<PromptCallback>>> =
   Lazy::new(|| Mutex::new(None));
 
@@ -39,15 +53,28 @@ static MAYBE_AFTER_PROMPT_CALLBACK: Lazy<Mutex<Option<PromptCallback>>> =
 pub fn permission_prompt(
   message: &str,
   flag: &str,
+  value: Option<&str>,
   api_name: Option<&str>,
   is_unary: bool,
 ) -> PromptResponse {
+  let stack = current_js_stack();
+  let prompt = PermissionPrompt {
+    message,
+    name: flag,
+    value,
+    api_name,
+    is_unary,
+    stack: &stack,
+  };
   if let Some(before_callback) = MAYBE_BEFORE_PROMPT_CALLBACK.lock().as_mut() {
     before_callback();
   }
-  let r = PERMISSION_PROMPTER
-    .lock()
-    .prompt(message, flag, api_name, is_unary);
+  let scoped_prompter =
+    SCOPED_PERMISSION_PROMPTER.with(|prompter| prompter.borrow().clone());
+  let r = match scoped_prompter {
+    Some(prompter) => prompter.lock().prompt(&prompt),
+    None => PERMISSION_PROMPTER.lock().prompt(&prompt),
+  };
   if let Some(after_callback) = MAYBE_AFTER_PROMPT_CALLBACK.lock().as_mut() {
     after_callback();
   }
@@ -64,26 +91,79 @@ pub fn set_prompt_callbacks(
 
 pub type PromptCallback = Box<dyn FnMut() + Send + Sync>;
 
+/// Makes `prompter` answer the permission prompts issued on this thread until
+/// the returned guard is dropped.
+pub(crate) fn scope_prompter(
+  prompter: Option<SharedPermissionPrompter>,
+) -> ScopedPrompterGuard {
+  let previous = SCOPED_PERMISSION_PROMPTER
+    .with(|scoped| std::mem::replace(&mut *scoped.borrow_mut(), prompter));
+  ScopedPrompterGuard { previous }
+}
+
+pub(crate) struct ScopedPrompterGuard {
+  previous: Option<SharedPermissionPrompter>,
+}
+
+impl Drop for ScopedPrompterGuard {
+  fn drop(&mut self) {
+    let previous = self.previous.take();
+    SCOPED_PERMISSION_PROMPTER.with(|scoped| *scoped.borrow_mut() = previous);
+  }
+}
+
+/// A request for a permission that is in the prompt state.
+#[derive(Clone, Copy, Debug)]
+pub struct PermissionPrompt<'a> {
+  /// Human readable description of the access, e.g.
+  /// `read access to "/etc/hosts"`.
+  pub message: &'a str,
+  /// Kind of the permission: "read", "write", "net", "env", "sys", "run",
+  /// "ffi" or "hrtime".
+  pub name: &'a str,
+  /// The requested path, host, variable, command or system info, or `None`
+  /// if access to everything of this kind is requested. Paths hidden from the
+  /// caller are given as `<display>`.
+  pub value: Option<&'a str>,
+  /// The API that triggered the prompt, if known.
+  pub api_name: Option<&'a str>,
+  /// Whether [`PromptResponse::AllowAll`] is an acceptable answer.
+  pub is_unary: bool,
+  /// The JavaScript call stack of the request, innermost frame first, e.g.
+  /// `readConfig (file:///app/main.ts:4:9)`. Empty if the request was not
+  /// made by JavaScript.
+  pub stack: &'a [String],
+}
+
+/// Answers permission prompts. The default prompter asks on the terminal;
+/// embedders can show their own dialog or deny without asking by setting
+/// another one with
+/// [`PermissionsContainer::set_prompter`](crate::permissions::PermissionsContainer::set_prompter).
 pub trait PermissionPrompter: Send + Sync {
-  fn prompt(
-    &mut self,
-    message: &str,
-    name: &str,
-    api_name: Option<&str>,
-    is_unary: bool,
-  ) -> PromptResponse;
+  fn prompt(&mut self, prompt: &PermissionPrompt) -> PromptResponse;
+}
+
+/// Denies every prompt without asking.
+pub struct DenyAllPrompter;
+
+impl PermissionPrompter for DenyAllPrompter {
+  fn prompt(&mut self, _prompt: &PermissionPrompt) -> PromptResponse {
+    PromptResponse::Deny
+  }
 }
 
 pub struct TtyPrompter;
 
 impl PermissionPrompter for TtyPrompter {
-  fn prompt(
-    &mut self,
-    message: &str,
-    name: &str,
-    api_name: Option<&str>,
-    is_unary: bool,
-  ) -> PromptResponse {
+  fn prompt(&mut self, prompt: &PermissionPrompt) -> PromptResponse {
+    let PermissionPrompt {
+      message,
+      name,
+      api_name,
+      is_unary,
+      ..
+    } = *prompt;
+
     if !atty::is(atty::Stream::Stdin) || !atty::is(atty::Stream::Stderr) {
       return PromptResponse::Deny;
     };
@@ -318,13 +398,7 @@ pub mod tests {
   pub struct TestPrompter;
 
   impl PermissionPrompter for TestPrompter {
-    fn prompt(
-      &mut self,
-      _message: &str,
-      _name: &str,
-      _api_name: Option<&str>,
-      _is_unary: bool,
-    ) -> PromptResponse {
+    fn prompt(&mut self, _prompt: &PermissionPrompt) -> PromptResponse {
       if STUB_PROMPT_VALUE.load(Ordering::SeqCst) {
         PromptResponse::Allow
       } else {
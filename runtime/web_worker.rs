@@ -11,6 +11,7 @@ use deno_broadcast_channel::InMemoryBroadcastChannel;
 use deno_cache::CreateCache;
 use deno_cache::SqliteBackedCache;
 use deno_core::ascii_str;
+use deno_core::error::generic_error;
 use deno_core::error::AnyError;
 use deno_core::error::JsError;
 use deno_core::futures::channel::mpsc;
@@ -26,11 +27,13 @@ use deno_core::CancelHandle;
 use deno_core::CompiledWasmModuleStore;
 use deno_core::Extension;
 use deno_core::GetErrorClassFn;
+use deno_core::IsolateConfig;
 use deno_core::JsRuntime;
 use deno_core::ModuleCode;
 use deno_core::ModuleId;
 use deno_core::ModuleLoader;
 use deno_core::ModuleSpecifier;
+use deno_core::NearHeapLimitAction;
 use deno_core::RuntimeOptions;
 use deno_core::SharedArrayBufferStore;
 use deno_core::Snapshot;
@@ -38,13 +41,14 @@ use deno_core::SourceMapGetter;
 use deno_fs::FileSystem;
 use deno_http::DefaultHttpPropertyExtractor;
 use deno_io::Stdio;
-use deno_kv::sqlite::SqliteDbHandler;
+use deno_kv::multi_backend::MultiBackendDbHandler;
 use deno_tls::RootCertStoreProvider;
 use deno_web::create_entangled_message_port;
 use deno_web::BlobStore;
 use deno_web::MessagePort;
 use log::debug;
 use std::cell::RefCell;
+use std::ffi::c_void;
 use std::fmt;
 use std::rc::Rc;
 use std::sync::atomic::AtomicBool;
@@ -52,6 +56,7 @@ use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -60,6 +65,19 @@ pub enum WebWorkerType {
   Module,
 }
 
+/// Limits on the resources a single worker may use, set with
+/// `new Worker(url, { deno: { memoryLimitMb, cpuTimeLimitMs } })`. A worker
+/// that exceeds one of them is terminated and its parent receives an error
+/// event.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkerResourceLimits {
+  /// Maximum size of the worker's heap, in megabytes.
+  pub memory_limit_mb: Option<u64>,
+  /// Maximum CPU time the worker's thread may use, in milliseconds.
+  pub cpu_time_limit_ms: Option<u64>,
+}
+
 #[derive(
   Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize,
 )]
@@ -191,6 +209,21 @@ impl WebWorkerInternalHandle {
     // Wake parent by closing the channel
     self.sender.close_channel();
   }
+
+  /// Terminate the worker because it exceeded one of its resource limits,
+  /// reporting `message` to the parent as a terminal error.
+  fn terminate_with_error(&mut self, message: String) {
+    if self.is_terminated() {
+      return;
+    }
+    let error = generic_error(message);
+    print_worker_error(&error, &self.name, None);
+    if let Err(err) = self.post_event(WorkerControlEvent::TerminalError(error))
+    {
+      debug!("Failed to post error of worker {}: {}", self.name, err);
+    }
+    self.terminate();
+  }
 }
 
 pub struct SendableWebWorkerHandle {
@@ -324,6 +357,9 @@ pub struct WebWorker {
   pub main_module: ModuleSpecifier,
   poll_for_messages_fn: Option<v8::Global<v8::Value>>,
   bootstrap_fn_global: Option<v8::Global<v8::Function>>,
+  // Dropped after `js_runtime`, as pending interrupts of the isolate point
+  // to it.
+  _cpu_time_limit: Option<Box<CpuTimeLimit>>,
 }
 
 pub struct WebWorkerOptions {
@@ -331,6 +367,7 @@ pub struct WebWorkerOptions {
   pub extensions: Vec<Extension>,
   pub startup_snapshot: Option<Snapshot>,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  pub dns_config: Option<deno_net::resolver::DnsConfig>,
   pub root_cert_store_provider: Option<Arc<dyn RootCertStoreProvider>>,
   pub seed: Option<u64>,
   pub fs: Arc<dyn FileSystem>,
@@ -349,7 +386,9 @@ pub struct WebWorkerOptions {
   pub shared_array_buffer_store: Option<SharedArrayBufferStore>,
   pub compiled_wasm_module_store: Option<CompiledWasmModuleStore>,
   pub cache_storage_dir: Option<std::path::PathBuf>,
+  pub cache_storage_max_size: Option<u64>,
   pub stdio: Stdio,
+  pub resource_limits: WorkerResourceLimits,
 }
 
 impl WebWorker {
@@ -390,8 +429,11 @@ impl WebWorker {
     // Permissions: many ops depend on this
     let unstable = options.bootstrap.unstable;
     let enable_testing_features = options.bootstrap.enable_testing_features;
+    let cache_storage_max_size = options.cache_storage_max_size;
     let create_cache = options.cache_storage_dir.map(|storage_dir| {
-      let create_cache_fn = move || SqliteBackedCache::new(storage_dir.clone());
+      let create_cache_fn = move || {
+        SqliteBackedCache::new(storage_dir.clone(), cache_storage_max_size)
+      };
       CreateCache(Arc::new(create_cache_fn))
     });
 
@@ -414,6 +456,7 @@ impl WebWorker {
             .unsafely_ignore_certificate_errors
             .clone(),
           file_fetch_handler: Rc::new(deno_fetch::FsFetchHandler),
+          dns_config: options.dns_config.clone(),
           ..Default::default()
         },
       ),
@@ -434,10 +477,11 @@ impl WebWorker {
         options.root_cert_store_provider.clone(),
         unstable,
         options.unsafely_ignore_certificate_errors.clone(),
+        options.dns_config.clone(),
       ),
       deno_tls::deno_tls::init_ops(),
       deno_kv::deno_kv::init_ops(
-        SqliteDbHandler::<PermissionsContainer>::new(None),
+        MultiBackendDbHandler::<PermissionsContainer>::new(None),
         unstable,
       ),
       deno_napi::deno_napi::init_ops::<PermissionsContainer>(),
@@ -460,6 +504,8 @@ impl WebWorker {
         options.pre_execute_module_cb.clone(),
         options.format_js_error_fn.clone(),
       ),
+      ops::blob::deno_blob::init_ops(),
+      ops::cron::deno_cron::init_ops(None),
       ops::fs_events::deno_fs_events::init_ops(),
       ops::os::deno_os_worker::init_ops(),
       ops::permissions::deno_permissions::init_ops(),
@@ -485,6 +531,14 @@ impl WebWorker {
     let startup_snapshot = options.startup_snapshot
       .expect("deno_runtime startup snapshot is not available with 'create_runtime_snapshot' Cargo feature.");
 
+    let resource_limits = options.resource_limits;
+    let isolate_config = IsolateConfig {
+      max_heap_size: resource_limits
+        .memory_limit_mb
+        .map(|mb| (mb as usize) << 20),
+      ..Default::default()
+    };
+
     let mut js_runtime = JsRuntime::new(RuntimeOptions {
       module_loader: Some(options.module_loader.clone()),
       startup_snapshot: Some(startup_snapshot),
@@ -494,6 +548,7 @@ impl WebWorker {
       compiled_wasm_module_store: options.compiled_wasm_module_store.clone(),
       extensions,
       inspector: options.maybe_inspector_server.is_some(),
+      isolate_config,
       ..Default::default()
     });
     init_runtime_module_map(&mut js_runtime);
@@ -522,6 +577,19 @@ impl WebWorker {
       (internal_handle, external_handle)
     };
 
+    if let Some(memory_limit_mb) = resource_limits.memory_limit_mb {
+      let mut internal_handle = internal_handle.clone();
+      js_runtime.set_near_heap_limit_callback(Box::new(move |_| {
+        internal_handle.terminate_with_error(format!(
+          "Worker exceeded its memory limit of {memory_limit_mb} MB"
+        ));
+        NearHeapLimitAction::Terminate
+      }));
+    }
+    let cpu_time_limit = resource_limits.cpu_time_limit_ms.map(|ms| {
+      CpuTimeLimit::start(Duration::from_millis(ms), internal_handle.clone())
+    });
+
     let bootstrap_fn_global = {
       let context = js_runtime.global_context();
       let scope = &mut js_runtime.handle_scope();
@@ -554,6 +622,7 @@ impl WebWorker {
         main_module,
         poll_for_messages_fn: None,
         bootstrap_fn_global: Some(bootstrap_fn_global),
+        _cpu_time_limit: cpu_time_limit,
       },
       external_handle,
     )
@@ -734,6 +803,112 @@ impl WebWorker {
   }
 }
 
+/// Terminates a worker once its thread has used more CPU time than allowed.
+///
+/// A watchdog thread periodically interrupts the isolate, and the interrupt
+/// compares the CPU time of the worker's thread with the limit. Time spent
+/// outside of JavaScript, e.g. in ops, only counts once JavaScript runs again.
+struct CpuTimeLimit {
+  limit: Duration,
+  start: Duration,
+  interrupt_pending: Arc<AtomicBool>,
+  internal_handle: WebWorkerInternalHandle,
+}
+
+impl CpuTimeLimit {
+  /// Must be called on the worker's thread.
+  fn start(
+    limit: Duration,
+    internal_handle: WebWorkerInternalHandle,
+  ) -> Box<Self> {
+    let interrupt_pending = Arc::new(AtomicBool::new(false));
+    let this = Box::new(Self {
+      limit,
+      start: thread_cpu_time(),
+      interrupt_pending: interrupt_pending.clone(),
+      internal_handle,
+    });
+
+    let interval =
+      (limit / 4).clamp(Duration::from_millis(1), Duration::from_millis(50));
+    let weak_interrupt_pending = Arc::downgrade(&interrupt_pending);
+    drop(interrupt_pending);
+    let has_terminated = this.internal_handle.has_terminated.clone();
+    let isolate_handle = this.internal_handle.isolate_handle.clone();
+    let data = &*this as *const Self as usize;
+    std::thread::spawn(move || loop {
+      std::thread::sleep(interval);
+      // The limit is gone once the worker has been dropped.
+      let Some(interrupt_pending) = weak_interrupt_pending.upgrade() else {
+        return;
+      };
+      if has_terminated.load(Ordering::SeqCst) {
+        return;
+      }
+      if !interrupt_pending.swap(true, Ordering::SeqCst) {
+        isolate_handle.request_interrupt(check_cpu_time, data as *mut c_void);
+      }
+    });
+    this
+  }
+}
+
+extern "C" fn check_cpu_time(_isolate: &mut v8::Isolate, data: *mut c_void) {
+  // SAFETY: `CpuTimeLimit` is owned by the `WebWorker` and dropped after its
+  // isolate, which discards pending interrupts. Interrupts run on the
+  // worker's thread.
+  let cpu_time_limit = unsafe { &mut *(data as *mut CpuTimeLimit) };
+  cpu_time_limit
+    .interrupt_pending
+    .store(false, Ordering::SeqCst);
+  let used = thread_cpu_time().saturating_sub(cpu_time_limit.start);
+  if used > cpu_time_limit.limit {
+    let limit_ms = cpu_time_limit.limit.as_millis();
+    cpu_time_limit.internal_handle.terminate_with_error(format!(
+      "Worker exceeded its CPU time limit of {limit_ms} ms"
+    ));
+  }
+}
+
+/// CPU time used by the current thread.
+#[cfg(unix)]
+fn thread_cpu_time() -> Duration {
+  let mut ts = libc::timespec {
+    tv_sec: 0,
+    tv_nsec: 0,
+  };
+  // SAFETY: `ts` is a valid timespec to write to.
+  unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) };
+  Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
+
+/// CPU time used by the current thread.
+#[cfg(windows)]
+fn thread_cpu_time() -> Duration {
+  use winapi::shared::minwindef::FILETIME;
+  use winapi::um::processthreadsapi::GetCurrentThread;
+  use winapi::um::processthreadsapi::GetThreadTimes;
+
+  let zero = FILETIME {
+    dwLowDateTime: 0,
+    dwHighDateTime: 0,
+  };
+  let (mut creation, mut exit, mut kernel, mut user) = (zero, zero, zero, zero);
+  // SAFETY: all pointers are valid FILETIMEs to write to.
+  unsafe {
+    GetThreadTimes(
+      GetCurrentThread(),
+      &mut creation,
+      &mut exit,
+      &mut kernel,
+      &mut user,
+    )
+  };
+  let to_100ns =
+    |t: FILETIME| ((t.dwHighDateTime as u64) << 32) | t.dwLowDateTime as u64;
+  Duration::from_nanos((to_100ns(kernel) + to_100ns(user)) * 100)
+}
+
 fn print_worker_error(
   error: &AnyError,
   name: &str,
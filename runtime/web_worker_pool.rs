@@ -0,0 +1,365 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::ops::worker_host::WorkerEventCb;
+use crate::web_worker::run_web_worker;
+use crate::web_worker::SendableWebWorkerHandle;
+use crate::web_worker::WebWorker;
+use crate::web_worker::WorkerId;
+use crate::worker::FormatJsErrorFn;
+use deno_core::error::AnyError;
+use deno_core::parking_lot::Mutex;
+use log::debug;
+use std::collections::VecDeque;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::Weak;
+
+/// Creates a worker whose main module has not been evaluated yet. Called on
+/// the thread the worker will run on, so a worker created with
+/// [`WebWorker::bootstrap_from_options`] is bootstrapped before a job needs
+/// it.
+pub type CreateWarmWebWorkerCb =
+  dyn Fn(WorkerId) -> (WebWorker, SendableWebWorkerHandle) + Sync + Send;
+
+pub struct WebWorkerPoolOptions {
+  /// Number of workers kept ready to run a job.
+  pub size: usize,
+  pub create_web_worker_cb: Arc<CreateWarmWebWorkerCb>,
+  pub preload_module_cb: Arc<WorkerEventCb>,
+  pub pre_execute_module_cb: Arc<WorkerEventCb>,
+  pub format_js_error_fn: Option<Arc<FormatJsErrorFn>>,
+}
+
+/// Keeps a number of web workers created and bootstrapped ahead of time, so
+/// a job-queue style embedder doesn't pay for creating an isolate and
+/// booting the runtime for every job.
+///
+/// Workers are one-shot: a worker only runs a single job, as evaluated
+/// modules can't be unloaded, and its thread exits once the job is done.
+/// Taking a worker boots replacements right away, so the pool never holds
+/// more than `size` workers that are ready or booting. Dropping the pool
+/// drops the workers that haven't been taken.
+pub struct WebWorkerPool(Arc<PoolInner>);
+
+struct PoolInner {
+  options: WebWorkerPoolOptions,
+  state: Mutex<PoolState>,
+}
+
+struct PoolState {
+  idle: VecDeque<WarmWebWorker>,
+  /// Workers being created that will join `idle` once they are ready.
+  booting: usize,
+  next_worker_id: WorkerId,
+}
+
+struct WarmWebWorker {
+  handle: SendableWebWorkerHandle,
+  start_tx: mpsc::SyncSender<()>,
+}
+
+impl WebWorkerPool {
+  pub fn new(options: WebWorkerPoolOptions) -> Result<Self, AnyError> {
+    let size = options.size;
+    let pool = Self(Arc::new(PoolInner {
+      options,
+      state: Mutex::new(PoolState {
+        idle: VecDeque::with_capacity(size),
+        booting: 0,
+        next_worker_id: WorkerId::default(),
+      }),
+    }));
+    pool.fill()?;
+    Ok(pool)
+  }
+
+  /// Starts evaluating the main module of a ready worker and returns its
+  /// handle, or `None` if no worker is ready yet. Messages can be posted to
+  /// the handle right away.
+  pub fn take(&self) -> Result<Option<SendableWebWorkerHandle>, AnyError> {
+    let warm_worker = self.0.state.lock().idle.pop_front();
+    // Also replaces workers that failed to be created.
+    self.fill()?;
+    let Some(warm_worker) = warm_worker else {
+      return Ok(None);
+    };
+    // The worker's thread only exits without being started when the pool
+    // is dropped.
+    warm_worker.start_tx.send(()).unwrap();
+    Ok(Some(warm_worker.handle))
+  }
+
+  /// Number of workers ready to be taken.
+  pub fn idle_count(&self) -> usize {
+    self.0.state.lock().idle.len()
+  }
+
+  /// Boots workers until `size` workers are ready or booting.
+  fn fill(&self) -> Result<(), AnyError> {
+    loop {
+      {
+        let mut state = self.0.state.lock();
+        if state.idle.len() + state.booting >= self.0.options.size {
+          return Ok(());
+        }
+        state.booting += 1;
+      }
+      if let Err(err) = spawn_warm_worker(Arc::downgrade(&self.0)) {
+        self.0.state.lock().booting -= 1;
+        return Err(err);
+      }
+    }
+  }
+}
+
+fn spawn_warm_worker(pool: Weak<PoolInner>) -> Result<(), AnyError> {
+  std::thread::Builder::new()
+    .name("warm-web-worker".to_string())
+    .spawn(move || {
+      let Some(inner) = pool.upgrade() else {
+        return;
+      };
+      let options = &inner.options;
+      let worker_id = {
+        let mut state = inner.state.lock();
+        state.next_worker_id = state.next_worker_id.next().unwrap();
+        state.next_worker_id
+      };
+      // A panicking callback must not leave the worker counted as booting,
+      // or the pool would never replace it.
+      let created = panic::catch_unwind(AssertUnwindSafe(|| {
+        (options.create_web_worker_cb)(worker_id)
+      }));
+      let (start_tx, start_rx) = mpsc::sync_channel::<()>(1);
+      let worker = {
+        let mut state = inner.state.lock();
+        state.booting -= 1;
+        let Ok((worker, handle)) = created else {
+          debug!("Failed to create warm web worker {}", worker_id);
+          return;
+        };
+        state.idle.push_back(WarmWebWorker { handle, start_tx });
+        worker
+      };
+      let preload_module_cb = options.preload_module_cb.clone();
+      let pre_execute_module_cb = options.pre_execute_module_cb.clone();
+      let format_js_error_fn = options.format_js_error_fn.clone();
+      drop(inner);
+
+      // Fails once the pool has been dropped together with the sender.
+      if start_rx.recv().is_err() {
+        return;
+      }
+      let main_module = worker.main_module.clone();
+      if let Err(err) = run_web_worker(
+        worker,
+        main_module,
+        None,
+        preload_module_cb,
+        pre_execute_module_cb,
+        format_js_error_fn,
+      ) {
+        debug!("Warm web worker {} failed: {}", worker_id, err);
+      }
+    })?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::permissions::PermissionsContainer;
+  use crate::web_worker::WebWorkerOptions;
+  use crate::web_worker::WebWorkerType;
+  use deno_core::ascii_str;
+  use deno_core::futures::future::LocalFutureObj;
+  use deno_core::futures::FutureExt;
+  use deno_core::resolve_import;
+  use deno_core::ModuleLoader;
+  use deno_core::ModuleSource;
+  use deno_core::ModuleSourceFuture;
+  use deno_core::ModuleSpecifier;
+  use deno_core::ModuleType;
+  use deno_core::ResolutionKind;
+  use std::collections::HashMap;
+  use std::pin::Pin;
+  use std::rc::Rc;
+  use std::sync::atomic::AtomicBool;
+  use std::sync::atomic::AtomicUsize;
+  use std::sync::atomic::Ordering;
+  use std::time::Duration;
+  use std::time::Instant;
+
+  struct JobLoader;
+
+  impl ModuleLoader for JobLoader {
+    fn resolve(
+      &self,
+      specifier: &str,
+      referrer: &str,
+      _kind: ResolutionKind,
+    ) -> Result<ModuleSpecifier, AnyError> {
+      Ok(resolve_import(specifier, referrer)?)
+    }
+
+    fn load(
+      &self,
+      module_specifier: &ModuleSpecifier,
+      _maybe_referrer: Option<&ModuleSpecifier>,
+      _is_dyn_import: bool,
+      _import_assertions: &HashMap<String, String>,
+    ) -> Pin<Box<ModuleSourceFuture>> {
+      let source = ModuleSource::new(
+        ModuleType::JavaScript,
+        ascii_str!("self.close();"),
+        module_specifier,
+      );
+      async move { Ok(source) }.boxed_local()
+    }
+  }
+
+  #[derive(Default)]
+  struct Counters {
+    created: AtomicUsize,
+    panic_on_create: AtomicBool,
+    panic_on_run: AtomicBool,
+  }
+
+  fn create_pool(size: usize, counters: Arc<Counters>) -> WebWorkerPool {
+    let preload_counters = counters.clone();
+    WebWorkerPool::new(WebWorkerPoolOptions {
+      size,
+      create_web_worker_cb: Arc::new(move |worker_id| {
+        if counters.panic_on_create.swap(false, Ordering::SeqCst) {
+          panic!("failed to create worker");
+        }
+        counters.created.fetch_add(1, Ordering::SeqCst);
+        WebWorker::bootstrap_from_options(
+          "pooled".to_string(),
+          PermissionsContainer::allow_all(),
+          ModuleSpecifier::parse("file:///job.js").unwrap(),
+          worker_id,
+          WebWorkerOptions {
+            bootstrap: Default::default(),
+            extensions: vec![],
+            startup_snapshot: None,
+            unsafely_ignore_certificate_errors: None,
+            dns_config: None,
+            root_cert_store_provider: None,
+            seed: None,
+            fs: Arc::new(deno_fs::RealFs),
+            module_loader: Rc::new(JobLoader),
+            npm_resolver: None,
+            create_web_worker_cb: Arc::new(|_| {
+              unimplemented!("web workers are not supported")
+            }),
+            preload_module_cb: Arc::new(|_| {
+              unimplemented!("web workers are not supported")
+            }),
+            pre_execute_module_cb: Arc::new(|_| {
+              unimplemented!("web workers are not supported")
+            }),
+            format_js_error_fn: None,
+            source_map_getter: None,
+            worker_type: WebWorkerType::Module,
+            maybe_inspector_server: None,
+            get_error_class_fn: None,
+            blob_store: Default::default(),
+            broadcast_channel: Default::default(),
+            shared_array_buffer_store: None,
+            compiled_wasm_module_store: None,
+            cache_storage_dir: None,
+            cache_storage_max_size: None,
+            stdio: Default::default(),
+            resource_limits: Default::default(),
+          },
+        )
+      }),
+      preload_module_cb: Arc::new(move |worker| {
+        if preload_counters.panic_on_run.swap(false, Ordering::SeqCst) {
+          panic!("job failed");
+        }
+        LocalFutureObj::new(Box::new(async move { Ok(worker) }))
+      }),
+      pre_execute_module_cb: Arc::new(|worker| {
+        LocalFutureObj::new(Box::new(async move { Ok(worker) }))
+      }),
+      format_js_error_fn: None,
+    })
+    .unwrap()
+  }
+
+  /// Waits until `count` workers are ready and none are booting.
+  fn wait_for_idle(pool: &WebWorkerPool, count: usize) {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while pool.idle_count() != count || pool.0.state.lock().booting != 0 {
+      assert!(
+        Instant::now() < deadline,
+        "pool never had {count} idle workers"
+      );
+      std::thread::sleep(Duration::from_millis(10));
+    }
+  }
+
+  #[test]
+  fn taken_workers_are_replaced() {
+    let counters = Arc::new(Counters::default());
+    let pool = create_pool(2, counters.clone());
+    wait_for_idle(&pool, 2);
+    assert_eq!(counters.created.load(Ordering::SeqCst), 2);
+
+    for taken in 1..=3 {
+      let handle = pool.take().unwrap();
+      assert!(handle.is_some());
+      wait_for_idle(&pool, 2);
+      assert_eq!(counters.created.load(Ordering::SeqCst), 2 + taken);
+    }
+  }
+
+  #[test]
+  fn pool_never_exceeds_its_size() {
+    let counters = Arc::new(Counters::default());
+    let pool = create_pool(1, counters.clone());
+    let mut taken = 0;
+    while taken < 3 {
+      if pool.take().unwrap().is_some() {
+        taken += 1;
+      }
+      let state = pool.0.state.lock();
+      assert!(state.idle.len() + state.booting <= 1);
+    }
+    wait_for_idle(&pool, 1);
+    // Finished jobs don't boot extra workers.
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(pool.idle_count(), 1);
+    assert_eq!(counters.created.load(Ordering::SeqCst), 4);
+  }
+
+  #[test]
+  fn panicking_job_does_not_break_the_pool() {
+    let counters = Arc::new(Counters::default());
+    let pool = create_pool(1, counters.clone());
+    wait_for_idle(&pool, 1);
+
+    counters.panic_on_run.store(true, Ordering::SeqCst);
+    assert!(pool.take().unwrap().is_some());
+    wait_for_idle(&pool, 1);
+    assert!(pool.take().unwrap().is_some());
+    wait_for_idle(&pool, 1);
+    assert_eq!(counters.created.load(Ordering::SeqCst), 3);
+  }
+
+  #[test]
+  fn panicking_create_callback_is_replaced() {
+    let counters = Arc::new(Counters::default());
+    counters.panic_on_create.store(true, Ordering::SeqCst);
+    let pool = create_pool(2, counters.clone());
+    wait_for_idle(&pool, 1);
+
+    assert!(pool.take().unwrap().is_some());
+    wait_for_idle(&pool, 2);
+    assert_eq!(counters.created.load(Ordering::SeqCst), 3);
+  }
+}
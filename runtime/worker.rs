@@ -33,12 +33,13 @@ use deno_core::SourceMapGetter;
 use deno_fs::FileSystem;
 use deno_http::DefaultHttpPropertyExtractor;
 use deno_io::Stdio;
-use deno_kv::sqlite::SqliteDbHandler;
+use deno_kv::multi_backend::MultiBackendDbHandler;
 use deno_tls::RootCertStoreProvider;
 use deno_web::BlobStore;
 use log::debug;
 
 use crate::inspector_server::InspectorServer;
+use crate::inspector_server::NodeInspector;
 use crate::ops;
 use crate::permissions::PermissionsContainer;
 use crate::BootstrapOptions;
@@ -100,6 +101,9 @@ pub struct WorkerOptions {
   pub create_params: Option<v8::CreateParams>,
 
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  /// The DNS resolver used by `fetch()` and the net APIs when they aren't
+  /// given one. The system's resolver is used if `None`.
+  pub dns_config: Option<deno_net::resolver::DnsConfig>,
   pub root_cert_store_provider: Option<Arc<dyn RootCertStoreProvider>>,
   pub seed: Option<u64>,
 
@@ -132,6 +136,9 @@ pub struct WorkerOptions {
   /// error in JavaScript.
   pub get_error_class_fn: Option<GetErrorClassFn>,
   pub cache_storage_dir: Option<std::path::PathBuf>,
+  /// The maximum total size of the responses in the Cache API storage, in
+  /// bytes. The least recently used responses are evicted to stay below it.
+  pub cache_storage_max_size: Option<u64>,
   pub origin_storage_dir: Option<std::path::PathBuf>,
   pub blob_store: BlobStore,
   pub broadcast_channel: InMemoryBroadcastChannel,
@@ -169,6 +176,7 @@ impl Default for WorkerOptions {
       module_loader: Rc::new(FsModuleLoader),
       seed: None,
       unsafely_ignore_certificate_errors: Default::default(),
+      dns_config: Default::default(),
       should_break_on_first_statement: Default::default(),
       should_wait_for_inspector_session: Default::default(),
       compiled_wasm_module_store: Default::default(),
@@ -178,6 +186,7 @@ impl Default for WorkerOptions {
       get_error_class_fn: Default::default(),
       origin_storage_dir: Default::default(),
       cache_storage_dir: Default::default(),
+      cache_storage_max_size: Default::default(),
       broadcast_channel: Default::default(),
       source_map_getter: Default::default(),
       root_cert_store_provider: Default::default(),
@@ -226,8 +235,11 @@ impl MainWorker {
     let unstable = options.bootstrap.unstable;
     let enable_testing_features = options.bootstrap.enable_testing_features;
     let exit_code = ExitCode(Arc::new(AtomicI32::new(0)));
+    let cache_storage_max_size = options.cache_storage_max_size;
     let create_cache = options.cache_storage_dir.map(|storage_dir| {
-      let create_cache_fn = move || SqliteBackedCache::new(storage_dir.clone());
+      let create_cache_fn = move || {
+        SqliteBackedCache::new(storage_dir.clone(), cache_storage_max_size)
+      };
       CreateCache(Arc::new(create_cache_fn))
     });
 
@@ -250,6 +262,7 @@ impl MainWorker {
             .unsafely_ignore_certificate_errors
             .clone(),
           file_fetch_handler: Rc::new(deno_fetch::FsFetchHandler),
+          dns_config: options.dns_config.clone(),
           ..Default::default()
         },
       ),
@@ -272,10 +285,11 @@ impl MainWorker {
         options.root_cert_store_provider.clone(),
         unstable,
         options.unsafely_ignore_certificate_errors.clone(),
+        options.dns_config.clone(),
       ),
       deno_tls::deno_tls::init_ops(),
       deno_kv::deno_kv::init_ops(
-        SqliteDbHandler::<PermissionsContainer>::new(
+        MultiBackendDbHandler::<PermissionsContainer>::new(
           options.origin_storage_dir.clone(),
         ),
         unstable,
@@ -299,6 +313,8 @@ impl MainWorker {
         options.web_worker_pre_execute_module_cb.clone(),
         options.format_js_error_fn.clone(),
       ),
+      ops::blob::deno_blob::init_ops(),
+      ops::cron::deno_cron::init_ops(options.origin_storage_dir.clone()),
       ops::fs_events::deno_fs_events::init_ops(),
       ops::os::deno_os::init_ops(exit_code.clone()),
       ops::permissions::deno_permissions::init_ops(),
@@ -338,20 +354,28 @@ impl MainWorker {
     });
     init_runtime_module_map(&mut js_runtime);
 
-    if let Some(server) = options.maybe_inspector_server.clone() {
-      server.register_inspector(
-        main_module.to_string(),
-        &mut js_runtime,
-        options.should_break_on_first_statement
-          || options.should_wait_for_inspector_session,
-      );
-
-      // Put inspector handle into the op state so we can put a breakpoint when
-      // executing a CJS entrypoint.
-      let op_state = js_runtime.op_state();
-      let inspector = js_runtime.inspector();
-      op_state.borrow_mut().put(inspector);
-    }
+    let node_inspector =
+      if let Some(server) = options.maybe_inspector_server.clone() {
+        let url = server.register_inspector(
+          main_module.to_string(),
+          &mut js_runtime,
+          options.should_break_on_first_statement
+            || options.should_wait_for_inspector_session,
+        );
+
+        // Put inspector handle into the op state so we can put a breakpoint when
+        // executing a CJS entrypoint.
+        let op_state = js_runtime.op_state();
+        let inspector = js_runtime.inspector();
+        op_state.borrow_mut().put(inspector);
+        NodeInspector::with_shared_server(main_module.to_string(), url)
+      } else {
+        NodeInspector::new(main_module.to_string())
+      };
+    js_runtime
+      .op_state()
+      .borrow_mut()
+      .put::<deno_node::NodeInspectorServerRc>(Rc::new(node_inspector));
 
     let bootstrap_fn_global = {
       let context = js_runtime.global_context();
@@ -583,3 +607,52 @@ impl MainWorker {
     Ok(local_value.is_false())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use deno_core::resolve_path;
+  use deno_io::StdioPipe;
+  use std::sync::Mutex;
+
+  #[test]
+  fn console_output_goes_to_stdio_sinks() {
+    let stdout = Arc::new(Mutex::new(Vec::new()));
+    let stderr = Arc::new(Mutex::new(Vec::new()));
+    let sink = |buf: &Arc<Mutex<Vec<u8>>>| {
+      let buf = buf.clone();
+      StdioPipe::Sink(Arc::new(move |bytes: &[u8]| {
+        buf.lock().unwrap().extend_from_slice(bytes);
+        Ok(())
+      }))
+    };
+    let options = WorkerOptions {
+      stdio: Stdio {
+        stdin: StdioPipe::Inherit,
+        stdout: sink(&stdout),
+        stderr: sink(&stderr),
+      },
+      ..Default::default()
+    };
+    let main_module =
+      resolve_path("./hello.js", &std::env::current_dir().unwrap()).unwrap();
+    let mut worker = MainWorker::bootstrap_from_options(
+      main_module,
+      PermissionsContainer::allow_all(),
+      options,
+    );
+
+    worker
+      .execute_script(
+        "sink_test.js",
+        ascii_str!(
+          "console.log('hello'); console.error('oops'); \
+           Deno.stdout.writeSync(new Uint8Array([33, 10]));"
+        ),
+      )
+      .unwrap();
+
+    assert_eq!(&*stdout.lock().unwrap(), b"hello\n!\n");
+    assert_eq!(&*stderr.lock().unwrap(), b"oops\n");
+  }
+}
@@ -740,10 +740,18 @@ fn bigint_to_f64(b: v8::Local<v8::BigInt>) -> f64 {
   sign * x
 }
 
+/// Strings at least this long are measured before being decoded, so they are
+/// copied into a single allocation of the exact size instead of possibly
+/// failing the over-allocated fast path and allocating again.
+const LARGE_STRING_LEN: usize = 64 * 1024;
+
 pub fn to_utf8(
   s: v8::Local<v8::String>,
   scope: &mut v8::HandleScope,
 ) -> String {
+  if s.length() >= LARGE_STRING_LEN {
+    return to_utf8_slow(s, scope);
+  }
   to_utf8_fast(s, scope).unwrap_or_else(|| to_utf8_slow(s, scope))
 }
 
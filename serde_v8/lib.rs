@@ -21,6 +21,7 @@ pub use magic::buffer::ZeroCopyBuf;
 pub use magic::bytestring::ByteString;
 pub use magic::detached_buffer::DetachedBuffer;
 pub use magic::string_or_buffer::StringOrBuffer;
+pub use magic::to_js_buffer::ToJsBuffer;
 pub use magic::u16string::U16String;
 pub use magic::ExternalPointer;
 pub use magic::Global;
@@ -8,6 +8,7 @@ mod external_pointer;
 mod global;
 pub(super) mod rawbytes;
 pub mod string_or_buffer;
+pub mod to_js_buffer;
 pub mod transl8;
 pub mod u16string;
 pub mod v8slice;
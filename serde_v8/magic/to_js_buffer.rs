@@ -0,0 +1,139 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use std::ffi::c_void;
+use std::fmt::Debug;
+
+use bytes::BytesMut;
+
+use super::transl8::MagicType;
+use super::transl8::ToV8;
+
+/// A buffer that is handed to V8 without copying, as the backing store of the
+/// `Uint8Array` it serializes to. The memory is released once V8 garbage
+/// collects the array.
+///
+/// Unlike `ZeroCopyBuf`, a `Vec<u8>` whose capacity exceeds its length is not
+/// reallocated to fit, and `BytesMut` buffers are supported. It can only be
+/// sent to V8, not received from it.
+pub struct ToJsBuffer(Option<Backing>);
+
+enum Backing {
+  Vec(Vec<u8>),
+  BytesMut(BytesMut),
+}
+
+impl Backing {
+  fn as_mut_slice(&mut self) -> &mut [u8] {
+    match self {
+      Self::Vec(vec) => vec,
+      Self::BytesMut(bytes) => bytes,
+    }
+  }
+}
+
+impl MagicType for ToJsBuffer {
+  const NAME: &'static str = "ToJsBuffer";
+  const MAGIC_NAME: &'static str = "$__v8_magic_ToJsBuffer";
+}
+
+impl serde::Serialize for ToJsBuffer {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    super::transl8::magic_serialize(serializer, self)
+  }
+}
+
+impl Debug for ToJsBuffer {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let len = self.len();
+    f.debug_struct("ToJsBuffer").field("len", &len).finish()
+  }
+}
+
+impl ToJsBuffer {
+  pub fn empty() -> Self {
+    Vec::new().into()
+  }
+
+  /// Length in bytes of the buffer, or 0 once it was sent to V8.
+  pub fn len(&self) -> usize {
+    match &self.0 {
+      Some(Backing::Vec(vec)) => vec.len(),
+      Some(Backing::BytesMut(bytes)) => bytes.len(),
+      None => 0,
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+}
+
+impl From<Vec<u8>> for ToJsBuffer {
+  fn from(vec: Vec<u8>) -> Self {
+    Self(Some(Backing::Vec(vec)))
+  }
+}
+
+impl From<Box<[u8]>> for ToJsBuffer {
+  fn from(buf: Box<[u8]>) -> Self {
+    buf.into_vec().into()
+  }
+}
+
+impl From<BytesMut> for ToJsBuffer {
+  fn from(bytes: BytesMut) -> Self {
+    Self(Some(Backing::BytesMut(bytes)))
+  }
+}
+
+unsafe extern "C" fn drop_backing(
+  _data: *mut c_void,
+  _byte_length: usize,
+  deleter_data: *mut c_void,
+) {
+  // SAFETY: `deleter_data` is the `Box<Backing>` leaked in `to_v8`, and V8
+  // calls the deleter exactly once.
+  drop(Box::from_raw(deleter_data as *mut Backing));
+}
+
+impl ToV8 for ToJsBuffer {
+  fn to_v8<'a>(
+    &mut self,
+    scope: &mut v8::HandleScope<'a>,
+  ) -> Result<v8::Local<'a, v8::Value>, crate::Error> {
+    let mut backing = Box::new(self.0.take().expect("ToJsBuffer was empty"));
+    let slice = backing.as_mut_slice();
+    let (data, len) = (slice.as_mut_ptr(), slice.len());
+
+    if len == 0 {
+      let ab = v8::ArrayBuffer::new(scope, 0);
+      return Ok(
+        v8::Uint8Array::new(scope, ab, 0, 0)
+          .expect("Failed to create Uint8Array")
+          .into(),
+      );
+    }
+
+    // SAFETY: the heap allocation `data` points into is owned by `backing`,
+    // which is neither moved nor mutated until V8 releases the backing store
+    // and `drop_backing` frees it.
+    let backing_store = unsafe {
+      v8::ArrayBuffer::new_backing_store_from_ptr(
+        data as *mut c_void,
+        len,
+        drop_backing,
+        Box::into_raw(backing) as *mut c_void,
+      )
+    };
+    let backing_store_shared = backing_store.make_shared();
+    let ab = v8::ArrayBuffer::with_backing_store(scope, &backing_store_shared);
+    Ok(
+      v8::Uint8Array::new(scope, ab, 0, len)
+        .expect("Failed to create Uint8Array")
+        .into(),
+    )
+  }
+}
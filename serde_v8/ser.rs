@@ -20,6 +20,7 @@ use crate::ByteString;
 use crate::DetachedBuffer;
 use crate::ExternalPointer;
 use crate::StringOrBuffer;
+use crate::ToJsBuffer;
 use crate::U16String;
 use crate::ZeroCopyBuf;
 
@@ -275,6 +276,7 @@ pub enum StructSerializers<'a, 'b, 'c> {
   ExternalPointer(MagicalSerializer<'a, 'b, 'c, magic::ExternalPointer>),
   Magic(MagicalSerializer<'a, 'b, 'c, magic::Value<'a>>),
   ZeroCopyBuf(MagicalSerializer<'a, 'b, 'c, ZeroCopyBuf>),
+  ToJsBuffer(MagicalSerializer<'a, 'b, 'c, ToJsBuffer>),
   MagicAnyValue(MagicalSerializer<'a, 'b, 'c, AnyValue>),
   MagicDetached(MagicalSerializer<'a, 'b, 'c, DetachedBuffer>),
   MagicByteString(MagicalSerializer<'a, 'b, 'c, ByteString>),
@@ -297,6 +299,7 @@ impl<'a, 'b, 'c> ser::SerializeStruct for StructSerializers<'a, 'b, 'c> {
       StructSerializers::ExternalPointer(s) => s.serialize_field(key, value),
       StructSerializers::Magic(s) => s.serialize_field(key, value),
       StructSerializers::ZeroCopyBuf(s) => s.serialize_field(key, value),
+      StructSerializers::ToJsBuffer(s) => s.serialize_field(key, value),
       StructSerializers::MagicAnyValue(s) => s.serialize_field(key, value),
       StructSerializers::MagicDetached(s) => s.serialize_field(key, value),
       StructSerializers::MagicByteString(s) => s.serialize_field(key, value),
@@ -314,6 +317,7 @@ impl<'a, 'b, 'c> ser::SerializeStruct for StructSerializers<'a, 'b, 'c> {
       StructSerializers::ExternalPointer(s) => s.end(),
       StructSerializers::Magic(s) => s.end(),
       StructSerializers::ZeroCopyBuf(s) => s.end(),
+      StructSerializers::ToJsBuffer(s) => s.end(),
       StructSerializers::MagicAnyValue(s) => s.end(),
       StructSerializers::MagicDetached(s) => s.end(),
       StructSerializers::MagicByteString(s) => s.end(),
@@ -592,6 +596,10 @@ impl<'a, 'b, 'c> ser::Serializer for Serializer<'a, 'b, 'c> {
         let m = MagicalSerializer::<ZeroCopyBuf>::new(self.scope);
         Ok(StructSerializers::ZeroCopyBuf(m))
       }
+      ToJsBuffer::MAGIC_NAME => {
+        let m = MagicalSerializer::<ToJsBuffer>::new(self.scope);
+        Ok(StructSerializers::ToJsBuffer(m))
+      }
       AnyValue::MAGIC_NAME => {
         let m = MagicalSerializer::<AnyValue>::new(self.scope);
         Ok(StructSerializers::MagicAnyValue(m))
@@ -4,6 +4,7 @@ use std::mem::transmute_copy;
 
 use crate::BigInt;
 use crate::ByteString;
+use crate::ToJsBuffer;
 use crate::U16String;
 use crate::ZeroCopyBuf;
 
@@ -64,6 +65,7 @@ pub enum Primitive {
   Float64(f64),
   String(String),
   ZeroCopyBuf(ZeroCopyBuf),
+  ToJsBuffer(ToJsBuffer),
   ByteString(ByteString),
   U16String(U16String),
   BigInt(BigInt),
@@ -89,6 +91,7 @@ impl serde::Serialize for Primitive {
       Self::Float64(x) => x.serialize(s),
       Self::String(x) => x.serialize(s),
       Self::ZeroCopyBuf(x) => x.serialize(s),
+      Self::ToJsBuffer(x) => x.serialize(s),
       Self::ByteString(x) => x.serialize(s),
       Self::U16String(x) => x.serialize(s),
       Self::BigInt(x) => x.serialize(s),
@@ -136,6 +139,8 @@ impl<T: serde::Serialize + 'static> From<T> for SerializablePkg {
       Self::Primitive(Primitive::String(tc(x)))
     } else if tid == TypeId::of::<ZeroCopyBuf>() {
       Self::Primitive(Primitive::ZeroCopyBuf(tc(x)))
+    } else if tid == TypeId::of::<ToJsBuffer>() {
+      Self::Primitive(Primitive::ToJsBuffer(tc(x)))
     } else if tid == TypeId::of::<ByteString>() {
       Self::Primitive(Primitive::ByteString(tc(x)))
     } else if tid == TypeId::of::<U16String>() {
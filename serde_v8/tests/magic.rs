@@ -148,6 +148,49 @@ fn magic_buffer() {
   })
 }
 
+#[test]
+fn magic_to_js_buffer() {
+  v8_do(|| {
+    // Init isolate
+    let isolate = &mut v8::Isolate::new(v8::CreateParams::default());
+    let handle_scope = &mut v8::HandleScope::new(isolate);
+    let context = v8::Context::new(handle_scope);
+    let scope = &mut v8::ContextScope::new(handle_scope, context);
+    let global = context.global(scope);
+
+    // Vec with spare capacity
+    let mut buf: Vec<u8> = Vec::with_capacity(16);
+    buf.extend_from_slice(&[1, 2, 3, 99, 5]);
+    let ptr = buf.as_ptr();
+    let v8_value =
+      serde_v8::to_v8(scope, serde_v8::ToJsBuffer::from(buf)).unwrap();
+    let array = v8::Local::<v8::Uint8Array>::try_from(v8_value).unwrap();
+    let store = array.buffer(scope).unwrap().get_backing_store();
+    assert_eq!(store.data().unwrap().as_ptr() as *const u8, ptr);
+    let key = serde_v8::to_v8(scope, "t1").unwrap();
+    global.set(scope, key, v8_value);
+    let eq = js_exec(scope, "t1.length === 5 && t1[3] === 99");
+    assert!(eq.is_true());
+
+    // BytesMut
+    let bytes = bytes::BytesMut::from(&b"hello"[..]);
+    let v8_value =
+      serde_v8::to_v8(scope, serde_v8::ToJsBuffer::from(bytes)).unwrap();
+    let key = serde_v8::to_v8(scope, "t2").unwrap();
+    global.set(scope, key, v8_value);
+    let eq = js_exec(scope, "t2.length === 5 && t2[0] === 104");
+    assert!(eq.is_true());
+
+    // Empty
+    let v8_value =
+      serde_v8::to_v8(scope, serde_v8::ToJsBuffer::empty()).unwrap();
+    let key = serde_v8::to_v8(scope, "t3").unwrap();
+    global.set(scope, key, v8_value);
+    let eq = js_exec(scope, "t3 instanceof Uint8Array && t3.length === 0");
+    assert!(eq.is_true());
+  })
+}
+
 #[test]
 fn magic_byte_string() {
   v8_do(|| {
@@ -137,6 +137,20 @@ pub unsafe extern "C" fn nonblocking_buffer(ptr: *const u8, len: usize) {
   assert_eq!(buf, vec![1, 2, 3, 4, 5, 6, 7, 8]);
 }
 
+/// # Safety
+///
+/// The pointer to the buffer must be valid and initialized, and the length must
+/// not be longer than the buffer's allocation.
+#[no_mangle]
+pub unsafe extern "C" fn nonblocking_buffer_delayed(
+  ptr: *const u8,
+  len: usize,
+) {
+  std::thread::sleep(std::time::Duration::from_millis(100));
+  let buf = std::slice::from_raw_parts(ptr, len);
+  assert_eq!(buf, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
 #[no_mangle]
 pub extern "C" fn get_add_u32_ptr() -> *const c_void {
   add_u32 as *const c_void
@@ -244,6 +258,16 @@ pub extern "C" fn call_stored_function_thread_safe() {
   });
 }
 
+#[no_mangle]
+pub extern "C" fn call_function_on_thread(func: Option<extern "C" fn(u32)>) {
+  let func = func.unwrap();
+  std::thread::spawn(move || {
+    for i in 1..=3 {
+      func(i);
+    }
+  });
+}
+
 #[no_mangle]
 pub extern "C" fn call_stored_function_thread_safe_and_log() {
   std::thread::spawn(move || {
@@ -543,3 +567,39 @@ pub unsafe extern "C" fn create_mixed(
 pub extern "C" fn print_mixed(mixed: Mixed) {
   println!("{mixed:?}");
 }
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union Number {
+  int: u64,
+  halves: [u32; 2],
+}
+
+#[no_mangle]
+pub extern "C" fn make_number(int: u64) -> Number {
+  Number { int }
+}
+
+#[no_mangle]
+pub extern "C" fn add_number_halves(number: Number) -> u32 {
+  // SAFETY: all bit patterns are valid for both members.
+  unsafe { number.halves[0] + number.halves[1] }
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub union FloatOrInt {
+  float: f64,
+  int: i32,
+}
+
+#[no_mangle]
+pub extern "C" fn make_float_or_int(float: f64) -> FloatOrInt {
+  FloatOrInt { float }
+}
+
+#[no_mangle]
+pub extern "C" fn scale_float_or_int(value: FloatOrInt, factor: f64) -> f64 {
+  // SAFETY: the tests only pass unions holding a float.
+  unsafe { value.float * factor }
+}
@@ -176,6 +176,9 @@ fn thread_safe_callback() {
   let expected = "\
     Callback on main thread\n\
     Callback on worker thread\n\
+    Nonblocking callback 1\n\
+    Nonblocking callback 2\n\
+    Nonblocking callback 3\n\
     Calling callback, isolate should stay asleep until callback is called\n\
     Callback being called\n\
     Isolate should now exit\n";
@@ -10,6 +10,7 @@ import {
   assertInstanceOf,
   assertEquals,
   assertFalse,
+  assertRejects,
 } from "../../test_util/std/testing/asserts.ts";
 
 const targetDir = Deno.execPath().replace(/[^\/\\]+$/, "");
@@ -40,6 +41,21 @@ assertThrows(
   "Failed to register symbol non_existent_symbol",
 );
 
+assertThrows(
+  () => {
+    Deno.dlopen(libPath, {
+      non_existent_symbol: {
+        parameters: [],
+        result: "void",
+        lazy: true,
+        optional: true,
+      },
+    });
+  },
+  TypeError,
+  "Foreign symbol 'non_existent_symbol' cannot be both lazy and optional",
+);
+
 assertThrows(() => {
   Deno.dlopen(libPath, {
     print_something: {
@@ -80,6 +96,8 @@ const Rect = ["f64", "f64", "f64", "f64"];
 const RectNested = [{ struct: Point }, { struct: Size }];
 const RectNestedCached = [{ struct: Size }, { struct: Size }];
 const Mixed = ["u8", "f32", { struct: Rect }, "usize", { struct: ["u32", "u32"] }];
+const NumberUnion = ["u64", { struct: ["u32", "u32"] }];
+const FloatOrInt = ["f64", "i32"];
 
 const dylib = Deno.dlopen(libPath, {
   "printSomething": {
@@ -167,6 +185,11 @@ const dylib = Deno.dlopen(libPath, {
     result: "void",
     nonblocking: true,
   },
+  "nonblocking_buffer_delayed": {
+    parameters: ["buffer", "usize"],
+    result: "void",
+    nonblocking: true,
+  },
   "get_add_u32_ptr": {
     parameters: [],
     result: "pointer",
@@ -284,6 +307,28 @@ const dylib = Deno.dlopen(libPath, {
     result: "void",
     optional: true,
   },
+  make_number: {
+    parameters: ["u64"],
+    result: { union: NumberUnion },
+  },
+  add_number_halves: {
+    parameters: [{ union: NumberUnion }],
+    result: "u32",
+  },
+  add_number_halves_async: {
+    name: "add_number_halves",
+    nonblocking: true,
+    parameters: [{ union: NumberUnion }],
+    result: "u32",
+  },
+  make_float_or_int: {
+    parameters: ["f64"],
+    result: { union: FloatOrInt },
+  },
+  scale_float_or_int: {
+    parameters: [{ union: FloatOrInt }, "f64"],
+    result: "f64",
+  },
   non_existent_symbol: {
     parameters: [],
     result: "void",
@@ -299,9 +344,56 @@ const dylib = Deno.dlopen(libPath, {
     type: "u32",
     optional: true,
   },
+  add_u32_lazy: {
+    name: "add_u32",
+    parameters: ["u32", "u32"],
+    result: "u32",
+    lazy: true,
+  },
+  add_u64_lazy: {
+    name: "add_u64",
+    parameters: ["u64", "u64"],
+    result: "u64",
+    lazy: true,
+  },
+  non_existent_lazy_symbol: {
+    parameters: [],
+    result: "void",
+    lazy: true,
+  },
+  non_existent_lazy_nonblocking_symbol: {
+    parameters: [],
+    result: "void",
+    nonblocking: true,
+    lazy: true,
+  },
 });
 const { symbols } = dylib;
 
+assertEquals(symbols.add_u32_lazy(1, 2), 3);
+assertEquals(symbols.add_u32_lazy(3, 4), 7);
+assertEquals(symbols.add_u64_lazy(2n ** 53n, 1n), 2n ** 53n + 1n);
+assertThrows(
+  () => symbols.non_existent_lazy_symbol(),
+  Error,
+  "Failed to register symbol non_existent_lazy_symbol",
+);
+assertThrows(
+  () => symbols.non_existent_lazy_symbol(),
+  Error,
+  "Failed to register symbol non_existent_lazy_symbol",
+);
+await assertRejects(
+  async () => await symbols.non_existent_lazy_nonblocking_symbol(),
+  Error,
+  "Failed to register symbol non_existent_lazy_nonblocking_symbol",
+);
+
+const exportedSymbols = dylib.exportedSymbols();
+assert(exportedSymbols.includes("add_u32"));
+assert(exportedSymbols.includes("static_u32"));
+assertFalse(exportedSymbols.includes("non_existent_symbol"));
+
 symbols.printSomething();
 const buffer = new Uint8Array([1, 2, 3, 4, 5, 6, 7, 8]);
 const buffer2 = new Uint8Array([9, 10]);
@@ -505,6 +597,14 @@ dylib.symbols.nonblocking_buffer(buffer3, buffer3.length).then(() => {
 });
 await promise;
 
+// The buffer stays alive while a nonblocking call uses it, even if it is
+// transferred away from JS before the call reads it.
+const buffer4 = new Uint8Array([1, 2, 3, 4, 5, 6, 7, 8]);
+const delayedPromise = dylib.symbols.nonblocking_buffer_delayed(buffer4, buffer4.length);
+structuredClone(buffer4.buffer, { transfer: [buffer4.buffer] });
+assertEquals(buffer4.length, 0);
+await delayedPromise;
+
 let start = performance.now();
 dylib.symbols.sleep_blocking(100);
 assert(performance.now() - start >= 100);
@@ -687,6 +787,20 @@ assertEquals(new BigUint64Array(mixedStruct.buffer, 40, 1)[0], 12456789n);
 assertEquals(new Uint32Array(mixedStruct.buffer, 48, 2), new Uint32Array([8, 32]));
 dylib.symbols.print_mixed(mixedStruct);
 
+// Test union returning and passing
+const number = dylib.symbols.make_number(2n ** 32n * 3n + 5n);
+assertInstanceOf(number, Uint8Array);
+assertEquals(number.length, 8);
+assertEquals(new BigUint64Array(number.buffer)[0], 2n ** 32n * 3n + 5n);
+assertEquals(dylib.symbols.add_number_halves(number), 8);
+assertEquals(await dylib.symbols.add_number_halves_async(new Uint32Array([1, 2])), 3);
+
+// A union mixing float and integer members travels in integer registers
+const floatOrInt = dylib.symbols.make_float_or_int(1.5);
+assertEquals(new Float64Array(floatOrInt.buffer)[0], 1.5);
+assertEquals(dylib.symbols.scale_float_or_int(floatOrInt, 3), 4.5);
+assertEquals(dylib.symbols.scale_float_or_int(new Float64Array([-2]), 0.25), -0.5);
+
 const cb = new Deno.UnsafeCallback({
   parameters: [{ struct: Rect }],
   result: { struct: Rect },
@@ -24,6 +24,10 @@ const dylib = Deno.dlopen(libPath, {
     parameters: [],
     result: "void",
   },
+  call_function_on_thread: {
+    parameters: ["function"],
+    result: "void",
+  },
 });
 
 let resolveWorker;
@@ -75,7 +79,28 @@ mainThreadCallback.unref();
 await sendWorkerMessage("unref");
 worker.terminate();
 
-// Test step 3: Register a callback that will be the only thing left keeping the isolate from exiting.
+// Test step 3: Call a nonblocking callback from a foreign thread, which
+// queues the calls and their arguments without waiting for them.
+
+let resolveNonblocking;
+const nonblockingPromise = new Promise((res) => {
+  resolveNonblocking = res;
+});
+const nonblockingCallback = Deno.UnsafeCallback.threadSafe(
+  { parameters: ["u32"], result: "void", nonblocking: true },
+  (value) => {
+    console.log(`Nonblocking callback ${value}`);
+    if (value === 3) {
+      resolveNonblocking();
+    }
+  },
+);
+
+dylib.symbols.call_function_on_thread(nonblockingCallback.pointer);
+await nonblockingPromise;
+nonblockingCallback.close();
+
+// Test step 4: Register a callback that will be the only thing left keeping the isolate from exiting.
 // Rely on it to keep Deno running until the callback comes in and unrefs the callback, after which Deno should exit.
 
 const cleanupCallback = new Deno.UnsafeCallback(
@@ -0,0 +1,28 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+import { Buffer } from "node:buffer";
+import { assert, assertEquals, loadTestLibrary } from "./common.js";
+
+const lib = loadTestLibrary();
+
+Deno.test("napi create buffer copy", function () {
+  const buf = lib.create_buffer_copy();
+  assert(buf instanceof Buffer);
+  assertEquals(buf.toString(), "hello");
+});
+
+Deno.test("napi buffer info", function () {
+  const buf = new Uint8Array([1, 2, 3, 4, 5]);
+  assertEquals(lib.buffer_sum(buf), 15);
+  assertEquals(lib.buffer_sum(buf.subarray(2, 4)), 7);
+  assertEquals(lib.buffer_sum(Buffer.from([10, 20])), 30);
+  assertEquals(lib.buffer_sum(new DataView(buf.buffer, 1, 2)), 5);
+});
+
+Deno.test("napi is buffer", function () {
+  assert(lib.is_buffer(Buffer.alloc(1)));
+  assert(lib.is_buffer(new Uint8Array(1)));
+  assert(lib.is_buffer(new DataView(new ArrayBuffer(1))));
+  assert(!lib.is_buffer(new ArrayBuffer(1)));
+  assert(!lib.is_buffer({}));
+});
@@ -7,6 +7,7 @@ export {
   assertThrows,
 } from "../test_util/std/testing/asserts.ts";
 export { fromFileUrl } from "../test_util/std/path/mod.ts";
+import { Buffer } from "node:buffer";
 
 const targetDir = Deno.execPath().replace(/[^\/\\]+$/, "");
 export const [libPrefix, libSuffix] = {
@@ -22,6 +23,6 @@ export function loadTestLibrary() {
 
   // Internal, used in ext/node
   return ops.op_napi_open(specifier, {
-    Buffer: {},
+    Buffer,
   });
 }
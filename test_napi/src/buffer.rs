@@ -0,0 +1,69 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+use crate::assert_napi_ok;
+use crate::napi_get_callback_info;
+use crate::napi_new_property;
+use napi_sys::*;
+use std::ptr;
+
+extern "C" fn create_buffer_copy(
+  env: napi_env,
+  _info: napi_callback_info,
+) -> napi_value {
+  let data = b"hello";
+  let mut result = ptr::null_mut();
+  assert_napi_ok!(napi_create_buffer_copy(
+    env,
+    data.len(),
+    data.as_ptr() as _,
+    ptr::null_mut(),
+    &mut result,
+  ));
+  result
+}
+
+extern "C" fn buffer_sum(
+  env: napi_env,
+  info: napi_callback_info,
+) -> napi_value {
+  let (args, argc, _) = napi_get_callback_info!(env, info, 1);
+  assert_eq!(argc, 1);
+
+  let mut data = ptr::null_mut();
+  let mut length = 0;
+  assert_napi_ok!(napi_get_buffer_info(env, args[0], &mut data, &mut length));
+  // SAFETY: napi_get_buffer_info returns the bytes viewed by the buffer.
+  let bytes = unsafe { std::slice::from_raw_parts(data as *const u8, length) };
+  let sum = bytes.iter().map(|&b| b as u32).sum();
+
+  let mut result = ptr::null_mut();
+  assert_napi_ok!(napi_create_uint32(env, sum, &mut result));
+  result
+}
+
+extern "C" fn is_buffer(env: napi_env, info: napi_callback_info) -> napi_value {
+  let (args, argc, _) = napi_get_callback_info!(env, info, 1);
+  assert_eq!(argc, 1);
+
+  let mut value = false;
+  assert_napi_ok!(napi_is_buffer(env, args[0], &mut value));
+
+  let mut result = ptr::null_mut();
+  assert_napi_ok!(napi_get_boolean(env, value, &mut result));
+  result
+}
+
+pub fn init(env: napi_env, exports: napi_value) {
+  let properties = &[
+    napi_new_property!(env, "create_buffer_copy", create_buffer_copy),
+    napi_new_property!(env, "buffer_sum", buffer_sum),
+    napi_new_property!(env, "is_buffer", is_buffer),
+  ];
+
+  assert_napi_ok!(napi_define_properties(
+    env,
+    exports,
+    properties.len(),
+    properties.as_ptr()
+  ));
+}
@@ -10,6 +10,7 @@ pub mod array;
 pub mod arraybuffer;
 pub mod r#async;
 pub mod bigint;
+pub mod buffer;
 pub mod callback;
 pub mod coerce;
 pub mod date;
@@ -147,6 +148,7 @@ unsafe extern "C" fn napi_register_module_v1(
   numbers::init(env, exports);
   typedarray::init(env, exports);
   arraybuffer::init(env, exports);
+  buffer::init(env, exports);
   array::init(env, exports);
   env::init(env, exports);
   error::init(env, exports);
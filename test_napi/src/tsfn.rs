@@ -3,7 +3,9 @@
 // This test performs initilization similar to napi-rs.
 // https://github.com/napi-rs/napi-rs/commit/a5a04a4e545f268769cc78e2bd6c45af4336aac3
 
+use crate::napi_new_property;
 use napi_sys as sys;
+use napi_sys::napi_property_descriptor;
 use std::ffi::c_char;
 use std::ffi::c_void;
 use std::ptr;
@@ -103,6 +105,104 @@ extern "C" fn custom_gc(
   );
 }
 
-pub fn init(env: sys::napi_env, _exports: sys::napi_value) {
+/// Calls the given function from another thread through a threadsafe
+/// function without `call_js_cb`.
+extern "C" fn call_from_thread(
+  env: sys::napi_env,
+  info: sys::napi_callback_info,
+) -> sys::napi_value {
+  let mut argc = 1;
+  let mut argv = [ptr::null_mut()];
+  check_status_or_panic!(
+    unsafe {
+      sys::napi_get_cb_info(
+        env,
+        info,
+        &mut argc,
+        argv.as_mut_ptr(),
+        ptr::null_mut(),
+        ptr::null_mut(),
+      )
+    },
+    "Get callback info in call_from_thread failed"
+  );
+  let mut async_resource_name = ptr::null_mut();
+  check_status_or_panic!(
+    unsafe {
+      sys::napi_create_string_utf8(
+        env,
+        "CallFromThread".as_ptr() as *const c_char,
+        14,
+        &mut async_resource_name,
+      )
+    },
+    "Create async resource string in call_from_thread failed"
+  );
+  let mut tsfn = ptr::null_mut();
+  check_status_or_panic!(
+    unsafe {
+      sys::napi_create_threadsafe_function(
+        env,
+        argv[0],
+        ptr::null_mut(),
+        async_resource_name,
+        0,
+        1,
+        ptr::null_mut(),
+        None,
+        ptr::null_mut(),
+        None,
+        &mut tsfn,
+      )
+    },
+    "Create ThreadsafeFunction in call_from_thread failed"
+  );
+
+  // Raw pointers aren't `Send`.
+  let tsfn = tsfn as usize;
+  std::thread::spawn(move || {
+    let tsfn = tsfn as sys::napi_threadsafe_function;
+    check_status_or_panic!(
+      unsafe {
+        sys::napi_call_threadsafe_function(
+          tsfn,
+          ptr::null_mut(),
+          sys::ThreadsafeFunctionCallMode::blocking,
+        )
+      },
+      "Call ThreadsafeFunction in call_from_thread failed"
+    );
+    check_status_or_panic!(
+      unsafe {
+        sys::napi_release_threadsafe_function(
+          tsfn,
+          sys::ThreadsafeFunctionReleaseMode::release,
+        )
+      },
+      "Release ThreadsafeFunction in call_from_thread failed"
+    );
+  });
+
+  ptr::null_mut()
+}
+
+pub fn init(env: sys::napi_env, exports: sys::napi_value) {
   create_custom_gc(env);
+
+  let properties = &[napi_new_property!(
+    env,
+    "call_from_thread",
+    call_from_thread
+  )];
+  check_status_or_panic!(
+    unsafe {
+      sys::napi_define_properties(
+        env,
+        exports,
+        properties.len(),
+        properties.as_ptr(),
+      )
+    },
+    "Define properties in tsfn init failed"
+  );
 }
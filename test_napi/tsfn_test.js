@@ -0,0 +1,12 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+import { assertEquals, loadTestLibrary } from "./common.js";
+
+const tsfn = loadTestLibrary();
+
+Deno.test("napi threadsafe function without call_js_cb", async function () {
+  let resolve;
+  const promise = new Promise((r) => resolve = r);
+  tsfn.call_from_thread((...args) => resolve(args.length));
+  assertEquals(await promise, 0);
+});
@@ -0,0 +1,150 @@
+// Copyright 2018-2023 the Deno authors. All rights reserved. MIT license.
+
+//! An in-memory store speaking the protocol of the remote `Deno.openKv`
+//! backend, served under `/kv/<name>/` by the main test server. Each name is
+//! a separate database. Requests must carry the `KV_ACCESS_TOKEN`.
+
+use hyper::Body;
+use hyper::Request;
+use hyper::Response;
+use hyper::StatusCode;
+use once_cell::sync::Lazy;
+use serde_json::json;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+pub const KV_ACCESS_TOKEN: &str = "test-kv-access-token";
+
+#[derive(Default)]
+struct Store {
+  version: u64,
+  /// The values, as sent by clients, and the versions they were set at by
+  /// key.
+  entries: BTreeMap<Vec<u8>, (Value, u64)>,
+}
+
+static STORES: Lazy<Mutex<HashMap<String, Store>>> =
+  Lazy::new(Default::default);
+
+fn versionstamp(version: u64) -> String {
+  format!("{version:016x}0000")
+}
+
+fn decode_key(value: &Value) -> Vec<u8> {
+  base64::decode(value.as_str().unwrap()).unwrap()
+}
+
+fn u64_value(value: &Value) -> Option<u64> {
+  if value["kind"] != "u64" {
+    return None;
+  }
+  value["value"].as_str()?.parse().ok()
+}
+
+fn snapshot_read(store: &Store, body: &Value) -> Value {
+  let ranges = body["ranges"]
+    .as_array()
+    .unwrap()
+    .iter()
+    .map(|range| {
+      let start = decode_key(&range["start"]);
+      let end = decode_key(&range["end"]);
+      let limit = range["limit"].as_u64().unwrap() as usize;
+      let entries = store.entries.range(start..end);
+      let to_json = |(key, (value, version)): (&Vec<u8>, &(Value, u64))| {
+        json!({
+          "key": base64::encode(key),
+          "value": value,
+          "versionstamp": versionstamp(*version),
+        })
+      };
+      let entries = if range["reverse"].as_bool().unwrap() {
+        entries.rev().take(limit).map(to_json).collect::<Vec<_>>()
+      } else {
+        entries.take(limit).map(to_json).collect::<Vec<_>>()
+      };
+      json!({ "entries": entries })
+    })
+    .collect::<Vec<_>>();
+  json!({ "ranges": ranges })
+}
+
+fn atomic_write(store: &mut Store, body: &Value) -> Value {
+  for check in body["checks"].as_array().unwrap() {
+    let key = decode_key(&check["key"]);
+    let current = store
+      .entries
+      .get(&key)
+      .map(|(_, version)| versionstamp(*version));
+    if current.as_deref() != check["versionstamp"].as_str() {
+      return json!({ "ok": false });
+    }
+  }
+
+  store.version += 1;
+  let version = store.version;
+  for mutation in body["mutations"].as_array().unwrap() {
+    let key = decode_key(&mutation["key"]);
+    let value = &mutation["value"];
+    let kind = mutation["kind"].as_str().unwrap();
+    if kind == "delete" {
+      store.entries.remove(&key);
+      continue;
+    }
+    let new_value = if kind == "set" {
+      value.clone()
+    } else {
+      let operand = u64_value(value).unwrap();
+      let new = match store.entries.get(&key).and_then(|(v, _)| u64_value(v)) {
+        None => operand,
+        Some(old) if kind == "sum" => old.wrapping_add(operand),
+        Some(old) if kind == "min" => old.min(operand),
+        Some(old) => old.max(operand),
+      };
+      json!({ "kind": "u64", "value": new.to_string() })
+    };
+    store.entries.insert(key, (new_value, version));
+  }
+  json!({ "ok": true, "versionstamp": versionstamp(version) })
+}
+
+pub async fn handle(
+  req: Request<Body>,
+) -> Result<Response<Body>, hyper::http::Error> {
+  let authorized = req
+    .headers()
+    .get("authorization")
+    .map(|value| value == format!("Bearer {KV_ACCESS_TOKEN}").as_str())
+    .unwrap_or(false);
+  if !authorized {
+    return Response::builder()
+      .status(StatusCode::UNAUTHORIZED)
+      .body(Body::from("Invalid access token"));
+  }
+
+  let path = req.uri().path().strip_prefix("/kv/").unwrap().to_string();
+  let Some((name, method)) = path.rsplit_once('/') else {
+    return Response::builder()
+      .status(StatusCode::NOT_FOUND)
+      .body(Body::empty());
+  };
+  let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+  let body: Value = serde_json::from_slice(&body).unwrap();
+
+  let mut stores = STORES.lock().unwrap();
+  let store = stores.entry(name.to_string()).or_default();
+  let response = match method {
+    "snapshot_read" => snapshot_read(store, &body),
+    "atomic_write" => atomic_write(store, &body),
+    _ => {
+      return Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::empty())
+    }
+  };
+  Response::builder()
+    .header("content-type", "application/json")
+    .body(Body::from(response.to_string()))
+}
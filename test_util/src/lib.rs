@@ -57,6 +57,7 @@ pub mod assertions;
 mod builders;
 pub mod factory;
 mod fs;
+pub mod kv_remote;
 pub mod lsp;
 mod npm;
 pub mod pty;
@@ -91,6 +92,7 @@ const WS_PORT: u16 = 4242;
 const WSS_PORT: u16 = 4243;
 const WS_CLOSE_PORT: u16 = 4244;
 const WS_PING_PORT: u16 = 4245;
+const WSS_CLIENT_AUTH_PORT: u16 = 4246;
 
 pub const PERMISSION_VARIANTS: [&str; 5] =
   ["read", "write", "env", "net", "run"];
@@ -518,7 +520,9 @@ async fn get_tls_config(
   }
 }
 
-async fn run_wss_server(addr: &SocketAddr) {
+/// With `client_auth`, connections without a valid client certificate are
+/// dropped after the TLS handshake.
+async fn run_wss_server(addr: &SocketAddr, client_auth: bool) {
   let cert_file = "tls/localhost.crt";
   let key_file = "tls/localhost.key";
   let ca_cert_file = "tls/RootCA.pem";
@@ -535,7 +539,12 @@ async fn run_wss_server(addr: &SocketAddr) {
     let acceptor = tls_acceptor.clone();
     tokio::spawn(async move {
       match acceptor.accept(stream).await {
-        Ok(tls_stream) => {
+        Ok(mut tls_stream) => {
+          let (_, tls_session) = tls_stream.get_mut();
+          if client_auth && tls_session.peer_certificates().is_none() {
+            eprintln!("wss_client_auth: no valid client certificate");
+            return;
+          }
           spawn_ws_server(tls_stream, |ws| {
             Box::pin(echo_websocket_handler(ws))
           });
@@ -728,6 +737,9 @@ async fn main_server(
   req: Request<Body>,
 ) -> Result<Response<Body>, hyper::http::Error> {
   return match (req.method(), req.uri().path()) {
+    (&hyper::Method::POST, path) if path.starts_with("/kv/") => {
+      kv_remote::handle(req).await
+    }
     (
       &hyper::Method::POST | &hyper::Method::PATCH | &hyper::Method::PUT,
       "/echo_server",
@@ -1590,7 +1602,10 @@ pub async fn run_all_servers() {
   let ws_ping_addr = SocketAddr::from(([127, 0, 0, 1], WS_PING_PORT));
   let ws_ping_server_fut = run_ws_ping_server(&ws_ping_addr);
   let wss_addr = SocketAddr::from(([127, 0, 0, 1], WSS_PORT));
-  let wss_server_fut = run_wss_server(&wss_addr);
+  let wss_server_fut = run_wss_server(&wss_addr, false);
+  let wss_client_auth_addr =
+    SocketAddr::from(([127, 0, 0, 1], WSS_CLIENT_AUTH_PORT));
+  let wss_client_auth_server_fut = run_wss_server(&wss_client_auth_addr, true);
   let ws_close_addr = SocketAddr::from(([127, 0, 0, 1], WS_CLOSE_PORT));
   let ws_close_server_fut = run_ws_close_server(&ws_close_addr);
 
@@ -1610,6 +1625,7 @@ pub async fn run_all_servers() {
       ws_server_fut,
       ws_ping_server_fut,
       wss_server_fut,
+      wss_client_auth_server_fut,
       tls_server_fut,
       tls_client_auth_server_fut,
       ws_close_server_fut,